@@ -0,0 +1,38 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use duat_bench::{Buffer, SOURCE_LEN, root_area, spread_cursors, text};
+use duat_core::{mode::EditHelper, widgets::Widget};
+
+fn edit(c: &mut Criterion) {
+    let (_ui, area) = root_area();
+    let buffer = Buffer::new(text(SOURCE_LEN));
+    let cursors = spread_cursors(buffer.read().text(), &area, 1000);
+
+    let mut group = c.benchmark_group("edit");
+    group.sample_size(20);
+
+    // Every edit is undone, so all iterations act on the same Text.
+    group.bench_function("insert with 1000 cursors", |b| {
+        b.iter(|| {
+            let mut cursors = cursors.clone();
+            let mut helper = EditHelper::new(&buffer, &area, &mut cursors);
+            helper.edit_each(|e| e.insert("// "));
+            helper.new_moment();
+            helper.undo();
+        })
+    });
+
+    group.bench_function("split lines with 1000 cursors", |b| {
+        b.iter(|| {
+            let mut cursors = cursors.clone();
+            let mut helper = EditHelper::new(&buffer, &area, &mut cursors);
+            helper.edit_each(|e| e.insert("\n"));
+            helper.new_moment();
+            helper.undo();
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, edit);
+criterion_main!(benches);
@@ -0,0 +1,28 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use duat_bench::root_area;
+use duat_core::ui::{Area, PushSpecs, Ui};
+
+fn layout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("layout");
+
+    group.bench_function("solve 50 areas", |b| {
+        b.iter(|| {
+            let (mut ui, root) = root_area();
+            let mut areas = vec![root];
+            for i in 1..50 {
+                let specs = match i % 2 {
+                    0 => PushSpecs::left(),
+                    _ => PushSpecs::below(),
+                };
+                let (child, _) = areas[i / 2].bisect(specs, false, true, Default::default());
+                areas.push(child);
+            }
+            ui.flush_layout();
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, layout);
+criterion_main!(benches);
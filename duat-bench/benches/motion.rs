@@ -0,0 +1,53 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use duat_bench::{Buffer, SOURCE_LEN, root_area, text};
+use duat_core::{
+    mode::{Cursors, EditHelper},
+    widgets::Widget,
+};
+
+fn motion(c: &mut Criterion) {
+    let (_ui, area) = root_area();
+    let buffer = Buffer::new(text(SOURCE_LEN));
+    let end = buffer.read().text().len();
+
+    let mut group = c.benchmark_group("motion");
+
+    group.bench_function("1000 words", |b| {
+        b.iter(|| {
+            let mut cursors = Cursors::new_excl();
+            let mut helper = EditHelper::new(&buffer, &area, &mut cursors);
+            for _ in 0..1000 {
+                helper.move_main(|mut m| {
+                    let word = m.search_fwd("[^\\s]+", None).next();
+                    if let Some((_, end)) = word {
+                        m.move_to(end);
+                    }
+                });
+            }
+        })
+    });
+
+    group.bench_function("1000 lines", |b| {
+        b.iter(|| {
+            let mut cursors = Cursors::new_excl();
+            let mut helper = EditHelper::new(&buffer, &area, &mut cursors);
+            for _ in 0..1000 {
+                helper.move_main(|mut m| m.move_ver(1));
+            }
+        })
+    });
+
+    group.bench_function("to the end and back", |b| {
+        b.iter(|| {
+            let mut cursors = Cursors::new_excl();
+            let mut helper = EditHelper::new(&buffer, &area, &mut cursors);
+            helper.move_main(|mut m| m.move_to(end));
+            helper.move_main(|mut m| m.move_to_coords(0, 0));
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, motion);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use duat_bench::{root_area, text};
+use duat_core::{
+    cfg::PrintCfg,
+    form,
+    text::{Key, Point, Tag},
+    ui::Area,
+};
+
+fn print(c: &mut Criterion) {
+    let (_ui, area) = root_area();
+    let cfg = PrintCfg::new();
+
+    // A form changes every few characters, much like with very
+    // detailed syntax highlighting.
+    let mut text = text(1024 * 1024);
+    let key = Key::new();
+    let ids = ["Accent", "DefaultOk", "AccentOk", "DefaultErr"].map(form::inner_to_id);
+    let points: Vec<Point> = text.chars_fwd(Point::default()).map(|(p, _)| p).collect();
+    for (i, range) in points.chunks_exact(4).enumerate() {
+        let id = ids[i % ids.len()];
+        text.insert_tag(range[0].byte(), Tag::PushForm(id), key);
+        text.insert_tag(range[2].byte(), Tag::PopForm(id), key);
+    }
+
+    let mut group = c.benchmark_group("print");
+
+    group.bench_function("dense tags, top", |b| {
+        area.scroll_to_row(&text, Point::default(), 0, cfg);
+        b.iter(|| area.print(&text, cfg, form::painter()))
+    });
+
+    group.bench_function("dense tags, middle", |b| {
+        let middle = text.point_at(text.len().byte() / 2);
+        area.scroll_around_point(&text, middle, cfg);
+        b.iter(|| area.print(&text, cfg, form::painter()))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, print);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use duat_bench::{SOURCE_LEN, text};
+use duat_core::text::Point;
+
+fn search(c: &mut Criterion) {
+    let mut text = text(SOURCE_LEN);
+
+    let mut group = c.benchmark_group("search");
+    group.sample_size(20);
+
+    group.bench_function("regex, many matches", |b| {
+        b.iter(|| {
+            let matches = text.search_fwd(r"fn \w+", Point::default(), None);
+            matches.unwrap().count()
+        })
+    });
+
+    group.bench_function("literal, no matches", |b| {
+        b.iter(|| {
+            let matches = text.search_fwd("this is not in the text", Point::default(), None);
+            matches.unwrap().count()
+        })
+    });
+
+    group.bench_function("regex, backwards", |b| {
+        let end = text.len();
+        b.iter(|| {
+            let matches = text.search_rev(r"\bimpl\b", end, None);
+            matches.unwrap().count()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, search);
+criterion_main!(benches);
@@ -0,0 +1,133 @@
+//! Fixtures for the benchmarks of Duat
+//!
+//! The benchmarks in this crate exercise the hot paths of
+//! [`duat_core`] and [`duat_term`] with realistic data, so that
+//! performance regressions can be measured when redesigning them:
+//!
+//! - `motion`: Moving the main cursor by words and lines;
+//! - `edit`: Editing with a thousand cursors, and undoing it;
+//! - `search`: Searching for patterns across the whole buffer;
+//! - `print`: Printing [`Text`] that is dense with [`Tag`]s;
+//! - `layout`: Solving a layout with 50 [`Area`]s.
+//!
+//! They can all be run with `cargo bench -p duat-bench`. In order to
+//! compare a change against the current state of the code, save a
+//! baseline before making it, and compare against it afterwards:
+//!
+//! ```bash
+//! cargo bench-save
+//! # Make your changes...
+//! cargo bench-cmp
+//! ```
+//!
+//! No terminal is needed in order to run them, since nothing is
+//! actually printed to the screen.
+//!
+//! [`Tag`]: duat_core::text::Tag
+use duat_core::{
+    cfg::PrintCfg,
+    data::RwData,
+    mode::Cursors,
+    text::Text,
+    ui::{Area as UiArea, PushSpecs, Ui as UiTrait},
+    widgets::{Widget, WidgetCfg},
+};
+use duat_term::{Area, StaticFns, Ui};
+
+/// The length of the [`Text`] used by most benchmarks, 10MB
+pub const SOURCE_LEN: usize = 10 * 1024 * 1024;
+
+/// Real source code, which is repeated in order to build larger
+/// [`Text`]s
+const SOURCES: &[&str] = &[
+    include_str!("../../duat-core/src/text/mod.rs"),
+    include_str!("../../duat-core/src/mode/helper/mod.rs"),
+    include_str!("../../duat-core/src/cmd/mod.rs"),
+    include_str!("../../duat-term/src/area/mod.rs"),
+    include_str!("../../duat-term/src/layout/rect.rs"),
+];
+
+/// Rust source code, with at least `len` bytes
+pub fn source(len: usize) -> String {
+    let mut source = String::with_capacity(len);
+    for src in SOURCES.iter().cycle() {
+        if source.len() >= len {
+            break;
+        }
+        source.push_str(src);
+    }
+
+    source
+}
+
+/// A [`Text`] of Rust source code, with at least `len` bytes
+pub fn text(len: usize) -> Text {
+    Text::from(source(len))
+}
+
+/// A new [`Ui`], alongside the root [`Area`] of its first window
+pub fn root_area() -> (Ui, Area) {
+    let mut ui = Ui::new(StaticFns::default());
+    let area = ui.new_root(<Area as UiArea>::Cache::default());
+    (ui, area)
+}
+
+/// [`Cursors`] spread evenly throughout the lines of a [`Text`]
+pub fn spread_cursors(text: &Text, area: &Area, amount: u32) -> Cursors {
+    let mut cursors = Cursors::new_excl();
+    let step = (text.len().line() / amount).max(1);
+
+    let mut guess_i = 0;
+    for line in (0..text.len().line())
+        .step_by(step as usize)
+        .take(amount as usize)
+    {
+        let point = text.point_at_line(line);
+        guess_i = cursors.insert_from_parts(guess_i, point, 0, text, area, PrintCfg::new()) + 1;
+    }
+
+    cursors
+}
+
+/// A bare [`Widget`], which just holds a [`Text`]
+///
+/// This is what the [`EditHelper`] acts on in the benchmarks.
+///
+/// [`EditHelper`]: duat_core::mode::EditHelper
+pub struct Buffer(Text);
+
+impl Buffer {
+    /// Returns a new [`Buffer`], ready to be edited
+    pub fn new(text: Text) -> RwData<Self> {
+        RwData::new(Self(text))
+    }
+}
+
+impl Widget<Ui> for Buffer {
+    type Cfg = BufferCfg;
+
+    fn cfg() -> Self::Cfg {
+        BufferCfg
+    }
+
+    fn text(&self) -> &Text {
+        &self.0
+    }
+
+    fn text_mut(&mut self) -> &mut Text {
+        &mut self.0
+    }
+
+    fn once() {}
+}
+
+/// The [`WidgetCfg`] of a [`Buffer`]
+pub struct BufferCfg;
+
+impl WidgetCfg<Ui> for BufferCfg {
+    type Widget = Buffer;
+
+    fn build(self, _: bool) -> (Self::Widget, impl Fn() -> bool + 'static, PushSpecs) {
+        (Buffer(Text::new()), || false, PushSpecs::left())
+    }
+}
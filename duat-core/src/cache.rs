@@ -27,12 +27,28 @@
 //! [`Cursors`]: crate::mode::Cursors
 //! [`Cursor`]: crate::mode::Cursor
 //! [`Point`]: crate::text::Point
-use std::{any::TypeId, io::Write, path::PathBuf};
+use std::{
+    any::TypeId,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
 use base64::Engine;
+use parking_lot::Mutex;
 pub use serde::{self, Deserialize, Serialize};
 
-use crate::{duat_name, src_crate};
+use crate::{
+    duat_name, src_crate,
+    text::{Text, err},
+};
+
+static LIMITS: Mutex<BackupLimits> = Mutex::new(BackupLimits {
+    max_count: 10,
+    max_age: Duration::from_secs(60 * 60 * 24 * 30),
+    max_size: 64 * 1024 * 1024,
+});
+static PENDING_RESTORE: Mutex<Option<u64>> = Mutex::new(None);
 
 /// Tries to load the cache stored by Duat for the given type
 ///
@@ -56,12 +72,19 @@ where
         base64.chars().step_by(5).collect()
     };
 
-    src.push("duat");
+    src.push("duat/structs");
     src.push(format!("{encoded}:{file_name}"));
     src.push(format!("{}::{}", src_crate::<C>(), duat_name::<C>()));
 
-    let contents = std::fs::read(src).ok()?.leak();
-    bincode::deserialize(contents).ok()
+    let contents = std::fs::read(&src).ok()?.leak();
+    match bincode::deserialize(contents) {
+        Ok(cache) => Some(cache),
+        Err(_) => {
+            // Keeping a copy around, since it will be overwritten.
+            dump_for_recovery(&src);
+            None
+        }
+    }
 }
 
 /// Stores the cache for the given type for that file
@@ -132,3 +155,220 @@ pub(super) fn delete_cache(path: impl Into<PathBuf>) {
         std::fs::remove_dir_all(src).unwrap();
     }
 }
+
+/// Limits on how many backups of the cache are kept
+///
+/// Every time Duat quits, a backup of the whole cache is made, and
+/// older ones are pruned according to these limits. The same limits
+/// apply to recovery dumps, which are copies of cache files that
+/// couldn't be read, made before they are overwritten.
+#[derive(Debug, Clone, Copy)]
+pub struct BackupLimits {
+    /// How many backups to keep, 10 by default
+    pub max_count: usize,
+    /// How old backups can be, 30 days by default
+    pub max_age: Duration,
+    /// The total size of the backups in bytes, 64 MiB by default
+    ///
+    /// The newest backup is always kept, even if it is bigger.
+    pub max_size: u64,
+}
+
+/// Sets the [`BackupLimits`] of the cache
+pub fn set_backup_limits(limits: BackupLimits) {
+    *LIMITS.lock() = limits;
+}
+
+/// A backup of the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backup {
+    timestamp: u64,
+    size: u64,
+}
+
+impl Backup {
+    /// When the backup was made, in seconds since the Unix epoch
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// The size of the backup, in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// How long ago the backup was made
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.timestamp))
+    }
+}
+
+/// The available backups of the cache, from newest to oldest
+pub fn backups() -> Vec<Backup> {
+    let Some(backups) = duat_dir("backups") else {
+        return Vec::new();
+    };
+
+    list_dated(&backups)
+        .into_iter()
+        .map(|(timestamp, path)| Backup { timestamp, size: dir_size(&path) })
+        .collect()
+}
+
+/// Restores a backup of the cache
+///
+/// Since the cache is stored when Duat quits or reloads, the backup
+/// is only restored after that, at which point the current cache is
+/// backed up as well, so restoring can be undone.
+pub fn restore_backup(timestamp: u64) -> Result<(), Text> {
+    if !backups().iter().any(|backup| backup.timestamp == timestamp) {
+        return Err(err!("There is no backup from " [*a] timestamp [] "."));
+    }
+
+    *PENDING_RESTORE.lock() = Some(timestamp);
+    Ok(())
+}
+
+/// Backs up the cache, pruning old backups
+pub(super) fn backup_cache() {
+    let (Some(structs), Some(backups)) = (duat_dir("structs"), duat_dir("backups")) else {
+        return;
+    };
+    if !structs.exists() {
+        return;
+    }
+
+    let _ = copy_dir(&structs, &unique_in(&backups));
+    prune(&backups);
+}
+
+/// Restores the backup set by [`restore_backup`], if there is one
+///
+/// The current cache is backed up before that. Returns `false` if
+/// nothing was restored.
+pub(super) fn apply_pending_restore() -> bool {
+    let Some(timestamp) = PENDING_RESTORE.lock().take() else {
+        return false;
+    };
+    let (Some(structs), Some(backups)) = (duat_dir("structs"), duat_dir("backups")) else {
+        return false;
+    };
+    // Copying first, since it could be pruned by the next backup.
+    let backup = backups.join(format!("{timestamp}.restoring"));
+    if copy_dir(&backups.join(timestamp.to_string()), &backup).is_err() {
+        return false;
+    }
+
+    backup_cache();
+    if structs.exists() {
+        let _ = std::fs::remove_dir_all(&structs);
+    }
+    let restored = copy_dir(&backup, &structs).is_ok();
+    let _ = std::fs::remove_dir_all(backup);
+
+    restored
+}
+
+/// Copies a cache file that couldn't be read
+fn dump_for_recovery(src: &Path) {
+    let Some(recovery) = duat_dir("recovery") else {
+        return;
+    };
+    let Some((dir, file)) = src.parent().and_then(Path::file_name).zip(src.file_name()) else {
+        return;
+    };
+
+    let dump = unique_in(&recovery);
+    if std::fs::create_dir_all(&dump).is_ok() {
+        let name = format!("{}:{}", dir.to_string_lossy(), file.to_string_lossy());
+        let _ = std::fs::copy(src, dump.join(name));
+    }
+    prune(&recovery);
+}
+
+/// The path of `$cache/duat/{name}`, if there is a cache directory
+fn duat_dir(name: &str) -> Option<PathBuf> {
+    Some(dirs_next::cache_dir()?.join("duat").join(name))
+}
+
+/// A new directory in `dir`, named after the current time
+fn unique_in(dir: &Path) -> PathBuf {
+    let mut timestamp = now();
+    while dir.join(timestamp.to_string()).exists() {
+        timestamp += 1;
+    }
+    dir.join(timestamp.to_string())
+}
+
+/// Removes entries of `dir` that go over the [`BackupLimits`]
+fn prune(dir: &Path) {
+    let limits = *LIMITS.lock();
+    let mut total_size = 0;
+
+    for (i, (timestamp, path)) in list_dated(dir).into_iter().enumerate() {
+        let age = Duration::from_secs(now().saturating_sub(timestamp));
+        total_size += dir_size(&path);
+
+        if i > 0 && (i >= limits.max_count || age > limits.max_age || total_size > limits.max_size)
+        {
+            let _ = std::fs::remove_dir_all(path);
+        }
+    }
+}
+
+/// The entries of `dir` named after timestamps, from newest to
+/// oldest
+fn list_dated(dir: &Path) -> Vec<(u64, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut dated: Vec<(u64, PathBuf)> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let timestamp = path.file_name()?.to_str()?.parse().ok()?;
+            Some((timestamp, path))
+        })
+        .collect();
+
+    dated.sort_unstable_by(|(lhs, _), (rhs, _)| rhs.cmp(lhs));
+    dated
+}
+
+/// The total size of the files in a directory
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
+/// Recursively copies a directory
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let to = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to)?;
+        } else {
+            std::fs::copy(entry.path(), to)?;
+        }
+    }
+    Ok(())
+}
+
+/// The current time, in seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
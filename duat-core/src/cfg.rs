@@ -74,6 +74,17 @@ impl NewLine {
     }
 }
 
+/// How to show a new line that is part of a selection
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SelectedNewLine {
+    /// Don't show anything beyond what [`NewLine`] shows
+    Hidden,
+    /// Highlight one cell past the end of the line
+    Cell,
+    /// Highlight the rest of the row, up to the right edge
+    ToEdge,
+}
+
 // Pretty much only exists because i wanted one of these with
 // usize as its builtin type.
 #[derive(Clone, Copy, Debug)]
@@ -92,6 +103,50 @@ impl ScrollOff {
     }
 }
 
+/// How many cells to leave empty on each side of an [`Area`]
+///
+/// This space is taken out of the [`Area`] when printing, so the
+/// width available for the [`Text`], and thus wrapping, is reduced
+/// accordingly.
+///
+/// [`Area`]: crate::ui::Area
+/// [`Text`]: crate::text::Text
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Padding {
+    left: u8,
+    right: u8,
+    top: u8,
+    bottom: u8,
+}
+
+impl Padding {
+    pub fn left(&self) -> u32 {
+        self.left as u32
+    }
+
+    pub fn right(&self) -> u32 {
+        self.right as u32
+    }
+
+    pub fn top(&self) -> u32 {
+        self.top as u32
+    }
+
+    pub fn bottom(&self) -> u32 {
+        self.bottom as u32
+    }
+
+    /// The width left after removing the horizontal padding
+    pub fn inner_width(&self, width: u32) -> u32 {
+        width.saturating_sub(self.left() + self.right())
+    }
+
+    /// The height left after removing the vertical padding
+    pub fn inner_height(&self, height: u32) -> u32 {
+        height.saturating_sub(self.top() + self.bottom())
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct WordChars(&'static LazyLock<(Regex, &'static [RangeInclusive<char>])>);
 
@@ -129,10 +184,17 @@ pub struct PrintCfg {
     pub wrap_method: WrapMethod,
     /// Whether to indent wrapped lines or not
     pub indent_wrap: bool,
+    /// A prefix shown at the start of wrapped lines, like `"↪ "`
+    ///
+    /// It is printed with the `"WrapPrefix"` form, and takes space
+    /// away from the wrapped line.
+    pub wrap_prefix: &'static str,
     /// Which places are considered a "tab stop"
     pub tab_stops: TabStops,
     /// Whether (and how) to show new lines
     pub new_line: NewLine,
+    /// How to show new lines that are part of a selection
+    pub selected_new_line: SelectedNewLine,
     /// How much space to keep between the cursor and edges
     pub scrolloff: ScrollOff,
     // NOTE: This is relevant for printing with `WrapMethod::Word`
@@ -142,6 +204,10 @@ pub struct PrintCfg {
     pub ending_space: bool,
     /// Whether or not to limit scrolloff on the end of lines
     pub force_scrolloff: bool,
+    /// Empty space to leave around the [`Text`]
+    ///
+    /// [`Text`]: crate::text::Text
+    pub padding: Padding,
 }
 
 impl PrintCfg {
@@ -149,12 +215,15 @@ impl PrintCfg {
         Self {
             wrap_method: WrapMethod::NoWrap,
             indent_wrap: true,
+            wrap_prefix: "",
             tab_stops: TabStops(4),
             new_line: NewLine::Hidden,
+            selected_new_line: SelectedNewLine::Cell,
             scrolloff: ScrollOff { x: 3, y: 3 },
             word_chars: WordChars::default(),
             ending_space: false,
             force_scrolloff: false,
+            padding: Padding { left: 0, right: 0, top: 0, bottom: 0 },
         }
     }
 
@@ -181,6 +250,10 @@ impl PrintCfg {
         Self { indent_wrap: true, ..self }
     }
 
+    pub const fn with_wrap_prefix(self, wrap_prefix: &'static str) -> Self {
+        Self { wrap_prefix, ..self }
+    }
+
     pub const fn with_tabs_size(self, tab_size: u8) -> Self {
         Self { tab_stops: TabStops(tab_size), ..self }
     }
@@ -199,6 +272,10 @@ impl PrintCfg {
         }
     }
 
+    pub const fn with_selected_new_line(self, selected_new_line: SelectedNewLine) -> Self {
+        Self { selected_new_line, ..self }
+    }
+
     pub const fn with_scrolloff(self, x: u8, y: u8) -> Self {
         Self { scrolloff: ScrollOff { x, y }, ..self }
     }
@@ -229,6 +306,27 @@ impl PrintCfg {
         Self { force_scrolloff: true, ..self }
     }
 
+    pub const fn with_padding(self, left: u8, right: u8, top: u8, bottom: u8) -> Self {
+        Self {
+            padding: Padding { left, right, top, bottom },
+            ..self
+        }
+    }
+
+    pub const fn with_hor_padding(self, left: u8, right: u8) -> Self {
+        Self {
+            padding: Padding { left, right, ..self.padding },
+            ..self
+        }
+    }
+
+    pub const fn with_ver_padding(self, top: u8, bottom: u8) -> Self {
+        Self {
+            padding: Padding { top, bottom, ..self.padding },
+            ..self
+        }
+    }
+
     /// The default used in files and other such inputs
     ///
     /// [`default`]: PrintCfg::default
@@ -236,12 +334,15 @@ impl PrintCfg {
         Self {
             wrap_method: WrapMethod::NoWrap,
             indent_wrap: true,
+            wrap_prefix: "",
             tab_stops: TabStops(4),
             new_line: NewLine::AlwaysAs(' '),
+            selected_new_line: SelectedNewLine::Cell,
             scrolloff: ScrollOff { x: 3, y: 3 },
             word_chars: WordChars::default(),
             ending_space: true,
             force_scrolloff: false,
+            padding: Padding { left: 0, right: 0, top: 0, bottom: 0 },
         }
     }
 }
@@ -314,6 +415,11 @@ impl IterCfg {
         !self.no_indent_wrap && self.cfg.indent_wrap
     }
 
+    #[inline]
+    pub const fn wrap_prefix(&self) -> &'static str {
+        self.cfg.wrap_prefix
+    }
+
     #[inline]
     pub const fn tab_stops(&self) -> TabStops {
         self.cfg.tab_stops
@@ -328,6 +434,11 @@ impl IterCfg {
         }
     }
 
+    #[inline]
+    pub const fn selected_new_line(&self) -> SelectedNewLine {
+        self.cfg.selected_new_line
+    }
+
     #[inline]
     pub const fn scrolloff(&self) -> ScrollOff {
         self.cfg.scrolloff
@@ -348,6 +459,11 @@ impl IterCfg {
         self.cfg.force_scrolloff
     }
 
+    #[inline]
+    pub const fn padding(&self) -> Padding {
+        self.cfg.padding
+    }
+
     #[inline]
     pub const fn wrap_width(&self, width: u32) -> u32 {
         match self.wrap_method() {
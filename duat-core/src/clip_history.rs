@@ -0,0 +1,277 @@
+//! A history of yanked text, with a picker to paste from it
+//!
+//! Whenever an editing [`Mode`] yanks or deletes some text, it can
+//! [record] the strings of every selection in this history. The
+//! [`Picker`] mode can then be used in order to pick one of the
+//! recent entries and paste it at every cursor:
+//!
+//! ```rust
+//! # use duat_core::{clip_history, mode, ui::Ui};
+//! # fn test<U: Ui>() {
+//! clip_history::record(vec!["hello".to_string(), "world".to_string()]);
+//! mode::set::<U>(clip_history::Picker::new());
+//! # }
+//! ```
+//!
+//! Entries can also be [pinned], in which case they are never
+//! evicted from the history, and are kept between sessions of Duat.
+//!
+//! [`Mode`]: crate::mode::Mode
+//! [record]: record
+//! [pinned]: toggle_pin
+use std::sync::{
+    LazyLock,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::{load_cache, store_cache},
+    context,
+    data::RwData,
+    mode::{self, Cursors, EditHelper, KeyCode::*, KeyEvent, KeyMod, Mode},
+    text::{Text, hint},
+    ui::Ui,
+    widgets::{File, FilterList},
+};
+
+static HISTORY: LazyLock<Mutex<Vec<Entry>>> = LazyLock::new(|| {
+    let pinned = load_cache::<PinnedEntries>(PINNED_PATH).unwrap_or_default();
+    Mutex::new(pinned.0.into_iter().map(Entry::pinned).collect())
+});
+static MAX_UNPINNED: AtomicUsize = AtomicUsize::new(30);
+const PINNED_PATH: &str = "clip-history";
+const PREVIEW_WIDTH: usize = 60;
+
+/// An entry in the yank history
+///
+/// Each entry holds one string per cursor that was yanking at the
+/// time.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Entry {
+    strs: Vec<String>,
+    is_pinned: bool,
+}
+
+impl Entry {
+    fn pinned(strs: Vec<String>) -> Self {
+        Self { strs, is_pinned: true }
+    }
+
+    /// The strings yanked by each cursor
+    pub fn strs(&self) -> &[String] {
+        &self.strs
+    }
+
+    /// Whether this entry is pinned
+    pub fn is_pinned(&self) -> bool {
+        self.is_pinned
+    }
+
+    /// A one line preview of this entry
+    ///
+    /// This shows the first line of the first string, how many lines
+    /// and selections were yanked, and whether it is pinned.
+    pub fn preview(&self) -> Text {
+        let first = self.strs.first().map(String::as_str).unwrap_or_default();
+        let line = first.lines().next().unwrap_or_default();
+        let mut line: String = line
+            .chars()
+            .map(|char| if char == '\t' { ' ' } else { char })
+            .take(PREVIEW_WIDTH)
+            .collect();
+        if line.chars().count() < first.chars().count() {
+            line.push('…');
+        }
+
+        let pin = if self.is_pinned { '*' } else { ' ' };
+        let mut preview = format!("{pin} {line}");
+
+        let lines = first.lines().count();
+        if lines > 1 {
+            preview.push_str(&format!(" ({lines} lines)"));
+        }
+        if self.strs.len() > 1 {
+            preview.push_str(&format!(" [{} selections]", self.strs.len()));
+        }
+
+        Text::from(preview)
+    }
+}
+
+/// Records a yank in the history
+///
+/// If an equal entry was already present, it is moved to the front,
+/// keeping whether it was pinned. Yanks where every string is empty
+/// are ignored.
+pub fn record(strs: Vec<String>) {
+    if strs.iter().all(String::is_empty) {
+        return;
+    }
+
+    let mut history = HISTORY.lock();
+    let is_pinned = match history.iter().position(|entry| entry.strs == strs) {
+        Some(i) => history.remove(i).is_pinned,
+        None => false,
+    };
+    history.insert(0, Entry { strs, is_pinned });
+
+    let max = MAX_UNPINNED.load(Ordering::Relaxed);
+    let mut unpinned = 0;
+    history.retain(|entry| {
+        unpinned += !entry.is_pinned as usize;
+        entry.is_pinned || unpinned <= max
+    });
+}
+
+/// The entries of the history, from most to least recent
+pub fn entries() -> Vec<Entry> {
+    HISTORY.lock().clone()
+}
+
+/// Sets how many unpinned entries are kept in the history
+///
+/// The default is 30.
+pub fn set_max_unpinned(max: usize) {
+    MAX_UNPINNED.store(max, Ordering::Relaxed);
+}
+
+/// Pins or unpins the entry at the given index
+///
+/// Returns whether the entry is now pinned, or [`None`] if there is
+/// no such entry. Pinned entries are stored, so they can be restored
+/// in the next session of Duat.
+pub fn toggle_pin(i: usize) -> Option<bool> {
+    let mut history = HISTORY.lock();
+    let entry = history.get_mut(i)?;
+    entry.is_pinned = !entry.is_pinned;
+    let is_pinned = entry.is_pinned;
+
+    store_pinned(&history);
+    Some(is_pinned)
+}
+
+/// Removes the entry at the given index, if it exists
+pub fn remove(i: usize) -> Option<Entry> {
+    let mut history = HISTORY.lock();
+    let entry = (i < history.len()).then(|| history.remove(i))?;
+
+    if entry.is_pinned {
+        store_pinned(&history);
+    }
+    Some(entry)
+}
+
+fn store_pinned(history: &[Entry]) {
+    let pinned = history.iter().filter(|entry| entry.is_pinned);
+    store_cache(
+        PINNED_PATH,
+        PinnedEntries(pinned.map(|entry| entry.strs.clone()).collect()),
+    );
+}
+
+/// A [`Mode`] to pick an entry from the history and paste it
+///
+/// The entries are shown in a [`FilterList`], and the selected one is
+/// pasted before every cursor when accepted. If there are less
+/// strings in the entry than there are cursors, the last one is
+/// repeated.
+///
+/// Besides the bindings of the [`FilterList`], the following keys are
+/// available:
+///
+/// - `<A-p>`: Pin or unpin the selected entry;
+/// - `<A-d>`: Remove the selected entry from the history.
+pub struct Picker<U: Ui>(FilterList<U>);
+
+impl<U: Ui> Picker<U> {
+    /// Returns a new [`Picker`], with the current entries
+    pub fn new() -> Self {
+        let list = FilterList::new(previews(), |i, widget, area, cursors| {
+            let Some(strs) = entries().get(i).map(|entry| entry.strs.clone()) else {
+                return;
+            };
+            let last = strs.last().unwrap();
+
+            let mut helper = EditHelper::new(widget, area, cursors);
+            let mut iter = strs.iter().chain(std::iter::repeat(last));
+            helper.edit_each(|e| e.insert(iter.next().unwrap()));
+
+            record(strs);
+        });
+
+        Self(
+            list.with_prompt("yank: ")
+                .with_height(8)
+                .with_binding(
+                    KeyEvent::new(Char('p'), KeyMod::ALT),
+                    ("<A-p>", "toggle pin"),
+                    |i, items| {
+                        toggle_pin(i);
+                        *items = previews();
+                    },
+                )
+                .with_binding(
+                    KeyEvent::new(Char('d'), KeyMod::ALT),
+                    ("<A-d>", "remove"),
+                    |i, items| {
+                        remove(i);
+                        *items = previews();
+                    },
+                ),
+        )
+    }
+}
+
+impl<U: Ui> Clone for Picker<U> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<U: Ui> Default for Picker<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<U: Ui> Mode<U> for Picker<U> {
+    type Widget = File;
+
+    fn send_key(
+        &mut self,
+        key: KeyEvent,
+        widget: &RwData<Self::Widget>,
+        area: &U::Area,
+        cursors: &mut Cursors,
+    ) {
+        self.0.send_key(key, widget, area, cursors);
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        <FilterList<U> as Mode<U>>::hints(&self.0)
+    }
+
+    fn on_switch(&mut self, widget: &RwData<Self::Widget>, area: &U::Area, cursors: &mut Cursors) {
+        if self.0.items().is_empty() {
+            context::echo(hint!("The yank history is empty"));
+            mode::reset();
+        } else {
+            self.0.on_switch(widget, area, cursors);
+        }
+    }
+}
+
+/// The previews of every entry, in order
+fn previews() -> Vec<String> {
+    HISTORY
+        .lock()
+        .iter()
+        .map(|entry| entry.preview().to_string())
+        .collect()
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PinnedEntries(Vec<Vec<String>>);
@@ -2,18 +2,25 @@
 //!
 //! TO BE DONE
 use std::{
+    cell::RefCell,
     collections::HashMap,
     fmt::Display,
     sync::{Arc, LazyLock},
 };
 
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use self::parameters::{RangeCtx, RangeSpec, Words, caller_of, quote, split_calls};
 pub use self::{
     control::*,
     global::*,
-    parameters::{Args, Flags, split_flags_and_args},
+    parameters::{Args, Flags, Range, split_flags_and_args},
 };
 use crate::{
-    Error, context,
+    Error,
+    cache::{load_cache, store_cache},
+    context,
     data::{RwData, RwLock},
     mode::Cursors,
     text::{Text, err, ok},
@@ -25,20 +32,36 @@ use crate::{
 mod control {
     use std::{
         path::PathBuf,
+        process::{Command, Stdio},
         sync::{
-            atomic::{AtomicBool, Ordering},
+            atomic::{AtomicBool, AtomicI32, Ordering},
             mpsc,
         },
+        time::Duration,
     };
 
+    use serde_json::json;
+
+    use super::{RANGE_CTX, parameters::RangeCtx};
     use crate::{
-        cmd, context, iter_around, iter_around_rev, mode,
-        text::{Text, err, ok},
-        ui::{Event, Ui, Window},
-        widgets::File,
+        cache,
+        cfg::TabStops,
+        cmd::{self, CmdResult, When},
+        context::{self, CurFile},
+        file_entry, filetype,
+        hooks::{self, OnFileTypeSet},
+        iter_around, iter_around_rev,
+        mode::{self, ConfirmSubstitutions},
+        opts::{self, OptValue, Scope},
+        registers, session,
+        text::{Point, Text, diff, err, ok},
+        toggle,
+        ui::{Area, Axis, Constraint, Event, LayoutNode, Side, Ui, Window},
+        widgets::{File, Node},
     };
 
     static HAS_ENDED: AtomicBool = AtomicBool::new(false);
+    static EXIT_CODE: AtomicI32 = AtomicI32::new(0);
 
     /// Returns `true` if Duat must quit/reload
     ///
@@ -48,6 +71,13 @@ mod control {
         HAS_ENDED.load(Ordering::Relaxed)
     }
 
+    /// The exit code that Duat should quit with
+    ///
+    /// This is only non zero if Duat was quit through `cquit`.
+    pub(crate) fn exit_code() -> i32 {
+        EXIT_CODE.load(Ordering::Relaxed)
+    }
+
     /// Ends duat, either for reloading the config, or quitting
     pub(crate) fn end_session() {
         HAS_ENDED.store(true, Ordering::Relaxed);
@@ -57,6 +87,10 @@ mod control {
     }
 
     pub(crate) fn add_session_commands<U: Ui>(tx: mpsc::Sender<Event>) -> crate::Result<(), ()> {
+        *RANGE_CTX.lock() = Some(range_ctx::<U>);
+        // Commands that act on the selections of the current File.
+        let on_file = When::file::<U>();
+
         cmd::add(["quit", "q"], {
             let tx = tx.clone();
 
@@ -66,6 +100,23 @@ mod control {
             }
         })?;
 
+        cmd::add(["cquit", "cq"], {
+            let tx = tx.clone();
+
+            move |_flags, mut args| {
+                let code = match args.next() {
+                    Ok(code) => code
+                        .parse()
+                        .map_err(|_| err!("Invalid exit code " [*a] code))?,
+                    Err(_) => 1,
+                };
+
+                EXIT_CODE.store(code, Ordering::Relaxed);
+                tx.send(Event::Quit).unwrap();
+                Ok(None)
+            }
+        })?;
+
         cmd::add(["write", "w"], move |_flags, mut args| {
             let file = context::cur_file::<U>()?;
 
@@ -80,20 +131,22 @@ mod control {
             };
 
             if paths.is_empty() {
-                file.inspect(|file, _, _| {
+                file.mutate_file(|file| {
                     if let Some(name) = file.path_set() {
                         let bytes = file.write()?;
+                        cmd::set_payload(bytes);
                         ok!("Wrote " [*a] bytes [] " bytes to " [*a] name [] ".")
                     } else {
                         Err(err!("Give the file a name, to write it with"))
                     }
                 })
             } else {
-                file.inspect(|file, _, _| {
+                file.mutate_file(|file| {
                     let mut bytes = 0;
                     for path in &paths {
                         bytes = file.write_to(path)?;
                     }
+                    cmd::set_payload(bytes);
 
                     let files_text = {
                         let mut builder = Text::builder();
@@ -117,6 +170,7 @@ mod control {
 
         cmd::add(["edit", "e"], {
             let windows = context::windows::<U>();
+            let tx = tx.clone();
 
             move |_, mut args| {
                 let windows = windows.read();
@@ -156,6 +210,74 @@ mod control {
             ok!("Switched to " [*a] name [] ".")
         })?;
 
+        cmd::add_completer("buffer", {
+            let windows = context::windows::<U>();
+
+            move |args, _| match args {
+                [] => windows
+                    .read()
+                    .iter()
+                    .flat_map(Window::nodes)
+                    .filter_map(|node| node.inspect_as::<File, String>(File::name))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        })?;
+
+        cmd::add(["session"], {
+            let tx = tx.clone();
+
+            move |_, mut args| {
+                let subcommand = args.next()?;
+                let name = args.next_else(err!("No session name supplied."))?;
+                args.ended()?;
+
+                match subcommand {
+                    "save" => {
+                        let len = session::save_session::<U>(name)?;
+                        ok!("Saved " [*a] len [] " files to the session " [*a] name [] ".")
+                    }
+                    "load" => {
+                        tx.send(Event::LoadSession(name.to_string())).unwrap();
+                        Ok(None)
+                    }
+                    _ => Err(err!("Unknown subcommand " [*a] subcommand [] ".")),
+                }
+            }
+        })?;
+
+        cmd::add(["session-restore"], |_, mut args| {
+            let Ok(timestamp) = args.next() else {
+                let backups = cache::backups();
+                if backups.is_empty() {
+                    return Err(err!("There are no backups to restore."));
+                }
+
+                let mut builder = Text::builder();
+                ok!(builder, "Backups:");
+                for backup in backups {
+                    let (age, size) = (fmt_age(backup.age()), backup.size().div_ceil(1024));
+                    ok!(builder, "\n  " [*a] { backup.timestamp() } [] ": " age " ago, " size "KiB");
+                }
+                return Ok(Some(builder.finish()));
+            };
+
+            let timestamp: u64 = timestamp
+                .parse()
+                .map_err(|_| err!("Invalid timestamp " [*a] timestamp [] "."))?;
+            cache::restore_backup(timestamp)?;
+
+            ok!("Backup " [*a] timestamp [] " will be restored when reloading or quitting.")
+        })?;
+
+        cmd::add(["switch-back"], move |_, _| {
+            if mode::switch_back::<U>() {
+                Ok(None)
+            } else {
+                Err(err!("There is no widget to switch back to."))
+            }
+        })?;
+
         cmd::add(["next-file"], {
             let windows = context::windows();
 
@@ -215,216 +337,1224 @@ mod control {
             }
         })?;
 
-        Ok(())
-    }
-}
+        cmd::add(["move-window"], {
+            let windows = context::windows::<U>();
 
-mod parameters;
+            move |_flags, mut args| {
+                let side = match args.next()? {
+                    "left" => Side::Left,
+                    "right" => Side::Right,
+                    "up" | "above" => Side::Above,
+                    "down" | "below" => Side::Below,
+                    side => return Err(err!("Invalid side " [*a] side [] ".")),
+                };
+                let name = args.next_else(err!("No file supplied."))?;
+                args.ended()?;
 
-mod global {
-    use super::{Args, CmdResult, Commands, Flags, Result};
-    use crate::{mode::Cursors, text::Text, ui::Ui, widgets::Widget};
+                let (area, target) = file_areas::<U>(&windows.read(), name)?;
+                area.move_next_to(&target, side)?;
 
-    static COMMANDS: Commands = Commands::new();
+                Ok(None)
+            }
+        })?;
 
-    /// Canonical way to quit Duat.
-    ///
-    /// By calling the quit command, all threads will finish their
-    /// tasks, and then Duat will execute a program closing
-    /// function, as defined by the [`Ui`].
-    pub fn quit() {
-        COMMANDS.run("quit").unwrap();
-    }
+        cmd::add_completer("move-window", {
+            let windows = context::windows::<U>();
 
-    /// Switches to/opens a [`File`] with the given name.
-    ///
-    /// If you wish to specifically switch to files that are already
-    /// open, use [`buffer`].
-    ///
-    /// If there are more arguments, they will be ignored.
-    ///
-    /// [`File`]: crate::widgets::File
-    pub fn edit(file: impl std::fmt::Display) -> Result<Option<Text>> {
-        COMMANDS.run(format!("edit {file}"))
-    }
+            move |args, _| match args {
+                [] => ["left", "right", "up", "down"].map(String::from).to_vec(),
+                [_] => file_names(&windows.read()),
+                _ => Vec::new(),
+            }
+        })?;
 
-    /// Switches to a [`File`] with the given name.
-    ///
-    /// If there is no file open with that name, does nothing. Use
-    /// [`edit`] if you wish to open files.
-    ///
-    /// If there are more arguments, they will be ignored.
-    ///
-    /// [`File`]: crate::widgets::File
-    pub fn buffer(file: impl std::fmt::Display) -> Result<Option<Text>> {
-        COMMANDS.run(format!("buffer {file}"))
-    }
+        cmd::add(["swap-window"], {
+            let windows = context::windows::<U>();
 
-    /// Switches to the next [`File`].
-    ///
-    /// This function will only look at files that are opened in the
-    /// current window. If you want to include other windows in the
-    /// search, use [`next_global_file`].
-    ///
-    /// [`File`]: crate::widgets::File
-    pub fn next_file() -> Result<Option<Text>> {
-        COMMANDS.run("next-file")
-    }
+            move |_flags, mut args| {
+                let name = args.next_else(err!("No file supplied."))?;
+                args.ended()?;
 
-    /// Switches to the previous [`File`].
-    ///
-    /// This function will only look at files that are opened in the
-    /// current window. If you want to include other windows in the
-    /// search, use [`prev_global_file`].
-    ///
-    /// [`File`]: crate::widgets::File
-    pub fn prev_file() -> Result<Option<Text>> {
-        COMMANDS.run("prev-file")
-    }
+                let (area, other) = file_areas::<U>(&windows.read(), name)?;
+                area.swap(&other)?;
 
-    /// Switches to the next [`File`].
-    ///
-    /// This function will look for files in all windows. If you want
-    /// to limit the search to just the current window, use
-    /// [`next_file`].
-    ///
-    /// [`File`]: crate::widgets::File
-    pub fn next_global_file() -> Result<Option<Text>> {
-        COMMANDS.run("next-file --global")
-    }
+                Ok(None)
+            }
+        })?;
 
-    /// Switches to the previous [`File`].
-    ///
-    /// This function will look for files in all windows. If you want
-    /// to limit the search to just the current window, use
-    /// [`prev_file`].
-    ///
-    /// [`File`]: crate::widgets::File
-    pub fn prev_global_file() -> Result<Option<Text>> {
-        COMMANDS.run("prev-file --global")
-    }
+        cmd::add_completer("swap-window", {
+            let windows = context::windows::<U>();
 
-    /// Tries to alias a `caller` to an existing `command`.
-    ///
-    /// Returns an [`Err`] if the `caller` is already a caller for
-    /// another command, or if `command` is not a real caller to an
-    /// existing command.
-    pub fn alias(alias: impl ToString, command: impl ToString) -> Result<Option<Text>> {
-        COMMANDS.alias(alias, command)
-    }
+            move |args, _| match args {
+                [] => file_names(&windows.read()),
+                _ => Vec::new(),
+            }
+        })?;
 
-    /// Runs a full command, with a caller, [`Flags`], and [`Args`].
-    ///
-    /// When running the command, the ordering of flags does not
-    /// matter, as long as they are placed before the arguments to the
-    /// command.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// # use duat_core::cmd;
-    /// cmd::run("set-prompt new-prompt");
-    /// ```
-    ///
-    /// In this case we're running a command that will affect the most
-    /// relevant [`CmdLine`]. See [`add_for`] for
-    /// more information.
-    ///
-    /// [`CmdLine`]: crate::widgets::CmdLine
-    pub fn run(call: impl std::fmt::Display) -> Result<Option<Text>> {
-        COMMANDS.run(call)
-    }
+        cmd::add(["layout-debug"], move |flags, _args| {
+            let widget = context::cur_widget::<U>()?;
+            let tree = widget.node().area().layout_tree();
 
-    /// Like [`run`], but notifies the result, not returning it
-    pub fn run_notify(call: impl std::fmt::Display) -> Result<Option<Text>> {
-        COMMANDS.run_notify(call)
-    }
+            let windows = context::windows::<U>().read();
+            let window = &windows[context::cur_window()];
+            let names: Vec<Option<&str>> = tree
+                .iter()
+                .map(|ln| {
+                    window
+                        .nodes()
+                        .find(|node| *node.area() == ln.area)
+                        .map(Node::widget_name)
+                })
+                .collect();
 
-    /// Adds a command to the global list of commands.
-    ///
-    /// This command cannot take any arguments beyond the [`Flags`]
-    /// and [`Args`], so any mutation of state must be done
-    /// through captured variables, usually in the form of
-    /// [`RwData`]s.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// # use duat_core::{cmd, data::RwData, hooks::{self, OnWindowOpen}, ui::Ui, widgets::status};
-    /// # fn test<U: Ui>() {
-    /// let var = RwData::new(35);
-    ///
-    /// cmd::add(["set-var"], {
-    ///     let var = var.clone();
-    ///     move |_flags, mut args| {
-    ///         let value: usize = args.next_as()?;
-    ///         *var.write() = value;
-    ///
-    ///         Ok(None)
-    ///     }
-    /// });
-    ///
-    /// hooks::add::<OnWindowOpen<U>>(move |builder| {
-    ///     let var = var.clone();
-    ///     builder.push(status!("The value is currently " var));
-    /// });
-    /// # }
-    /// ```
-    ///
-    /// Since `var` is an [`RwData`], it will be updated
-    /// automatically in the [`StatusLine`]
-    ///
-    /// [`StatusLine`]: https://docs.rs/duat/latest/duat/widgets/struct.StatusLine.html
-    /// [`RoData`]: crate::data::RoData
-    /// [`RwData`]: crate::data::RwData
-    pub fn add(
-        callers: impl IntoIterator<Item = impl ToString>,
-        f: impl FnMut(Flags, Args) -> CmdResult + 'static,
-    ) -> Result<()> {
-        COMMANDS.add(callers, f)
-    }
+            if flags.word("json") {
+                let json = layout_json(&tree, &names);
+                let str = json.to_string();
+                cmd::set_payload(json);
+                ok!(str)
+            } else {
+                Ok(Some(layout_text(&tree, &names)))
+            }
+        })?;
 
-    /// Adds a command that can mutate a widget of the given type,
-    /// along with its associated [`dyn Area`].
-    ///
-    /// This command will look for the [`Widget`] in the
-    /// following order:
-    ///
-    /// 1. Any widget directly attached to the current file.
-    /// 2. One other instance in the active window.
-    /// 3. Instances in other windows.
-    ///
-    /// Keep in mind that this command will always execute on the
-    /// first widget found.
-    ///
-    /// This search algorithm allows a more versatile configuration of
-    /// widgets, for example, one may have a [`CmdLine`] per
-    /// [`File`], or one singular [`CmdLine`] that acts upon
-    /// all files in the window, and both would respond correctly
-    /// to the `"set-prompt"` command.
-    ///
-    /// # Examples
-    ///
-    /// In this example, we'll create a simple `Timer` widget:
-    ///
-    /// ```rust
-    /// // Required feature for widgets.
-    /// # use std::{
-    /// #     sync::{atomic::{AtomicBool, Ordering}, Arc}, marker::PhantomData,
-    /// #     time::{Duration, Instant}
-    /// # };
-    /// # use duat_core::{
-    /// #     cmd, form::{self, Form}, text::{text, Text, AlignCenter},
-    /// #     ui::{Area, PushSpecs, Ui}, widgets::{Widget, WidgetCfg},
-    /// # };
-    /// struct TimerCfg<U>(PhantomData<U>);
-    ///
-    /// impl<U: Ui> WidgetCfg<U> for TimerCfg<U> {
-    ///     type Widget = Timer;
-    ///
-    ///     fn build(self, _is_file: bool) -> (Timer, impl Fn() -> bool, PushSpecs) {
-    ///         let widget = Timer {
-    ///             text: text!(AlignCenter [Counter] 0 [] "ms"),
+        cmd::add(["toggle"], move |_flags, mut args| {
+            let name = args.next_else(err!("No widget to toggle was supplied."))?;
+            args.ended()?;
+
+            match toggle::toggle::<U>(name) {
+                Some(true) => ok!("Showing " [*a] name),
+                Some(false) => ok!("Hiding " [*a] name),
+                None => Err(err!("No widget was registered as " [*a] name [] ".")),
+            }
+        })?;
+
+        cmd::add_completer("toggle", |args, _| match args {
+            [] => toggle::names(),
+            _ => Vec::new(),
+        })?;
+
+        cmd::add(["reveal"], move |_flags, _args| {
+            let file = context::cur_file::<U>()?;
+            let path = file
+                .inspect(|file, _, _| file.path_set())
+                .ok_or_else(|| err!("The file has no path to reveal."))?;
+
+            let dir = PathBuf::from(&path)
+                .parent()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .ok_or_else(|| err!("The file has no containing directory."))?;
+
+            let mut command = if cfg!(target_os = "macos") {
+                Command::new("open")
+            } else if cfg!(target_os = "windows") {
+                Command::new("explorer")
+            } else {
+                Command::new("xdg-open")
+            };
+
+            command
+                .arg(&dir)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()?;
+
+            ok!("Revealed " [*a] dir [] ".")
+        })?;
+
+        cmd::add(["set"], move |_flags, mut args| {
+            let file = context::cur_file::<U>()?;
+            let arg = args.next_else(err!("No option supplied."))?;
+
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => {
+                    let mut rest = Vec::new();
+                    while let Ok(arg) = args.next() {
+                        rest.push(arg);
+                    }
+                    (arg, (!rest.is_empty()).then(|| rest.join(" ")))
+                }
+            };
+
+            // Showing the value, with "set name?".
+            if let Some(name) = name.strip_suffix('?') {
+                return show_option(file, name);
+            }
+
+            // Unsetting booleans, with "set noname".
+            let (name, value) = match (name.strip_prefix("no"), &value) {
+                (Some(bool), None)
+                    if opts::get(name).is_none()
+                        && matches!(opts::get(bool), Some(OptValue::Bool(_))) =>
+                {
+                    (bool, Some("false".to_string()))
+                }
+                _ => (name, value),
+            };
+
+            // Non boolean options are shown if no value is given.
+            if value.is_none() && !matches!(opts::get(name), Some(OptValue::Bool(_))) {
+                return show_option(file, name);
+            }
+
+            let value = opts::parse(name, value.as_deref().unwrap_or(""))?;
+            match opts::scope_of(name) {
+                Some(Scope::File) => {
+                    file.mutate_file(|file| opts::set_for(file, name, value.clone()))?
+                }
+                _ => opts::set(name, value.clone())?,
+            }
+
+            ok!("Set " [*a] name [] " to " [*a] value [] ".")
+        })?;
+
+        cmd::add_completer("set", |args, _| match args {
+            [] => opts::names().into_iter().map(str::to_string).collect(),
+            _ => Vec::new(),
+        })?;
+
+        cmd::add(["set?"], move |_flags, mut args| {
+            let file = context::cur_file::<U>()?;
+            match args.next() {
+                Ok(name) => show_option(file, name),
+                Err(_) => {
+                    let mut builder = Text::builder();
+                    for (i, name) in opts::names().into_iter().enumerate() {
+                        let value = file.inspect(|file, _, _| opts::get_for(file, name));
+                        if let Some(value) = value {
+                            if i > 0 {
+                                ok!(builder, [] ", ");
+                            }
+                            ok!(builder, [*a] name [] "=" value);
+                        }
+                    }
+                    Ok(Some(builder.finish()))
+                }
+            }
+        })?;
+
+        cmd::add_when(["autoformat"], on_file.clone(), move |_flags, mut args| {
+            let file = context::cur_file::<U>()?;
+            let is_on = match args.next() {
+                Ok("on") => true,
+                Ok("off") => false,
+                Ok(arg) => {
+                    return Err(
+                        err!("Expected " [*a] "on" [] " or " [*a] "off" [] ", got " [*a] arg),
+                    );
+                }
+                Err(_) => {
+                    let value = file.inspect(|file, _, _| opts::get_for(file, "autoformat"));
+                    let is_on = value != Some(OptValue::Bool(false));
+                    return ok!("Autoformat is " [*a] { if is_on { "on" } else { "off" } });
+                }
+            };
+
+            file.mutate_file(|file| opts::set_for(file, "autoformat", OptValue::Bool(is_on)))?;
+            ok!("Turned autoformat " [*a] { if is_on { "on" } else { "off" } } [] " for this file.")
+        })?;
+
+        cmd::add_completer("autoformat", |args, _| match args {
+            [] => vec!["on".to_string(), "off".to_string()],
+            _ => Vec::new(),
+        })?;
+
+        cmd::add_ranged_when(["sort"], "%", on_file.clone(), move |flags, args| {
+            let range = args.range().unwrap();
+            let file = context::cur_file::<U>()?;
+
+            file.mutate_data(|file, area, cursors| {
+                let mut file = file.write();
+                let cfg = file.print_cfg();
+                let text = file.text_mut();
+
+                let (start, end) = range.points(text);
+                let old = text.strs_in_range((start, end)).concat();
+                let has_last_nl = old.ends_with('\n');
+                let mut lines: Vec<&str> = old.lines().collect();
+
+                lines.sort_unstable();
+                if flags.blob("u") {
+                    lines.dedup();
+                }
+                if flags.blob("r") {
+                    lines.reverse();
+                }
+
+                let mut new = lines.join("\n");
+                if has_last_nl {
+                    new.push('\n');
+                }
+                if new == old {
+                    return;
+                }
+
+                text.replace_range((start, end), &new);
+                text.new_moment();
+
+                let (b, old_end, new_len) = (start.byte(), end.byte(), new.len() as u32);
+                let map = |byte: u32| match byte {
+                    byte if byte < b => byte,
+                    byte if byte < old_end => b + (byte - b).min(new_len),
+                    byte => byte - old_end + b + new_len,
+                };
+                cursors.write().map_bytes(map, text, area, &cfg);
+            });
+
+            ok!("Sorted " [*a] { range.len() } [] " lines.")
+        })?;
+
+        cmd::add_ranged_when(["align"], "'<,'>", on_file.clone(), move |_, mut args| {
+            let range = args.range().unwrap();
+            let range_was_given = args.range_was_given();
+            let pat: String = args.collect();
+            if pat.is_empty() {
+                return Err(err!("No pattern to align on."));
+            }
+            let file = context::cur_file::<U>()?;
+
+            let aligned = file.mutate_data(|file, area, cursors| {
+                let mut file = file.write();
+                let cfg = file.print_cfg();
+                let text = file.text_mut();
+
+                // With many selections, align on the lines of all of them.
+                let c = cursors.read();
+                let mut lines: Vec<u32> = match c.len() > 1 && !range_was_given {
+                    true => c
+                        .iter()
+                        .flat_map(|(cursor, _)| {
+                            let (start, end) = cursor.point_range(c.is_incl(), text);
+                            let end = match end > start {
+                                true => text.point_at(end.byte() - 1),
+                                false => end,
+                            };
+                            start.line()..=end.line()
+                        })
+                        .collect(),
+                    false => range.lines().collect(),
+                };
+                drop(c);
+                lines.sort_unstable();
+                lines.dedup();
+
+                let lines: Vec<(u32, String)> = lines
+                    .into_iter()
+                    .map(|line| {
+                        let start = text.point_at_line(line);
+                        let end = match line < text.len().line() {
+                            true => text.point_at_line(line + 1),
+                            false => text.len(),
+                        };
+                        let str = text.strs_in_range((start, end)).concat();
+                        (start.byte(), str.trim_end_matches('\n').to_string())
+                    })
+                    .collect();
+
+                let padding = align_padding(&lines, &pat, cfg.tab_stops);
+                if padding.is_empty() {
+                    return false;
+                }
+
+                for &(byte, len) in padding.iter().rev() {
+                    let point = text.point_at(byte);
+                    text.replace_range((point, point), " ".repeat(len as usize));
+                }
+                text.new_moment();
+
+                let map = |byte: u32| {
+                    let before = padding.iter().take_while(|(b, _)| *b <= byte);
+                    byte + before.map(|(_, len)| len).sum::<u32>()
+                };
+                cursors.write().map_bytes(map, text, area, &cfg);
+                true
+            });
+
+            match aligned {
+                true => Ok(None),
+                false => ok!("Nothing to align."),
+            }
+        })?;
+
+        cmd::add_ranged_when(
+            ["substitute", "s"],
+            ".",
+            on_file.clone(),
+            move |flags, mut args| {
+                let range = args.range().unwrap();
+                let pat = args.next_else(err!("No pattern to substitute."))?;
+                let replacement = args.next_else(err!("No replacement for the pattern."))?;
+                args.ended()?;
+
+                let regex = regex_automata::meta::Regex::new(pat)
+                    .map_err(|e| err!("Invalid pattern " [*a] pat [] ": " { e.to_string() }))?;
+                let all = flags.word("all") || flags.blob("g");
+                let confirm = flags.word("confirm") || flags.blob("c");
+                let on_selections = flags.word("selections") || flags.blob("s");
+                let file = context::cur_file::<U>()?;
+
+                let (subs, lines) = file.mutate_data(|file, area, cursors| {
+                    let mut file = file.write();
+                    let cfg = file.print_cfg();
+                    let text = file.text_mut();
+
+                    let ranges: Vec<(Point, Point)> = match on_selections {
+                        true => {
+                            let c = cursors.read();
+                            c.iter()
+                                .map(|(cursor, _)| cursor.point_range(c.is_incl(), text))
+                                .collect()
+                        }
+                        false => vec![range.points(text)],
+                    };
+
+                    let subs = mode::substitutions(text, ranges, &regex, replacement, all);
+                    let lines = mode::lines_of(text, &subs);
+                    if !confirm && !subs.is_empty() {
+                        let map = mode::substitute_all(text, &subs);
+                        cursors.write().map_bytes(map, text, area, &cfg);
+                    }
+
+                    (subs, lines)
+                });
+
+                if subs.is_empty() {
+                    Err(err!("No matches for " [*a] pat [] "."))
+                } else if confirm {
+                    let len = subs.len();
+                    mode::set::<U>(ConfirmSubstitutions::new(subs));
+                    ok!(
+                        "Confirm " [*a] len [] " matches with " [*a] "y" [] ", " [*a] "n" [] ", "
+                        [*a] "a" [] " or " [*a] "q" [] "."
+                    )
+                } else {
+                    ok!("Replaced " [*a] { subs.len() } [] " matches on " [*a] lines [] " lines.")
+                }
+            },
+        )?;
+
+        cmd::add(["set-var"], move |_flags, mut args| {
+            let file = context::cur_file::<U>()?;
+            let key = args.next_else(err!("No variable supplied."))?.to_string();
+
+            let mut value = String::new();
+            while let Ok(arg) = args.next() {
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                value.push_str(arg);
+            }
+
+            file.mutate_vars(|vars| vars.set(&key, value.clone()));
+            ok!("Set " [*a] key [] " to " [*a] value [] ".")
+        })?;
+
+        cmd::add(["unset-var"], move |_flags, mut args| {
+            let file = context::cur_file::<U>()?;
+            let key = args.next_else(err!("No variable supplied."))?;
+
+            if file.mutate_vars(|vars| vars.remove(key)) {
+                ok!("Unset " [*a] key [] ".")
+            } else {
+                Err(err!("There is no variable " [*a] key [] "."))
+            }
+        })?;
+
+        cmd::add(["var"], move |_flags, mut args| {
+            let file = context::cur_file::<U>()?;
+            let key = args.next_else(err!("No variable supplied."))?;
+
+            match file.inspect(|file, _, _| file.vars().display(key)) {
+                Some(value) => {
+                    cmd::set_payload(value.clone());
+                    ok!([*a] key [] " is " [*a] value [] ".")
+                }
+                None if file.inspect(|file, _, _| file.vars().contains(key)) => {
+                    Err(err!("The variable " [*a] key [] " can't be shown."))
+                }
+                None => Err(err!("There is no variable " [*a] key [] ".")),
+            }
+        })?;
+
+        cmd::add(["copy-path"], move |_flags, _args| {
+            let file = context::cur_file::<U>()?;
+            let path = file.inspect(|file, _, _| file.path());
+
+            registers::set::<U>(registers::CLIPBOARD, vec![path.clone()]);
+            ok!("Copied " [*a] path [] ".")
+        })?;
+
+        cmd::add_when(["copy-position"], on_file.clone(), move |_flags, _args| {
+            let file = context::cur_file::<U>()?;
+            let position = file.inspect(|file, _, cursors| {
+                let caret = cursors
+                    .get_main()
+                    .map(|main| main.caret())
+                    .unwrap_or_default();
+                let line_start = file.text().point_at_line(caret.line());
+                let col = caret.char() - line_start.char();
+                format!("{}:{}:{}", file.path(), caret.line() + 1, col + 1)
+            });
+
+            registers::set::<U>(registers::CLIPBOARD, vec![position.clone()]);
+            ok!("Copied " [*a] position [] ".")
+        })?;
+
+        cmd::add_when(["file-info"], on_file.clone(), move |_flags, _args| {
+            let file = context::cur_file::<U>()?;
+            file.mutate_file(|file| {
+                file.refresh_info();
+                let info = file.info();
+
+                let mut builder = Text::builder();
+                ok!(builder, [*a] { file.name() } [] ": ");
+                if let (Some(size), Some(perms), Some(modified)) =
+                    (info.size_fmt(), info.permissions(), info.modified_fmt())
+                {
+                    ok!(builder, [*a] size [] ", " [*a] perms [] ", modified " [*a] modified [] ", ");
+                } else {
+                    ok!(builder, "not on disk, ");
+                }
+                ok!(builder, [*a] { info.encoding() } [] ", " [*a] { info.line_ending() });
+
+                Ok(Some(builder.finish()))
+            })
+        })?;
+
+        cmd::add_when(["filetype"], on_file.clone(), move |_flags, mut args| {
+            let file = context::cur_file::<U>()?;
+            let Ok(new) = args.next() else {
+                return match file.inspect(|file, _, _| file.filetype().map(str::to_string)) {
+                    Some(filetype) => ok!([*a] { file.name() } [] " is of type " [*a] filetype),
+                    None => ok!("The type of " [*a] { file.name() } [] " is unknown."),
+                };
+            };
+            args.ended()?;
+
+            file.mutate_data(|file, _, _| {
+                file.write().set_filetype(new.to_string());
+                hooks::trigger::<OnFileTypeSet>(file.clone());
+            });
+            filetype::set_forms_of(Some(new));
+
+            ok!("Set the type of " [*a] { file.name() } [] " to " [*a] new)
+        })?;
+
+        cmd::add_when(
+            ["file-changed"],
+            on_file.clone(),
+            move |_flags, mut args| {
+                let reload = match args.next_else(err!("Pick either reload or keep."))? {
+                    "reload" => true,
+                    "keep" => false,
+                    other => {
+                        return Err(err!("Invalid choice " [*a] other [] ", pick reload or keep."));
+                    }
+                };
+                args.ended()?;
+                let file = context::cur_file::<U>()?;
+
+                if !file.inspect(|file, _, _| file.was_changed_on_disk()) {
+                    return Err(err!("The file wasn't changed on disk."));
+                }
+
+                if reload {
+                    file.mutate_data(|file, area, cursors| {
+                        let mut file = file.write();
+                        let contents = std::fs::read_to_string(file.path())?;
+                        let cfg = file.print_cfg();
+                        let text = file.text_mut();
+
+                        // Only the lines that changed are replaced, so the cursors
+                        // stay in place, and the reload can be undone in one go.
+                        let old = text.strs().concat();
+                        let hunks = diff::line_bytes(&old, &contents);
+                        if !hunks.is_empty() {
+                            text.new_moment();
+                            for hunk in hunks.iter().rev() {
+                                let range = (
+                                    text.point_at(hunk.old.start as u32),
+                                    text.point_at(hunk.old.end as u32),
+                                );
+                                text.replace_range(range, &contents[hunk.new.clone()]);
+                            }
+                            text.new_moment();
+
+                            let map = |byte: u32| diff::map_index(&hunks, byte as usize) as u32;
+                            cursors.write().map_bytes(map, text, area, &cfg);
+                        }
+
+                        file.set_changed_on_disk(false);
+                        file.refresh_info();
+                        ok!("Reloaded " [*a] { file.name() } [] " from disk.")
+                    })
+                } else {
+                    file.mutate_file(|file| {
+                        file.set_changed_on_disk(false);
+                        file.refresh_info();
+                        ok!("Kept the contents of " [*a] { file.name() } [] ".")
+                    })
+                }
+            },
+        )?;
+
+        cmd::add_completer("file-changed", |args, _| match args {
+            [] => vec!["reload".to_string(), "keep".to_string()],
+            _ => Vec::new(),
+        })?;
+
+        cmd::add_when(["selection-undo"], on_file.clone(), move |_flags, _args| {
+            let file = context::cur_file::<U>()?;
+            let undone = file.mutate_data(|file, _, cursors| {
+                file.write().text_mut().selection_undo(&mut cursors.write())
+            });
+
+            match undone {
+                true => Ok(None),
+                false => Err(err!("No selections to go back to.")),
+            }
+        })?;
+
+        cmd::add_when(["selection-redo"], on_file, move |_flags, _args| {
+            let file = context::cur_file::<U>()?;
+            let redone = file.mutate_data(|file, _, cursors| {
+                file.write().text_mut().selection_redo(&mut cursors.write())
+            });
+
+            match redone {
+                true => Ok(None),
+                false => Err(err!("No selections to go forward to.")),
+            }
+        })?;
+
+        Ok(())
+    }
+
+    /// Shows the value of an option, in the active [`File`]
+    fn show_option<U: Ui>(file: &CurFile<U>, name: &str) -> CmdResult {
+        let value = file
+            .inspect(|file, _, _| opts::get_for(file, name))
+            .ok_or_else(|| err!("There is no option " [*a] name [] "."))?;
+
+        cmd::set_payload(value.clone());
+        ok!([*a] name [] " is " [*a] value [] ".")
+    }
+
+    /// The [`Area`]s of the active [`File`] and of another one
+    ///
+    /// Both [`File`]s have to be in the current window.
+    fn file_areas<U: Ui>(windows: &[Window<U>], name: &str) -> Result<(U::Area, U::Area), Text> {
+        let file = context::cur_file::<U>()?;
+        if file.name() == name {
+            return Err(err!("Can't move " [*a] name [] " relative to itself."));
+        }
+
+        let (w, node) = file_entry(windows, name)?;
+        if w != context::cur_window() {
+            return Err(err!([*a] name [] " is in another window."));
+        }
+
+        Ok((file.inspect(|_, area, _| area.clone()), node.area().clone()))
+    }
+
+    /// The names of the [`File`]s in the current window
+    fn file_names<U: Ui>(windows: &[Window<U>]) -> Vec<String> {
+        windows[context::cur_window()]
+            .file_names()
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    /// A human readable rendition of a layout tree
+    fn layout_text<A>(tree: &[LayoutNode<A>], names: &[Option<&str>]) -> Text {
+        let mut builder = Text::builder();
+
+        for (i, (ln, name)) in tree.iter().zip(names).enumerate() {
+            let width = ln.br.0.saturating_sub(ln.tl.0);
+            let height = ln.br.1.saturating_sub(ln.tl.1);
+            let indent = "  ".repeat(ln.depth);
+            ok!(builder, [] indent [*a] "#" i [] " at " { format!("{:?}", ln.tl) } ", "
+                [*a] width [] "x" [*a] height);
+
+            if let Some(axis) = ln.axis {
+                ok!(builder, [] ", " { axis_name(axis) });
+            }
+            if ln.is_clustered {
+                ok!(builder, [] ", clustered");
+            }
+            if let Some(con) = ln.hor_con {
+                ok!(builder, [] ", hor: " { format!("{con:?}") });
+            }
+            if let Some(con) = ln.ver_con {
+                ok!(builder, [] ", ver: " { format!("{con:?}") });
+            }
+            if let Some(name) = name {
+                ok!(builder, [] " " [*a] name);
+            }
+
+            if i + 1 < tree.len() {
+                ok!(builder, [] "\n");
+            }
+        }
+
+        builder.finish()
+    }
+
+    /// A machine readable rendition of a layout tree, in JSON
+    fn layout_json<A>(tree: &[LayoutNode<A>], names: &[Option<&str>]) -> serde_json::Value {
+        fn con_json(con: Constraint) -> serde_json::Value {
+            match con {
+                Constraint::Ratio(num, den) => json!({ "ratio": [num, den] }),
+                Constraint::Length(len) => json!({ "length": len }),
+                Constraint::Min(min) => json!({ "min": min }),
+                Constraint::Max(max) => json!({ "max": max }),
+            }
+        }
+
+        let mut parents: Vec<usize> = Vec::new();
+        let nodes = tree
+            .iter()
+            .zip(names)
+            .enumerate()
+            .map(|(i, (ln, name))| {
+                parents.truncate(ln.depth);
+                let parent = parents.last().copied();
+                parents.push(i);
+
+                json!({
+                    "index": i,
+                    "parent": parent,
+                    "depth": ln.depth,
+                    "x": ln.tl.0,
+                    "y": ln.tl.1,
+                    "width": ln.br.0.saturating_sub(ln.tl.0),
+                    "height": ln.br.1.saturating_sub(ln.tl.1),
+                    "axis": ln.axis.map(axis_name),
+                    "clustered": ln.is_clustered,
+                    "hor_con": ln.hor_con.map(con_json),
+                    "ver_con": ln.ver_con.map(con_json),
+                    "widget": name,
+                })
+            })
+            .collect();
+
+        serde_json::Value::Array(nodes)
+    }
+
+    /// Where to pad, and by how much, in order to align `pat`
+    ///
+    /// Each line is given by the byte where it starts and its
+    /// contents. Every occurrence of `pat` is aligned, the first ones
+    /// with each other, then the second ones, and so on. Returns the
+    /// byte and length of each padding, in order.
+    fn align_padding(lines: &[(u32, String)], pat: &str, tabs: TabStops) -> Vec<(u32, u32)> {
+        let matches: Vec<Vec<usize>> = lines
+            .iter()
+            .map(|(_, line)| line.match_indices(pat).map(|(i, _)| i).collect())
+            .collect();
+        let mut pads: Vec<Vec<u32>> = matches.iter().map(|m| vec![0; m.len()]).collect();
+
+        // The column of the nth match of a line, with the padding so far.
+        let col_of = |line: &str, matches: &[usize], pads: &[u32], n: usize| {
+            let mut col = 0;
+            let mut prev = 0;
+            for (&i, &pad) in matches.iter().zip(pads).take(n + 1) {
+                col = line[prev..i].chars().fold(col, |col, char| match char {
+                    '\t' => col + tabs.spaces_at(col),
+                    _ => col + 1,
+                });
+                if i < matches[n] {
+                    col += pad;
+                }
+                prev = i;
+            }
+            col
+        };
+
+        let max_matches = matches.iter().map(Vec::len).max().unwrap_or(0);
+        for n in 0..max_matches {
+            let cols: Vec<Option<u32>> = lines
+                .iter()
+                .zip(&matches)
+                .zip(&pads)
+                .map(|(((_, line), m), p)| (n < m.len()).then(|| col_of(line, m, p, n)))
+                .collect();
+            let Some(target) = cols.iter().flatten().max().copied() else {
+                continue;
+            };
+            for (col, pads) in cols.iter().zip(pads.iter_mut()) {
+                if let Some(col) = col {
+                    pads[n] = target - col;
+                }
+            }
+        }
+
+        lines
+            .iter()
+            .zip(matches.iter().zip(&pads))
+            .flat_map(|((start, _), (m, p))| {
+                m.iter()
+                    .zip(p)
+                    .filter(|(_, pad)| **pad > 0)
+                    .map(move |(i, pad)| (start + *i as u32, *pad))
+            })
+            .collect()
+    }
+
+    /// What is needed to resolve a [`Range`] on the current [`File`]
+    ///
+    /// [`Range`]: super::Range
+    fn range_ctx<U: Ui>() -> Result<RangeCtx, Text> {
+        let file = context::cur_file::<U>()?;
+        Ok(file.inspect(|file, _, cursors| {
+            let text = file.text();
+            let len = text.len();
+            // An empty line after the last '\n' doesn't count.
+            let last = match len.line() > 0 && text.point_at_line(len.line()) == len {
+                true => len.line() - 1,
+                false => len.line(),
+            };
+
+            let (cur, sel) = match cursors.get_main() {
+                Some(main) => {
+                    let (start, end) = main.point_range(cursors.is_incl(), text);
+                    let end = match end > start {
+                        true => text.point_at(end.byte() - 1),
+                        false => end,
+                    };
+                    (main.line(), (start.line(), end.line()))
+                }
+                None => (0, (0, 0)),
+            };
+
+            RangeCtx { cur: cur.min(last), sel, last }
+        }))
+    }
+
+    fn axis_name(axis: Axis) -> &'static str {
+        match axis {
+            Axis::Horizontal => "horizontal",
+            Axis::Vertical => "vertical",
+        }
+    }
+
+    /// A short description of a [`Duration`]
+    fn fmt_age(age: Duration) -> String {
+        match age.as_secs() {
+            secs @ ..60 => format!("{secs}s"),
+            secs @ ..3600 => format!("{}m", secs / 60),
+            secs @ ..86400 => format!("{}h", secs / 3600),
+            secs => format!("{}d", secs / 86400),
+        }
+    }
+}
+
+mod parameters;
+
+mod global {
+    use std::{any::Any, cell::RefCell, sync::mpsc};
+
+    use super::{Args, CmdResult, Commands, Flags, RangeSpec, Result, When};
+    use crate::{mode::Cursors, text::Text, ui::Ui, widgets::Widget};
+
+    static COMMANDS: Commands = Commands::new();
+
+    thread_local! {
+        /// The payload of the command running on this thread
+        static PAYLOAD: RefCell<Option<Box<dyn Any + Send>>> = const { RefCell::new(None) };
+        /// The payload of the last command that finished on this thread
+        static RETURNED: RefCell<Option<Box<dyn Any + Send>>> = const { RefCell::new(None) };
+    }
+
+    /// Canonical way to quit Duat.
+    ///
+    /// By calling the quit command, all threads will finish their
+    /// tasks, and then Duat will execute a program closing
+    /// function, as defined by the [`Ui`].
+    pub fn quit() {
+        COMMANDS.run("quit").unwrap();
+    }
+
+    /// Quits Duat with an exit code
+    ///
+    /// This is useful when Duat is the `$EDITOR` of programs like
+    /// `git`, which abort the operation if the editor fails.
+    pub fn cquit(code: i32) {
+        COMMANDS.run(format!("cquit {code}")).unwrap();
+    }
+
+    /// Switches to/opens a [`File`] with the given name.
+    ///
+    /// If you wish to specifically switch to files that are already
+    /// open, use [`buffer`].
+    ///
+    /// If there are more arguments, they will be ignored.
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn edit(file: impl std::fmt::Display) -> Result<Option<Text>> {
+        COMMANDS.run(format!("edit {file}"))
+    }
+
+    /// Switches to a [`File`] with the given name.
+    ///
+    /// If there is no file open with that name, does nothing. Use
+    /// [`edit`] if you wish to open files.
+    ///
+    /// If there are more arguments, they will be ignored.
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn buffer(file: impl std::fmt::Display) -> Result<Option<Text>> {
+        COMMANDS.run(format!("buffer {file}"))
+    }
+
+    /// Switches back to the previously focused widget.
+    ///
+    /// Unlike [`mode::reset`], this will return to whichever widget
+    /// was focused before the current one, be it a [`File`] or not,
+    /// restoring the [`Mode`] it was in.
+    ///
+    /// [`mode::reset`]: crate::mode::reset
+    /// [`Mode`]: crate::mode::Mode
+    /// [`File`]: crate::widgets::File
+    pub fn switch_back() -> Result<Option<Text>> {
+        COMMANDS.run("switch-back")
+    }
+
+    /// Switches to the next [`File`].
+    ///
+    /// This function will only look at files that are opened in the
+    /// current window. If you want to include other windows in the
+    /// search, use [`next_global_file`].
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn next_file() -> Result<Option<Text>> {
+        COMMANDS.run("next-file")
+    }
+
+    /// Switches to the previous [`File`].
+    ///
+    /// This function will only look at files that are opened in the
+    /// current window. If you want to include other windows in the
+    /// search, use [`prev_global_file`].
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn prev_file() -> Result<Option<Text>> {
+        COMMANDS.run("prev-file")
+    }
+
+    /// Switches to the next [`File`].
+    ///
+    /// This function will look for files in all windows. If you want
+    /// to limit the search to just the current window, use
+    /// [`next_file`].
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn next_global_file() -> Result<Option<Text>> {
+        COMMANDS.run("next-file --global")
+    }
+
+    /// Switches to the previous [`File`].
+    ///
+    /// This function will look for files in all windows. If you want
+    /// to limit the search to just the current window, use
+    /// [`prev_file`].
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn prev_global_file() -> Result<Option<Text>> {
+        COMMANDS.run("prev-file --global")
+    }
+
+    /// Tries to alias a `caller` to an existing `command`.
+    ///
+    /// The `command` may contain positional parameters, in the form
+    /// of `%1`, `%2`, etc. These will be replaced by the arguments
+    /// passed to the alias when it is called. You can also use `%@`
+    /// in order to place all remaining arguments in a specific spot,
+    /// and `%%` for a literal `%`:
+    ///
+    /// ```rust
+    /// # use duat_core::cmd;
+    /// cmd::alias("gc", "grep %1 --type rust %@");
+    /// ```
+    ///
+    /// If there is no `%@` in the `command`, the remaining arguments
+    /// will be appended to the end of the call.
+    ///
+    /// Arguments with whitespace can be surrounded by double quotes,
+    /// both in the `command` and when calling the alias, and they
+    /// will be passed on as a single argument:
+    ///
+    /// ```rust
+    /// # use duat_core::cmd;
+    /// cmd::alias("notes", "edit \"my notes.md\"");
+    /// ```
+    ///
+    /// Returns an [`Err`] if the `caller` is already a caller for
+    /// another command, if `command` is not a real caller to an
+    /// existing command, or if its parameters are malformed.
+    pub fn alias(alias: impl ToString, command: impl ToString) -> Result<Option<Text>> {
+        COMMANDS.alias(alias, command)
+    }
+
+    /// Runs a full command, with a caller, [`Flags`], and [`Args`].
+    ///
+    /// When running the command, the ordering of flags does not
+    /// matter, as long as they are placed before the arguments to the
+    /// command.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use duat_core::cmd;
+    /// cmd::run("set-prompt new-prompt");
+    /// ```
+    ///
+    /// In this case we're running a command that will affect the most
+    /// relevant [`CmdLine`]. See [`add_for`] for
+    /// more information.
+    ///
+    /// [`CmdLine`]: crate::widgets::CmdLine
+    pub fn run(call: impl std::fmt::Display) -> Result<Option<Text>> {
+        COMMANDS.run(call)
+    }
+
+    /// Like [`run`], but notifies the result, not returning it
+    pub fn run_notify(call: impl std::fmt::Display) -> Result<Option<Text>> {
+        COMMANDS.run_notify(call)
+    }
+
+    /// Like [`run`], but runs the command on Duat's main loop
+    ///
+    /// Unlike [`run`], this can be safely called from other threads,
+    /// like the ones spawned by [`thread::spawn`], since the command
+    /// will only run alongside the other ones, through
+    /// [`session::dispatch`]. Its result is sent through the returned
+    /// [`Receiver`].
+    ///
+    /// [`thread::spawn`]: crate::thread::spawn
+    /// [`session::dispatch`]: crate::session::dispatch
+    /// [`Receiver`]: std::sync::mpsc::Receiver
+    pub fn run_later(call: impl ToString) -> mpsc::Receiver<Result<Option<Text>>> {
+        let call = call.to_string();
+        crate::session::dispatch(move || run(call))
+    }
+
+    /// Like [`run`], but also returns the command's [payload]
+    ///
+    /// This is meant for programmatic consumers of commands, which
+    /// may want machine readable results, instead of [`Text`]. The
+    /// payload will be [`None`] if the command didn't set one, or if
+    /// it is not of type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use duat_core::cmd;
+    /// # fn test() -> cmd::Result<()> {
+    /// let (_, callers) = cmd::run_with_payload::<Vec<String>>("help-list")?;
+    /// if let Some(callers) = callers {
+    ///     assert!(callers.iter().any(|caller| caller == "alias"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [payload]: set_payload
+    pub fn run_with_payload<T: 'static>(
+        call: impl std::fmt::Display,
+    ) -> Result<(Option<Text>, Option<T>)> {
+        RETURNED.set(None);
+        let text = COMMANDS.run(call)?;
+        let payload = RETURNED.take();
+
+        Ok((text, payload.and_then(|p| p.downcast().ok().map(|p| *p))))
+    }
+
+    /// Sets the structured payload of the running command
+    ///
+    /// This should be called from within a command, and the payload
+    /// can then be retrieved by whoever called it through
+    /// [`run_with_payload`]. If it is called more than once, only the
+    /// last payload will be kept.
+    ///
+    /// ```rust
+    /// # use duat_core::cmd;
+    /// cmd::add(["count-to"], |_, mut args| {
+    ///     let max: usize = args.next_as()?;
+    ///     cmd::set_payload((1..=max).collect::<Vec<usize>>());
+    ///     Ok(None)
+    /// });
+    /// ```
+    pub fn set_payload<T: Send + 'static>(payload: T) {
+        PAYLOAD.set(Some(Box::new(payload)));
+    }
+
+    /// Adds a command to the global list of commands.
+    ///
+    /// This command cannot take any arguments beyond the [`Flags`]
+    /// and [`Args`], so any mutation of state must be done
+    /// through captured variables, usually in the form of
+    /// [`RwData`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use duat_core::{cmd, data::RwData, hooks::{self, OnWindowOpen}, ui::Ui, widgets::status};
+    /// # fn test<U: Ui>() {
+    /// let var = RwData::new(35);
+    ///
+    /// cmd::add(["set-var"], {
+    ///     let var = var.clone();
+    ///     move |_flags, mut args| {
+    ///         let value: usize = args.next_as()?;
+    ///         *var.write() = value;
+    ///
+    ///         Ok(None)
+    ///     }
+    /// });
+    ///
+    /// hooks::add::<OnWindowOpen<U>>(move |builder| {
+    ///     let var = var.clone();
+    ///     builder.push(status!("The value is currently " var));
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// Since `var` is an [`RwData`], it will be updated
+    /// automatically in the [`StatusLine`]
+    ///
+    /// [`StatusLine`]: https://docs.rs/duat/latest/duat/widgets/struct.StatusLine.html
+    /// [`RoData`]: crate::data::RoData
+    /// [`RwData`]: crate::data::RwData
+    pub fn add(
+        callers: impl IntoIterator<Item = impl ToString>,
+        f: impl FnMut(Flags, Args) -> CmdResult + 'static,
+    ) -> Result<()> {
+        COMMANDS.add(callers, f)
+    }
+
+    /// Adds a command that can only be ran in certain conditions
+    ///
+    /// Unlike [`add`], which adds commands that can be ran from
+    /// anywhere, the commands added by this function will only run
+    /// if the [`When`] allows it. If it doesn't, the runner will
+    /// return an error explaining why, instead of acting on the
+    /// wrong widget.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use duat_core::{cmd::{self, When}, ui::Ui};
+    /// # fn test<U: Ui>() {
+    /// cmd::add_when(["clear-file"], When::file::<U>(), |_flags, _args| {
+    ///     // Logic to clear the current file.
+    ///     Ok(None)
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// If this command is called while, for example, a picker is
+    /// focused, the error "Cannot run clear-file while Picker is
+    /// focused." will be returned.
+    pub fn add_when(
+        callers: impl IntoIterator<Item = impl ToString>,
+        when: When,
+        f: impl FnMut(Flags, Args) -> CmdResult + 'static,
+    ) -> Result<()> {
+        COMMANDS.add_when(callers, when, f)
+    }
+
+    /// Adds a command that acts on a [`Range`] of lines
+    ///
+    /// A [`Range`] can be given before the caller, like in `10,20
+    /// sort`, `.,.+5 sort` or `'<,'> sort`, and it will be resolved
+    /// on the current [`File`], then passed to the command through
+    /// [`Args::range`]. If no [`Range`] is given, the `default` is
+    /// used, written in the same syntax:
+    ///
+    /// ```rust
+    /// # use duat_core::cmd;
+    /// # fn test() -> cmd::Result<()> {
+    /// cmd::add_ranged(["count-lines"], "%", |_flags, args| {
+    ///     let range = args.range().unwrap();
+    ///     Ok(Some(format!("{} lines", range.len()).into()))
+    /// })
+    /// # }
+    /// ```
+    ///
+    /// Calling commands that weren't added through this function
+    /// with a [`Range`] will return an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `default` is not a valid [`Range`].
+    ///
+    /// [`Range`]: super::Range
+    /// [`File`]: crate::widgets::File
+    pub fn add_ranged(
+        callers: impl IntoIterator<Item = impl ToString>,
+        default: &str,
+        f: impl FnMut(Flags, Args) -> CmdResult + 'static,
+    ) -> Result<()> {
+        add_ranged_when(callers, default, When::anywhere(), f)
+    }
+
+    /// Adds a ranged command that can only run in certain conditions
+    ///
+    /// This is a combination of [`add_ranged`] and [`add_when`].
+    pub(crate) fn add_ranged_when(
+        callers: impl IntoIterator<Item = impl ToString>,
+        default: &str,
+        when: When,
+        f: impl FnMut(Flags, Args) -> CmdResult + 'static,
+    ) -> Result<()> {
+        let Some(default) = RangeSpec::parse(default) else {
+            panic!("\"{default}\" is not a valid range.");
+        };
+        COMMANDS.add_ranged(callers, default, when, f)
+    }
+
+    /// Adds a command that can mutate a widget of the given type,
+    /// along with its associated [`dyn Area`].
+    ///
+    /// This command will look for the [`Widget`] in the
+    /// following order:
+    ///
+    /// 1. Any widget directly attached to the current file.
+    /// 2. One other instance in the active window.
+    /// 3. Instances in other windows.
+    ///
+    /// Keep in mind that this command will always execute on the
+    /// first widget found.
+    ///
+    /// This search algorithm allows a more versatile configuration of
+    /// widgets, for example, one may have a [`CmdLine`] per
+    /// [`File`], or one singular [`CmdLine`] that acts upon
+    /// all files in the window, and both would respond correctly
+    /// to the `"set-prompt"` command.
+    ///
+    /// # Examples
+    ///
+    /// In this example, we'll create a simple `Timer` widget:
+    ///
+    /// ```rust
+    /// // Required feature for widgets.
+    /// # use std::{
+    /// #     sync::{atomic::{AtomicBool, Ordering}, Arc}, marker::PhantomData,
+    /// #     time::{Duration, Instant}
+    /// # };
+    /// # use duat_core::{
+    /// #     cmd, form::{self, Form}, text::{text, Text, AlignCenter},
+    /// #     ui::{Area, PushSpecs, Ui}, widgets::{Widget, WidgetCfg},
+    /// # };
+    /// struct TimerCfg<U>(PhantomData<U>);
+    ///
+    /// impl<U: Ui> WidgetCfg<U> for TimerCfg<U> {
+    ///     type Widget = Timer;
+    ///
+    ///     fn build(self, _is_file: bool) -> (Timer, impl Fn() -> bool, PushSpecs) {
+    ///         let widget = Timer {
+    ///             text: text!(AlignCenter [Counter] 0 [] "ms"),
     ///             instant: Instant::now(),
     ///             // No need to use an `RwData`, since
     ///             // `RwData::has_changed` is never called.
@@ -571,9 +1701,83 @@ mod global {
         COMMANDS.add_for(callers, f)
     }
 
+    /// Adds a completer to an existing command
+    ///
+    /// The completer receives the arguments before the one being
+    /// completed, and the partial argument itself, returning the
+    /// possible completions for it. Only those that start with the
+    /// partial argument will be shown:
+    ///
+    /// ```rust
+    /// # use duat_core::cmd;
+    /// # fn test() -> cmd::Result<()> {
+    /// cmd::add(["fruit"], |_flags, _args| Ok(None))?;
+    ///
+    /// cmd::add_completer("fruit", |_args, _partial| {
+    ///     vec!["apple".to_string(), "banana".to_string()]
+    /// })
+    /// # }
+    /// ```
+    ///
+    /// If the command already had a completer, it is replaced.
+    pub fn add_completer(
+        caller: &str,
+        f: impl FnMut(&[&str], &str) -> Vec<String> + 'static,
+    ) -> Result<()> {
+        COMMANDS.add_completer(caller, f)
+    }
+
+    /// Adds a completer to an existing command, with access to a
+    /// widget of type `W`
+    ///
+    /// This is the completion counterpart to [`add_for`], letting
+    /// the completer use the state of the widget, for example, to
+    /// suggest values that were set on it previously. The widget is
+    /// found the same way as with [`add_for`]. If there is no such
+    /// widget, there are no completions.
+    pub fn add_completer_for<W: Widget<U>, U: Ui>(
+        caller: &str,
+        f: impl FnMut(&W, &[&str], &str) -> Vec<String> + 'static,
+    ) -> Result<()> {
+        COMMANDS.add_completer_for(caller, f)
+    }
+
+    /// The completions of the last argument of a call
+    ///
+    /// If the call has only one word, the completions are the
+    /// callers that start with it. Otherwise, they come from the
+    /// completer of the command, if it has one. Returns the byte
+    /// where the completed argument starts, alongside the
+    /// completions.
+    pub fn completions(call: &str) -> (usize, Vec<String>) {
+        COMMANDS.completions(call)
+    }
+
     pub(crate) fn caller_exists(caller: &str) -> bool {
         COMMANDS.caller_exists(caller)
     }
+
+    /// Gives a command its own payload, until it is dropped
+    ///
+    /// Commands can run other commands, so the payload of the one
+    /// that was running is put aside, and restored once the new one
+    /// is done. The payload of the new one is then kept for
+    /// [`run_with_payload`].
+    pub(super) struct PayloadFrame(Option<Box<dyn Any + Send>>);
+
+    impl PayloadFrame {
+        /// Puts aside the payload of the running command
+        pub(super) fn enter() -> Self {
+            Self(PAYLOAD.take())
+        }
+    }
+
+    impl Drop for PayloadFrame {
+        fn drop(&mut self) {
+            let payload = PAYLOAD.replace(self.0.take());
+            RETURNED.set(payload);
+        }
+    }
 }
 
 /// A list of commands.
@@ -595,6 +1799,7 @@ impl Commands {
             let inner = RwData::new(InnerCommands {
                 list: Vec::new(),
                 aliases: HashMap::new(),
+                user: Vec::new(),
             });
 
             let alias = {
@@ -610,12 +1815,91 @@ impl Commands {
                         let alias = args.next()?.to_string();
                         let args: String = args.collect();
 
-                        Ok(inner.write().try_alias(alias, args)?)
+                        Ok(inner.write().try_alias(alias, args)?)
+                    }
+                })
+            };
+
+            let command_define = {
+                let inner = inner.clone();
+                Command::new(["command-define"], move |_, mut args| {
+                    let name = args.next()?.to_string();
+                    let body: String = args.collect();
+                    if body.is_empty() {
+                        return Err(err!("No body given for " [*a] name [] "."));
+                    }
+
+                    // Check that all callers in the body exist, since we only
+                    // check the parameters when loading from the cache.
+                    for call in split_calls(&body) {
+                        let Some(caller) = caller_of(call) else {
+                            continue;
+                        };
+
+                        if caller == name {
+                            return Err(err!("The command " [*a] name [] " can't call itself."));
+                        } else if !caller_exists(caller) {
+                            return Err(Error::<()>::CallerNotFound(caller.to_string()).into());
+                        }
+                    }
+
+                    let mut inner_w = inner.write();
+                    inner_w.try_define(UserCommand { name: name.clone(), body }, &inner)?;
+                    inner_w.store_user_commands();
+
+                    ok!("Defined the command " [*a] name [] ".")
+                })
+            };
+
+            let command_undefine = {
+                let inner = inner.clone();
+                Command::new(["command-undefine"], move |_, mut args| {
+                    let name = args.next()?;
+                    args.ended()?;
+
+                    let mut inner = inner.write();
+                    if inner.undefine(name) {
+                        inner.store_user_commands();
+                        ok!("Undefined the command " [*a] name [] ".")
+                    } else {
+                        Err(err!("There is no user command named " [*a] name [] "."))
                     }
                 })
             };
 
-            inner.write().try_add(alias).unwrap();
+            let help_list = {
+                let inner = inner.clone();
+                Command::new(["help-list"], move |_, mut args| {
+                    args.ended()?;
+                    let inner = inner.read();
+
+                    let callers: Vec<String> = inner
+                        .list
+                        .iter()
+                        .flat_map(|cmd| cmd.callers().iter().cloned())
+                        .chain(inner.aliases.keys().cloned())
+                        .collect();
+                    set_payload(callers);
+
+                    Ok(Some(inner.help_list()))
+                })
+            };
+
+            let mut inner_w = inner.write();
+            inner_w.try_add(alias).unwrap();
+            inner_w.try_add(command_define).unwrap();
+            inner_w.try_add(command_undefine).unwrap();
+            inner_w.try_add(help_list).unwrap();
+
+            if let Some(UserCommands(user_cmds)) = load_cache(USER_COMMANDS_PATH) {
+                for user_cmd in user_cmds {
+                    // Errors are ignored here, since a previously valid
+                    // command could now conflict with a newly added one.
+                    let _ = inner_w.try_define(user_cmd, &inner);
+                }
+            }
+
+            drop(inner_w);
             inner
         }))
     }
@@ -627,31 +1911,7 @@ impl Commands {
 
     /// Runs a command from a call
     fn run(&self, call: impl Display) -> Result<Option<Text>> {
-        let call = call.to_string();
-        let mut args = call.split_whitespace();
-        let caller = args.next().ok_or(Error::Empty)?.to_string();
-
-        let (command, call) = self.0.inspect(|inner| {
-            if let Some(command) = inner.aliases.get(&caller) {
-                let (command, call) = command;
-                let mut call = call.clone() + " ";
-                call.extend(args);
-
-                Ok((command.clone(), call))
-            } else {
-                let command = inner
-                    .list
-                    .iter()
-                    .find(|cmd| cmd.callers().contains(&caller))
-                    .ok_or(Error::CallerNotFound(caller))?;
-
-                Ok((command.clone(), call.clone()))
-            }
-        })?;
-
-        let (flags, args) = split_flags_and_args(&call);
-
-        command.try_exec(Flags::new(&flags), args)
+        run_in(&self.0, call.to_string())
     }
 
     /// Runs a command and notifies its result
@@ -675,6 +1935,29 @@ impl Commands {
         self.0.write().try_add(command)
     }
 
+    /// Adds a command that can only run in certain conditions
+    fn add_when(
+        &self,
+        callers: impl IntoIterator<Item = impl ToString>,
+        when: When,
+        f: impl FnMut(Flags, Args) -> CmdResult + 'static,
+    ) -> Result<()> {
+        let command = Command::new(callers, f).with_when(when);
+        self.0.write().try_add(command)
+    }
+
+    /// Adds a command that acts on a [`Range`] of lines
+    fn add_ranged(
+        &self,
+        callers: impl IntoIterator<Item = impl ToString>,
+        default: RangeSpec,
+        when: When,
+        f: impl FnMut(Flags, Args) -> CmdResult + 'static,
+    ) -> Result<()> {
+        let command = Command::new(callers, f).with_range(default).with_when(when);
+        self.0.write().try_add(command)
+    }
+
     /// Adds a command for a widget of type `W`
     fn add_for<W: Widget<U>, U: Ui>(
         &'static self,
@@ -711,6 +1994,100 @@ impl Commands {
         self.0.write().try_add(command)
     }
 
+    /// Adds a completer to the command with the given caller
+    fn add_completer<F>(&self, caller: &str, f: F) -> Result<()>
+    where
+        F: FnMut(&[&str], &str) -> Vec<String> + 'static,
+    {
+        let completer: Completer = RwData::new_unsized::<F>(Arc::new(RwLock::new(f)));
+        let mut inner = self.0.write();
+
+        let command = inner
+            .list
+            .iter_mut()
+            .find(|cmd| cmd.callers().iter().any(|c| c == caller))
+            .ok_or_else(|| Error::CallerNotFound(caller.to_string()))?;
+        command.completer = Some(completer.clone());
+        let callers = command.callers.clone();
+
+        for alias in inner.aliases.values_mut() {
+            if Arc::ptr_eq(&alias.command.callers, &callers) {
+                alias.command.completer = Some(completer.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a completer for a widget of type `W`
+    fn add_completer_for<W: Widget<U>, U: Ui>(
+        &'static self,
+        caller: &str,
+        mut f: impl FnMut(&W, &[&str], &str) -> Vec<String> + 'static,
+    ) -> Result<()> {
+        let windows = context::windows::<U>();
+
+        self.add_completer(caller, move |args, arg| {
+            let windows = windows.read();
+            let Ok((_, node)) = widget_entry::<W, U>(&windows, context::cur_window()) else {
+                return Vec::new();
+            };
+
+            node.inspect_as(|widget: &W| f(widget, args, arg))
+                .unwrap_or_default()
+        })
+    }
+
+    /// The completions of the last argument of a call
+    fn completions(&self, call: &str) -> (usize, Vec<String>) {
+        if let Some((_, rest)) = RangeSpec::split(call) {
+            let (start, completions) = self.completions(rest);
+            return (call.len() - rest.len() + start, completions);
+        }
+
+        let start = call
+            .char_indices()
+            .rev()
+            .find(|(_, char)| char.is_whitespace())
+            .map(|(b, char)| b + char.len_utf8())
+            .unwrap_or(0);
+        let arg = &call[start..];
+
+        let mut words = call[..start].split_whitespace();
+        let Some(caller) = words.next() else {
+            let inner = self.0.read();
+            let callers = inner.list.iter().flat_map(|cmd| cmd.callers().iter());
+            let mut callers: Vec<String> = callers
+                .chain(inner.aliases.keys())
+                .filter(|caller| caller.starts_with(arg))
+                .cloned()
+                .collect();
+            callers.sort_unstable();
+            callers.dedup();
+
+            return (start, callers);
+        };
+
+        let completer = self.0.inspect(|inner| match inner.aliases.get(caller) {
+            Some(alias) => alias.command.completer.clone(),
+            None => inner
+                .list
+                .iter()
+                .find(|cmd| cmd.callers().iter().any(|c| c == caller))
+                .and_then(|cmd| cmd.completer.clone()),
+        });
+
+        let Some(completer) = completer else {
+            return (start, Vec::new());
+        };
+
+        let args: Vec<&str> = words.collect();
+        let mut completions = (completer.write())(&args, arg);
+        completions.retain(|completion| completion.starts_with(arg));
+
+        (start, completions)
+    }
+
     /// Checks if a caller/alias exists or not
     fn caller_exists(&self, caller: &str) -> bool {
         let inner = self.0.read();
@@ -723,6 +2100,53 @@ impl Commands {
     }
 }
 
+/// A condition that must be met in order for a command to run
+///
+/// By default, commands can run from anywhere, but some of them only
+/// make sense when a specific [`Widget`] is focused. Commands added
+/// through [`add_when`] will check this condition before running,
+/// returning a precise error if it is not met.
+#[derive(Clone)]
+pub struct When(Arc<dyn Fn(&str) -> std::result::Result<(), Text> + Send + Sync>);
+
+impl When {
+    /// The command can run no matter what is focused
+    pub fn anywhere() -> Self {
+        Self(Arc::new(|_| Ok(())))
+    }
+
+    /// The command can only run while a [`File`] is focused
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn file<U: Ui>() -> Self {
+        Self::focused::<crate::widgets::File, U>()
+    }
+
+    /// The command can only run while a `W` is focused
+    pub fn focused<W: Widget<U>, U: Ui>() -> Self {
+        Self(Arc::new(|caller| {
+            let node = context::cur_widget::<U>()?.node();
+
+            if node.data_is::<W>() {
+                Ok(())
+            } else {
+                Err(err!(
+                    "Cannot run " [*a] caller [] " while " [*a] { node.widget_name() } []
+                    " is focused."
+                ))
+            }
+        }))
+    }
+
+    /// A custom condition
+    ///
+    /// The function receives the caller used to run the command, and
+    /// should return an [`Err`] explaining why it cannot be ran.
+    pub fn new(f: impl Fn(&str) -> std::result::Result<(), Text> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
 /// The standard error that should be returned when [`run`]ning
 /// commands.
 ///
@@ -735,6 +2159,87 @@ pub type CmdResult = std::result::Result<Option<Text>, Text>;
 struct Command {
     f: RwData<dyn FnMut(Flags, Args) -> CmdResult>,
     callers: Arc<[String]>,
+    when: When,
+    completer: Option<Completer>,
+    range: Option<RangeSpec>,
+}
+
+/// A function that completes the last argument of a [`Command`]
+type Completer = RwData<dyn FnMut(&[&str], &str) -> Vec<String>>;
+
+/// Gets what is needed to resolve [`Range`]s from the current
+/// [`File`]
+///
+/// [`File`]: crate::widgets::File
+static RANGE_CTX: Mutex<Option<fn() -> std::result::Result<RangeCtx, Text>>> = Mutex::new(None);
+
+/// Runs a command from a call, in a list of commands
+///
+/// This is separate from [`Commands::run`] so that [`UserCommand`]s
+/// can call other commands in the list that they were defined in.
+fn run_in(inner: &RwData<InnerCommands>, call: String) -> Result<Option<Text>> {
+    let (range, call) = match RangeSpec::split(&call) {
+        Some((range, call)) => (Some(range), call.to_string()),
+        None => (None, call),
+    };
+    let mut args = Words(&call);
+    let caller = args.next().ok_or(Error::Empty)?.to_string();
+
+    let (command, call) = inner.inspect(|inner| {
+        if let Some(alias) = inner.aliases.get(&caller) {
+            let call = alias.expand(&caller, args).map_err(Error::CommandFailed)?;
+
+            Ok((alias.command.clone(), call))
+        } else {
+            let command = inner
+                .list
+                .iter()
+                .find(|cmd| cmd.callers().contains(&caller))
+                .ok_or_else(|| Error::CallerNotFound(caller.clone()))?;
+
+            Ok((command.clone(), call.clone()))
+        }
+    })?;
+
+    (command.when.0)(&caller).map_err(Error::CommandFailed)?;
+
+    // This has to be checked before executing the command, since its
+    // function is locked while it runs.
+    for caller in command.callers() {
+        RunningGuard::check(caller).map_err(Error::CommandFailed)?;
+    }
+
+    let range = match (range, command.range) {
+        (Some(_), None) => {
+            let msg = err!([*a] caller [] " doesn't take a range.");
+            return Err(Error::CommandFailed(msg));
+        }
+        (range, Some(default)) => {
+            let ctx = resolve_ctx().map_err(Error::CommandFailed)?;
+            let resolved = range
+                .unwrap_or(default)
+                .resolve(&ctx)
+                .map_err(Error::CommandFailed)?;
+            Some((resolved, range.is_some()))
+        }
+        (None, None) => None,
+    };
+
+    let (flags, mut args) = split_flags_and_args(&call);
+    if let Some((range, was_given)) = range {
+        args.set_range(range, was_given);
+    }
+
+    let _frame = PayloadFrame::enter();
+    command.try_exec(Flags::new(&flags), args)
+}
+
+/// What is needed to resolve a [`Range`] right now
+fn resolve_ctx() -> std::result::Result<RangeCtx, Text> {
+    let ctx = RANGE_CTX
+        .lock()
+        .ok_or_else(|| err!("There is no file to take a range from."))?;
+    ctx()
 }
 
 impl Command {
@@ -757,9 +2262,22 @@ impl Command {
         Self {
             f: RwData::new_unsized::<F>(Arc::new(RwLock::new(f))),
             callers,
+            when: When::anywhere(),
+            completer: None,
+            range: None,
         }
     }
 
+    /// Restricts the execution of this command to a [`When`]
+    fn with_when(self, when: When) -> Self {
+        Self { when, ..self }
+    }
+
+    /// Lets this command take a [`Range`], with a default
+    fn with_range(self, default: RangeSpec) -> Self {
+        Self { range: Some(default), ..self }
+    }
+
     /// Executes the inner function if the `caller` matches any of
     /// the callers in [`self`].
     fn try_exec(&self, flags: Flags, args: Args<'_>) -> Result<Option<Text>> {
@@ -774,19 +2292,263 @@ impl Command {
 
 unsafe impl Send for Command {}
 unsafe impl Sync for Command {}
+
+/// An alias to a full call of a [`Command`]
+#[derive(Clone)]
+struct Alias {
+    command: Command,
+    call: String,
+    params: Params,
+}
+
+impl Alias {
+    /// Returns a new [`Alias`], validating its parameters
+    fn new(command: Command, call: String) -> Result<Self> {
+        let params = Params::parse(&call)?;
+        Ok(Self { command, call, params })
+    }
+
+    /// Expands the call of this [`Alias`] with the given `args`
+    fn expand<'a>(
+        &self,
+        alias: &str,
+        args: impl Iterator<Item = &'a str>,
+    ) -> std::result::Result<String, Text> {
+        let args: Vec<&str> = args.collect();
+        self.params.expand(&self.call, alias, &args, true)
+    }
+}
+
+/// The positional parameters of a call
+///
+/// A call may contain positional parameters (`%1`, `%2`, ...), a
+/// placeholder for the remaining arguments (`%@`), and escaped
+/// percent signs (`%%`).
+#[derive(Clone, Copy)]
+struct Params {
+    count: usize,
+    has_rest: bool,
+}
+
+impl Params {
+    /// Parses the parameters of a `call`, checking their validity
+    fn parse(call: &str) -> Result<Self> {
+        let mut used = Vec::new();
+        let mut has_rest = false;
+
+        let invalid = |param: String| Error::InvalidParameter(call.to_string(), param);
+
+        let mut chars = call.chars().peekable();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                continue;
+            }
+
+            match chars.peek() {
+                Some('%') => {
+                    chars.next();
+                }
+                Some('@') => {
+                    chars.next();
+                    has_rest = true;
+                }
+                Some(c) if c.is_ascii_digit() => {
+                    let mut num = 0;
+                    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        chars.next();
+                        num = num * 10 + digit as usize;
+                    }
+
+                    if num == 0 {
+                        return Err(invalid(String::from("%0")));
+                    }
+                    if !used.contains(&num) {
+                        used.push(num);
+                    }
+                }
+                Some(c) => return Err(invalid(format!("%{c}"))),
+                None => return Err(invalid(String::from("%"))),
+            }
+        }
+
+        // Parameters must form a contiguous range from `%1` to `%n`.
+        let count = used.iter().copied().max().unwrap_or(0);
+        if let Some(missing) = (1..=count).find(|n| !used.contains(n)) {
+            return Err(invalid(format!("%{missing}")));
+        }
+
+        Ok(Self { count, has_rest })
+    }
+
+    /// Expands a `call` with these parameters, using `args`
+    ///
+    /// If `append_rest` is `true` and there is no `%@` in the
+    /// `call`, the arguments past `%n` will be appended to it.
+    fn expand(
+        &self,
+        call: &str,
+        name: &str,
+        args: &[&str],
+        append_rest: bool,
+    ) -> std::result::Result<String, Text> {
+        if args.len() < self.count {
+            let plural = if self.count == 1 {
+                " argument"
+            } else {
+                " arguments"
+            };
+            return Err(err!(
+                [*a] name [] " expects at least " [*a] { self.count } []
+                plural ", received " [*a] { args.len() } [] "."
+            ));
+        }
+
+        let rest: Vec<_> = args[self.count..].iter().map(|arg| quote(arg)).collect();
+        let rest = rest.join(" ");
+        let mut expanded = String::with_capacity(call.len());
+
+        let mut chars = call.chars().peekable();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                expanded.push(char);
+                continue;
+            }
+
+            match chars.next() {
+                Some('%') => expanded.push('%'),
+                Some('@') => expanded.push_str(&rest),
+                Some(digit) => {
+                    let mut num = digit.to_digit(10).unwrap() as usize;
+                    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        chars.next();
+                        num = num * 10 + digit as usize;
+                    }
+                    expanded.push_str(&quote(args[num - 1]));
+                }
+                None => unreachable!("Validated when the call was parsed"),
+            }
+        }
+
+        if append_rest && !self.has_rest && !rest.is_empty() {
+            expanded.push(' ');
+            expanded.push_str(&rest);
+        }
+
+        Ok(expanded)
+    }
+}
+
+/// A command defined at runtime, through `command-define`
+///
+/// The body of this command is a `;` separated sequence of calls to
+/// other commands, which may include [parameters].
+///
+/// [parameters]: Params
+#[derive(Clone, Serialize, Deserialize)]
+struct UserCommand {
+    name: String,
+    body: String,
+}
+
+impl UserCommand {
+    /// Turns this [`UserCommand`] into a runnable [`Command`]
+    ///
+    /// The calls in its body are ran in the same list of commands
+    /// that it is a part of.
+    fn to_command(&self, inner: &RwData<InnerCommands>) -> Result<Command> {
+        let calls: Vec<String> = split_calls(&self.body)
+            .into_iter()
+            .map(str::trim)
+            .filter(|call| !call.is_empty())
+            .map(String::from)
+            .collect();
+
+        if calls.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let params = Params::parse(&self.body)?;
+        let name = self.name.clone();
+        let inner = inner.clone();
+
+        Ok(Command::new([&self.name], move |_, mut args| {
+            let _running = RunningGuard::enter(&name)?;
+
+            let mut owned = Vec::new();
+            while let Ok(arg) = args.next() {
+                owned.push(arg.to_string());
+            }
+            let args: Vec<&str> = owned.iter().map(String::as_str).collect();
+
+            let mut ret = None;
+            for (i, call) in calls.iter().enumerate() {
+                let call = params.expand(call, &name, &args, i == calls.len() - 1)?;
+                ret = run_in(&inner, call)?;
+            }
+
+            Ok(ret)
+        }))
+    }
+}
+
+thread_local! {
+    /// The [`UserCommand`]s running on this thread, innermost last
+    static RUNNING_USER_COMMANDS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks a [`UserCommand`] as running, until it is dropped
+///
+/// This stops [`UserCommand`]s that call themselves, be it directly
+/// or through other [`UserCommand`]s, from recursing forever.
+struct RunningGuard;
+
+impl RunningGuard {
+    /// Enters a [`UserCommand`], failing if it is already running
+    fn enter(name: &str) -> std::result::Result<Self, Text> {
+        Self::check(name)?;
+        RUNNING_USER_COMMANDS.with_borrow_mut(|running| running.push(name.to_string()));
+        Ok(Self)
+    }
+
+    /// Fails if a [`UserCommand`] with this name is already running
+    fn check(name: &str) -> std::result::Result<(), Text> {
+        RUNNING_USER_COMMANDS.with_borrow(|running| {
+            match running.iter().position(|running| running == name) {
+                Some(i) => {
+                    let chain = running[i..].join(" -> ");
+                    Err(err!(
+                        "The command " [*a] name [] " calls itself through "
+                        [*a] chain " -> " name [] "."
+                    ))
+                }
+                None => Ok(()),
+            }
+        })
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING_USER_COMMANDS.with_borrow_mut(|running| running.pop());
+    }
+}
+
+/// The list of [`UserCommand`]s, as stored in the cache
+#[derive(Default, Serialize, Deserialize)]
+struct UserCommands(Vec<UserCommand>);
+
 struct InnerCommands {
     list: Vec<Command>,
-    aliases: HashMap<String, (Command, String)>,
+    aliases: HashMap<String, Alias>,
+    user: Vec<UserCommand>,
 }
 
 impl InnerCommands {
     /// Tries to add the given command to the list.
     fn try_add(&mut self, command: Command) -> Result<()> {
-        let mut new_callers = command.callers().iter();
-
         let commands = self.list.iter();
         for caller in commands.flat_map(|cmd| cmd.callers().iter()) {
-            if new_callers.any(|new_caller| new_caller == caller) {
+            if command.callers().contains(caller) {
                 return Err(Error::CallerAlreadyExists(caller.clone()));
             }
         }
@@ -814,9 +2576,9 @@ impl InnerCommands {
         let mut cmds = self.list.iter();
 
         if let Some(command) = cmds.find(|cmd| cmd.callers().contains(&caller)) {
-            let entry = (command.clone(), call.clone());
+            let entry = Alias::new(command.clone(), call.clone())?;
             match self.aliases.insert(alias.clone(), entry) {
-                Some((_, prev_call)) => ok!(
+                Some(Alias { call: prev_call, .. }) => ok!(
                     "Aliased " [*a] alias []
                     " from " [*a] prev_call []
                     " to " [*a] call [] "."
@@ -827,6 +2589,212 @@ impl InnerCommands {
             Err(Error::CallerNotFound(caller))
         }
     }
+
+    /// Tries to define a [`UserCommand`], replacing a previous one
+    /// with the same name
+    fn try_define(&mut self, user_cmd: UserCommand, inner: &RwData<InnerCommands>) -> Result<()> {
+        let command = user_cmd.to_command(inner)?;
+
+        // Validate against every other command before removing the
+        // previous definition, so a failed redefinition keeps it.
+        let is_redefinition = self.user.iter().any(|cmd| cmd.name == user_cmd.name);
+        let others = self
+            .list
+            .iter()
+            .filter(|cmd| !(is_redefinition && cmd.callers().contains(&user_cmd.name)));
+        for caller in others.flat_map(|cmd| cmd.callers().iter()) {
+            if command.callers().contains(caller) {
+                return Err(Error::CallerAlreadyExists(caller.clone()));
+            }
+        }
+
+        if is_redefinition {
+            self.undefine(&user_cmd.name);
+        }
+
+        self.list.push(command);
+        self.user.push(user_cmd);
+
+        Ok(())
+    }
+
+    /// Removes a [`UserCommand`], returning `false` if it didn't
+    /// exist
+    fn undefine(&mut self, name: &str) -> bool {
+        let Some(i) = self.user.iter().position(|cmd| cmd.name == name) else {
+            return false;
+        };
+
+        self.user.remove(i);
+        self.list
+            .retain(|cmd| !cmd.callers().iter().any(|caller| caller == name));
+
+        true
+    }
+
+    /// Stores the [`UserCommand`]s, so they persist between sessions
+    fn store_user_commands(&self) {
+        store_cache(USER_COMMANDS_PATH, UserCommands(self.user.clone()));
+    }
+
+    /// A list of all commands, aliases and user defined commands
+    fn help_list(&self) -> Text {
+        let mut builder = Text::builder();
+        ok!(builder, "Commands:");
+
+        for cmd in self.list.iter() {
+            let Some(first) = cmd.callers().first() else {
+                continue;
+            };
+            if self.user.iter().any(|user| user.name == *first) {
+                continue;
+            }
+
+            ok!(builder, "\n  " [*a] first);
+            for caller in cmd.callers().iter().skip(1) {
+                ok!(builder, [] ", " [*a] caller);
+            }
+        }
+
+        if !self.user.is_empty() {
+            ok!(builder, [] "\nUser commands:");
+            for user in self.user.iter() {
+                ok!(builder, "\n  " [*a] { &user.name } [] " = " { &user.body });
+            }
+        }
+
+        if !self.aliases.is_empty() {
+            let mut aliases: Vec<_> = self.aliases.iter().collect();
+            aliases.sort_unstable_by_key(|(alias, _)| *alias);
+
+            ok!(builder, [] "\nAliases:");
+            for (alias, Alias { call, .. }) in aliases {
+                ok!(builder, "\n  " [*a] alias [] " = " call);
+            }
+        }
+
+        builder.finish()
+    }
 }
 
+/// The path used to cache [`UserCommand`]s
+static USER_COMMANDS_PATH: &str = "user-commands";
+
 pub type Result<T> = crate::Result<T, ()>;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Commands, Error, UserCommand, When, add, caller_of, run_with_payload, set_payload,
+        split_calls,
+    };
+    use crate::text::err;
+
+    #[test]
+    fn command_is_rejected_when_its_condition_is_not_met() {
+        let commands = Commands::new();
+        let never = When::new(|caller| Err(err!("Cannot run " [*a] caller [] " right now.")));
+        commands
+            .add_when(["never-runs"], never, |_, _| panic!("The command ran"))
+            .unwrap();
+
+        match commands.run("never-runs") {
+            Err(Error::CommandFailed(msg)) => {
+                assert_eq!(msg.to_string(), "Cannot run never-runs right now.")
+            }
+            _ => panic!("The command wasn't rejected"),
+        }
+    }
+
+    #[test]
+    fn command_runs_when_its_condition_is_met() {
+        let commands = Commands::new();
+        commands
+            .add_when(["always-runs"], When::anywhere(), |_, _| Ok(None))
+            .unwrap();
+
+        assert!(commands.run("always-runs").is_ok());
+    }
+
+    #[test]
+    fn alias_keeps_quoted_arguments_whole() {
+        let commands = Commands::new();
+        commands
+            .add(["list-args"], |_, mut args| {
+                let mut listed = Vec::new();
+                while let Ok(arg) = args.next() {
+                    listed.push(arg.to_string());
+                }
+                Ok(Some(listed.join("|").into()))
+            })
+            .unwrap();
+
+        commands.run("alias la list-args \"my file\" %1").unwrap();
+        let listed = commands.run("la \"second arg\" rest").unwrap().unwrap();
+
+        assert_eq!(listed.to_string(), "my file|second arg|rest");
+    }
+
+    #[test]
+    fn indirect_recursion_is_an_error() {
+        let commands = Commands::new();
+        commands.add(["do-nothing"], |_, _| Ok(None)).unwrap();
+        let define = |name: &str, body: &str| {
+            let user_cmd = UserCommand {
+                name: name.to_string(),
+                body: body.to_string(),
+            };
+            commands
+                .0
+                .write()
+                .try_define(user_cmd, &commands.0)
+                .unwrap();
+        };
+
+        define("rb", "do-nothing");
+        define("ra", "rb");
+        define("rb", "ra");
+
+        match commands.run("ra") {
+            Err(Error::CommandFailed(msg)) => assert_eq!(
+                msg.to_string(),
+                "The command ra calls itself through ra -> rb -> ra."
+            ),
+            _ => panic!("The recursion wasn't caught"),
+        }
+    }
+
+    #[test]
+    fn nested_commands_keep_their_own_payloads() {
+        add(["payload-inner"], |_, _| {
+            set_payload("inner");
+            Ok(None)
+        })
+        .unwrap();
+        add(["payload-outer"], |_, _| {
+            set_payload("outer");
+            let (_, inner) = run_with_payload::<&str>("payload-inner").unwrap();
+            assert_eq!(inner, Some("inner"));
+            Ok(None)
+        })
+        .unwrap();
+        add(["payload-none"], |_, _| Ok(None)).unwrap();
+
+        let (_, outer) = run_with_payload::<&str>("payload-outer").unwrap();
+        assert_eq!(outer, Some("outer"));
+
+        let (_, none) = run_with_payload::<&str>("payload-none").unwrap();
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn user_command_bodies_are_split_outside_of_quotes() {
+        assert_eq!(split_calls("edit \"a;b\"; write"), [
+            "edit \"a;b\"",
+            " write"
+        ]);
+        assert_eq!(caller_of("10,20 sort"), Some("sort"));
+        assert_eq!(caller_of("'<,'>sort -r"), Some("sort"));
+        assert_eq!(caller_of("\"quoted\" arg"), Some("quoted"));
+    }
+}
@@ -1,22 +1,47 @@
 // THE ENTIRE PARAMETER SYSTEM WILL BE REDONE
 use std::{
-    any::TypeId,
+    any::{Any, TypeId},
+    borrow::Cow,
     iter::Peekable,
-    str::{FromStr, SplitWhitespace},
+    str::FromStr,
 };
 
-use crate::text::{Text, err};
+use crate::text::{Point, Text, err};
 
 #[derive(Clone)]
 pub struct Args<'a> {
     count: usize,
     expected: Option<usize>,
-    args: Peekable<SplitWhitespace<'a>>,
+    args: Peekable<Words<'a>>,
+    range: Option<Range>,
+    range_was_given: bool,
 }
 
 impl<'a> Args<'a> {
+    /// The [`Range`] of lines that the command was called on
+    ///
+    /// This is only [`Some`] for commands added through
+    /// [`cmd::add_ranged`], in which case it is always [`Some`].
+    ///
+    /// [`cmd::add_ranged`]: super::add_ranged
+    pub fn range(&self) -> Option<Range> {
+        self.range
+    }
+
+    /// Whether the [`Range`] was given in the call
+    ///
+    /// If it wasn't, the [`Range`] is the default one of the command.
+    pub fn range_was_given(&self) -> bool {
+        self.range_was_given
+    }
+
+    pub(super) fn set_range(&mut self, range: Range, was_given: bool) {
+        self.range = Some(range);
+        self.range_was_given = was_given;
+    }
+
     #[allow(clippy::should_implement_trait)]
-    pub fn next(&mut self) -> std::result::Result<&str, Text> {
+    pub fn next(&mut self) -> std::result::Result<&'a str, Text> {
         match self.args.next() {
             Some(arg) => {
                 self.count += 1;
@@ -52,7 +77,7 @@ impl<'a> Args<'a> {
         })
     }
 
-    pub fn next_else<T>(&mut self, to_text: T) -> std::result::Result<&str, Text>
+    pub fn next_else<T>(&mut self, to_text: T) -> std::result::Result<&'a str, Text>
     where
         T: Into<Text>,
     {
@@ -81,11 +106,18 @@ impl<'a> Args<'a> {
         }
     }
 
+    /// Collects the remaining arguments
+    ///
+    /// If collecting into a [`String`], the arguments are separated
+    /// by spaces, and the ones that were quoted are quoted again, so
+    /// the [`String`] can be used as part of another call.
     pub fn collect<B: FromIterator<&'a str> + 'static>(&mut self) -> B {
         let args: Vec<&str> = (&mut self.args).collect();
 
         if TypeId::of::<B>() == TypeId::of::<String>() {
-            B::from_iter(args.into_iter().intersperse(" "))
+            let args = args.into_iter().map(quote).intersperse(Cow::Borrowed(" "));
+            let string: Box<dyn Any> = Box::new(args.collect::<String>());
+            *string.downcast::<B>().unwrap()
         } else {
             B::from_iter(args)
         }
@@ -148,7 +180,7 @@ pub fn split_flags_and_args(command: &str) -> (InnerFlags<'_>, Args<'_>) {
     let mut blob = String::new();
     let mut word = Vec::new();
 
-    let mut args = command.split_whitespace().peekable();
+    let mut args = Words(command).peekable();
 
     args.next();
 
@@ -179,5 +211,283 @@ pub fn split_flags_and_args(command: &str) -> (InnerFlags<'_>, Args<'_>) {
         count: 0,
         expected: None,
         args,
+        range: None,
+        range_was_given: false,
     })
 }
+
+/// An [`Iterator`] over the words of a call
+///
+/// Words are separated by whitespace, unless they are surrounded by
+/// double quotes, like in `edit "my file"`, in which case the quotes
+/// are removed. There is no escaping, so quoted words can't contain
+/// double quotes themselves.
+#[derive(Clone)]
+pub(super) struct Words<'a>(pub(super) &'a str);
+
+impl<'a> Iterator for Words<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let str = self.0.trim_start();
+        if let Some(quoted) = str.strip_prefix('"') {
+            let (word, rest) = quoted.split_once('"').unwrap_or((quoted, ""));
+            self.0 = rest;
+            Some(word)
+        } else if str.is_empty() {
+            self.0 = str;
+            None
+        } else {
+            let end = str.find(char::is_whitespace).unwrap_or(str.len());
+            self.0 = &str[end..];
+            Some(&str[..end])
+        }
+    }
+}
+
+/// Splits a sequence of calls, separated by `;`
+///
+/// Like with [`Words`], a `;` surrounded by double quotes doesn't
+/// separate anything, so `edit "a;b"; write` is two calls.
+pub(super) fn split_calls(str: &str) -> Vec<&str> {
+    let mut calls = Vec::new();
+    let mut start = 0;
+    let mut is_quoted = false;
+
+    for (b, char) in str.char_indices() {
+        match char {
+            '"' => is_quoted = !is_quoted,
+            ';' if !is_quoted => {
+                calls.push(&str[start..b]);
+                start = b + 1;
+            }
+            _ => {}
+        }
+    }
+    calls.push(&str[start..]);
+
+    calls
+}
+
+/// The caller of a call, skipping its [`RangeSpec`], if there is one
+pub(super) fn caller_of(call: &str) -> Option<&str> {
+    let call = RangeSpec::split(call).map_or(call, |(_, rest)| rest);
+    Words(call).next()
+}
+
+/// Quotes a word, if that is needed for [`Words`] to return it whole
+pub(super) fn quote(word: &str) -> Cow<'_, str> {
+    if word.is_empty() || word.starts_with('"') || word.contains(char::is_whitespace) {
+        Cow::Owned(format!("\"{word}\""))
+    } else {
+        Cow::Borrowed(word)
+    }
+}
+
+/// A range of lines that a command acts on
+///
+/// Ranges are given before the caller of a command, like in
+/// `10,20 sort` or `'<,'> sort`, and are resolved on the current
+/// [`File`] before the command runs. The following addresses are
+/// recognized:
+///
+/// - `{n}`: The `n`th line, starting from 1;
+/// - `.`: The line of the main cursor;
+/// - `$`: The last line;
+/// - `'<` and `'>`: The first and last lines of the main cursor's
+///   selection;
+///
+/// Each address can be followed by offsets, like `.+3` or `$-10`.
+/// Two addresses can be separated by a `,`, and `%` is the same as
+/// `1,$`.
+///
+/// [`File`]: crate::widgets::File
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    start: u32,
+    end: u32,
+}
+
+impl Range {
+    /// Returns a new [`Range`], from the `start` line to the `end`
+    /// line, inclusive, starting from 0
+    pub fn new(start: u32, end: u32) -> Self {
+        Self {
+            start: start.min(end),
+            end: start.max(end),
+        }
+    }
+
+    /// The first line of the [`Range`], starting from 0
+    pub fn start(&self) -> u32 {
+        self.start
+    }
+
+    /// The last line of the [`Range`], starting from 0
+    pub fn end(&self) -> u32 {
+        self.end
+    }
+
+    /// The lines in the [`Range`]
+    pub fn lines(&self) -> std::ops::RangeInclusive<u32> {
+        self.start..=self.end
+    }
+
+    /// The number of lines in the [`Range`]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u32 {
+        self.end - self.start + 1
+    }
+
+    /// The [`Point`]s at the start of the first line and at the end
+    /// of the last line, including its `\n`
+    pub fn points(&self, text: &Text) -> (Point, Point) {
+        let last = text.len().line();
+        let start = text.point_at_line(self.start.min(last));
+        let end = match self.end < last {
+            true => text.point_at_line(self.end + 1),
+            false => text.len(),
+        };
+        (start, end)
+    }
+}
+
+/// What is needed to resolve a [`RangeSpec`]
+pub(crate) struct RangeCtx {
+    /// The line of the main cursor
+    pub cur: u32,
+    /// The first and last lines of the main cursor's selection
+    pub sel: (u32, u32),
+    /// The last line of the [`File`]
+    ///
+    /// [`File`]: crate::widgets::File
+    pub last: u32,
+}
+
+/// An unresolved [`Range`], as it was written in a call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RangeSpec {
+    /// The whole file, written as `%`
+    Whole,
+    /// One or two addresses
+    Addrs(Addr, Option<Addr>),
+}
+
+impl RangeSpec {
+    /// Splits a [`RangeSpec`] from the start of a call
+    ///
+    /// Returns [`None`] if the call doesn't start with one. The
+    /// caller may come right after the range, like in `10,20sort`.
+    pub(crate) fn split(call: &str) -> Option<(Self, &str)> {
+        let call = call.trim_start();
+        if let Some(rest) = call.strip_prefix('%') {
+            return Some((Self::Whole, rest));
+        }
+
+        let (first, rest) = Addr::split(call)?;
+        match rest.strip_prefix(',') {
+            Some(rest) => {
+                let (second, rest) = Addr::split(rest)?;
+                Some((Self::Addrs(first, Some(second)), rest))
+            }
+            None => Some((Self::Addrs(first, None), rest)),
+        }
+    }
+
+    /// Parses a [`RangeSpec`] that must take up the whole `str`
+    pub(crate) fn parse(str: &str) -> Option<Self> {
+        Self::split(str).and_then(|(spec, rest)| rest.trim().is_empty().then_some(spec))
+    }
+
+    /// Resolves this [`RangeSpec`] into a [`Range`]
+    pub(crate) fn resolve(&self, ctx: &RangeCtx) -> Result<Range, Text> {
+        match self {
+            RangeSpec::Whole => Ok(Range::new(0, ctx.last)),
+            RangeSpec::Addrs(first, second) => {
+                let start = first.resolve(ctx)?;
+                let end = second.map(|addr| addr.resolve(ctx)).transpose()?;
+                Ok(Range::new(start, end.unwrap_or(start)))
+            }
+        }
+    }
+}
+
+/// A single line address, with an offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Addr {
+    base: AddrBase,
+    offset: i64,
+}
+
+impl Addr {
+    /// Splits an [`Addr`] from the start of a `str`
+    fn split(str: &str) -> Option<(Self, &str)> {
+        let (base, mut rest) = if let Some(rest) = str.strip_prefix('.') {
+            (AddrBase::Cur, rest)
+        } else if let Some(rest) = str.strip_prefix('$') {
+            (AddrBase::Last, rest)
+        } else if let Some(rest) = str.strip_prefix("'<") {
+            (AddrBase::SelStart, rest)
+        } else if let Some(rest) = str.strip_prefix("'>") {
+            (AddrBase::SelEnd, rest)
+        } else {
+            let (num, rest) = split_number(str)?;
+            (AddrBase::Line(num), rest)
+        };
+
+        let mut offset = 0;
+        loop {
+            let (sign, after) = match rest.chars().next() {
+                Some('+') => (1, &rest[1..]),
+                Some('-') => (-1, &rest[1..]),
+                _ => break,
+            };
+            // A lone sign counts as one line, like in ".+".
+            let (num, after) = split_number(after).unwrap_or((1, after));
+            offset += sign * num as i64;
+            rest = after;
+        }
+
+        Some((Self { base, offset }, rest))
+    }
+
+    /// Resolves this [`Addr`] into a line, starting from 0
+    fn resolve(&self, ctx: &RangeCtx) -> Result<u32, Text> {
+        let line = match self.base {
+            AddrBase::Line(0) => return Err(err!("Lines start at " [*a] 1 [] ".")),
+            AddrBase::Line(line) => line as i64 - 1,
+            AddrBase::Cur => ctx.cur as i64,
+            AddrBase::Last => ctx.last as i64,
+            AddrBase::SelStart => ctx.sel.0 as i64,
+            AddrBase::SelEnd => ctx.sel.1 as i64,
+        } + self.offset;
+
+        if (0..=ctx.last as i64).contains(&line) {
+            Ok(line as u32)
+        } else {
+            Err(err!(
+                "Line " [*a] { line + 1 } [] " is out of bounds, the last line is "
+                [*a] { ctx.last + 1 } [] "."
+            ))
+        }
+    }
+}
+
+/// What an [`Addr`] is relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddrBase {
+    Line(u32),
+    Cur,
+    Last,
+    SelStart,
+    SelEnd,
+}
+
+/// Splits a number from the start of a `str`
+fn split_number(str: &str) -> Option<(u32, &str)> {
+    let len = str
+        .find(|char: char| !char.is_ascii_digit())
+        .unwrap_or(str.len());
+    let num = str[..len].parse().ok()?;
+    Some((num, &str[len..]))
+}
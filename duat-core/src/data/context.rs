@@ -11,7 +11,7 @@ use super::{RoData, RwData, private::InnerData};
 use crate::{
     mode::{self, Cursors},
     ui::{Area, Ui},
-    widgets::{File, Node, Widget},
+    widgets::{File, Node, RelatedWidgets, Vars, View, Widget},
 };
 
 mod global {
@@ -21,6 +21,7 @@ mod global {
             LazyLock, OnceLock,
             atomic::{AtomicUsize, Ordering},
         },
+        time::{Duration, Instant},
     };
 
     use super::{CurFile, CurWidget, FileParts, FileReader};
@@ -31,7 +32,7 @@ mod global {
         mode::Regular,
         text::Text,
         ui::{Ui, Window},
-        widgets::{File, Node},
+        widgets::{File, Node, View},
     };
 
     static MODE_NAME: LazyLock<RwData<&str>> =
@@ -41,11 +42,20 @@ mod global {
     static CUR_WINDOW: AtomicUsize = AtomicUsize::new(0);
     static WINDOWS: OnceLock<&(dyn Any + Send + Sync)> = OnceLock::new();
     static NOTIFICATIONS: LazyLock<RwData<Text>> = LazyLock::new(RwData::default);
+    static ECHO: LazyLock<RwData<Option<(Text, Instant)>>> = LazyLock::new(RwData::default);
+    static KEY_HINTS: LazyLock<RwData<Vec<(&str, &str)>>> = LazyLock::new(RwData::default);
 
     pub fn mode_name() -> &'static RwData<&'static str> {
         &MODE_NAME
     }
 
+    /// The key hints published by the current [`Mode`]
+    ///
+    /// [`Mode`]: crate::mode::Mode
+    pub fn key_hints() -> &'static RwData<Vec<(&'static str, &'static str)>> {
+        &KEY_HINTS
+    }
+
     pub fn fixed_reader<U: Ui>() -> Result<FileReader<U>, File> {
         Ok(cur_file()?.fixed_reader())
     }
@@ -66,6 +76,19 @@ mod global {
         Ok(cur_widget)
     }
 
+    /// The [`View`] of the currently focused [`Area`]
+    ///
+    /// This gives you access to the [`Cursors`] and [`PrintInfo`] of
+    /// the [`Widget`] in the exact [`Area`] that it is being shown.
+    ///
+    /// [`Area`]: crate::ui::Area
+    /// [`Cursors`]: crate::mode::Cursors
+    /// [`PrintInfo`]: crate::ui::Area::PrintInfo
+    /// [`Widget`]: crate::widgets::Widget
+    pub fn cur_view<U: Ui>() -> Result<View<U>, File> {
+        Ok(cur_widget::<U>()?.view())
+    }
+
     pub fn cur_window() -> usize {
         CUR_WINDOW.load(Ordering::Relaxed)
     }
@@ -78,6 +101,39 @@ mod global {
         *NOTIFICATIONS.write() = msg
     }
 
+    /// Shows a transient message in the echo area
+    ///
+    /// Unlike [`notify`], which is meant for the results of commands,
+    /// this is meant for short lived feedback, like pending keys, the
+    /// current register, or whether a macro is being recorded. The
+    /// message is cleared automatically after 3 seconds.
+    ///
+    /// By default, the echo area shares space with the [`CmdLine`],
+    /// and is shown whenever it is not focused.
+    ///
+    /// [`CmdLine`]: crate::widgets::CmdLine
+    pub fn echo(msg: Text) {
+        echo_for(msg, Duration::from_secs(3))
+    }
+
+    /// Shows a transient message in the echo area for a duration
+    ///
+    /// If another message is echoed before this one expires, it will
+    /// be replaced.
+    pub fn echo_for(msg: Text, duration: Duration) {
+        *ECHO.write() = Some((msg, Instant::now() + duration))
+    }
+
+    /// Clears the echo area, even if the message hasn't expired
+    pub fn clear_echo() {
+        *ECHO.write() = None
+    }
+
+    /// The current message in the echo area, alongside its expiry
+    pub(crate) fn echoed() -> &'static RwData<Option<(Text, Instant)>> {
+        &ECHO
+    }
+
     pub fn setup<U: Ui>(
         cur_file: &'static CurFile<U>,
         cur_widget: &'static CurWidget<U>,
@@ -126,12 +182,12 @@ impl<U: Ui> CurFile<U> {
 
     pub fn fixed_reader(&self) -> FileReader<U> {
         let data = self.0.raw_read();
-        let (file, area, cursors, related) = data.clone().unwrap();
+        let (file, view, related) = data.clone().unwrap();
         let file_state = AtomicUsize::new(file.cur_state().load(Ordering::Relaxed));
-        let cursors_state = AtomicUsize::new(cursors.cur_state.load(Ordering::Relaxed));
+        let cursors_state = AtomicUsize::new(view.cursors.cur_state.load(Ordering::Relaxed));
 
         FileReader {
-            data: RoData::new(Some((file, area, cursors, related))),
+            data: RoData::new(Some((file, view, related))),
             file_state,
             cursors_state,
         }
@@ -139,7 +195,7 @@ impl<U: Ui> CurFile<U> {
 
     pub fn dyn_reader(&self) -> FileReader<U> {
         let data = self.0.raw_read();
-        let (file, .., cursors, _) = data.clone().unwrap();
+        let (file, View { cursors, .. }, _) = data.clone().unwrap();
 
         FileReader {
             data: RoData::from(&*self.0),
@@ -150,11 +206,16 @@ impl<U: Ui> CurFile<U> {
 
     pub fn inspect<R>(&self, f: impl FnOnce(&File, &U::Area, &Cursors) -> R) -> R {
         let data = self.0.raw_read();
-        let (file, area, cursors, _) = data.as_ref().unwrap();
+        let (file, View { area, cursors, .. }, _) = data.as_ref().unwrap();
 
         cursors.inspect(|c| f(&file.read(), area, c))
     }
 
+    /// The [`View`] of the active [`File`]
+    pub fn view(&self) -> View<U> {
+        self.0.raw_read().as_ref().unwrap().1.clone()
+    }
+
     /// The name of the active [`File`]'s file.
     pub fn name(&self) -> String {
         self.0.raw_read().as_ref().unwrap().0.read().name()
@@ -165,6 +226,18 @@ impl<U: Ui> CurFile<U> {
         self.0.raw_read().as_ref().unwrap().0.read().path()
     }
 
+    /// Mutates the [`Vars`] of the active [`File`]
+    pub fn mutate_vars<R>(&self, f: impl FnOnce(&mut Vars) -> R) -> R {
+        self.mutate_file(|file| f(file.vars_mut()))
+    }
+
+    /// Mutates the active [`File`], without touching its [`Text`]
+    ///
+    /// [`Text`]: crate::text::Text
+    pub(crate) fn mutate_file<R>(&self, f: impl FnOnce(&mut File) -> R) -> R {
+        f(&mut self.0.raw_read().as_ref().unwrap().0.write())
+    }
+
     // NOTE: Doesn't return result, since it is expected that widgets can
     // only be created after the file exists.
     pub fn file_ptr_eq(&self, other: &Node<U>) -> bool {
@@ -176,7 +249,7 @@ impl<U: Ui> CurFile<U> {
         f: impl FnOnce(&RwData<File>, &U::Area, &RwData<Cursors>) -> R,
     ) -> R {
         let data = self.0.raw_read();
-        let (file, area, cursors, _) = data.as_ref().unwrap();
+        let (file, View { area, cursors, .. }, _) = data.as_ref().unwrap();
 
         cursors.inspect(|c| {
             let mut file = file.write();
@@ -233,29 +306,29 @@ impl<U: Ui> CurFile<U> {
         };
 
         let data = self.0.raw_read();
-        let (file, area, cursors, rel) = data.as_ref().unwrap();
+        let (file, View { area, cursors, .. }, rel) = data.as_ref().unwrap();
 
-        let rel = rel.read();
         if file.data_is::<W>() {
             let mut cursors = cursors.write();
             file.mutate_as(|w| f(w, area, &mut cursors))
         } else {
-            rel.iter()
-                .find(|node| node.data_is::<W>())
-                .and_then(|node| {
-                    let (widget, area, cursors) = node.as_active();
-                    let mut cursors = cursors.write();
-                    widget.mutate_as(|w| f(w, area, &mut cursors))
-                })
+            let node = rel.node_of::<W>()?;
+            let (widget, area, cursors) = node.as_active();
+            let mut cursors = cursors.write();
+            widget.mutate_as(|w| f(w, area, &mut cursors))
         }
     }
 
     pub(crate) fn get_related_widget<W: Widget<U>>(&self) -> Option<Node<U>> {
         let data = self.0.write();
         let (.., related) = data.as_ref().unwrap();
-        let related = related.read();
 
-        related.iter().find(|node| node.data_is::<W>()).cloned()
+        related.node_of::<W>()
+    }
+
+    /// The [`RelatedWidgets`] of the active [`File`]
+    pub fn related_widgets(&self) -> RelatedWidgets<U> {
+        self.0.raw_read().as_ref().unwrap().2.clone()
     }
 }
 
@@ -274,7 +347,7 @@ pub struct FileReader<U: Ui> {
 impl<U: Ui> FileReader<U> {
     pub fn inspect<R>(&self, f: impl FnOnce(&File, &U::Area, &Cursors) -> R) -> R {
         let data = self.data.read();
-        let (file, area, cursors, _) = data.as_ref().unwrap();
+        let (file, View { area, cursors, .. }, _) = data.as_ref().unwrap();
 
         self.file_state
             .store(file.cur_state().load(Ordering::Acquire), Ordering::Release);
@@ -290,34 +363,28 @@ impl<U: Ui> FileReader<U> {
 
     pub fn inspect_related<T: 'static, R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
         let data = self.data.read();
-        let (file, _, cursors, related) = data.as_ref().unwrap();
+        let (file, View { cursors, .. }, related) = data.as_ref().unwrap();
 
         if file.data_is::<T>() {
             file.inspect_as(f)
         } else if cursors.data_is::<T>() {
             cursors.inspect_as(f)
         } else {
-            let related = related.read();
-            related
-                .iter()
-                .find(|node| node.data_is::<T>())
-                .and_then(|w| w.inspect_as(f))
+            related.node_of::<T>().and_then(|node| node.inspect_as(f))
         }
     }
 
     pub fn inspect_file_and<T: 'static, R>(&self, f: impl FnOnce(&File, &T) -> R) -> Option<R> {
         let data = self.data.read();
-        let (file, _, cursors, related) = data.as_ref().unwrap();
+        let (file, View { cursors, .. }, related) = data.as_ref().unwrap();
 
         if cursors.data_is::<T>() {
             cursors.inspect_as(|c| f(&file.read(), c))
         } else if cursors.data_is::<T>() {
             cursors.inspect_as::<T, R>(|c| f(&file.read(), c))
         } else {
-            let related = related.read();
             related
-                .iter()
-                .find(|node| node.data_is::<T>())
+                .node_of::<T>()
                 .and_then(|node| node.inspect_as(|widget| f(&file.read(), widget)))
         }
     }
@@ -337,7 +404,8 @@ impl<U: Ui> FileReader<U> {
         // to the new file.
         let mut has_changed = self.data.has_changed();
         let data = self.data.read();
-        let (file, .., cursors, _) = data.as_ref().unwrap();
+        let (file, view, _) = data.as_ref().unwrap();
+        let cursors = view.cursors();
 
         has_changed |= {
             let state = file.cur_state().load(Ordering::Acquire);
@@ -354,7 +422,8 @@ impl<U: Ui> FileReader<U> {
 
 impl<U: Ui> Clone for FileReader<U> {
     fn clone(&self) -> Self {
-        let (file, .., cursors, _) = self.data.read().clone().unwrap();
+        let (file, view, _) = self.data.read().clone().unwrap();
+        let cursors = view.cursors();
 
         Self {
             data: self.data.clone(),
@@ -474,6 +543,11 @@ impl<U: Ui> CurWidget<U> {
         ret
     }
 
+    /// The [`View`] of the active [`Widget`]
+    pub fn view(&self) -> View<U> {
+        self.0.raw_read().as_ref().unwrap().view().clone()
+    }
+
     pub(crate) fn node(&self) -> Node<U> {
         self.0.read().as_ref().unwrap().clone()
     }
@@ -485,9 +559,4 @@ impl<U: Ui> Default for CurWidget<U> {
     }
 }
 
-pub(crate) type FileParts<U> = (
-    RwData<File>,
-    <U as Ui>::Area,
-    RwData<Cursors>,
-    RwData<Vec<Node<U>>>,
-);
+pub(crate) type FileParts<U> = (RwData<File>, View<U>, RelatedWidgets<U>);
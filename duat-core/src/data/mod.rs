@@ -21,16 +21,20 @@
 //! [`File`]: crate::widgets::File
 //! [`Text`]: crate::text::Text
 //! [`StatusLine`]: crate::widgets::StatusLine
+use std::time::Duration;
+
 pub use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 pub use self::{
     ro::RoData,
     rw::{ReadWriteGuard, RwData},
+    throttled::Throttled,
 };
 
 pub mod context;
 mod ro;
 mod rw;
+mod throttled;
 
 /// Private trait for the [`RwData`] and [`RoData`] structs.
 pub trait Data<T>: private::InnerData<T>
@@ -45,6 +49,7 @@ where
 pub struct DataMap<I: ?Sized + Send + Sync + 'static, O> {
     data: RoData<I>,
     f: Box<dyn FnMut() -> O + Send + Sync>,
+    throttle: Option<Duration>,
 }
 
 impl<I: ?Sized + Send + Sync + 'static, O> DataMap<I, O> {
@@ -54,7 +59,13 @@ impl<I: ?Sized + Send + Sync + 'static, O> DataMap<I, O> {
         Box<dyn FnMut() -> O + Send + Sync>,
         Box<dyn Fn() -> bool + Send + Sync>,
     ) {
-        let checker = Box::new(move || self.data.has_changed());
+        let checker: Box<dyn Fn() -> bool + Send + Sync> = match self.throttle {
+            Some(interval) => {
+                let throttled = self.data.throttled(interval);
+                Box::new(move || throttled.has_changed())
+            }
+            None => Box::new(move || self.data.has_changed()),
+        };
         (self.f, checker)
     }
 }
@@ -64,15 +75,27 @@ impl<I: ?Sized + Send + Sync + 'static, O: 'static> DataMap<I, O> {
         DataMap {
             data: self.data,
             f: Box::new(move || f((self.f)())),
+            throttle: self.throttle,
         }
     }
+
+    /// Limits updates to at most once per interval
+    ///
+    /// See [`Throttled`] for more information.
+    pub fn throttled(self, interval: Duration) -> Self {
+        Self { throttle: Some(interval), ..self }
+    }
 }
 
 impl<I: ?Sized + Send + Sync + 'static> RwData<I> {
     pub fn map<O>(&self, mut f: impl FnMut(&I) -> O + Send + Sync + 'static) -> DataMap<I, O> {
         let data = RoData::from(self);
         let f = move || f(&*data.read());
-        DataMap { data: RoData::from(self), f: Box::new(f) }
+        DataMap {
+            data: RoData::from(self),
+            f: Box::new(f),
+            throttle: None,
+        }
     }
 }
 
@@ -80,7 +103,11 @@ impl<I: ?Sized + Send + Sync + 'static> RoData<I> {
     pub fn map<O>(&self, mut f: impl FnMut(&I) -> O + Send + Sync + 'static) -> DataMap<I, O> {
         let data = self.clone();
         let f = move || f(&*data.read());
-        DataMap { data: self.clone(), f: Box::new(f) }
+        DataMap {
+            data: self.clone(),
+            f: Box::new(f),
+            throttle: None,
+        }
     }
 }
 
@@ -0,0 +1,104 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use super::{Data, RoData, RwLockReadGuard};
+
+/// A [`RoData`] whose [`has_changed`] fires at most once per interval
+///
+/// This is useful for data that changes very frequently, like a
+/// spinner or the progress of a job, where checking every change
+/// would cause a storm of redraws.
+///
+/// Changes are never lost: if the data changed while the interval
+/// hadn't passed, [`has_changed`] will return `true` once it does,
+/// so the last change is always shown.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use duat_core::data::RwData;
+/// let progress = RwData::new(0);
+/// let throttled = progress.throttled(Duration::from_millis(100));
+///
+/// *progress.write() += 1;
+/// assert!(throttled.has_changed());
+///
+/// *progress.write() += 1;
+/// // The interval hasn't passed yet.
+/// assert!(!throttled.has_changed());
+///
+/// std::thread::sleep(Duration::from_millis(100));
+/// // But the change is not lost.
+/// assert!(throttled.has_changed());
+/// ```
+///
+/// [`has_changed`]: Throttled::has_changed
+pub struct Throttled<T: ?Sized + 'static> {
+    data: RoData<T>,
+    interval: Duration,
+    last_fire: Mutex<Option<Instant>>,
+    pending: AtomicBool,
+}
+
+impl<T: ?Sized + 'static> Throttled<T> {
+    /// Returns a new [`Throttled`] from a [`RoData`]
+    pub fn new(data: RoData<T>, interval: Duration) -> Self {
+        Self {
+            data,
+            interval,
+            last_fire: Mutex::new(None),
+            pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether the data has changed, at most once per interval
+    pub fn has_changed(&self) -> bool {
+        if self.data.has_changed() {
+            self.pending.store(true, Ordering::Release);
+        }
+
+        if !self.pending.load(Ordering::Acquire) {
+            return false;
+        }
+
+        let mut last_fire = self.last_fire.lock();
+        if last_fire.is_none_or(|last| last.elapsed() >= self.interval) {
+            *last_fire = Some(Instant::now());
+            self.pending.store(false, Ordering::Release);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Blocking reference to the information
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.data.read()
+    }
+
+    /// Blocking inspection of the inner data
+    pub fn inspect<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.data.inspect(f)
+    }
+}
+
+impl<T: ?Sized + 'static> RoData<T> {
+    /// Returns a [`Throttled`] version of this [`RoData`]
+    ///
+    /// See [`Throttled`] for more information.
+    pub fn throttled(&self, interval: Duration) -> Throttled<T> {
+        Throttled::new(self.clone(), interval)
+    }
+}
+
+impl<T: ?Sized + 'static> super::RwData<T> {
+    /// Returns a [`Throttled`] reader of this [`RwData`]
+    ///
+    /// See [`Throttled`] for more information.
+    pub fn throttled(&self, interval: Duration) -> Throttled<T> {
+        Throttled::new(self.to_ro(), interval)
+    }
+}
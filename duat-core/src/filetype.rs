@@ -0,0 +1,359 @@
+//! Detection of the type of [`File`]s, and configuration per type
+//!
+//! When a [`File`] is opened, its filetype is detected from, in order
+//! of precedence, a modeline in its first or last lines, its
+//! extension, or the interpreter in its shebang:
+//!
+//! ```text
+//! # vim: set ft=yaml:
+//! # -*- mode: yaml -*-
+//! #!/usr/bin/env python3
+//! ```
+//!
+//! After that, the [`OnFileTypeSet`] hook is triggered, and anything
+//! attached to the filetype is applied. This lets you configure
+//! [`File`]s by their type, without having to match on their paths:
+//!
+//! ```rust
+//! # use duat_core::{filetype, form::Form};
+//! filetype::set_print_cfg("yaml", |cfg| cfg.with_tabs_size(2));
+//! filetype::set_print_cfg("markdown", |cfg| cfg.word_wrapped());
+//! filetype::set_form("markdown", "markup.heading", Form::blue().bold());
+//! ```
+//!
+//! [`PrintCfg`]s are applied when the [`File`] is opened, [`Form`]s
+//! are set while a [`File`] of that type is focused, and remaps made
+//! with [`filetype::map`] only happen on [`File`]s of that type. The
+//! filetype can also be changed with the `filetype` command:
+//!
+//! ```text
+//! :filetype markdown
+//! ```
+//!
+//! [`File`]: crate::widgets::File
+//! [`OnFileTypeSet`]: crate::hooks::OnFileTypeSet
+//! [`filetype::map`]: map
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, LazyLock},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    cfg::PrintCfg,
+    context,
+    form::{self, Form},
+    hooks::{self, FocusedOn, OnFileTypeSet},
+    mode::{self, AsGives, Mode},
+    text::{Point, Text},
+    ui::Ui,
+    widgets::{File, Node},
+};
+
+/// How many lines at the start and end of a [`Text`] are searched for
+/// modelines
+const MODELINE_LINES: u32 = 5;
+
+static EXTENSIONS: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(Mutex::default);
+static PRINT_CFGS: LazyLock<Mutex<Vec<(String, PrintCfgFn)>>> = LazyLock::new(Mutex::default);
+static FORMS: LazyLock<Mutex<Vec<(String, &'static str, Form)>>> = LazyLock::new(Mutex::default);
+/// The [`Form`]s replaced by the ones of the focused filetype
+static REPLACED: LazyLock<Mutex<Vec<(&'static str, Form)>>> = LazyLock::new(Mutex::default);
+
+/// Detects the filetype of a [`File`], given its path and [`Text`]
+///
+/// [`File`]: crate::widgets::File
+pub fn detect(path: &str, text: &Text) -> Option<String> {
+    if let Some(filetype) = from_modeline(text) {
+        return Some(filetype);
+    }
+
+    let path = Path::new(path);
+    let ext = path.extension().and_then(|ext| ext.to_str());
+    let name = path.file_name().and_then(|name| name.to_str());
+
+    let from_ext = ext.and_then(|ext| {
+        let extensions = EXTENSIONS.lock();
+        let filetype = extensions.get(ext).map(String::as_str);
+        filetype
+            .or_else(|| find(DEFAULT_EXTENSIONS, ext))
+            .map(str::to_string)
+    });
+    let from_name = || {
+        name.and_then(|name| find(FILE_NAMES, name))
+            .map(str::to_string)
+    };
+
+    from_ext.or_else(from_name).or_else(|| from_shebang(text))
+}
+
+/// Makes files with an extension have a filetype
+///
+/// The extension is given without the leading dot, e.g. `"rs"`. This
+/// takes precedence over Duat's own list of extensions.
+pub fn add_extension(ext: impl ToString, filetype: impl ToString) {
+    EXTENSIONS
+        .lock()
+        .insert(ext.to_string(), filetype.to_string());
+}
+
+/// Changes the [`PrintCfg`] of [`File`]s of a filetype
+///
+/// The function receives the [`PrintCfg`] that the [`File`] would
+/// have, returning the one that it will have. Functions added later
+/// are applied after earlier ones.
+///
+/// [`File`]: crate::widgets::File
+pub fn set_print_cfg(
+    filetype: impl ToString,
+    f: impl Fn(PrintCfg) -> PrintCfg + Send + Sync + 'static,
+) {
+    PRINT_CFGS.lock().push((filetype.to_string(), Arc::new(f)));
+}
+
+/// Sets a [`Form`] while a [`File`] of a filetype is focused
+///
+/// When focus moves to a [`File`] of another filetype, the [`Form`]
+/// goes back to what it was before.
+///
+/// [`File`]: crate::widgets::File
+pub fn set_form(filetype: impl ToString, name: impl ToString, form: impl Into<Form>) {
+    let name: &'static str = name.to_string().leak();
+    FORMS.lock().push((filetype.to_string(), name, form.into()));
+}
+
+/// Maps a sequence of keys to another, on [`File`]s of a filetype
+///
+/// This works just like [`mode::map`], but the remap only happens
+/// while the active [`File`] is of the given filetype. These remaps
+/// take precedence over the ones made with [`mode::map`].
+///
+/// [`File`]: crate::widgets::File
+pub fn map<M: Mode<U>, U: Ui>(filetype: impl ToString, take: &str, give: impl AsGives<U>) {
+    mode::map_for_filetype::<M, U>(filetype.to_string(), take, give, false);
+}
+
+/// Aliases a sequence of keys to another, on [`File`]s of a filetype
+///
+/// This works just like [`mode::alias`], but the alias only happens
+/// while the active [`File`] is of the given filetype.
+///
+/// [`File`]: crate::widgets::File
+pub fn alias<M: Mode<U>, U: Ui>(filetype: impl ToString, take: &str, give: impl AsGives<U>) {
+    mode::map_for_filetype::<M, U>(filetype.to_string(), take, give, true);
+}
+
+/// Applies the [`PrintCfg`] changes of a filetype
+pub(crate) fn apply_print_cfgs(filetype: &str, cfg: PrintCfg) -> PrintCfg {
+    let fns: Vec<PrintCfgFn> = PRINT_CFGS
+        .lock()
+        .iter()
+        .filter(|(ft, _)| ft == filetype)
+        .map(|(_, f)| f.clone())
+        .collect();
+
+    fns.iter().fold(cfg, |cfg, f| f(cfg))
+}
+
+/// Triggers [`OnFileTypeSet`] for a newly opened [`File`]
+///
+/// If the [`File`] is the active one, its [`Form`]s are also set.
+pub(crate) fn file_opened<U: Ui>(node: &Node<U>) {
+    let Some(file) = node.try_downcast::<File>() else {
+        return;
+    };
+    let filetype = file.read().filetype().map(str::to_string);
+
+    if context::cur_file::<U>().is_ok_and(|cur| cur.file_ptr_eq(node)) {
+        set_forms_of(filetype.as_deref());
+    }
+    if filetype.is_some() {
+        hooks::trigger::<OnFileTypeSet>(file);
+    }
+}
+
+/// Adds the hook that sets the [`Form`]s of focused [`File`]s
+pub(crate) fn add_hooks<U: Ui>() {
+    hooks::remove("FileTypeForms");
+    hooks::add_grouped::<FocusedOn<File, U>>("FileTypeForms", |(file, ..)| {
+        let filetype = file.read().filetype().map(str::to_string);
+        set_forms_of(filetype.as_deref());
+    });
+}
+
+/// Sets the [`Form`]s of a filetype, undoing those of the last one
+pub(crate) fn set_forms_of(filetype: Option<&str>) {
+    let mut replaced = REPLACED.lock();
+    for (name, form) in replaced.drain(..) {
+        form::set(name, form);
+    }
+
+    let Some(filetype) = filetype else {
+        return;
+    };
+    for (_, name, form) in FORMS.lock().iter().filter(|(ft, ..)| ft == filetype) {
+        replaced.push((name, form::from_id(form::inner_to_id(name))));
+        form::set(*name, *form);
+    }
+}
+
+/// The filetype given by a Vim or Emacs modeline
+fn from_modeline(text: &Text) -> Option<String> {
+    let lines = text.len().line() + 1;
+    let first = 0..MODELINE_LINES.min(lines);
+    let last = lines.saturating_sub(MODELINE_LINES).max(first.end)..lines;
+
+    first.chain(last).find_map(|line| {
+        let start = text.point_at_line(line);
+        let end = match line + 1 < lines {
+            true => text.point_at_line(line + 1),
+            false => text.len(),
+        };
+        let line = text.strs_in_range((start, end)).concat();
+
+        vim_modeline(&line)
+            .or_else(|| emacs_modeline(&line))
+            .map(str::to_lowercase)
+    })
+}
+
+/// The filetype in a modeline like `vim: set ft=rust:`
+fn vim_modeline(line: &str) -> Option<&str> {
+    let (_, options) = ["vim:", "vi:", "ex:"].iter().find_map(|prefix| {
+        let (before, after) = line.split_once(prefix)?;
+        (before.is_empty() || before.ends_with(char::is_whitespace)).then_some((before, after))
+    })?;
+
+    options
+        .split([' ', ':', '\t'])
+        .find_map(|opt| opt.strip_prefix("ft=").or(opt.strip_prefix("filetype=")))
+        .filter(|filetype| !filetype.is_empty())
+}
+
+/// The filetype in a modeline like `-*- mode: rust -*-`
+fn emacs_modeline(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (vars, _) = rest.split_once("-*-")?;
+
+    if !vars.contains(':') {
+        return Some(vars.trim()).filter(|mode| !mode.is_empty());
+    }
+    vars.split(';').find_map(|var| {
+        let (key, value) = var.split_once(':')?;
+        (key.trim().eq_ignore_ascii_case("mode")).then(|| value.trim())
+    })
+}
+
+/// The filetype of the interpreter in a shebang
+fn from_shebang(text: &Text) -> Option<String> {
+    let first_line = text
+        .chars_fwd(Point::default())
+        .map(|(_, char)| char)
+        .take_while(|char| *char != '\n')
+        .collect::<String>();
+    let command = first_line.strip_prefix("#!")?;
+
+    let mut words = command.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    // Versions, like in python3.12, are irrelevant.
+    let program = program.trim_end_matches(|char: char| char.is_ascii_digit() || char == '.');
+
+    find(INTERPRETERS, program).map(str::to_string)
+}
+
+/// Finds the filetype of a key in a list
+fn find(list: &'static [(&[&str], &str)], key: &str) -> Option<&'static str> {
+    list.iter()
+        .find_map(|(keys, filetype)| keys.contains(&key).then_some(*filetype))
+}
+
+type PrintCfgFn = Arc<dyn Fn(PrintCfg) -> PrintCfg + Send + Sync>;
+
+/// The filetypes of common extensions
+const DEFAULT_EXTENSIONS: &[(&[&str], &str)] = &[
+    (&["c", "h"], "c"),
+    (&["cc", "cpp", "cxx", "hh", "hpp", "hxx"], "cpp"),
+    (&["cs"], "csharp"),
+    (&["css"], "css"),
+    (&["go"], "go"),
+    (&["hs"], "haskell"),
+    (&["html", "htm"], "html"),
+    (&["java"], "java"),
+    (&["js", "mjs", "cjs"], "javascript"),
+    (&["json"], "json"),
+    (&["jsx"], "jsx"),
+    (&["lua"], "lua"),
+    (&["md", "markdown"], "markdown"),
+    (&["nix"], "nix"),
+    (&["ml", "mli"], "ocaml"),
+    (&["php"], "php"),
+    (&["pl", "pm"], "perl"),
+    (&["py", "pyi"], "python"),
+    (&["rb"], "ruby"),
+    (&["rs"], "rust"),
+    (&["scala", "sc"], "scala"),
+    (&["sh", "bash", "zsh"], "sh"),
+    (&["sql"], "sql"),
+    (&["toml"], "toml"),
+    (&["ts", "mts", "cts"], "typescript"),
+    (&["tsx"], "tsx"),
+    (&["txt"], "text"),
+    (&["xml", "svg"], "xml"),
+    (&["yaml", "yml"], "yaml"),
+    (&["zig"], "zig"),
+];
+
+/// The filetypes of files that are known by their names
+const FILE_NAMES: &[(&[&str], &str)] = &[
+    (&["Makefile", "makefile", "GNUmakefile"], "make"),
+    (&["Dockerfile", "Containerfile"], "dockerfile"),
+    (&[".bashrc", ".bash_profile", ".profile", ".zshrc"], "sh"),
+    (&["Cargo.lock"], "toml"),
+];
+
+/// The filetypes of interpreters in shebangs
+const INTERPRETERS: &[(&[&str], &str)] = &[
+    (&["sh", "bash", "dash", "zsh", "ksh"], "sh"),
+    (&["python", "pypy"], "python"),
+    (&["node", "deno", "bun"], "javascript"),
+    (&["ruby"], "ruby"),
+    (&["perl"], "perl"),
+    (&["lua", "luajit"], "lua"),
+    (&["php"], "php"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::detect;
+    use crate::text::Text;
+
+    #[test]
+    fn detects_from_extension_and_name() {
+        let text = Text::new();
+        assert_eq!(detect("/a/b.yml", &text).as_deref(), Some("yaml"));
+        assert_eq!(detect("/a/Makefile", &text).as_deref(), Some("make"));
+        assert_eq!(detect("/a/b.unknown", &text), None);
+    }
+
+    #[test]
+    fn detects_from_shebang() {
+        let text = Text::from("#!/usr/bin/env -S python3.12 -u\nprint(1)\n");
+        assert_eq!(detect("/a/script", &text).as_deref(), Some("python"));
+        let text = Text::from("#!/bin/bash\necho\n");
+        assert_eq!(detect("/a/script", &text).as_deref(), Some("sh"));
+    }
+
+    #[test]
+    fn modelines_take_precedence() {
+        let text = Text::from("# vim: set ts=2 ft=yaml:\nkey: value\n");
+        assert_eq!(detect("/a/b.txt", &text).as_deref(), Some("yaml"));
+        let lines: String = (0..20).map(|i| format!("line {i}\n")).collect();
+        let text = Text::from(format!("{lines}<!-- -*- mode: Markdown -*- -->\n"));
+        assert_eq!(detect("/a/b.txt", &text).as_deref(), Some("markdown"));
+    }
+}
@@ -2,13 +2,17 @@
 use std::sync::{LazyLock, OnceLock};
 
 use FormType::*;
-use crossterm::style::{Attribute, Attributes, ContentStyle};
-pub use crossterm::{cursor::SetCursorStyle as CursorShape, style::Color};
+use crossterm::style::{Attributes, ContentStyle};
+pub use crossterm::{
+    cursor::SetCursorStyle as CursorShape,
+    style::{Attribute, Color},
+};
 use parking_lot::{RwLock, RwLockWriteGuard};
 
 pub use self::global::{
-    FormFmt, extra_cursor, from_id, id_of, inner_to_id, main_cursor, name_of, painter, set,
-    set_extra_cursor, set_main_cursor, set_weak, unset_extra_cursor, unset_main_cursor,
+    FormFmt, disable_attribute, enable_attribute, extra_cursor, from_id, id_of, inner_to_id,
+    main_cursor, name_of, painter, set, set_dim_inactive, set_extra_cursor, set_main_cursor,
+    set_priority, set_weak, unset_extra_cursor, unset_main_cursor,
 };
 use crate::{data::RwLockReadGuard, ui::Sender};
 
@@ -27,6 +31,9 @@ static BASE_FORMS: &[(&str, Form, FormType)] = &[
     ("MainSelection", Form::on_dark_grey().0, Normal),
     ("ExtraSelection", Form::on_dark_grey().0, Ref(M_SEL_ID)),
     ("Inactive", Form::grey().0, Normal),
+    ("MainCursorEol", Form::reverse().0, Ref(M_CUR_ID)),
+    ("ExtraCursorEol", Form::reverse().0, Ref(E_CUR_ID)),
+    ("Suggestion", Form::dark_grey().0, Normal),
     // Tree sitter Forms
     ("type", Form::yellow().0, Normal),
     ("constant", Form::dark_grey().0, Normal),
@@ -55,7 +62,7 @@ mod global {
 
     use parking_lot::Mutex;
 
-    use super::{BASE_FORMS, BuiltForm, CursorShape, Form, FormId, Painter, Palette};
+    use super::{Attribute, BASE_FORMS, BuiltForm, CursorShape, Form, FormId, Painter, Palette};
 
     static PALETTE: Palette = Palette::new();
     static FORMS: LazyLock<Mutex<Vec<&str>>> =
@@ -164,6 +171,74 @@ mod global {
         }
     }
 
+    /// Sets the priority of the [`Form`] by the name of `name`
+    ///
+    /// When multiple [`Form`]s are applied to the same character,
+    /// the attributes (foreground, background, underline color, and
+    /// text attributes) of [`Form`]s with a higher priority override
+    /// those of [`Form`]s with a lower priority. For [`Form`]s with
+    /// the same priority, the last one to be applied wins.
+    ///
+    /// By default, every [`Form`] has a priority of
+    /// [`DEFAULT_PRIORITY`], while the cursor [`Form`]s have a
+    /// priority of [`u8::MAX`], so they're always shown on top.
+    ///
+    /// ```rust
+    /// # use duat_core::form::{self, DEFAULT_PRIORITY};
+    /// // Search matches are shown on top of selections.
+    /// form::set_priority("MainSelection", DEFAULT_PRIORITY + 10);
+    /// form::set_priority("SearchMatch", DEFAULT_PRIORITY + 20);
+    /// ```
+    ///
+    /// [`DEFAULT_PRIORITY`]: super::DEFAULT_PRIORITY
+    pub fn set_priority(name: impl ToString, priority: u8) {
+        let name: &'static str = name.to_string().leak();
+        crate::thread::queue(move || PALETTE.set_priority(name, priority));
+    }
+
+    /// Dims the [`File`]s that are not focused
+    ///
+    /// When this is enabled, every [`Form`] printed in an unfocused
+    /// [`File`] is replaced by a dimmed variant of itself. Colors
+    /// given in [`Color::Rgb`] are blended into the background of the
+    /// `"Default"` form, while other colors are printed with the
+    /// [`Attribute::Dim`] attribute.
+    ///
+    /// This makes it obvious which [`File`] is focused, when there
+    /// are lots of them on screen.
+    ///
+    /// [`File`]: crate::widgets::File
+    /// [`Color::Rgb`]: super::Color::Rgb
+    /// [`Attribute::Dim`]: crossterm::style::Attribute::Dim
+    pub fn set_dim_inactive(dim: bool) {
+        crate::thread::queue(move || PALETTE.set_dim_inactive(dim));
+    }
+
+    /// Stops an [`Attribute`] from being printed, in every [`Form`]
+    ///
+    /// This is meant for terminals that render some attributes
+    /// poorly, like [`Attribute::Italic`] in some fonts. Since it is
+    /// applied when [`Form`]s are printed, it works regardless of
+    /// the colorscheme, and of [`Form`]s that are set later.
+    ///
+    /// ```rust
+    /// # use duat_core::form::{self, Attribute};
+    /// form::disable_attribute(Attribute::Italic);
+    /// form::disable_attribute(Attribute::SlowBlink);
+    /// ```
+    pub fn disable_attribute(attr: Attribute) {
+        crate::thread::queue(move || PALETTE.set_attribute_enabled(attr, false));
+    }
+
+    /// Lets an [`Attribute`] be printed again
+    ///
+    /// This undoes [`form::disable_attribute`].
+    ///
+    /// [`form::disable_attribute`]: disable_attribute
+    pub fn enable_attribute(attr: Attribute) {
+        crate::thread::queue(move || PALETTE.set_attribute_enabled(attr, true));
+    }
+
     /// Returns a [`Form`], given a [`FormId`].
     ///
     /// If you are thinking of using this for printing purposes,
@@ -421,9 +496,11 @@ impl Form {
     mimic_method_new!(/**rapid_blink*/ rapid_blink Attribute::RapidBlink);
     mimic_method_new!(/**hidden*/ hidden Attribute::Hidden);
     mimic_method_new!(/**crossed_out*/ crossed_out Attribute::CrossedOut);
+    mimic_method_new!(/**strikethrough*/ strikethrough Attribute::CrossedOut);
     mimic_method_new!(/**double_underlined*/ double_underlined Attribute::DoubleUnderlined);
     mimic_method_new!(/**undercurled*/ undercurled Attribute::Undercurled);
     mimic_method_new!(/**underdashed*/ underdashed Attribute::Underdashed);
+    mimic_method_new!(/**underdotted*/ underdotted Attribute::Underdotted);
     mimic_method_new!(/**black*/ black on_black underline_black Color::Black);
     mimic_method_new!(/**dark_grey*/ dark_grey on_dark_grey underline_dark_grey Color::DarkGrey);
     mimic_method_new!(/**red*/ red on_red underline_red Color::Red);
@@ -529,9 +606,11 @@ impl BuiltForm {
     mimic_method_cycle!(/**rapid_blink*/ rapid_blink Attribute::RapidBlink);
     mimic_method_cycle!(/**hidden*/ hidden Attribute::Hidden);
     mimic_method_cycle!(/**crossed_out*/ crossed_out Attribute::CrossedOut);
+    mimic_method_cycle!(/**strikethrough*/ strikethrough Attribute::CrossedOut);
     mimic_method_cycle!(/**double_underlined*/ double_underlined Attribute::DoubleUnderlined);
     mimic_method_cycle!(/**undercurled*/ undercurled Attribute::Undercurled);
     mimic_method_cycle!(/**underdashed*/ underdashed Attribute::Underdashed);
+    mimic_method_cycle!(/**underdotted*/ underdotted Attribute::Underdotted);
     mimic_method_cycle!(/**black*/ black on_black underline_black Color::Black);
     mimic_method_cycle!(/**dark_grey*/ dark_grey on_dark_grey underline_dark_grey Color::DarkGrey);
     mimic_method_cycle!(/**red*/ red on_red underline_red Color::Red);
@@ -597,6 +676,12 @@ impl std::ops::Deref for BuiltForm {
     }
 }
 
+impl From<BuiltForm> for Form {
+    fn from(value: BuiltForm) -> Self {
+        value.0
+    }
+}
+
 /// The [`FormId`] of the `"Default"` form
 pub const DEFAULT_ID: FormId = FormId(0);
 /// The [`FormId`] of the `"MainCursor"` form
@@ -609,11 +694,33 @@ pub const M_SEL_ID: FormId = FormId(10);
 pub const E_SEL_ID: FormId = FormId(11);
 /// The [`FormId`] of the `"Inactive"` form
 pub const INACTIVE_ID: FormId = FormId(12);
+/// The [`FormId`] of the `"MainCursorEol"` form
+pub const M_CUR_EOL_ID: FormId = FormId(13);
+/// The [`FormId`] of the `"ExtraCursorEol"` form
+pub const E_CUR_EOL_ID: FormId = FormId(14);
+/// The priority of [`Form`]s, unless [set otherwise]
+///
+/// [set otherwise]: set_priority
+pub const DEFAULT_PRIORITY: u8 = 100;
 
 struct InnerPalette {
     main_cursor: Option<CursorShape>,
     extra_cursor: Option<CursorShape>,
     forms: Vec<(&'static str, Form, FormType)>,
+    priorities: Vec<u8>,
+    dim_inactive: bool,
+    disabled_attrs: Attributes,
+}
+
+impl InnerPalette {
+    /// The priority of the [`Form`] with the given [`FormId`]
+    fn priority_of(&self, id: FormId) -> u8 {
+        match self.priorities.get(id.0 as usize) {
+            Some(priority) => *priority,
+            None if [M_CUR_ID, E_CUR_ID, M_CUR_EOL_ID, E_CUR_EOL_ID].contains(&id) => u8::MAX,
+            None => DEFAULT_PRIORITY,
+        }
+    }
 }
 
 /// The list of forms to be used when rendering.
@@ -629,6 +736,9 @@ impl Palette {
                 main_cursor,
                 extra_cursor: main_cursor,
                 forms: BASE_FORMS.to_vec(),
+                priorities: Vec::new(),
+                dim_inactive: false,
+                disabled_attrs: Attributes::none(),
             })
         }))
     }
@@ -636,7 +746,7 @@ impl Palette {
     /// Sets a [`Form`]
     fn set_form(&self, name: &'static str, form: Form) {
         let form = match name {
-            "MainCursor" | "ExtraCursor" => form.as_finished(),
+            "MainCursor" | "ExtraCursor" | "MainCursorEol" | "ExtraCursorEol" => form.as_finished(),
             _ => form,
         };
 
@@ -660,7 +770,7 @@ impl Palette {
     /// Sets a [`Form`] "weakly"
     fn set_weak_form(&self, name: &'static str, form: Form) {
         let form = match name {
-            "MainCursor" | "ExtraCursor" => form.as_finished(),
+            "MainCursor" | "ExtraCursor" | "MainCursorEol" | "ExtraCursorEol" => form.as_finished(),
             _ => form,
         };
 
@@ -757,10 +867,33 @@ impl Palette {
         }
     }
 
+    /// Sets the priority of a [`Form`]
+    fn set_priority(&self, name: &'static str, priority: u8) {
+        let id = self.id_from_name(name);
+        let mut inner = self.0.write();
+
+        let i = id.0 as usize;
+        if inner.priorities.len() <= i {
+            let len = inner.forms.len().max(i + 1);
+            let priorities: Vec<u8> = (inner.priorities.len()..len)
+                .map(|i| inner.priority_of(FormId(i as u16)))
+                .collect();
+            inner.priorities.extend(priorities);
+        }
+        inner.priorities[i] = priority;
+
+        if let Some(sender) = SENDER.get() {
+            sender.send_form_changed().unwrap()
+        }
+    }
+
     /// Returns a form, given a [`FormId`].
     fn form_from_id(&self, id: FormId) -> Option<Form> {
         let inner = self.0.read_recursive();
-        inner.forms.get(id.0 as usize).map(|(_, form, _)| *form)
+        inner.forms.get(id.0 as usize).map(|(_, form, _)| Form {
+            style: without_attrs(form.style, inner.disabled_attrs),
+            ..*form
+        })
     }
 
     /// Returns the name of the [`FormId`]
@@ -802,6 +935,30 @@ impl Palette {
         }
     }
 
+    /// Sets whether unfocused [`File`]s should be dimmed
+    ///
+    /// [`File`]: crate::widgets::File
+    fn set_dim_inactive(&self, dim: bool) {
+        self.0.write().dim_inactive = dim;
+        if let Some(sender) = SENDER.get() {
+            sender.send_form_changed().unwrap()
+        }
+    }
+
+    /// Sets whether an [`Attribute`] should be printed
+    fn set_attribute_enabled(&self, attr: Attribute, enabled: bool) {
+        let mut inner = self.0.write();
+        match enabled {
+            true => inner.disabled_attrs.unset(attr),
+            false => inner.disabled_attrs.set(attr),
+        }
+        drop(inner);
+
+        if let Some(sender) = SENDER.get() {
+            sender.send_form_changed().unwrap()
+        }
+    }
+
     /// Unsets the [`CursorShape`] of the main cursor
     fn unset_main_cursor(&self) {
         self.0.write().main_cursor = None;
@@ -824,19 +981,55 @@ impl Palette {
         let default = inner.forms[DEFAULT_ID.0 as usize].1;
         Painter {
             inner,
-            cur: vec![(default, DEFAULT_ID)],
+            cur: vec![(default, DEFAULT_ID, 0)],
             cur_sty: default.style,
+            is_dimmed: false,
         }
     }
 }
 
 pub struct Painter {
     inner: RwLockReadGuard<'static, InnerPalette>,
-    cur: Vec<(Form, FormId)>,
+    cur: Vec<(Form, FormId, u8)>,
     cur_sty: ContentStyle,
+    is_dimmed: bool,
 }
 
 impl Painter {
+    /// Makes this [`Painter`] print dimmed [`Form`]s
+    ///
+    /// This only has an effect if [`form::set_dim_inactive`] was
+    /// called with `true`, and is meant for [`File`]s that are not
+    /// focused.
+    ///
+    /// [`form::set_dim_inactive`]: set_dim_inactive
+    /// [`File`]: crate::widgets::File
+    pub fn dimmed(mut self) -> Self {
+        self.is_dimmed = self.inner.dim_inactive;
+        self.cur_sty = self.make_style();
+        self
+    }
+
+    /// Makes this [`Painter`] print dimmed [`Form`]s, regardless of
+    /// [`form::set_dim_inactive`]
+    ///
+    /// This is meant for what is beneath a floating [`Area`] that
+    /// [shades] its surroundings.
+    ///
+    /// [`form::set_dim_inactive`]: set_dim_inactive
+    /// [`Area`]: crate::ui::Area
+    /// [shades]: crate::ui::FloatSpecs::with_shade
+    pub fn shaded(mut self) -> Self {
+        self.is_dimmed = true;
+        self.cur_sty = self.make_style();
+        self
+    }
+
+    /// Whether this [`Painter`] is printing dimmed [`Form`]s
+    pub fn is_dimmed(&self) -> bool {
+        self.is_dimmed
+    }
+
     /// Applies the `Form` with the given `id` and returns the result,
     /// given previous triggers.
     #[inline(always)]
@@ -845,8 +1038,11 @@ impl Painter {
         let forms = &self.inner.forms;
         let form = forms.get(i).map(|(_, f, _)| *f).unwrap_or(Form::new().0);
 
-        // So the cursor is always the last form
-        self.cur.push((form, id));
+        // Forms are kept sorted by priority, so the ones with higher
+        // priority (like the cursors) are applied last.
+        let priority = self.inner.priority_of(id);
+        let i = self.cur[1..].partition_point(|(.., p)| *p <= priority) + 1;
+        self.cur.insert(i, (form, id, priority));
         self.cur_sty = self.make_style();
         self.cur_sty
     }
@@ -856,7 +1052,7 @@ impl Painter {
     #[inline(always)]
     pub fn remove(&mut self, id: FormId) -> ContentStyle {
         let mut applied_forms = self.cur.iter().enumerate();
-        if let Some((index, _)) = applied_forms.rfind(|(_, &(_, i))| i == id) {
+        if let Some((index, _)) = applied_forms.rfind(|(_, &(_, i, _))| i == id) {
             self.cur.remove(index);
             self.cur_sty = self.make_style();
         }
@@ -881,7 +1077,7 @@ impl Painter {
 
         let (mut fg_done, mut bg_done, mut ul_done, mut attr_done) = (false, false, false, false);
 
-        for &(Form { style, finished }, _) in &self.cur {
+        for &(Form { style, finished }, ..) in &self.cur {
             if let Some(new_fg) = style.foreground_color
                 && (!fg_done || finished)
             {
@@ -906,7 +1102,21 @@ impl Painter {
             }
         }
 
-        form.style
+        // Disabled attributes are removed before dimming, since that
+        // may add the Dim attribute by itself.
+        form.style = without_attrs(form.style, self.inner.disabled_attrs);
+
+        if self.is_dimmed {
+            dim(form.style, self.cur[0].0.style.background_color)
+        } else {
+            form.style
+        }
+    }
+
+    /// Whether the [`Form`] with the given `id` is currently applied
+    #[inline(always)]
+    pub fn is_applied(&self, id: FormId) -> bool {
+        self.cur.iter().any(|(_, i, _)| *i == id)
     }
 
     #[inline(always)]
@@ -951,6 +1161,55 @@ pub(crate) fn set_sender(sender: Sender) {
         .unwrap_or_else(|_| panic!("Sender set more than once"));
 }
 
+/// Makes every widget print again, as if a [`Form`] had changed
+///
+/// This is used when [`Painter`]s would now paint differently, like
+/// when a floating [`Area`] starts or stops shading what is beneath
+/// it.
+///
+/// [`Area`]: crate::ui::Area
+pub(crate) fn reprint_all() {
+    if let Some(sender) = SENDER.get() {
+        sender.send_form_changed().unwrap()
+    }
+}
+
+/// Removes all [`Attribute`]s in `disabled` from a [`ContentStyle`]
+fn without_attrs(mut style: ContentStyle, disabled: Attributes) -> ContentStyle {
+    if !disabled.is_empty() {
+        for attr in Attribute::iterator().filter(|attr| disabled.has(*attr)) {
+            style.attributes.unset(attr);
+        }
+    }
+    style
+}
+
+/// A dimmed variant of a [`ContentStyle`]
+///
+/// [`Color::Rgb`] foregrounds are blended halfway into the
+/// background, since terminals don't handle [`Attribute::Dim`] very
+/// consistently. Any other colors fall back to said attribute.
+fn dim(mut style: ContentStyle, default_bg: Option<Color>) -> ContentStyle {
+    let bg = match style.background_color.or(default_bg) {
+        Some(Color::Rgb { r, g, b }) => (r, g, b),
+        _ => (0, 0, 0),
+    };
+    let blend = |fg: u8, bg: u8| ((fg as u16 + bg as u16) / 2) as u8;
+
+    match style.foreground_color {
+        Some(Color::Rgb { r, g, b }) => {
+            style.foreground_color = Some(Color::Rgb {
+                r: blend(r, bg.0),
+                g: blend(g, bg.1),
+                b: blend(b, bg.2),
+            })
+        }
+        _ => style.attributes.set(Attribute::Dim),
+    }
+
+    style
+}
+
 /// An enum that helps in the modification of forms
 #[derive(Debug, Clone)]
 enum FormType {
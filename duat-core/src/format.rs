@@ -0,0 +1,290 @@
+//! Formatting [`File`]s with external formatters, on every write
+//!
+//! A [`Formatter`] is an external command which receives the contents
+//! of a [`File`] through stdin, and prints the formatted version to
+//! stdout. They are set per file extension:
+//!
+//! ```rust
+//! # use std::time::Duration;
+//! # use duat_core::format::{self, Formatter};
+//! format::set_formatter(
+//!     "rs",
+//!     Formatter::new("rustfmt").with_args(["--edition", "2024"]),
+//! );
+//! format::set_formatter(
+//!     "ts",
+//!     Formatter::new("prettier")
+//!         .with_args(["--stdin-filepath", "{path}"])
+//!         .with_timeout(Duration::from_secs(10)),
+//! );
+//! ```
+//!
+//! After a [`File`] is written, its formatter is run in a separate
+//! thread. If it succeeds before the timeout, and the [`File`] hasn't
+//! changed in the meantime, only the lines that differ are replaced,
+//! as a single moment in the history. This means that the cursors
+//! stay in place, and the formatting can be undone in one go. The
+//! [`File`] is then written again.
+//!
+//! Formatting can be turned off on a per [`File`] basis with the
+//! `autoformat` option, or the `autoformat on|off` command.
+//!
+//! [`File`]: crate::widgets::File
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    context,
+    data::RwData,
+    hooks::{self, AfterFileWrite},
+    mode,
+    opts::{self, OptValue, Scope},
+    text::{Text, diff, err},
+    ui::{Area, Ui, Window},
+    widgets::{File, View, Widget},
+};
+
+static FORMATTERS: LazyLock<Mutex<HashMap<String, Formatter>>> = LazyLock::new(Mutex::default);
+/// Paths whose formatter is currently running
+static FORMATTING: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(Mutex::default);
+/// Paths that were just written with the output of a formatter
+static REWRITTEN: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(Mutex::default);
+
+/// An external formatter
+///
+/// The contents of the [`File`] are sent to its stdin, and the
+/// formatted contents are read from its stdout. Any argument that
+/// is exactly `{path}` is replaced by the path of the [`File`].
+///
+/// [`File`]: crate::widgets::File
+#[derive(Debug, Clone)]
+pub struct Formatter {
+    cmd: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl Formatter {
+    /// Returns a new [`Formatter`], with no arguments
+    ///
+    /// The default timeout is 5 seconds.
+    pub fn new(cmd: impl ToString) -> Self {
+        Self {
+            cmd: cmd.to_string(),
+            args: Vec::new(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Adds arguments to the command
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.to_string()));
+        self
+    }
+
+    /// How long to wait before killing the formatter
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Runs the formatter on some contents, returning the output
+    ///
+    /// This will block until the formatter is done, or until the
+    /// timeout is reached.
+    pub fn run(&self, path: &str, contents: &str) -> Result<String, Text> {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| if arg == "{path}" { path } else { arg.as_str() });
+
+        let mut child = Command::new(&self.cmd)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| err!("Failed to run " [*a] { &self.cmd } []": " { e.to_string() }))?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+        let mut stderr = child.stderr.take().unwrap();
+        let start = Instant::now();
+
+        std::thread::scope(|s| {
+            // Writing and reading at the same time, so neither pipe fills.
+            let written = s.spawn(move || stdin.write_all(contents.as_bytes()));
+            let out = s.spawn(move || {
+                let mut out = String::new();
+                stdout.read_to_string(&mut out).map(|_| out)
+            });
+            let errs = s.spawn(move || {
+                let mut errs = String::new();
+                let _ = stderr.read_to_string(&mut errs);
+                errs
+            });
+
+            let status = loop {
+                match child.try_wait() {
+                    Ok(Some(status)) => break status,
+                    Ok(None) if start.elapsed() >= self.timeout => {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return Err(err!([*a] { &self.cmd } [] " timed out"));
+                    }
+                    Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+                    Err(e) => return Err(err!([*a] { &self.cmd } [] ": " { e.to_string() })),
+                }
+            };
+
+            let written = written.join().unwrap();
+            let out = out.join().unwrap();
+            let errs = errs.join().unwrap();
+
+            if status.success() {
+                // A formatter that didn't read all of its input (e.g. a broken
+                // pipe) can't be trusted with the whole file.
+                written.map_err(
+                    |e| err!("Failed to write to " [*a] { &self.cmd } []": " { e.to_string() }),
+                )?;
+                out.map_err(|e| err!([*a] { &self.cmd } [] ": " { e.to_string() }))
+            } else {
+                let line = errs.lines().find(|line| !line.trim().is_empty());
+                Err(err!([*a] { &self.cmd } [] " failed: " { line.unwrap_or("no output") }))
+            }
+        })
+    }
+}
+
+/// Sets the [`Formatter`] for files with a given extension
+///
+/// The extension is given without the leading dot, e.g. `"rs"`.
+pub fn set_formatter(ext: impl ToString, formatter: Formatter) {
+    FORMATTERS.lock().insert(ext.to_string(), formatter);
+}
+
+/// Removes the [`Formatter`] for files with a given extension
+pub fn remove_formatter(ext: &str) -> Option<Formatter> {
+    FORMATTERS.lock().remove(ext)
+}
+
+/// The [`Formatter`] that would be used for a path
+pub fn formatter_for(path: &str) -> Option<Formatter> {
+    let ext = Path::new(path).extension()?.to_str()?;
+    FORMATTERS.lock().get(ext).cloned()
+}
+
+/// Declares the `autoformat` option and adds the formatting hook
+pub(crate) fn add_hooks<U: Ui>() {
+    opts::declare_bool("autoformat", true, Scope::File);
+
+    hooks::remove("Format");
+    hooks::add_grouped::<AfterFileWrite>("Format", |(path, _)| {
+        // Either the write came from applying the formatter's output,
+        // or the formatter is already running.
+        if REWRITTEN.lock().remove(path) || !FORMATTING.lock().insert(path.clone()) {
+            return;
+        }
+
+        match prepare::<U>(path) {
+            Some((formatter, contents)) => {
+                let path = path.clone();
+                crate::thread::spawn(move || match formatter.run(&path, &contents) {
+                    Ok(formatted) => crate::thread::queue(move || {
+                        apply::<U>(&path, &contents, &formatted);
+                        FORMATTING.lock().remove(&path);
+                    }),
+                    Err(msg) => {
+                        context::notify(msg);
+                        FORMATTING.lock().remove(&path);
+                    }
+                });
+            }
+            None => {
+                FORMATTING.lock().remove(path);
+            }
+        }
+    });
+}
+
+/// The [`Formatter`] and contents of the [`File`], if it should be
+/// formatted
+fn prepare<U: Ui>(path: &str) -> Option<(Formatter, String)> {
+    let formatter = formatter_for(path)?;
+    let (file, ..) = find_file::<U>(path)?;
+    let file = file.read();
+
+    let is_on = opts::get_for(&file, "autoformat") != Some(OptValue::Bool(false));
+    is_on.then(|| (formatter, file.text().to_string()))
+}
+
+/// Applies the output of a [`Formatter`] and writes the [`File`]
+fn apply<U: Ui>(path: &str, contents: &str, formatted: &str) {
+    if contents == formatted {
+        return;
+    }
+    let Some((file, View { area, cursors, .. })) = find_file::<U>(path) else {
+        return;
+    };
+
+    let mut file = file.write();
+    let mut cursors = cursors.write();
+    let cfg = <File as Widget<U>>::print_cfg(&file);
+    let text = <File as Widget<U>>::text_mut(&mut file);
+    text.remove_cursors(&cursors, &area, cfg);
+
+    // The File was edited while the formatter was running.
+    if text.to_string() != contents {
+        text.add_cursors(&cursors, &area, cfg);
+        return;
+    }
+
+    let hunks = diff::line_bytes(contents, formatted);
+
+    text.new_moment();
+    for hunk in hunks.iter().rev() {
+        let range = (
+            text.point_at(hunk.old.start as u32),
+            text.point_at(hunk.old.end as u32),
+        );
+        text.replace_range(range, &formatted[hunk.new.clone()]);
+    }
+    text.new_moment();
+
+    let map = |byte: u32| diff::map_index(&hunks, byte as usize) as u32;
+    cursors.map_bytes(map, text, &area, &cfg);
+
+    if let Some(main) = cursors.get_main() {
+        area.scroll_around_point(text, main.caret(), cfg);
+    }
+    text.add_cursors(&cursors, &area, cfg);
+
+    <File as Widget<U>>::update(&mut file, &area);
+    if !mode::is_printing_stopped() {
+        <File as Widget<U>>::print(&mut file, &area);
+    }
+
+    REWRITTEN.lock().insert(path.to_string());
+    if let Err(msg) = file.write() {
+        REWRITTEN.lock().remove(path);
+        context::notify(err!("Failed to write formatted file: " { msg }));
+    }
+}
+
+/// Finds an open [`File`] by its path
+fn find_file<U: Ui>(path: &str) -> Option<(RwData<File>, View<U>)> {
+    let windows = context::windows::<U>().read();
+    windows.iter().flat_map(Window::nodes).find_map(|node| {
+        let (file, view, _) = node.as_file()?;
+        let is_path = file.read().path() == path;
+        is_path.then_some((file, view))
+    })
+}
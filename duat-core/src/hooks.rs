@@ -26,10 +26,29 @@
 //! - [`OnWindowOpen`], which lets you push widgets around the window.
 //! - [`FocusedOn`] lets you act on a [widget] whenever it is focused.
 //! - [`UnfocusedFrom`] lets you act on a [widget] when unfocused.
+//! - [`OnFocusChange`] lets you act on both the previously and newly
+//!   focused [widgets][widget], regardless of their types.
 //! - [`KeySent`] lets you act on a [dyn Widget], depending on the
 //!   [key] sent to it.
 //! - [`KeySentTo`], unlike [`KeySent`], lets you act on a specific
 //!   [widget], given a [key].
+//! - [`ChangeRecorded`] lets you act on the keys of a repeatable
+//!   change, once a [`Mode`] is done recording it.
+//! - [`BeforeFileWrite`] lets you veto or transform the contents of a
+//!   [`File`] before it is written.
+//! - [`AfterFileWrite`] lets you act on a [`File`] after it has been
+//!   written to a path.
+//! - [`OnFileClose`] lets you clean up after a [`File`] when it is
+//!   closed.
+//! - [`OnFileChangedExternally`] lets you act on a [`File`] that was
+//!   changed on disk by another program.
+//! - [`OnFileTypeSet`] lets you act on a [`File`] once its filetype
+//!   is known.
+//! - [`OnFirstPrint`] lets you act on a [widget] after it is printed
+//!   for the first time.
+//! - [`OnHover`] lets you show information about the position that
+//!   the cursor is resting on.
+//! - [`OnOptionChanged`] lets you react to changes in [options].
 //!
 //! # A note on execution
 //!
@@ -52,22 +71,38 @@
 //! ```
 //!
 //! [`File`]: crate::widgets::File
+//! [`Mode`]: crate::mode::Mode
 //! [`LineNumbers`]: crate::widgets::LineNumbers
 //! [widget]: Widget
 //! [dyn Widget]: Widget
 //! [key]: KeyEvent
 //! [deadlocks]: https://en.wikipedia.org/wiki/Deadlock_(computer_science)
 //! [commands]: crate::cmd
-use std::{any::TypeId, collections::HashMap, marker::PhantomData, sync::LazyLock};
+//! [options]: crate::opts
+use std::{
+    any::TypeId,
+    cell::Cell,
+    collections::HashMap,
+    marker::PhantomData,
+    ptr::NonNull,
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
 use parking_lot::{Mutex, RwLock};
 
 pub use self::global::*;
 use crate::{
     data::RwData,
+    hover::HoverHandle,
     mode::{Cursors, KeyEvent},
+    opts::OptValue,
+    text::Text,
     ui::{Area, FileBuilder, Ui, WindowBuilder},
-    widgets::Widget,
+    widgets::{File, Widget},
 };
 
 pub struct SessionStarted<U: Ui>(PhantomData<U>);
@@ -126,9 +161,14 @@ impl<W: Widget<U>, U: Ui> Hookable for FocusedOn<W, U> {
         let mut widget = widget.write();
         let cfg = widget.print_cfg();
 
+        // Files restore their PrintInfo from the View, and the main caret
+        // was on screen when it was stored. Other widgets may have had
+        // their Text replaced on focus, so they have to follow the caret.
         widget.text_mut().add_cursors(&cursors, area, cfg);
-        if let Some(main) = cursors.get_main() {
-            area.scroll_around_point(widget.text(), main.caret(), widget.print_cfg());
+        if TypeId::of::<W>() != TypeId::of::<File>()
+            && let Some(main) = cursors.get_main()
+        {
+            area.scroll_around_point(widget.text(), main.caret(), cfg);
         }
 
         widget.update(area);
@@ -167,6 +207,23 @@ impl<W: Widget<U>, U: Ui> Hookable for UnfocusedFrom<W, U> {
     }
 }
 
+/// Triggers whenever the focus changes from one widget to another
+///
+/// This is triggered after [`UnfocusedFrom`] and [`FocusedOn`], and
+/// unlike them, it isn't tied to specific [widget] types.
+///
+/// # Arguments
+///
+/// - An [`RwData<dyn Widget<U>>`] for the previously focused widget.
+/// - An [`RwData<dyn Widget<U>>`] for the newly focused widget.
+///
+/// [widget]: crate::widgets::Widget
+pub struct OnFocusChange<U: Ui>(PhantomData<U>);
+
+impl<U: Ui> Hookable for OnFocusChange<U> {
+    type Args = (RwData<dyn Widget<U>>, RwData<dyn Widget<U>>);
+}
+
 /// The [`Mode`] has changed
 ///
 /// # Arguments
@@ -216,11 +273,313 @@ impl<W: Widget<U>, U: Ui> Hookable for KeySentTo<W, U> {
     type Args = (KeyEvent, RwData<W>);
 }
 
+/// Triggers whenever a [`Mode`] finishes recording a change
+///
+/// # Arguments
+///
+/// - The keys that make up the change, starting with the one that was
+///   being sent when [`mode::start_change`] was called.
+///
+/// [`Mode`]: crate::mode::Mode
+/// [`mode::start_change`]: crate::mode::start_change
+pub struct ChangeRecorded;
+
+impl Hookable for ChangeRecorded {
+    type Args = Vec<KeyEvent>;
+}
+
+/// Triggers right before a [`File`] is written to a path
+///
+/// Unlike most hooks, this one is triggered synchronously, so the
+/// [`WriteHandle`] can be used to affect the write itself.
+///
+/// # Arguments
+///
+/// - A [`WriteHandle`], with the path being written to and the
+///   contents to write, which can be used to [veto] or [transform]
+///   the write.
+///
+/// [veto]: WriteHandle::veto
+/// [transform]: WriteHandle::transform
+pub struct BeforeFileWrite;
+
+impl Hookable for BeforeFileWrite {
+    type Args = WriteHandle;
+}
+
+/// Triggers after a [`File`] has been successfully written
+///
+/// # Arguments
+///
+/// - The path that the [`File`] was written to.
+/// - The number of bytes written.
+pub struct AfterFileWrite;
+
+impl Hookable for AfterFileWrite {
+    type Args = (String, usize);
+}
+
+/// Triggers whenever a [`File`] is closed
+///
+/// This happens for every open [`File`] when Duat quits, and when
+/// the config is reloaded, since the [`File`]s are then handed over
+/// to the new config, which replaces them in their windows. It is the
+/// place to clean up any per file state that a plugin may be keeping.
+///
+/// # Arguments
+///
+/// - The [`File`] being closed.
+pub struct OnFileClose;
+
+impl Hookable for OnFileClose {
+    type Args = RwData<File>;
+}
+
+/// Triggers when a [`File`] is changed by another program
+///
+/// This only happens for [`File`]s opened while the `watch-files`
+/// option was on. See the [`watcher`] module for more information.
+///
+/// # Arguments
+///
+/// - The [`File`] that was changed.
+///
+/// [`watcher`]: crate::watcher
+pub struct OnFileChangedExternally;
+
+impl Hookable for OnFileChangedExternally {
+    type Args = RwData<File>;
+}
+
+/// Triggers when the filetype of a [`File`] is set
+///
+/// This happens when a [`File`] whose filetype could be detected is
+/// opened, and when the filetype is changed with the `filetype`
+/// command. See the [`filetype`] module for more information.
+///
+/// # Arguments
+///
+/// - The [`File`], whose filetype is in [`File::filetype`].
+///
+/// [`filetype`]: crate::filetype
+pub struct OnFileTypeSet;
+
+impl Hookable for OnFileTypeSet {
+    type Args = RwData<File>;
+}
+
+/// Triggers after a [widget] has been printed for the first time
+///
+/// This is useful for lazy initialization, since by that point, the
+/// widget will have been properly sized and placed on screen.
+///
+/// # Arguments
+///
+/// - The widget itself.
+/// - Its [area].
+///
+/// [widget]: crate::widgets::Widget
+/// [area]: crate::ui::Area
+pub struct OnFirstPrint<W: Widget<U>, U: Ui>(PhantomData<(W, U)>);
+
+impl<W: Widget<U>, U: Ui> Hookable for OnFirstPrint<W, U> {
+    type Args = (RwData<W>, U::Area);
+}
+
+/// Triggers when the main cursor rests on a position for a while
+///
+/// The delay can be changed with [`hover::set_delay`].
+///
+/// # Arguments
+///
+/// - A [`HoverHandle`], with the hovered [`File`] and [`Point`],
+///   which can be used to [show] information about them.
+///
+/// [`hover::set_delay`]: crate::hover::set_delay
+/// [`Point`]: crate::text::Point
+/// [show]: HoverHandle::show
+pub struct OnHover;
+
+impl Hookable for OnHover {
+    type Args = HoverHandle;
+}
+
+/// [`Hookable`]: Triggers when an [option] changes
+///
+/// This is triggered both when the global value changes and when
+/// the value for a specific [`File`] changes.
+///
+/// # Arguments
+///
+/// - The name of the option.
+/// - Its new value.
+///
+/// [option]: crate::opts
+pub struct OnOptionChanged;
+
+impl Hookable for OnOptionChanged {
+    type Args = (&'static str, OptValue);
+}
+
+/// A handle to a [`File`] that is about to be written
+///
+/// This is sent to [`BeforeFileWrite`] hooks, and lets them stop
+/// the write, or change what is going to be written.
+///
+/// The contents of the [`File`] are not copied into the handle, they
+/// are only read if a hook asks for them, while the hooks are being
+/// triggered.
+#[derive(Clone)]
+pub struct WriteHandle {
+    path: String,
+    inner: Arc<Mutex<WriteState>>,
+}
+
+impl WriteHandle {
+    /// Triggers [`BeforeFileWrite`] for the [`Text`] of a [`File`],
+    /// returning the transformed contents, if there were any
+    /// transformations
+    pub(crate) fn trigger(path: impl ToString, text: &Text) -> Result<Option<String>, String> {
+        let handle = Self {
+            path: path.to_string(),
+            inner: Arc::new(Mutex::new(WriteState { contents: None, veto: None })),
+        };
+
+        let _guard = WrittenTextGuard::set(text);
+        trigger_now::<BeforeFileWrite>(handle.clone());
+
+        let mut inner = handle.inner.lock();
+        match inner.veto.take() {
+            Some(reason) => Err(reason),
+            None => Ok(inner.contents.take()),
+        }
+    }
+
+    /// The path that the [`File`] will be written to
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The contents that will be written, after transformations
+    ///
+    /// If called after the [`BeforeFileWrite`] hooks are done, this
+    /// will return an empty [`String`].
+    pub fn contents(&self) -> String {
+        match &self.inner.lock().contents {
+            Some(contents) => contents.clone(),
+            None => with_written_text(Text::to_string).unwrap_or_default(),
+        }
+    }
+
+    /// Prevents the write from happening
+    ///
+    /// The `reason` will be shown as the error of the write.
+    pub fn veto(&self, reason: impl ToString) {
+        self.inner.lock().veto = Some(reason.to_string());
+    }
+
+    /// Transforms the contents that will be written
+    ///
+    /// This does not change the [`File`] itself, only what ends up
+    /// being written to the path. If called after the
+    /// [`BeforeFileWrite`] hooks are done, this does nothing.
+    pub fn transform(&self, f: impl FnOnce(&str) -> String) {
+        let mut inner = self.inner.lock();
+        let new = match &inner.contents {
+            Some(contents) => Some(f(contents)),
+            None => with_written_text(|text| f(&text.to_string())),
+        };
+        if let Some(new) = new {
+            inner.contents = Some(new);
+        }
+    }
+}
+
+struct WriteState {
+    contents: Option<String>,
+    veto: Option<String>,
+}
+
+thread_local! {
+    /// The [`Text`] being written, while [`BeforeFileWrite`] is
+    /// being triggered
+    static WRITTEN_TEXT: Cell<Option<NonNull<Text>>> = const { Cell::new(None) };
+}
+
+/// Sets the [`Text`] being written, until it is dropped
+///
+/// The guard borrows the [`Text`], so it can't outlive it.
+struct WrittenTextGuard<'a> {
+    prev: Option<NonNull<Text>>,
+    _text: PhantomData<&'a Text>,
+}
+
+impl<'a> WrittenTextGuard<'a> {
+    fn set(text: &'a Text) -> Self {
+        Self {
+            prev: WRITTEN_TEXT.replace(Some(NonNull::from(text))),
+            _text: PhantomData,
+        }
+    }
+}
+
+impl Drop for WrittenTextGuard<'_> {
+    fn drop(&mut self) {
+        WRITTEN_TEXT.set(self.prev);
+    }
+}
+
+/// Calls `f` on the [`Text`] being written, if there is one
+fn with_written_text<R>(f: impl FnOnce(&Text) -> R) -> Option<R> {
+    // SAFETY: The pointer is only set by a WrittenTextGuard, which
+    // borrows the Text for its whole lifetime, and restores the
+    // previous pointer when dropped, on the same thread. The guard is
+    // private, and never leaked.
+    WRITTEN_TEXT.get().map(|text| f(unsafe { text.as_ref() }))
+}
+
+/// A handle to a function running periodically
+///
+/// This is returned by [`hooks::every`], and can be used to stop the
+/// function from running. Dropping the handle does _not_ stop it.
+///
+/// [`hooks::every`]: every
+#[derive(Clone)]
+pub struct TimerHandle(Arc<AtomicBool>);
+
+impl TimerHandle {
+    /// Stops the function from running again
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`TimerHandle::cancel`] was called
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A function that runs every `period`
+struct Timer {
+    next: Instant,
+    period: Duration,
+    f: Arc<Mutex<dyn FnMut() + Send>>,
+    handle: TimerHandle,
+}
+
 /// Hook functions
 mod global {
-    use super::{Hookable, Hooks};
+    use std::{
+        sync::{Arc, atomic::AtomicBool},
+        time::{Duration, Instant},
+    };
+
+    use parking_lot::Mutex;
+
+    use super::{Hookable, Hooks, Timer, TimerHandle};
 
     static HOOKS: Hooks = Hooks::new();
+    static TIMERS: Mutex<Vec<Timer>> = Mutex::new(Vec::new());
 
     /// Adds a [hook]
     ///
@@ -274,6 +633,64 @@ mod global {
         HOOKS.trigger::<H>(args)
     }
 
+    /// Runs a function every `period`
+    ///
+    /// The function is scheduled by Duat's main loop, just like
+    /// [triggered] hooks, so things like autosaving or a clock in the
+    /// [`StatusLine`] don't need to spawn their own sleeping thread.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use duat_core::{cmd, hooks};
+    /// let handle = hooks::every(Duration::from_secs(60), || {
+    ///     let _ = cmd::run_notify("write");
+    /// });
+    /// // Later on...
+    /// handle.cancel();
+    /// ```
+    ///
+    /// If the function takes longer than `period` to run, runs are
+    /// skipped, instead of piling up.
+    ///
+    /// [triggered]: trigger
+    /// [`StatusLine`]: crate::widgets::StatusLine
+    pub fn every(period: Duration, f: impl FnMut() + Send + 'static) -> TimerHandle {
+        let handle = TimerHandle(Arc::new(AtomicBool::new(false)));
+        TIMERS.lock().push(Timer {
+            next: Instant::now() + period,
+            period,
+            f: Arc::new(Mutex::new(f)),
+            handle: handle.clone(),
+        });
+
+        handle
+    }
+
+    /// Runs the functions added by [`hooks::every`] that are due
+    ///
+    /// This is called on every iteration of the main loop.
+    ///
+    /// [`hooks::every`]: every
+    pub(crate) fn run_timers() {
+        let now = Instant::now();
+        let mut timers = TIMERS.lock();
+        timers.retain(|timer| !timer.handle.is_cancelled());
+
+        for timer in timers.iter_mut().filter(|timer| timer.next <= now) {
+            timer.next = now + timer.period;
+
+            let (f, handle) = (timer.f.clone(), timer.handle.clone());
+            crate::thread::queue(move || {
+                // If the last run is still going on, this one is skipped.
+                if !handle.is_cancelled()
+                    && let Some(mut f) = f.try_lock()
+                {
+                    f()
+                }
+            });
+        }
+    }
+
     /// Checks if a give group exists
     ///
     /// Returns `true` if said group was added via
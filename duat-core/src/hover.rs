@@ -0,0 +1,188 @@
+//! Information about the position the cursor is resting on
+//!
+//! After the main cursor of the active [`File`] stays on the same
+//! [`Point`] for a [delay], the [`OnHover`] hook is triggered with a
+//! [`HoverHandle`]. Hooks can then [show] some [`Text`] about that
+//! position (spelling suggestions, diagnostics, documentation, etc),
+//! which is dismissed as soon as the cursor moves:
+//!
+//! ```rust
+//! # use duat_core::{hooks::{self, OnHover}, text::text};
+//! hooks::add::<OnHover>(|handle| {
+//!     if handle.point().line() == 0 {
+//!         handle.show(text!("This is the first line"));
+//!     }
+//! });
+//! ```
+//!
+//! Since there are no floating areas yet, the [`Text`] is shown in
+//! the echo area, just like with [`context::echo`].
+//!
+//! [`File`]: crate::widgets::File
+//! [delay]: set_delay
+//! [`OnHover`]: crate::hooks::OnHover
+//! [show]: HoverHandle::show
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    context,
+    hooks::{self, OnHover},
+    text::{Point, Text},
+    ui::Ui,
+};
+
+static DELAY: Mutex<Duration> = Mutex::new(Duration::from_millis(500));
+static STATE: Mutex<Option<HoverState>> = Mutex::new(None);
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+const SHOW_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// Sets how long the cursor has to rest before [`OnHover`] triggers
+///
+/// The default is 500 milliseconds.
+///
+/// [`OnHover`]: crate::hooks::OnHover
+pub fn set_delay(delay: Duration) {
+    *DELAY.lock() = delay;
+}
+
+/// Dismisses the hover information, if it is being shown
+///
+/// Any [`HoverHandle`]s that were sent before this are invalidated,
+/// so [showing] [`Text`] through them will do nothing.
+///
+/// [showing]: HoverHandle::show
+pub fn dismiss() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    if let Some(state) = STATE.lock().as_mut()
+        && std::mem::take(&mut state.is_shown)
+    {
+        context::clear_echo();
+    }
+}
+
+/// A handle to the position being hovered
+///
+/// This is sent to [`OnHover`] hooks, and can be used to show
+/// [`Text`] about the position, even after the hook is done (e.g.
+/// when waiting on an external process). If the cursor moves before
+/// the [`Text`] is shown, nothing will happen.
+///
+/// [`OnHover`]: crate::hooks::OnHover
+#[derive(Clone)]
+pub struct HoverHandle {
+    file: String,
+    point: Point,
+    generation: usize,
+    shown: Arc<Mutex<Vec<Text>>>,
+}
+
+impl HoverHandle {
+    /// The name of the [`File`] being hovered
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// The [`Point`] being hovered
+    pub fn point(&self) -> Point {
+        self.point
+    }
+
+    /// Whether the cursor is still resting on this position
+    pub fn is_valid(&self) -> bool {
+        GENERATION.load(Ordering::Relaxed) == self.generation
+    }
+
+    /// Shows some [`Text`] about the hovered position
+    ///
+    /// If more than one hook shows [`Text`], they are all shown, one
+    /// below the other, in the order that they were shown.
+    pub fn show(&self, text: Text) {
+        if !self.is_valid() {
+            return;
+        }
+
+        let mut shown = self.shown.lock();
+        shown.push(text);
+
+        let mut builder = Text::builder();
+        for (i, text) in shown.iter().enumerate() {
+            if i > 0 {
+                builder.push("\n");
+            }
+            builder.push(text.clone());
+        }
+
+        context::echo_for(builder.finish(), SHOW_DURATION);
+        if let Some(state) = STATE.lock().as_mut() {
+            state.is_shown = true;
+        }
+    }
+}
+
+/// Checks if the main cursor has moved or rested long enough
+///
+/// This is called on every iteration of the main loop.
+pub(crate) fn update<U: Ui>() {
+    let Ok(cur_file) = context::cur_file::<U>() else {
+        return;
+    };
+    let (file, point) = cur_file
+        .inspect(|file, _, cursors| (file.name(), cursors.get_main().map(|main| main.caret())));
+
+    let mut state = STATE.lock();
+    let has_moved = state
+        .as_ref()
+        .is_none_or(|state| state.file != file || state.point != point);
+
+    if has_moved {
+        let was_shown = state.as_ref().is_some_and(|state| state.is_shown);
+        *state = Some(HoverState {
+            file,
+            point,
+            since: Instant::now(),
+            has_triggered: false,
+            is_shown: false,
+        });
+        drop(state);
+
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+        if was_shown {
+            context::clear_echo();
+        }
+        return;
+    }
+
+    let state = state.as_mut().unwrap();
+    if let Some(point) = state.point
+        && !state.has_triggered
+        && state.since.elapsed() >= *DELAY.lock()
+    {
+        state.has_triggered = true;
+        let handle = HoverHandle {
+            file: state.file.clone(),
+            point,
+            generation: GENERATION.load(Ordering::Relaxed),
+            shown: Arc::default(),
+        };
+
+        hooks::trigger::<OnHover>(handle);
+    }
+}
+
+struct HoverState {
+    file: String,
+    point: Option<Point>,
+    since: Instant,
+    has_triggered: bool,
+    is_shown: bool,
+}
@@ -0,0 +1,168 @@
+//! Automatic indentation of new lines
+//!
+//! Whenever a [`Mode`] adds a new line, it can ask for the
+//! indentation that line should have, through
+//! [`Editor::insert_newline_indented`] or [`File::indent_for_line`].
+//! That indentation is decided by an [`IndentProvider`], which can be
+//! replaced with [`set_provider`]:
+//!
+//! ```rust
+//! # use duat_core::indent::{self, CopyPrevious};
+//! indent::set_provider(CopyPrevious);
+//! ```
+//!
+//! By default, the [`Brackets`] provider is used, which copies the
+//! indentation of the previous line, adding a level after opening
+//! brackets, and removing one before closing brackets.
+//!
+//! [`Mode`]: crate::mode::Mode
+//! [`Editor::insert_newline_indented`]: crate::mode::Editor::insert_newline_indented
+//! [`File::indent_for_line`]: crate::widgets::File::indent_for_line
+use std::sync::{Arc, LazyLock};
+
+use parking_lot::RwLock;
+
+use crate::{cfg::PrintCfg, text::Text};
+
+static PROVIDER: LazyLock<RwLock<Arc<dyn IndentProvider>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(Brackets)));
+
+/// Something that decides how much a line should be indented
+pub trait IndentProvider: Send + Sync + 'static {
+    /// The indentation that a line should have, in columns
+    ///
+    /// This is called right after the line was created, so it may
+    /// already have some text, moved from the end of the previous
+    /// line.
+    fn indent_for_line(&self, text: &Text, line: u32, cfg: &PrintCfg) -> u32;
+}
+
+/// Copies the indentation of the previous non blank line
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CopyPrevious;
+
+impl IndentProvider for CopyPrevious {
+    fn indent_for_line(&self, text: &Text, line: u32, cfg: &PrintCfg) -> u32 {
+        previous_non_blank(text, line, cfg).map_or(0, |(_, indent)| indent)
+    }
+}
+
+/// Like [`CopyPrevious`], but aware of brackets
+///
+/// If the previous non blank line ends in an opening bracket, a
+/// level of indentation is added, and if the line starts with a
+/// closing bracket, one is removed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Brackets;
+
+impl IndentProvider for Brackets {
+    fn indent_for_line(&self, text: &Text, line: u32, cfg: &PrintCfg) -> u32 {
+        let level = cfg.tab_stops.0 as u32;
+        let Some((prev, indent)) = previous_non_blank(text, line, cfg) else {
+            return 0;
+        };
+
+        let opens = text
+            .chars_rev(text.point_at_line(prev + 1))
+            .map(|(_, char)| char)
+            .find(|char| !char.is_whitespace())
+            .is_some_and(|char| ['(', '[', '{'].contains(&char));
+        let closes = text
+            .chars_fwd(text.point_at_line(line))
+            .map(|(_, char)| char)
+            .find(|char| !matches!(char, ' ' | '\t'))
+            .is_some_and(|char| [')', ']', '}'].contains(&char));
+
+        match (opens, closes) {
+            (true, false) => indent + level,
+            (false, true) => indent.saturating_sub(level),
+            _ => indent,
+        }
+    }
+}
+
+/// Sets the [`IndentProvider`] for every [`File`]
+///
+/// [`File`]: crate::widgets::File
+pub fn set_provider(provider: impl IndentProvider) {
+    *PROVIDER.write() = Arc::new(provider);
+}
+
+/// The indentation that a line should have, in columns
+///
+/// This uses the [`IndentProvider`] set with [`set_provider`].
+pub fn indent_for_line(text: &Text, line: u32, cfg: &PrintCfg) -> u32 {
+    let provider = PROVIDER.read().clone();
+    provider.indent_for_line(text, line, cfg)
+}
+
+/// The indentation of a line, or [`None`] if it is blank
+pub fn indent_of(text: &Text, line: u32, cfg: &PrintCfg) -> Option<u32> {
+    let mut indent = 0;
+    for (_, char) in text.chars_fwd(text.point_at_line(line)) {
+        match char {
+            ' ' => indent += 1,
+            '\t' => indent += cfg.tab_stops.spaces_at(indent),
+            '\n' => return None,
+            _ => return Some(indent),
+        }
+    }
+
+    None
+}
+
+/// A [`String`] that indents a line by a number of columns
+///
+/// Tabs are used if the previous non blank line is indented with
+/// them, spaces are used otherwise.
+pub(crate) fn indent_string(text: &Text, line: u32, indent: u32, cfg: &PrintCfg) -> String {
+    let uses_tabs = previous_non_blank(text, line, cfg)
+        .is_some_and(|(prev, _)| text.char_at(text.point_at_line(prev)) == Some('\t'));
+
+    if uses_tabs {
+        let level = cfg.tab_stops.0 as u32;
+        let tabs = "\t".repeat((indent / level) as usize);
+        tabs + &" ".repeat((indent % level) as usize)
+    } else {
+        " ".repeat(indent as usize)
+    }
+}
+
+/// The previous non blank line and its indentation
+fn previous_non_blank(text: &Text, line: u32, cfg: &PrintCfg) -> Option<(u32, u32)> {
+    (0..line)
+        .rev()
+        .find_map(|prev| indent_of(text, prev, cfg).map(|indent| (prev, indent)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Brackets, CopyPrevious, IndentProvider, indent_string};
+    use crate::{cfg::PrintCfg, text::Text};
+
+    #[test]
+    fn copies_previous_non_blank_line() {
+        let text = Text::from("fn main() {\n    let a = 1;\n\n\n");
+        let cfg = PrintCfg::new();
+        assert_eq!(CopyPrevious.indent_for_line(&text, 3, &cfg), 4);
+        assert_eq!(CopyPrevious.indent_for_line(&text, 0, &cfg), 0);
+    }
+
+    #[test]
+    fn indents_after_and_dedents_before_brackets() {
+        let text = Text::from("fn main() {\n\n    let a = [\n    ]\n}\n");
+        let cfg = PrintCfg::new();
+        assert_eq!(Brackets.indent_for_line(&text, 1, &cfg), 4);
+        assert_eq!(Brackets.indent_for_line(&text, 3, &cfg), 4);
+        assert_eq!(Brackets.indent_for_line(&text, 4, &cfg), 0);
+    }
+
+    #[test]
+    fn keeps_indentation_style() {
+        let cfg = PrintCfg::new();
+        let tabs = Text::from("\tfoo\n\n");
+        assert_eq!(indent_string(&tabs, 1, 6, &cfg), "\t  ");
+        let spaces = Text::from("  foo\n\n");
+        assert_eq!(indent_string(&spaces, 1, 6, &cfg), "      ");
+    }
+}
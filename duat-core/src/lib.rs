@@ -297,16 +297,59 @@ use self::{
 
 pub mod cache;
 pub mod cfg;
+pub mod clip_history;
 pub mod cmd;
 pub mod data;
+pub mod filetype;
 pub mod form;
+pub mod format;
 pub mod hooks;
+pub mod hover;
+pub mod indent;
 pub mod mode;
+pub mod opts;
+pub mod registers;
 pub mod session;
+pub mod suggestion;
 pub mod text;
+pub mod toggle;
 pub mod ui;
+pub mod watcher;
 pub mod widgets;
 
+pub mod prelude {
+    //! The stable API for plugin authors
+    //!
+    //! This module contains everything that is commonly needed in
+    //! order to create [`Mode`]s, [`Widget`]s and [`Plugin`]s. Items
+    //! that are exported here are considered part of Duat's public
+    //! API, and will only change in breaking releases. Items that are
+    //! only reachable through other paths may change more freely.
+    //!
+    //! ```rust
+    //! use duat_core::prelude::*;
+    //! ```
+    //!
+    //! [`Mode`]: crate::mode::Mode
+    //! [`Widget`]: crate::widgets::Widget
+    //! [`Plugin`]: crate::Plugin
+    pub use crate::{
+        Plugin,
+        cfg::PrintCfg,
+        cmd::{self, Args, CmdResult, Flags, When},
+        context,
+        data::{Data, RoData, RwData},
+        form::{self, CursorShape, Form, FormId},
+        hooks::{self, Hookable},
+        mode::{
+            self, Cursor, Cursors, EditHelper, IncSearcher, KeyCode, KeyEvent, KeyMod, Mode, key,
+        },
+        text::{Builder, Key, Keys, Point, Tag, Text, TwoPoints, err, hint, ok, text},
+        ui::{Area, PushSpecs, Ui},
+        widgets::{File, View, Widget, WidgetCfg},
+    };
+}
+
 /// A plugin for Duat
 ///
 /// A plugin is something that can be invoked in the configuration
@@ -458,6 +501,16 @@ pub fn periodic_checker(duration: Duration) -> impl Fn() -> bool {
     move || check.fetch_and(false, Ordering::Acquire)
 }
 
+/// Types that shouldn't be nameable outside of Duat
+mod private {
+    /// Prevents a trait from being implemented outside of Duat
+    ///
+    /// Traits that have this as a supertrait can be used as bounds,
+    /// but not implemented, so new implementors can be added without
+    /// breaking anyone.
+    pub trait Sealed {}
+}
+
 /// An error that can be displayed as [`Text`] in Duat
 pub trait DuatError {
     fn into_text(self) -> Text;
@@ -468,6 +521,8 @@ pub trait DuatError {
 pub enum Error<E> {
     /// An alias wasn't just a single word
     AliasNotSingleWord(String),
+    /// A call to a command has a malformed parameter
+    InvalidParameter(String, String),
     /// The caller for a command already pertains to another
     CallerAlreadyExists(String),
     /// No commands have the given caller as one of their own
@@ -502,6 +557,7 @@ impl<E1> Error<E1> {
     pub fn into_other_type<E2>(self) -> Error<E2> {
         match self {
             Self::AliasNotSingleWord(caller) => Error::AliasNotSingleWord(caller),
+            Self::InvalidParameter(call, param) => Error::InvalidParameter(call, param),
             Self::CallerAlreadyExists(caller) => Error::CallerAlreadyExists(caller),
             Self::CallerNotFound(caller) => Error::CallerNotFound(caller),
             Self::CommandFailed(failure) => Error::CommandFailed(failure),
@@ -527,6 +583,9 @@ impl<E> DuatError for Error<E> {
             Self::AliasNotSingleWord(caller) => err!(
                 "The caller " [*a] caller [] " is not a single word."
             ),
+            Self::InvalidParameter(call, param) => err!(
+                "The parameter " [*a] param [] " in " [*a] call [] " is invalid."
+            ),
             Self::CallerAlreadyExists(caller) => err!(
                 "The caller " [*a] caller [] " already exists."
             ),
@@ -552,6 +611,7 @@ impl<E> std::fmt::Debug for Error<E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut debug = f.debug_tuple(match self {
             Self::AliasNotSingleWord(_) => "AliasNotSingleWord",
+            Self::InvalidParameter(..) => "InvalidParameter",
             Self::CallerAlreadyExists(_) => "CallerAlreadyExists",
             Self::CallerNotFound(_) => "CallerNotFound",
             Self::CommandFailed(_) => "CommandFailed",
@@ -567,6 +627,7 @@ impl<E> std::fmt::Debug for Error<E> {
             Self::AliasNotSingleWord(str)
             | Self::CallerAlreadyExists(str)
             | Self::CallerNotFound(str) => debug.field(&str),
+            Self::InvalidParameter(call, param) => debug.field(&call).field(&param),
             Self::CommandFailed(text) => debug.field(&text),
             Self::Empty
             | Self::NoFileYet
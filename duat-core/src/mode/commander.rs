@@ -1,8 +1,24 @@
 use super::{Cursors, EditHelper, KeyCode, KeyEvent, KeyMod, Mode, key};
 use crate::{data::RwData, text::Point, ui::Ui, widgets::CmdLine};
 
-#[derive(Clone)]
-pub struct Command;
+/// The [`Mode`] used to type in the [`CmdLine`]
+///
+/// Besides typing, `<Tab>` and `<S-Tab>` cycle through the
+/// completions of the word before the cursor, if the current
+/// [`CmdLineMode`] has any.
+///
+/// [`CmdLineMode`]: crate::widgets::CmdLineMode
+#[derive(Clone, Default)]
+pub struct Command {
+    completion: Option<Completion>,
+}
+
+impl Command {
+    /// Returns a new [`Command`] mode
+    pub fn new() -> Self {
+        Self { completion: None }
+    }
+}
 
 impl<U: Ui> Mode<U> for Command {
     type Widget = CmdLine<U>;
@@ -15,9 +31,35 @@ impl<U: Ui> Mode<U> for Command {
         cursors: &mut Cursors,
     ) {
         cursors.make_excl();
+
+        let completion = match key {
+            key!(KeyCode::Tab) => self.next_completion(widget, cursors, 1),
+            key!(KeyCode::BackTab, KeyMod::SHIFT) => self.next_completion(widget, cursors, -1),
+            _ => {
+                self.completion = None;
+                None
+            }
+        };
+
         let mut helper = EditHelper::new(widget, area, cursors);
 
         match key {
+            key!(KeyCode::Tab) | key!(KeyCode::BackTab, KeyMod::SHIFT) => {
+                if let Some((0, completion)) = completion {
+                    let len = completion.chars().count() as i32;
+                    helper.edit_main(|e| e.insert(completion));
+                    helper.move_main(|mut m| m.move_hor(len));
+                } else if let Some((replaced, completion)) = completion {
+                    helper.move_main(|mut m| {
+                        let caret = m.caret();
+                        m.move_hor(-(replaced as i32));
+                        m.set_anchor();
+                        m.move_to(caret);
+                    });
+                    helper.edit_main(|e| e.replace(completion));
+                }
+            }
+
             key!(KeyCode::Backspace) => {
                 helper.move_main(|mut m| {
                     m.set_anchor();
@@ -75,6 +117,7 @@ impl<U: Ui> Mode<U> for Command {
                 super::reset();
             }
             key!(KeyCode::Enter) => {
+                widget.write().confirm();
                 cursors.clear();
                 super::reset();
             }
@@ -82,3 +125,49 @@ impl<U: Ui> Mode<U> for Command {
         }
     }
 }
+
+impl Command {
+    /// Moves to another completion, returning it
+    ///
+    /// Also returns how many characters before the cursor should be
+    /// replaced by it.
+    fn next_completion<U: Ui>(
+        &mut self,
+        widget: &RwData<CmdLine<U>>,
+        cursors: &Cursors,
+        by: i32,
+    ) -> Option<(usize, String)> {
+        if let Some(comp) = self.completion.as_mut() {
+            let len = comp.list.len() as i32;
+            comp.index = (comp.index as i32 + by).rem_euclid(len) as usize;
+            let replaced = std::mem::replace(&mut comp.len, comp.list[comp.index].chars().count());
+            return Some((replaced, comp.list[comp.index].clone()));
+        }
+
+        let caret = cursors.get_main()?.caret();
+        let (replaced, list) = widget.read().completions(caret.byte())?;
+        if list.is_empty() {
+            return None;
+        }
+
+        let index = if by > 0 { 0 } else { list.len() - 1 };
+        let completion = list[index].clone();
+
+        self.completion = Some(Completion {
+            len: completion.chars().count(),
+            list,
+            index,
+        });
+
+        Some((replaced, completion))
+    }
+}
+
+/// The completions being cycled through
+#[derive(Clone)]
+struct Completion {
+    list: Vec<String>,
+    index: usize,
+    /// How many characters the current completion takes
+    len: usize,
+}
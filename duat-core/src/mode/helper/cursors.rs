@@ -81,7 +81,9 @@ impl Cursors {
     }
 
     pub fn rotate_main(&mut self, amount: i32) {
-        self.main = (self.main as i32 + amount).rem_euclid(100) as usize
+        if !self.is_empty() {
+            self.main = (self.main as i32 + amount).rem_euclid(self.len() as i32) as usize
+        }
     }
 
     pub fn remove_extras(&mut self) {
@@ -188,6 +190,64 @@ impl Cursors {
         }
     }
 
+    /// Moves every caret and anchor to a new byte, mapped by `f`
+    ///
+    /// `f` must preserve the order of bytes, so the [`Cursor`]s
+    /// remain sorted. This is meant for when the [`Text`] was
+    /// changed without going through the [`Cursor`]s, like when
+    /// applying the output of an external formatter.
+    pub(crate) fn map_bytes(
+        &mut self,
+        f: impl Fn(u32) -> u32,
+        text: &Text,
+        area: &impl Area,
+        cfg: &PrintCfg,
+    ) {
+        let max = text.len().byte();
+        for cursor in self.buf.iter_mut() {
+            let old = *cursor;
+            let caret = text.point_at(f(cursor.byte()).min(max));
+            cursor.move_to(caret, text, area, cfg);
+            if let Some(anchor) = cursor.anchor() {
+                let anchor = text.point_at(f(anchor.byte()).min(max));
+                cursor.swap_ends();
+                cursor.move_to(anchor, text, area, cfg);
+                cursor.swap_ends();
+            }
+            cursor.keep_desired_cols_of(old);
+        }
+    }
+
+    /// Replaces these [`Cursor`]s with copies of the ones in `other`
+    ///
+    /// Whether selections are inclusive is kept, since that is
+    /// decided by the current [`Mode`].
+    ///
+    /// [`Mode`]: crate::mode::Mode
+    pub(crate) fn restore(&mut self, other: &Cursors) {
+        let is_incl = self.is_incl;
+        *self = other.clone();
+        self.is_incl = is_incl;
+        for cursor in self.buf.iter_mut() {
+            cursor.change_i = None;
+        }
+    }
+
+    /// Keeps the desired columns of [`Cursor`]s that were replaced
+    ///
+    /// This is meant for when every [`Cursor`] is rebuilt, like on
+    /// undos and redos. [`Cursor`]s that start where one of `prev`
+    /// did keep its desired columns, so vertical movement isn't
+    /// affected.
+    pub(crate) fn keep_desired_cols_of(&mut self, prev: &Cursors) {
+        for cursor in self.buf.iter_mut() {
+            let start = cursor.start().byte();
+            if let Ok(i) = binary_search_by_key(&prev.buf, start, |c| c.start().byte()) {
+                cursor.keep_desired_cols_of(prev.buf[i]);
+            }
+        }
+    }
+
     pub(super) fn drain(&mut self) -> impl Iterator<Item = (Cursor, bool)> + '_ {
         let orig_main = self.main;
         self.main = 0;
@@ -286,10 +346,14 @@ mod cursor {
                 self.caret = VPoint::default();
                 return;
             };
-            self.caret = VPoint::new(point.min(last), text, area, cfg);
+            let point = text.grapheme_start(point.min(last));
+            self.caret = VPoint::new(point, text, area, cfg);
         }
 
         /// Internal horizontal movement function.
+        ///
+        /// Moves by grapheme clusters, so the caret never lands in
+        /// the middle of one.
         pub fn move_hor(&mut self, by: i32, text: &Text, area: &impl Area, cfg: &PrintCfg) {
             let (Some(last), false) = (text.last_point(), by == 0) else {
                 return;
@@ -302,22 +366,16 @@ mod cursor {
                 last
             } else if by.abs() < 500 {
                 if by > 0 {
-                    let (point, _) = text
-                        .chars_fwd(self.caret())
-                        .take(by as usize + 1)
-                        .last()
-                        .unwrap();
-                    point
+                    let mut graphemes = text.graphemes_fwd(self.caret());
+                    graphemes.nth(by as usize).unwrap_or(last)
                 } else {
-                    let (point, _) = text
-                        .chars_rev(self.caret())
-                        .take(by.unsigned_abs() as usize)
-                        .last()
-                        .unwrap();
-                    point
+                    let mut graphemes = text.graphemes_rev(self.caret());
+                    graphemes
+                        .nth(by.unsigned_abs() as usize - 1)
+                        .unwrap_or_default()
                 }
             } else {
-                text.point_at_char(target)
+                text.grapheme_start(text.point_at_char(target))
             };
 
             self.caret = VPoint::new(point, text, area, cfg);
@@ -342,6 +400,7 @@ mod cursor {
                     })
                     .unwrap_or(last)
             };
+            let point = text.grapheme_start(point);
 
             self.caret.point = point;
             self.caret.vcol = vcol(point, text, area, cfg)
@@ -381,7 +440,9 @@ mod cursor {
                         }
                     })
             } else {
-                let end = text.points_after(self.caret.point).unwrap();
+                let end = text
+                    .points_after(self.caret.point)
+                    .unwrap_or(text.len_points());
 
                 area.rev_print_iter(text.iter_rev(end), cfg)
                     .filter_map(|(Caret { x, wrap, .. }, item)| {
@@ -402,7 +463,7 @@ mod cursor {
                     })
             };
 
-            self.caret.point = point.unwrap_or(last_valid);
+            self.caret.point = text.grapheme_start(point.unwrap_or(last_valid));
             self.caret.vcol = vcol(self.caret.point, text, area, cfg.dont_wrap())
         }
 
@@ -413,6 +474,7 @@ mod cursor {
             area: &impl Area,
             cfg: &PrintCfg,
         ) {
+            let old = *self;
             let shifted_caret = self.caret().shift_by(shift);
             self.move_to(shifted_caret, text, area, cfg);
             if let Some(anchor) = self.anchor() {
@@ -421,6 +483,21 @@ mod cursor {
                 self.move_to(shifted_anchor, text, area, cfg);
                 self.swap_ends();
             }
+            // Edits on other lines shouldn't change where vertical
+            // movement goes.
+            self.keep_desired_cols_of(old);
+        }
+
+        /// Keeps the desired columns of another [`Cursor`]
+        ///
+        /// This only happens for ends that are on the same column as
+        /// the other [`Cursor`]'s, since moving horizontally is what
+        /// sets the desired column.
+        pub(super) fn keep_desired_cols_of(&mut self, other: Cursor) {
+            self.caret.keep_desired_of(other.caret);
+            if let (Some(anchor), Some(other)) = (self.anchor.as_mut(), other.anchor) {
+                anchor.keep_desired_of(other);
+            }
         }
 
         /// Sets the position of the anchor to be the same as the
@@ -478,6 +555,16 @@ mod cursor {
             self.caret.line()
         }
 
+        /// The column that vertical movement tries to reach. Indexed
+        /// at 0.
+        ///
+        /// This is the column of the caret when it last moved
+        /// horizontally, and it is kept when moving over shorter
+        /// lines, as well as when editing other lines.
+        pub fn desired_col(&self) -> u32 {
+            self.caret.dcol
+        }
+
         /// Returns the range between `target` and `anchor`.
         ///
         /// If `anchor` isn't set, returns an empty range on `target`.
@@ -609,6 +696,15 @@ mod cursor {
         fn vcol(&self) -> u32 {
             self.vcol
         }
+
+        /// Keeps the desired columns of `other`, if on the same
+        /// column
+        fn keep_desired_of(&mut self, other: VPoint) {
+            if self.vcol == other.vcol {
+                self.dcol = other.dcol;
+                self.dwcol = other.dwcol;
+            }
+        }
     }
 
     fn vcol(point: Point, text: &Text, area: &impl Area, cfg: IterCfg) -> u32 {
@@ -8,8 +8,10 @@
 pub use self::cursors::{Cursor, Cursors};
 use crate::{
     binary_search_by_key_and_index,
-    cfg::PrintCfg,
+    cfg::{PrintCfg, WordChars},
     data::RwData,
+    indent::{self, indent_of},
+    registers,
     text::{Change, Key, Keys, Point, RegexPattern, Searcher, Tag, Text},
     ui::Area,
     widgets::{File, Widget},
@@ -208,11 +210,13 @@ where
     /// [`edit_main`]: Self::edit_main
     /// [`edit_each`]: Self::edit_each
     pub fn edit_nth(&mut self, edit: impl FnOnce(&mut Editor<A, W>), n: usize) {
+        let mut widget = self.widget.raw_write();
+        widget.text_mut().record_cursors_before(self.cursors);
+
         let Some((mut cursor, was_main)) = self.cursors.remove(n) else {
             panic!("Cursor index {n} out of bounds.");
         };
 
-        let mut widget = self.widget.raw_write();
         let mut shift = (0, 0, 0);
 
         let c_i = {
@@ -261,6 +265,8 @@ where
                 change.shift_by(shift);
             }
         }
+
+        widget.text_mut().record_cursors_after(self.cursors);
     }
 
     /// Edits on the main [`Cursor`]'s selection
@@ -292,9 +298,10 @@ where
     /// [`edit_nth`]: Self::edit_nth
     /// [`edit_main`]: Self::edit_main
     pub fn edit_each(&mut self, mut f: impl FnMut(&mut Editor<A, W>)) {
-        let removed: Vec<_> = self.cursors.drain().collect();
-
         let mut widget = self.widget.raw_write();
+        widget.text_mut().record_cursors_before(self.cursors);
+
+        let removed: Vec<_> = self.cursors.drain().collect();
         let cfg = widget.print_cfg();
         let mut shift = (0, 0, 0);
         let mut sh_from = 0;
@@ -381,6 +388,8 @@ where
                 change.shift_by(shift);
             }
         }
+
+        widget.text_mut().record_cursors_after(self.cursors);
     }
 
     ////////// Moving functions
@@ -479,6 +488,57 @@ where
         }
     }
 
+    /// Keeps only the [`Cursor`]s for which `f` returns `true`
+    ///
+    /// Like in [`move_each`], the [`Mover`] can also alter the
+    /// selection, and intersecting cursors will be merged.
+    ///
+    /// Unlike calling [`Mover::destroy`] within [`move_each`], this
+    /// will never leave the [`Cursors`] empty: if `f` returns `false`
+    /// for every [`Cursor`], the main one will be kept.
+    ///
+    /// ```rust
+    /// # use duat_core::{mode::EditHelper, ui::Area, widgets::File};
+    /// fn keep_non_empty<S>(helper: &mut EditHelper<File, impl Area, S>) {
+    ///     helper.retain_cursors(|m| m.anchor().is_some());
+    /// }
+    /// ```
+    ///
+    /// [`move_each`]: Self::move_each
+    pub fn retain_cursors(&mut self, mut f: impl FnMut(Mover<A, S>) -> bool) {
+        let removed_cursors: Vec<(Cursor, bool)> = self.cursors.drain().collect();
+
+        let mut widget = self.widget.raw_write();
+        let mut main = None;
+
+        for (i, (cursor, is_main)) in removed_cursors.into_iter().enumerate() {
+            if is_main {
+                main = Some(cursor);
+            }
+
+            let mut cursor = Some(cursor);
+            let keep = f(Mover::new(
+                &mut cursor,
+                is_main,
+                widget.text_mut(),
+                self.area,
+                self.cursors,
+                self.cfg,
+                &mut self.searcher,
+            ));
+
+            if keep && let Some(cursor) = cursor {
+                self.cursors.insert(i, is_main, cursor);
+            }
+        }
+
+        if self.cursors.is_empty()
+            && let Some(main) = main
+        {
+            self.cursors.insert(0, true, main);
+        }
+    }
+
     ////////// Text functions
 
     /// Inserts a [`Tag`] at the given position
@@ -508,6 +568,35 @@ where
         self.widget.write().text_mut().remove_tags_of(keys);
     }
 
+    /// Inserts the [suggestion] at the main [`Cursor`], if there is
+    /// one
+    ///
+    /// The main [`Cursor`] is then moved to the end of it. Returns
+    /// `false` if there was no suggestion, or if it was not at the
+    /// main [`Cursor`], in which case it is dismissed.
+    ///
+    /// [suggestion]: crate::suggestion
+    pub fn accept_suggestion(&mut self) -> bool {
+        let main = self.cursors.get_main().map(|main| main.caret());
+        let suggestion = self.widget.write().text_mut().take_suggestion();
+        let Some((_, suggestion)) = suggestion.filter(|(at, _)| Some(*at) == main) else {
+            return false;
+        };
+
+        self.edit_main(|e| e.insert(&suggestion));
+        self.move_main(|mut m| m.move_hor(suggestion.chars().count() as i32));
+        true
+    }
+
+    /// Removes the [suggestion] being shown, if there is one
+    ///
+    /// Returns `false` if there was no suggestion.
+    ///
+    /// [suggestion]: crate::suggestion
+    pub fn dismiss_suggestion(&mut self) -> bool {
+        self.widget.write().text_mut().take_suggestion().is_some()
+    }
+
     /// Begins a new moment
     ///
     /// A new moment indicates a break in the history of the [`Text`],
@@ -535,6 +624,23 @@ where
         widget.update(self.area);
     }
 
+    /// Goes back to the previous selections, without editing
+    ///
+    /// Only selections since the last edit are remembered.
+    pub fn selection_undo(&mut self) {
+        let mut widget = self.widget.raw_write();
+        widget.text_mut().selection_undo(self.cursors);
+    }
+
+    /// Goes forward to the next selections, undoing a
+    /// [`selection_undo`]
+    ///
+    /// [`selection_undo`]: Self::selection_undo
+    pub fn selection_redo(&mut self) {
+        let mut widget = self.widget.raw_write();
+        widget.text_mut().selection_redo(self.cursors);
+    }
+
     /// Returns the length of the [`Text`], in [`Point`]
     pub fn text_len(&self) -> Point {
         self.widget.read().text().len()
@@ -552,10 +658,178 @@ where
         self.cursors.remove_extras();
     }
 
+    /// Rotates the index of the main [`Cursor`] by `amount`
+    ///
+    /// The index wraps around, so rotating forwards from the last
+    /// [`Cursor`] makes the first one the main [`Cursor`].
     pub fn rotate_main(&mut self, amount: i32) {
         self.cursors.rotate_main(amount);
     }
 
+    /// Rotates the contents of the selections by `amount`
+    ///
+    /// With a positive `amount`, the contents of each selection are
+    /// moved to the selections after it, wrapping around at the end.
+    /// The selections themselves are kept around the new contents.
+    pub fn rotate_contents(&mut self, amount: i32) {
+        let mut strs = Vec::with_capacity(self.cursors.len());
+        self.move_each(|mut m| {
+            if m.anchor_is_start() {
+                m.swap_ends();
+            }
+            strs.push(m.selection().concat());
+        });
+
+        let len = strs.len();
+        if len > 1 {
+            strs.rotate_right(amount.rem_euclid(len as i32) as usize);
+            let mut strs = strs.into_iter();
+            self.edit_each(|e| e.replace(strs.next().unwrap()));
+        }
+    }
+
+    /// Selects every match of a regex within each selection
+    ///
+    /// Each selection is replaced by one selection per match, and
+    /// selections without any matches are left as they are.
+    ///
+    /// # Panics
+    ///
+    /// If the regex is not valid, this method will panic.
+    ///
+    /// ```rust
+    /// # use duat_core::{mode::EditHelper, ui::Area, widgets::File};
+    /// fn select_numbers<S>(helper: &mut EditHelper<File, impl Area, S>) {
+    ///     helper.select_matches("[0-9]+");
+    /// }
+    /// ```
+    pub fn select_matches<R>(&mut self, pat: R)
+    where
+        R: RegexPattern<Match = (Point, Point)> + Clone,
+    {
+        self.reselect_each(false, |m, end| {
+            m.search_fwd(pat.clone(), Some(end)).collect()
+        });
+    }
+
+    /// Splits each selection on the matches of a regex
+    ///
+    /// This is the opposite of [`select_matches`], the new selections
+    /// are the pieces of the old ones between each match.
+    ///
+    /// # Panics
+    ///
+    /// If the regex is not valid, this method will panic.
+    ///
+    /// [`select_matches`]: Self::select_matches
+    pub fn split_by<R>(&mut self, pat: R)
+    where
+        R: RegexPattern<Match = (Point, Point)> + Clone,
+    {
+        self.reselect_each(true, |m, end| {
+            m.search_fwd(pat.clone(), Some(end)).collect()
+        });
+    }
+
+    /// Keeps only the selections with a match of a regex
+    ///
+    /// If `keep` is `false`, the selections with a match are removed
+    /// instead. Like with [`retain_cursors`], the main [`Cursor`] is
+    /// kept if no other would be.
+    ///
+    /// # Panics
+    ///
+    /// If the regex is not valid, this method will panic.
+    ///
+    /// [`retain_cursors`]: Self::retain_cursors
+    pub fn keep_matching<R>(&mut self, pat: R, keep: bool)
+    where
+        R: RegexPattern<Match = (Point, Point)> + Clone,
+    {
+        self.retain_cursors(|mut m| {
+            if m.anchor_is_start() {
+                m.swap_ends();
+            }
+            let end = m.selection_end();
+            m.search_fwd(pat.clone(), Some(end)).next().is_some() == keep
+        });
+    }
+
+    /// Replaces each selection with the ranges found by `f`
+    ///
+    /// `f` receives a [`Mover`] with its `caret` on the start of the
+    /// selection, as well as the end of said selection. If `split`
+    /// is `true`, the pieces between the ranges are selected
+    /// instead.
+    fn reselect_each(
+        &mut self,
+        split: bool,
+        mut f: impl FnMut(&mut Mover<A, S>, Point) -> Vec<(Point, Point)>,
+    ) {
+        self.move_each(|mut m| {
+            if m.anchor_is_start() {
+                m.swap_ends();
+            }
+            let (start, end) = (m.caret(), m.selection_end());
+            let matches = f(&mut m, end);
+
+            let ranges = if split {
+                let mut pieces = Vec::new();
+                let mut piece_start = start;
+                for (p0, p1) in matches {
+                    if p0 > piece_start {
+                        pieces.push((piece_start, p0));
+                    }
+                    piece_start = p1;
+                }
+                if end > piece_start {
+                    pieces.push((piece_start, end));
+                }
+                pieces
+            } else {
+                matches
+            };
+
+            for (i, &(p0, p1)) in ranges.iter().enumerate() {
+                m.select_range(p0, p1);
+                if i < ranges.len() - 1 {
+                    m.copy();
+                }
+            }
+        });
+    }
+
+    ////////// Register functions
+
+    /// Copies the selection of each [`Cursor`] to a register
+    ///
+    /// The selections are stored in order, so they can be pasted on
+    /// the same [`Cursor`]s later. See the [`registers`] module for
+    /// more information.
+    pub fn copy_selection_to(&mut self, reg: char) {
+        let mut strs = Vec::with_capacity(self.cursors.len());
+        self.move_each(|m| strs.push(m.selection().concat()));
+        registers::set::<A::Ui>(reg, strs);
+    }
+
+    /// Pastes the contents of a register on each [`Cursor`]
+    ///
+    /// Each [`Cursor`] gets the string yanked by the [`Cursor`] in
+    /// the same position, with the last one being repeated if there
+    /// are less strings than [`Cursor`]s. Like with
+    /// [`Editor::insert`], the strings are placed behind the `caret`.
+    ///
+    /// Returns `false` if the register is empty.
+    pub fn paste_from(&mut self, reg: char) -> bool {
+        let Some(strs) = registers::get(reg) else {
+            return false;
+        };
+
+        let mut iter = strs.iter().chain(std::iter::repeat(strs.last().unwrap()));
+        self.edit_each(|e| e.insert(iter.next().unwrap()));
+        true
+    }
+
     /// The [`Cursors`] in use
     pub fn cursors(&self) -> &Cursors {
         self.cursors
@@ -567,6 +841,39 @@ where
     }
 }
 
+impl<A, S> EditHelper<'_, File, A, S>
+where
+    A: Area,
+{
+    /// Shows a suggestion at the main [`Cursor`]
+    ///
+    /// The suggestion comes from the [`SuggestionProvider`]s of the
+    /// [`File`]. If none of them suggest anything, the previous
+    /// suggestion is removed. Returns whether a suggestion is now
+    /// being shown.
+    ///
+    /// [`SuggestionProvider`]: crate::suggestion::SuggestionProvider
+    pub fn suggest(&mut self) -> bool {
+        let Some(main) = self.cursors.get_main().map(|main| main.caret()) else {
+            return false;
+        };
+
+        let mut file = self.widget.write();
+        let suggestion = file.suggestion_at(main);
+        let text = <File as Widget<A::Ui>>::text_mut(&mut file);
+        match suggestion {
+            Some(suggestion) => {
+                text.set_suggestion(main, suggestion);
+                true
+            }
+            None => {
+                text.take_suggestion();
+                false
+            }
+        }
+    }
+}
+
 impl<'a, A> EditHelper<'a, File, A, Searcher>
 where
     A: Area,
@@ -638,6 +945,55 @@ where
     change_diff: i32,
 }
 
+/// Incremental versions of the selection functions
+///
+/// These use the regex being typed in an [`IncSearch`], so they are
+/// meant to be used by [`IncSearcher`]s.
+///
+/// [`IncSearch`]: crate::widgets::IncSearch
+/// [`IncSearcher`]: crate::mode::IncSearcher
+impl<W, A> EditHelper<'_, W, A, Searcher>
+where
+    W: Widget<A::Ui> + 'static,
+    A: Area,
+{
+    /// Selects every match of the [`IncSearch`] within each selection
+    ///
+    /// Like [`select_matches`], selections without any matches are
+    /// left as they are.
+    ///
+    /// [`IncSearch`]: crate::widgets::IncSearch
+    /// [`select_matches`]: Self::select_matches
+    pub fn select_matches_inc(&mut self) {
+        self.reselect_each(false, |m, end| m.search_inc_fwd(Some(end)).collect());
+    }
+
+    /// Splits each selection on the matches of the [`IncSearch`]
+    ///
+    /// [`IncSearch`]: crate::widgets::IncSearch
+    pub fn split_by_inc(&mut self) {
+        self.reselect_each(true, |m, end| m.search_inc_fwd(Some(end)).collect());
+    }
+
+    /// Keeps only the selections with a match of the [`IncSearch`]
+    ///
+    /// If `keep` is `false`, the selections with a match are removed
+    /// instead. Selections that match the regex as a whole also
+    /// count, even if the regex can't be found inside of them.
+    ///
+    /// [`IncSearch`]: crate::widgets::IncSearch
+    pub fn keep_matching_inc(&mut self, keep: bool) {
+        self.retain_cursors(|mut m| {
+            if m.anchor_is_start() {
+                m.swap_ends();
+            }
+            let end = m.selection_end();
+            let contains_match = m.search_inc_fwd(Some(end)).next().is_some();
+            (contains_match || m.matches_inc()) == keep
+        });
+    }
+}
+
 impl<'a, 'b, A, W> Editor<'a, 'b, A, W>
 where
     A: Area,
@@ -732,6 +1088,42 @@ where
         }
     }
 
+    /// Inserts a new line behind the `caret`, indenting it
+    ///
+    /// The indentation of the new line is decided by the
+    /// [`IndentProvider`] in use. Like with [`insert`], the `caret`
+    /// stays in place, so this returns how many [`char`]s were
+    /// inserted, in order to move past them.
+    ///
+    /// [`IndentProvider`]: indent::IndentProvider
+    /// [`insert`]: Self::insert
+    pub fn insert_newline_indented(&mut self) -> u32 {
+        self.insert('\n');
+
+        let text = self.widget.text();
+        let line = self.cursor.line() + 1;
+        let indent = indent::indent_for_line(text, line, self.cfg);
+        let indent = indent::indent_string(text, line, indent, self.cfg);
+
+        if !indent.is_empty() {
+            let start = text.point_at_line(line);
+            let change = Change::new(indent.clone(), (start, start), text);
+            self.edit(change);
+
+            if let Some(anchor) = self.cursor.anchor()
+                && anchor >= start
+            {
+                let len = indent.chars().count() as i32;
+                self.cursor.swap_ends();
+                self.cursor
+                    .move_hor(len, self.widget.text(), self.area, self.cfg);
+                self.cursor.swap_ends();
+            }
+        }
+
+        1 + indent.chars().count() as u32
+    }
+
     /// Edits the file with a [`Change`]
     fn edit(&mut self, change: Change<String>) {
         let shift = *self.shift;
@@ -901,13 +1293,11 @@ where
     /// one of these chunks, the other `&str` will just be empty.
     ///
     /// [`GapBuffer`]: gapbuf::GapBuffer
+    ///
+    /// If the [`Cursors`] are inclusive, the last [`char`] of the
+    /// selection will be included.
     pub fn selection(&self) -> [&str; 2] {
-        let anchor = self.anchor().unwrap_or(self.caret());
-        let range = if anchor < self.caret() {
-            (anchor, self.caret())
-        } else {
-            (self.caret(), anchor)
-        };
+        let range = self.cursor.unwrap().point_range(self.is_incl(), self.text);
         self.text.strs_in_range(range)
     }
 
@@ -1009,6 +1399,267 @@ where
         self.text.search_rev(pat, self.caret(), start).unwrap()
     }
 
+    ////////// Text object functions
+
+    /// Returns the [`Point`] of the bracket matching the one on the
+    /// `caret`
+    ///
+    /// The recognized pairs are `()`, `[]`, `{}` and `<>`. If the
+    /// `caret` is not on one of these, or if the bracket has no
+    /// match, returns [`None`].
+    pub fn matching_bracket(&self) -> Option<Point> {
+        let char = self.char();
+        let &(open, close) = BRACKETS.iter().find(|(o, c)| *o == char || *c == char)?;
+
+        if char == open {
+            pair_end(self.text.chars_fwd(self.caret()), open, close)
+        } else {
+            let iter = [(self.caret(), char)].into_iter();
+            pair_end(iter.chain(self.text.chars_rev(self.caret())), close, open)
+        }
+    }
+
+    /// Returns the [`Point`]s of the `open` and `close` [`char`]s
+    /// surrounding the `caret`
+    ///
+    /// Nesting is taken into account, so the returned pair is the
+    /// innermost one that contains the `caret`. If the `caret` is on
+    /// `open` or `close`, that delimiter is considered part of the
+    /// pair.
+    ///
+    /// If `open` and `close` are the same [`char`] (e.g. quotes),
+    /// the pair will be formed by the closest one at or before the
+    /// `caret` and the next one after it.
+    ///
+    /// ```rust
+    /// # use duat_core::{mode::EditHelper, ui::Area, widgets::File};
+    /// fn select_inside_parens<S>(helper: &mut EditHelper<File, impl Area, S>) {
+    ///     helper.move_each(|mut m| {
+    ///         if let Some((start, end)) = m.enclosing_pair('(', ')') {
+    ///             m.move_to(start);
+    ///             m.move_hor(1);
+    ///             m.set_anchor();
+    ///             m.move_to(end);
+    ///             m.move_hor(-1);
+    ///         }
+    ///     })
+    /// }
+    /// ```
+    pub fn enclosing_pair(&self, open: char, close: char) -> Option<(Point, Point)> {
+        let caret = self.caret();
+        let mut rev = [(caret, self.char())]
+            .into_iter()
+            .chain(self.text.chars_rev(caret));
+
+        let start = if open == close {
+            rev.find_map(|(p, c)| (c == open).then_some(p))?
+        } else {
+            let mut depth = 0;
+            rev.find_map(|(p, c)| {
+                if c == close && p != caret {
+                    depth += 1;
+                } else if c == open {
+                    if depth == 0 {
+                        return Some(p);
+                    }
+                    depth -= 1;
+                }
+                None
+            })?
+        };
+
+        let end = if open == close {
+            let mut fwd = self.text.chars_fwd(start).skip(1);
+            fwd.find_map(|(p, c)| (c == close).then_some(p))?
+        } else {
+            pair_end(self.text.chars_fwd(start), open, close)?
+        };
+
+        (end >= caret).then_some((start, end))
+    }
+
+    /// Returns the [`Point`]s of the first and last [`char`]s of the
+    /// run around the `caret` where `f` returns `true`
+    ///
+    /// Returns [`None`] if `f` returns `false` for the [`char`] on
+    /// the `caret`.
+    pub fn enclosing(&self, f: impl Fn(char) -> bool) -> Option<(Point, Point)> {
+        if !f(self.char()) {
+            return None;
+        }
+
+        let caret = self.caret();
+        let start = self.text.chars_rev(caret).take_while(|(_, c)| f(*c)).last();
+        let end = self.text.chars_fwd(caret).take_while(|(_, c)| f(*c)).last();
+
+        Some((
+            start.map_or(caret, |(p, _)| p),
+            end.map_or(caret, |(p, _)| p),
+        ))
+    }
+
+    ////////// Text object selection
+
+    /// Selects the word on the `caret`
+    ///
+    /// A word is a run of [`char`]s of the same [`CharCategory`]. If
+    /// `is_big`, it is instead any run of non whitespace [`char`]s.
+    /// If `around`, the spaces and tabs after the word are selected
+    /// as well.
+    ///
+    /// Returns `false`, without selecting anything, if the `caret` is
+    /// on whitespace.
+    pub fn select_word(&mut self, is_big: bool, around: bool) -> bool {
+        let w_chars = self.cfg.word_chars;
+        let cat = CharCategory::of(self.char(), w_chars);
+        if cat == CharCategory::Space {
+            return false;
+        }
+
+        let Some((start, end)) = self.enclosing(|c| match is_big {
+            true => CharCategory::of(c, w_chars) != CharCategory::Space,
+            false => CharCategory::of(c, w_chars) == cat,
+        }) else {
+            return false;
+        };
+
+        let end = match around {
+            true => (self.text.chars_fwd(end).skip(1))
+                .take_while(|(_, c)| [' ', '\t'].contains(c))
+                .last()
+                .map_or(end, |(p, _)| p),
+            false => end,
+        };
+
+        self.select(start, end);
+        true
+    }
+
+    /// Selects the paragraph on the `caret`
+    ///
+    /// A paragraph is a run of lines that aren't blank. If `around`,
+    /// the blank lines after it are selected as well.
+    ///
+    /// Returns `false`, without selecting anything, if the `caret` is
+    /// on a blank line.
+    pub fn select_paragraph(&mut self, around: bool) -> bool {
+        let line = self.caret().line();
+        let is_blank = |line| indent_of(self.text, line, &self.cfg).is_none();
+        if is_blank(line) {
+            return false;
+        }
+
+        let max = self.text.len().line();
+        let first = (0..line).rev().take_while(|l| !is_blank(*l)).last();
+        let last = (line + 1..=max).take_while(|l| !is_blank(*l)).last();
+        let (first, mut last) = (first.unwrap_or(line), last.unwrap_or(line));
+
+        if around {
+            last = (last + 1..=max)
+                .take_while(|l| is_blank(*l))
+                .last()
+                .unwrap_or(last);
+        }
+
+        self.select_lines(first, last);
+        true
+    }
+
+    /// Selects the inside of the `open` and `close` pair around the
+    /// `caret`
+    ///
+    /// The pair is found in the same way as in
+    /// [`Mover::enclosing_pair`]. If `around`, `open` and `close`
+    /// are selected as well.
+    ///
+    /// Returns `false`, without selecting anything, if there is no
+    /// such pair, or if there is nothing inside of it.
+    pub fn select_surrounding_pair(&mut self, open: char, close: char, around: bool) -> bool {
+        let Some((start, end)) = self.enclosing_pair(open, close) else {
+            return false;
+        };
+
+        if around {
+            self.select(start, end);
+        } else if end.char() > start.char() + 1 {
+            let start = self.text.point_at_char(start.char() + 1);
+            let end = self.text.point_at_char(end.char() - 1);
+            self.select(start, end);
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    /// Selects the lines around the `caret` that are at least as
+    /// indented as its own
+    ///
+    /// Blank lines within the block are part of it. If `around`, so
+    /// are the blank lines right before and after it.
+    ///
+    /// Returns `false`, without selecting anything, if the `caret` is
+    /// on a blank line.
+    pub fn select_indent_block(&mut self, around: bool) -> bool {
+        let line = self.caret().line();
+        let indent = |line| indent_of(self.text, line, &self.cfg);
+        let Some(base) = indent(line) else {
+            return false;
+        };
+
+        let in_block = |l: &u32| indent(*l).is_none_or(|indent| indent >= base);
+        let max = self.text.len().line();
+        let first = (0..line).rev().take_while(in_block).last();
+        let last = (line + 1..=max).take_while(in_block).last();
+        let (mut first, mut last) = (first.unwrap_or(line), last.unwrap_or(line));
+
+        if !around {
+            // The line of the caret isn't blank, so these stop there.
+            while indent(first).is_none() {
+                first += 1;
+            }
+            while indent(last).is_none() {
+                last -= 1;
+            }
+        }
+
+        self.select_lines(first, last);
+        true
+    }
+
+    /// Selects from `start` to `end`, including the [`char`] on `end`
+    fn select(&mut self, start: Point, end: Point) {
+        self.move_to(start);
+        self.set_anchor();
+        self.move_to(end);
+        if !self.is_incl() {
+            self.move_hor(1);
+        }
+    }
+
+    /// Selects from `start` to `end`, excluding the [`char`] on `end`
+    fn select_range(&mut self, start: Point, end: Point) {
+        if end > start {
+            self.select(start, self.text.point_at_char(end.char() - 1));
+        } else {
+            self.move_to(start);
+            self.unset_anchor();
+        }
+    }
+
+    /// Selects whole lines, including the last one's `'\n'`
+    fn select_lines(&mut self, first: u32, last: u32) {
+        let start = self.text.point_at_line(first);
+        let end = if last < self.text.len().line() {
+            let next = self.text.point_at_line(last + 1);
+            self.text.point_at_char(next.char() - 1)
+        } else {
+            self.text.last_point().unwrap_or(start)
+        };
+
+        self.select(start, end);
+    }
+
     ////////// Cursor queries
 
     /// Returns the `caret`
@@ -1021,11 +1672,28 @@ where
         self.cursor.unwrap().anchor()
     }
 
+    /// The [`Point`] right after the end of the selection
+    fn selection_end(&self) -> Point {
+        self.cursor
+            .unwrap()
+            .point_range(self.is_incl(), self.text)
+            .1
+    }
+
     /// Returns `true` if the `anchor` exists before the `caret`
     pub fn anchor_is_start(&self) -> bool {
         self.anchor().is_none_or(|anchor| anchor < self.caret())
     }
 
+    /// The column that vertical movement tries to reach
+    ///
+    /// This is the column the `caret` was on when it last moved
+    /// horizontally, which may be past the end of its current line.
+    /// Indexed at 0.
+    pub fn desired_col(&self) -> u32 {
+        self.cursor.unwrap().desired_col()
+    }
+
     /// Whether or not this is the main [`Cursor`]
     pub fn is_main(&self) -> bool {
         self.is_main
@@ -1084,3 +1752,53 @@ where
         self.inc_searcher.matches(str)
     }
 }
+
+/// The category of a [`char`], as far as words are concerned
+///
+/// This is what separates words in [`Mover::select_word`], and it is
+/// meant to be used by [`Mode`]s, so that they all agree on what a
+/// word is.
+///
+/// [`Mode`]: super::Mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharCategory {
+    /// A [`char`] in the [`WordChars`]
+    Word,
+    /// Any [`char`] that is neither a word [`char`] nor whitespace
+    Special,
+    /// A space, tab or new line
+    Space,
+}
+
+impl CharCategory {
+    /// The [`CharCategory`] of a [`char`], given the [`WordChars`]
+    pub fn of(char: char, w_chars: WordChars) -> Self {
+        if w_chars.contains(char) {
+            CharCategory::Word
+        } else if [' ', '\t', '\n'].contains(&char) {
+            CharCategory::Space
+        } else {
+            CharCategory::Special
+        }
+    }
+}
+
+/// The pairs of brackets recognized by [`Mover::matching_bracket`]
+const BRACKETS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+/// Finds the end of a pair, starting on the [`char`] that opens it
+fn pair_end(
+    mut iter: impl Iterator<Item = (Point, char)>,
+    open: char,
+    close: char,
+) -> Option<Point> {
+    let mut depth = 0;
+    iter.find_map(|(p, c)| {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+        }
+        (depth == 0).then_some(p)
+    })
+}
@@ -2,10 +2,12 @@ use core::str;
 
 pub use crossterm::event::{KeyCode, KeyEvent, KeyModifiers as KeyMod};
 
+pub(crate) use self::substitute::{ConfirmSubstitutions, lines_of, substitute_all, substitutions};
 pub use self::{
     commander::Command,
-    helper::{Cursor, Cursors, EditHelper, Editor, Mover},
+    helper::{CharCategory, Cursor, Cursors, EditHelper, Editor, Mover},
     inc_search::{ExtendFwd, ExtendRev, Fwd, IncSearcher, Rev},
+    mouse::{MouseButton, MouseEvent, MouseEventKind, default_mouse_handling},
     regular::Regular,
     remap::*,
     switch::*,
@@ -15,12 +17,15 @@ use crate::{data::RwData, ui::Ui, widgets::Widget};
 mod commander;
 mod helper;
 mod inc_search;
+mod mouse;
 mod regular;
 mod remap;
+mod substitute;
 
 mod switch {
     use std::{
-        any::TypeId,
+        any::{Any, TypeId},
+        collections::HashMap,
         sync::{
             Arc, LazyLock,
             atomic::{AtomicBool, Ordering},
@@ -30,21 +35,51 @@ mod switch {
     use crossterm::event::KeyEvent;
     use parking_lot::Mutex;
 
-    use super::Mode;
+    use super::{Cursors, Mode, MouseEvent, MouseEventKind};
     use crate::{
-        context, duat_name, file_entry,
-        hooks::{self, ModeSwitched},
-        ui::{Ui, Window},
+        context,
+        data::RwData,
+        duat_name, file_entry,
+        hooks::{self, ChangeRecorded, ModeSwitched, OnFocusChange},
+        text,
+        ui::{Area, Ui, Window},
         widget_entry,
-        widgets::{CmdLine, CmdLineMode, File, Node},
+        widgets::{CmdLine, CmdLineMode, File, Node, Widget},
     };
 
     static PRINTING_IS_STOPPED: AtomicBool = AtomicBool::new(false);
     static SEND_KEY: LazyLock<Mutex<Box<dyn FnMut(KeyEvent) + Send + Sync>>> =
         LazyLock::new(|| Mutex::new(Box::new(|_| {})));
+    static SEND_MOUSE: LazyLock<Mutex<Box<dyn FnMut(MouseEvent) + Send + Sync>>> =
+        LazyLock::new(|| Mutex::new(Box::new(|_| {})));
+    static SEND_PASTE: LazyLock<Mutex<Box<dyn FnMut(String) + Send + Sync>>> =
+        LazyLock::new(|| Mutex::new(Box::new(|_| {})));
     static RESET_MODE: LazyLock<Mutex<Arc<dyn Fn() + Send + Sync>>> =
         LazyLock::new(|| Mutex::new(Arc::new(|| {})));
     static SET_MODE: Mutex<Option<Box<dyn FnOnce() + Send + Sync>>> = Mutex::new(None);
+    static CUR_KEY: Mutex<Option<KeyEvent>> = Mutex::new(None);
+    static RECORDING: Mutex<Option<Vec<KeyEvent>>> = Mutex::new(None);
+    static LAST_CHANGE: Mutex<Option<RepeatableChange>> = Mutex::new(None);
+    static QUEUED_KEYS: Mutex<Vec<KeyEvent>> = Mutex::new(Vec::new());
+    static QUEUED_FNS: Mutex<Vec<Arc<dyn Fn() + Send + Sync>>> = Mutex::new(Vec::new());
+    static MACRO: Mutex<Option<(char, Vec<KeyEvent>)>> = Mutex::new(None);
+    static MACROS: LazyLock<Mutex<HashMap<char, Vec<KeyEvent>>>> = LazyLock::new(Mutex::default);
+    static REPLAYING: Mutex<Vec<char>> = Mutex::new(Vec::new());
+    static CUR_MODE: Mutex<Option<Arc<dyn Fn() + Send + Sync>>> = Mutex::new(None);
+    static FOCUS_HISTORY: Mutex<Vec<Box<dyn Any + Send>>> = Mutex::new(Vec::new());
+    static SWITCHING_BACK: AtomicBool = AtomicBool::new(false);
+    const MAX_FOCUS_HISTORY: usize = 32;
+
+    /// A change that can be replayed by [`mode::repeat_last_change`]
+    ///
+    /// [`mode::repeat_last_change`]: repeat_last_change
+    #[derive(Clone)]
+    pub enum RepeatableChange {
+        /// The keys that were sent in order to make the change
+        Keys(Vec<KeyEvent>),
+        /// A function that applies the change on the current cursors
+        Fn(Arc<dyn Fn() + Send + Sync>),
+    }
 
     /// Whether or not the [`Mode`] has changed
     pub fn was_set() -> Option<Box<dyn FnOnce() + Send + Sync>> {
@@ -137,6 +172,221 @@ mod switch {
         }
     }
 
+    /// Switches back to the previously focused widget
+    ///
+    /// Every time the focus changes, the widget that was focused, as
+    /// well as the [`Mode`] it was in, are pushed to a focus history.
+    /// This function pops the last entry of that history, and
+    /// switches back to it, restoring its [`Mode`].
+    ///
+    /// Unlike [`mode::reset`], which always goes back to the default
+    /// [`Mode`], this lets a widget spawned by another (e.g. a
+    /// picker, opened from the [`CmdLine`]) return to whatever
+    /// spawned it when dismissed.
+    ///
+    /// Returns `false` if there is no widget to switch back to.
+    ///
+    /// [`mode::reset`]: reset
+    pub fn switch_back<U: Ui>() -> bool {
+        let Some(FocusEntry { node, set_mode }) = pop_focus_entry::<U>() else {
+            return false;
+        };
+
+        *SET_MODE.lock() = Some(Box::new(move || {
+            SWITCHING_BACK.store(true, Ordering::Release);
+            switch_widget(node);
+            SWITCHING_BACK.store(false, Ordering::Release);
+
+            match set_mode {
+                Some(set_mode) => set_mode(),
+                None => RESET_MODE.lock().clone()(),
+            }
+        }));
+
+        true
+    }
+
+    /// Starts recording a repeatable change
+    ///
+    /// The key currently being sent, as well as every key sent after
+    /// it, will be recorded, until [`mode::finish_change`] is called.
+    /// If a change was already being recorded, it is discarded.
+    ///
+    /// This lets a [`Mode`] declare a sequence of keys (e.g. an
+    /// operator, followed by a motion, or all the keys typed in an
+    /// insert mode) as a single unit, which can then be replayed.
+    ///
+    /// [`mode::finish_change`]: finish_change
+    pub fn start_change() {
+        *RECORDING.lock() = Some(CUR_KEY.lock().into_iter().collect());
+    }
+
+    /// Finishes recording a repeatable change
+    ///
+    /// This will trigger the [`ChangeRecorded`] hook with the keys
+    /// that were recorded since [`mode::start_change`] was called. If
+    /// no change was being recorded, nothing happens.
+    ///
+    /// [`mode::start_change`]: start_change
+    pub fn finish_change() {
+        if let Some(keys) = RECORDING.lock().take() {
+            *LAST_CHANGE.lock() = Some(RepeatableChange::Keys(keys.clone()));
+            hooks::trigger::<ChangeRecorded>(keys);
+        }
+    }
+
+    /// Stops recording a change, without keeping it
+    ///
+    /// This should be called when a change is aborted midway, like
+    /// when an operator is not followed by a valid motion.
+    pub fn cancel_change() {
+        *RECORDING.lock() = None;
+    }
+
+    /// Records a change through a semantic description of it
+    ///
+    /// Instead of replaying keys, [`mode::repeat_last_change`] will
+    /// call `f`, which should apply the change on the current
+    /// [`Cursors`] of the active widget. Any change being recorded
+    /// through [`mode::start_change`] is discarded.
+    ///
+    /// [`mode::repeat_last_change`]: repeat_last_change
+    /// [`mode::start_change`]: start_change
+    /// [`Cursors`]: crate::mode::Cursors
+    pub fn record_change(f: impl Fn() + Send + Sync + 'static) {
+        *RECORDING.lock() = None;
+        *LAST_CHANGE.lock() = Some(RepeatableChange::Fn(Arc::new(f)));
+    }
+
+    /// The last change that was recorded, if there is one
+    pub fn last_change() -> Option<RepeatableChange> {
+        LAST_CHANGE.lock().clone()
+    }
+
+    /// Replays the last recorded change at the current cursors
+    ///
+    /// Like with [`mode::send_keys`], the change is only replayed
+    /// after the current key is done being handled, so this function
+    /// can be called from within [`Mode::send_key`].
+    ///
+    /// Returns `false` if no change has been recorded yet.
+    ///
+    /// [`mode::send_keys`]: send_keys
+    pub fn repeat_last_change() -> bool {
+        match LAST_CHANGE.lock().clone() {
+            Some(RepeatableChange::Keys(keys)) => send_keys(keys),
+            Some(RepeatableChange::Fn(f)) => QUEUED_FNS.lock().push(f),
+            None => return false,
+        }
+
+        true
+    }
+
+    /// Whether a change is currently being recorded
+    pub fn is_recording_change() -> bool {
+        RECORDING.lock().is_some()
+    }
+
+    /// Starts recording a macro in a register
+    ///
+    /// Every key sent to the active [`Mode`] from now on will be
+    /// recorded, until [`mode::stop_recording`] is called. Keys sent
+    /// by replaying other macros are not recorded, only the keys that
+    /// replayed them. If a macro was already being recorded, it is
+    /// discarded.
+    ///
+    /// [`mode::stop_recording`]: stop_recording
+    pub fn record_macro(register: char) {
+        *MACRO.lock() = Some((register, Vec::new()));
+    }
+
+    /// Stops recording a macro, storing it in its register
+    ///
+    /// The key currently being sent, which is presumably the one that
+    /// stopped the recording, is not part of the macro. Returns the
+    /// register of the macro, or [`None`] if no macro was being
+    /// recorded.
+    pub fn stop_recording() -> Option<char> {
+        let (register, mut keys) = MACRO.lock().take()?;
+        if keys.last().is_some_and(|key| Some(*key) == *CUR_KEY.lock()) {
+            keys.pop();
+        }
+        MACROS.lock().insert(register, keys);
+
+        Some(register)
+    }
+
+    /// The register of the macro being recorded, if there is one
+    pub fn recording_macro() -> Option<char> {
+        MACRO.lock().as_ref().map(|(register, _)| *register)
+    }
+
+    /// The keys of the macro stored in a register
+    pub fn macro_keys(register: char) -> Option<Vec<KeyEvent>> {
+        MACROS.lock().get(&register).cloned()
+    }
+
+    /// Replays the macro in a register a number of times
+    ///
+    /// Like with [`mode::send_keys`], the macro is only replayed
+    /// after the current key is done being handled, so this
+    /// function can be called from within [`Mode::send_key`].
+    /// Every change made by the replay is part of the same moment
+    /// in the history of the current [`File`], so it can be
+    /// undone all at once.
+    ///
+    /// Returns `false` if the register is empty, or if its macro is
+    /// already being replayed, which would never end.
+    ///
+    /// [`mode::send_keys`]: send_keys
+    pub fn replay_macro<U: Ui>(register: char, count: usize) -> bool {
+        if REPLAYING.lock().contains(&register) {
+            return false;
+        }
+        let Some(keys) = macro_keys(register).filter(|keys| !keys.is_empty()) else {
+            return false;
+        };
+
+        QUEUED_FNS.lock().push(Arc::new(move || {
+            let new_moment = || {
+                if let Ok(file) = context::cur_file::<U>() {
+                    file.mutate_file(|file| file.text_mut().new_moment());
+                }
+            };
+
+            new_moment();
+            let was_grouped = text::group_moments(true);
+            let was_stopped = is_printing_stopped();
+            REPLAYING.lock().push(register);
+
+            stop_printing();
+            for _ in 0..count {
+                for key in keys.iter() {
+                    send_key_to(*key);
+                }
+            }
+
+            REPLAYING.lock().pop();
+            if !was_stopped {
+                resume_printing();
+            }
+            text::group_moments(was_grouped);
+            new_moment();
+        }));
+
+        true
+    }
+
+    /// Sends [`KeyEvent`]s to the active [`Mode`]
+    ///
+    /// The keys are sent right after the current key is done being
+    /// handled, so this function can be called from within
+    /// [`Mode::send_key`]. Printing is stopped until the last of them
+    /// is sent.
+    pub fn send_keys(keys: impl IntoIterator<Item = KeyEvent>) {
+        QUEUED_KEYS.lock().extend(keys);
+    }
+
     /// Whether or not printing has been stopped
     ///
     /// This is done when sending multiple keys at the same time
@@ -156,21 +406,177 @@ mod switch {
 
     /// Switches to a certain widget
     pub(super) fn switch_widget<U: Ui>(node: Node<U>) {
-        if let Ok(widget) = context::cur_widget::<U>() {
-            widget.node().on_unfocus();
+        let prev = context::cur_widget::<U>().ok().map(|widget| widget.node());
+        if let Some(prev) = &prev {
+            prev.on_unfocus();
         }
 
         context::set_cur(node.as_file(), node.clone());
 
         node.on_focus();
+
+        if let Some(prev) = prev
+            && !prev.ptr_eq(node.widget())
+        {
+            if !SWITCHING_BACK.load(Ordering::Acquire) {
+                push_focus_entry(prev.clone());
+            }
+            // Reprinting, in case they have to be dimmed or undimmed.
+            for node in [&prev, &node] {
+                if node.data_is::<File>() {
+                    node.update_and_print();
+                }
+            }
+            hooks::trigger::<OnFocusChange<U>>((prev.widget().clone(), node.widget().clone()));
+        }
+    }
+
+    /// An entry in the focus history
+    struct FocusEntry<U: Ui> {
+        node: Node<U>,
+        set_mode: Option<Arc<dyn Fn() + Send + Sync>>,
+    }
+
+    // SAFETY: The entries are only ever accessed from the main thread,
+    // in the same way that the current widget is.
+    unsafe impl<U: Ui> Send for FocusEntry<U> {}
+
+    /// Pushes the [`Node`] that was just unfocused to the history
+    fn push_focus_entry<U: Ui>(node: Node<U>) {
+        let mut history = FOCUS_HISTORY.lock();
+        history.retain(|entry| {
+            entry
+                .downcast_ref::<FocusEntry<U>>()
+                .is_some_and(|entry| !entry.node.ptr_eq(node.widget()))
+        });
+
+        let set_mode = CUR_MODE.lock().clone();
+        history.push(Box::new(FocusEntry { node, set_mode }));
+
+        if history.len() > MAX_FOCUS_HISTORY {
+            history.remove(0);
+        }
+    }
+
+    /// Pops the last entry whose [`Node`] is still open
+    fn pop_focus_entry<U: Ui>() -> Option<FocusEntry<U>> {
+        let windows = context::windows::<U>().read();
+        let mut history = FOCUS_HISTORY.lock();
+
+        while let Some(entry) = history.pop() {
+            let Ok(entry) = entry.downcast::<FocusEntry<U>>() else {
+                continue;
+            };
+            if windows
+                .iter()
+                .flat_map(Window::nodes)
+                .any(|node| node.ptr_eq(entry.node.widget()))
+            {
+                return Some(*entry);
+            }
+        }
+
+        None
     }
 
     /// Sends the [`KeyEvent`] to the active [`Mode`]
     pub(super) fn send_key_to(key: KeyEvent) {
+        if let Some(keys) = RECORDING.lock().as_mut() {
+            keys.push(key);
+        }
+        if let Some((_, keys)) = MACRO.lock().as_mut()
+            && REPLAYING.lock().is_empty()
+        {
+            keys.push(key);
+        }
+        *CUR_KEY.lock() = Some(key);
+
         SEND_KEY.lock()(key);
         if let Some(set_mode) = was_set() {
             set_mode()
         }
+
+        let fns = std::mem::take(&mut *QUEUED_FNS.lock());
+        for f in fns {
+            f()
+        }
+
+        let queued = std::mem::take(&mut *QUEUED_KEYS.lock());
+        if !queued.is_empty() {
+            let was_stopped = is_printing_stopped();
+            let end = queued.len() - 1;
+            for (i, key) in queued.into_iter().enumerate() {
+                if i < end {
+                    stop_printing()
+                } else if !was_stopped {
+                    resume_printing()
+                }
+
+                send_key_to(key);
+            }
+        }
+    }
+
+    /// Sends a [`MouseEvent`] to whatever is under it
+    ///
+    /// If that is the active widget, it is sent to the active
+    /// [`Mode`]. If it is another [`File`], clicking switches to it.
+    /// Otherwise, it is sent to the widget itself.
+    pub(crate) fn send_mouse<U: Ui>(event: MouseEvent) {
+        let node = {
+            let windows = context::windows::<U>().read();
+            let window = &windows[context::cur_window()];
+            let Some(node) = window.node_at(event.coord()) else {
+                return;
+            };
+            node.clone()
+        };
+
+        // Clicking on a floating widget brings it to the top.
+        if let MouseEventKind::Down(_) = event.kind()
+            && node.area().is_floating()
+        {
+            context::windows::<U>()
+                .mutate(|windows| windows[context::cur_window()].raise_floating(node.area()));
+        }
+
+        let point = node.raw_inspect(|widget| {
+            let cfg = widget.print_cfg();
+            node.area()
+                .point_at_coord(widget.text(), event.coord(), cfg)
+        });
+        let event = event.with_point(point);
+
+        let is_active =
+            context::cur_widget::<U>().is_ok_and(|cur| node.ptr_eq(cur.node().widget()));
+        if is_active {
+            SEND_MOUSE.lock()(event);
+            if let Some(set_mode) = was_set() {
+                set_mode()
+            }
+        } else if node.data_is::<File>() {
+            if let MouseEventKind::Down(_) = event.kind() {
+                *SET_MODE.lock() = Some(Box::new(move || {
+                    switch_widget(node);
+                    RESET_MODE.lock().clone()()
+                }));
+            }
+        } else {
+            node.on_mouse(event);
+        }
+    }
+
+    /// Sends pasted text to the active [`Mode`]
+    ///
+    /// Line endings are normalized to `'\n'` before doing so, since
+    /// some terminals send `'\r'` instead.
+    pub(crate) fn send_paste(text: String) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+        SEND_PASTE.lock()(text);
+        if let Some(set_mode) = was_set() {
+            set_mode()
+        }
     }
 
     /// Inner function that sends [`KeyEvent`]s
@@ -181,12 +587,63 @@ mod switch {
 
         widget.mutate_data_as(|widget, area, cursors| {
             let mut c = cursors.write();
-            mode.send_key(key, widget, area, &mut c)
+            record_selections::<U>(widget, &c);
+            mode.send_key(key, widget, area, &mut c);
+            record_selections::<U>(widget, &c);
+        });
+
+        update_hints(mode);
+    }
+
+    /// Inner function that sends [`MouseEvent`]s
+    fn send_mouse_fn<U: Ui>(mode: &mut impl Mode<U>, event: MouseEvent) {
+        let Ok(widget) = context::cur_widget::<U>() else {
+            return;
+        };
+
+        widget.mutate_data_as(|widget, area, cursors| {
+            let mut c = cursors.write();
+            record_selections::<U>(widget, &c);
+            mode.send_mouse(event, widget, area, &mut c);
+            record_selections::<U>(widget, &c);
+        });
+
+        update_hints(mode);
+    }
+
+    /// Records the state of the [`Cursors`] in the selection history
+    fn record_selections<U: Ui>(widget: &RwData<impl Widget<U>>, cursors: &Cursors) {
+        widget.raw_write().text_mut().record_selections(cursors);
+    }
+
+    /// Inner function that sends pasted text
+    fn send_paste_fn<U: Ui>(mode: &mut impl Mode<U>, text: String) {
+        let Ok(widget) = context::cur_widget::<U>() else {
+            return;
+        };
+
+        widget.mutate_data_as(|widget, area, cursors| {
+            let mut c = cursors.write();
+            mode.on_paste(text, widget, area, &mut c)
         });
+
+        update_hints(mode);
+    }
+
+    /// Publishes the [hints] of a [`Mode`], if they have changed
+    ///
+    /// [hints]: Mode::hints
+    fn update_hints<U: Ui>(mode: &impl Mode<U>) {
+        let hints = mode.hints();
+        if *context::key_hints().read() != hints {
+            *context::key_hints().write() = hints;
+        }
     }
 
     /// Inner function that sets [`Mode`]s
     fn set_mode_fn<M: Mode<U>, U: Ui>(mut mode: M) {
+        let initial = mode.clone();
+
         // If we are on the correct widget, no switch is needed.
         if context::cur_widget::<U>().unwrap().type_id() != TypeId::of::<M::Widget>() {
             let windows = context::windows().read();
@@ -214,6 +671,7 @@ mod switch {
         });
 
         crate::mode::set_send_key::<M, U>();
+        update_hints(&mode);
 
         context::mode_name().mutate(|mode| {
             let new_mode = duat_name::<M>();
@@ -221,7 +679,18 @@ mod switch {
             *mode = new_mode;
         });
 
-        *SEND_KEY.lock() = Box::new(move |key| send_key_fn::<U>(&mut mode, key));
+        *CUR_MODE.lock() = Some(Arc::new(move || set_mode_fn::<M, U>(initial.clone())));
+
+        let mode = Arc::new(Mutex::new(mode));
+        *SEND_KEY.lock() = Box::new({
+            let mode = mode.clone();
+            move |key| send_key_fn::<U>(&mut *mode.lock(), key)
+        });
+        *SEND_MOUSE.lock() = Box::new({
+            let mode = mode.clone();
+            move |event| send_mouse_fn::<U>(&mut *mode.lock(), event)
+        });
+        *SEND_PASTE.lock() = Box::new(move |text| send_paste_fn::<U>(&mut *mode.lock(), text));
     }
 }
 
@@ -549,6 +1018,65 @@ pub trait Mode<U: Ui>: Sized + Clone + Send + Sync + 'static {
     /// [`Text`]: crate::text::Text
     #[allow(unused)]
     fn on_switch(&mut self, widget: &RwData<Self::Widget>, area: &U::Area, cursors: &mut Cursors) {}
+
+    /// Sends a [`MouseEvent`] to this [`Mode`]
+    ///
+    /// This is only called for events on the widget of this
+    /// [`Mode`]. By default, it calls [`default_mouse_handling`],
+    /// which moves the main cursor and selects with clicks and
+    /// drags.
+    fn send_mouse(
+        &mut self,
+        event: MouseEvent,
+        widget: &RwData<Self::Widget>,
+        area: &U::Area,
+        cursors: &mut Cursors,
+    ) {
+        default_mouse_handling(event, widget, area, cursors)
+    }
+
+    /// Receives text that was pasted into the [`Ui`]
+    ///
+    /// By default, the text is inserted at every cursor, as a single
+    /// moment in the history of the [`Text`], such that it can be
+    /// undone in one go. The cursors are then moved to the end of it.
+    ///
+    /// Since the text is not sent as individual keys, [`Mode`]s that
+    /// do something special with keys, like auto-indentation, will
+    /// leave pasted text as is.
+    ///
+    /// [`Text`]: crate::text::Text
+    fn on_paste(
+        &mut self,
+        text: String,
+        widget: &RwData<Self::Widget>,
+        area: &U::Area,
+        cursors: &mut Cursors,
+    ) {
+        let len = text.chars().count() as i32;
+        let mut helper = EditHelper::new(widget, area, cursors);
+
+        helper.new_moment();
+        helper.edit_each(|e| e.insert(&text));
+        helper.move_each(|mut m| {
+            m.unset_anchor();
+            m.move_hor(len)
+        });
+        helper.new_moment();
+    }
+
+    /// Key hints relevant to the current state of this [`Mode`]
+    ///
+    /// These are pairs of keys and what they do, which will be shown
+    /// by the [`KeyHints`] widget. They are requested whenever this
+    /// [`Mode`] is set, as well as after every key is sent to it, so
+    /// a [`Mode`] that waits on more keys (e.g. a "goto" key) can
+    /// return hints for the keys that can follow.
+    ///
+    /// [`KeyHints`]: crate::widgets::KeyHints
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
 }
 
 /// This is a macro for matching keys in patterns:
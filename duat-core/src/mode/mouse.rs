@@ -0,0 +1,118 @@
+//! Mouse events, and how they reach [`Mode`]s and widgets
+//!
+//! The [`Ui`] sends [`MouseEvent`]s with the cell of the screen where
+//! they happened. Duat then figures out which widget is on that cell,
+//! and which [`Point`] of its [`Text`] is printed there:
+//!
+//! - If it is the active widget, the event is sent to the active
+//!   [`Mode`], through [`Mode::send_mouse`];
+//! - If it is another [`File`], clicking on it switches to it;
+//! - Otherwise, the event is sent to the widget, through
+//!   [`Widget::on_mouse`].
+//!
+//! By default, [`Mode`]s handle mouse events with
+//! [`default_mouse_handling`], which lets you click to move the main
+//! cursor, drag to select, and scroll to move vertically.
+//!
+//! [`Mode`]: super::Mode
+//! [`Mode::send_mouse`]: super::Mode::send_mouse
+//! [`Ui`]: crate::ui::Ui
+//! [`Text`]: crate::text::Text
+//! [`File`]: crate::widgets::File
+//! [`Widget::on_mouse`]: crate::widgets::Widget::on_mouse
+pub use crossterm::event::{MouseButton, MouseEventKind};
+
+use super::{Cursors, EditHelper, KeyMod};
+use crate::{data::RwData, text::Point, ui::Area, widgets::Widget};
+
+/// How many lines are moved with every scroll of the wheel
+const SCROLL_LINES: i32 = 3;
+
+/// An event from the mouse
+///
+/// Besides the [kind] of event and where on the screen it happened,
+/// this also holds the [`Point`] of the [`Text`] that was printed on
+/// that cell, if there was one.
+///
+/// [kind]: MouseEventKind
+/// [`Text`]: crate::text::Text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+    kind: MouseEventKind,
+    coord: (u32, u32),
+    modifiers: KeyMod,
+    point: Option<Point>,
+}
+
+impl MouseEvent {
+    /// Returns a new [`MouseEvent`], on the `(x, y)` cell
+    pub fn new(kind: MouseEventKind, coord: (u32, u32), modifiers: KeyMod) -> Self {
+        Self { kind, coord, modifiers, point: None }
+    }
+
+    /// What happened
+    pub fn kind(&self) -> MouseEventKind {
+        self.kind
+    }
+
+    /// The cell where it happened, as `(x, y)`
+    pub fn coord(&self) -> (u32, u32) {
+        self.coord
+    }
+
+    /// The modifiers that were held down
+    pub fn modifiers(&self) -> KeyMod {
+        self.modifiers
+    }
+
+    /// The [`Point`] of the [`Text`] printed on the cell
+    ///
+    /// [`Text`]: crate::text::Text
+    pub fn point(&self) -> Option<Point> {
+        self.point
+    }
+
+    pub(crate) fn with_point(self, point: Option<Point>) -> Self {
+        Self { point, ..self }
+    }
+}
+
+/// The default way that [`Mode`]s handle [`MouseEvent`]s
+///
+/// - Clicking with the left button moves the main cursor, removing
+///   all others;
+/// - Dragging with the left button selects from where the click
+///   happened;
+/// - Scrolling moves the main cursor vertically.
+///
+/// [`Mode`]: super::Mode
+pub fn default_mouse_handling<W, A>(
+    event: MouseEvent,
+    widget: &RwData<W>,
+    area: &A,
+    cursors: &mut Cursors,
+) where
+    W: Widget<A::Ui>,
+    A: Area,
+{
+    let mut helper = EditHelper::new(widget, area, cursors);
+
+    match (event.kind, event.point) {
+        (MouseEventKind::Down(MouseButton::Left), Some(point)) => {
+            helper.remove_extra_cursors();
+            helper.move_main(|mut m| {
+                m.unset_anchor();
+                m.move_to(point);
+            });
+        }
+        (MouseEventKind::Drag(MouseButton::Left), Some(point)) => helper.move_main(|mut m| {
+            if m.anchor().is_none() {
+                m.set_anchor();
+            }
+            m.move_to(point);
+        }),
+        (MouseEventKind::ScrollDown, _) => helper.move_main(|mut m| m.move_ver(SCROLL_LINES)),
+        (MouseEventKind::ScrollUp, _) => helper.move_main(|mut m| m.move_ver(-SCROLL_LINES)),
+        _ => {}
+    }
+}
@@ -21,15 +21,25 @@ impl<U: Ui> super::Mode<U> for Regular {
         cursors.make_excl();
         let mut helper = EditHelper::new(widget, area, cursors);
 
+        if let key!(KeyCode::Tab) = key
+            && helper.accept_suggestion()
+        {
+            return;
+        }
+        helper.dismiss_suggestion();
+
         match key {
             // Characters
             key!(KeyCode::Char(char), KeyMod::SHIFT | KeyMod::NONE) => {
                 helper.edit_each(|e| e.insert(char));
                 helper.move_each(|mut m| m.move_hor(1));
+                helper.suggest();
             }
             key!(KeyCode::Enter) => {
-                helper.edit_each(|e| e.insert('\n'));
-                helper.move_each(|mut m| m.move_hor(1));
+                let mut lens = Vec::with_capacity(helper.cursors().len());
+                helper.edit_each(|e| lens.push(e.insert_newline_indented()));
+                let mut lens = lens.into_iter();
+                helper.move_each(|mut m| m.move_hor(lens.next().unwrap_or(1) as i32));
             }
 
             // Text Removal
@@ -52,6 +62,7 @@ impl<U: Ui> super::Mode<U> for Regular {
                         m.unset_anchor();
                     }
                 });
+                helper.suggest();
             }
             key!(KeyCode::Delete) => {
                 let mut anchors = Vec::with_capacity(helper.cursors().len());
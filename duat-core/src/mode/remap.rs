@@ -19,7 +19,7 @@ mod global {
     use crossterm::event::{KeyCode, KeyEvent, KeyModifiers as KeyMod};
     use parking_lot::Mutex;
 
-    use super::{Gives, Remapper};
+    use super::{Gives, Remap, Remapper};
     use crate::{
         data::RoData,
         mode::Mode,
@@ -97,6 +97,21 @@ mod global {
         REMAPPER.remap::<M, U>(str_to_keys(take), give.into_gives(), true);
     }
 
+    /// Maps or aliases a sequence of keys, only on [`File`]s of a
+    /// filetype
+    ///
+    /// [`File`]: crate::widgets::File
+    pub(crate) fn map_for_filetype<M: Mode<U>, U: Ui>(
+        filetype: String,
+        take: &str,
+        give: impl AsGives<U>,
+        is_alias: bool,
+    ) {
+        let mut remap = Remap::new(str_to_keys(take), give.into_gives(), is_alias);
+        remap.filetype = Some(filetype);
+        REMAPPER.add::<M>(remap);
+    }
+
     pub fn cur_sequence() -> RoData<(Vec<KeyEvent>, bool)> {
         RoData::from(&*REMAPPER.cur_seq)
     }
@@ -360,16 +375,21 @@ impl Remapper {
 
     /// Maps a sequence of characters to another
     fn remap<M: Mode<U>, U: Ui>(&self, take: Vec<KeyEvent>, give: Gives, is_alias: bool) {
-        let ty = TypeId::of::<M>();
-        let remap = Remap::new(take, give, is_alias);
+        self.add::<M>(Remap::new(take, give, is_alias));
+    }
 
+    /// Adds a [`Remap`] to a [`Mode`]
+    ///
+    /// Only [`Remap`]s of the same filetype can intersect each other.
+    fn add<M: 'static>(&self, remap: Remap) {
+        let ty = TypeId::of::<M>();
         let mut remaps = self.remaps.lock();
 
         if let Some((_, remaps)) = remaps.iter_mut().find(|(m, _)| ty == *m) {
-            if remaps
-                .iter()
-                .all(|r| !(r.takes.starts_with(&remap.takes) || remap.takes.starts_with(&r.takes)))
-            {
+            if remaps.iter().all(|r| {
+                r.filetype != remap.filetype
+                    || !(r.takes.starts_with(&remap.takes) || remap.takes.starts_with(&r.takes))
+            }) {
                 remaps.push(remap);
             }
         } else {
@@ -379,6 +399,10 @@ impl Remapper {
 
     /// Sends a key to be remapped or not
     fn send_key<M: Mode<U>, U: Ui>(&self, key: KeyEvent) {
+        let filetype = context::cur_file::<U>()
+            .ok()
+            .and_then(|file| file.inspect(|file, _, _| file.filetype().map(str::to_string)));
+
         let remaps = self.remaps.lock();
         let Some((_, remaps)) = remaps.iter().find(|(m, _)| TypeId::of::<M>() == *m) else {
             mode::send_key_to(key);
@@ -389,7 +413,13 @@ impl Remapper {
         let (cur_seq, is_alias) = &mut *cur_seq;
         cur_seq.push(key);
 
-        if let Some(remap) = remaps.iter().find(|r| r.takes.starts_with(cur_seq)) {
+        // Remaps of the filetype take precedence over general ones.
+        if let Some(remap) = remaps
+            .iter()
+            .filter(|r| r.filetype.is_none() || r.filetype == filetype)
+            .filter(|r| r.takes.starts_with(cur_seq))
+            .min_by_key(|r| r.filetype.is_none())
+        {
             *is_alias = remap.is_alias;
             if remap.takes.len() == cur_seq.len() {
                 if remap.is_alias {
@@ -457,11 +487,12 @@ struct Remap {
     takes: Vec<KeyEvent>,
     gives: Gives,
     is_alias: bool,
+    filetype: Option<String>,
 }
 
 impl Remap {
     pub fn new(takes: Vec<KeyEvent>, gives: Gives, is_alias: bool) -> Self {
-        Self { takes, gives, is_alias }
+        Self { takes, gives, is_alias, filetype: None }
     }
 }
 
@@ -0,0 +1,285 @@
+//! Substitution of regex matches, used by the `substitute` command
+//!
+//! The matches are found ahead of time, so they can either be
+//! replaced all at once, or be confirmed one by one, through the
+//! [`ConfirmSubstitutions`] [`Mode`].
+//!
+//! [`Mode`]: super::Mode
+use regex_automata::meta::Regex;
+
+use super::{Cursors, EditHelper, KeyCode, KeyEvent, key};
+use crate::{
+    context,
+    data::RwData,
+    text::{Builder, Point, Text, ok},
+    ui::{Area, Ui},
+    widgets::File,
+};
+
+/// A match of the `substitute` command, and what should replace it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Substitution {
+    start: u32,
+    end: u32,
+    new: String,
+}
+
+/// Finds the [`Substitution`]s within some ranges of a [`Text`]
+///
+/// Matching is done line by line, and only the first match on each
+/// line is substituted, unless `all` is `true`. The `replacement`
+/// can refer to capture groups, like `$1` or `${name}`.
+pub(crate) fn substitutions(
+    text: &Text,
+    ranges: impl IntoIterator<Item = (Point, Point)>,
+    regex: &Regex,
+    replacement: &str,
+    all: bool,
+) -> Vec<Substitution> {
+    let mut subs = Vec::new();
+
+    for (start, end) in ranges {
+        let str = text.strs_in_range((start, end)).concat();
+        let mut byte = start.byte();
+
+        for line in str.split_inclusive('\n') {
+            let haystack = line.strip_suffix('\n').unwrap_or(line);
+            let captures = regex.captures_iter(haystack);
+
+            for caps in captures.take(if all { usize::MAX } else { 1 }) {
+                let m = caps.get_match().unwrap();
+                let mut new = String::new();
+                caps.interpolate_string_into(haystack, replacement, &mut new);
+
+                subs.push(Substitution {
+                    start: byte + m.start() as u32,
+                    end: byte + m.end() as u32,
+                    new,
+                });
+            }
+
+            byte += line.len() as u32;
+        }
+    }
+
+    // Selections may touch each other, leading to repeated matches.
+    subs.sort_unstable_by_key(|sub| (sub.start, sub.end));
+    subs.dedup_by_key(|sub| sub.start);
+    subs
+}
+
+/// Replaces every [`Substitution`] at once
+///
+/// Returns a function to map bytes from before the substitutions to
+/// bytes after them, in order to move [`Cursor`]s.
+///
+/// [`Cursor`]: super::Cursor
+pub(crate) fn substitute_all<'s>(
+    text: &mut Text,
+    subs: &'s [Substitution],
+) -> impl Fn(u32) -> u32 + 's {
+    for sub in subs.iter().rev() {
+        let (start, end) = (text.point_at(sub.start), text.point_at(sub.end));
+        text.replace_range((start, end), &sub.new);
+    }
+    text.new_moment();
+
+    move |byte: u32| {
+        let mut shift = 0i64;
+        for sub in subs.iter().take_while(|sub| sub.start <= byte) {
+            let new_len = sub.new.len() as i64;
+            if byte < sub.end {
+                let within = (byte - sub.start) as i64;
+                return (sub.start as i64 + shift + within.min(new_len)) as u32;
+            }
+            shift += new_len - (sub.end - sub.start) as i64;
+        }
+        (byte as i64 + shift) as u32
+    }
+}
+
+/// The number of distinct lines with [`Substitution`]s
+pub(crate) fn lines_of(text: &Text, subs: &[Substitution]) -> usize {
+    let mut lines: Vec<u32> = subs
+        .iter()
+        .map(|sub| text.point_at(sub.start).line())
+        .collect();
+    lines.dedup();
+    lines.len()
+}
+
+/// A [`Mode`] to confirm each match of the `substitute` command
+///
+/// Every match is selected in turn, and the following keys are
+/// accepted:
+///
+/// - `y`: Replaces the match;
+/// - `n`: Skips the match;
+/// - `a`: Replaces this match and all the following ones;
+/// - `q` or `<Esc>`: Stops, keeping the matches that were already
+///   replaced;
+///
+/// [`Mode`]: super::Mode
+#[derive(Clone)]
+pub(crate) struct ConfirmSubstitutions {
+    subs: Vec<Substitution>,
+    cur: usize,
+    shift: i64,
+    replaced: usize,
+}
+
+impl ConfirmSubstitutions {
+    /// Returns a new [`ConfirmSubstitutions`]
+    pub(crate) fn new(subs: Vec<Substitution>) -> Self {
+        Self { subs, cur: 0, shift: 0, replaced: 0 }
+    }
+
+    /// The current match, shifted by the previous replacements
+    fn cur_points(&self, widget: &RwData<File>) -> Option<(Point, Point, &str)> {
+        let sub = self.subs.get(self.cur)?;
+        let text = widget.read();
+        let text = text.text();
+        let start = text.point_at((sub.start as i64 + self.shift) as u32);
+        let end = text.point_at((sub.end as i64 + self.shift) as u32);
+
+        Some((start, end, &sub.new))
+    }
+
+    /// Replaces the current match, moving on to the next one
+    fn replace_cur(&mut self, helper: &mut EditHelper<File, impl Area, ()>, widget: &RwData<File>) {
+        let Some((start, end, new)) = self.cur_points(widget) else {
+            return;
+        };
+        let new = new.to_string();
+
+        helper.edit_main(|e| e.replace(&new));
+        self.shift += new.len() as i64 - (end.byte() - start.byte()) as i64;
+        self.replaced += 1;
+        self.cur += 1;
+    }
+
+    /// Selects the current match, returning `false` if there is none
+    fn select_cur(
+        &self,
+        helper: &mut EditHelper<File, impl Area, ()>,
+        widget: &RwData<File>,
+    ) -> bool {
+        let Some((start, end, _)) = self.cur_points(widget) else {
+            return false;
+        };
+
+        helper.remove_extra_cursors();
+        helper.move_main(|mut m| {
+            m.unset_anchor();
+            m.move_to(start);
+            if end > start {
+                m.set_anchor();
+                m.move_to(end);
+            }
+        });
+
+        true
+    }
+
+    /// Leaves this [`Mode`], notifying about the replacements
+    ///
+    /// [`Mode`]: super::Mode
+    fn finish(&self, helper: &mut EditHelper<File, impl Area, ()>) {
+        helper.new_moment();
+        helper.move_main(|mut m| {
+            m.unset_anchor();
+        });
+
+        let mut builder = Builder::new();
+        ok!(
+            builder, [] "Replaced " [*a] { self.replaced } [] " of " [*a] { self.subs.len() }
+            [] " matches."
+        );
+        context::notify(builder.finish());
+
+        super::reset();
+    }
+}
+
+impl<U: Ui> super::Mode<U> for ConfirmSubstitutions {
+    type Widget = File;
+
+    fn send_key(
+        &mut self,
+        key: KeyEvent,
+        widget: &RwData<Self::Widget>,
+        area: &U::Area,
+        cursors: &mut Cursors,
+    ) {
+        let mut helper = EditHelper::new(widget, area, cursors);
+
+        match key {
+            key!(KeyCode::Char('y')) => self.replace_cur(&mut helper, widget),
+            key!(KeyCode::Char('n')) => self.cur += 1,
+            key!(KeyCode::Char('a')) => {
+                while self.select_cur(&mut helper, widget) {
+                    self.replace_cur(&mut helper, widget);
+                }
+            }
+            key!(KeyCode::Char('q')) | key!(KeyCode::Esc) => self.cur = self.subs.len(),
+            _ => return,
+        }
+
+        if !self.select_cur(&mut helper, widget) {
+            self.finish(&mut helper);
+        }
+    }
+
+    fn on_switch(&mut self, widget: &RwData<Self::Widget>, area: &U::Area, cursors: &mut Cursors) {
+        cursors.make_excl();
+        let mut helper = EditHelper::new(widget, area, cursors);
+        helper.new_moment();
+
+        if !self.select_cur(&mut helper, widget) {
+            self.finish(&mut helper);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex_automata::meta::Regex;
+
+    use super::{substitute_all, substitutions};
+    use crate::text::Text;
+
+    fn substitute(text: &str, pat: &str, replacement: &str, all: bool) -> (String, usize) {
+        let mut text = Text::from(text);
+        let regex = Regex::new(pat).unwrap();
+        let range = (text.point_at(0), text.len());
+        let subs = substitutions(&text, [range], &regex, replacement, all);
+
+        substitute_all(&mut text, &subs);
+        (text.to_string(), subs.len())
+    }
+
+    #[test]
+    fn first_match_on_each_line() {
+        let (text, count) = substitute("a a\nb a a\n", "a", "c", false);
+        assert_eq!(text, "c a\nb c a");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn all_matches_with_captures() {
+        let (text, count) = substitute("x=1, y=2\nz=3\n", r"(\w)=(\d)", "$2=$1", true);
+        assert_eq!(text, "1=x, 2=y\n3=z");
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn maps_bytes_after_substitutions() {
+        let mut text = Text::from("ab ab ab\n");
+        let regex = Regex::new("ab").unwrap();
+        let range = (text.point_at(0), text.len());
+        let subs = substitutions(&text, [range], &regex, "xyz", true);
+
+        let map = substitute_all(&mut text, &subs);
+        assert_eq!([map(0), map(1), map(2), map(3), map(8)], [0, 1, 3, 4, 11]);
+    }
+}
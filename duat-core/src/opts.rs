@@ -0,0 +1,344 @@
+//! A registry of typed options
+//!
+//! Unlike the [`PrintCfg`], which only concerns itself with how
+//! [`Text`] is printed, options can be declared by anyone, be it
+//! Duat itself, [`Plugin`]s or the user, and changed at runtime
+//! through the `set` command:
+//!
+//! ```rust
+//! # use duat_core::opts::{self, Scope};
+//! opts::declare_bool("spell", false, Scope::File);
+//! opts::declare_int("scroll-speed", 3, 1..=20, Scope::Global);
+//! opts::declare_enum("theme", "dark", &["dark", "light"], Scope::Global);
+//!
+//! assert_eq!(opts::get_int("scroll-speed"), Some(3));
+//! ```
+//!
+//! Then, from the command line, you could call `set spell`, `set
+//! nospell`, `set scroll-speed=5` or `set theme light`. Calling `set
+//! theme?` will show the current value of an option.
+//!
+//! Options with [`Scope::File`] are set on the active [`File`], and
+//! fall back to the global value when not set in it.
+//!
+//! Whenever an option changes, the [`OnOptionChanged`] hook is
+//! triggered, so [`Widget`]s and [`Plugin`]s can react to it.
+//!
+//! [`PrintCfg`]: crate::cfg::PrintCfg
+//! [`Plugin`]: crate::Plugin
+//! [`Widget`]: crate::widgets::Widget
+//! [`OnOptionChanged`]: crate::hooks::OnOptionChanged
+use std::{collections::HashMap, fmt::Display, ops::RangeInclusive, sync::LazyLock};
+
+use parking_lot::Mutex;
+
+use crate::{
+    hooks::{self, OnOptionChanged},
+    text::{Text, err},
+    widgets::File,
+};
+
+static OPTIONS: LazyLock<Mutex<HashMap<&'static str, Opt>>> = LazyLock::new(Mutex::default);
+
+/// The value of an option
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptValue {
+    /// A boolean option, set with `set {name}` and `set no{name}`
+    Bool(bool),
+    /// An integer option, within a range
+    Int(i64),
+    /// An arbitrary string
+    Str(String),
+    /// One of a list of variants
+    Enum(&'static str),
+}
+
+impl Display for OptValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptValue::Bool(bool) => write!(f, "{bool}"),
+            OptValue::Int(int) => write!(f, "{int}"),
+            OptValue::Str(str) => write!(f, "{str}"),
+            OptValue::Enum(variant) => write!(f, "{variant}"),
+        }
+    }
+}
+
+/// Where an option is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// The option has one value for all of Duat
+    Global,
+    /// The option can have a value for each [`File`], falling back
+    /// to the global value
+    File,
+}
+
+/// Declares a boolean option
+///
+/// If the option was already declared, this does nothing, so
+/// [`Plugin`]s and the user can declare the same option.
+///
+/// [`Plugin`]: crate::Plugin
+pub fn declare_bool(name: &'static str, default: bool, scope: Scope) {
+    declare(name, OptKind::Bool, OptValue::Bool(default), scope);
+}
+
+/// Declares an integer option, which must be within `range`
+///
+/// If the option was already declared, this does nothing, so
+/// [`Plugin`]s and the user can declare the same option.
+///
+/// [`Plugin`]: crate::Plugin
+pub fn declare_int(name: &'static str, default: i64, range: RangeInclusive<i64>, scope: Scope) {
+    let default = default.clamp(*range.start(), *range.end());
+    declare(name, OptKind::Int(range), OptValue::Int(default), scope);
+}
+
+/// Declares a string option
+///
+/// If the option was already declared, this does nothing, so
+/// [`Plugin`]s and the user can declare the same option.
+///
+/// [`Plugin`]: crate::Plugin
+pub fn declare_str(name: &'static str, default: impl ToString, scope: Scope) {
+    declare(
+        name,
+        OptKind::Str,
+        OptValue::Str(default.to_string()),
+        scope,
+    );
+}
+
+/// Declares an option that must be one of `variants`
+///
+/// If the option was already declared, this does nothing, so
+/// [`Plugin`]s and the user can declare the same option.
+///
+/// # Panics
+///
+/// Panics if `default` is not one of the `variants`.
+///
+/// [`Plugin`]: crate::Plugin
+pub fn declare_enum(
+    name: &'static str,
+    default: &'static str,
+    variants: &'static [&'static str],
+    scope: Scope,
+) {
+    assert!(
+        variants.contains(&default),
+        "{default} is not a variant of the {name} option"
+    );
+    declare(
+        name,
+        OptKind::Enum(variants),
+        OptValue::Enum(default),
+        scope,
+    );
+}
+
+/// The global value of an option, if it was declared
+pub fn get(name: &str) -> Option<OptValue> {
+    OPTIONS.lock().get(name).map(|opt| opt.value.clone())
+}
+
+/// The value of an option for a [`File`]
+///
+/// If the option has [`Scope::Global`], or wasn't set in the
+/// [`File`], this is the same as [`opts::get`].
+///
+/// [`opts::get`]: get
+pub fn get_for(file: &File, name: &str) -> Option<OptValue> {
+    let options = OPTIONS.lock();
+    let opt = options.get(name)?;
+    match opt.scope {
+        Scope::Global => Some(opt.value.clone()),
+        Scope::File => file
+            .vars()
+            .get::<OptValue>(&var_key(name))
+            .cloned()
+            .or_else(|| Some(opt.value.clone())),
+    }
+}
+
+/// The global value of a boolean option
+pub fn get_bool(name: &str) -> Option<bool> {
+    match get(name)? {
+        OptValue::Bool(bool) => Some(bool),
+        _ => None,
+    }
+}
+
+/// The global value of an integer option
+pub fn get_int(name: &str) -> Option<i64> {
+    match get(name)? {
+        OptValue::Int(int) => Some(int),
+        _ => None,
+    }
+}
+
+/// The global value of a string or enum option
+pub fn get_str(name: &str) -> Option<String> {
+    match get(name)? {
+        OptValue::Str(str) => Some(str),
+        OptValue::Enum(variant) => Some(variant.to_string()),
+        _ => None,
+    }
+}
+
+/// Sets the global value of an option
+///
+/// Returns an [`Err`] if the option wasn't declared, or if the
+/// value is invalid for it.
+pub fn set(name: &str, value: OptValue) -> Result<(), Text> {
+    let name = {
+        let mut options = OPTIONS.lock();
+        let Some((&name, _)) = options.get_key_value(name) else {
+            return Err(err!("There is no option " [*a] name [] "."));
+        };
+        let opt = options.get_mut(name).unwrap();
+        opt.kind.validate(&value)?;
+
+        if opt.value == value {
+            return Ok(());
+        }
+        opt.value = value.clone();
+        name
+    };
+
+    hooks::trigger::<OnOptionChanged>((name, value));
+    Ok(())
+}
+
+/// Sets the value of an option for a [`File`]
+///
+/// If the option has [`Scope::Global`], this is the same as
+/// [`opts::set`].
+///
+/// [`opts::set`]: set
+pub fn set_for(file: &mut File, name: &str, value: OptValue) -> Result<(), Text> {
+    let name = {
+        let options = OPTIONS.lock();
+        let Some((&name, opt)) = options.get_key_value(name) else {
+            return Err(err!("There is no option " [*a] name [] "."));
+        };
+        if opt.scope == Scope::Global {
+            drop(options);
+            return set(name, value);
+        }
+        opt.kind.validate(&value)?;
+        name
+    };
+
+    let key = var_key(name);
+    if file.vars().get::<OptValue>(&key) != Some(&value) {
+        file.vars_mut().set(key, value.clone());
+        hooks::trigger::<OnOptionChanged>((name, value));
+    }
+
+    Ok(())
+}
+
+/// Parses a value for an option, from a string
+///
+/// Empty strings are `true` for boolean options.
+pub fn parse(name: &str, value: &str) -> Result<OptValue, Text> {
+    let options = OPTIONS.lock();
+    let Some(opt) = options.get(name) else {
+        return Err(err!("There is no option " [*a] name [] "."));
+    };
+    opt.kind.parse(name, value)
+}
+
+/// The [`Scope`] of an option, if it was declared
+pub fn scope_of(name: &str) -> Option<Scope> {
+    OPTIONS.lock().get(name).map(|opt| opt.scope)
+}
+
+/// The names of all declared options
+pub fn names() -> Vec<&'static str> {
+    let mut names: Vec<_> = OPTIONS.lock().keys().copied().collect();
+    names.sort_unstable();
+    names
+}
+
+/// Declares an option, if it wasn't declared already
+fn declare(name: &'static str, kind: OptKind, value: OptValue, scope: Scope) {
+    OPTIONS
+        .lock()
+        .entry(name)
+        .or_insert(Opt { kind, value, scope });
+}
+
+/// The key of an option in the [`Vars`] of a [`File`]
+///
+/// [`Vars`]: crate::widgets::Vars
+fn var_key(name: &str) -> String {
+    format!("opt:{name}")
+}
+
+struct Opt {
+    kind: OptKind,
+    value: OptValue,
+    scope: Scope,
+}
+
+/// The type of an option, and its restrictions
+enum OptKind {
+    Bool,
+    Int(RangeInclusive<i64>),
+    Str,
+    Enum(&'static [&'static str]),
+}
+
+impl OptKind {
+    /// Returns an [`Err`] if the value doesn't fit this kind
+    fn validate(&self, value: &OptValue) -> Result<(), Text> {
+        match (self, value) {
+            (OptKind::Bool, OptValue::Bool(_)) | (OptKind::Str, OptValue::Str(_)) => Ok(()),
+            (OptKind::Int(range), OptValue::Int(int)) => match range.contains(int) {
+                true => Ok(()),
+                false => Err(err!(
+                    [*a] int [] " is not within " [*a] { range.start() } []
+                    " and " [*a] { range.end() } [] "."
+                )),
+            },
+            (OptKind::Enum(variants), OptValue::Enum(variant)) => {
+                match variants.contains(variant) {
+                    true => Ok(()),
+                    false => Err(err!([*a] variant [] " is not a valid variant.")),
+                }
+            }
+            _ => Err(err!("Wrong type of value for the option.")),
+        }
+    }
+
+    /// Parses an [`OptValue`] of this kind
+    fn parse(&self, name: &str, value: &str) -> Result<OptValue, Text> {
+        let value = match self {
+            OptKind::Bool => match value {
+                "" | "true" | "on" | "yes" => OptValue::Bool(true),
+                "false" | "off" | "no" => OptValue::Bool(false),
+                _ => return Err(err!([*a] value [] " is not a boolean.")),
+            },
+            OptKind::Int(_) => match value.parse() {
+                Ok(int) => OptValue::Int(int),
+                Err(_) => return Err(err!([*a] value [] " is not an integer.")),
+            },
+            OptKind::Str => OptValue::Str(value.to_string()),
+            OptKind::Enum(variants) => match variants.iter().find(|v| **v == value) {
+                Some(variant) => OptValue::Enum(variant),
+                None => {
+                    let variants = variants.join(", ");
+                    return Err(err!(
+                        "The option " [*a] name [] " must be one of " [*a] variants [] "."
+                    ));
+                }
+            },
+        };
+
+        self.validate(&value)?;
+        Ok(value)
+    }
+}
@@ -0,0 +1,84 @@
+//! Registers to yank text into and paste it from
+//!
+//! Each register is identified by a [`char`], and holds one string
+//! per cursor that was yanking at the time. [`Mode`]s will usually
+//! interact with them through [`EditHelper::copy_selection_to`] and
+//! [`EditHelper::paste_from`]:
+//!
+//! ```rust
+//! # use duat_core::{mode::EditHelper, registers, ui::Area, widgets::File};
+//! # fn test<S>(helper: &mut EditHelper<File, impl Area, S>) {
+//! helper.copy_selection_to(registers::DEFAULT);
+//! helper.paste_from(registers::DEFAULT);
+//! # }
+//! ```
+//!
+//! Yanking into the [`CLIPBOARD`] register also sets the system
+//! clipboard, as long as the [`Ui`] supports it. With
+//! [`sync_default_with_clipboard`], the same is done for the
+//! [`DEFAULT`] register.
+//!
+//! [`Mode`]: crate::mode::Mode
+//! [`EditHelper::copy_selection_to`]: crate::mode::EditHelper::copy_selection_to
+//! [`EditHelper::paste_from`]: crate::mode::EditHelper::paste_from
+use std::{
+    collections::HashMap,
+    sync::{
+        LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
+
+use crate::{clip_history, ui::Ui};
+
+/// The register used when no other is specified
+pub const DEFAULT: char = '"';
+/// A register that discards anything yanked into it
+pub const NULL: char = '_';
+/// A register that also sets the system clipboard
+pub const CLIPBOARD: char = '+';
+
+static REGISTERS: LazyLock<Mutex<HashMap<char, Vec<String>>>> = LazyLock::new(Mutex::default);
+static SYNC_DEFAULT: AtomicBool = AtomicBool::new(false);
+
+/// Stores strings in a register
+///
+/// Yanking into [`NULL`] does nothing. Otherwise, the strings are
+/// also [recorded] in the yank history. Since the system clipboard
+/// only holds one string, when sending them to it, they are joined
+/// by new lines.
+///
+/// [recorded]: clip_history::record
+pub fn set<U: Ui>(reg: char, strs: Vec<String>) {
+    if reg == NULL {
+        return;
+    }
+
+    if reg == CLIPBOARD || (reg == DEFAULT && SYNC_DEFAULT.load(Ordering::Relaxed)) {
+        U::set_clipboard(&strs.join("\n"));
+    }
+
+    clip_history::record(strs.clone());
+    REGISTERS.lock().insert(reg, strs);
+}
+
+/// The strings stored in a register
+///
+/// Returns [`None`] if nothing was yanked into it.
+pub fn get(reg: char) -> Option<Vec<String>> {
+    REGISTERS
+        .lock()
+        .get(&reg)
+        .filter(|strs| !strs.is_empty())
+        .cloned()
+}
+
+/// Makes yanks into the [`DEFAULT`] register set the clipboard
+///
+/// This is off by default, in which case only the [`CLIPBOARD`]
+/// register does so.
+pub fn sync_default_with_clipboard(sync: bool) {
+    SYNC_DEFAULT.store(sync, Ordering::Relaxed);
+}
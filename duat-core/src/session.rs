@@ -1,24 +1,43 @@
 use std::{
     path::PathBuf,
+    process::Command,
     sync::{
+        Arc,
         atomic::{AtomicUsize, Ordering},
         mpsc,
     },
     time::Duration,
 };
 
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
 use crate::{
     Plugin,
-    cache::{delete_cache, load_cache, store_cache},
+    cache::{self, delete_cache, load_cache, store_cache},
     cfg::PrintCfg,
     cmd, context,
     data::RwData,
-    hooks::{self, OnFileOpen, OnWindowOpen, SessionStarted},
-    mode,
-    ui::{Area, Event, FileBuilder, Layout, MasterOnLeft, Sender, Ui, Window, WindowBuilder},
-    widgets::{File, FileCfg, Node, Widget, WidgetCfg},
+    filetype,
+    hooks::{self, OnFileClose, OnFileOpen, OnWindowOpen, SessionStarted},
+    hover,
+    mode::{self, Cursors},
+    text::{Point, Text, err, ok},
+    ui::{
+        Area, Axis, Constraint, Event, FileBuilder, Layout, MasterOnLeft, Sender, Side, Ui, Window,
+        WindowBuilder,
+    },
+    widgets::{File, FileCfg, Node, View, Widget, WidgetCfg},
 };
 
+/// The name of the [`File`] being filtered, if using `--filter`
+///
+/// This is kept by name, since [`File`]s are recreated when reloading
+/// the config.
+static FILTERED: Mutex<Option<String>> = Mutex::new(None);
+/// Functions to call on the next iteration of the main loop
+static DISPATCHED: Mutex<Vec<Box<dyn FnOnce() + Send>>> = Mutex::new(Vec::new());
+
 #[doc(hidden)]
 pub struct SessionCfg<U: Ui> {
     ui: U,
@@ -31,26 +50,77 @@ impl<U: Ui> SessionCfg<U> {
     pub fn new(ui: U) -> Self {
         crate::DEBUG_TIME_START.get_or_init(std::time::Instant::now);
 
+        let mut file_cfg = FileCfg::new();
+        file_cfg.add_opener(Arc::new(ZipOpener));
+        file_cfg.add_opener(Arc::new(RemoteOpener));
+        crate::text::declare_search_opts();
+        crate::text::declare_history_opts();
+        crate::watcher::declare_opts();
+        crate::format::add_hooks::<U>();
+        crate::filetype::add_hooks::<U>();
+
         SessionCfg {
             ui,
-            file_cfg: FileCfg::new(),
+            file_cfg,
             layout: Box::new(|| Box::new(MasterOnLeft)),
             plugins: Vec::new(),
         }
     }
 
     pub fn session_from_args(mut self, tx: mpsc::Sender<Event>) -> Session<U> {
+        let mut args = parse_args(std::env::args().skip(1));
+
+        // A saved session is only restored if no files were given.
+        let mut staged = None;
+        if let Some(name) = std::env::args()
+            .find_map(|arg| arg.strip_prefix("--session=").map(|name| name.to_string()))
+            && args.is_empty()
+        {
+            match stage_session::<U>(&name) {
+                Ok(session) => {
+                    args = session
+                        .files
+                        .iter()
+                        .map(|(path, _)| (path.clone(), None))
+                        .collect();
+                    staged = Some(session);
+                }
+                Err(err) => context::notify(err),
+            }
+        }
+
+        // When filtering, the stdin buffer is always the first one.
+        let is_filter = std::env::args().skip(1).any(|arg| arg == "--filter");
+        if is_filter && !args.iter().any(|(path, _)| path.as_os_str() == "-") {
+            args.insert(0, (PathBuf::from("-"), None));
+        }
+
+        // This has to happen before the Ui takes over the terminal. After
+        // that, input is read from the tty, not from stdin.
+        let mut stdin = args
+            .iter()
+            .any(|(path, _)| path.as_os_str() == "-")
+            .then(read_stdin);
+
         self.ui.open();
 
-        let mut args = std::env::args();
-        let first = args.nth(1).map(PathBuf::from);
+        let mut args = args.into_iter();
+        let (first, first_pos) = match args.next() {
+            Some((path, pos)) => (Some(path), pos),
+            None => (None, None),
+        };
 
         let (widget, checker, _) = if let Some(path) = first {
-            <FileCfg as WidgetCfg<U>>::build(self.file_cfg.clone().open_path(path), false)
+            let file_cfg = file_cfg_for(&self.file_cfg, path, &mut stdin);
+            <FileCfg as WidgetCfg<U>>::build(file_cfg, false)
         } else {
             self.file_cfg.clone().build(false)
         };
 
+        if is_filter {
+            *FILTERED.lock() = Some(widget.name());
+        }
+
         let (window, node) = Window::new(&mut self.ui, widget, checker, (self.layout)());
         let cur_window = context::set_windows(vec![window]);
 
@@ -59,21 +129,40 @@ impl<U: Ui> SessionCfg<U> {
             cur_window,
             file_cfg: self.file_cfg,
             tx,
+            placed: Vec::new(),
         };
 
         context::set_cur(node.as_file(), node.clone());
         cmd::add_session_commands::<U>(session.tx.clone()).unwrap();
 
         // Open and process files.
-        let builder = FileBuilder::new(node, context::cur_window());
+        filetype::file_opened(&node);
+        let builder = FileBuilder::new(node.clone(), context::cur_window());
         hooks::trigger_now::<OnFileOpen<U>>(builder);
+        if let Some(pos) = first_pos {
+            session.place_cursor(node, pos);
+        }
 
-        args.for_each(|file| session.open_file(PathBuf::from(file)));
+        for (path, pos) in args {
+            let file_cfg = file_cfg_for(&session.file_cfg, path, &mut stdin);
+            if let Some(node) = session.open_file_inner(file_cfg)
+                && let Some(pos) = pos
+            {
+                session.place_cursor(node, pos);
+            }
+        }
 
         // Build the window's widgets.
         let builder = WindowBuilder::new(0);
         hooks::trigger_now::<OnWindowOpen<U>>(builder);
 
+        if let Some(staged) = staged {
+            restore_layout::<U>(&staged.layout);
+            if let Some(name) = staged.active {
+                mode::reset_switch_to::<U>(name);
+            }
+        }
+
         session
     }
 
@@ -103,12 +192,14 @@ impl<U: Ui> SessionCfg<U> {
             cur_window,
             file_cfg: self.file_cfg,
             tx,
+            placed: Vec::new(),
         };
 
         context::set_cur(node.as_file(), node.clone());
         cmd::add_session_commands::<U>(session.tx.clone()).unwrap();
 
         // Open and process files.
+        filetype::file_opened(&node);
         let builder = FileBuilder::new(node, context::cur_window());
         hooks::trigger_now::<OnFileOpen<U>>(builder);
 
@@ -135,6 +226,26 @@ impl<U: Ui> SessionCfg<U> {
         self.plugins.push(Box::new(plugin));
     }
 
+    /// Adds a [`FileOpener`], which can intercept the opening of
+    /// files
+    ///
+    /// Openers added later take precedence over ones added earlier.
+    pub fn add_opener(&mut self, opener: impl FileOpener) {
+        self.file_cfg.add_opener(Arc::new(opener));
+    }
+
+    /// Adds an override for the [`PrintCfg`] of [`File`]s
+    ///
+    /// The function receives the path of the [`File`] and the
+    /// [`PrintCfg`] it would have, returning the one that it will
+    /// have. Overrides added later are applied after earlier ones.
+    pub fn add_print_cfg_override(
+        &mut self,
+        f: impl Fn(&str, PrintCfg) -> PrintCfg + Send + Sync + 'static,
+    ) {
+        self.file_cfg.add_override(Arc::new(f));
+    }
+
     pub fn load_plugin_and<P: Plugin<U>>(&mut self, f: impl FnOnce(&mut P)) {
         let cache = load_cache::<P::Cache>("").unwrap_or_default();
         let mut plugin = P::new(cache);
@@ -148,27 +259,91 @@ pub struct Session<U: Ui> {
     cur_window: &'static AtomicUsize,
     file_cfg: FileCfg,
     tx: mpsc::Sender<Event>,
+    placed: Vec<Node<U>>,
 }
 
 impl<U: Ui> Session<U> {
     pub fn open_file(&mut self, path: PathBuf) {
+        self.open_file_inner(self.file_cfg.clone().open_path(path));
+    }
+
+    /// Opens a [`File`], returning its [`Node`] if it was opened
+    fn open_file_inner(&mut self, file_cfg: FileCfg) -> Option<Node<U>> {
         let windows = context::windows::<U>();
         let pushed = windows.mutate(|windows| {
             let cur_window = self.cur_window.load(Ordering::Relaxed);
-            let (file, checker, _) =
-                <FileCfg as WidgetCfg<U>>::build(self.file_cfg.clone().open_path(path), false);
+            let (file, checker, _) = <FileCfg as WidgetCfg<U>>::build(file_cfg, false);
             windows[cur_window].push_file(file, checker)
         });
 
         match pushed {
             Ok((node, _)) => {
-                let builder = FileBuilder::new(node, context::cur_window());
+                filetype::file_opened(&node);
+                let builder = FileBuilder::new(node.clone(), context::cur_window());
                 hooks::trigger_now::<OnFileOpen<U>>(builder);
+                Some(node)
+            }
+            Err(err) => {
+                context::notify(err.into());
+                None
             }
-            Err(err) => context::notify(err.into()),
         }
     }
 
+    /// Places the main cursor of a [`File`] at a [`StartPos`]
+    ///
+    /// This is recorded as a jump, so `selection-undo` goes back to
+    /// where the cursor was before.
+    ///
+    /// The [`File`] is only scrolled to the cursor once the layout
+    /// has been flushed, at the start of the [`Session`].
+    fn place_cursor(&mut self, node: Node<U>, pos: StartPos) {
+        let Some((file, view, _)) = node.as_file() else {
+            return;
+        };
+        let mut file = file.write();
+        let cfg = file.print_cfg();
+        let text = file.text_mut();
+
+        let point = match pos {
+            StartPos::Coords(line, col) => {
+                let line = line.saturating_sub(1).min(text.len().line());
+                let start = text.point_at_line(line);
+                let end = match line < text.len().line() {
+                    true => text.point_at_line(line + 1).char() - 1,
+                    false => text.len().char(),
+                };
+                let col = col.unwrap_or(1).saturating_sub(1);
+                text.point_at_char((start.char() + col).min(end))
+            }
+            StartPos::Pattern(pat) => {
+                match text
+                    .search_fwd(&pat, Point::default(), None)
+                    .map(|mut m| m.next())
+                {
+                    Ok(Some((point, _))) => point,
+                    Ok(None) => {
+                        context::notify(err!("Pattern " [*a] pat [] " not found"));
+                        return;
+                    }
+                    Err(err) => {
+                        context::notify(err!([*a] { err.to_string() }));
+                        return;
+                    }
+                }
+            }
+        };
+
+        let mut cursors = view.cursors().write();
+        let origin = cursors.clone();
+        cursors.clear();
+        cursors.insert_from_parts(0, point, 0, text, view.area(), cfg);
+        text.record_jump(&origin, &cursors);
+        drop((file, cursors));
+
+        self.placed.push(node);
+    }
+
     /// Start the application, initiating a read/response loop.
     pub fn start(mut self, rx: mpsc::Receiver<Event>) -> Vec<(RwData<File>, bool)> {
         hooks::trigger::<SessionStarted<U>>(());
@@ -201,8 +376,21 @@ impl<U: Ui> Session<U> {
         });
 
         self.ui.flush_layout();
+
+        // Now that the areas have a size, files can be scrolled.
+        for node in std::mem::take(&mut self.placed) {
+            if let Some((file, view, _)) = node.as_file() {
+                let file = file.read();
+                if let Some(main) = view.cursors().read().get_main() {
+                    view.area()
+                        .scroll_around_point(file.text(), main.caret(), file.print_cfg());
+                }
+            }
+        }
+
         self.ui.start(Sender::new(self.tx.clone()));
         crate::form::set_sender(Sender::new(self.tx.clone()));
+        crate::ui::set_sender(Sender::new(self.tx.clone()));
 
         // The main loop.
         loop {
@@ -227,22 +415,48 @@ impl<U: Ui> Session<U> {
 
             match reason_to_break {
                 BreakTo::QuitDuat => {
+                    DISPATCHED.lock().clear();
+                    crate::ui::clear_event_handlers();
+                    self.close_files();
+                    let filtered = filtered_contents::<U>();
                     crate::thread::quit_queue();
                     cmd::end_session();
                     self.save_cache(true);
+                    if !cache::apply_pending_restore() {
+                        cache::backup_cache();
+                    }
                     self.ui.close();
 
+                    // Programs like git rely on the exit code to abort, and
+                    // pipelines shouldn't receive anything in that case.
+                    let code = cmd::exit_code();
+                    if code != 0 {
+                        std::process::exit(code);
+                    }
+
+                    if let Some(contents) = filtered {
+                        use std::io::Write;
+                        let mut stdout = std::io::stdout().lock();
+                        stdout.write_all(contents.as_bytes()).unwrap();
+                        stdout.flush().unwrap();
+                    }
+
                     break Vec::new();
                 }
                 BreakTo::ReloadConfig => {
+                    DISPATCHED.lock().clear();
+                    crate::ui::clear_event_handlers();
+                    self.close_files();
                     crate::thread::quit_queue();
                     cmd::end_session();
                     self.save_cache(false);
+                    cache::apply_pending_restore();
                     self.ui.end();
 
                     break self.reload_config();
                 }
                 BreakTo::OpenFile(file) => self.open_file(file),
+                BreakTo::LoadSession(name) => self.load_session(&name),
             }
         }
     }
@@ -264,6 +478,8 @@ impl<U: Ui> Session<U> {
                 if let Ok(event) = rx.recv_timeout(Duration::from_millis(10)) {
                     match event {
                         Event::Key(key) => mode::send_key(key),
+                        Event::Mouse(event) => mode::send_mouse::<U>(event),
+                        Event::Paste(text) => mode::send_paste(text),
                         Event::Resize | Event::FormChange => {
                             for node in cur_window.nodes() {
                                 s.spawn(|| node.update_and_print());
@@ -273,21 +489,46 @@ impl<U: Ui> Session<U> {
                         Event::ReloadConfig => break BreakTo::ReloadConfig,
                         Event::Quit => break BreakTo::QuitDuat,
                         Event::OpenFile(file) => break BreakTo::OpenFile(file),
+                        Event::LoadSession(name) => break BreakTo::LoadSession(name),
+                        Event::Custom(event) => crate::ui::handle_event(event),
                     }
                 }
 
+                let dispatched = std::mem::take(&mut *DISPATCHED.lock());
+                for f in dispatched {
+                    f();
+                }
+
+                hover::update::<U>();
+                crate::watcher::update::<U>();
+                hooks::run_timers();
+
                 for node in cur_window.nodes() {
                     if node.needs_update() {
-                        s.spawn(|| node.update_and_print());
+                        s.spawn(|| node.update_and_print_if_changed());
                     }
                 }
             }
         })
     }
 
+    /// Triggers [`OnFileClose`] for every open [`File`]
+    fn close_files(&self) {
+        let files: Vec<RwData<File>> = context::windows::<U>()
+            .read()
+            .iter()
+            .flat_map(Window::nodes)
+            .filter_map(|node| node.try_downcast())
+            .collect();
+
+        for file in files {
+            hooks::trigger_now::<OnFileClose>(file);
+        }
+    }
+
     fn save_cache(&self, is_quitting_duat: bool) {
         let windows = context::windows::<U>().read();
-        for (file, area, cursors, _) in windows
+        for (file, View { area, cursors, .. }, _) in windows
             .iter()
             .flat_map(Window::nodes)
             .filter_map(|node| node.as_file())
@@ -327,6 +568,57 @@ impl<U: Ui> Session<U> {
             .collect()
     }
 
+    /// Restores a session saved with `session save`
+    ///
+    /// [`File`]s that are already open get their [`Cursors`] back,
+    /// while the others are opened. Then, the window is rearranged
+    /// to match the saved layout, and the [`File`] that was active is
+    /// switched to.
+    fn load_session(&mut self, name: &str) {
+        let staged = match stage_session::<U>(name) {
+            Ok(staged) => staged,
+            Err(err) => {
+                context::notify(err);
+                return;
+            }
+        };
+
+        for (path, saved_cursors) in staged.files {
+            let path_str = path.to_string_lossy().to_string();
+            let open = context::windows::<U>()
+                .read()
+                .iter()
+                .flat_map(Window::nodes)
+                .find(|node| node.inspect_as(|file: &File| file.path()) == Some(path_str.clone()))
+                .cloned();
+
+            match open.as_ref().and_then(Node::as_file) {
+                Some((file, view, _)) => {
+                    let file = file.read();
+                    *view.cursors().write() = saved_cursors;
+                    if let Some(main) = view.cursors().read().get_main() {
+                        view.area().scroll_around_point(
+                            file.text(),
+                            main.caret(),
+                            file.print_cfg(),
+                        );
+                    }
+                }
+                None => {
+                    self.open_file_inner(self.file_cfg.clone().open_path(path));
+                }
+            }
+        }
+
+        restore_layout::<U>(&staged.layout);
+        if let Some(active) = staged.active {
+            mode::reset_switch_to::<U>(active);
+        }
+        let mut builder = Text::builder();
+        ok!(builder, "Loaded the session " [*a] name [] ".");
+        context::notify(builder.finish());
+    }
+
     fn open_file_from_cfg(&mut self, file_cfg: FileCfg, is_active: bool) {
         let pushed = context::windows().mutate(|windows| {
             let cur_window = self.cur_window.load(Ordering::Relaxed);
@@ -346,6 +638,7 @@ impl<U: Ui> Session<U> {
 
         match pushed {
             Ok((node, _)) => {
+                filetype::file_opened(&node);
                 let builder = FileBuilder::new(node, context::cur_window());
                 hooks::trigger_now::<OnFileOpen<U>>(builder);
             }
@@ -354,9 +647,604 @@ impl<U: Ui> Session<U> {
     }
 }
 
+/// An interceptor for the opening of [`File`]s
+///
+/// Whenever a [`File`] is opened, be it through the command line
+/// arguments or the `edit` command, Duat will check if any of the
+/// registered [`FileOpener`]s [handles] the target. If one does, it
+/// will be used to [read] the contents of the [`File`], and later to
+/// [write] them back, instead of the file system.
+///
+/// By default, Duat comes with two of these: one that reads entries
+/// from `.zip` archives (`foo.zip/entry.txt`), and one that fetches
+/// remote files through `scp` (`scp://host/path` or
+/// `sftp://host/path`).
+///
+/// [handles]: FileOpener::handles
+/// [read]: FileOpener::read
+/// [write]: FileOpener::write
+pub trait FileOpener: Send + Sync + 'static {
+    /// Whether this [`FileOpener`] should handle the target
+    fn handles(&self, target: &str) -> bool;
+
+    /// Reads the contents of the target
+    fn read(&self, target: &str) -> Result<String, Text>;
+
+    /// Writes the contents back to the target
+    ///
+    /// By default, the target is read only, and this returns an
+    /// error.
+    fn write(&self, target: &str, _contents: &str) -> Result<usize, Text> {
+        Err(err!([*a] target [] " is read only."))
+    }
+}
+
+/// Reads entries from `.zip` archives, through `unzip`
+///
+/// The entries are opened as read only.
+struct ZipOpener;
+
+impl FileOpener for ZipOpener {
+    fn handles(&self, target: &str) -> bool {
+        target.contains(".zip/")
+    }
+
+    fn read(&self, target: &str) -> Result<String, Text> {
+        let (archive, entry) = target.split_once(".zip/").unwrap();
+        let archive = format!("{archive}.zip");
+        run_external(Command::new("unzip").args(["-p", "--", &archive, entry]))
+    }
+}
+
+/// Reads and writes remote files, through `scp`
+struct RemoteOpener;
+
+impl RemoteOpener {
+    /// Turns `scp://host/path` into `host:path`, which `scp` uses
+    fn to_scp_target(target: &str) -> String {
+        let rest = target
+            .strip_prefix("scp://")
+            .or_else(|| target.strip_prefix("sftp://"))
+            .unwrap();
+        match rest.split_once('/') {
+            Some((host, path)) => format!("{host}:/{path}"),
+            None => format!("{rest}:"),
+        }
+    }
+}
+
+impl FileOpener for RemoteOpener {
+    fn handles(&self, target: &str) -> bool {
+        target.starts_with("scp://") || target.starts_with("sftp://")
+    }
+
+    fn read(&self, target: &str) -> Result<String, Text> {
+        let scp_target = Self::to_scp_target(target);
+        let (local, _) = temp_file()?;
+
+        let result = run_external(Command::new("scp").args(["-q", "--", &scp_target, &local]));
+        let contents = result.and_then(|_| Ok(std::fs::read_to_string(&local)?));
+        let _ = std::fs::remove_file(&local);
+        contents
+    }
+
+    fn write(&self, target: &str, contents: &str) -> Result<usize, Text> {
+        let scp_target = Self::to_scp_target(target);
+        let (local, mut file) = temp_file()?;
+
+        let result = std::io::Write::write_all(&mut file, contents.as_bytes())
+            .map_err(Text::from)
+            .and_then(|_| {
+                run_external(Command::new("scp").args(["-q", "--", &local, &scp_target]))
+            });
+        let _ = std::fs::remove_file(&local);
+
+        result.map(|_| contents.len())
+    }
+}
+
+/// Calls a function on Duat's main loop
+///
+/// This is meant for background threads, like the ones spawned by
+/// [`thread::spawn`], which need to run commands, change [`Widget`]s
+/// or otherwise interact with Duat in the same way that [`Mode`]s do.
+/// The function is called on the next iteration of the main loop,
+/// and its return value is sent through the returned [`Receiver`].
+///
+/// If Duat quits or reloads its config before that happens, the
+/// function is dropped, disconnecting the [`Receiver`].
+///
+/// [`thread::spawn`]: crate::thread::spawn
+/// [`Mode`]: crate::mode::Mode
+/// [`Receiver`]: mpsc::Receiver
+pub fn dispatch<R: Send + 'static>(f: impl FnOnce() -> R + Send + 'static) -> mpsc::Receiver<R> {
+    let (tx, rx) = mpsc::channel();
+    DISPATCHED.lock().push(Box::new(move || {
+        // The receiver may have been dropped, if no one cares about it.
+        let _ = tx.send(f());
+    }));
+
+    rx
+}
+
+/// Creates a new temporary file, which no one else can access
+///
+/// The file is created exclusively, so a file or symlink already
+/// placed on its path by someone else is never reused.
+fn temp_file() -> Result<(String, std::fs::File), Text> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    loop {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |dur| dur.subsec_nanos());
+        let name = format!(
+            "duat-remote-{}-{}-{nanos:x}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+
+        match options.open(&path) {
+            Ok(file) => break Ok((path.to_string_lossy().to_string(), file)),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(err) => break Err(err.into()),
+        }
+    }
+}
+
+/// Runs an external command, returning its stdout
+fn run_external(command: &mut Command) -> Result<String, Text> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(err!([*a] { stderr.trim() }))
+    }
+}
+
+/// The open [`File`]s and the layout of the window, as saved by the
+/// `session save` command
+///
+/// The widgets around the [`File`]s are built again by the hooks that
+/// built them in the first place, once the [`File`]s are opened.
+/// After that, the [`File`]s are moved around and every [`Area`] is
+/// constrained, until the window looks like it did when it was saved.
+#[derive(Serialize, Deserialize)]
+struct SavedSession<C> {
+    files: Vec<SavedFile<C>>,
+    layout: Vec<SavedNode>,
+}
+
+/// A [`File`] in a [`SavedSession`]
+#[derive(Serialize, Deserialize)]
+struct SavedFile<C> {
+    name: String,
+    path: String,
+    is_active: bool,
+    cursors: Cursors,
+    /// The [`Area`]'s cache, which stores how it was scrolled
+    cache: C,
+}
+
+/// A node in the saved layout tree of a window, in pre-order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SavedNode {
+    depth: usize,
+    axis: Option<Axis>,
+    hor_con: Option<Constraint>,
+    ver_con: Option<Constraint>,
+    is_clustered: bool,
+    /// The [`Widget`] that occupies this node, if it is a leaf
+    widget: Option<WidgetKey>,
+}
+
+/// Identifies a [`Widget`] across sessions
+///
+/// Since [`Widget`]s other than [`File`]s are not saved, they are
+/// identified by their name, the [`File`] that they are clustered
+/// with, and how many [`Widget`]s of the same kind came before them
+/// in that cluster.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct WidgetKey {
+    name: String,
+    file: Option<String>,
+    nth: usize,
+}
+
+/// What [`stage_session`] returns, in order to restore a session
+struct StagedSession {
+    files: Vec<(PathBuf, Cursors)>,
+    active: Option<String>,
+    layout: Vec<SavedNode>,
+}
+
+/// Saves the open [`File`]s, along with their [`Cursors`], scroll and
+/// the layout of the window
+///
+/// The session can then be restored with `session load`, or when
+/// starting Duat with `--session={name}`. Returns how many [`File`]s
+/// were saved.
+pub(crate) fn save_session<U: Ui>(name: &str) -> Result<usize, Text> {
+    let active = context::cur_file::<U>()
+        .ok()
+        .map(|cur_file| cur_file.path());
+    let windows = context::windows::<U>().read();
+
+    let files: Vec<_> = windows
+        .iter()
+        .flat_map(Window::nodes)
+        .filter_map(|node| {
+            let (file, View { area, cursors, .. }, _) = node.as_file()?;
+            let file = file.read();
+            file.exists().then(|| SavedFile {
+                name: file.name(),
+                path: file.path(),
+                is_active: active.as_ref() == Some(&file.path()),
+                cursors: cursors.read().clone(),
+                cache: area.cache().unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    if files.is_empty() {
+        return Err(err!("There are no files on disk to save."));
+    }
+
+    let (layout, _) = layout_of(&windows[context::cur_window()]);
+
+    let len = files.len();
+    store_cache(session_path(name), SavedSession { files, layout });
+    Ok(len)
+}
+
+/// Loads a session saved with [`save_session`]
+///
+/// The [`Cursors`] and scroll of each [`File`] are put in its cache,
+/// so they are picked up when it is opened. The layout is returned,
+/// to be [restored] once the [`File`]s are open.
+///
+/// [restored]: restore_layout
+fn stage_session<U: Ui>(name: &str) -> Result<StagedSession, Text> {
+    let saved: SavedSession<<U::Area as Area>::Cache> = load_cache(session_path(name))
+        .ok_or_else(|| err!("There is no session named " [*a] name [] "."))?;
+
+    let mut active = None;
+    let files = saved
+        .files
+        .into_iter()
+        .map(|file| {
+            store_cache(&file.path, file.cache);
+            store_cache(&file.path, file.cursors.clone());
+            if file.is_active {
+                active = Some(file.name);
+            }
+            (PathBuf::from(file.path), file.cursors)
+        })
+        .collect();
+
+    Ok(StagedSession { files, active, layout: saved.layout })
+}
+
+/// The layout tree of a [`Window`], as [`SavedNode`]s, along with
+/// the [`Area`] of each node
+///
+/// Floating [`Area`]s are not part of the tree, so they are not
+/// saved.
+fn layout_of<U: Ui>(window: &Window<U>) -> (Vec<SavedNode>, Vec<U::Area>) {
+    let Some(first) = window.nodes().find(|node| !node.area().is_floating()) else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let (mut nodes, areas): (Vec<SavedNode>, Vec<U::Area>) = first
+        .area()
+        .layout_tree()
+        .into_iter()
+        .map(|layout_node| {
+            let node = SavedNode {
+                depth: layout_node.depth,
+                axis: layout_node.axis,
+                hor_con: layout_node.hor_con,
+                ver_con: layout_node.ver_con,
+                is_clustered: layout_node.is_clustered,
+                widget: None,
+            };
+            (node, layout_node.area)
+        })
+        .unzip();
+
+    let node_of = |area: &U::Area| window.nodes().find(|node| node.area() == area);
+
+    // Files first, since every other Widget is identified by them.
+    for (node, area) in nodes.iter_mut().zip(&areas) {
+        if let Some(path) = node_of(area).and_then(|node| node.inspect_as(File::path)) {
+            let name = crate::duat_name::<File>().to_string();
+            node.widget = Some(WidgetKey { name, file: Some(path), nth: 0 });
+        }
+    }
+
+    for i in 0..nodes.len() {
+        let Some(node) = node_of(&areas[i]).filter(|_| nodes[i].widget.is_none()) else {
+            continue;
+        };
+        let name = node.widget_name().to_string();
+        let file = cluster_of(&nodes, i)
+            .and_then(|master| first_file(&nodes, master))
+            .map(str::to_string);
+        let nth = nodes[..i]
+            .iter()
+            .filter_map(|node| node.widget.as_ref())
+            .filter(|key| key.name == name && key.file == file)
+            .count();
+
+        nodes[i].widget = Some(WidgetKey { name, file, nth });
+    }
+
+    (nodes, areas)
+}
+
+/// Rearranges the current [`Window`] to match a saved layout
+///
+/// First, the [`File`]s are moved next to each other, from the root
+/// of the saved tree to its leaves, so that every split is recreated
+/// inside of the previous ones. Then, every [`Area`] that was
+/// constrained when the session was saved is constrained again.
+fn restore_layout<U: Ui>(saved: &[SavedNode]) {
+    let windows = context::windows::<U>().read();
+    let window = &windows[context::cur_window()];
+    if saved.is_empty() {
+        return;
+    }
+
+    let area_of_file = |path: &str| {
+        window
+            .nodes()
+            .find(|node| node.inspect_as(File::path).as_deref() == Some(path))
+            .map(|node| node.area().clone())
+    };
+
+    let mut to_arrange = vec![0];
+    while let Some(i) = to_arrange.pop() {
+        let side = match saved[i].axis {
+            Some(_) if unit_of(saved, i) == Some(i) => continue,
+            Some(Axis::Horizontal) => Side::Right,
+            Some(Axis::Vertical) => Side::Below,
+            None => continue,
+        };
+
+        let children: Vec<(usize, U::Area)> = children(saved, i)
+            .filter_map(|child| Some((child, area_of_file(first_file(saved, child)?)?)))
+            .collect();
+
+        for pair in children.windows(2) {
+            let ((_, prev), (_, area)) = (&pair[0], &pair[1]);
+            if area != prev {
+                let _ = area.move_next_to(prev, side);
+            }
+        }
+
+        to_arrange.extend(children.into_iter().map(|(child, _)| child));
+    }
+
+    let (nodes, areas) = layout_of(window);
+    for (i, (node, area)) in nodes.iter().zip(&areas).enumerate() {
+        let Some(key) = node_key(&nodes, i) else {
+            continue;
+        };
+        let Some(saved_node) = (0..saved.len())
+            .find(|j| node_key(saved, *j).as_ref() == Some(&key))
+            .map(|j| &saved[j])
+        else {
+            continue;
+        };
+
+        if let Some(con) = saved_node.hor_con
+            && node.hor_con != Some(con)
+        {
+            let _ = area.constrain_hor(con);
+        }
+        if let Some(con) = saved_node.ver_con
+            && node.ver_con != Some(con)
+        {
+            let _ = area.constrain_ver(con);
+        }
+    }
+}
+
+/// Identifies the node at `i` across sessions
+///
+/// Leaves are identified by their [`Widget`], and the masters of
+/// clusters by the [`File`] in them. Other nodes are only created in
+/// order to hold those, so they aren't identified.
+fn node_key(nodes: &[SavedNode], i: usize) -> Option<Result<&WidgetKey, &str>> {
+    match &nodes[i].widget {
+        Some(key) => Some(Ok(key)),
+        None if unit_of(nodes, i) == Some(i) => first_file(nodes, i).map(Err),
+        None => None,
+    }
+}
+
+/// The indices of the nodes in the subtree at `i`, including it
+fn subtree(nodes: &[SavedNode], i: usize) -> impl Iterator<Item = usize> + '_ {
+    let depth = nodes[i].depth;
+    std::iter::once(i).chain((i + 1..nodes.len()).take_while(move |j| nodes[*j].depth > depth))
+}
+
+/// The direct children of the node at `i`
+fn children(nodes: &[SavedNode], i: usize) -> impl Iterator<Item = usize> + '_ {
+    let depth = nodes[i].depth;
+    subtree(nodes, i).filter(move |j| nodes[*j].depth == depth + 1)
+}
+
+/// The path of the first [`File`] in the subtree at `i`
+fn first_file(nodes: &[SavedNode], i: usize) -> Option<&str> {
+    subtree(nodes, i).find_map(|j| {
+        let key = nodes[j].widget.as_ref()?;
+        let is_file = key.name == crate::duat_name::<File>();
+        is_file.then_some(key.file.as_deref()).flatten()
+    })
+}
+
+/// The master of the cluster that the node at `i` belongs to
+///
+/// Returns [`None`] if the node isn't clustered with anything.
+fn cluster_of(nodes: &[SavedNode], i: usize) -> Option<usize> {
+    let mut master = None;
+    let mut depth = nodes[i].depth;
+    for j in (0..i).rev() {
+        if nodes[j].depth < depth {
+            if !nodes[j].is_clustered {
+                break;
+            }
+            master = Some(j);
+            depth = nodes[j].depth;
+        }
+    }
+    master
+}
+
+/// The node that moves along with the first [`File`] in `i`
+///
+/// That is, its cluster master, or the [`File`]'s own node, if it
+/// isn't clustered with anything.
+fn unit_of(nodes: &[SavedNode], i: usize) -> Option<usize> {
+    let path = first_file(nodes, i)?;
+    let file = subtree(nodes, i).find(|j| {
+        let key = nodes[*j].widget.as_ref();
+        key.is_some_and(|key| {
+            key.name == crate::duat_name::<File>() && key.file.as_deref() == Some(path)
+        })
+    })?;
+    Some(cluster_of(nodes, file).unwrap_or(file))
+}
+
+/// The path under which a session is cached
+fn session_path(name: &str) -> String {
+    format!("session:{name}")
+}
+
+/// Where to place the main cursor of a [`File`] opened from the
+/// command line
+enum StartPos {
+    /// A line and, optionally, a column, both starting at 1
+    Coords(u32, Option<u32>),
+    /// The first match of a regex pattern
+    Pattern(String),
+}
+
+/// Parses the paths given in the command line
+///
+/// Paths can be followed by `:{line}` or `:{line}:{col}`, and can be
+/// preceded by `+{line}` or `+/{pattern}` arguments, which place the
+/// main cursor in the [`File`].
+fn parse_args(args: impl Iterator<Item = String>) -> Vec<(PathBuf, Option<StartPos>)> {
+    let mut files = Vec::new();
+    let mut next_pos = None;
+
+    for arg in args {
+        if arg.starts_with("--") {
+            continue;
+        } else if let Some(pos) = arg.strip_prefix('+') {
+            next_pos = match pos.strip_prefix('/') {
+                Some(pat) => Some(StartPos::Pattern(pat.to_string())),
+                None => pos.parse().ok().map(|line| StartPos::Coords(line, None)),
+            };
+            continue;
+        }
+
+        let (path, pos) = split_coords(&arg);
+        files.push((path, next_pos.take().or(pos)));
+    }
+
+    files
+}
+
+/// The contents of the [`File`] being filtered, with `--filter`
+///
+/// When Duat is used as a filter, the contents of stdin are edited in
+/// the first [`File`], which is printed to stdout when quitting,
+/// unless Duat was quit with a non-zero exit code, like with `cquit`.
+fn filtered_contents<U: Ui>() -> Option<String> {
+    let name = FILTERED.lock().clone()?;
+    context::windows::<U>()
+        .read()
+        .iter()
+        .flat_map(Window::nodes)
+        .filter_map(|node| node.try_downcast::<File>())
+        .find(|file| file.read().name() == name)
+        .map(|file| file.read().text().to_string())
+}
+
+/// Reads the contents of stdin, for the `-` path
+///
+/// If stdin is a terminal, nothing is read, since there would be
+/// nothing piped into Duat.
+fn read_stdin() -> String {
+    use std::io::IsTerminal;
+
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return String::new();
+    }
+
+    std::io::read_to_string(stdin).unwrap_or_else(|err| {
+        context::notify(err!("Failed to read stdin: " [*a] { err.to_string() }));
+        String::new()
+    })
+}
+
+/// The [`FileCfg`] for a path given in the command line
+///
+/// The `-` path opens an unnamed buffer with the contents of stdin,
+/// which will ask for a path when written. Only the first `-` gets
+/// said contents.
+fn file_cfg_for(file_cfg: &FileCfg, path: PathBuf, stdin: &mut Option<String>) -> FileCfg {
+    if path.as_os_str() == "-" {
+        let text = Text::from(stdin.take().unwrap_or_default());
+        file_cfg.clone().take_text(text)
+    } else {
+        file_cfg.clone().open_path(path)
+    }
+}
+
+/// Splits a `:{line}` or `:{line}:{col}` suffix from a path
+///
+/// If a file with the full name exists, nothing is split.
+fn split_coords(arg: &str) -> (PathBuf, Option<StartPos>) {
+    let path = PathBuf::from(arg);
+    if path.exists() {
+        return (path, None);
+    }
+
+    let Some((rest, Ok(last))) = arg
+        .rsplit_once(':')
+        .map(|(rest, last)| (rest, last.parse::<u32>()))
+    else {
+        return (path, None);
+    };
+
+    if let Some((file, line)) = rest.rsplit_once(':')
+        && let Ok(line) = line.parse::<u32>()
+    {
+        (
+            PathBuf::from(file),
+            Some(StartPos::Coords(line, Some(last))),
+        )
+    } else {
+        (PathBuf::from(rest), Some(StartPos::Coords(last, None)))
+    }
+}
+
 enum BreakTo {
     ReloadConfig,
     OpenFile(PathBuf),
+    LoadSession(String),
     QuitDuat,
 }
 
@@ -365,3 +1253,62 @@ unsafe impl<U: Ui> Sync for SessionCfg<U> {}
 
 unsafe impl<U: Ui> Send for Session<U> {}
 unsafe impl<U: Ui> Sync for Session<U> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{SavedNode, WidgetKey, children, cluster_of, first_file, node_key, unit_of};
+    use crate::{ui::Axis, widgets::File};
+
+    fn node(depth: usize, axis: Option<Axis>, is_clustered: bool) -> SavedNode {
+        SavedNode {
+            depth,
+            axis,
+            hor_con: None,
+            ver_con: None,
+            is_clustered,
+            widget: None,
+        }
+    }
+
+    fn leaf(depth: usize, name: &str, file: &str) -> SavedNode {
+        let file = Some(file.to_string());
+        let widget = Some(WidgetKey { name: name.to_string(), file, nth: 0 });
+        SavedNode { widget, ..node(depth, None, false) }
+    }
+
+    #[test]
+    fn saved_layouts_are_split_into_files_and_clusters() {
+        let file = crate::duat_name::<File>();
+        let nodes = [
+            node(0, Some(Axis::Vertical), false),
+            node(1, Some(Axis::Horizontal), false),
+            node(2, Some(Axis::Horizontal), true),
+            leaf(3, "LineNumbers", "a"),
+            leaf(3, file, "a"),
+            node(2, Some(Axis::Vertical), false),
+            leaf(3, file, "b"),
+            leaf(3, file, "c"),
+            leaf(1, "StatusLine", "a"),
+        ];
+
+        assert_eq!(first_file(&nodes, 0), Some("a"));
+        assert_eq!(first_file(&nodes, 5), Some("b"));
+        assert_eq!(first_file(&nodes, 8), None);
+
+        assert_eq!(children(&nodes, 0).collect::<Vec<_>>(), [1, 8]);
+        assert_eq!(children(&nodes, 1).collect::<Vec<_>>(), [2, 5]);
+        assert_eq!(children(&nodes, 5).collect::<Vec<_>>(), [6, 7]);
+
+        assert_eq!(cluster_of(&nodes, 3), Some(2));
+        assert_eq!(cluster_of(&nodes, 6), None);
+        assert_eq!(unit_of(&nodes, 1), Some(2));
+        assert_eq!(unit_of(&nodes, 5), Some(6));
+
+        assert_eq!(node_key(&nodes, 2), Some(Err("a")));
+        assert_eq!(node_key(&nodes, 5), None);
+        assert_eq!(
+            node_key(&nodes, 7),
+            Some(Ok(nodes[7].widget.as_ref().unwrap()))
+        );
+    }
+}
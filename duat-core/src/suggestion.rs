@@ -0,0 +1,123 @@
+//! Inline suggestions, shown as ghost text at the cursor
+//!
+//! Every [`File`] can have a number of [`SuggestionProvider`]s,
+//! which are asked for a suggestion at the main cursor whenever a
+//! [`Mode`] [requests one], usually right after typing:
+//!
+//! ```rust
+//! # use duat_core::{data::RwData, suggestion::Words, widgets::File};
+//! # fn test(file: RwData<File>) {
+//! file.write().add_suggestion_provider(Words);
+//! # }
+//! ```
+//!
+//! The suggestion is shown as [ghost text], and the [`Mode`] can
+//! then [accept] it, inserting it into the [`File`], or [dismiss]
+//! it. In the [`Regular`] mode, that is done with `Tab` and `Esc`,
+//! and any other key dismisses it as well. The suggestion is also
+//! removed before any edit to the [`Text`], so it never ends up
+//! inside of a real change.
+//!
+//! [`File`]: crate::widgets::File
+//! [`Mode`]: crate::mode::Mode
+//! [requests one]: crate::mode::EditHelper::suggest
+//! [ghost text]: crate::text::Tag::GhostText
+//! [accept]: crate::mode::EditHelper::accept_suggestion
+//! [dismiss]: crate::mode::EditHelper::dismiss_suggestion
+//! [`Regular`]: crate::mode::Regular
+use crate::text::{Point, Text};
+
+/// Something that suggests text to insert at the cursor
+pub trait SuggestionProvider: Send + Sync + 'static {
+    /// The text that should be inserted at the `caret`, if any
+    ///
+    /// Only the text after the `caret` is returned, so a provider
+    /// that completes words should leave out the part that was
+    /// already typed.
+    fn suggest(&self, text: &Text, caret: Point) -> Option<String>;
+}
+
+/// Completes the word behind the cursor with other words in the
+/// [`Text`]
+///
+/// The closest word before the cursor that starts with what was
+/// typed is picked, followed by the closest one after it. Words
+/// are only completed after at least two [`char`]s were typed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Words;
+
+impl SuggestionProvider for Words {
+    fn suggest(&self, text: &Text, caret: Point) -> Option<String> {
+        let prefix: String = {
+            let mut prefix: Vec<char> = text
+                .chars_rev(caret)
+                .map(|(_, char)| char)
+                .take_while(|char| is_word_char(*char))
+                .collect();
+            prefix.reverse();
+            prefix.into_iter().collect()
+        };
+        if prefix.chars().count() < 2 || text.char_at(caret).is_some_and(is_word_char) {
+            return None;
+        }
+
+        let str = text.strs().concat();
+        let completions = |str: &str| {
+            str.split(|char: char| !is_word_char(char))
+                .filter(|word| word.len() > prefix.len() && word.starts_with(&prefix))
+                .map(|word| word[prefix.len()..].to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let start = caret.byte() as usize - prefix.len();
+        let before = completions(&str[..start]).pop();
+        before.or_else(|| {
+            completions(&str[caret.byte() as usize..])
+                .into_iter()
+                .next()
+        })
+    }
+}
+
+/// Whether a [`char`] is part of a word
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SuggestionProvider, Words};
+    use crate::text::{Point, Text};
+
+    #[test]
+    fn completes_from_closest_word() {
+        let text = Text::from("foobar foobaz\nfo foo\nfoolish\n");
+        let caret =
+            |text: &Text, line: u32, col: u32| text.point_at(text.point_at_line(line).byte() + col);
+
+        assert_eq!(
+            Words.suggest(&text, caret(&text, 1, 6)),
+            Some("baz".to_string())
+        );
+        assert_eq!(
+            Words.suggest(&text, caret(&text, 1, 2)),
+            Some("obaz".to_string())
+        );
+        assert_eq!(Words.suggest(&text, caret(&text, 1, 1)), None);
+        assert_eq!(Words.suggest(&text, caret(&text, 1, 5)), None);
+        assert_eq!(Words.suggest(&text, caret(&text, 2, 3)), None);
+        assert_eq!(Words.suggest(&text, Point::default()), None);
+    }
+
+    #[test]
+    fn suggestion_is_removed_before_edits() {
+        let mut text = Text::from("foo\n");
+        let at = text.point_at(3);
+        text.set_suggestion(at, "bar");
+        assert_eq!(text.suggestion(), Some((at, "bar")));
+
+        text.replace_range((at, at), "d");
+        assert_eq!(text.suggestion(), None);
+        assert_eq!(text.strs().concat(), "food\n");
+    }
+}
@@ -0,0 +1,430 @@
+//! Diffing of [`Text`]s and strings
+//!
+//! This module computes the differences between two sequences, be
+//! they lines, words, or anything else that can be compared, through
+//! [Myers' algorithm]. The result is a list of [`Hunk`]s, each
+//! representing a range in the old sequence that was replaced by a
+//! range in the new one:
+//!
+//! ```rust
+//! # use duat_core::text::diff::{self, Hunk};
+//! let old = "one\ntwo\nthree\n";
+//! let new = "one\n2\nthree\nfour\n";
+//!
+//! assert_eq!(diff::lines(old, new), vec![
+//!     Hunk { old: 1..2, new: 1..2 },
+//!     Hunk { old: 3..3, new: 3..4 },
+//! ]);
+//! ```
+//!
+//! This is meant to be used by anything that needs to compare
+//! versions of a [`Text`], like gutters showing changes since the
+//! last commit, reloading a [`File`] from disk, or previewing a
+//! replacement.
+//!
+//! [Myers' algorithm]: http://www.xmailserver.org/diff2.pdf
+//! [`File`]: crate::widgets::File
+use std::ops::Range;
+
+use super::Text;
+
+/// Past this many cells kept for backtracking, [`slices`] stops
+/// looking for the smallest changes
+const MAX_TRACE_CELLS: usize = 1 << 22;
+
+/// A change between two sequences
+///
+/// The `old` range was replaced by the `new` range. If `old` is
+/// empty, this is an insertion, if `new` is empty, this is a
+/// deletion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The range in the old sequence
+    pub old: Range<usize>,
+    /// The range in the new sequence
+    pub new: Range<usize>,
+}
+
+impl Hunk {
+    /// Whether this [`Hunk`] only inserts elements
+    pub fn is_insertion(&self) -> bool {
+        self.old.is_empty()
+    }
+
+    /// Whether this [`Hunk`] only deletes elements
+    pub fn is_deletion(&self) -> bool {
+        self.new.is_empty()
+    }
+
+    /// Whether this [`Hunk`] replaces elements with others
+    pub fn is_modification(&self) -> bool {
+        !self.old.is_empty() && !self.new.is_empty()
+    }
+}
+
+/// The [`Hunk`]s between two slices
+///
+/// If the slices are too different, with thousands of separate
+/// changes, the part between their common prefix and suffix is
+/// returned as one [`Hunk`], since finding the smallest changes
+/// would take too much memory.
+pub fn slices<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Hunk> {
+    // Common prefixes and suffixes are usually most of the sequences,
+    // and they are trivial to take out.
+    let prefix = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let Some(edits) = edits(old_mid, new_mid) else {
+        return vec![Hunk {
+            old: prefix..old.len() - suffix,
+            new: prefix..new.len() - suffix,
+        }];
+    };
+
+    let mut hunks = Vec::new();
+    let (mut o, mut n) = (prefix, prefix);
+    let mut cur: Option<Hunk> = None;
+
+    for edit in edits {
+        match edit {
+            Edit::Equal => {
+                hunks.extend(cur.take());
+                o += 1;
+                n += 1;
+                continue;
+            }
+            Edit::Delete => o += 1,
+            Edit::Insert => n += 1,
+        }
+
+        let hunk = cur.get_or_insert(Hunk {
+            old: o - (edit == Edit::Delete) as usize..o,
+            new: n - (edit == Edit::Insert) as usize..n,
+        });
+        hunk.old.end = o;
+        hunk.new.end = n;
+    }
+    hunks.extend(cur);
+
+    hunks
+}
+
+/// The [`Hunk`]s between the lines of two strings
+///
+/// The ranges in the [`Hunk`]s are line indices.
+pub fn lines(old: &str, new: &str) -> Vec<Hunk> {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+    slices(&old, &new)
+}
+
+/// The [`Hunk`]s between the lines of two strings, as byte ranges
+///
+/// This is the same as [`lines`], but the ranges in the [`Hunk`]s
+/// are the byte ranges of the lines, so they can be used to replace
+/// only the lines that changed, and to [map] positions from one
+/// string to the other.
+///
+/// [map]: map_index
+pub fn line_bytes(old: &str, new: &str) -> Vec<Hunk> {
+    let line_starts = |str: &str| {
+        let mut starts = vec![0];
+        starts.extend(str.match_indices('\n').map(|(b, _)| b + 1));
+        if starts.last() != Some(&str.len()) {
+            starts.push(str.len());
+        }
+        starts
+    };
+    let (old_starts, new_starts) = (line_starts(old), line_starts(new));
+
+    lines(old, new)
+        .into_iter()
+        .map(|hunk| Hunk {
+            old: old_starts[hunk.old.start]..old_starts[hunk.old.end],
+            new: new_starts[hunk.new.start]..new_starts[hunk.new.end],
+        })
+        .collect()
+}
+
+/// The [`Hunk`]s between the lines of two [`Text`]s
+///
+/// The ranges in the [`Hunk`]s are line indices.
+pub fn text_lines(old: &Text, new: &Text) -> Vec<Hunk> {
+    lines(&old.to_string(), &new.to_string())
+}
+
+/// The [`Hunk`]s between the words of two strings
+///
+/// Words are runs of alphanumeric characters, runs of whitespace,
+/// or single characters of any other kind. The ranges in the
+/// [`Hunk`]s are byte ranges, which makes this useful for
+/// highlighting the changes within modified lines.
+pub fn words(old: &str, new: &str) -> Vec<Hunk> {
+    let old_words = split_words(old);
+    let new_words = split_words(new);
+
+    let byte_range = |words: &[(usize, &str)], range: Range<usize>, len: usize| {
+        let start = words.get(range.start).map_or(len, |(b, _)| *b);
+        let end = words.get(range.end).map_or(len, |(b, _)| *b);
+        start..end
+    };
+
+    let old_strs: Vec<&str> = old_words.iter().map(|(_, word)| *word).collect();
+    let new_strs: Vec<&str> = new_words.iter().map(|(_, word)| *word).collect();
+
+    slices(&old_strs, &new_strs)
+        .into_iter()
+        .map(|hunk| Hunk {
+            old: byte_range(&old_words, hunk.old, old.len()),
+            new: byte_range(&new_words, hunk.new, new.len()),
+        })
+        .collect()
+}
+
+/// An edit in the shortest edit script between two sequences
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// The shortest edit script between two slices, through Myers'
+/// algorithm
+///
+/// Returns [`None`] if the slices are so different that keeping
+/// track of the edits would take more than [`MAX_TRACE_CELLS`].
+fn edits<T: PartialEq>(old: &[T], new: &[T]) -> Option<Vec<Edit>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize;
+
+    // Whether the furthest path on diagonal `k` came from a deletion.
+    let goes_down =
+        |v: &dyn Fn(isize) -> isize, k: isize, d: isize| k == -d || (k != d && v(k - 1) < v(k + 1));
+
+    let mut v = vec![0isize; 2 * max + 2];
+    // Only the diagonals reachable at each `d` are kept, which is
+    // all that is needed in order to backtrack.
+    let mut trace: Vec<(usize, Vec<isize>)> = Vec::new();
+    let mut cells = 0;
+
+    'search: for d in 0..=max as isize {
+        let start = (offset - d - 1).max(0) as usize;
+        let end = (offset + d + 2) as usize;
+        cells += end - start;
+        if cells > MAX_TRACE_CELLS {
+            return None;
+        }
+        trace.push((start, v[start..end].to_vec()));
+
+        for k in (-d..=d).step_by(2) {
+            let get = |k: isize| v[(k + offset) as usize];
+            let mut x = match goes_down(&get, k, d) {
+                true => get(k + 1),
+                false => get(k - 1) + 1,
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[(k + offset) as usize] = x;
+
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::with_capacity(max);
+    let (mut x, mut y) = (n, m);
+
+    for (d, (start, v)) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let get = |k: isize| v[(k + offset) as usize - start];
+
+        let prev_k = match goes_down(&get, k, d) {
+            true => k + 1,
+            false => k - 1,
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+
+        if d > 0 {
+            edits.push(match x == prev_x {
+                true => Edit::Insert,
+                false => Edit::Delete,
+            });
+        }
+
+        (x, y) = (prev_x, prev_y);
+    }
+
+    edits.reverse();
+    Some(edits)
+}
+
+/// Maps an index in the old sequence to the new one, given their
+/// [`Hunk`]s
+///
+/// Indices outside of the [`Hunk`]s are shifted by the difference in
+/// length of the [`Hunk`]s before them, while indices inside of one
+/// are kept at the same offset from its start, as long as that fits
+/// in the new range.
+pub fn map_index(hunks: &[Hunk], index: usize) -> usize {
+    let mut shift = 0isize;
+    for hunk in hunks {
+        if index < hunk.old.start {
+            break;
+        } else if index < hunk.old.end {
+            let offset = (index - hunk.old.start).min(hunk.new.len());
+            return hunk.new.start + offset;
+        }
+        shift += hunk.new.len() as isize - hunk.old.len() as isize;
+    }
+    index.saturating_add_signed(shift)
+}
+
+/// Splits a string into words, alongside their byte positions
+fn split_words(str: &str) -> Vec<(usize, &str)> {
+    #[derive(PartialEq)]
+    enum Kind {
+        Word,
+        Space,
+        Other,
+    }
+
+    let kind = |char: char| {
+        if char.is_alphanumeric() || char == '_' {
+            Kind::Word
+        } else if char.is_whitespace() {
+            Kind::Space
+        } else {
+            Kind::Other
+        }
+    };
+
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut last = None;
+
+    for (b, char) in str.char_indices() {
+        let cur = kind(char);
+        if let Some(last) = &last
+            && (*last != cur || cur == Kind::Other)
+        {
+            words.push((start, &str[start..b]));
+            start = b;
+        }
+        last = Some(cur);
+    }
+
+    if start < str.len() {
+        words.push((start, &str[start..]));
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hunk, line_bytes, lines, map_index, slices, words};
+
+    fn hunk(old: std::ops::Range<usize>, new: std::ops::Range<usize>) -> Hunk {
+        Hunk { old, new }
+    }
+
+    #[test]
+    fn empty_inputs() {
+        assert_eq!(lines("", ""), []);
+        assert_eq!(lines("", "one\n"), [hunk(0..0, 0..1)]);
+        assert_eq!(lines("one\n", ""), [hunk(0..1, 0..0)]);
+        assert_eq!(words("", ""), []);
+    }
+
+    #[test]
+    fn all_inserted_and_all_deleted() {
+        let hunks = lines("", "one\ntwo\nthree\n");
+        assert_eq!(hunks, [hunk(0..0, 0..3)]);
+        assert!(hunks[0].is_insertion());
+
+        let hunks = lines("one\ntwo\nthree\n", "");
+        assert_eq!(hunks, [hunk(0..3, 0..0)]);
+        assert!(hunks[0].is_deletion());
+
+        assert_eq!(slices(&[1, 2, 3], &[4, 5]), [hunk(0..3, 0..2)]);
+    }
+
+    #[test]
+    fn separate_changes_are_separate_hunks() {
+        let old = [1, 2, 3, 4, 5, 6, 7];
+        let new = [1, 9, 3, 4, 6, 7, 8];
+        assert_eq!(slices(&old, &new), [
+            hunk(1..2, 1..2),
+            hunk(4..5, 4..4),
+            hunk(7..7, 6..7)
+        ]);
+    }
+
+    #[test]
+    fn words_are_byte_ranges() {
+        let old = "let foo = bar(1);";
+        let new = "let foo_bar = bar(2);";
+        assert_eq!(words(old, new), [hunk(4..7, 4..11), hunk(14..15, 18..19)]);
+    }
+
+    #[test]
+    fn trailing_line_without_newline() {
+        assert_eq!(lines("one\ntwo", "one\ntwo\n"), [hunk(1..2, 1..2)]);
+        assert_eq!(lines("one\ntwo", "one\ntwo\nthree"), [hunk(1..2, 1..3)]);
+        assert_eq!(line_bytes("one\ntwo", "one\n2"), [hunk(4..7, 4..5)]);
+    }
+
+    #[test]
+    fn line_bytes_and_mapping() {
+        let old = "one\ntwo\nthree\nfour\n";
+        let new = "one\n2\nthree\nfour\nfive\n";
+        let hunks = line_bytes(old, new);
+        assert_eq!(hunks, [hunk(4..8, 4..6), hunk(19..19, 17..22)]);
+
+        // Before, inside, and after a changed line.
+        assert_eq!(map_index(&hunks, 2), 2);
+        assert_eq!(map_index(&hunks, 5), 5);
+        assert_eq!(map_index(&hunks, 7), 6);
+        assert_eq!(map_index(&hunks, 10), 8);
+        assert_eq!(map_index(&hunks, 19), 22);
+    }
+
+    #[test]
+    fn very_different_inputs_are_one_hunk() {
+        let old: Vec<usize> = (0..6000).map(|i| i * 2).collect();
+        let new: Vec<usize> = (0..6000).map(|i| i * 2 + 1).collect();
+        let mut mixed = old.clone();
+        mixed[0] = 1;
+        mixed[5999] = 1;
+
+        assert_eq!(slices(&old, &new), [hunk(0..6000, 0..6000)]);
+        assert_eq!(slices(&old, &mixed), [
+            hunk(0..1, 0..1),
+            hunk(5999..6000, 5999..6000)
+        ]);
+    }
+}
@@ -9,18 +9,65 @@
 //! well as Kakoune style undoing (multiple [`Change`]s per
 //! [`Moment`]).
 //!
+//! Separately, a [`SelectionHistory`] keeps the states that the
+//! [`Cursors`] went through since the last edit, so that selections
+//! can be undone without touching the [`Text`].
+//!
 //! [`undo`]: Text::undo
 //! [`redo`]: Text::redo
-use std::ops::Range;
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use super::{Point, Text};
-use crate::binary_search_by_key_and_index;
+use crate::{
+    binary_search_by_key_and_index,
+    mode::Cursors,
+    opts::{self, OptValue, Scope},
+};
+
+static MOMENTS_ARE_GROUPED: AtomicBool = AtomicBool::new(false);
+/// How many states a [`SelectionHistory`] can hold
+const MAX_SELECTION_STATES: usize = 100;
+
+/// Sets whether new [`Moment`]s can be started, returning the
+/// previous value
+///
+/// While grouped, [`History::new_moment`] does nothing, so that a
+/// sequence of actions (like a replayed macro) can be undone at once.
+pub(crate) fn group_moments(grouped: bool) -> bool {
+    MOMENTS_ARE_GROUPED.swap(grouped, Ordering::Relaxed)
+}
+
+/// Declares the `undo-cursors` option
+///
+/// With `"restore"`, undoing and redoing brings back the [`Cursors`]
+/// as they were before and after the [`Moment`]. With `"change"`, a
+/// [`Cursor`] is placed on each [`Change`] instead.
+///
+/// [`Cursor`]: crate::mode::Cursor
+pub(crate) fn declare_opts() {
+    opts::declare_enum(
+        "undo-cursors",
+        "restore",
+        &["restore", "change"],
+        Scope::Global,
+    );
+}
+
+/// Whether undoing and redoing should restore the [`Cursors`]
+pub(crate) fn restores_cursors() -> bool {
+    opts::get("undo-cursors") != Some(OptValue::Enum("change"))
+}
 
 /// The history of edits, contains all moments
 #[derive(Default, Debug, Clone)]
 pub struct History {
     moments: Vec<Moment>,
     current_moment: usize,
+    cursors_before: Option<Cursors>,
+    selections: SelectionHistory,
 }
 
 impl History {
@@ -44,9 +91,10 @@ impl History {
         {
             moment
         } else {
-            self.new_moment();
+            self.push_moment();
             self.moments.last_mut().unwrap()
         };
+        self.selections.clear();
         moment.add_change(guess_i, change)
     }
 
@@ -74,20 +122,53 @@ impl History {
         {
             moment
         } else {
-            self.new_moment();
+            self.push_moment();
             self.moments.last_mut().unwrap()
         };
+        if moment.changes.is_empty() {
+            moment.before = self.cursors_before.take();
+        }
+        self.selections.clear();
         moment.add_desync_change(guess_i, change, shift, sh_from)
     }
 
+    /// Records the [`Cursors`] before an edit
+    ///
+    /// If the edit starts a new [`Moment`], these are the [`Cursors`]
+    /// it will be undone to.
+    pub fn record_cursors_before(&mut self, cursors: &Cursors) {
+        self.cursors_before = Some(cursors.clone());
+    }
+
+    /// Records the [`Cursors`] after an edit
+    ///
+    /// The last [`Cursors`] recorded in a [`Moment`] are the ones it
+    /// will be redone to.
+    pub fn record_cursors_after(&mut self, cursors: &Cursors) {
+        let is_last_moment = self.current_moment == self.moments.len();
+        if let Some(moment) = self.moments.last_mut()
+            && is_last_moment
+            && !moment.changes.is_empty()
+        {
+            moment.after = Some(cursors.clone());
+        }
+    }
+
     /// Declares that the current moment is complete and starts a
     /// new one
     pub fn new_moment(&mut self) {
+        if !MOMENTS_ARE_GROUPED.load(Ordering::Relaxed) {
+            self.push_moment();
+        }
+    }
+
+    /// Starts a new moment, even if they are being grouped
+    fn push_moment(&mut self) {
         let is_last_moment = self.current_moment == self.moments.len();
         // If the last moment in history is empty, we can keep using it.
-        if !is_last_moment || self.moments.last().is_none_or(|m| !m.0.is_empty()) {
+        if !is_last_moment || self.moments.last().is_none_or(|m| !m.changes.is_empty()) {
             self.moments.truncate(self.current_moment);
-            self.moments.push(Moment(Vec::new()));
+            self.moments.push(Moment::default());
             self.current_moment += 1;
         }
     }
@@ -96,14 +177,15 @@ impl History {
     ///
     /// If The [History] is already at the end, returns [None]
     /// instead.
-    pub fn move_forward(&mut self) -> Option<&[Change<String>]> {
+    pub fn move_forward(&mut self) -> Option<&Moment> {
         if self.current_moment == self.moments.len()
-            || self.moments[self.current_moment].0.is_empty()
+            || self.moments[self.current_moment].changes.is_empty()
         {
             None
         } else {
             self.current_moment += 1;
-            Some(&self.moments[self.current_moment - 1].0)
+            self.selections.clear();
+            Some(&self.moments[self.current_moment - 1])
         }
     }
 
@@ -111,16 +193,17 @@ impl History {
     ///
     /// If The [History] is already at the start, returns [None]
     /// instead.
-    pub fn move_backwards(&mut self) -> Option<&[Change<String>]> {
+    pub fn move_backwards(&mut self) -> Option<&Moment> {
         if self.current_moment == 0 {
             None
         } else {
             self.current_moment -= 1;
 
-            if self.moments[self.current_moment].0.is_empty() {
+            if self.moments[self.current_moment].changes.is_empty() {
                 self.move_backwards()
             } else {
-                Some(&self.moments[self.current_moment].0)
+                self.selections.clear();
+                Some(&self.moments[self.current_moment])
             }
         }
     }
@@ -130,22 +213,149 @@ impl History {
 
         // Check, in order to prevent modification of earlier moments.
         if self.moments.last().is_none() || !is_last_moment {
-            self.new_moment();
+            self.push_moment();
         }
 
-        &mut self.moments.last_mut().unwrap().0
+        &mut self.moments.last_mut().unwrap().changes
     }
+
+    /// The [`SelectionHistory`] since the last edit
+    pub fn selections(&mut self) -> &mut SelectionHistory {
+        &mut self.selections
+    }
+}
+
+/// The states that the [`Cursors`] went through since the last edit
+///
+/// Since stored [`Cursors`] would not match an edited [`Text`], this
+/// history is cleared whenever a [`Change`] is made, undone or
+/// redone. Consecutive states with a single [`Cursor`] and no
+/// selection replace each other, so that plain movement doesn't
+/// drown out the states worth going back to.
+///
+/// [`Cursor`]: crate::mode::Cursor
+#[derive(Default, Debug, Clone)]
+pub struct SelectionHistory {
+    states: Vec<Cursors>,
+    current: usize,
+}
+
+impl SelectionHistory {
+    /// Records a state of the [`Cursors`]
+    ///
+    /// Does nothing if it is the same as the current state. If
+    /// states were undone, they are discarded.
+    pub fn record(&mut self, cursors: &Cursors) {
+        if let Some(cur) = self.states.get(self.current) {
+            if same_selections(cur, cursors) {
+                return;
+            }
+
+            let replaces_cur = is_trivial(cur) && is_trivial(cursors);
+            self.states.truncate(self.current + !replaces_cur as usize);
+        }
+
+        self.push(cursors);
+    }
+
+    /// Records a jump from one state of the [`Cursors`] to another
+    ///
+    /// Unlike with [`record`], the origin is kept even if both states
+    /// are plain carets, so the jump can always be undone.
+    ///
+    /// [`record`]: Self::record
+    pub fn record_jump(&mut self, from: &Cursors, to: &Cursors) {
+        self.record(from);
+        if same_selections(&self.states[self.current], to) {
+            return;
+        }
+
+        self.states.truncate(self.current + 1);
+        self.push(to);
+    }
+
+    /// Goes back to the previous state, if there is one
+    pub fn undo(&mut self) -> Option<&Cursors> {
+        self.current = self.current.checked_sub(1)?;
+        Some(&self.states[self.current])
+    }
+
+    /// Goes forward to the next state, if there is one
+    pub fn redo(&mut self) -> Option<&Cursors> {
+        if self.current + 1 >= self.states.len() {
+            return None;
+        }
+        self.current += 1;
+        Some(&self.states[self.current])
+    }
+
+    /// Forgets every recorded state
+    pub fn clear(&mut self) {
+        self.states.clear();
+        self.current = 0;
+    }
+
+    /// Pushes a state, making it the current one
+    fn push(&mut self, cursors: &Cursors) {
+        self.states.push(cursors.clone());
+        if self.states.len() > MAX_SELECTION_STATES {
+            self.states.remove(0);
+        }
+        self.current = self.states.len() - 1;
+    }
+}
+
+/// Whether two [`Cursors`] select the same ranges
+fn same_selections(lhs: &Cursors, rhs: &Cursors) -> bool {
+    lhs.len() == rhs.len()
+        && lhs.main_index() == rhs.main_index()
+        && lhs
+            .iter()
+            .zip(rhs.iter())
+            .all(|((lhs, _), (rhs, _))| lhs.caret() == rhs.caret() && lhs.anchor() == rhs.anchor())
+}
+
+/// Whether the [`Cursors`] are just one caret, with no selection
+fn is_trivial(cursors: &Cursors) -> bool {
+    cursors.len() == 1 && cursors.iter().all(|(cursor, _)| cursor.anchor().is_none())
 }
 
 /// A moment in history, which may contain changes, or may just
 /// contain selections
 ///
-/// It also contains information about how to print the file, so that
-/// going back in time is less jarring.
+/// It also contains the [`Cursors`] from before and after its
+/// [`Change`]s, so that going back in time is less jarring.
 #[derive(Default, Debug, Clone)]
-pub struct Moment(Vec<Change<String>>);
+pub struct Moment {
+    changes: Vec<Change<String>>,
+    before: Option<Cursors>,
+    after: Option<Cursors>,
+}
 
 impl Moment {
+    /// The [`Change`]s of this [`Moment`]
+    pub fn changes(&self) -> &[Change<String>] {
+        &self.changes
+    }
+
+    /// The [`Cursors`] from before the first [`Change`]
+    ///
+    /// Only [`Change`]s made through an [`EditHelper`] record them.
+    ///
+    /// [`EditHelper`]: crate::mode::EditHelper
+    pub fn cursors_before(&self) -> Option<&Cursors> {
+        self.before.as_ref()
+    }
+
+    /// The [`Cursors`] from after the last [`Change`]
+    ///
+    /// Only [`Change`]s made through an [`EditHelper`] record them.
+    ///
+    /// [`EditHelper`]: crate::mode::EditHelper
+    pub fn cursors_after(&self) -> Option<&Cursors> {
+        self.after.as_ref()
+    }
+
     /// First try to merge this change with as many changes as
     /// possible, then add it in
     ///
@@ -203,20 +413,21 @@ impl Moment {
         let sh_from = sh_from.unwrap_or(usize::MAX);
         let sh = |n: usize| if sh_from <= n { shift } else { (0, 0, 0) };
 
-        let initial_len = self.0.len();
+        let initial_len = self.changes.len();
 
         let c_i = if let Some(guess_i) = guess_i
-            && let Some(c) = self.0.get(guess_i)
+            && let Some(c) = self.changes.get(guess_i)
             && c.start.shift_by(sh(guess_i)) <= change.start
             && change.start <= c.added_end().shift_by(sh(guess_i))
         {
             guess_i
         } else {
             let f = |i: usize, c: &Change<String>| c.start.shift_by(sh(i));
-            match binary_search_by_key_and_index(&self.0, change.start, f) {
+            match binary_search_by_key_and_index(&self.changes, change.start, f) {
                 Err(i)
                     if let Some(prev_i) = i.checked_sub(1)
-                        && change.start <= self.0[prev_i].added_end().shift_by(sh(prev_i)) =>
+                        && change.start
+                            <= self.changes[prev_i].added_end().shift_by(sh(prev_i)) =>
                 {
                     i - 1
                 }
@@ -225,7 +436,7 @@ impl Moment {
         };
 
         let end_i = if self
-            .0
+            .changes
             .get(c_i + 1)
             .is_none_or(|c| change.taken_end() < c.start.shift_by(sh(c_i + 1)))
         {
@@ -233,11 +444,11 @@ impl Moment {
         } else {
             let f = |i: usize, c: &Change<String>| c.start.shift_by(sh(c_i + 1 + i));
             let (Ok(i) | Err(i)) =
-                binary_search_by_key_and_index(&self.0[c_i + 1..], change.taken_end(), f);
+                binary_search_by_key_and_index(&self.changes[c_i + 1..], change.taken_end(), f);
             c_i + 1 + i
         };
 
-        let merged_ahead = if let Some(prev_change) = self.0.get_mut(end_i) {
+        let merged_ahead = if let Some(prev_change) = self.changes.get_mut(end_i) {
             let mut older = std::mem::take(prev_change);
             let prev_start = older.start;
             older.shift_by(sh(end_i));
@@ -245,7 +456,7 @@ impl Moment {
             let (changes_after, merged) = if let Some(mut older) = change.try_merge(older) {
                 older.start = prev_start;
                 *prev_change = older;
-                self.0.insert(end_i, change);
+                self.changes.insert(end_i, change);
                 (end_i + 2, false)
             } else {
                 *prev_change = change;
@@ -253,24 +464,28 @@ impl Moment {
             };
 
             if shift != (0, 0, 0) && sh_from == usize::MAX {
-                for change in &mut self.0[changes_after..] {
+                for change in &mut self.changes[changes_after..] {
                     change.start = change.start.shift_by(shift)
                 }
             }
             merged
         } else {
-            self.0.insert(end_i, change);
+            self.changes.insert(end_i, change);
             false
         };
 
-        let prior_changes: Vec<Change<String>> = self.0.drain(c_i..end_i).collect();
-        let added_change = self.0.get_mut(c_i).unwrap();
+        let prior_changes: Vec<Change<String>> = self.changes.drain(c_i..end_i).collect();
+        let added_change = self.changes.get_mut(c_i).unwrap();
         for (i, mut c) in prior_changes.into_iter().enumerate() {
             c.shift_by(sh(c_i + i));
             let _ = added_change.try_merge(c);
         }
 
-        (c_i, self.0.len() as i32 - initial_len as i32, merged_ahead)
+        (
+            c_i,
+            self.changes.len() as i32 - initial_len as i32,
+            merged_ahead,
+        )
     }
 }
 
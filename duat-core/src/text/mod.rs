@@ -70,19 +70,32 @@
 //! [`Mode`]: crate::mode::Mode
 //! [`EditHelper`]: crate::mode::EditHelper
 mod builder;
+pub mod diff;
 mod history;
 mod iter;
 mod reader;
 mod records;
 mod search;
+mod sink;
 mod tags;
 
-use std::{ops::RangeBounds, path::Path, rc::Rc, str::from_utf8_unchecked, sync::Arc};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::RangeBounds,
+    path::Path,
+    rc::Rc,
+    str::from_utf8_unchecked,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use gapbuf::GapBuffer;
 use history::History;
 use records::Records;
 use tags::{FwdTags, RevTags};
+use unicode_segmentation::GraphemeCursor;
 
 use self::tags::Tags;
 pub use self::{
@@ -90,10 +103,16 @@ pub use self::{
     history::Change,
     iter::{Item, Iter, RevIter},
     part::Part,
-    point::{Point, TwoPoints, utf8_char_width},
+    point::{Point, TwoPoints},
     reader::{Reader, TreeSitter},
-    search::{RegexPattern, Searcher},
-    tags::{Key, Keys, Tag, ToggleId},
+    search::{Case, Fuzzy, Literal, Matcher, RegexPattern, SearchFlags, Searcher},
+    sink::TextSink,
+    tags::{Key, Keys, Tag, TextId, ToggleId},
+};
+pub(crate) use self::{
+    history::{declare_opts as declare_history_opts, group_moments},
+    point::utf8_char_width,
+    search::declare_opts as declare_search_opts,
 };
 use crate::{
     DuatError,
@@ -102,8 +121,13 @@ use crate::{
     ui::Area,
 };
 
+/// How many bytes apart the [`Records`] of big chunks of text are
+const BYTES_PER_RECORD: usize = 1024;
+/// How many [`char`]s around a [`Point`] are looked at in order to
+/// find grapheme cluster boundaries
+const GRAPHEME_CONTEXT: usize = 32;
+
 /// The text in a given [`Area`]
-#[derive(Default)]
 pub struct Text {
     buf: Box<GapBuffer<u8>>,
     tags: Box<Tags>,
@@ -111,6 +135,9 @@ pub struct Text {
     history: History,
     readers: Vec<Box<dyn Reader>>,
     tree_sitter: Option<Box<TreeSitter>>,
+    revision: Revision,
+    cursors_hash: u64,
+    suggestion: Option<Box<(Point, String)>>,
 }
 
 impl Text {
@@ -125,6 +152,9 @@ impl Text {
             history: History::new(),
             readers: Vec::new(),
             tree_sitter: None,
+            revision: Revision::new(),
+            cursors_hash: 0,
+            suggestion: None,
         }
     }
 
@@ -139,14 +169,13 @@ impl Text {
         let mut text = Self {
             buf,
             tags,
-            records: Box::new(Records::with_max((
-                file.len() as u32,
-                file.chars().count() as u32,
-                file.bytes().filter(|b| *b == b'\n').count() as u32,
-            ))),
+            records: Box::new(records_of(&file)),
             history: History::new(),
             readers: Vec::new(),
             tree_sitter: None,
+            revision: Revision::new(),
+            cursors_hash: 0,
+            suggestion: None,
         };
 
         let tree_sitter = TreeSitter::new(&mut text, path);
@@ -171,6 +200,24 @@ impl Text {
 
     ////////// Querying functions
 
+    /// The revision of this [`Text`]
+    ///
+    /// This number changes whenever the [`Text`] is modified, be it
+    /// through edits, or through the addition and removal of
+    /// [`Tag`]s, or the movement of [`Cursor`]s. It only changes once
+    /// for a batch of modifications made between two calls. Revisions
+    /// are unique across all [`Text`]s, so if the [`Text`] of a
+    /// [`Widget`] is replaced wholesale, its revision will also
+    /// be different.
+    ///
+    /// This makes it a cheap way to check if a [`Text`] needs to be
+    /// printed again, or if something derived from it is outdated.
+    ///
+    /// [`Widget`]: crate::widgets::Widget
+    pub fn revision(&self) -> u64 {
+        self.revision.get()
+    }
+
     /// The [`Point`] at the end of the text
     pub fn len(&self) -> Point {
         let (b, c, l) = self.records.max();
@@ -198,6 +245,72 @@ impl Text {
         }
     }
 
+    /// Whether a [`Point`] is at the start of a grapheme cluster
+    ///
+    /// A grapheme cluster is what is seen as one character on screen,
+    /// like a letter followed by combining accents, or an emoji made
+    /// out of many [`char`]s joined together. Cursors should never be
+    /// placed inside of one.
+    pub fn is_grapheme_boundary(&self, point: Point) -> bool {
+        let (Some((_, prev)), Some(next)) = (self.chars_rev(point).next(), self.char_at(point))
+        else {
+            return true;
+        };
+        if prev.is_ascii() && next.is_ascii() {
+            return !(prev == '\r' && next == '\n');
+        }
+
+        let start = self.chars_rev(point).take(GRAPHEME_CONTEXT).last();
+        let start = start.map_or(point, |(start, _)| start);
+        let end = self.chars_fwd(point).nth(GRAPHEME_CONTEXT);
+        let end = end.map_or(self.len(), |(end, _)| end);
+
+        let str = self.strs_in_range((start, end)).concat();
+        let offset = (point.byte() - start.byte()) as usize;
+        // Without more context, a boundary is the better guess.
+        GraphemeCursor::new(offset, str.len(), true)
+            .is_boundary(&str, 0)
+            .unwrap_or(true)
+    }
+
+    /// The start of the grapheme cluster that contains a [`Point`]
+    ///
+    /// If the [`Point`] is already at the start of one, it is
+    /// returned as is.
+    pub fn grapheme_start(&self, point: Point) -> Point {
+        if self.is_grapheme_boundary(point) {
+            return point;
+        }
+        self.chars_rev(point)
+            .map(|(p, _)| p)
+            .find(|p| self.is_grapheme_boundary(*p))
+            .unwrap_or_default()
+    }
+
+    /// A forward iterator over the starts of grapheme clusters
+    ///
+    /// The first [`Point`] returned is the start of the [grapheme
+    /// cluster] that contains `p`.
+    ///
+    /// [grapheme cluster]: Self::is_grapheme_boundary
+    pub fn graphemes_fwd(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.chars_fwd(self.grapheme_start(p))
+            .map(|(p, _)| p)
+            .filter(|p| self.is_grapheme_boundary(*p))
+    }
+
+    /// A reverse iterator over the starts of grapheme clusters
+    ///
+    /// The first [`Point`] returned is the start of the [grapheme
+    /// cluster] before the one that contains `p`.
+    ///
+    /// [grapheme cluster]: Self::is_grapheme_boundary
+    pub fn graphemes_rev(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.chars_rev(self.grapheme_start(p))
+            .map(|(p, _)| p)
+            .filter(|p| self.is_grapheme_boundary(*p))
+    }
+
     /// The two [`&str`]s that compose the [buffer]
     ///
     /// In order to iterate over them, I recommend using the
@@ -373,7 +486,7 @@ impl Text {
                 .last()
         } else {
             let mut c_len = 0;
-            self.strs_in_range_inner(..)
+            self.strs_in_range_inner(..b)
                 .into_iter()
                 .flat_map(str::chars)
                 .rev()
@@ -564,6 +677,8 @@ impl Text {
     /// Merges `String`s with the body of text, given a range to
     /// replace
     fn replace_range_inner(&mut self, change: Change<&str>) {
+        self.take_suggestion();
+
         let mut readers = std::mem::take(&mut self.readers);
         let mut ts = self.tree_sitter.take();
         for reader in readers.iter_mut() {
@@ -594,6 +709,9 @@ impl Text {
         let start_rec = (start.byte(), start.char(), start.line());
         self.records.transform(start_rec, old_len, new_len);
         self.records.insert(start_rec);
+        if edit.len() >= BYTES_PER_RECORD {
+            add_records(&mut self.records, start_rec, edit);
+        }
 
         self.tags
             .transform(start.byte()..taken_end.byte(), change.added_end().byte());
@@ -606,6 +724,7 @@ impl Text {
         }
         self.readers = readers;
         self.tree_sitter = ts;
+        self.revision.outdate();
     }
 
     /// Adds a new [`Reader`] to this [`Text`]
@@ -623,11 +742,12 @@ impl Text {
             return;
         };
 
+        let prev = cursors.clone();
         cursors.clear();
 
         let mut shift = (0, 0, 0);
 
-        for (i, change) in moment.iter().enumerate() {
+        for (i, change) in moment.changes().iter().enumerate() {
             let mut change = change.as_ref();
             change.shift_by(shift);
             self.replace_range_inner(change.reverse());
@@ -640,6 +760,13 @@ impl Text {
             shift.2 += change.taken_end().line() as i32 - change.added_end().line() as i32;
         }
 
+        if history::restores_cursors()
+            && let Some(before) = moment.cursors_before()
+        {
+            cursors.restore(before);
+        } else {
+            cursors.keep_desired_cols_of(&prev);
+        }
         self.history = history;
     }
 
@@ -650,15 +777,23 @@ impl Text {
             return;
         };
 
+        let prev = cursors.clone();
         cursors.clear();
 
-        for (i, change) in moment.iter().enumerate() {
+        for (i, change) in moment.changes().iter().enumerate() {
             let start = change.start();
             self.replace_range_inner(change.as_ref());
 
             cursors.insert_from_parts(i, start, change.added_text().len(), self, area, cfg);
         }
 
+        if history::restores_cursors()
+            && let Some(after) = moment.cursors_after()
+        {
+            cursors.restore(after);
+        } else {
+            cursors.keep_desired_cols_of(&prev);
+        }
         self.history = history;
     }
 
@@ -671,6 +806,59 @@ impl Text {
         self.history.changes_mut()
     }
 
+    /// Records the [`Cursors`] before an edit, to restore on undos
+    pub(crate) fn record_cursors_before(&mut self, cursors: &Cursors) {
+        self.history.record_cursors_before(cursors);
+    }
+
+    /// Records the [`Cursors`] after an edit, to restore on redos
+    pub(crate) fn record_cursors_after(&mut self, cursors: &Cursors) {
+        self.history.record_cursors_after(cursors);
+    }
+
+    /// Records a state of the [`Cursors`], to go back to with
+    /// [`selection_undo`]
+    ///
+    /// [`selection_undo`]: Self::selection_undo
+    pub(crate) fn record_selections(&mut self, cursors: &Cursors) {
+        self.history.selections().record(cursors);
+    }
+
+    /// Records a jump of the [`Cursors`], which [`selection_undo`]
+    /// can then go back from
+    ///
+    /// [`selection_undo`]: Self::selection_undo
+    pub(crate) fn record_jump(&mut self, from: &Cursors, to: &Cursors) {
+        self.history.selections().record_jump(from, to);
+    }
+
+    /// Goes back to the previous selections, without editing
+    ///
+    /// Only selections since the last edit are remembered. Returns
+    /// `false` if there was nothing to go back to.
+    pub fn selection_undo(&mut self, cursors: &mut Cursors) -> bool {
+        self.history.selections().record(cursors);
+        let Some(prev) = self.history.selections().undo() else {
+            return false;
+        };
+        cursors.restore(prev);
+        true
+    }
+
+    /// Goes forward to the next selections, undoing a
+    /// [`selection_undo`]
+    ///
+    /// Returns `false` if there was nothing to go forward to.
+    ///
+    /// [`selection_undo`]: Self::selection_undo
+    pub fn selection_redo(&mut self, cursors: &mut Cursors) -> bool {
+        let Some(next) = self.history.selections().redo() else {
+            return false;
+        };
+        cursors.restore(next);
+        true
+    }
+
     ////////// Writing functions
 
     /// Clones the inner [`GapBuffer`] as a [`String`]
@@ -741,13 +929,15 @@ impl Text {
     /// Inserts a [`Tag`] at the given position
     pub fn insert_tag(&mut self, at: u32, tag: Tag, key: Key) {
         self.tags.insert(at, tag, key);
+        self.revision.outdate();
     }
 
     /// Removes all the [`Tag`]s from a position related to a [key]
     ///
     /// [key]: Keys
     pub fn remove_tags_on(&mut self, at: u32, keys: impl Keys) {
-        self.tags.remove_at(at, keys)
+        self.tags.remove_at(at, keys);
+        self.revision.outdate();
     }
 
     /// Removes the [`Tag`]s of a [key] from the whole [`Text`]
@@ -762,7 +952,53 @@ impl Text {
     /// [key]: Keys
     /// [`File`]: crate::widgets::File
     pub fn remove_tags_of(&mut self, keys: impl Keys) {
-        self.tags.remove_of(keys)
+        self.tags.remove_of(keys);
+        self.revision.outdate();
+    }
+
+    ////////// Suggestion functions
+
+    /// Shows a suggestion as [ghost text] at a [`Point`]
+    ///
+    /// Only one suggestion can be shown at a time, so this replaces
+    /// the previous one. It is also removed before any edit to the
+    /// [`Text`], so it never gets in the way of real changes.
+    ///
+    /// [ghost text]: Tag::GhostText
+    pub fn set_suggestion(&mut self, at: Point, suggestion: impl ToString) {
+        self.take_suggestion();
+        let suggestion = suggestion.to_string();
+        if suggestion.is_empty() {
+            return;
+        }
+
+        let ghost = Tag::GhostText(text!([Suggestion] { suggestion.clone() }));
+        self.tags.insert(at.byte(), ghost, Key::for_suggestion());
+        self.suggestion = Some(Box::new((at, suggestion)));
+        self.revision.outdate();
+    }
+
+    /// The suggestion being shown, and where it is
+    pub fn suggestion(&self) -> Option<(Point, &str)> {
+        self.suggestion
+            .as_ref()
+            .map(|suggestion| (suggestion.0, suggestion.1.as_str()))
+    }
+
+    /// Removes the suggestion being shown, returning it
+    pub fn take_suggestion(&mut self) -> Option<(Point, String)> {
+        let (at, suggestion) = *self.suggestion.take()?;
+        self.tags.remove_at(at.byte(), Key::for_suggestion());
+        self.revision.outdate();
+        Some((at, suggestion))
+    }
+
+    /// The [`Text`] of an [`EolGhost`] or [`RightAligned`] [`Tag`]
+    ///
+    /// [`EolGhost`]: Tag::EolGhost
+    /// [`RightAligned`]: Tag::RightAligned
+    pub fn ghost_text(&self, id: TextId) -> Option<&Text> {
+        self.tags.get_text(&id)
     }
 
     /// Removes all [`Tag`]s
@@ -774,14 +1010,22 @@ impl Text {
     /// [`File`]: crate::widgets::File
     pub fn clear_tags(&mut self) {
         self.tags = Box::new(Tags::with_len(self.buf.len() as u32));
+        self.revision.outdate();
     }
 
     /// Removes the tags for all the cursors, used before they are
     /// expected to move
+    ///
+    /// The [revision] only changes if the [`Cursor`]s were placed
+    /// differently from the last time, so taking them out and putting
+    /// them back in doesn't count as a change.
+    ///
+    /// [revision]: Self::revision
     pub(crate) fn add_cursors(&mut self, cursors: &Cursors, area: &impl Area, cfg: PrintCfg) {
+        let mut hasher = DefaultHasher::new();
         if cursors.len() < 500 {
             for (cursor, is_main) in cursors.iter() {
-                self.add_cursor(cursor, is_main, cursors);
+                self.add_cursor(cursor, is_main, cursors, &mut hasher);
             }
         } else {
             let start = area.first_point(self, cfg);
@@ -789,10 +1033,16 @@ impl Text {
             for (cursor, is_main) in cursors.iter() {
                 let range = cursor.range(cursors.is_incl());
                 if range.end > start.byte() && range.start < end.byte() {
-                    self.add_cursor(cursor, is_main, cursors);
+                    self.add_cursor(cursor, is_main, cursors, &mut hasher);
                 }
             }
         }
+
+        let cursors_hash = hasher.finish();
+        if cursors_hash != self.cursors_hash {
+            self.cursors_hash = cursors_hash;
+            self.revision.outdate();
+        }
     }
 
     /// Adds the tags for all the cursors, used after they are
@@ -815,7 +1065,13 @@ impl Text {
     }
 
     /// Adds a [`Cursor`] to the [`Text`]
-    fn add_cursor(&mut self, cursor: &Cursor, is_main: bool, cursors: &Cursors) {
+    fn add_cursor(
+        &mut self,
+        cursor: &Cursor,
+        is_main: bool,
+        cursors: &Cursors,
+        hasher: &mut impl Hasher,
+    ) {
         let (start, end) = if let Some(anchor) = cursor.anchor()
             && anchor < cursor.caret()
         {
@@ -836,6 +1092,7 @@ impl Text {
         ];
 
         for (p, tag) in tags.into_iter().skip(no_selection) {
+            (p.byte(), is_main).hash(hasher);
             let record = (p.byte(), p.char(), p.line());
             self.records.insert(record);
             self.tags.insert(p.byte(), tag, Key::for_cursors());
@@ -914,7 +1171,7 @@ impl Text {
     ///
     /// Duat works fine with [`Tag`]s in the middle of a codepoint,
     /// but external utilizers may not, so keep that in mind.
-    pub fn tags_fwd(&self, at: u32) -> FwdTags {
+    pub(crate) fn tags_fwd(&self, at: u32) -> FwdTags {
         self.tags.fwd_at(at)
     }
 
@@ -924,7 +1181,7 @@ impl Text {
     ///
     /// Duat works fine with [`Tag`]s in the middle of a codepoint,
     /// but external utilizers may not, so keep that in mind.
-    pub fn tags_rev(&self, at: u32) -> RevTags {
+    pub(crate) fn tags_rev(&self, at: u32) -> RevTags {
         self.tags.rev_at(at)
     }
 }
@@ -942,6 +1199,12 @@ impl std::fmt::Debug for Text {
     }
 }
 
+impl Default for Text {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Clone for Text {
     fn clone(&self) -> Self {
         Self {
@@ -951,6 +1214,9 @@ impl Clone for Text {
             history: self.history.clone(),
             readers: Vec::new(),
             tree_sitter: None,
+            revision: Revision(AtomicU64::new(self.revision.get())),
+            cursors_hash: self.cursors_hash,
+            suggestion: self.suggestion.clone(),
         }
     }
 }
@@ -1109,29 +1375,36 @@ mod point {
     ///
     /// [`Text`]: super::Text
     /// [ghost text]: super::Tag::GhostText
-    pub trait TwoPoints: Clone + Copy {
+    ///
+    /// This trait is sealed, and cannot be implemented outside of
+    /// Duat.
+    pub trait TwoPoints: Clone + Copy + crate::private::Sealed {
         /// Returns two [`Point`]s, for `Text` and ghosts
         fn to_points(self) -> (Point, Option<Point>);
     }
 
+    impl crate::private::Sealed for Point {}
     impl TwoPoints for Point {
         fn to_points(self) -> (Point, Option<Point>) {
             (self, None)
         }
     }
 
+    impl crate::private::Sealed for (Point, Point) {}
     impl TwoPoints for (Point, Point) {
         fn to_points(self) -> (Point, Option<Point>) {
             (self.0, Some(self.1))
         }
     }
 
+    impl crate::private::Sealed for (Point, Option<Point>) {}
     impl TwoPoints for (Point, Option<Point>) {
         fn to_points(self) -> (Point, Option<Point>) {
             self
         }
     }
 
+    impl crate::private::Sealed for Item {}
     impl TwoPoints for Item {
         fn to_points(self) -> (Point, Option<Point>) {
             (self.real, self.ghost)
@@ -1204,7 +1477,7 @@ mod point {
     /// Given a first byte, determines how many bytes are in this
     /// UTF-8 character
     #[inline]
-    pub const fn utf8_char_width(b: u8) -> u32 {
+    pub(crate) const fn utf8_char_width(b: u8) -> u32 {
         UTF8_CHAR_WIDTH[b as usize] as u32
     }
 }
@@ -1218,7 +1491,7 @@ mod part {
     //! printing text.
     //!
     //! [`Text`]: super::Text
-    use super::tags::{RawTag, ToggleId};
+    use super::tags::{RawTag, TextId, ToggleId};
     use crate::form::FormId;
 
     /// A part of the [`Text`], can be a [`char`] or a [`Tag`].
@@ -1250,6 +1523,9 @@ mod part {
         AlignRight,
         ToggleStart(ToggleId),
         ToggleEnd(ToggleId),
+        EolGhost(TextId),
+        RightAligned(TextId),
+        LineBg(FormId),
         ResetState,
     }
 
@@ -1270,6 +1546,9 @@ mod part {
                 RawTag::EndAlignRight(_) => Part::AlignLeft,
                 RawTag::ToggleStart(_, id) => Part::ToggleStart(id),
                 RawTag::ToggleEnd(_, id) => Part::ToggleEnd(id),
+                RawTag::EolGhost(_, id) => Part::EolGhost(id),
+                RawTag::RightAligned(_, id) => Part::RightAligned(id),
+                RawTag::LineBg(_, id) => Part::LineBg(id),
                 RawTag::ConcealUntil(_) => Part::ResetState,
                 RawTag::StartConceal(_) | RawTag::EndConceal(_) | RawTag::GhostText(..) => {
                     unreachable!("These tags are automatically processed elsewhere.")
@@ -1321,6 +1600,33 @@ fn cursor_tags(is_main: bool) -> (Tag, Tag, Tag) {
     }
 }
 
+/// The [`Records`] of a [`str`], spread throughout it
+fn records_of(str: &str) -> Records<(u32, u32, u32)> {
+    let lines = str.bytes().filter(|b| *b == b'\n').count();
+    let max = (str.len() as u32, str.chars().count() as u32, lines as u32);
+    let mut records = Records::with_max(max);
+    add_records(&mut records, (0, 0, 0), str);
+    records
+}
+
+/// Adds [`Records`] every [`BYTES_PER_RECORD`] bytes of a [`str`]
+/// that starts at `start`
+///
+/// Without these, finding a [`Point`] in a big chunk of text, like a
+/// freshly opened file, would mean going through it char by char.
+fn add_records(records: &mut Records<(u32, u32, u32)>, start: (u32, u32, u32), str: &str) {
+    let (b, mut c, mut l) = start;
+    let mut last = 0;
+    for (i, char) in str.char_indices() {
+        if i - last >= BYTES_PER_RECORD {
+            records.insert((b + i as u32, c, l));
+            last = i;
+        }
+        c += 1;
+        l += (char == '\n') as u32;
+    }
+}
+
 /// Convenience function for the bounds of a range
 fn get_ends(range: impl std::ops::RangeBounds<u32>, max: u32) -> (u32, u32) {
     let start = match range.start_bound() {
@@ -1369,15 +1675,110 @@ macro impl_from_to_string($t:ty) {
             Self {
                 buf,
                 tags,
-                records: Box::new(Records::with_max((
-                    value.len() as u32,
-                    value.chars().count() as u32,
-                    value.lines().count() as u32,
-                ))),
+                records: Box::new(records_of(&value)),
                 history: History::new(),
                 readers: Vec::new(),
                 tree_sitter: None,
+                revision: Revision::new(),
+                cursors_hash: 0,
+                suggestion: None,
+            }
+        }
+    }
+}
+
+/// The revision of a [`Text`]
+///
+/// Changes only mark the revision as outdated, with a new one being
+/// taken when it is read, so a batch of changes only advances it
+/// once. The highest bit is used as the outdated mark.
+struct Revision(AtomicU64);
+
+impl Revision {
+    const OUTDATED: u64 = 1 << 63;
+
+    /// Returns a new [`Revision`]
+    fn new() -> Self {
+        Self(AtomicU64::new(next_revision()))
+    }
+
+    /// Marks the [`Revision`] as outdated
+    fn outdate(&mut self) {
+        *self.0.get_mut() |= Self::OUTDATED;
+    }
+
+    /// The current revision number, taking a new one if outdated
+    fn get(&self) -> u64 {
+        let mut cur = self.0.load(Ordering::Acquire);
+        while cur & Self::OUTDATED != 0 {
+            let new = next_revision();
+            match self
+                .0
+                .compare_exchange(cur, new, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => return new,
+                Err(other) => cur = other,
+            }
+        }
+        cur
+    }
+}
+
+/// A new revision number for a [`Text`]
+fn next_revision() -> u64 {
+    static REVISION: AtomicU64 = AtomicU64::new(0);
+    REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Point, Text};
+
+    #[test]
+    fn points_in_big_texts() {
+        let str: String = (0..2000)
+            .map(|i| format!("línea {i}{}\n", "é".repeat(i % 7)))
+            .collect();
+        let mut text = Text::from(str.as_str());
+        let edit: String = (0..500).map(|i| format!("añadida {i}\n")).collect();
+        let at = text.point_at_line(1000);
+        text.replace_range((at, at), &edit);
+        let str = text.strs_in_range((Point::default(), text.len())).concat();
+
+        let (mut c, mut l) = (0, 0);
+        for (b, char) in str.char_indices() {
+            let point = Point::from_raw(b as u32, c, l);
+            assert_eq!(text.point_at(b as u32), point);
+            assert_eq!(text.point_at_char(c), point);
+            if b == 0 || str.as_bytes()[b - 1] == b'\n' {
+                assert_eq!(text.point_at_line(l), point);
             }
+            c += 1;
+            l += (char == '\n') as u32;
         }
+        assert_eq!(text.len(), Point::from_raw(str.len() as u32, c, l));
+    }
+
+    #[test]
+    fn grapheme_boundaries() {
+        let str = "ae\u{301}👩\u{200d}🔬\r\nb";
+        let text = Text::from(str);
+        let starts: Vec<u32> = text
+            .graphemes_fwd(Point::default())
+            .map(|point| point.byte())
+            .collect();
+        assert_eq!(starts, [0, 1, 4, 15, 17]);
+
+        let in_accent = text.point_at(2);
+        assert!(!text.is_grapheme_boundary(in_accent));
+        assert_eq!(text.grapheme_start(in_accent).byte(), 1);
+        let in_emoji = text.point_at(11);
+        assert_eq!(text.grapheme_start(in_emoji).byte(), 4);
+
+        let rev: Vec<u32> = text
+            .graphemes_rev(in_emoji)
+            .map(|point| point.byte())
+            .collect();
+        assert_eq!(rev, [1, 0]);
     }
 }
@@ -1,4 +1,4 @@
-use std::{any::Any, ops::Range, path::Path, sync::LazyLock};
+use std::{ops::Range, path::Path};
 
 use gapbuf::GapBuffer;
 use parking_lot::Mutex;
@@ -26,181 +26,366 @@ pub trait Reader: Send + Sync + 'static {
     fn after_change(&mut self, text: &mut Text, change: Change<&str>);
 }
 
+/// Incremental syntax highlighting, through tree-sitter
+///
+/// When a [`Text`] is created [from a file], the language is picked
+/// from its extension. Then, on every [`Change`], the syntax tree is
+/// edited and reparsed, and only the [`Tag`]s on the ranges that
+/// changed are replaced.
+///
+/// Every capture name of the highlighting query becomes a [`Form`]
+/// of the same name, which means that a colorscheme that sets
+/// `"keyword"` or `"function.macro"` will apply to every language.
+/// These [`Form`]s [weakly reference] the capture name before the
+/// last `'.'`, so `"function.macro"` is `"function"` unless set.
+///
+/// [from a file]: Text::from_file
+/// [`Form`]: crate::form::Form
+/// [weakly reference]: form::set_weak
 pub struct TreeSitter {
     parser: Parser,
-    query: Query,
+    query: &'static Query,
+    forms: &'static [FormId],
     tree: Tree,
-    forms: &'static [(FormId, Key, Key)],
+    key: Key,
 }
 
 impl TreeSitter {
+    /// Returns a new [`TreeSitter`], if the language of `path` is
+    /// known
+    ///
+    /// This will also highlight the whole [`Text`].
     pub fn new(text: &mut Text, path: impl AsRef<Path>) -> Option<Self> {
-        let language = tree_sitter_rust::LANGUAGE;
-        let mut parser = Parser::new();
-        parser.set_language(&language.into()).unwrap();
-        let tree = parser.parse_with(&mut buf_parse(text), None).unwrap();
-
-        let buf = TsBuf(&text.buf);
-
-        let query = Query::new(
-            &language.into(),
-            include_str!("../../../ts-queries/rust/highlights.scm"),
-        )
-        .unwrap();
-        let mut cursor = QueryCursor::new();
-        let mut captures = cursor.captures(&query, tree.root_node(), buf);
-
-        let forms = forms_from_query("Rust", &query);
-
-        while let Some((captures, _)) = captures.next() {
-            for cap in captures.captures.iter() {
-                let range = cap.node.range();
-                let (start, end) = (range.start_byte as u32, range.end_byte as u32);
-                let (form, start_key, end_key) = forms[cap.index as usize];
-                if start != end {
-                    text.tags.insert(start, Tag::PushForm(form), start_key);
-                    text.tags.insert(end, Tag::PopForm(form), end_key);
-                }
-            }
-        }
+        let ext = path.as_ref().extension()?.to_str()?;
+        let (_, lang, language, query) = LANGUAGES.iter().find(|(exts, ..)| exts.contains(&ext))?;
 
-        crate::log_file!("after tree sitter: {:#?}", text.tags);
+        let language = language();
+        let (query, forms) = query_and_forms(lang, &language, query)?;
 
-        Some(TreeSitter { parser, query, tree, forms })
+        let mut parser = Parser::new();
+        parser.set_language(&language).ok()?;
+        let tree = parser.parse_with(&mut buf_parse(text), None)?;
+
+        let ts = TreeSitter {
+            parser,
+            query,
+            forms,
+            tree,
+            key: Key::new(),
+        };
+        ts.highlight(text, 0..text.len().byte());
+
+        Some(ts)
     }
 
     pub(super) fn after_change(&mut self, text: &mut Text, change: Change<&str>) {
         let start = change.start();
-        let added = change.added_end();
-        let taken = change.taken_end();
-
         let start_position = ts_point(start, text);
-        let prev = (start_position.column, start);
+
         self.tree.edit(&InputEdit {
             start_byte: start.byte() as usize,
-            old_end_byte: taken.byte() as usize,
-            new_end_byte: added.byte() as usize,
+            old_end_byte: change.taken_end().byte() as usize,
+            new_end_byte: change.added_end().byte() as usize,
             start_position,
-            old_end_position: ts_point_from_prev(taken, prev, text),
-            new_end_position: ts_point_from_prev(added, prev, text),
+            old_end_position: ts_point_after(start_position, change.taken_text()),
+            new_end_position: ts_point_after(start_position, change.added_text()),
         });
 
-        let tree = self
+        let Some(tree) = self
             .parser
             .parse_with(&mut buf_parse(text), Some(&self.tree))
-            .unwrap();
+        else {
+            return;
+        };
+
+        // The changed ranges only include differences in structure, so
+        // the edited range must also be highlighted again.
+        let mut ranges: Vec<Range<u32>> = self
+            .tree
+            .changed_ranges(&tree)
+            .map(|r| r.start_byte as u32..r.end_byte as u32)
+            .collect();
+        ranges.push(start.byte()..change.added_end().byte());
+
+        self.tree = tree;
 
-        let changes = self.tree.changed_ranges(&tree);
-        // If no change is reported, we will add tags on the added range.
-        let added = changes.is_empty().then_some(start.byte()..added.byte());
+        for range in ranges {
+            let range = self.expand(range);
+            self.highlight(text, range);
+        }
+    }
+
+    /// Expands a byte range to the nodes on either end of it
+    ///
+    /// This is done because captures may depend on the whole content
+    /// of a node, so an edit in the middle of an identifier could
+    /// change the [`Tag`]s on both of its ends.
+    fn expand(&self, range: Range<u32>) -> Range<u32> {
+        let root = self.tree.root_node();
+        let node_at = |b: u32| {
+            root.descendant_for_byte_range(b as usize, b as usize)
+                .filter(|node| node.parent().is_some())
+        };
+
+        let start = node_at(range.start).map_or(range.start, |n| n.start_byte() as u32);
+        let end = node_at(range.end).map_or(range.end, |n| n.end_byte() as u32);
+        start.min(range.start)..end.max(range.end)
+    }
+
+    /// Replaces the [`Tag`]s of this [`TreeSitter`] in a byte range
+    ///
+    /// Only [`Tag`]s within the range are replaced, so captures that
+    /// span past it keep their [`Tag`]s on the outside.
+    fn highlight(&self, text: &mut Text, range: Range<u32>) {
+        let len = text.len().byte();
+        text.tags.remove_from(range.start..range.end + 1, self.key);
 
         let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(
+            range.start.saturating_sub(1) as usize..(range.end + 1).min(len) as usize,
+        );
+
         let buf = TsBuf(&text.buf);
+        let mut captures = cursor.captures(self.query, self.tree.root_node(), buf);
+        let contains = |b: u32| (range.start..=range.end).contains(&b);
 
-        let ranges = changes.map(|c| c.start_byte as u32..c.end_byte as u32);
-        for range in ranges.chain(added) {
-            let Some((start, end)) = range_to_change(range.clone(), &self.tree, &tree) else {
-                continue;
-            };
-            cursor.set_byte_range(start..end);
-            let mut captures = cursor.captures(&self.query, self.tree.root_node(), buf);
-            while let Some((captures, _)) = captures.next() {
-                for cap in captures.captures.iter() {
-                    let range = cap.node.range();
-                    let (start, end) = (range.start_byte as u32, range.end_byte as u32);
-                    let (_, start_key, end_key) = self.forms[cap.index as usize];
-                        text.tags.remove_at(start, start_key);
-                        text.tags.remove_at(end, end_key);
+        while let Some((captures, _)) = captures.next() {
+            for cap in captures.captures.iter() {
+                let (start, end) = (cap.node.start_byte() as u32, cap.node.end_byte() as u32);
+                let form = self.forms[cap.index as usize];
+                if start == end {
+                    continue;
                 }
-            }
-
-            let mut captures = cursor.captures(&self.query, tree.root_node(), buf);
-            while let Some((captures, _)) = captures.next() {
-                for cap in captures.captures.iter() {
-                    let range = cap.node.range();
-                    let (start, end) = (range.start_byte as u32, range.end_byte as u32);
-                    let (form, start_key, end_key) = self.forms[cap.index as usize];
-                    if start != end {
-                        text.tags.insert(start, Tag::PushForm(form), start_key);
-                        text.tags.insert(end, Tag::PopForm(form), end_key);
-                    }
+                if contains(start) {
+                    text.tags.insert(start, Tag::PushForm(form), self.key);
+                }
+                if contains(end) {
+                    text.tags.insert(end, Tag::PopForm(form), self.key);
                 }
             }
         }
-
-        self.tree = tree;
-
-        crate::log_file!("after tree-sitter: {:#?}", text.tags);
     }
 }
 
-fn range_to_change(range: Range<u32>, old: &Tree, new: &Tree) -> Option<(usize, usize)> {
-    let (start, end) = (range.start as usize, range.end as usize);
-    let old = old.root_node();
-    let old_desc = old.descendant_for_byte_range(start, end).unwrap();
-
-    let new = new.root_node();
-    let new_desc = new.descendant_for_byte_range(start, end).unwrap();
-    let parent = new_desc.parent().unwrap_or(new_desc);
-
-    (!nodes_are_equal(old_desc, new_desc)).then_some((parent.start_byte(), parent.end_byte()))
-}
-
 fn buf_parse<'a>(text: &'a Text) -> impl FnMut(usize, TSPoint) -> &'a [u8] {
     let [s0, s1] = text.strs();
     |byte, _point| {
         if byte < s0.len() {
-            s0[byte..].as_bytes()
+            &s0.as_bytes()[byte..]
         } else {
-            s1[byte - s0.len()..].as_bytes()
+            &s1.as_bytes()[byte - s0.len()..]
         }
     }
 }
 
+/// The tree-sitter position of a [`Point`]
 fn ts_point(point: Point, text: &Text) -> TSPoint {
-    let strs = text.strs_in_range((Point::default(), point));
-    let iter = strs.into_iter().flat_map(str::bytes);
-    let col = iter.take_while(|&b| b != b'\n').count();
-
-    TSPoint::new(point.line() as usize, col)
+    let line_start = text.point_at_line(point.line());
+    TSPoint::new(
+        point.line() as usize,
+        (point.byte() - line_start.byte()) as usize,
+    )
 }
 
-fn ts_point_from_prev(point: Point, prev: (usize, Point), text: &Text) -> TSPoint {
-    let (col, prev) = prev;
-    let strs = text.strs_in_range((prev, point));
-    let iter = strs.into_iter().flat_map(str::bytes);
-
-    let col = if point.line() == prev.line() {
-        col + iter.count()
-    } else {
-        iter.take_while(|&b| b != b'\n').count()
-    };
-
-    TSPoint::new(point.line() as usize, col)
+/// The tree-sitter position after `str`, if it started at `start`
+fn ts_point_after(start: TSPoint, str: &str) -> TSPoint {
+    match str.rfind('\n') {
+        Some(i) => {
+            let lines = str.bytes().filter(|b| *b == b'\n').count();
+            TSPoint::new(start.row + lines, str.len() - (i + 1))
+        }
+        None => TSPoint::new(start.row, start.column + str.len()),
+    }
 }
 
-fn forms_from_query(lang: &'static str, query: &Query) -> &'static [(FormId, Key, Key)] {
-    static LISTS: Mutex<Vec<(&'static str, &[(FormId, Key, Key)])>> = Mutex::new(Vec::new());
+/// The highlighting [`Query`] of a language, and its [`FormId`]s
+///
+/// Both are only created once per language, and are then shared by
+/// every [`TreeSitter`] of that language.
+fn query_and_forms(
+    lang: &'static str,
+    language: &Language,
+    query: &str,
+) -> Option<(&'static Query, &'static [FormId])> {
+    static LISTS: Mutex<Vec<(&str, &Query, &[FormId])>> = Mutex::new(Vec::new());
     let mut lists = LISTS.lock();
 
-    if let Some((_, forms)) = lists.iter().find(|(l, _)| *l == lang) {
-        forms
-    } else {
-        let mut forms = Vec::new();
-        for name in query.capture_names() {
-            forms.push(if name.contains('.') {
-                let refed = name.split('.').next().unwrap().to_string().leak();
-                (form::set_weak(name, refed), Key::new(), Key::new())
-            } else {
-                (form::set_weak(name, "Default"), Key::new(), Key::new())
-            });
-        }
-
-        lists.push((lang, forms.leak()));
-        lists.last().unwrap().1
+    if let Some((_, query, forms)) = lists.iter().find(|(l, ..)| *l == lang) {
+        return Some((query, forms));
     }
+
+    let query: &'static Query = Box::leak(Box::new(Query::new(language, query).ok()?));
+    let forms: Vec<FormId> = query
+        .capture_names()
+        .iter()
+        .map(|name| match name.rsplit_once('.') {
+            Some((refed, _)) => form::set_weak(name, refed),
+            None => form::set_weak(name, "Default"),
+        })
+        .collect();
+
+    let forms = forms.leak();
+    lists.push((lang, query, forms));
+    Some((query, forms))
 }
 
+/// The languages that can be highlighted
+///
+/// Each entry has the file extensions of the language, its name, its
+/// grammar, and the [`Query`] used for highlighting it.
+const LANGUAGES: &[(&[&str], &str, fn() -> Language, &str)] = &[
+    (
+        &["sh", "bash"],
+        "Bash",
+        || tree_sitter_bash::LANGUAGE.into(),
+        tree_sitter_bash::HIGHLIGHT_QUERY,
+    ),
+    (
+        &["c", "h"],
+        "C",
+        || tree_sitter_c::LANGUAGE.into(),
+        tree_sitter_c::HIGHLIGHT_QUERY,
+    ),
+    (
+        &["cs"],
+        "C#",
+        || tree_sitter_c_sharp::LANGUAGE.into(),
+        tree_sitter_c_sharp::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["cc", "cpp", "cxx", "hpp", "hxx"],
+        "C++",
+        || tree_sitter_cpp::LANGUAGE.into(),
+        tree_sitter_cpp::HIGHLIGHT_QUERY,
+    ),
+    (
+        &["css"],
+        "CSS",
+        || tree_sitter_css::LANGUAGE.into(),
+        tree_sitter_css::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["ex", "exs"],
+        "Elixir",
+        || tree_sitter_elixir::LANGUAGE.into(),
+        tree_sitter_elixir::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["go"],
+        "Go",
+        || tree_sitter_go::LANGUAGE.into(),
+        tree_sitter_go::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["hs"],
+        "Haskell",
+        || tree_sitter_haskell::LANGUAGE.into(),
+        tree_sitter_haskell::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["html", "htm", "xhtml"],
+        "HTML",
+        || tree_sitter_html::LANGUAGE.into(),
+        tree_sitter_html::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["java"],
+        "Java",
+        || tree_sitter_java::LANGUAGE.into(),
+        tree_sitter_java::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["js", "mjs", "cjs"],
+        "JavaScript",
+        || tree_sitter_javascript::LANGUAGE.into(),
+        tree_sitter_javascript::HIGHLIGHT_QUERY,
+    ),
+    (
+        &["lua"],
+        "Lua",
+        || tree_sitter_lua::LANGUAGE.into(),
+        tree_sitter_lua::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["md"],
+        "Markdown",
+        || tree_sitter_md::LANGUAGE.into(),
+        tree_sitter_md::HIGHLIGHT_QUERY_BLOCK,
+    ),
+    (
+        &["m"],
+        "Objective-C",
+        || tree_sitter_objc::LANGUAGE.into(),
+        tree_sitter_objc::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["ml"],
+        "OCaml",
+        || tree_sitter_ocaml::LANGUAGE_OCAML.into(),
+        tree_sitter_ocaml::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["php"],
+        "PHP",
+        || tree_sitter_php::LANGUAGE_PHP.into(),
+        tree_sitter_php::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["pp"],
+        "Puppet",
+        || tree_sitter_puppet::LANGUAGE.into(),
+        tree_sitter_puppet::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["py", "pyi"],
+        "Python",
+        || tree_sitter_python::LANGUAGE.into(),
+        tree_sitter_python::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["r", "R"],
+        "R",
+        || tree_sitter_r::LANGUAGE.into(),
+        tree_sitter_r::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["rb"],
+        "Ruby",
+        || tree_sitter_ruby::LANGUAGE.into(),
+        tree_sitter_ruby::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["rs"],
+        "Rust",
+        || tree_sitter_rust::LANGUAGE.into(),
+        tree_sitter_rust::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["sc", "scala"],
+        "Scala",
+        || tree_sitter_scala::LANGUAGE.into(),
+        tree_sitter_scala::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["swift"],
+        "Swift",
+        || tree_sitter_swift::LANGUAGE.into(),
+        tree_sitter_swift::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["ts", "mts", "cts"],
+        "TypeScript",
+        || tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        tree_sitter_typescript::HIGHLIGHTS_QUERY,
+    ),
+    (
+        &["tsx"],
+        "TSX",
+        || tree_sitter_typescript::LANGUAGE_TSX.into(),
+        tree_sitter_typescript::HIGHLIGHTS_QUERY,
+    ),
+];
+
 impl<'a> TextProvider<&'a [u8]> for TsBuf<'a> {
     type I = std::array::IntoIter<&'a [u8], 2>;
 
@@ -230,104 +415,3 @@ fn log_node(node: Node) {
         node = next_exists.then_some(cursor.node());
     }
 }
-
-// static LANGUAGES: LazyLock<Mutex<Vec<(&str, &str, &str, Language,
-// &str)>>> = LazyLock::new(|| {    use tree_sitter_rust as rust;
-//    Mutex::new(vec![
-//        (".c", "C", "c", rust::LANGUAGE.into(), include_str!()),
-//        (".cc", "C++", "cpp"),
-//        (".cl", "Common Lisp", "common-lisp"),
-//        (".clj", "Clojure", "clojure"),
-//        (".comp", "GLSL", "glsl"),
-//        (".cpp", "C++", "cpp"),
-//        (".cs", "C#", "csharp"),
-//        (".css", "CSS", "css"),
-//        (".cxx", "C++", "cpp"),
-//        (".dart", "Dart", "dart"),
-//        (".frag", "GLSL", "glsl"),
-//        (".geom", "GLSL", "glsl"),
-//        (".glsl", "GLSL", "glsl"),
-//        (".go", "Go", "go"),
-//        (".h", "C", "c"),
-//        (".haml", "Haml", "haml"),
-//        (".handlebars", "Handlebars", "handlebars"),
-//        (".hbs", "Handlebars", "handlebars"),
-//        (".hlsl", "HLSL", "HLSL"),
-//        (".hpp", "C++", "cpp"),
-//        (".html", "HTML", "html"),
-//        (".hxx", "C++", "cpp"),
-//        (".ini", "INI", "ini"),
-//        (".java", "Java", "java"),
-//        (".jinja", "Jinja", "jinja"),
-//        (".jinja2", "Jinja", "jinja"),
-//        (".js", "JavaScript", "javascript"),
-//        (".json", "JSON", "json"),
-//        (".jsonc", "JSON with Comments", "jsonc"),
-//        (".kt", "Kotlin", "kotlin"),
-//        (".less", "Less", "less"),
-//        (".lua", "Lua", "lua"),
-//        (".md", "Markdown", "markdown"),
-//        (".pl", "Perl", "perl"),
-//        (".py", "Python", "python"),
-//        (".pyc", "Python", "python"),
-//        (".pyo", "Python", "python"),
-//        (".rb", "Ruby", "ruby"),
-//        (".rkt", "Racket", "racket"),
-//        (".rs", "Rust", "rust"),
-//        (".sass", "SASS", "sass"),
-//        (".sc", "Scala", "scala"),
-//        (".scala", "Scala", "scala"),
-//        (".scss", "SCSS", "scss"),
-//        (".sh", "Shell", "shell"),
-//        (".sql", "SQL", "sql"),
-//        (".swift", "Swift", "swift"),
-//        (".tesc", "GLSL", "glsl"),
-//        (".tese", "GLSL", "glsl"),
-//        (".tex", "TeX", "tex"),
-//        (".toml", "TOML", "toml"),
-//        (".ts", "TypeScript", "typescript"),
-//        (".vert", "GLSL", "glsl"),
-//        (".xhtml", "XHTML", "xhtml"),
-//        (".xml", "XML", "xml"),
-//        (".yaml", "YAML", "yaml"),
-//        (".yml", "YAML", "yaml"),
-//    ])
-//});
-
-fn nodes_are_equal(old: Node, new: Node) -> bool {
-    let (mut old_c, mut new_c) = (old.walk(), new.walk());
-
-    let mut old_node = Some(old_c.node());
-    let mut new_node = Some(new_c.node());
-    let mut old_depth = 0;
-    let mut new_depth = 0;
-    loop {
-        match (old_node, new_node) {
-            (Some(old), Some(new)) => {
-                if old.range() != new.range() || old.kind_id() != new.kind_id() {
-                    break false;
-                }
-            }
-            (None, None) => break true,
-            (Some(_), None) | (None, Some(_)) => break false,
-        }
-
-        let mut next_exists = old_c.goto_first_child();
-        old_depth += next_exists as usize;
-        next_exists = old_c.goto_next_sibling();
-        while !next_exists && old_depth > 0 && old_c.goto_parent() {
-            old_depth -= 1;
-            next_exists = old_c.goto_next_sibling();
-        }
-        old_node = next_exists.then_some(old_c.node());
-
-        let mut next_exists = new_c.goto_first_child();
-        new_depth += next_exists as usize;
-        next_exists = new_c.goto_next_sibling();
-        while !next_exists && new_depth > 0 && new_c.goto_parent() {
-            new_depth -= 1;
-            next_exists = new_c.goto_next_sibling();
-        }
-        new_node = next_exists.then_some(new_c.node());
-    }
-}
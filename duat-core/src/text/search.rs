@@ -1,5 +1,9 @@
-use std::{collections::HashMap, sync::LazyLock};
+use std::{collections::HashMap, ops::Range, sync::LazyLock};
 
+use memchr::{
+    memchr2_iter,
+    memmem::{Finder, FinderRev},
+};
 use parking_lot::{RwLock, RwLockWriteGuard};
 use regex_automata::{
     Anchored, Input, PatternID,
@@ -8,6 +12,7 @@ use regex_automata::{
 };
 
 use super::{Point, Text};
+use crate::opts::{self, OptValue, Scope};
 
 impl Text {
     pub fn search_fwd<R: RegexPattern>(
@@ -122,8 +127,817 @@ impl Text {
     }
 }
 
+impl Text {
+    /// Returns an iterator over the matches of a literal pattern
+    ///
+    /// Unlike [`Text::search_fwd`], this function doesn't need to
+    /// move the gap of the [`GapBuffer`], since it searches the
+    /// bytes on each side of it directly, which makes it a lot
+    /// cheaper to call repeatedly on big [`Text`]s.
+    ///
+    /// Since both the [`Text`] and the pattern are valid UTF-8, the
+    /// matches are guaranteed to be on char boundaries.
+    ///
+    /// [`GapBuffer`]: gapbuf::GapBuffer
+    pub fn search_literal_fwd<'a>(
+        &'a self,
+        pat: &str,
+        at: Point,
+        end: Option<Point>,
+    ) -> impl Iterator<Item = (Point, Point)> + 'a {
+        let finder = Finder::new(pat).into_owned();
+        let (s0, s1) = self.buf.as_slices();
+        let (gap, len) = (s0.len(), pat.len());
+        let end = end.map_or(self.len().byte(), |end| end.byte()) as usize;
+        let mut pos = at.byte() as usize;
+
+        std::iter::from_fn(move || {
+            if len == 0 || pos + len > end {
+                return None;
+            }
+
+            let start = if pos < gap
+                && let Some(start) = finder.find(&s0[pos..gap.min(end)])
+            {
+                pos + start
+            } else if let Some((b, bytes)) = straddling(s0, s1, pos, end, len)
+                && let Some(start) = finder.find(&bytes)
+            {
+                b + start
+            } else if end > gap {
+                let b = pos.max(gap);
+                b + finder.find(&s1[b - gap..end - gap])?
+            } else {
+                return None;
+            };
+
+            pos = start + len;
+            Some((self.point_at(start as u32), self.point_at(pos as u32)))
+        })
+    }
+
+    /// Returns an iterator over the reverse matches of a literal
+    /// pattern
+    ///
+    /// Unlike [`Text::search_rev`], this function doesn't need to
+    /// move the gap of the [`GapBuffer`], since it searches the
+    /// bytes on each side of it directly, which makes it a lot
+    /// cheaper to call repeatedly on big [`Text`]s.
+    ///
+    /// Since both the [`Text`] and the pattern are valid UTF-8, the
+    /// matches are guaranteed to be on char boundaries.
+    ///
+    /// [`GapBuffer`]: gapbuf::GapBuffer
+    pub fn search_literal_rev<'a>(
+        &'a self,
+        pat: &str,
+        at: Point,
+        start: Option<Point>,
+    ) -> impl Iterator<Item = (Point, Point)> + 'a {
+        let finder = FinderRev::new(pat).into_owned();
+        let (s0, s1) = self.buf.as_slices();
+        let (gap, len) = (s0.len(), pat.len());
+        let start = start.map_or(0, |start| start.byte()) as usize;
+        let mut pos = at.byte() as usize;
+
+        std::iter::from_fn(move || {
+            if len == 0 || start + len > pos {
+                return None;
+            }
+
+            let b = start.max(gap);
+            let found = if pos > b
+                && let Some(found) = finder.rfind(&s1[b - gap..pos - gap])
+            {
+                b + found
+            } else if let Some((b, bytes)) = straddling(s0, s1, start, pos, len)
+                && let Some(found) = finder.rfind(&bytes)
+            {
+                b + found
+            } else if start < gap {
+                start + finder.rfind(&s0[start..pos.min(gap)])?
+            } else {
+                return None;
+            };
+
+            pos = found;
+            Some((
+                self.point_at(found as u32),
+                self.point_at((found + len) as u32),
+            ))
+        })
+    }
+}
+
+impl Text {
+    /// Returns an iterator over the matches of a [`Matcher`]
+    ///
+    /// Like [`Text::search_literal_fwd`], this function doesn't move
+    /// the gap of the [`GapBuffer`].
+    ///
+    /// [`GapBuffer`]: gapbuf::GapBuffer
+    pub fn search_matcher_fwd<'a>(
+        &'a self,
+        matcher: &'a (impl Matcher + ?Sized),
+        at: Point,
+        end: Option<Point>,
+    ) -> impl Iterator<Item = (Point, Point)> + 'a {
+        let end = end.unwrap_or(self.len());
+        let mut at = at;
+
+        std::iter::from_fn(move || {
+            let (p0, p1) = matcher.find_fwd(self, at, end)?;
+            at = if p1 > p0 {
+                p1
+            } else {
+                p1.fwd(self.char_at(p1)?)
+            };
+            Some((p0, p1))
+        })
+    }
+
+    /// Returns an iterator over the reverse matches of a [`Matcher`]
+    ///
+    /// Like [`Text::search_literal_rev`], this function doesn't move
+    /// the gap of the [`GapBuffer`].
+    ///
+    /// [`GapBuffer`]: gapbuf::GapBuffer
+    pub fn search_matcher_rev<'a>(
+        &'a self,
+        matcher: &'a (impl Matcher + ?Sized),
+        at: Point,
+        start: Option<Point>,
+    ) -> impl Iterator<Item = (Point, Point)> + 'a {
+        let start = start.unwrap_or_default();
+        let mut at = at;
+
+        std::iter::from_fn(move || {
+            let (p0, p1) = matcher.find_rev(self, at, start)?;
+            at = if p1 > p0 {
+                p0
+            } else {
+                let (p, _) = self.chars_rev(p0).next()?;
+                p
+            };
+            Some((p0, p1))
+        })
+    }
+}
+
+/// A pattern that is matched without going through a regex
+///
+/// This is implemented by [`Literal`] and [`Fuzzy`], and can be
+/// searched for with [`Text::search_matcher_fwd`] and
+/// [`Text::search_matcher_rev`], or through a [`Searcher`] created
+/// with [`Searcher::with_flags`].
+pub trait Matcher: Send + Sync {
+    /// The first match in the `at..end` range
+    fn find_fwd(&self, text: &Text, at: Point, end: Point) -> Option<(Point, Point)>;
+
+    /// The last match in the `start..at` range
+    fn find_rev(&self, text: &Text, at: Point, start: Point) -> Option<(Point, Point)>;
+
+    /// Whether the whole of `haystack` matches
+    fn matches(&self, haystack: &str) -> bool;
+}
+
+/// How a [`Literal`] treats the case of characters
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// Characters must have the same case
+    #[default]
+    Sensitive,
+    /// Characters can have any case
+    Insensitive,
+    /// [`Case::Insensitive`] if the pattern has no uppercase
+    /// characters, [`Case::Sensitive`] otherwise
+    Smart,
+}
+
+/// A literal pattern, optionally case or accent insensitive, or
+/// only matching whole words
+///
+/// Word characters are alphanumeric characters and `'_'`.
+///
+/// Case insensitivity is done through case folding, which lowercases
+/// characters, and expands the ones whose full folding differs from
+/// that, so `"ss"` matches `"ß"` and `"s"` matches `"ſ"`. Accent
+/// insensitivity decomposes characters, then strips combining marks,
+/// so `"cafe"` matches `"café"`, whether its `'é'` is precomposed or
+/// not. The decomposition is not a full NFKD normalization, but a
+/// table of Latin-1, Latin Extended-A, some ligatures and fullwidth
+/// ASCII. Both are done as the [`Text`] is iterated, so no
+/// normalized copy of it is ever made.
+#[derive(Debug, Clone)]
+pub struct Literal {
+    pat: String,
+    folded: Vec<char>,
+    ignore_case: bool,
+    ignore_accents: bool,
+    whole_word: bool,
+}
+
+impl Literal {
+    /// Returns a new [`Literal`], with the given [`Case`] handling
+    pub fn new(pat: impl ToString, case: Case) -> Self {
+        let pat = pat.to_string();
+        let ignore_case = match case {
+            Case::Sensitive => false,
+            Case::Insensitive => true,
+            Case::Smart => !pat.chars().any(char::is_uppercase),
+        };
+
+        let mut literal = Self {
+            pat,
+            folded: Vec::new(),
+            ignore_case,
+            ignore_accents: false,
+            whole_word: false,
+        };
+        literal.fold_pat();
+        literal
+    }
+
+    /// Only match whole words
+    pub fn whole_word(self) -> Self {
+        Self { whole_word: true, ..self }
+    }
+
+    /// Ignore accents and other combining marks
+    pub fn ignore_accents(self) -> Self {
+        let mut literal = Self { ignore_accents: true, ..self };
+        literal.fold_pat();
+        literal
+    }
+
+    fn fold_pat(&mut self) {
+        let (ignore_case, ignore_accents) = (self.ignore_case, self.ignore_accents);
+        self.folded = self
+            .pat
+            .chars()
+            .flat_map(|char| fold(char, ignore_case, ignore_accents))
+            .collect();
+    }
+
+    /// The byte positions where a match could start, within `range`
+    ///
+    /// If the first character of the pattern is ASCII, and accents
+    /// aren't ignored, this is done with [`memchr`] on each side of
+    /// the gap, otherwise every character is a candidate.
+    fn candidates<'a>(
+        &self,
+        text: &'a Text,
+        range: Range<usize>,
+    ) -> Box<dyn DoubleEndedIterator<Item = usize> + 'a> {
+        let first = self.folded[0];
+        let [s0, s1] = text.strs_in_range_inner(range.start as u32..range.end as u32);
+        let gap = range.start + s0.len();
+
+        // Some non ASCII characters are folded into ASCII ones, like 'ß'
+        // into "ss", and the Kelvin sign into 'k'.
+        let folds_from_non_ascii = self.ignore_case && matches!(first, 'f' | 'i' | 'k' | 's');
+
+        if first.is_ascii() && !self.ignore_accents && !folds_from_non_ascii {
+            let (lower, upper) = match self.ignore_case {
+                true => (first.to_ascii_lowercase(), first.to_ascii_uppercase()),
+                false => (first, first),
+            };
+            let (s0_iter, s1_iter) = (
+                memchr2_iter(lower as u8, upper as u8, s0.as_bytes()),
+                memchr2_iter(lower as u8, upper as u8, s1.as_bytes()),
+            );
+            let start = range.start;
+            Box::new(
+                s0_iter
+                    .map(move |b| start + b)
+                    .chain(s1_iter.map(move |b| gap + b)),
+            )
+        } else {
+            let start = range.start;
+            Box::new(
+                s0.char_indices()
+                    .map(move |(b, _)| start + b)
+                    .chain(s1.char_indices().map(move |(b, _)| gap + b)),
+            )
+        }
+    }
+
+    /// The end of a match starting at `start`, if there is one
+    ///
+    /// When ignoring accents, combining marks that follow the match
+    /// are also part of it.
+    fn match_at(&self, text: &Text, start: usize, end: usize) -> Option<usize> {
+        let mut chars = text
+            .strs_in_range_inner(start as u32..end as u32)
+            .into_iter()
+            .flat_map(str::chars)
+            .peekable();
+
+        let mut pat = self.folded.iter();
+        let mut len = 0;
+        while pat.len() > 0 {
+            let char = chars.next()?;
+            let mut folded = fold(char, self.ignore_case, self.ignore_accents).peekable();
+            // Matches can't start on characters that get folded away.
+            if len == 0 && folded.peek().is_none() {
+                return None;
+            }
+            if !folded.all(|f| pat.next() == Some(&f)) {
+                return None;
+            }
+            len += char.len_utf8();
+        }
+
+        while self.ignore_accents
+            && let Some(char) = chars.next_if(|char| is_combining_mark(*char))
+        {
+            len += char.len_utf8();
+        }
+
+        let end = start + len;
+        (!self.whole_word || is_word_boundary(text, start, end)).then_some(end)
+    }
+}
+
+impl Matcher for Literal {
+    fn find_fwd(&self, text: &Text, at: Point, end: Point) -> Option<(Point, Point)> {
+        if self.folded.is_empty() {
+            return None;
+        }
+        let (at, end) = (at.byte() as usize, end.byte() as usize);
+
+        self.candidates(text, at..end).find_map(|start| {
+            let end = self.match_at(text, start, end)?;
+            Some((text.point_at(start as u32), text.point_at(end as u32)))
+        })
+    }
+
+    fn find_rev(&self, text: &Text, at: Point, start: Point) -> Option<(Point, Point)> {
+        if self.folded.is_empty() {
+            return None;
+        }
+        let (start, at) = (start.byte() as usize, at.byte() as usize);
+
+        self.candidates(text, start..at).rev().find_map(|start| {
+            let end = self.match_at(text, start, at)?;
+            Some((text.point_at(start as u32), text.point_at(end as u32)))
+        })
+    }
+
+    fn matches(&self, haystack: &str) -> bool {
+        haystack
+            .chars()
+            .flat_map(|char| fold(char, self.ignore_case, self.ignore_accents))
+            .eq(self.folded.iter().copied())
+    }
+}
+
+/// Folds a character for comparison
+///
+/// If ignoring accents, the character is decomposed and stripped of
+/// its combining marks, which can leave nothing, if it was one. If
+/// ignoring case, the result then has its [case folded].
+///
+/// [case folded]: fold_case
+fn fold(char: char, ignore_case: bool, ignore_accents: bool) -> impl Iterator<Item = char> {
+    let (char, str) = match ignore_accents {
+        true => match unaccent(char) {
+            Unaccented::Mark => (None, ""),
+            Unaccented::Char(char) => (Some(char), ""),
+            Unaccented::Str(str) => (None, str),
+        },
+        false => (Some(char), ""),
+    };
+
+    char.into_iter().chain(str.chars()).flat_map(move |char| {
+        let folded = fold_case(char).take(if ignore_case { usize::MAX } else { 0 });
+        folded.chain((!ignore_case).then_some(char))
+    })
+}
+
+/// Folds the case of a character
+///
+/// This is mostly lowercasing, except for the characters whose full
+/// case folding is different, like `'ß'`, which becomes `"ss"`, or
+/// `'ſ'` and `'ς'`, which become `'s'` and `'σ'`.
+fn fold_case(char: char) -> impl Iterator<Item = char> {
+    let folded = match char {
+        'ß' | 'ẞ' => "ss",
+        'ſ' => "s",
+        'ς' => "σ",
+        'ϐ' => "β",
+        'ϑ' => "θ",
+        'ϕ' => "φ",
+        'ϖ' => "π",
+        'ϰ' => "κ",
+        'ϱ' => "ρ",
+        'ϵ' => "ε",
+        '\u{fb00}' => "ff",
+        '\u{fb01}' => "fi",
+        '\u{fb02}' => "fl",
+        '\u{fb03}' => "ffi",
+        '\u{fb04}' => "ffl",
+        '\u{fb05}' | '\u{fb06}' => "st",
+        _ => "",
+    };
+
+    let lower = folded.is_empty().then(|| char.to_lowercase());
+    folded.chars().chain(lower.into_iter().flatten())
+}
+
+/// The result of stripping the accents from a character
+enum Unaccented {
+    Mark,
+    Char(char),
+    Str(&'static str),
+}
+
+/// Strips the accents from a character
+///
+/// This covers the compatibility decompositions of Latin-1, Latin
+/// Extended-A, some common ligatures and fullwidth ASCII, which
+/// should be enough for most text. Characters not covered are left
+/// as is, unless they are combining marks.
+fn unaccent(char: char) -> Unaccented {
+    use Unaccented::*;
+
+    /// The upper case version is on the even offsets from `first`
+    fn pair(char: char, first: u32, upper: &'static str, lower: &'static str) -> Unaccented {
+        match (char as u32 - first) % 2 {
+            0 => Str(upper),
+            _ => Str(lower),
+        }
+    }
+
+    match char {
+        char if is_combining_mark(char) => Mark,
+        'À'..='Å' => Str("A"),
+        'Ç' => Str("C"),
+        'È'..='Ë' => Str("E"),
+        'Ì'..='Ï' => Str("I"),
+        'Ñ' => Str("N"),
+        'Ò'..='Ö' => Str("O"),
+        'Ù'..='Ü' => Str("U"),
+        'Ý' => Str("Y"),
+        'à'..='å' => Str("a"),
+        'ç' => Str("c"),
+        'è'..='ë' => Str("e"),
+        'ì'..='ï' => Str("i"),
+        'ñ' => Str("n"),
+        'ò'..='ö' => Str("o"),
+        'ù'..='ü' => Str("u"),
+        'ý' | 'ÿ' => Str("y"),
+        'ª' => Str("a"),
+        'º' => Str("o"),
+        '¹' => Str("1"),
+        '²' => Str("2"),
+        '³' => Str("3"),
+        '\u{a0}' => Str(" "),
+        '\u{100}'..='\u{105}' => pair(char, 0x100, "A", "a"),
+        '\u{106}'..='\u{10d}' => pair(char, 0x106, "C", "c"),
+        '\u{10e}'..='\u{10f}' => pair(char, 0x10e, "D", "d"),
+        '\u{112}'..='\u{11b}' => pair(char, 0x112, "E", "e"),
+        '\u{11c}'..='\u{123}' => pair(char, 0x11c, "G", "g"),
+        '\u{124}'..='\u{125}' => pair(char, 0x124, "H", "h"),
+        '\u{128}'..='\u{12f}' => pair(char, 0x128, "I", "i"),
+        '\u{130}' => Str("I"),
+        '\u{132}'..='\u{133}' => pair(char, 0x132, "IJ", "ij"),
+        '\u{134}'..='\u{135}' => pair(char, 0x134, "J", "j"),
+        '\u{136}'..='\u{137}' => pair(char, 0x136, "K", "k"),
+        '\u{139}'..='\u{13e}' => pair(char, 0x139, "L", "l"),
+        '\u{13f}'..='\u{140}' => pair(char, 0x13f, "L·", "l·"),
+        '\u{143}'..='\u{148}' => pair(char, 0x143, "N", "n"),
+        '\u{149}' => Str("ʼn"),
+        '\u{14c}'..='\u{151}' => pair(char, 0x14c, "O", "o"),
+        '\u{154}'..='\u{159}' => pair(char, 0x154, "R", "r"),
+        '\u{15a}'..='\u{161}' => pair(char, 0x15a, "S", "s"),
+        '\u{162}'..='\u{165}' => pair(char, 0x162, "T", "t"),
+        '\u{168}'..='\u{173}' => pair(char, 0x168, "U", "u"),
+        '\u{174}'..='\u{175}' => pair(char, 0x174, "W", "w"),
+        '\u{176}'..='\u{177}' => pair(char, 0x176, "Y", "y"),
+        '\u{178}' => Str("Y"),
+        '\u{179}'..='\u{17e}' => pair(char, 0x179, "Z", "z"),
+        '\u{17f}' => Str("s"),
+        '…' => Str("..."),
+        '\u{fb00}' => Str("ff"),
+        '\u{fb01}' => Str("fi"),
+        '\u{fb02}' => Str("fl"),
+        '\u{fb03}' => Str("ffi"),
+        '\u{fb04}' => Str("ffl"),
+        '\u{fb05}' | '\u{fb06}' => Str("st"),
+        '\u{ff01}'..='\u{ff5e}' => Char(char::from_u32(char as u32 - 0xfee0).unwrap()),
+        char => Char(char),
+    }
+}
+
+/// Whether a character is a combining mark
+fn is_combining_mark(char: char) -> bool {
+    matches!(
+        char,
+        '\u{300}'..='\u{36f}'
+            | '\u{1ab0}'..='\u{1aff}'
+            | '\u{1dc0}'..='\u{1dff}'
+            | '\u{20d0}'..='\u{20ff}'
+            | '\u{fe20}'..='\u{fe2f}'
+    )
+}
+
+/// A fuzzy pattern, matching any line that contains its characters
+/// in order
+///
+/// The case of characters is handled like [`Case::Smart`]. Matches
+/// never cross lines, and go from the first to the last matched
+/// character, picking the tightest span that ends on the first
+/// possible character.
+#[derive(Debug, Clone)]
+pub struct Fuzzy {
+    pat: Vec<char>,
+    ignore_case: bool,
+}
+
+impl Fuzzy {
+    /// Returns a new [`Fuzzy`] pattern
+    pub fn new(pat: impl AsRef<str>) -> Self {
+        let pat = pat.as_ref();
+        let ignore_case = !pat.chars().any(char::is_uppercase);
+        Self {
+            pat: pat.chars().filter(|c| !c.is_whitespace()).collect(),
+            ignore_case,
+        }
+    }
+
+    /// Scores a match against `haystack`
+    ///
+    /// Returns [`None`] if `haystack` doesn't contain the characters
+    /// of the pattern in order. Otherwise, returns the score, and
+    /// the byte positions of each matched character in `haystack`.
+    ///
+    /// Higher scores are better: consecutive characters, characters
+    /// at the start of words and matches at the start of `haystack`
+    /// are favored, while gaps in between characters are penalized.
+    pub fn score(&self, haystack: &str) -> Option<(i32, Vec<usize>)> {
+        let (start, end) = self.span(haystack)?;
+
+        let mut positions = Vec::with_capacity(self.pat.len());
+        let mut score = 0;
+        let mut pat = self.pat.iter().peekable();
+        let mut prev: Option<char> = haystack[..start].chars().next_back();
+        let mut gap = 0;
+
+        for (b, char) in haystack[start..end].char_indices() {
+            let b = start + b;
+            if let Some(p) = pat.peek()
+                && self.char_eq(**p, char)
+            {
+                pat.next();
+                score += 16;
+                if positions.last().is_some_and(|last: &usize| {
+                    *last + haystack[*last..].chars().next().unwrap().len_utf8() == b
+                }) {
+                    score += 8;
+                }
+                if prev.is_none_or(|prev| {
+                    !is_word_char(prev) || (prev.is_lowercase() && char.is_uppercase())
+                }) {
+                    score += 8;
+                }
+                if gap > 0 {
+                    score -= 3 + (gap - 1).min(15);
+                }
+                positions.push(b);
+                gap = 0;
+            } else {
+                gap += 1;
+            }
+            prev = Some(char);
+        }
+
+        Some((score - (start as i32).min(15), positions))
+    }
+
+    /// The byte range of the tightest match in `haystack`
+    fn span(&self, haystack: &str) -> Option<(usize, usize)> {
+        if self.pat.is_empty() {
+            return None;
+        }
+
+        let mut pat = self.pat.iter();
+        let mut p = pat.next();
+        let mut end = None;
+        for (b, char) in haystack.char_indices() {
+            if self.char_eq(*p.unwrap(), char) {
+                p = pat.next();
+                if p.is_none() {
+                    end = Some(b + char.len_utf8());
+                    break;
+                }
+            }
+        }
+        let end = end?;
+
+        // Going back from the end, in order to find the tightest span.
+        let mut pat = self.pat.iter().rev();
+        let mut p = pat.next();
+        for (b, char) in haystack[..end].char_indices().rev() {
+            if self.char_eq(*p.unwrap(), char) {
+                p = pat.next();
+                if p.is_none() {
+                    return Some((b, end));
+                }
+            }
+        }
+
+        unreachable!("Since the pattern was matched forwards, it must match backwards");
+    }
+
+    /// Whether a character of the pattern matches one of the
+    /// haystack
+    fn char_eq(&self, pat: char, char: char) -> bool {
+        pat == char || (self.ignore_case && char.to_lowercase().eq(pat.to_lowercase()))
+    }
+
+    /// The matching span in each line of the `start..end` range, in
+    /// the given order
+    fn find_in_lines(
+        &self,
+        text: &Text,
+        start: Point,
+        end: Point,
+        lines: impl Iterator<Item = u32>,
+    ) -> Option<(Point, Point)> {
+        lines
+            .filter_map(|line| {
+                let l0 = text.point_at_line(line).max(start);
+                let l1 = match line < text.len().line() {
+                    true => text.point_at_line(line + 1),
+                    false => text.len(),
+                }
+                .min(end);
+                if l0 >= l1 {
+                    return None;
+                }
+
+                let str: String = text.strs_in_range((l0, l1)).concat();
+                let (b0, b1) = self.span(str.trim_end_matches('\n'))?;
+                Some((
+                    text.point_at(l0.byte() + b0 as u32),
+                    text.point_at(l0.byte() + b1 as u32),
+                ))
+            })
+            .next()
+    }
+}
+
+impl Matcher for Fuzzy {
+    fn find_fwd(&self, text: &Text, at: Point, end: Point) -> Option<(Point, Point)> {
+        self.find_in_lines(text, at, end, at.line()..=end.line())
+    }
+
+    fn find_rev(&self, text: &Text, at: Point, start: Point) -> Option<(Point, Point)> {
+        self.find_in_lines(text, start, at, (start.line()..=at.line()).rev())
+    }
+
+    fn matches(&self, haystack: &str) -> bool {
+        self.span(haystack).is_some()
+    }
+}
+
+/// Flags for a [`Searcher`], which replace the regex with a
+/// [`Matcher`]
+///
+/// In an [`IncSearch`], these are given before the pattern, like
+/// `-w -S pattern`:
+///
+/// - `-w`: only match whole words, literally.
+/// - `-i`: match literally, ignoring case.
+/// - `-S`: match literally, ignoring case unless the pattern has
+///   uppercase characters.
+/// - `-a`: match literally, ignoring accents. Only Latin characters
+///   and some ligatures are decomposed, see [`Literal`].
+/// - `-f`: match fuzzily, on each line.
+///
+/// When no flags are given, the global defaults are used instead,
+/// which are controlled by the `search-case` option (`sensitive`,
+/// `insensitive` or `smart`) and the `search-ignore-accents` option.
+/// Like the `-a` flag, that option only decomposes Latin characters,
+/// some ligatures and fullwidth ASCII, instead of doing a full NFKD
+/// normalization.
+///
+/// [`IncSearch`]: crate::widgets::IncSearch
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchFlags {
+    /// Only match whole words
+    pub whole_word: bool,
+    /// How to treat the case of characters
+    pub case: Case,
+    /// Ignore accents and other combining marks
+    pub ignore_accents: bool,
+    /// Match fuzzily, instead of literally
+    pub fuzzy: bool,
+}
+
+impl SearchFlags {
+    /// Splits the flags at the start of a query from the pattern
+    ///
+    /// Flags are only recognized at the start of the query, and are
+    /// separated from the pattern by a space. Returns the flags, and
+    /// the byte where the pattern starts.
+    pub fn parse(query: &str) -> (Self, usize) {
+        let mut flags = Self::default();
+        let mut start = 0;
+
+        while let Some((flag, _)) = query[start..].split_once(' ') {
+            match flag {
+                "-w" => flags.whole_word = true,
+                "-i" => flags.case = Case::Insensitive,
+                "-S" => flags.case = Case::Smart,
+                "-a" => flags.ignore_accents = true,
+                "-f" => flags.fuzzy = true,
+                _ => break,
+            }
+            start += flag.len() + 1;
+        }
+
+        (flags, start)
+    }
+
+    /// Whether any flags were set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The flags set by the global search options
+    ///
+    /// These are the `search-case` and `search-ignore-accents`
+    /// options, and are used when a query has no flags.
+    pub fn global() -> Self {
+        declare_opts();
+        let case = match opts::get("search-case") {
+            Some(OptValue::Enum("insensitive")) => Case::Insensitive,
+            Some(OptValue::Enum("smart")) => Case::Smart,
+            _ => Case::Sensitive,
+        };
+        let ignore_accents = opts::get_bool("search-ignore-accents").unwrap_or(false);
+
+        Self { case, ignore_accents, ..Self::default() }
+    }
+
+    /// The [`Matcher`] for a pattern with these flags
+    fn matcher(&self, pat: &str) -> Box<dyn Matcher> {
+        if self.fuzzy {
+            Box::new(Fuzzy::new(pat))
+        } else {
+            let mut literal = Literal::new(pat, self.case);
+            if self.whole_word {
+                literal = literal.whole_word();
+            }
+            if self.ignore_accents {
+                literal = literal.ignore_accents();
+            }
+            Box::new(literal)
+        }
+    }
+}
+
+/// Declares the global search options
+///
+/// This is done when starting Duat, so the options can be set before
+/// searching for anything.
+pub(crate) fn declare_opts() {
+    opts::declare_enum(
+        "search-case",
+        "sensitive",
+        &["sensitive", "insensitive", "smart"],
+        Scope::Global,
+    );
+    opts::declare_bool("search-ignore-accents", false, Scope::Global);
+}
+
+/// Whether the `start..end` range is surrounded by non word
+/// characters
+fn is_word_boundary(text: &Text, start: usize, end: usize) -> bool {
+    let before = text
+        .strs_in_range_inner(..start as u32)
+        .into_iter()
+        .flat_map(str::chars)
+        .next_back();
+    let after = text
+        .strs_in_range_inner(end as u32..)
+        .into_iter()
+        .flat_map(str::chars)
+        .next();
+
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
 pub struct Searcher {
     pat: String,
+    is_literal: bool,
+    matcher: Option<Box<dyn Matcher>>,
     fwd_dfa: &'static DFA,
     rev_dfa: &'static DFA,
     fwd_cache: RwLockWriteGuard<'static, Cache>,
@@ -134,6 +948,8 @@ impl Searcher {
     pub fn new(pat: String) -> Result<Self, Box<regex_syntax::Error>> {
         let dfas = dfas_from_pat(&pat)?;
         Ok(Self {
+            is_literal: !pat.chars().any(regex_syntax::is_meta_character),
+            matcher: None,
             pat,
             fwd_dfa: &dfas.fwd.0,
             rev_dfa: &dfas.rev.0,
@@ -142,12 +958,72 @@ impl Searcher {
         })
     }
 
+    /// Returns a new [`Searcher`], whose pattern is matched
+    /// according to [`SearchFlags`]
+    ///
+    /// If flags are set, the pattern is not treated as a regex, but
+    /// matched by a [`Literal`] or [`Fuzzy`] [`Matcher`].
+    ///
+    /// Otherwise, the [global] flags are used. If the pattern has no
+    /// special characters, it is then matched by a [`Literal`] with
+    /// those flags. If it does, it is still treated as a regex, which
+    /// can only ignore case.
+    ///
+    /// [global]: SearchFlags::global
+    pub fn with_flags(pat: String, flags: SearchFlags) -> Result<Self, Box<regex_syntax::Error>> {
+        let flags = if flags.is_empty() {
+            let global = SearchFlags::global();
+            let is_literal = !pat.chars().any(regex_syntax::is_meta_character);
+            let ignore_case = match global.case {
+                Case::Sensitive => false,
+                Case::Insensitive => true,
+                Case::Smart => !pat.chars().any(char::is_uppercase),
+            };
+
+            if global.is_empty() || (!is_literal && !ignore_case) {
+                return Self::new(pat);
+            } else if !is_literal {
+                // Validating first, so errors point to the right place.
+                dfas_from_pat(&pat)?;
+                let mut searcher = Self::new(format!("(?i){pat}"))?;
+                searcher.pat = pat;
+                return Ok(searcher);
+            }
+            global
+        } else {
+            flags
+        };
+
+        let dfas = dfas_from_pat(regex_syntax::escape(&pat))?;
+        Ok(Self {
+            is_literal: true,
+            matcher: Some(flags.matcher(&pat)),
+            pat,
+            fwd_dfa: &dfas.fwd.0,
+            rev_dfa: &dfas.rev.0,
+            fwd_cache: dfas.fwd.1.write(),
+            rev_cache: dfas.rev.1.write(),
+        })
+    }
+
+    /// Searches forward, starting from `at`
+    ///
+    /// If the pattern has no special characters, it will be searched
+    /// for literally, through [`Text::search_literal_fwd`], which is
+    /// a lot cheaper. If it was created with [`SearchFlags`], its
+    /// [`Matcher`] is used instead.
     pub fn search_fwd<'b>(
         &'b mut self,
         text: &'b mut Text,
         at: Point,
         end: Option<Point>,
-    ) -> impl Iterator<Item = (Point, Point)> + 'b {
+    ) -> Box<dyn Iterator<Item = (Point, Point)> + 'b> {
+        if let Some(matcher) = &self.matcher {
+            return Box::new(text.search_matcher_fwd(matcher.as_ref(), at, end));
+        } else if self.is_literal {
+            return Box::new(text.search_literal_fwd(&self.pat, at, end));
+        }
+
         let haystack = match end {
             Some(end) => unsafe {
                 text.make_contiguous_in(at.byte()..end.byte());
@@ -167,7 +1043,7 @@ impl Searcher {
         let fwd_cache = &mut self.fwd_cache;
         let rev_cache = &mut self.rev_cache;
         let gap = at.byte();
-        std::iter::from_fn(move || {
+        Box::new(std::iter::from_fn(move || {
             let init = fwd_input.start();
             let end = loop {
                 if let Ok(Some(half)) = fwd_dfa.try_search_fwd(fwd_cache, &fwd_input) {
@@ -203,15 +1079,27 @@ impl Searcher {
             last_point = end;
 
             Some((start, end))
-        })
+        }))
     }
 
+    /// Searches in reverse, starting from `at`
+    ///
+    /// If the pattern has no special characters, it will be searched
+    /// for literally, through [`Text::search_literal_rev`], which is
+    /// a lot cheaper. If it was created with [`SearchFlags`], its
+    /// [`Matcher`] is used instead.
     pub fn search_rev<'b>(
         &'b mut self,
         text: &'b mut Text,
         at: Point,
         start: Option<Point>,
-    ) -> impl Iterator<Item = (Point, Point)> + 'b {
+    ) -> Box<dyn Iterator<Item = (Point, Point)> + 'b> {
+        if let Some(matcher) = &self.matcher {
+            return Box::new(text.search_matcher_rev(matcher.as_ref(), at, start));
+        } else if self.is_literal {
+            return Box::new(text.search_literal_rev(&self.pat, at, start));
+        }
+
         let haystack = match start {
             Some(start) => unsafe {
                 text.make_contiguous_in(start.byte()..at.byte());
@@ -231,7 +1119,7 @@ impl Searcher {
         let fwd_cache = &mut self.fwd_cache;
         let rev_cache = &mut self.rev_cache;
         let gap = start.map(|p| p.byte()).unwrap_or(0);
-        std::iter::from_fn(move || {
+        Box::new(std::iter::from_fn(move || {
             let init = rev_input.end();
             let start = loop {
                 if let Ok(Some(half)) = rev_dfa.try_search_rev(rev_cache, &rev_input) {
@@ -264,11 +1152,15 @@ impl Searcher {
             last_point = start;
 
             Some((start, end))
-        })
+        }))
     }
 
     /// Whether or not the regex matches a specific pattern
     pub fn matches(&mut self, query: impl AsRef<[u8]>) -> bool {
+        if let Some(matcher) = &self.matcher {
+            return std::str::from_utf8(query.as_ref()).is_ok_and(|str| matcher.matches(str));
+        }
+
         let input = Input::new(&query).anchored(Anchored::Yes);
 
         let Ok(Some(half)) = self.fwd_dfa.try_search_fwd(&mut self.fwd_cache, &input) else {
@@ -284,6 +1176,29 @@ impl Searcher {
     }
 }
 
+/// The bytes around the gap in which a match of `len` bytes could
+/// straddle both slices, limited to the `start..end` range
+///
+/// Also returns the byte where said bytes start.
+fn straddling(
+    s0: &[u8],
+    s1: &[u8],
+    start: usize,
+    end: usize,
+    len: usize,
+) -> Option<(usize, Vec<u8>)> {
+    let gap = s0.len();
+    if len < 2 || start >= gap || end <= gap {
+        return None;
+    }
+
+    let b0 = start.max(gap.saturating_sub(len - 1));
+    let b1 = end.min(gap + len - 1);
+    let bytes = s0[b0..].iter().chain(&s1[..b1 - gap]).copied().collect();
+
+    Some((b0, bytes))
+}
+
 struct DFAs {
     fwd: (DFA, RwLock<Cache>),
     rev: (DFA, RwLock<Cache>),
@@ -446,3 +1361,53 @@ impl Patterns<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Case, Literal, Matcher};
+    use crate::text::{Point, Text};
+
+    #[test]
+    fn case_folding() {
+        let insensitive = |pat: &str| Literal::new(pat, Case::Insensitive);
+
+        assert!(insensitive("strasse").matches("Straße"));
+        assert!(insensitive("STRASSE").matches("STRAẞE"));
+        assert!(insensitive("straße").matches("STRASSE"));
+        assert!(insensitive("s").matches("ſ"));
+        assert!(insensitive("k").matches("\u{212a}"));
+        assert!(insensitive("office").matches("o\u{fb03}ce"));
+        assert!(insensitive("σ").matches("ς"));
+        assert!(insensitive("éclair").matches("ÉCLAIR"));
+
+        assert!(!insensitive("s").matches("ß"));
+        assert!(!Literal::new("ss", Case::Sensitive).matches("ß"));
+        assert!(!Literal::new("Straße", Case::Smart).matches("strasse"));
+    }
+
+    #[test]
+    fn folded_matches_are_found_in_text() {
+        let text = Text::from("the Straße and the \u{212a}elvin\n");
+        let end = text.len();
+
+        let found =
+            Literal::new("strasse", Case::Insensitive).find_fwd(&text, Point::default(), end);
+        let (start, end_of_match) = found.unwrap();
+        assert_eq!((start.byte(), end_of_match.byte()), (4, 11));
+
+        let found = Literal::new("kelvin", Case::Smart).find_rev(&text, end, Point::default());
+        assert_eq!(found.map(|(start, _)| start.byte()), Some(20));
+    }
+
+    #[test]
+    fn accents_are_ignored() {
+        let unaccented = |pat: &str| Literal::new(pat, Case::Sensitive).ignore_accents();
+
+        assert!(unaccented("cafe").matches("café"));
+        assert!(unaccented("cafe").matches("cafe\u{301}"));
+        assert!(unaccented("odz").matches("ódź"));
+        assert!(unaccented("ffi").matches("\u{fb03}"));
+        assert!(!unaccented("Cafe").matches("café"));
+        assert!(!unaccented("cafe").matches("caff"));
+    }
+}
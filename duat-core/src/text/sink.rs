@@ -0,0 +1,126 @@
+//! A thread safe handle for editing a [`Text`]
+//!
+//! Widgets that are only ever written to by background jobs (e.g. a
+//! log or a scratch buffer) can't have their [`Text`] written to
+//! directly from other threads, since that would race with printing.
+//! Instead, they should own a [`TextSink`], which queues up edits
+//! that are only applied when the widget updates itself on the main
+//! update pass.
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use parking_lot::Mutex;
+
+use super::Text;
+
+/// A thread safe queue of edits to a [`Text`]
+///
+/// Clones of a [`TextSink`] refer to the same queue, so you can hand
+/// one out to as many background jobs as needed, while the [`Widget`]
+/// keeps another in order to [`apply`] the edits.
+///
+/// ```rust
+/// # use duat_core::{text::{Text, TextSink}, ui::Ui};
+/// struct Log {
+///     text: Text,
+///     sink: TextSink,
+/// }
+///
+/// impl Log {
+///     fn update<U: Ui>(&mut self, _area: &U::Area) {
+///         // Edits sent from other threads are applied here.
+///         self.sink.apply(&mut self.text);
+///     }
+/// }
+/// ```
+///
+/// The [`Widget`]'s checker should return `true` whenever
+/// [`has_edits`] does, so that the main loop knows it needs to be
+/// updated.
+///
+/// [`Widget`]: crate::widgets::Widget
+/// [`apply`]: TextSink::apply
+/// [`has_edits`]: TextSink::has_edits
+#[derive(Clone, Default)]
+pub struct TextSink {
+    edits: Arc<Mutex<Vec<Edit>>>,
+    has_edits: Arc<AtomicBool>,
+}
+
+impl TextSink {
+    /// Returns a new, empty [`TextSink`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues [`Text`] to be appended to the end
+    pub fn append(&self, text: impl Into<Text>) {
+        self.push(Edit::Append(text.into()))
+    }
+
+    /// Queues a replacement of the whole [`Text`]
+    ///
+    /// Any edits queued before this one are discarded, since they
+    /// would be replaced anyway.
+    pub fn replace(&self, text: impl Into<Text>) {
+        let mut edits = self.edits.lock();
+        edits.clear();
+        edits.push(Edit::Replace(text.into()));
+        self.has_edits.store(true, Ordering::Release);
+    }
+
+    /// Queues the clearing of the [`Text`]
+    pub fn clear(&self) {
+        self.replace(Text::new())
+    }
+
+    /// Whether there are edits waiting to be applied
+    pub fn has_edits(&self) -> bool {
+        self.has_edits.load(Ordering::Acquire)
+    }
+
+    /// Applies all queued edits to a [`Text`], in order
+    ///
+    /// This should be called in [`Widget::update`], which is the only
+    /// place where the [`Text`] of a [`Widget`] can be safely
+    /// modified.
+    ///
+    /// [`Widget`]: crate::widgets::Widget
+    /// [`Widget::update`]: crate::widgets::Widget::update
+    pub fn apply(&self, text: &mut Text) {
+        let edits = {
+            let mut edits = self.edits.lock();
+            self.has_edits.store(false, Ordering::Release);
+            std::mem::take(&mut *edits)
+        };
+
+        for edit in edits {
+            match edit {
+                Edit::Append(new) => {
+                    let end = text.len();
+                    text.replace_range((end, end), new.to_string());
+
+                    // The appended bytes come in untagged, so their length
+                    // is handed over to the tags of the new Text.
+                    let new_end = text.len().byte();
+                    text.tags.transform(end.byte()..new_end, end.byte());
+                    text.tags.extend(*new.tags);
+                }
+                Edit::Replace(new) => *text = new,
+            }
+        }
+    }
+
+    fn push(&self, edit: Edit) {
+        self.edits.lock().push(edit);
+        self.has_edits.store(true, Ordering::Release);
+    }
+}
+
+/// An edit waiting to be applied
+enum Edit {
+    Append(Text),
+    Replace(Text),
+}
@@ -12,7 +12,7 @@ use std::{
     sync::atomic::{AtomicU16, Ordering},
 };
 
-static KEY_COUNT: AtomicU16 = AtomicU16::new(3);
+static KEY_COUNT: AtomicU16 = AtomicU16::new(4);
 
 /// The id of a [ghost text]
 ///
@@ -148,6 +148,11 @@ impl Key {
     pub(crate) const fn for_alias() -> Self {
         Self(2)
     }
+
+    /// A [`Key`] specifically for suggestions
+    pub(in crate::text) const fn for_suggestion() -> Self {
+        Self(3)
+    }
 }
 
 impl std::fmt::Debug for Key {
@@ -181,9 +186,11 @@ impl std::iter::Step for Key {
 /// This can be either one [`Key`] or multiple, in which case many
 /// different [`Key`]s will be searched for and [removed]
 ///
+/// This trait is sealed, and cannot be implemented outside of Duat.
+///
 /// [`Tag`]: super::Tag
 /// [removed]: crate::text::Text::remove_tags_on
-pub trait Keys: Clone + PartialEq + Eq {
+pub trait Keys: Clone + PartialEq + Eq + crate::private::Sealed {
     /// All [`Key`]s that should be searched
     fn range(self) -> Range<Key>;
 
@@ -194,12 +201,14 @@ pub trait Keys: Clone + PartialEq + Eq {
     }
 }
 
+impl crate::private::Sealed for Key {}
 impl Keys for Key {
     fn range(self) -> Range<Key> {
         Key(self.0)..Key(self.0 + 1)
     }
 }
 
+impl crate::private::Sealed for Range<Key> {}
 impl Keys for Range<Key> {
     fn range(self) -> Range<Key> {
         self
@@ -145,7 +145,8 @@ impl Tags {
     pub fn extend(&mut self, mut other: Tags) {
         let len = self.buf.len();
         let init_skip = if let Some(TagOrSkip::Skip(first)) = other.buf.get(0)
-            && let Some(TagOrSkip::Skip(last)) = self.buf.get_mut(len - 1)
+            && let Some(TagOrSkip::Skip(last)) =
+                len.checked_sub(1).and_then(|i| self.buf.get_mut(i))
         {
             let first = *first;
             *last += first;
@@ -200,6 +201,32 @@ impl Tags {
         }
     }
 
+    /// Removes all [`RawTag`]s of a given [`Keys`] in a byte range
+    pub fn remove_from(&mut self, range: Range<u32>, keys: impl Keys) {
+        let (n, b, _) = self
+            .get_skip_at(range.start.min(self.len_bytes()))
+            .unwrap_or((self.buf.len() as u32, self.len_bytes(), 0));
+        // Tags on the start of the skip come right before it.
+        let n = rev_range(&self.buf, ..n)
+            .take_while(|(_, ts)| ts.is_tag())
+            .last()
+            .map_or(n, |(n, _)| n as u32);
+
+        let keys = keys.range();
+        let mut b_to_remove: Vec<u32> = fwd_range(&self.buf, n..)
+            .filter_map(entries_fwd(b))
+            .take_while(|(_, b, _)| *b < range.end)
+            .filter_map(|(_, b, t)| {
+                (b >= range.start && keys.clone().contains(t.key())).then_some(b)
+            })
+            .collect();
+        b_to_remove.dedup();
+
+        for b in b_to_remove {
+            self.remove_at(b, keys.clone());
+        }
+    }
+
     /// Removes [`RawTag`]s given a predicate
     pub fn remove_at_if(&mut self, at: u32, f: impl Fn(&RawTag) -> bool) {
         let (n, b, skip) = match self.get_skip_at(at) {
@@ -251,17 +278,7 @@ impl Tags {
         let new = old.start..new_end;
         // In case we're appending to the GapBuffer, a shortcut can be made.
         let Some((n, b, skip)) = self.get_skip_at(old.start) else {
-            // Unlike inserting in the middle, appending should not move the tags
-            // ahead.
-            let last = self.buf.len().saturating_sub(1);
-            if let Some(TagOrSkip::Skip(skip)) = self.buf.get_mut(last) {
-                *skip += new.end - old.start;
-                self.records.append((0, new.end - old.start));
-            } else if new.end > old.start {
-                self.buf.push_back(TagOrSkip::Skip(new.end - old.start));
-                self.records.append((1, new.end - old.start));
-            }
-
+            self.append_len(new.end - old.start);
             return;
         };
 
@@ -270,7 +287,11 @@ impl Tags {
         }
         if new_end > old.start {
             let only_insert = old.start == old.end;
-            self.insert_len(old.start, new_end - old.start, (n, b, skip), only_insert);
+            // The removal may have shrunk or removed the skip.
+            match self.get_skip_at(old.start) {
+                Some(entry) => self.insert_len(old.start, new_end - old.start, entry, only_insert),
+                None => self.append_len(new_end - old.start),
+            }
         }
 
         ////////// Range management
@@ -289,6 +310,19 @@ impl Tags {
         }
     }
 
+    fn append_len(&mut self, len: u32) {
+        // Unlike inserting in the middle, appending should not move the tags
+        // ahead.
+        let last = self.buf.len().saturating_sub(1);
+        if let Some(TagOrSkip::Skip(skip)) = self.buf.get_mut(last) {
+            *skip += len;
+            self.records.append((0, len));
+        } else if len > 0 {
+            self.buf.push_back(TagOrSkip::Skip(len));
+            self.records.append((1, len));
+        }
+    }
+
     fn insert_len(&mut self, at: u32, len: u32, (n, b, skip): (u32, u32, u32), only_insert: bool) {
         // If a == b, we change the length before the tags
         // If I am inserting and removing, then only the skip of the modified
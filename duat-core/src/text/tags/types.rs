@@ -44,6 +44,23 @@ pub enum Tag {
     EndAlignRight,
 
     GhostText(Text),
+    /// Text shown after the end of the line, not affecting layout
+    ///
+    /// It is clipped if there isn't enough space left in the line.
+    EolGhost(Text),
+    /// Text shown flush right in the line, not affecting layout
+    ///
+    /// It is clipped if there isn't enough space left in the line.
+    RightAligned(Text),
+    /// Extends the background of a form to the end of the line
+    ///
+    /// Unlike [`PushForm`], this does not apply to any characters,
+    /// it only paints the empty space between the last character of
+    /// the line and the right edge of the area, which is useful for
+    /// things like a cursor line or diff views.
+    ///
+    /// [`PushForm`]: Tag::PushForm
+    LineBg(FormId),
     StartConceal,
     EndConceal,
 
@@ -58,6 +75,14 @@ impl Tag {
         Self::GhostText(to_text.into())
     }
 
+    pub fn eol_ghost(to_text: impl Into<Text>) -> Self {
+        Self::EolGhost(to_text.into())
+    }
+
+    pub fn right_aligned(to_text: impl Into<Text>) -> Self {
+        Self::RightAligned(to_text.into())
+    }
+
     pub fn to_raw(
         self,
         key: Key,
@@ -80,6 +105,17 @@ impl Tag {
                 texts.insert(id, text);
                 (RawTag::GhostText(key, id), None)
             }
+            Self::EolGhost(text) => {
+                let id = TextId::new();
+                texts.insert(id, text);
+                (RawTag::EolGhost(key, id), None)
+            }
+            Self::RightAligned(text) => {
+                let id = TextId::new();
+                texts.insert(id, text);
+                (RawTag::RightAligned(key, id), None)
+            }
+            Self::LineBg(id) => (RawTag::LineBg(key, id), None),
             Self::StartConceal => (RawTag::StartConceal(key), None),
             Self::EndConceal => (RawTag::EndConceal(key), None),
             Self::ToggleStart(toggle) => {
@@ -151,6 +187,12 @@ pub enum RawTag {
     ConcealUntil(u32),
 
     GhostText(Key, TextId),
+    /// Text printed after the end of the line.
+    EolGhost(Key, TextId),
+    /// Text printed flush right in the line.
+    RightAligned(Key, TextId),
+    /// Extends the background of a form to the end of the line.
+    LineBg(Key, FormId),
 
     // Not Implemented:
     /// Begins a toggleable section in the text.
@@ -243,6 +285,9 @@ impl RawTag {
             | Self::StartConceal(key)
             | Self::EndConceal(key)
             | Self::GhostText(key, _)
+            | Self::EolGhost(key, _)
+            | Self::RightAligned(key, _)
+            | Self::LineBg(key, _)
             | Self::ToggleStart(key, _)
             | Self::ToggleEnd(key, _) => *key,
             Self::ConcealUntil(_) => unreachable!(
@@ -269,6 +314,9 @@ impl std::fmt::Debug for RawTag {
             RawTag::EndConceal(key) => write!(f, "EndConceal({key:?})"),
             RawTag::ConcealUntil(key) => write!(f, "ConcealUntil({key:?})"),
             RawTag::GhostText(key, id) => write!(f, "GhostText({key:?}, {id:?})"),
+            RawTag::EolGhost(key, id) => write!(f, "EolGhost({key:?}, {id:?})"),
+            RawTag::RightAligned(key, id) => write!(f, "RightAligned({key:?}, {id:?})"),
+            RawTag::LineBg(key, id) => write!(f, "LineBg({key:?}, {})", form::name_of(*id)),
             RawTag::ToggleStart(key, id) => write!(f, "ToggleStart({key:?}, {id:?})"),
             RawTag::ToggleEnd(key, id) => write!(f, "ToggleEnd({key:?}, {id:?})"),
         }
@@ -294,6 +342,9 @@ impl PartialEq for RawTag {
             (RawTag::EndConceal(_), RawTag::EndConceal(_)) => true,
             (RawTag::ConcealUntil(_), RawTag::ConcealUntil(_)) => true,
             (RawTag::GhostText(_, lhs), RawTag::GhostText(_, rhs)) => lhs == rhs,
+            (RawTag::EolGhost(_, lhs), RawTag::EolGhost(_, rhs)) => lhs == rhs,
+            (RawTag::RightAligned(_, lhs), RawTag::RightAligned(_, rhs)) => lhs == rhs,
+            (RawTag::LineBg(_, lhs), RawTag::LineBg(_, rhs)) => lhs == rhs,
             (RawTag::ToggleStart(_, lhs), RawTag::ToggleStart(_, rhs)) => lhs == rhs,
             (RawTag::ToggleEnd(_, lhs), RawTag::ToggleEnd(_, rhs)) => lhs == rhs,
             (..) => false,
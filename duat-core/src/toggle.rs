@@ -0,0 +1,114 @@
+//! [`Widget`]s that can be shown and hidden on demand
+//!
+//! Any type of [`Widget`] can be registered under a name, after which
+//! every [`Widget`] of that type can be shown and hidden with the
+//! `toggle` command:
+//!
+//! ```rust
+//! # use duat_core::{toggle, ui::Ui, widgets::LineNumbers};
+//! # fn test<U: Ui>() {
+//! toggle::register::<LineNumbers<U>, U>("line-numbers");
+//! # }
+//! ```
+//!
+//! ```text
+//! :toggle line-numbers
+//! ```
+//!
+//! Hidden [`Widget`]s are not removed, they are only shrunk down to
+//! nothing, and stop being updated. This means that they keep their
+//! state, showing up just as they were when toggled back on. While a
+//! type is hidden, the [`Widget`]s of that type that get pushed, like
+//! by the [`OnFileOpen`] hook, also start out hidden.
+//!
+//! [`OnFileOpen`]: crate::hooks::OnFileOpen
+use parking_lot::Mutex;
+
+use crate::{
+    context,
+    ui::{Area, Ui, Window},
+    widgets::{Node, Widget},
+};
+
+static TOGGLES: Mutex<Vec<Toggle>> = Mutex::new(Vec::new());
+
+/// A name for a type of [`Widget`], and whether it is shown
+struct Toggle {
+    name: String,
+    widget_name: &'static str,
+    is_shown: bool,
+}
+
+/// Lets [`Widget`]s of type `W` be toggled by a name
+///
+/// If the name was already registered, it will now toggle `W`
+/// instead.
+pub fn register<W: Widget<U>, U: Ui>(name: impl ToString) {
+    let name = name.to_string();
+    let widget_name = crate::duat_name::<W>();
+
+    let mut toggles = TOGGLES.lock();
+    if let Some(toggle) = toggles.iter_mut().find(|toggle| toggle.name == name) {
+        toggle.widget_name = widget_name;
+    } else {
+        toggles.push(Toggle { name, widget_name, is_shown: true });
+    }
+}
+
+/// Whether the [`Widget`]s of a name are shown
+///
+/// Returns [`None`] if no [`Widget`] was [registered] with that
+/// name.
+///
+/// [registered]: register
+pub fn is_shown(name: &str) -> Option<bool> {
+    let toggles = TOGGLES.lock();
+    let toggle = toggles.iter().find(|toggle| toggle.name == name)?;
+    Some(toggle.is_shown)
+}
+
+/// Shows or hides the [`Widget`]s of a name, in every window
+///
+/// Returns whether they are now shown, or [`None`] if no [`Widget`]
+/// was [registered] with that name.
+///
+/// [registered]: register
+pub(crate) fn toggle<U: Ui>(name: &str) -> Option<bool> {
+    let (widget_name, is_shown) = {
+        let mut toggles = TOGGLES.lock();
+        let toggle = toggles.iter_mut().find(|toggle| toggle.name == name)?;
+        toggle.is_shown = !toggle.is_shown;
+        (toggle.widget_name, toggle.is_shown)
+    };
+
+    let nodes: Vec<Node<U>> = context::windows::<U>()
+        .read()
+        .iter()
+        .flat_map(Window::nodes)
+        .filter(|node| node.widget_name() == widget_name)
+        .cloned()
+        .collect();
+
+    for node in nodes {
+        let _ = match is_shown {
+            true => node.area().reveal(),
+            false => node.area().hide(),
+        };
+    }
+
+    Some(is_shown)
+}
+
+/// The names that [`Widget`]s were registered with
+pub(crate) fn names() -> Vec<String> {
+    let toggles = TOGGLES.lock();
+    toggles.iter().map(|toggle| toggle.name.clone()).collect()
+}
+
+/// Whether new [`Widget`]s with this name should start hidden
+pub(crate) fn starts_hidden(widget_name: &str) -> bool {
+    let toggles = TOGGLES.lock();
+    toggles
+        .iter()
+        .any(|toggle| toggle.widget_name == widget_name && !toggle.is_shown)
+}
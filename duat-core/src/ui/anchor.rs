@@ -0,0 +1,146 @@
+//! Placement of floating areas next to the main cursor
+//!
+//! Popups like completion lists, hover information and signature
+//! help all need to be shown right next to the main cursor of a
+//! [`File`], and need to follow it as it moves or as the [`File`]
+//! scrolls. A [`CursorAnchor`] does the math for that: every time it
+//! is [updated], it figures out where the main cursor is on screen,
+//! and places a rectangle of the requested size above or below it,
+//! flipping sides when there is no room near the edges of the
+//! window.
+//!
+//! [updated]: CursorAnchor::update
+use super::{Area, Side};
+use crate::{cfg::PrintCfg, mode::Cursors, text::Text, widgets::File};
+
+/// Where a floating area was placed by a [`CursorAnchor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatRect {
+    /// The top left corner, as `(x, y)`
+    pub tl: (u32, u32),
+    /// The bottom right corner, as `(x, y)`, exclusive
+    pub br: (u32, u32),
+    /// On which [`Side`] of the cursor it was placed
+    ///
+    /// This is either [`Side::Above`] or [`Side::Below`].
+    pub side: Side,
+}
+
+impl FloatRect {
+    /// The width of the [`FloatRect`]
+    pub fn width(&self) -> u32 {
+        self.br.0 - self.tl.0
+    }
+
+    /// The height of the [`FloatRect`]
+    pub fn height(&self) -> u32 {
+        self.br.1 - self.tl.1
+    }
+}
+
+/// Keeps a floating area anchored to the main cursor of a [`File`]
+///
+/// By default, the area is placed below the cursor, and only goes
+/// above it if there isn't enough room below. If there isn't enough
+/// room on either side, the side with the most room is picked, and
+/// the area is shrunk to fit in it.
+#[derive(Debug, Clone)]
+pub struct CursorAnchor {
+    prefers: Side,
+    rect: Option<FloatRect>,
+}
+
+impl CursorAnchor {
+    /// Returns a new [`CursorAnchor`], which prefers to be below
+    pub fn below() -> Self {
+        Self { prefers: Side::Below, rect: None }
+    }
+
+    /// Returns a new [`CursorAnchor`], which prefers to be above
+    pub fn above() -> Self {
+        Self { prefers: Side::Above, rect: None }
+    }
+
+    /// Updates the placement, returning `true` if it changed
+    ///
+    /// This should be called whenever the [`File`] is printed, since
+    /// the main cursor may have moved on screen, even if it didn't
+    /// move in the [`Text`]. `size` is the `(width, height)` that
+    /// the floating area would like to have.
+    pub fn update<A: Area>(
+        &mut self,
+        file: &File,
+        area: &A,
+        cursors: &Cursors,
+        size: (u32, u32),
+    ) -> bool {
+        let rect = cursor_coord(file.text(), area, cursors, file.print_cfg())
+            .zip(area.layout_tree().first().map(|root| (root.tl, root.br)))
+            .and_then(|(coord, bounds)| place(self.prefers, coord, size, bounds));
+
+        let has_changed = rect != self.rect;
+        self.rect = rect;
+        has_changed
+    }
+
+    /// Where the floating area should be, if it should be shown
+    ///
+    /// This is [`None`] if the main cursor is not visible, or if
+    /// there is no room left for the floating area.
+    pub fn rect(&self) -> Option<FloatRect> {
+        self.rect
+    }
+}
+
+impl Default for CursorAnchor {
+    fn default() -> Self {
+        Self::below()
+    }
+}
+
+/// The cell where the main cursor is printed
+fn cursor_coord<A: Area>(
+    text: &Text,
+    area: &A,
+    cursors: &Cursors,
+    cfg: PrintCfg,
+) -> Option<(u32, u32)> {
+    let main = cursors.get_main()?;
+    area.coord_of_point(text, main.caret(), cfg)
+}
+
+/// Places a rectangle of a given `size` next to `coord`
+fn place(
+    prefers: Side,
+    (x, y): (u32, u32),
+    (width, height): (u32, u32),
+    (tl, br): ((u32, u32), (u32, u32)),
+) -> Option<FloatRect> {
+    let below = br.1.saturating_sub(y + 1);
+    let above = y.saturating_sub(tl.1);
+
+    let side = match prefers {
+        Side::Above if above >= height || above >= below => Side::Above,
+        Side::Above => Side::Below,
+        _ if below >= height || below >= above => Side::Below,
+        _ => Side::Above,
+    };
+
+    let height = height.min(if side == Side::Above { above } else { below });
+    let width = width.min(br.0.saturating_sub(tl.0));
+    if height == 0 || width == 0 {
+        return None;
+    }
+
+    let x = x.min(br.0 - width).max(tl.0);
+    let y = match side {
+        Side::Above => y - height,
+        _ => y + 1,
+    };
+
+    Some(FloatRect {
+        tl: (x, y),
+        br: (x + width, y + height),
+        side,
+    })
+}
@@ -185,7 +185,7 @@ where
             let (node, parent) = window.push(widget, &*area, checker, specs, true);
 
             if let Some(related) = self.node.related_widgets() {
-                related.write().push(node.clone())
+                related.register(node.clone())
             }
 
             if let Some(parent) = &parent {
@@ -260,10 +260,47 @@ where
 
         let (node, parent) = window.push(widget, &area, checker, specs, true);
         if let Some(related) = self.node.related_widgets() {
-            related.write().push(node.clone())
+            related.register(node.clone())
         }
         (node.area().clone(), parent)
     }
+
+    /// The [`Area`] of the cluster of the [`File`] and its widgets
+    ///
+    /// This is the [`Area`] that the next widget will be pushed
+    /// around, so it grows as more widgets are pushed. It can be used
+    /// to configure the cluster as a whole, like changing the edges
+    /// around it, in a [`Ui`] that supports that.
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn area(&self) -> U::Area {
+        self.area.borrow().clone()
+    }
+
+    /// Clusters the [`File`] and its widgets with another [`Area`]
+    ///
+    /// Widgets pushed through this [`FileBuilder`] are already
+    /// clustered with the [`File`], so they move along with it. This
+    /// method extends that cluster to an `area` that was created in
+    /// some other way, like a widget pushed to the window.
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn cluster_with(
+        &self,
+        area: &U::Area,
+    ) -> Result<(), <U::Area as Area>::ConstraintChangeErr> {
+        self.area.borrow().cluster_with(area)
+    }
+
+    /// Dissolves the cluster of the [`File`] and its widgets
+    ///
+    /// After this, the [`File`] and each of its widgets can be moved
+    /// on their own.
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn uncluster(&self) -> Result<(), <U::Area as Area>::ConstraintChangeErr> {
+        self.node.area().uncluster()
+    }
 }
 
 impl<U: Ui> Drop for FileBuilder<U> {
@@ -0,0 +1,305 @@
+//! A reusable list of selectable entries
+//!
+//! The [`Menu`] is not a [`Widget`] by itself. Instead, it holds the
+//! entries, the selection and the scrolling state of a list, and can
+//! build a [`Text`] out of them. This lets features that need to
+//! show a list of options, like completions, pickers or confirmation
+//! prompts, share the same behavior, regardless of where the list is
+//! shown.
+//!
+//! ```rust
+//! # use duat_core::{text::text, ui::{Menu, MenuEvent}, mode::{KeyCode, KeyEvent, KeyMod}};
+//! let mut menu = Menu::new([text!("Yes"), text!("No")]).with_height(5);
+//!
+//! let event = menu.send_key(KeyEvent::new(KeyCode::Down, KeyMod::NONE));
+//! assert_eq!(event, MenuEvent::Moved);
+//! assert_eq!(menu.selected(), Some(1));
+//! ```
+//!
+//! [`Widget`]: crate::widgets::Widget
+use std::{ops::Range, sync::LazyLock};
+
+use crate::{
+    form::{self, FormId},
+    mode::{KeyCode::*, KeyEvent, KeyMod, key},
+    text::{Builder, Key, Point, Tag, Text},
+};
+
+/// A list of [`Text`] entries, with a selection
+///
+/// The entries can be laid out in more than one column, in which
+/// case they fill each row from left to right. Only `height` rows are
+/// shown at a time, and the [`Menu`] is scrolled in order to keep the
+/// selected entry visible.
+#[derive(Clone, Default)]
+pub struct Menu {
+    items: Vec<Text>,
+    selected: usize,
+    top_row: usize,
+    height: usize,
+    columns: usize,
+}
+
+impl Menu {
+    /// Returns a new [`Menu`] with the given entries
+    ///
+    /// By default, the [`Menu`] has one column and shows up to 10
+    /// rows.
+    pub fn new(items: impl IntoIterator<Item = Text>) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+            selected: 0,
+            top_row: 0,
+            height: 10,
+            columns: 1,
+        }
+    }
+
+    /// Sets how many rows of the [`Menu`] are shown at once
+    pub fn with_height(self, height: usize) -> Self {
+        let mut menu = Self { height: height.max(1), ..self };
+        menu.scroll_to_selected();
+        menu
+    }
+
+    /// Sets in how many columns the entries are laid out
+    pub fn with_columns(self, columns: usize) -> Self {
+        let mut menu = Self { columns: columns.max(1), ..self };
+        menu.scroll_to_selected();
+        menu
+    }
+
+    /// Replaces the entries, keeping the selection if possible
+    pub fn set_items(&mut self, items: impl IntoIterator<Item = Text>) {
+        self.items = items.into_iter().collect();
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+        self.scroll_to_selected();
+    }
+
+    /// The entries of the [`Menu`]
+    pub fn items(&self) -> &[Text] {
+        &self.items
+    }
+
+    /// The index of the selected entry, if there are any entries
+    pub fn selected(&self) -> Option<usize> {
+        (!self.items.is_empty()).then_some(self.selected)
+    }
+
+    /// The selected entry, if there are any entries
+    pub fn selected_item(&self) -> Option<&Text> {
+        self.items.get(self.selected)
+    }
+
+    /// The range of entries that are currently visible
+    pub fn visible(&self) -> Range<usize> {
+        let start = self.top_row * self.columns;
+        start..(start + self.height * self.columns).min(self.items.len())
+    }
+
+    ////////// Movement functions
+
+    /// Selects the entry at the given index
+    ///
+    /// The index is clamped to the last entry.
+    pub fn select(&mut self, i: usize) {
+        self.selected = i.min(self.items.len().saturating_sub(1));
+        self.scroll_to_selected();
+    }
+
+    /// Moves the selection by a number of entries, wrapping around
+    pub fn select_by(&mut self, by: i32) {
+        if self.items.is_empty() {
+            return;
+        }
+        let len = self.items.len() as i64;
+        let i = (self.selected as i64 + by as i64).rem_euclid(len);
+        self.select(i as usize);
+    }
+
+    /// Selects the next entry, wrapping around
+    pub fn select_next(&mut self) {
+        self.select_by(1);
+    }
+
+    /// Selects the previous entry, wrapping around
+    pub fn select_prev(&mut self) {
+        self.select_by(-1);
+    }
+
+    /// Moves the selection by a number of rows, without wrapping
+    pub fn select_by_rows(&mut self, by: i32) {
+        let i = self.selected as i64 + by as i64 * self.columns as i64;
+        let last = self.items.len().saturating_sub(1) as i64;
+        self.select(i.clamp(0, last) as usize);
+    }
+
+    /// Selects the first entry
+    pub fn select_first(&mut self) {
+        self.select(0);
+    }
+
+    /// Selects the last entry
+    pub fn select_last(&mut self) {
+        self.select(usize::MAX);
+    }
+
+    ////////// Input glue
+
+    /// Handles a [`KeyEvent`], returning what happened
+    ///
+    /// The default bindings are:
+    ///
+    /// - `<Down>`, `<Tab>` and `<C-n>`: Next entry;
+    /// - `<Up>`, `<S-Tab>` and `<C-p>`: Previous entry;
+    /// - `<Right>` and `<Left>`: Next and previous column;
+    /// - `<PageDown>` and `<PageUp>`: Scroll by a page;
+    /// - `<Home>` and `<End>`: First and last entries;
+    /// - `<Enter>`: Accept the selected entry;
+    /// - `<Esc>`: Cancel.
+    ///
+    /// Any other key is [`MenuEvent::Ignored`], so [`Mode`]s can
+    /// handle it however they see fit.
+    ///
+    /// [`Mode`]: crate::mode::Mode
+    pub fn send_key(&mut self, key: KeyEvent) -> MenuEvent {
+        let prev = self.selected;
+        let page = self.height as i32;
+
+        match key {
+            key!(Down | Tab) | key!(Char('n'), KeyMod::CONTROL) => {
+                if self.columns == 1 {
+                    self.select_next()
+                } else {
+                    self.select_by_rows(1)
+                }
+            }
+            key!(Up) | key!(BackTab, KeyMod::SHIFT) | key!(Char('p'), KeyMod::CONTROL) => {
+                if self.columns == 1 {
+                    self.select_prev()
+                } else {
+                    self.select_by_rows(-1)
+                }
+            }
+            key!(Right) if self.columns > 1 => self.select_by(1),
+            key!(Left) if self.columns > 1 => self.select_by(-1),
+            key!(PageDown) => self.select_by_rows(page),
+            key!(PageUp) => self.select_by_rows(-page),
+            key!(Home) => self.select_first(),
+            key!(End) => self.select_last(),
+            key!(Enter) => {
+                return match self.selected() {
+                    Some(i) => MenuEvent::Accepted(i),
+                    None => MenuEvent::Cancelled,
+                };
+            }
+            key!(Esc) => return MenuEvent::Cancelled,
+            _ => return MenuEvent::Ignored,
+        }
+
+        if self.selected == prev {
+            MenuEvent::Unchanged
+        } else {
+            MenuEvent::Moved
+        }
+    }
+
+    ////////// Text building
+
+    /// Builds a [`Text`] with the visible rows of the [`Menu`]
+    ///
+    /// Entries are printed with the `"Menu"` form, while the selected
+    /// one is printed with `"MenuSelected"`. When there are multiple
+    /// columns, they are padded to the width of the widest entry in
+    /// each column, measured in [`char`]s.
+    pub fn text(&self) -> Text {
+        let mut builder = Builder::new();
+        let mut ranges = Vec::new();
+        let mut byte = 0;
+        let widths = self.column_widths();
+
+        let Range { start, end } = self.visible();
+
+        for (i, item) in self.items[start..end].iter().enumerate() {
+            let (i, col) = (start + i, i % self.columns);
+
+            let id = match i == self.selected {
+                true => *SELECTED,
+                false => *ENTRY,
+            };
+            let entry_start = byte;
+            builder.push(item.clone());
+            byte += item.len().byte();
+
+            if col + 1 < self.columns && i + 1 < end {
+                let padding = widths[col] - width_of(item);
+                builder.push(" ".repeat(padding));
+                ranges.push((entry_start, byte + padding as u32, id));
+                builder.push(" ");
+                byte += padding as u32 + 1;
+            } else {
+                ranges.push((entry_start, byte, id));
+                builder.push("\n");
+                byte += 1;
+            }
+        }
+
+        // The forms are added afterwards, since pushing a Text to the
+        // Builder would get rid of them.
+        let mut text = builder.finish();
+        for (start, end, id) in ranges {
+            text.insert_tag(start, Tag::PushForm(id), *KEY);
+            text.insert_tag(end, Tag::PopForm(id), *KEY);
+        }
+
+        text
+    }
+
+    /// Scrolls the [`Menu`], so the selected entry is visible
+    fn scroll_to_selected(&mut self) {
+        let row = self.selected / self.columns;
+        if row < self.top_row {
+            self.top_row = row;
+        } else if row >= self.top_row + self.height {
+            self.top_row = row + 1 - self.height;
+        }
+    }
+
+    /// The width of each column, in [`char`]s
+    fn column_widths(&self) -> Vec<usize> {
+        let mut widths = vec![0; self.columns];
+        for (i, item) in self.items.iter().enumerate() {
+            let col = i % self.columns;
+            widths[col] = widths[col].max(width_of(item));
+        }
+        widths
+    }
+}
+
+/// What happened after a [`Menu`] received a [`KeyEvent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuEvent {
+    /// The selection was moved
+    Moved,
+    /// The key was a movement, but the selection didn't change
+    Unchanged,
+    /// The entry at the given index was accepted
+    Accepted(usize),
+    /// The [`Menu`] was cancelled
+    Cancelled,
+    /// The key isn't bound by the [`Menu`]
+    Ignored,
+}
+
+/// The width of the first line of a [`Text`], in [`char`]s
+fn width_of(text: &Text) -> usize {
+    text.iter_fwd(Point::default())
+        .filter_map(|item| item.part.as_char())
+        .take_while(|char| *char != '\n')
+        .count()
+}
+
+static KEY: LazyLock<Key> = LazyLock::new(Key::new);
+static ENTRY: LazyLock<FormId> = LazyLock::new(|| form::set_weak("Menu", "Default"));
+static SELECTED: LazyLock<FormId> =
+    LazyLock::new(|| form::set_weak("MenuSelected", "MainSelection"));
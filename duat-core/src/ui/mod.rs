@@ -1,27 +1,34 @@
+mod anchor;
 mod builder;
 mod layout;
+mod menu;
 
 use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
     fmt::Debug,
     path::PathBuf,
-    sync::{Arc, mpsc},
+    sync::{Arc, LazyLock, OnceLock, mpsc},
 };
 
 use crossterm::event::KeyEvent;
 use layout::iter_files_for_layout;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 
 pub use self::{
+    anchor::{CursorAnchor, FloatRect},
     builder::{FileBuilder, WindowBuilder},
     layout::{FileId, Layout, MasterOnLeft},
+    menu::{Menu, MenuEvent},
 };
 use crate::{
     DuatError,
     cache::load_cache,
     cfg::{IterCfg, PrintCfg},
     data::{RoData, RwData},
-    form::Painter,
+    form::{self, Painter},
+    mode::MouseEvent,
     text::{Item, Iter, Point, RevIter, Text},
     widgets::{File, Node, Widget},
 };
@@ -73,6 +80,14 @@ pub trait Ui: Sized + Send + Sync + 'static {
     /// adding or removing widgets, so the ui should calculate the
     /// layout.
     fn flush_layout(&mut self);
+
+    /// Sets the contents of the system clipboard
+    ///
+    /// This is called when yanking into a [register] that is
+    /// synchronized with the clipboard. By default, it does nothing.
+    ///
+    /// [register]: crate::registers
+    fn set_clipboard(_str: &str) {}
 }
 
 /// An [`Area`] that supports printing [`Text`]
@@ -84,7 +99,7 @@ pub trait Area: Send + Sync + Sized {
     type Ui: Ui<Area = Self>;
     type ConstraintChangeErr: std::error::Error + DuatError;
     type Cache: Default + Serialize + Deserialize<'static> + 'static;
-    type PrintInfo: Default + Clone + Send + Sync;
+    type PrintInfo: Default + Clone + PartialEq + Send + Sync;
 
     /// Returns the statics from `self`
     fn cache(&self) -> Option<Self::Cache>;
@@ -95,12 +110,33 @@ pub trait Area: Send + Sync + Sized {
     /// Gets the height of the area
     fn height(&self) -> u32;
 
+    /// The top left and bottom right corners of [`self`], as `(x, y)`
+    ///
+    /// The bottom right corner is exclusive.
+    fn bounds(&self) -> ((u32, u32), (u32, u32));
+
     /// Scrolls the [`Text`] (up or down) until the main cursor is
     /// within the [`ScrollOff`] range.
     ///
     /// [`ScrollOff`]: crate::cfg::ScrollOff
     fn scroll_around_point(&self, text: &Text, point: Point, cfg: PrintCfg);
 
+    /// Scrolls the [`Text`] vertically by a number of wrapped lines
+    ///
+    /// Negative values scroll up, positive values scroll down. This
+    /// scrolling is independent from the main cursor, so it will be
+    /// kept until the cursor moves out of the [`ScrollOff`] range.
+    ///
+    /// [`ScrollOff`]: crate::cfg::ScrollOff
+    fn scroll_ver(&self, text: &Text, by: i32, cfg: PrintCfg);
+
+    /// Scrolls the [`Text`] so that the wrapped line with `point`
+    /// is printed on the `row`th row of the [`Area`]
+    ///
+    /// If `row` is larger than the amount of lines before `point`,
+    /// the [`Text`] will be scrolled all the way to the top.
+    fn scroll_to_row(&self, text: &Text, point: Point, row: u32, cfg: PrintCfg);
+
     // Returns the [`Point`]s that would printed first.
     fn top_left(&self) -> (Point, Option<Point>);
 
@@ -136,6 +172,19 @@ pub trait Area: Send + Sync + Sized {
     /// Restores the original constraints of the widget
     fn restore_constraints(&self) -> Result<(), Self::ConstraintChangeErr>;
 
+    /// Hides [`self`], by shrinking it down to nothing
+    ///
+    /// [`self`] keeps its place in the layout, and its length is
+    /// restored by [`Area::reveal`]. Constraints changed while hidden
+    /// are only applied once [`self`] is revealed.
+    fn hide(&self) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// Reveals [`self`], if it was hidden by [`Area::hide`]
+    fn reveal(&self) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// Whether [`self`] was hidden by [`Area::hide`]
+    fn is_hidden(&self) -> bool;
+
     /// Requests that the width be enough to fit a certain piece of
     /// text.
     fn request_width_to_fit(&self, text: &str) -> Result<(), Self::ConstraintChangeErr>;
@@ -149,6 +198,32 @@ pub trait Area: Send + Sync + Sized {
     /// The last point that should be printed
     fn last_point(&self, text: &Text, cfg: PrintCfg) -> Point;
 
+    /// The [`Point`] printed on a given cell of the screen
+    ///
+    /// This is the inverse of printing: given the absolute `(x, y)`
+    /// coordinates of a cell, returns the [`Point`] of the [`Text`]
+    /// shown there, taking into account wrapping, tabs, horizontal
+    /// scrolling, padding and alignment. Cells on ghost text resolve
+    /// to the [`Point`] where the ghost text is placed, cells past
+    /// the end of a row resolve to its last [`Point`], and cells
+    /// below the end of the [`Text`] resolve to the last printed
+    /// [`Point`].
+    ///
+    /// Returns [`None`] if the cell is outside of the [`Area`]'s
+    /// printable region.
+    fn point_at_coord(&self, text: &Text, coord: (u32, u32), cfg: PrintCfg) -> Option<Point>;
+
+    /// The cell of the screen where a [`Point`] is printed
+    ///
+    /// This is the inverse of [`Area::point_at_coord`]: given a
+    /// [`Point`] of the [`Text`], returns the absolute `(x, y)`
+    /// coordinates of the cell where it would be printed, taking
+    /// into account wrapping, tabs, horizontal scrolling, padding and
+    /// alignment.
+    ///
+    /// Returns [`None`] if the [`Point`] is not visible.
+    fn coord_of_point(&self, text: &Text, point: Point, cfg: PrintCfg) -> Option<(u32, u32)>;
+
     /// Sets a previously acquired [`PrintInfo`] to the area
     ///
     /// [`PrintInfo`]: Area::PrintInfo
@@ -170,6 +245,9 @@ pub trait Area: Send + Sync + Sized {
     /// would eventually reach `other`.
     fn is_master_of(&self, other: &Self) -> bool;
 
+    /// Whether [`self`] was spawned with [`Area::spawn_floating`]
+    fn is_floating(&self) -> bool;
+
     /// Returns the clustered master of [`self`], if there is one
     ///
     /// If [`self`] belongs to a clustered group, return the most
@@ -177,6 +255,42 @@ pub trait Area: Send + Sync + Sized {
     /// members of the cluster.
     fn get_cluster_master(&self) -> Option<Self>;
 
+    /// Clusters [`self`] with `other`, so they move as one unit
+    ///
+    /// This should make the closest common ancestor of both
+    /// [`Area`]s their cluster master, such that moving or removing
+    /// it affects both of them. This can be used, for example, to
+    /// group a [`File`] with widgets that weren't pushed to it.
+    fn cluster_with(&self, other: &Self) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// Dissolves the cluster that [`self`] belongs to
+    ///
+    /// After this, every member of the cluster can be moved on its
+    /// own. Returns an error if [`self`] wasn't in a cluster.
+    fn uncluster(&self) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// Moves [`self`] to a [`Side`] of `target`
+    ///
+    /// This should move whole clusters: if [`self`] is in a cluster,
+    /// its cluster master is moved, and if `target` is in a cluster,
+    /// [`self`] is placed next to its cluster master. The members of
+    /// the moved cluster should keep their positions relative to each
+    /// other, so a [`File`]'s satellites are never left behind.
+    fn move_next_to(&self, target: &Self, side: Side) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// Swaps the positions of [`self`] and `other`
+    ///
+    /// Like with [`Area::move_next_to`], this should swap their
+    /// whole clusters.
+    fn swap(&self, other: &Self) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// The layout tree of the window that holds [`self`]
+    ///
+    /// The [`LayoutNode`]s should be returned in pre-order, that is,
+    /// every parent comes right before its children. This is meant
+    /// for introspection, in order to diagnose layout bugs.
+    fn layout_tree(&self) -> Vec<LayoutNode<Self>>;
+
     /// Returns a printing iterator
     ///
     /// Given an iterator of [`text::Item`]s, returns an iterator
@@ -219,6 +333,57 @@ pub trait Area: Send + Sync + Sized {
         cfg: IterCfg,
     ) -> impl Iterator<Item = (Caret, Item)> + Clone + 'a;
 
+    /// The visual rows that a range of the [`Text`] would occupy
+    ///
+    /// For each row, returns where it starts and ends, how many cells
+    /// of the [`Area`] it takes up, and how many are left before the
+    /// right edge. This is useful for [`Reader`]s that need to know
+    /// how the [`Text`] will be laid out, e.g. for placing right
+    /// aligned annotations at the end of a line.
+    ///
+    /// The returned rows include the whole first and last rows of
+    /// the range, even if it starts or ends in the middle of them.
+    ///
+    /// [`Reader`]: crate::text::Reader
+    fn visual_rows(
+        &self,
+        text: &Text,
+        (start, end): (Point, Point),
+        cfg: PrintCfg,
+    ) -> Vec<VisualRow>
+    where
+        Self: Sized,
+    {
+        let width = cfg.padding.inner_width(self.width());
+        let line_start = text.visual_line_start(start);
+        let mut rows: Vec<VisualRow> = Vec::new();
+
+        for (caret, item) in self.print_iter(text.iter_fwd(line_start), IterCfg::new(cfg)) {
+            if caret.wrap || rows.is_empty() {
+                if let Some(last) = rows.last_mut() {
+                    last.end = item.real;
+                }
+                if item.real > end || (item.real == end && end > start) {
+                    break;
+                }
+                rows.push(VisualRow {
+                    start: item.real,
+                    end: item.real,
+                    width: 0,
+                    remaining: width,
+                });
+            }
+
+            let row = rows.last_mut().unwrap();
+            row.end = row.end.max(item.real);
+            row.width = row.width.max(caret.x + caret.len);
+            row.remaining = width.saturating_sub(row.width);
+        }
+
+        rows.retain(|row| row.end > start || row.start >= start);
+        rows
+    }
+
     /// Bisects the [`Area`][Ui::Area] with the given index into
     /// two.
     ///
@@ -262,6 +427,46 @@ pub trait Area: Send + Sync + Sized {
         on_files: bool,
         cache: Self::Cache,
     ) -> (Self, Option<Self>);
+
+    /// Spawns a floating [`Area`], anchored to [`self`]
+    ///
+    /// The new [`Area`] is printed on top of every other [`Area`] in
+    /// the window, including the floating ones that were spawned
+    /// before it, until the z-order is changed with
+    /// [`Area::raise_floating`] or [`Area::lower_floating`]. It
+    /// follows [`self`] around as the layout changes.
+    fn spawn_floating(&self, specs: FloatSpecs, cache: Self::Cache) -> Self;
+
+    /// Changes the [`FloatSpecs`] of a floating [`Area`]
+    ///
+    /// Returns an error if [`self`] is not floating.
+    fn set_float_specs(&self, specs: FloatSpecs) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// The [`FloatSpecs`] of [`self`], if it is floating
+    fn float_specs(&self) -> Option<FloatSpecs>;
+
+    /// Places a floating [`Area`] above all other floating ones
+    ///
+    /// Returns an error if [`self`] is not floating.
+    fn raise_floating(&self) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// Places a floating [`Area`] below all other floating ones
+    ///
+    /// It is still printed above the tiled [`Area`]s. Returns an
+    /// error if [`self`] is not floating.
+    fn lower_floating(&self) -> Result<(), Self::ConstraintChangeErr>;
+
+    /// The position of [`self`] in the z-order of floating [`Area`]s
+    ///
+    /// Higher levels are printed on top of lower ones. Returns
+    /// [`None`] if [`self`] is not floating.
+    fn float_level(&self) -> Option<usize>;
+
+    /// Removes a floating [`Area`] from the screen
+    ///
+    /// After this, the [`Area`] should not be printed to anymore.
+    /// Returns an error if [`self`] is not floating.
+    fn remove_floating(&self) -> Result<(), Self::ConstraintChangeErr>;
 }
 
 /// A container for a master [`Area`] in Parsec
@@ -343,6 +548,75 @@ where
         (self.nodes.last().unwrap().clone(), parent)
     }
 
+    /// Pushes a floating [`Widget`], anchored to an existing one
+    ///
+    /// The [`Widget`] will be printed above all others, until it is
+    /// removed with [`Window::remove_floating`].
+    pub fn push_floating<W: Widget<U>>(
+        &mut self,
+        widget: W,
+        area: &U::Area,
+        checker: impl Fn() -> bool + 'static,
+        specs: FloatSpecs,
+    ) -> Node<U> {
+        let widget = RwData::<dyn Widget<U>>::new_unsized::<W>(Arc::new(RwLock::new(widget)));
+        let child = area.spawn_floating(specs, <U::Area as Area>::Cache::default());
+        if specs.shades() {
+            form::reprint_all();
+        }
+
+        self.nodes.push(Node::new::<W>(widget, child, checker));
+        self.nodes.last().unwrap().clone()
+    }
+
+    /// Removes a floating [`Widget`] from this [`Window`]
+    ///
+    /// Returns `false` if the [`Area`] wasn't floating.
+    pub fn remove_floating(&mut self, area: &U::Area) -> bool {
+        let shaded = area.float_specs().is_some_and(|specs| specs.shades());
+        if area.remove_floating().is_err() {
+            return false;
+        }
+        self.nodes.retain(|node| node.area() != area);
+        for related in self.nodes.iter().filter_map(Node::related_widgets) {
+            related.unregister(area);
+        }
+        if shaded {
+            form::reprint_all();
+        }
+        true
+    }
+
+    /// Places a floating [`Widget`] above all other floating ones
+    ///
+    /// Returns `false` if the [`Area`] wasn't floating.
+    pub fn raise_floating(&mut self, area: &U::Area) -> bool {
+        let raised = area.raise_floating().is_ok();
+        if raised && self.has_shading_float() {
+            form::reprint_all();
+        }
+        raised
+    }
+
+    /// Places a floating [`Widget`] below all other floating ones
+    ///
+    /// Returns `false` if the [`Area`] wasn't floating.
+    pub fn lower_floating(&mut self, area: &U::Area) -> bool {
+        let lowered = area.lower_floating().is_ok();
+        if lowered && self.has_shading_float() {
+            form::reprint_all();
+        }
+        lowered
+    }
+
+    /// Whether any floating [`Widget`] shades what is beneath it
+    fn has_shading_float(&self) -> bool {
+        self.nodes.iter().any(|node| {
+            let specs = node.area().float_specs();
+            specs.is_some_and(|specs| specs.shades())
+        })
+    }
+
     /// Pushes a [`File`] to the file's parent
     ///
     /// This function will push to the edge of `self.files_parent`
@@ -373,6 +647,43 @@ where
         self.nodes.iter()
     }
 
+    /// The [`Node`] that is shown on the `(x, y)` cell, if there is
+    /// one
+    ///
+    /// Floating [`Node`]s are checked first, from the top of the
+    /// z-order to the bottom, since they are printed on top of the
+    /// others. Nothing beneath a floating [`Node`] that
+    /// [shades][FloatSpecs::with_shade] is returned.
+    pub fn node_at(&self, (x, y): (u32, u32)) -> Option<&Node<U>> {
+        let contains = |node: &Node<U>| {
+            let ((tl_x, tl_y), (br_x, br_y)) = node.area().bounds();
+            (tl_x..br_x).contains(&x) && (tl_y..br_y).contains(&y)
+        };
+
+        let mut floating: Vec<_> = self
+            .nodes
+            .iter()
+            .filter_map(|node| Some((node.area().float_level()?, node)))
+            .collect();
+        floating.sort_unstable_by_key(|(level, _)| std::cmp::Reverse(*level));
+
+        for (_, node) in floating {
+            if contains(node) {
+                return Some(node);
+            } else if node
+                .area()
+                .float_specs()
+                .is_some_and(|specs| specs.shades())
+            {
+                return None;
+            }
+        }
+
+        self.nodes
+            .iter()
+            .find(|node| !node.area().is_floating() && contains(node))
+    }
+
     /// Returns an [`Iterator`] over the names of [`File`]s
     /// and their respective [`Widget`] indices.
     ///
@@ -390,7 +701,7 @@ where
 }
 
 /// A dimension on screen, can either be horizontal or vertical
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Axis {
     Horizontal,
     Vertical,
@@ -417,11 +728,22 @@ impl From<PushSpecs> for Axis {
 
 pub enum Event {
     Key(KeyEvent),
+    Mouse(MouseEvent),
+    Paste(String),
     Resize,
     FormChange,
     ReloadConfig,
     OpenFile(PathBuf),
+    /// Restores a session saved with the `session save` command
+    LoadSession(String),
     Quit,
+    /// An event of any other type, sent by a backend or plugin
+    ///
+    /// It is handled by the functions added with
+    /// [`ui::add_event_handler`] for its type.
+    ///
+    /// [`ui::add_event_handler`]: add_event_handler
+    Custom(Box<dyn Any + Send>),
 }
 
 pub struct Sender(mpsc::Sender<Event>);
@@ -435,6 +757,14 @@ impl Sender {
         self.0.send(Event::Key(key))
     }
 
+    pub fn send_mouse(&self, event: MouseEvent) -> Result<(), mpsc::SendError<Event>> {
+        self.0.send(Event::Mouse(event))
+    }
+
+    pub fn send_paste(&self, text: String) -> Result<(), mpsc::SendError<Event>> {
+        self.0.send(Event::Paste(text))
+    }
+
     pub fn send_resize(&self) -> Result<(), mpsc::SendError<Event>> {
         self.0.send(Event::Resize)
     }
@@ -443,11 +773,112 @@ impl Sender {
         self.0.send(Event::ReloadConfig)
     }
 
+    /// Sends an event of any type, to be handled on the main loop
+    ///
+    /// This is the same as [`ui::send_event`], but from a [`Sender`].
+    ///
+    /// [`ui::send_event`]: send_event
+    pub fn send_custom<E: Any + Send>(&self, event: E) -> Result<(), mpsc::SendError<Event>> {
+        self.0.send(Event::Custom(Box::new(event)))
+    }
+
     pub(crate) fn send_form_changed(&self) -> Result<(), mpsc::SendError<Event>> {
         self.0.send(Event::FormChange)
     }
 }
 
+static SENDER: OnceLock<Sender> = OnceLock::new();
+static EVENT_HANDLERS: LazyLock<Mutex<HashMap<TypeId, Vec<EventHandler>>>> =
+    LazyLock::new(Mutex::default);
+
+type EventHandler = Box<dyn FnMut(&dyn Any) + Send>;
+
+/// Sends an event of any type, to be handled on the main loop
+///
+/// This lets backends and plugins send their own events (like
+/// gestures, notifications from file watchers, or commands from
+/// other processes) through the same loop as keys and mouse events,
+/// so they are handled in the order that they came in. The event is
+/// handled by every function added through [`ui::add_event_handler`]
+/// for its type, and is ignored if there are none.
+///
+/// Returns `false` if Duat hasn't started yet or has already quit,
+/// in which case the event is dropped.
+///
+/// [`ui::add_event_handler`]: add_event_handler
+pub fn send_event<E: Any + Send>(event: E) -> bool {
+    SENDER
+        .get()
+        .is_some_and(|sender| sender.send_custom(event).is_ok())
+}
+
+/// Adds a function to handle events of type `E`
+///
+/// The function is called on the main loop for every event of type
+/// `E` that is sent through [`ui::send_event`] or
+/// [`Sender::send_custom`]. Multiple functions can handle the same
+/// type of event, and are called in the order they were added. They
+/// are removed when the config is reloaded.
+///
+/// ```rust
+/// use duat_core::{context, text::text, ui};
+///
+/// struct FileChanged(String);
+///
+/// ui::add_event_handler(|FileChanged(path): &FileChanged| {
+///     context::notify(text!([*a] path [] " changed on disk"));
+/// });
+///
+/// // From any other thread:
+/// ui::send_event(FileChanged("src/main.rs".to_string()));
+/// ```
+///
+/// [`ui::send_event`]: send_event
+pub fn add_event_handler<E: Any + Send>(mut f: impl FnMut(&E) + Send + 'static) {
+    let handler: EventHandler = Box::new(move |event| {
+        if let Some(event) = event.downcast_ref::<E>() {
+            f(event)
+        }
+    });
+    EVENT_HANDLERS
+        .lock()
+        .entry(TypeId::of::<E>())
+        .or_default()
+        .push(handler);
+}
+
+/// Handles an [`Event::Custom`], calling every handler of its type
+pub(crate) fn handle_event(event: Box<dyn Any + Send>) {
+    let type_id = (*event).type_id();
+    // The handlers are taken out, so they can add new handlers.
+    let Some(mut handlers) = EVENT_HANDLERS.lock().remove(&type_id) else {
+        return;
+    };
+
+    for handler in handlers.iter_mut() {
+        handler(&*event);
+    }
+
+    let mut event_handlers = EVENT_HANDLERS.lock();
+    let added = event_handlers.remove(&type_id).unwrap_or_default();
+    handlers.extend(added);
+    event_handlers.insert(type_id, handlers);
+}
+
+/// Sets the [`Sender`] for [`ui::send_event`]
+///
+/// [`ui::send_event`]: send_event
+pub(crate) fn set_sender(sender: Sender) {
+    SENDER
+        .set(sender)
+        .unwrap_or_else(|_| panic!("Sender set more than once"));
+}
+
+/// Removes every event handler, when quitting or reloading
+pub(crate) fn clear_event_handlers() {
+    EVENT_HANDLERS.lock().clear();
+}
+
 pub struct RoWindow<'a, U>(&'a Window<U>)
 where
     U: Ui;
@@ -542,6 +973,7 @@ pub struct PushSpecs {
     side: Side,
     ver_con: Option<Constraint>,
     hor_con: Option<Constraint>,
+    is_framed: bool,
 }
 
 impl PushSpecs {
@@ -551,6 +983,7 @@ impl PushSpecs {
             side: Side::Left,
             ver_con: None,
             hor_con: None,
+            is_framed: true,
         }
     }
 
@@ -560,6 +993,7 @@ impl PushSpecs {
             side: Side::Right,
             ver_con: None,
             hor_con: None,
+            is_framed: true,
         }
     }
 
@@ -569,6 +1003,7 @@ impl PushSpecs {
             side: Side::Above,
             ver_con: None,
             hor_con: None,
+            is_framed: true,
         }
     }
 
@@ -578,6 +1013,7 @@ impl PushSpecs {
             side: Side::Below,
             ver_con: None,
             hor_con: None,
+            is_framed: true,
         }
     }
 
@@ -657,6 +1093,15 @@ impl PushSpecs {
         }
     }
 
+    /// Don't frame the edges between the [`Widget`] and its
+    /// neighbours
+    ///
+    /// This is only a hint, and it's up to the [`Ui`] to decide what
+    /// a frame is, if it has any.
+    pub fn without_frame(self) -> Self {
+        Self { is_framed: false, ..self }
+    }
+
     pub fn axis(&self) -> Axis {
         match self.side {
             Side::Above | Side::Below => Axis::Vertical,
@@ -683,6 +1128,11 @@ impl PushSpecs {
         }
     }
 
+    /// Whether the edges around the [`Widget`] should be framed
+    pub fn is_framed(&self) -> bool {
+        self.is_framed
+    }
+
     pub fn is_resizable_on(&self, axis: Axis) -> bool {
         let con = match axis {
             Axis::Horizontal => self.hor_con,
@@ -692,7 +1142,111 @@ impl PushSpecs {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Information on how a floating [`Widget`] should be placed
+///
+/// A floating [`Area`] is placed on top of the regular layout, and is
+/// anchored to another [`Area`], called its parent. The [`Anchor`]
+/// decides which corner of the floating [`Area`] is tied to the same
+/// corner of the parent, and the offset moves it from there:
+///
+/// ```rust
+/// use duat_core::ui::{Anchor, FloatSpecs};
+/// // 20 by 5 cells, 3 cells to the right and 2 below the top left
+/// // corner of the parent.
+/// let specs = FloatSpecs::new(Anchor::TopLeft, 20, 5).with_offset(3, 2);
+/// ```
+///
+/// If the floating [`Area`] wouldn't fit on the screen, it is moved
+/// back in, and shrunk only if that isn't enough.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatSpecs {
+    anchor: Anchor,
+    offset: (i32, i32),
+    width: u32,
+    height: u32,
+    shades: bool,
+}
+
+impl FloatSpecs {
+    /// Returns new [`FloatSpecs`], with a given size
+    pub fn new(anchor: Anchor, width: u32, height: u32) -> Self {
+        Self {
+            anchor,
+            offset: (0, 0),
+            width,
+            height,
+            shades: false,
+        }
+    }
+
+    /// Moves the floating [`Area`] away from its [`Anchor`]
+    ///
+    /// Positive values move it right and down.
+    pub fn with_offset(self, x: i32, y: i32) -> Self {
+        Self { offset: (x, y), ..self }
+    }
+
+    /// Changes the size of the floating [`Area`]
+    pub fn with_size(self, width: u32, height: u32) -> Self {
+        Self { width, height, ..self }
+    }
+
+    /// Dims everything beneath the floating [`Area`]
+    ///
+    /// This includes the tiled [`Area`]s, as well as the floating
+    /// ones that are lower in the z-order. It also stops the mouse
+    /// from reaching them, which is useful for things like prompts,
+    /// that should be dealt with before going back to editing.
+    pub fn with_shade(self) -> Self {
+        Self { shades: true, ..self }
+    }
+
+    /// The [`Anchor`] of the floating [`Area`]
+    pub fn anchor(&self) -> Anchor {
+        self.anchor
+    }
+
+    /// The offset from the [`Anchor`], as `(x, y)`
+    pub fn offset(&self) -> (i32, i32) {
+        self.offset
+    }
+
+    /// The requested size, as `(width, height)`
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Whether everything beneath the floating [`Area`] is dimmed
+    pub fn shades(&self) -> bool {
+        self.shades
+    }
+}
+
+/// A corner that a floating [`Area`] is anchored to
+///
+/// This corner of the floating [`Area`] is placed on the same corner
+/// of its parent, plus the offset in the [`FloatSpecs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Whether this [`Anchor`] is on the left side
+    pub fn is_left(&self) -> bool {
+        matches!(self, Anchor::TopLeft | Anchor::BottomLeft)
+    }
+
+    /// Whether this [`Anchor`] is on the top side
+    pub fn is_top(&self) -> bool {
+        matches!(self, Anchor::TopLeft | Anchor::TopRight)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Constraint {
     Ratio(u16, u16),
     Length(f32),
@@ -710,6 +1264,44 @@ pub enum Side {
     Left,
 }
 
+/// A row of [`Text`], as laid out in an [`Area`]
+///
+/// Returned by [`Area::visual_rows`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VisualRow {
+    /// The first [`Point`] printed in this row
+    pub start: Point,
+    /// The first [`Point`] of the next row
+    pub end: Point,
+    /// How many cells are taken up by this row
+    pub width: u32,
+    /// How many cells are left before the right edge of the [`Area`]
+    pub remaining: u32,
+}
+
+/// A node in the layout tree of a window
+///
+/// Returned by [`Area::layout_tree`].
+#[derive(Debug, Clone)]
+pub struct LayoutNode<A> {
+    /// The [`Area`] of this node
+    pub area: A,
+    /// How deep in the tree this node is, `0` being the root
+    pub depth: usize,
+    /// The top left corner, as `(x, y)`
+    pub tl: (u32, u32),
+    /// The bottom right corner, as `(x, y)`
+    pub br: (u32, u32),
+    /// The [`Axis`] of the children, if this node has any
+    pub axis: Option<Axis>,
+    /// The horizontal [`Constraint`] of this node
+    pub hor_con: Option<Constraint>,
+    /// The vertical [`Constraint`] of this node
+    pub ver_con: Option<Constraint>,
+    /// Whether or not the children of this node are clustered
+    pub is_clustered: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Caret {
     pub x: u32,
@@ -0,0 +1,131 @@
+//! Watching open [`File`]s for changes made by other programs
+//!
+//! When the `watch-files` option is on, every [`File`] opened from
+//! then on is watched. Whenever one of them is modified by another
+//! program, the [`OnFileChangedExternally`] hook is triggered, and
+//! the user is asked to pick between the two versions, with the
+//! `file-changed` command:
+//!
+//! ```text
+//! :file-changed reload
+//! :file-changed keep
+//! ```
+//!
+//! The first one replaces the contents of the [`File`] with the ones
+//! on disk (which can be undone), while the second one keeps them as
+//! they are. Until one of them is picked, writing the [`File`] fails,
+//! so the changes on disk are never silently overwritten.
+//!
+//! [`File`]: crate::widgets::File
+//! [`OnFileChangedExternally`]: crate::hooks::OnFileChangedExternally
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+
+use crate::{
+    context,
+    hooks::{self, OnFileChangedExternally},
+    opts::{self, Scope},
+    text::err,
+    ui::{Ui, Window},
+    widgets::File,
+};
+
+static WATCHER: LazyLock<Mutex<Option<RecommendedWatcher>>> = LazyLock::new(Mutex::default);
+static WATCHED: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(Mutex::default);
+static CHANGED: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(Mutex::default);
+
+/// Declares the `watch-files` option
+pub(crate) fn declare_opts() {
+    opts::declare_bool("watch-files", false, Scope::Global);
+}
+
+/// Starts watching a path, if the `watch-files` option is on
+///
+/// The parent directory is watched instead of the path itself, so
+/// that programs that write by replacing the file are also caught.
+pub(crate) fn watch(path: &Path) {
+    if !opts::get_bool("watch-files").unwrap_or(false) {
+        return;
+    }
+    let Some(dir) = path.parent() else {
+        return;
+    };
+
+    let mut watched = WATCHED.lock();
+    let dir_is_watched = watched.iter().any(|path| path.parent() == Some(dir));
+    if !watched.insert(path.to_path_buf()) || dir_is_watched {
+        return;
+    }
+
+    let mut watcher = WATCHER.lock();
+    if watcher.is_none() {
+        *watcher = notify::recommended_watcher(|res| {
+            if let Ok(Event {
+                kind: EventKind::Modify(_) | EventKind::Create(_),
+                paths,
+                ..
+            }) = res
+            {
+                let watched = WATCHED.lock();
+                let paths = paths.into_iter().filter(|path| watched.contains(path));
+                CHANGED.lock().extend(paths);
+            }
+        })
+        .ok();
+    }
+
+    if let Some(watcher) = watcher.as_mut()
+        && let Err(error) = watcher.watch(dir, RecursiveMode::NonRecursive)
+    {
+        context::notify(
+            err!("Couldn't watch " [*a] { path.to_string_lossy() } [] ": " { error.to_string() }),
+        );
+    }
+}
+
+/// Checks the [`File`]s that changed since the last call
+///
+/// Changes are only reported if the modification time on disk is
+/// not the one that the [`File`] knows about, so the [`File`]'s own
+/// writes are ignored.
+pub(crate) fn update<U: Ui>() {
+    let changed = std::mem::take(&mut *CHANGED.lock());
+    if changed.is_empty() {
+        return;
+    }
+
+    let files: Vec<_> = context::windows::<U>()
+        .read()
+        .iter()
+        .flat_map(Window::nodes)
+        .filter_map(|node| node.try_downcast::<File>())
+        .filter(|file| changed.contains(&PathBuf::from(file.read().path())))
+        .collect();
+
+    for file in files {
+        let name = {
+            let mut file = file.write();
+            let modified = fs::metadata(file.path()).and_then(|metadata| metadata.modified());
+            if modified.ok() == file.info().modified() {
+                continue;
+            }
+
+            file.set_changed_on_disk(true);
+            file.refresh_info();
+            file.name()
+        };
+
+        hooks::trigger::<OnFileChangedExternally>(file);
+        context::notify(err!(
+            [*a] name [] " was changed on disk, use " [*a] "file-changed reload" []
+            " or " [*a] "file-changed keep"
+        ));
+    }
+}
@@ -13,6 +13,7 @@ use std::{
     any::TypeId,
     marker::PhantomData,
     sync::{Arc, LazyLock},
+    time::Instant,
 };
 
 use parking_lot::RwLock;
@@ -23,9 +24,9 @@ use crate::{
     cmd,
     data::{RoData, RwData, context},
     form::{self, Form},
-    hooks,
+    hooks::{self, FocusedOn, UnfocusedFrom},
     mode::{self, Command, Cursors, IncSearcher},
-    text::{Ghost, Key, Searcher, Tag, Text, text},
+    text::{Ghost, Key, SearchFlags, Searcher, Tag, Text, text},
     ui::{PushSpecs, Ui},
     widgets::{Widget, WidgetCfg},
 };
@@ -33,6 +34,7 @@ use crate::{
 pub struct CmdLineCfg<U> {
     prompt: String,
     specs: PushSpecs,
+    on_unfocus: OnUnfocus,
     ghost: PhantomData<U>,
 }
 
@@ -41,6 +43,7 @@ impl<U> CmdLineCfg<U> {
         CmdLineCfg {
             prompt: String::from(":"),
             specs: PushSpecs::below().with_ver_len(1.0),
+            on_unfocus: OnUnfocus::Clear,
             ghost: PhantomData,
         }
     }
@@ -70,6 +73,34 @@ impl<U: Ui> CmdLineCfg<U> {
             ..self
         }
     }
+
+    /// What to do with unconfirmed text when the [`CmdLine`] is
+    /// unfocused
+    ///
+    /// By default, this is [`OnUnfocus::Clear`].
+    pub fn on_unfocus(self, on_unfocus: OnUnfocus) -> Self {
+        Self { on_unfocus, ..self }
+    }
+}
+
+/// What the [`CmdLine`] does with its text when unfocused
+///
+/// This only applies when focus moves away without the text being
+/// confirmed with `<Enter>`, like when clicking on another widget,
+/// or when a plugin switches to another one. Confirmed text is
+/// always handed to the [`CmdLineMode`], which will, for example,
+/// run it as a command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnUnfocus {
+    /// Discards the text, as if `<Esc>` had been pressed
+    #[default]
+    Clear,
+    /// Hides the text, restoring it when the [`CmdLine`] is focused
+    /// again
+    Keep,
+    /// Hands the text to the [`CmdLineMode`], as if `<Enter>` had
+    /// been pressed
+    Execute,
 }
 
 impl<U: Ui> WidgetCfg<U> for CmdLineCfg<U> {
@@ -90,6 +121,9 @@ impl<U: Ui> WidgetCfg<U> for CmdLineCfg<U> {
             text: Text::new(),
             prompt: RwData::new(self.prompt.clone()),
             mode: RwData::new(mode),
+            on_unfocus: self.on_unfocus,
+            confirmed: false,
+            kept: None,
         };
 
         let checker = {
@@ -109,7 +143,8 @@ impl<U: Ui> WidgetCfg<U> for CmdLineCfg<U> {
 ///
 /// * [`RunCommands`], which runs commands (duh);
 /// * [`ShowNotifications`], which shows notifications, usually about
-///   commands;
+///   commands, as well as transient messages sent via
+///   [`context::echo`];
 /// * [`IncSearch<Inc>`], which will perform an incremental search,
 ///   based on [`Inc`].
 ///
@@ -119,6 +154,9 @@ impl<U: Ui> WidgetCfg<U> for CmdLineCfg<U> {
 /// this functionality, or want notifications somewhere else, you can
 /// use [`hooks::remove`].
 ///
+/// If it is unfocused before its text is confirmed, what happens to
+/// said text is determined by [`OnUnfocus`].
+///
 /// [modes]: CmdLineMode
 /// [`Inc`]: IncSearcher
 /// [hook]: crate::hooks
@@ -126,13 +164,38 @@ pub struct CmdLine<U: Ui> {
     text: Text,
     prompt: RwData<String>,
     mode: RwData<RwData<dyn CmdLineMode<U>>>,
+    on_unfocus: OnUnfocus,
+    confirmed: bool,
+    kept: Option<String>,
 }
 
 impl<U: Ui> CmdLine<U> {
+    /// Confirms the current text, handing it to the [`CmdLineMode`]
+    /// once the [`CmdLine`] is unfocused
+    pub(crate) fn confirm(&mut self) {
+        self.confirmed = true;
+    }
+
     pub(crate) fn set_mode<M: CmdLineMode<U>>(&mut self, mode: M) {
         run_once::<M, U>();
         *self.mode.write() = RwData::new_unsized::<M>(Arc::new(RwLock::new(mode)));
     }
+
+    /// The completions of the [`Text`] before `byte`, if the
+    /// [`CmdLineMode`] has any
+    ///
+    /// Also returns how many characters before `byte` are part of
+    /// the word being completed.
+    pub(crate) fn completions(&self, byte: u32) -> Option<(usize, Vec<String>)> {
+        let text = self.text.to_string();
+        let before = &text[..(byte as usize).min(text.len())];
+        let (start, completions) = self.mode.read().write().completions(before)?;
+
+        Some((
+            before[start.min(before.len())..].chars().count(),
+            completions,
+        ))
+    }
 }
 
 impl<U: Ui> Widget<U> for CmdLine<U> {
@@ -168,14 +231,45 @@ impl<U: Ui> Widget<U> for CmdLine<U> {
             Ok(None)
         })
         .unwrap();
+
+        cmd::add_completer_for::<CmdLine<U>, U>("set-prompt", |command_line, args, _| match args {
+            [] => vec![command_line.prompt.read().clone()],
+            _ => Vec::new(),
+        })
+        .unwrap();
+
+        // The Text is replaced on focus and unfocus, so the Cursors have
+        // to be placed again, at the end of any restored text.
+        hooks::add::<FocusedOn<CmdLine<U>, U>>(|(cmd_line, area, cursors)| {
+            let cmd_line = cmd_line.read();
+            let mut cursors = cursors.write();
+            cursors.clear();
+            if let Some(last) = cmd_line.text.last_point() {
+                let cfg = cmd_line.print_cfg();
+                cursors.insert_from_parts(0, last, 0, &cmd_line.text, area, cfg);
+            }
+        });
+        hooks::add::<UnfocusedFrom<CmdLine<U>, U>>(|(_, _, cursors)| cursors.write().clear());
     }
 
     fn on_focus(&mut self, _area: &U::Area) {
-        self.text = text!({ Ghost(text!({ &self.prompt })) } '\n');
+        let kept = self.kept.take().unwrap_or_default();
+        self.text = text!({ Ghost(text!({ &self.prompt })) } kept '\n');
         self.mode.read().write().on_focus(&mut self.text);
     }
 
     fn on_unfocus(&mut self, _area: &<U as Ui>::Area) {
+        if !std::mem::take(&mut self.confirmed) {
+            match self.on_unfocus {
+                OnUnfocus::Clear => self.text = Text::new(),
+                OnUnfocus::Keep => {
+                    let kept = std::mem::take(&mut self.text).to_string();
+                    self.kept = Some(kept).filter(|kept| !kept.is_empty());
+                }
+                OnUnfocus::Execute => {}
+            }
+        }
+
         self.mode.read().write().on_unfocus(&mut self.text);
     }
 }
@@ -195,6 +289,15 @@ pub trait CmdLineMode<U: Ui>: Send + Sync + 'static {
         false
     }
 
+    /// Completions for the text before the cursor
+    ///
+    /// Returns the byte where the completed word starts, alongside
+    /// the completions for it, or [`None`] if this mode doesn't
+    /// complete anything.
+    fn completions(&mut self, _text: &str) -> Option<(usize, Vec<String>)> {
+        None
+    }
+
     fn once()
     where
         Self: Sized,
@@ -209,7 +312,7 @@ pub struct RunCommands<U> {
 
 impl<U: Ui> RunCommands<U> {
     pub fn new() -> Self {
-        mode::set::<U>(Command);
+        mode::set::<U>(Command::new());
         Self { key: Key::new(), ghost: PhantomData }
     }
 }
@@ -237,6 +340,10 @@ impl<U: Ui> CmdLineMode<U> for RunCommands<U> {
         }
     }
 
+    fn completions(&mut self, text: &str) -> Option<(usize, Vec<String>)> {
+        Some(cmd::completions(text))
+    }
+
     fn on_unfocus(&mut self, text: &mut Text) {
         let text = std::mem::take(text);
 
@@ -260,7 +367,9 @@ impl<U: Ui> Default for RunCommands<U> {
 
 pub struct ShowNotifications<U> {
     notifications: &'static RwData<Text>,
+    echoed: &'static RwData<Option<(Text, Instant)>>,
     has_changed: bool,
+    showing_echo: bool,
     ghost: PhantomData<U>,
 }
 
@@ -268,7 +377,9 @@ impl<U: Ui> ShowNotifications<U> {
     pub fn new() -> Self {
         Self {
             notifications: context::notifications(),
+            echoed: context::echoed(),
             has_changed: false,
+            showing_echo: false,
             ghost: PhantomData,
         }
     }
@@ -281,14 +392,29 @@ impl<U: Ui> CmdLineMode<U> for ShowNotifications<U> {
 
     fn has_changed(&mut self) -> bool {
         self.has_changed = self.notifications.has_changed();
-        self.has_changed
+        let echo_changed = self.echoed.has_changed();
+        let echo_expired = self.showing_echo
+            && self
+                .echoed
+                .raw_read()
+                .as_ref()
+                .is_none_or(|(_, expiry)| Instant::now() >= *expiry);
+
+        self.has_changed || echo_changed || echo_expired
     }
 
     fn update(&mut self, text: &mut Text) {
         if self.has_changed {
             self.has_changed = false;
+            self.showing_echo = false;
             *text = self.notifications.read().clone();
+        } else if let Some((echo, expiry)) = self.echoed.read().as_ref()
+            && Instant::now() < *expiry
+        {
+            self.showing_echo = true;
+            *text = echo.clone();
         } else {
+            self.showing_echo = false;
             *text = Text::new();
         }
     }
@@ -300,6 +426,12 @@ impl<U: Ui> Default for ShowNotifications<U> {
     }
 }
 
+/// A [`CmdLineMode`] for incremental searching
+///
+/// The query is a regex, unless it starts with [`SearchFlags`], like
+/// `-w` for whole words, `-i` for case insensitive, `-S` for smart
+/// case, `-a` for accent insensitive or `-f` for fuzzy matching, in
+/// which case it is matched without a regex.
 pub struct IncSearch<I: IncSearcher<U>, U: Ui> {
     fn_or_inc: FnOrInc<I, U>,
     key: Key,
@@ -308,7 +440,7 @@ pub struct IncSearch<I: IncSearcher<U>, U: Ui> {
 
 impl<I: IncSearcher<U>, U: Ui> IncSearch<I, U> {
     pub fn new(f: impl IncFn<I, U> + Send + Sync + 'static) -> Self {
-        mode::set::<U>(Command);
+        mode::set::<U>(Command::new());
 
         Self {
             fn_or_inc: FnOrInc::Fn(Some(Box::new(f))),
@@ -332,7 +464,10 @@ impl<I: IncSearcher<U>, U: Ui> CmdLineMode<U> for IncSearch<I, U> {
 
         let cur_file = context::cur_file::<U>().unwrap();
 
-        match Searcher::new(text.to_string()) {
+        let query = text.to_string();
+        let (flags, start) = SearchFlags::parse(&query);
+
+        match Searcher::with_flags(query[start..].to_string(), flags) {
             Ok(searcher) => {
                 cur_file.mutate_data(|file, area, cursors| {
                     let mut c = cursors.write();
@@ -347,8 +482,9 @@ impl<I: IncSearcher<U>, U: Ui> CmdLineMode<U> for IncSearch<I, U> {
                 let span = err.span();
                 let id = crate::form::id_of!("ParseCommandErr");
 
-                text.insert_tag(span.start.offset as u32, Tag::PushForm(id), self.key);
-                text.insert_tag(span.end.offset as u32, Tag::PopForm(id), self.key);
+                let (start, end) = (start + span.start.offset, start + span.end.offset);
+                text.insert_tag(start as u32, Tag::PushForm(id), self.key);
+                text.insert_tag(end as u32, Tag::PopForm(id), self.key);
             }
         }
     }
@@ -0,0 +1,516 @@
+//! Marks for the lines of a [`File`] that changed
+//!
+//! This widget compares the [`File`] with a base version of it, and
+//! marks the lines that were added, changed, or that had lines
+//! deleted below them. By default, the base version is the one saved
+//! on disk, but it can also be the one in the last git commit:
+//!
+//! ```rust
+//! # use duat_core::{hooks::{self, OnFileOpen}, ui::Ui, widgets::{DiffGutter, Widget}};
+//! # fn test<U: Ui>() {
+//! hooks::add::<OnFileOpen<U>>(|builder| {
+//!     builder.push(DiffGutter::cfg().against_git_head());
+//! });
+//! # }
+//! ```
+//!
+//! The marks are updated as the [`File`] is edited, and only the
+//! lines around each edit are compared again. The base version is
+//! read again, in a separate thread, whenever the [`File`] is
+//! written.
+//!
+//! [`File`]: super::File
+use std::{
+    fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+    time::SystemTime,
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    context::{self, FileReader},
+    form::{self, Form},
+    text::{
+        Text,
+        diff::{self, Hunk},
+        text,
+    },
+    ui::{Area, PushSpecs, Ui},
+    widgets::{File, Widget, WidgetCfg, cache_key},
+};
+
+pub struct DiffGutter<U: Ui> {
+    reader: FileReader<U>,
+    text: Text,
+    cfg: DiffGutterCfg<U>,
+    modified: Option<SystemTime>,
+    fetched: Fetched,
+    base: Option<Vec<String>>,
+    base_gen: usize,
+    diffed: Diffed,
+}
+
+impl<U: Ui> DiffGutter<U> {
+    /// Diffs the lines that changed in the [`File`], or all of them,
+    /// if its base version changed
+    fn update_marks(&mut self) {
+        let fetched = self.fetched.lock().take();
+        let Self {
+            reader,
+            cfg,
+            modified,
+            fetched: fetching,
+            base,
+            base_gen,
+            diffed,
+            ..
+        } = self;
+
+        reader.inspect(|file, _, _| {
+            if file.info().modified() != *modified {
+                *modified = file.info().modified();
+                fetch_base(file, cfg.base, fetching);
+            }
+
+            if let Some(fetched) = fetched {
+                *base = fetched.map(|base| base.split_inclusive('\n').map(String::from).collect());
+                *base_gen += 1;
+                *diffed = match base {
+                    Some(base) => Diffed::new(base, file.text()),
+                    None => Diffed::default(),
+                };
+            } else if let Some(base) = base {
+                diffed.update(base, file.text());
+            }
+        });
+    }
+
+    fn update_text(&mut self) {
+        self.text = self.reader.inspect(|file, _, _| {
+            let mut builder = Text::builder();
+
+            for (line, _) in file.printed_lines() {
+                match mark_of(&self.diffed.hunks, *line as usize) {
+                    Some(Mark::Added) => text!(builder, [DiffAdded] { self.cfg.added }),
+                    Some(Mark::Changed) => text!(builder, [DiffChanged] { self.cfg.changed }),
+                    Some(Mark::Deleted) => text!(builder, [DiffDeleted] { self.cfg.deleted }),
+                    None => {}
+                }
+                text!(builder, [] "\n");
+            }
+
+            builder.finish()
+        });
+    }
+}
+
+impl<U: Ui> Widget<U> for DiffGutter<U> {
+    type Cfg = DiffGutterCfg<U>;
+
+    fn cfg() -> Self::Cfg {
+        DiffGutterCfg::new()
+    }
+
+    fn update(&mut self, _area: &U::Area) {
+        self.update_marks();
+        self.update_text();
+    }
+
+    fn cache_key(&self, area: &U::Area) -> Option<u64> {
+        Some(self.reader.inspect(|file, _, _| {
+            cache_key((
+                file.printed_lines(),
+                file.text().revision(),
+                file.info().modified(),
+                self.base_gen,
+                area.height(),
+            ))
+        }))
+    }
+
+    fn text(&self) -> &Text {
+        &self.text
+    }
+
+    fn text_mut(&mut self) -> &mut Text {
+        &mut self.text
+    }
+
+    fn once() {
+        form::set_weak("DiffAdded", Form::green());
+        form::set_weak("DiffChanged", Form::yellow());
+        form::set_weak("DiffDeleted", Form::red());
+    }
+}
+
+/// What the lines of the [`File`] are compared against
+#[derive(Debug, Clone, Copy)]
+enum Base {
+    /// The contents of the [`File`] on disk
+    Saved,
+    /// The contents of the [`File`] in the last git commit
+    GitHead,
+}
+
+/// How a line differs from the base version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Added,
+    Changed,
+    Deleted,
+}
+
+/// The base version of a [`File`], once it is read in another thread
+///
+/// The outer [`Option`] is [`Some`] if a new base version arrived,
+/// which may be [`None`] if it couldn't be read.
+type Fetched = Arc<Mutex<Option<Option<String>>>>;
+
+/// The [`Hunk`]s between the base version and the [`File`], as of
+/// its last diffing
+#[derive(Default)]
+struct Diffed {
+    /// The contents of the [`File`] when it was last diffed
+    contents: String,
+    /// How many lines there were in the [`File`]
+    lines: usize,
+    /// The [`Hunk`]s, from the base version to the [`File`]
+    hunks: Vec<Hunk>,
+}
+
+impl Diffed {
+    /// Diffs every line of the [`Text`]
+    fn new(base: &[String], text: &Text) -> Self {
+        let contents = text.strs().concat();
+        let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+        let base: Vec<&str> = base.iter().map(String::as_str).collect();
+
+        Self {
+            lines: lines.len(),
+            hunks: diff::slices(&base, &lines),
+            contents,
+        }
+    }
+
+    /// Diffs only the lines that changed since the last diffing
+    ///
+    /// The lines that changed are found by comparing the start and
+    /// end of the [`Text`] with the last contents. They are then
+    /// widened to cover any [`Hunk`]s that they touch, and only that
+    /// range is compared with the matching range of the base.
+    fn update(&mut self, base: &[String], text: &Text) {
+        let [s0, s1] = text.strs();
+        let (old_len, new_len) = (self.contents.len(), s0.len() + s1.len());
+        let new_bytes = || s0.bytes().chain(s1.bytes());
+
+        let mut prefix = new_bytes()
+            .zip(self.contents.bytes())
+            .take_while(|(new, old)| new == old)
+            .count();
+        if prefix == old_len && prefix == new_len {
+            return;
+        }
+        while !self.contents.is_char_boundary(prefix) {
+            prefix -= 1;
+        }
+        let mut suffix = new_bytes()
+            .rev()
+            .zip(self.contents.bytes().rev())
+            .take_while(|(new, old)| new == old)
+            .count()
+            .min(old_len.min(new_len) - prefix);
+        while !self.contents.is_char_boundary(old_len - suffix) {
+            suffix -= 1;
+        }
+
+        // The lines in [start, old_end) of the last contents became the
+        // lines in [start, new_end) of the Text.
+        let (old_lines, new_lines) = (self.lines, line_count(text));
+        let start = text.point_at(prefix as u32).line() as usize;
+        let new_end = (text.point_at((new_len - suffix) as u32).line() as usize + 1).min(new_lines);
+        let old_end = old_lines - (new_lines - new_end);
+
+        let changed = text.strs_in_range((
+            text.point_at(prefix as u32),
+            text.point_at((new_len - suffix) as u32),
+        ));
+        self.contents
+            .replace_range(prefix..old_len - suffix, &changed.concat());
+        self.lines = new_lines;
+
+        let first = self.hunks.partition_point(|hunk| hunk.new.end < start);
+        let last = self.hunks.partition_point(|hunk| hunk.new.start <= old_end);
+        let touched = &self.hunks[first..last];
+
+        let start = touched
+            .first()
+            .map_or(start, |hunk| hunk.new.start.min(start));
+        let end = touched
+            .last()
+            .map_or(old_end, |hunk| hunk.new.end.max(old_end));
+        let growth = |hunks: &[Hunk]| -> isize {
+            hunks
+                .iter()
+                .map(|hunk| hunk.old.len() as isize - hunk.new.len() as isize)
+                .sum()
+        };
+        let shift = growth(&self.hunks[..first]);
+        let base_range =
+            start.saturating_add_signed(shift)..end.saturating_add_signed(shift + growth(touched));
+        let delta = new_lines as isize - old_lines as isize;
+        let new_range = start..end.saturating_add_signed(delta);
+
+        let line_start = |line: usize| match line < new_lines {
+            true => text.point_at_line(line as u32).byte() as usize,
+            false => new_len,
+        };
+        let region = &self.contents[line_start(new_range.start)..line_start(new_range.end)];
+        let region: Vec<String> = region.split_inclusive('\n').map(String::from).collect();
+
+        let hunks = diff::slices(&base[base_range.clone()], &region)
+            .into_iter()
+            .map(|hunk| Hunk {
+                old: hunk.old.start + base_range.start..hunk.old.end + base_range.start,
+                new: hunk.new.start + start..hunk.new.end + start,
+            });
+
+        for hunk in &mut self.hunks[last..] {
+            hunk.new = hunk.new.start.saturating_add_signed(delta)
+                ..hunk.new.end.saturating_add_signed(delta);
+        }
+        self.hunks.splice(first..last, hunks);
+    }
+}
+
+/// Configuration options for the [`DiffGutter<U>`] widget.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffGutterCfg<U> {
+    base: Base,
+    added: char,
+    changed: char,
+    deleted: char,
+    specs: PushSpecs,
+    ghost: PhantomData<U>,
+}
+
+impl<U> Default for DiffGutterCfg<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<U> DiffGutterCfg<U> {
+    pub fn new() -> Self {
+        Self {
+            base: Base::Saved,
+            added: '▎',
+            changed: '▎',
+            deleted: '▁',
+            specs: PushSpecs::left().with_hor_len(1.0),
+            ghost: PhantomData,
+        }
+    }
+
+    /// Compares the [`File`] with its last git commit
+    ///
+    /// If the [`File`] is not in a git repository, or was never
+    /// committed, no lines are marked.
+    pub fn against_git_head(self) -> Self {
+        Self { base: Base::GitHead, ..self }
+    }
+
+    /// Compares the [`File`] with its contents on disk
+    ///
+    /// This is the default.
+    pub fn against_saved(self) -> Self {
+        Self { base: Base::Saved, ..self }
+    }
+
+    /// The [`char`] shown on added lines
+    pub fn with_added(self, added: char) -> Self {
+        Self { added, ..self }
+    }
+
+    /// The [`char`] shown on changed lines
+    pub fn with_changed(self, changed: char) -> Self {
+        Self { changed, ..self }
+    }
+
+    /// The [`char`] shown on lines with deleted lines below them
+    pub fn with_deleted(self, deleted: char) -> Self {
+        Self { deleted, ..self }
+    }
+
+    /// Leaves an empty column between the marks and the [`File`]
+    pub fn padded(self) -> Self {
+        Self {
+            specs: self.specs.with_hor_len(2.0),
+            ..self
+        }
+    }
+
+    pub fn on_the_right(self) -> Self {
+        Self { specs: self.specs.to_right(), ..self }
+    }
+}
+
+impl<U: Ui> WidgetCfg<U> for DiffGutterCfg<U> {
+    type Widget = DiffGutter<U>;
+
+    fn build(self, _: bool) -> (Self::Widget, impl Fn() -> bool, PushSpecs) {
+        let reader = context::cur_file().unwrap().fixed_reader();
+        let specs = self.specs;
+
+        let fetched = Fetched::default();
+        let modified = reader.inspect(|file, _, _| {
+            fetch_base(file, self.base, &fetched);
+            file.info().modified()
+        });
+
+        let mut widget = DiffGutter {
+            reader: reader.clone(),
+            text: Text::default(),
+            cfg: self,
+            modified,
+            fetched: fetched.clone(),
+            base: None,
+            base_gen: 0,
+            diffed: Diffed::default(),
+        };
+        widget.update_text();
+
+        let checker = move || reader.has_changed() || fetched.lock().is_some();
+        (widget, checker, specs)
+    }
+}
+
+/// Reads the base version of a [`File`] in another thread
+fn fetch_base(file: &File, base: Base, fetched: &Fetched) {
+    let Some(path) = file.path_set().map(PathBuf::from) else {
+        *fetched.lock() = Some(None);
+        return;
+    };
+
+    let fetched = fetched.clone();
+    crate::thread::spawn(move || *fetched.lock() = Some(base_of(&path, base)));
+}
+
+/// The base version of the file at a path, if there is one
+fn base_of(path: &Path, base: Base) -> Option<String> {
+    match base {
+        Base::Saved => fs::read_to_string(path).ok(),
+        Base::GitHead => {
+            let name = path.file_name()?.to_string_lossy();
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(path.parent()?)
+                .args(["show", &format!("HEAD:./{name}")])
+                .output()
+                .ok()?;
+
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8(output.stdout).ok())
+                .flatten()
+        }
+    }
+}
+
+/// The [`Mark`] of a line, given the [`Hunk`]s from the base version
+fn mark_of(hunks: &[Hunk], line: usize) -> Option<Mark> {
+    let first = hunks.partition_point(|hunk| hunk.new.end < line);
+    let mut mark = None;
+
+    for hunk in hunks[first..].iter() {
+        if hunk.new.start > line + 1 {
+            break;
+        } else if hunk.new.contains(&line) {
+            return match hunk.is_insertion() {
+                true => Some(Mark::Added),
+                false => Some(Mark::Changed),
+            };
+        } else if hunk.is_deletion() && hunk.new.start.saturating_sub(1) == line {
+            mark = Some(Mark::Deleted);
+        }
+    }
+
+    mark
+}
+
+/// How many lines there are in a [`Text`]
+///
+/// Like with [`str::split_inclusive`], a last line without a `'\n'`
+/// is counted, but an empty one is not.
+fn line_count(text: &Text) -> usize {
+    let len = text.len();
+    let ends_in_newline = text
+        .chars_rev(len)
+        .next()
+        .is_none_or(|(_, char)| char == '\n');
+    len.line() as usize + !ends_in_newline as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diffed, Mark, mark_of};
+    use crate::text::{Text, diff};
+
+    fn base(str: &str) -> Vec<String> {
+        str.split_inclusive('\n').map(String::from).collect()
+    }
+
+    #[test]
+    fn marks_added_changed_and_deleted_lines() {
+        let base = "one\ntwo\nthree\nfour\n";
+
+        let hunks = diff::lines(base, "one\n2\nthree\nthree and a half\n");
+        assert_eq!(mark_of(&hunks, 0), None);
+        assert_eq!(mark_of(&hunks, 1), Some(Mark::Changed));
+        assert_eq!(mark_of(&hunks, 2), None);
+        assert_eq!(mark_of(&hunks, 3), Some(Mark::Changed));
+
+        let hunks = diff::lines(base, "one\nthree\nfour\nfive\n");
+        assert_eq!(mark_of(&hunks, 0), Some(Mark::Deleted));
+        assert_eq!(mark_of(&hunks, 1), None);
+        assert_eq!(mark_of(&hunks, 3), Some(Mark::Added));
+    }
+
+    #[test]
+    fn incremental_diffs_match_whole_ones() {
+        let base = base("one\ntwo\nthree\nfour\nfive\nsix\n");
+        let mut text = Text::from("one\ntwo\nthree\nfour\nfive\nsix\n");
+        let mut diffed = Diffed::new(&base, &text);
+        assert!(diffed.hunks.is_empty());
+
+        let edits: [(u32, u32, &str); 7] = [
+            (4, 7, "2"),
+            (0, 0, "zero\n"),
+            (16, 16, "three and a half\n"),
+            (5, 12, ""),
+            (0, 5, "one\n"),
+            (30, 35, "cinco"),
+            (0, 0, "é"),
+        ];
+        for (start, end, edit) in edits {
+            let range = (text.point_at(start), text.point_at(end));
+            text.replace_range(range, edit);
+
+            diffed.update(&base, &text);
+            let whole = Diffed::new(&base, &text);
+            assert_eq!(diffed.contents, whole.contents);
+            assert_eq!(diffed.lines, whole.lines);
+            for line in 0..=whole.lines {
+                assert_eq!(
+                    mark_of(&diffed.hunks, line),
+                    mark_of(&whole.hunks, line),
+                    "line {line} after replacing {start}..{end} with {edit:?}"
+                );
+            }
+        }
+    }
+}
@@ -11,13 +11,26 @@
 //!
 //! [`LineNumbers`]: crate::widgets::LineNumbers
 //! [`Cursor`]: crate::mode::Cursor
-use std::{fs, io::ErrorKind, path::PathBuf};
+use std::{
+    any::Any,
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
     cfg::{IterCfg, PrintCfg},
-    form,
-    text::{Text},
+    context, filetype, form,
+    hooks::{self, AfterFileWrite, WriteHandle},
+    indent,
+    session::FileOpener,
+    suggestion::SuggestionProvider,
+    text::{Point, Text},
     ui::{Area, PushSpecs, Ui},
+    watcher,
     widgets::{Widget, WidgetCfg},
 };
 
@@ -26,6 +39,8 @@ use crate::{
 pub struct FileCfg {
     text_op: TextOp,
     cfg: PrintCfg,
+    openers: Vec<Arc<dyn FileOpener>>,
+    overrides: Vec<Arc<dyn Fn(&str, PrintCfg) -> PrintCfg + Send + Sync>>,
 }
 
 impl FileCfg {
@@ -34,6 +49,8 @@ impl FileCfg {
         FileCfg {
             text_op: TextOp::NewBuffer,
             cfg: PrintCfg::default_for_input(),
+            openers: Vec::new(),
+            overrides: Vec::new(),
         }
     }
 
@@ -42,11 +59,19 @@ impl FileCfg {
         Self { text_op: TextOp::OpenPath(path), ..self }
     }
 
+    /// Opens an unnamed buffer with the given [`Text`]
+    pub(crate) fn take_text(self, text: Text) -> Self {
+        Self {
+            text_op: TextOp::TakeText(Box::new(text), Path::new_unset()),
+            ..self
+        }
+    }
+
     /// Takes a previous [`File`]
     pub(crate) fn take_from_prev(self, prev: &mut File) -> Self {
         let text = std::mem::take(&mut prev.text);
         Self {
-            text_op: TextOp::TakeText(text, prev.path.clone()),
+            text_op: TextOp::TakeText(Box::new(text), prev.path.clone()),
             ..self
         }
     }
@@ -55,15 +80,48 @@ impl FileCfg {
     pub(crate) fn set_print_cfg(&mut self, cfg: PrintCfg) {
         self.cfg = cfg;
     }
+
+    /// Adds a [`FileOpener`], taking precedence over previous ones
+    pub(crate) fn add_opener(&mut self, opener: Arc<dyn FileOpener>) {
+        self.openers.push(opener);
+    }
+
+    /// Adds an override of the [`PrintCfg`], based on the path
+    pub(crate) fn add_override(
+        &mut self,
+        f: Arc<dyn Fn(&str, PrintCfg) -> PrintCfg + Send + Sync>,
+    ) {
+        self.overrides.push(f);
+    }
 }
 
 impl<U: Ui> WidgetCfg<U> for FileCfg {
     type Widget = File;
 
     fn build(self, _: bool) -> (Self::Widget, impl Fn() -> bool, PushSpecs) {
+        let opener = |target: &str| {
+            self.openers
+                .iter()
+                .rev()
+                .find(|o| o.handles(target))
+                .cloned()
+        };
+
         let (text, path) = match self.text_op {
             TextOp::NewBuffer => (Text::new(), Path::new_unset()),
-            TextOp::TakeText(text, path) => (text, path),
+            TextOp::TakeText(text, path) => (*text, path),
+            TextOp::OpenPath(path) if let Some(opener) = opener(&path.to_string_lossy()) => {
+                let target = path.to_string_lossy().to_string();
+                match opener.read(&target) {
+                    Ok(contents) => (Text::from(contents), Path::Opened(target, opener)),
+                    // Without the opener, writing won't replace the target
+                    // with an empty File.
+                    Err(err) => {
+                        context::notify(err);
+                        (Text::new(), Path::new_unset())
+                    }
+                }
+            }
             // TODO: Add an option for automatic path creation.
             TextOp::OpenPath(path) => match path.canonicalize() {
                 Ok(path) => (Text::from_file(&path), Path::SetExists(path)),
@@ -102,12 +160,28 @@ impl<U: Ui> WidgetCfg<U> for FileCfg {
             text
         };
 
-        let file = File {
+        let mut file = File {
             path,
             text,
             cfg: self.cfg,
             printed_lines: Vec::new(),
+            vars: Vars::default(),
+            info: FileInfo::default(),
+            changed_on_disk: false,
+            filetype: None,
+            suggestion_providers: Vec::new(),
         };
+        file.refresh_info();
+        if let Path::SetExists(path) = &file.path {
+            watcher::watch(path);
+        }
+
+        let path = file.path();
+        file.filetype = filetype::detect(&path, &file.text);
+        if let Some(filetype) = &file.filetype {
+            file.cfg = filetype::apply_print_cfgs(filetype, file.cfg);
+        }
+        file.cfg = self.overrides.iter().fold(file.cfg, |cfg, f| f(&path, cfg));
 
         // The PushSpecs don't matter
         (file, Box::new(|| false), PushSpecs::above())
@@ -120,6 +194,11 @@ pub struct File {
     text: Text,
     cfg: PrintCfg,
     printed_lines: Vec<(u32, bool)>,
+    vars: Vars,
+    info: FileInfo,
+    changed_on_disk: bool,
+    filetype: Option<String>,
+    suggestion_providers: Vec<Arc<dyn SuggestionProvider>>,
 }
 
 impl File {
@@ -127,27 +206,67 @@ impl File {
 
     /// Writes the file to the current [`Path`], if one was set
     ///
+    /// This also gathers the [`FileInfo`] again, so the new
+    /// modification time isn't mistaken for a change by another
+    /// program.
+    ///
     /// [`Path`]: std::path::Path
-    pub fn write(&self) -> Result<usize, String> {
-        if let Path::SetExists(path) = &self.path {
-            self.text
-                .write_to(std::io::BufWriter::new(
-                    fs::File::create(path).map_err(|err| err.to_string())?,
-                ))
-                .map_err(|err| err.to_string())
+    pub fn write(&mut self) -> Result<usize, String> {
+        let bytes = if let Path::Opened(target, opener) = &self.path {
+            let contents = self.before_write(target)?;
+            let contents = contents.unwrap_or_else(|| self.text.to_string());
+            opener
+                .write(target, &contents)
+                .map_err(|err| err.to_string())?
+        } else if let Path::SetExists(path) = &self.path {
+            if self.changed_on_disk {
+                return Err(String::from(
+                    "The file was changed on disk, use file-changed reload or file-changed keep",
+                ));
+            }
+            let contents = self.before_write(path.to_string_lossy())?;
+            let file = fs::File::create(path).map_err(|err| err.to_string())?;
+            let writer = std::io::BufWriter::new(file);
+            match contents {
+                Some(contents) => write_str(writer, &contents),
+                None => self.text.write_to(writer),
+            }
+            .map_err(|err| err.to_string())?
         } else {
-            Err(String::from(
+            return Err(String::from(
                 "The file has no associated path, and no path was given to write to",
-            ))
-        }
+            ));
+        };
+
+        self.refresh_info();
+        hooks::trigger::<AfterFileWrite>((self.path(), bytes));
+        Ok(bytes)
     }
 
     /// Writes the file to the given [`Path`]
     ///
     /// [`Path`]: std::path::Path
-    pub fn write_to(&self, path: impl AsRef<str>) -> std::io::Result<usize> {
-        self.text
-            .write_to(std::io::BufWriter::new(fs::File::create(path.as_ref())?))
+    pub fn write_to(&mut self, path: impl AsRef<str>) -> std::io::Result<usize> {
+        let path = path.as_ref();
+        let contents = self.before_write(path).map_err(std::io::Error::other)?;
+
+        let writer = std::io::BufWriter::new(fs::File::create(path)?);
+        let bytes = match contents {
+            Some(contents) => write_str(writer, &contents),
+            None => self.text.write_to(writer),
+        }?;
+
+        self.refresh_info();
+        hooks::trigger::<AfterFileWrite>((path.to_string(), bytes));
+        Ok(bytes)
+    }
+
+    /// Triggers [`BeforeFileWrite`], returning the transformed
+    /// contents, if hooks have transformed them
+    ///
+    /// [`BeforeFileWrite`]: hooks::BeforeFileWrite
+    fn before_write(&self, path: impl ToString) -> Result<Option<String>, String> {
+        WriteHandle::trigger(path, &self.text)
     }
 
     ////////// Path querying functions
@@ -166,6 +285,7 @@ impl File {
 
                 format!("{path}/*scratch file*#{id}")
             }
+            Path::Opened(target, _) => target.clone(),
         }
     }
 
@@ -178,6 +298,7 @@ impl File {
                 Some(path.to_string_lossy().to_string())
             }
             Path::UnSet(_) => None,
+            Path::Opened(target, _) => Some(target.clone()),
         }
     }
 
@@ -190,6 +311,7 @@ impl File {
                 path.file_name().unwrap().to_string_lossy().to_string()
             }
             Path::UnSet(id) => format!("*scratch file #{id}*"),
+            Path::Opened(target, _) => target.rsplit('/').next().unwrap().to_string(),
         }
     }
 
@@ -202,6 +324,7 @@ impl File {
                 Some(path.file_name().unwrap().to_string_lossy().to_string())
             }
             Path::UnSet(_) => None,
+            Path::Opened(target, _) => Some(target.rsplit('/').next().unwrap().to_string()),
         }
     }
 
@@ -240,13 +363,112 @@ impl File {
         &mut self.text
     }
 
-    /// The mutable [`Text`] of the [`File`]
+    /// The [`PrintCfg`] of the [`File`]
     pub fn print_cfg(&self) -> PrintCfg {
         self.cfg
     }
 
+    /// The indentation that a line should have, in columns
+    ///
+    /// This is decided by the [`IndentProvider`] in use, and is meant
+    /// for lines that were just created.
+    ///
+    /// [`IndentProvider`]: indent::IndentProvider
+    pub fn indent_for_line(&self, line: u32) -> u32 {
+        indent::indent_for_line(&self.text, line, &self.cfg)
+    }
+
+    /// Sets the [`PrintCfg`] of the [`File`]
+    pub fn set_print_cfg(&mut self, cfg: PrintCfg) {
+        self.cfg = cfg;
+    }
+
+    /// The filetype of the [`File`], if it is known
+    ///
+    /// See the [`filetype`] module for how it is detected.
+    pub fn filetype(&self) -> Option<&str> {
+        self.filetype.as_deref()
+    }
+
+    /// Sets the filetype of the [`File`]
+    ///
+    /// The [`PrintCfg`] changes attached to the new filetype are
+    /// applied on top of the current [`PrintCfg`].
+    pub(crate) fn set_filetype(&mut self, filetype: String) {
+        self.cfg = filetype::apply_print_cfgs(&filetype, self.cfg);
+        self.filetype = Some(filetype);
+    }
+
+    /// Adds a [`SuggestionProvider`] to the [`File`]
+    ///
+    /// When a suggestion is requested, the providers are asked in the
+    /// order that they were added, and the first suggestion is used.
+    pub fn add_suggestion_provider(&mut self, provider: impl SuggestionProvider) {
+        self.suggestion_providers.push(Arc::new(provider));
+    }
+
+    /// The first suggestion of the [`SuggestionProvider`]s at a
+    /// [`Point`]
+    pub(crate) fn suggestion_at(&self, caret: Point) -> Option<String> {
+        self.suggestion_providers
+            .iter()
+            .find_map(|provider| provider.suggest(&self.text, caret))
+    }
+
+    /// The [`Vars`] local to this [`File`]
+    pub fn vars(&self) -> &Vars {
+        &self.vars
+    }
+
+    /// Mutable access to the [`Vars`] local to this [`File`]
+    ///
+    /// Since this requires writing to the [`File`], anything that
+    /// reads from it (like a [`StatusLine`]) will be updated.
+    ///
+    /// [`StatusLine`]: super::StatusLine
+    pub fn vars_mut(&mut self) -> &mut Vars {
+        &mut self.vars
+    }
+
+    /// Information about the [`File`] on disk
+    ///
+    /// This is gathered when the [`File`] is opened and written, and
+    /// can be gathered again with [`File::refresh_info`].
+    pub fn info(&self) -> &FileInfo {
+        &self.info
+    }
+
+    /// Gathers the [`FileInfo`] again
+    ///
+    /// Use this if the file may have changed on disk.
+    pub fn refresh_info(&mut self) {
+        let metadata = match &self.path {
+            Path::SetExists(path) | Path::SetAbsent(path) => fs::metadata(path).ok(),
+            Path::UnSet(_) | Path::Opened(..) => None,
+        };
+        self.info = FileInfo::new(metadata, &self.text);
+    }
+
+    /// Whether the [`File`] was changed on disk by another program
+    ///
+    /// This is only known if the `watch-files` option was on when
+    /// the [`File`] was opened, and stays `true` until the user
+    /// decides what to do about it. See the [`watcher`] module for
+    /// more information.
+    pub fn was_changed_on_disk(&self) -> bool {
+        self.changed_on_disk
+    }
+
+    /// Sets whether the [`File`] was changed on disk
+    pub(crate) fn set_changed_on_disk(&mut self, changed: bool) {
+        self.changed_on_disk = changed;
+    }
+
     /// Whether o not the [`File`] exists or not
     pub fn exists(&self) -> bool {
+        if let Path::Opened(..) = &self.path {
+            return true;
+        }
         self.path_set()
             .is_some_and(|p| std::fs::exists(PathBuf::from(&p)).is_ok_and(|e| e))
     }
@@ -287,7 +509,12 @@ impl<U: Ui> Widget<U> for File {
 
         let mut has_wrapped = false;
 
-        area.print_with(&self.text, self.cfg, form::painter(), move |caret, item| {
+        let painter = match area.is_active() {
+            true => form::painter(),
+            false => form::painter().dimmed(),
+        };
+
+        area.print_with(&self.text, self.cfg, painter, move |caret, item| {
             has_wrapped |= caret.wrap;
             if has_wrapped && item.part.is_char() {
                 has_wrapped = false;
@@ -296,7 +523,202 @@ impl<U: Ui> Widget<U> for File {
                 last_line = Some(line);
                 printed_lines.push((line, wrapped));
             }
-        })
+        });
+
+        // An empty buffer still has its first line shown.
+        if self.printed_lines.is_empty() && area.height() > 0 {
+            self.printed_lines.push((0, false));
+        }
+    }
+}
+
+/// Variables local to a [`File`]
+///
+/// This is a standard place for plugins to keep state for each
+/// [`File`], instead of global maps keyed by the [`File`]'s name.
+/// Values of any type can be stored:
+///
+/// ```rust
+/// # use duat_core::widgets::File;
+/// # fn test(file: &mut File) {
+/// file.vars_mut().set("spell_lang", "en");
+/// file.vars_mut().set("word_count", 0usize);
+///
+/// assert_eq!(file.vars().get::<&str>("spell_lang"), Some(&"en"));
+/// # }
+/// ```
+///
+/// These variables can also be set and read with the `set-var` and
+/// `var` commands, in which case they are [`String`]s.
+///
+/// Do note that, since they can be of any type, these variables are
+/// cleared when reloading the config.
+#[derive(Default)]
+pub struct Vars(HashMap<String, Box<dyn Any + Send + Sync>>);
+
+impl Vars {
+    /// Sets a variable, replacing any previous value
+    pub fn set<T: Any + Send + Sync>(&mut self, key: impl ToString, value: T) {
+        self.0.insert(key.to_string(), Box::new(value));
+    }
+
+    /// Gets a variable, if it exists and is of type `T`
+    pub fn get<T: Any>(&self, key: &str) -> Option<&T> {
+        self.0.get(key).and_then(|value| value.downcast_ref())
+    }
+
+    /// Gets a variable mutably, if it exists and is of type `T`
+    pub fn get_mut<T: Any>(&mut self, key: &str) -> Option<&mut T> {
+        self.0.get_mut(key).and_then(|value| value.downcast_mut())
+    }
+
+    /// Removes a variable, returning `true` if it existed
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.0.remove(key).is_some()
+    }
+
+    /// Whether a variable with this name exists
+    pub fn contains(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// The names of all variables
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// A variable as a [`String`]
+    ///
+    /// This only works for [`String`]s, [`str`]s, [`bool`]s,
+    /// [`char`]s and numbers, since the other types can't be
+    /// known in advance.
+    pub fn display(&self, key: &str) -> Option<String> {
+        let value = self.0.get(key)?;
+
+        macro display($($ty:ty),+) {
+            $(
+                if let Some(value) = value.downcast_ref::<$ty>() {
+                    return Some(value.to_string());
+                }
+            )+
+        }
+
+        display!(String, &str, bool, char, i8, i16, i32, i64, isize);
+        display!(u8, u16, u32, u64, usize, f32, f64);
+
+        None
+    }
+}
+
+/// Information about a [`File`] on disk
+///
+/// This includes the size, permissions and modification time of the
+/// file, if it exists, as well as the encoding and line ending of its
+/// contents.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    size: Option<u64>,
+    permissions: Option<String>,
+    modified: Option<SystemTime>,
+    has_bom: bool,
+    has_crlf: bool,
+}
+
+impl FileInfo {
+    /// Gathers the [`FileInfo`] from the [`Metadata`] and contents
+    ///
+    /// [`Metadata`]: fs::Metadata
+    fn new(metadata: Option<fs::Metadata>, text: &Text) -> Self {
+        let has_bom = text.char_at(Point::default()) == Some('\u{feff}');
+        let has_crlf = text
+            .chars_fwd(Point::default())
+            .scan(None, |prev, (_, char)| Some((prev.replace(char), char)))
+            .find(|(_, char)| *char == '\n')
+            .is_some_and(|(prev, _)| prev == Some('\r'));
+
+        Self {
+            size: metadata.as_ref().map(fs::Metadata::len),
+            permissions: metadata.as_ref().map(permissions_str),
+            modified: metadata.and_then(|metadata| metadata.modified().ok()),
+            has_bom,
+            has_crlf,
+        }
+    }
+
+    /// The size of the file on disk, in bytes
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// The size of the file on disk, in a human readable form
+    ///
+    /// This is something like `"12 B"` or `"3.4 KiB"`.
+    pub fn size_fmt(&self) -> Option<String> {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+        let size = self.size?;
+        if size < 1024 {
+            return Some(format!("{size} B"));
+        }
+
+        let mut size = size as f64 / 1024.0;
+        let mut unit = UNITS[0];
+        for next in &UNITS[1..] {
+            if size < 1024.0 {
+                break;
+            }
+            size /= 1024.0;
+            unit = next;
+        }
+        Some(format!("{size:.1} {unit}"))
+    }
+
+    /// The permissions of the file, like `"rw-r--r--"`
+    ///
+    /// Outside of Unix, this is only `"r--"` or `"rw-"`, depending on
+    /// the file being read only or not.
+    pub fn permissions(&self) -> Option<&str> {
+        self.permissions.as_deref()
+    }
+
+    /// The last time that the file was modified
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// The last time that the file was modified, in UTC
+    ///
+    /// This is formatted as `"YYYY-MM-DD HH:MM UTC"`.
+    pub fn modified_fmt(&self) -> Option<String> {
+        let secs = self.modified?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let (days, secs) = (secs / 86400, secs % 86400);
+        let (year, month, day) = civil_from_days(days as i64);
+        Some(format!(
+            "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+            secs / 3600,
+            secs % 3600 / 60
+        ))
+    }
+
+    /// The encoding of the contents
+    ///
+    /// Since Duat only opens UTF-8 files, this is either `"utf-8"`,
+    /// or `"utf-8-bom"`, if the contents start with a byte order
+    /// mark.
+    pub fn encoding(&self) -> &'static str {
+        match self.has_bom {
+            true => "utf-8-bom",
+            false => "utf-8",
+        }
+    }
+
+    /// The line ending of the contents, either `"lf"` or `"crlf"`
+    ///
+    /// This is decided by the first line ending found.
+    pub fn line_ending(&self) -> &'static str {
+        match self.has_crlf {
+            true => "crlf",
+            false => "lf",
+        }
     }
 }
 
@@ -306,6 +728,8 @@ enum Path {
     SetExists(PathBuf),
     SetAbsent(PathBuf),
     UnSet(usize),
+    /// Opened through a [`FileOpener`]
+    Opened(String, Arc<dyn FileOpener>),
 }
 
 impl Path {
@@ -323,6 +747,58 @@ impl Path {
 enum TextOp {
     #[default]
     NewBuffer,
-    TakeText(Text, Path),
+    TakeText(Box<Text>, Path),
     OpenPath(PathBuf),
 }
+
+/// The permissions in [`Metadata`], like `"rw-r--r--"`
+///
+/// [`Metadata`]: fs::Metadata
+fn permissions_str(metadata: &fs::Metadata) -> String {
+    #[cfg(unix)]
+    let (mode, bits) = {
+        use std::os::unix::fs::PermissionsExt;
+        (metadata.permissions().mode(), 9)
+    };
+    #[cfg(not(unix))]
+    let (mode, bits) = (
+        if metadata.permissions().readonly() {
+            0o4
+        } else {
+            0o6
+        },
+        3,
+    );
+
+    (0..bits)
+        .rev()
+        .map(|bit| match mode & (1 << bit) != 0 {
+            true => ['x', 'w', 'r'][bit % 3],
+            false => '-',
+        })
+        .collect()
+}
+
+/// The year, month and day of a number of days since the Unix epoch
+///
+/// This is Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Writes a [`str`] to a [writer], returning the amount of bytes
+///
+/// [writer]: std::io::Write
+fn write_str(mut writer: impl std::io::Write, str: &str) -> std::io::Result<usize> {
+    writer.write_all(str.as_bytes())?;
+    Ok(str.len())
+}
@@ -0,0 +1,372 @@
+//! A filtered list of entries, for pickers of all kinds
+//!
+//! The [`FilterList`] is a [`Mode`] that shows a [`Menu`] in the echo
+//! area, alongside a query line. Typing narrows down the entries to
+//! the ones that fuzzily match the query, highlighting the matched
+//! characters, and accepting one of them calls a function with its
+//! index. This way, the buffer list, the clipboard history and any
+//! other picker added by plugins all share the same bindings and
+//! looks:
+//!
+//! ```rust
+//! # use duat_core::{context, mode, text::text, ui::Ui, widgets::FilterList};
+//! # fn test<U: Ui>() {
+//! let names = ["main.rs", "lib.rs", "Cargo.toml"];
+//! let list = FilterList::<U>::new(names, move |i, _, _, _| {
+//!     context::notify(text!("Picked " [*a] { names[i] }));
+//! })
+//! .with_prompt("file: ");
+//!
+//! mode::set::<U>(list);
+//! # }
+//! ```
+//!
+//! [`Mode`]: crate::mode::Mode
+use std::{
+    ops::Range,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use crate::{
+    context,
+    data::RwData,
+    form::{self, FormId},
+    mode::{self, Cursors, KeyCode::*, KeyEvent, KeyMod, Mode, key},
+    text::{Key, Tag, Text, hint},
+    ui::{Menu, MenuEvent, Ui},
+    widgets::File,
+};
+
+const SHOW_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// A [`Mode`] to pick one of many entries, filtering them as you type
+///
+/// Besides the bindings of the [`Menu`], the following keys are
+/// available:
+///
+/// - Any character: Adds it to the query;
+/// - `<Backspace>`: Removes the last character of the query;
+/// - `<C-u>`: Clears the query.
+///
+/// The query is matched case insensitively, unless it has uppercase
+/// characters. Every character of the query must be present in an
+/// entry, in the same order, but not necessarily next to each other.
+/// The matched characters are shown with the `"MenuMatch"` form.
+///
+/// Extra bindings can be added with [`FilterList::with_binding`].
+///
+/// [`Mode`]: crate::mode::Mode
+pub struct FilterList<U: Ui> {
+    items: Vec<String>,
+    query: String,
+    prompt: String,
+    matches: Vec<(usize, Vec<Range<usize>>)>,
+    menu: Menu,
+    on_accept: Arc<OnAccept<U>>,
+    on_move: Option<Arc<dyn Fn(usize) + Send + Sync>>,
+    bindings: Vec<Binding>,
+}
+
+impl<U: Ui> FilterList<U> {
+    /// Returns a new [`FilterList`]
+    ///
+    /// `on_accept` is called with the index of the accepted entry in
+    /// `items`, not in the filtered list. By the time it's called,
+    /// the [`FilterList`] has already been left, so it is free to
+    /// switch to another [`Mode`].
+    ///
+    /// [`Mode`]: crate::mode::Mode
+    pub fn new(
+        items: impl IntoIterator<Item = impl ToString>,
+        on_accept: impl Fn(usize, &RwData<File>, &U::Area, &mut Cursors) + Send + Sync + 'static,
+    ) -> Self {
+        let mut list = Self {
+            items: items.into_iter().map(|item| item.to_string()).collect(),
+            query: String::new(),
+            prompt: String::from("> "),
+            matches: Vec::new(),
+            menu: Menu::new([]),
+            on_accept: Arc::new(on_accept),
+            on_move: None,
+            bindings: Vec::new(),
+        };
+        list.filter();
+        list
+    }
+
+    /// Sets the prompt shown before the query
+    ///
+    /// The default is `"> "`.
+    pub fn with_prompt(self, prompt: impl ToString) -> Self {
+        Self { prompt: prompt.to_string(), ..self }
+    }
+
+    /// Sets how many entries are shown at once
+    pub fn with_height(self, height: usize) -> Self {
+        Self {
+            menu: self.menu.with_height(height),
+            ..self
+        }
+    }
+
+    /// Calls a function whenever the selected entry changes
+    ///
+    /// This can be used, for example, in order to preview the
+    /// selected entry. The function receives the index of the entry
+    /// in the original list.
+    pub fn on_move(self, f: impl Fn(usize) + Send + Sync + 'static) -> Self {
+        Self { on_move: Some(Arc::new(f)), ..self }
+    }
+
+    /// Adds a binding that acts on the selected entry
+    ///
+    /// The function receives the index of the selected entry in the
+    /// original list, and can modify the list, which is then filtered
+    /// again. The `keys` and `desc` are shown as [hints].
+    ///
+    /// [hints]: crate::mode::Mode::hints
+    pub fn with_binding(
+        mut self,
+        key: KeyEvent,
+        (keys, desc): (&'static str, &'static str),
+        f: impl Fn(usize, &mut Vec<String>) + Send + Sync + 'static,
+    ) -> Self {
+        self.bindings
+            .push(Binding { key, keys, desc, f: Arc::new(f) });
+        self
+    }
+
+    /// The entries of the list, before filtering
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// The current query
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The index of the selected entry in the original list
+    pub fn selected(&self) -> Option<usize> {
+        self.menu.selected().map(|i| self.matches[i].0)
+    }
+
+    /// Filters the entries again, selecting the best match
+    fn filter(&mut self) {
+        let mut scored: Vec<_> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| {
+                let (score, ranges) = fuzzy_match(item, &self.query)?;
+                Some((score, i, ranges))
+            })
+            .collect();
+        scored.sort_by_key(|(score, i, _)| (*score, *i));
+
+        self.matches = scored
+            .into_iter()
+            .map(|(_, i, ranges)| (i, ranges))
+            .collect();
+        let items = self
+            .matches
+            .iter()
+            .map(|(i, _)| Text::from(self.items[*i].clone()));
+        self.menu.set_items(items);
+        self.menu.select_first();
+    }
+
+    /// Shows the list in the echo area
+    fn show(&self) {
+        let mut text = self.menu.text();
+
+        for (line, (_, ranges)) in self.matches[self.menu.visible()].iter().enumerate() {
+            let byte = text.point_at_line(line as u32).byte();
+            for range in ranges {
+                let (start, end) = (byte + range.start as u32, byte + range.end as u32);
+                text.insert_tag(start, Tag::PushForm(*MATCH), *KEY);
+                text.insert_tag(end, Tag::PopForm(*MATCH), *KEY);
+            }
+        }
+
+        // The query goes at the bottom, so the Menu's tags aren't moved.
+        let prompt_start = text.len();
+        text.replace_range(
+            (prompt_start, prompt_start),
+            format!("{}{}", self.prompt, self.query),
+        );
+        let (start, end) = (
+            prompt_start.byte(),
+            prompt_start.byte() + self.prompt.len() as u32,
+        );
+        text.insert_tag(start, Tag::PushForm(*PROMPT), *KEY);
+        text.insert_tag(end, Tag::PopForm(*PROMPT), *KEY);
+
+        context::echo_for(text, SHOW_DURATION);
+    }
+
+    /// Leaves this [`Mode`], clearing the echo area
+    ///
+    /// [`Mode`]: crate::mode::Mode
+    fn leave(&self) {
+        context::clear_echo();
+        mode::reset();
+    }
+}
+
+impl<U: Ui> Clone for FilterList<U> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            query: self.query.clone(),
+            prompt: self.prompt.clone(),
+            matches: self.matches.clone(),
+            menu: self.menu.clone(),
+            on_accept: self.on_accept.clone(),
+            on_move: self.on_move.clone(),
+            bindings: self.bindings.clone(),
+        }
+    }
+}
+
+impl<U: Ui> Mode<U> for FilterList<U> {
+    type Widget = File;
+
+    fn send_key(
+        &mut self,
+        key: KeyEvent,
+        widget: &RwData<Self::Widget>,
+        area: &U::Area,
+        cursors: &mut Cursors,
+    ) {
+        let binding = self.bindings.iter().find(|binding| binding.key == key);
+        if let Some(f) = binding.map(|binding| binding.f.clone()) {
+            if let Some(i) = self.selected() {
+                let prev = self.menu.selected();
+                f(i, &mut self.items);
+                self.filter();
+                self.menu.select(prev.unwrap_or(0));
+                self.show();
+            }
+            return;
+        }
+
+        match key {
+            key!(Char(char)) | key!(Char(char), KeyMod::SHIFT) => self.query.push(char),
+            key!(Backspace) => {
+                if self.query.pop().is_none() {
+                    return;
+                }
+            }
+            key!(Char('u'), KeyMod::CONTROL) => self.query.clear(),
+            key => {
+                match self.menu.send_key(key) {
+                    MenuEvent::Moved => {
+                        if let (Some(on_move), Some(i)) = (&self.on_move, self.selected()) {
+                            on_move(i);
+                        }
+                        self.show();
+                    }
+                    MenuEvent::Accepted(i) => {
+                        self.leave();
+                        (self.on_accept)(self.matches[i].0, widget, area, cursors);
+                    }
+                    MenuEvent::Cancelled => self.leave(),
+                    MenuEvent::Unchanged | MenuEvent::Ignored => {}
+                }
+                return;
+            }
+        }
+
+        self.filter();
+        self.show();
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        let mut hints = vec![("<Enter>", "accept")];
+        hints.extend(
+            self.bindings
+                .iter()
+                .map(|binding| (binding.keys, binding.desc)),
+        );
+        hints.push(("<Esc>", "cancel"));
+        hints
+    }
+
+    fn on_switch(&mut self, _: &RwData<Self::Widget>, _: &U::Area, _: &mut Cursors) {
+        if self.items.is_empty() {
+            context::echo(hint!("There is nothing to pick from"));
+            mode::reset();
+        } else {
+            self.show();
+        }
+    }
+}
+
+/// A function to call when an entry is accepted
+type OnAccept<U> = dyn Fn(usize, &RwData<File>, &<U as Ui>::Area, &mut Cursors) + Send + Sync;
+
+/// An extra binding of a [`FilterList`]
+#[derive(Clone)]
+struct Binding {
+    key: KeyEvent,
+    keys: &'static str,
+    desc: &'static str,
+    f: Arc<dyn Fn(usize, &mut Vec<String>) + Send + Sync>,
+}
+
+/// Fuzzily matches a query against an entry
+///
+/// Returns a score, lower being better, and the byte ranges of the
+/// matched characters, or [`None`] if the entry doesn't match.
+fn fuzzy_match(item: &str, query: &str) -> Option<(usize, Vec<Range<usize>>)> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let eq = |lhs: char, rhs: char| match case_sensitive {
+        true => lhs == rhs,
+        false => lhs.to_lowercase().eq(rhs.to_lowercase()),
+    };
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut chars = item.char_indices();
+    for q in query.chars() {
+        let (b, char) = chars.find(|(_, char)| eq(*char, q))?;
+        match ranges.last_mut() {
+            Some(range) if range.end == b => range.end = b + char.len_utf8(),
+            _ => ranges.push(b..b + char.len_utf8()),
+        }
+    }
+
+    // Fewer and earlier runs of matched characters are better.
+    let score = ranges.len() * item.len() + ranges.first().map_or(0, |range| range.start);
+    Some((score, ranges))
+}
+
+static KEY: LazyLock<Key> = LazyLock::new(Key::new);
+static MATCH: LazyLock<FormId> = LazyLock::new(|| form::set_weak("MenuMatch", "Accent"));
+static PROMPT: LazyLock<FormId> = LazyLock::new(|| form::id_of!("Prompt"));
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_match;
+
+    #[test]
+    fn matches_in_order() {
+        let (_, ranges) = fuzzy_match("clip_history.rs", "chis").unwrap();
+        assert_eq!(ranges, [0..1, 5..8]);
+        assert_eq!(fuzzy_match("clip_history.rs", "sih"), None);
+    }
+
+    #[test]
+    fn smart_case() {
+        assert!(fuzzy_match("Cargo.toml", "cargo").is_some());
+        assert!(fuzzy_match("cargo.toml", "Cargo").is_none());
+    }
+
+    #[test]
+    fn prefers_contiguous_matches() {
+        let (contiguous, _) = fuzzy_match("mode.rs", "mod").unwrap();
+        let (scattered, _) = fuzzy_match("m_o_d.rs", "mod").unwrap();
+        assert!(contiguous < scattered);
+    }
+}
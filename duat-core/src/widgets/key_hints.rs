@@ -0,0 +1,157 @@
+//! A bar showing the keys available in the current [`Mode`]
+//!
+//! This is similar to Kakoune's autoinfo, or Helix's infobox. Every
+//! [`Mode`] can publish a list of [hints], which is updated whenever
+//! the mode changes, or after every key is sent to it. The
+//! [`KeyHints`] widget then lays these hints out in columns.
+//!
+//! If there are no hints, or if the [`Area`] is too narrow to show
+//! even one column of them, the widget shrinks down to nothing.
+//!
+//! [`Mode`]: crate::mode::Mode
+//! [hints]: crate::mode::Mode::hints
+use std::marker::PhantomData;
+
+use crate::{
+    context,
+    data::RoData,
+    form::{self, Form},
+    text::{Text, text},
+    ui::{Area, Constraint, PushSpecs, Ui},
+    widgets::{Widget, WidgetCfg},
+};
+
+/// A widget that shows the [hints] of the current [`Mode`]
+///
+/// [hints]: crate::mode::Mode::hints
+/// [`Mode`]: crate::mode::Mode
+pub struct KeyHints<U> {
+    hints: RoData<Vec<(&'static str, &'static str)>>,
+    text: Text,
+    max_rows: usize,
+    ghost: PhantomData<U>,
+}
+
+impl<U: Ui> Widget<U> for KeyHints<U> {
+    type Cfg = KeyHintsCfg<U>;
+
+    fn cfg() -> Self::Cfg {
+        KeyHintsCfg::new()
+    }
+
+    fn update(&mut self, area: &U::Area) {
+        let hints = self.hints.read();
+        let keys_width = hints.iter().map(|(keys, _)| width(keys)).max();
+        let desc_width = hints.iter().map(|(_, desc)| width(desc)).max();
+
+        let (Some(keys_width), Some(desc_width)) = (keys_width, desc_width) else {
+            self.text = Text::new();
+            let _ = area.constrain_ver(Constraint::Length(0.0));
+            return;
+        };
+
+        // One space between keys and description, two between columns.
+        let col_width = keys_width + 1 + desc_width + 2;
+        let cols = (area.width() as usize + 2) / col_width;
+        let rows = hints.len().div_ceil(cols.max(1));
+
+        if cols == 0 || rows > self.max_rows {
+            self.text = Text::new();
+            let _ = area.constrain_ver(Constraint::Length(0.0));
+            return;
+        }
+
+        let mut builder = Text::builder();
+        for row in 0..rows {
+            let row_hints = hints.iter().skip(row).step_by(rows);
+            for (i, (keys, desc)) in row_hints.enumerate() {
+                if i > 0 {
+                    text!(builder, "  ");
+                }
+                let keys_pad = " ".repeat(keys_width - width(keys) + 1);
+                text!(builder, [KeyHintsKey] keys [] keys_pad [KeyHintsDesc] desc);
+                if (i + 1) * rows + row < hints.len() {
+                    text!(builder, { " ".repeat(desc_width - width(desc)) });
+                }
+            }
+            text!(builder, "\n");
+        }
+
+        self.text = builder.finish();
+        let _ = area.constrain_ver(Constraint::Length(rows as f32));
+    }
+
+    fn text(&self) -> &Text {
+        &self.text
+    }
+
+    fn text_mut(&mut self) -> &mut Text {
+        &mut self.text
+    }
+
+    fn once() {
+        form::set_weak("KeyHintsKey", Form::yellow());
+        form::set_weak("KeyHintsDesc", "Default");
+    }
+}
+
+/// Configuration options for the [`KeyHints`] widget
+pub struct KeyHintsCfg<U> {
+    specs: PushSpecs,
+    max_rows: usize,
+    ghost: PhantomData<U>,
+}
+
+impl<U> KeyHintsCfg<U> {
+    pub fn new() -> Self {
+        Self {
+            specs: PushSpecs::below().with_ver_len(1.0),
+            max_rows: 6,
+            ghost: PhantomData,
+        }
+    }
+
+    /// Pushes the [`KeyHints`] above, instead of below
+    pub fn above(self) -> Self {
+        Self {
+            specs: PushSpecs::above().with_ver_len(1.0),
+            ..self
+        }
+    }
+
+    /// The maximum number of rows of hints
+    ///
+    /// If the hints don't fit in this many rows, they are hidden.
+    /// The default is 6.
+    pub fn with_max_rows(self, max_rows: usize) -> Self {
+        Self { max_rows: max_rows.max(1), ..self }
+    }
+}
+
+impl<U> Default for KeyHintsCfg<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<U: Ui> WidgetCfg<U> for KeyHintsCfg<U> {
+    type Widget = KeyHints<U>;
+
+    fn build(self, _: bool) -> (Self::Widget, impl Fn() -> bool, PushSpecs) {
+        let hints = RoData::from(context::key_hints());
+
+        let widget = KeyHints {
+            hints: hints.clone(),
+            text: Text::new(),
+            max_rows: self.max_rows,
+            ghost: PhantomData,
+        };
+
+        (widget, move || hints.has_changed(), self.specs)
+    }
+}
+
+/// The width of a hint, in [`char`]s
+fn width(str: &str) -> usize {
+    str.chars().count()
+}
@@ -9,15 +9,19 @@
 //! of the numbers, with one more option to change that of the main
 //! cursor's line number.
 //!
+//! Relative numbers can also count the rows of wrapped lines, so they
+//! match how far vertical movement over wrapped lines goes.
+//!
 //! [`File`]: super::File
 use std::{fmt::Alignment, marker::PhantomData};
 
 use crate::{
     context::{self, FileReader},
     form::{self, Form},
+    mode::Cursors,
     text::{Builder, Tag, Text, text},
     ui::{Area, Constraint, PushSpecs, Ui},
-    widgets::{Widget, WidgetCfg},
+    widgets::{File, Widget, WidgetCfg, cache_key},
 };
 
 pub struct LineNumbers<U: Ui> {
@@ -35,13 +39,18 @@ impl<U: Ui> LineNumbers<U> {
     }
 
     fn update_text(&mut self) {
-        self.text = self.reader.inspect(|file, _, cursors| {
+        self.text = self.reader.inspect(|file, area, cursors| {
             let printed_lines = file.printed_lines();
             let main_line = if cursors.is_empty() {
                 u32::MAX
             } else {
                 cursors.main().line()
             };
+            let main_row = if self.cfg.count_wraps {
+                main_row::<U>(file, area, cursors)
+            } else {
+                None
+            };
 
             let mut builder = Text::builder();
             text!(builder, { tag_from_align(self.cfg.align) });
@@ -59,7 +68,8 @@ impl<U: Ui> LineNumbers<U> {
                 }
 
                 let is_wrapped = *is_wrapped && index > 0;
-                push_text(&mut builder, *line, main_line, is_wrapped, &self.cfg);
+                let rows = main_row.map(|main_row| (index as u32, main_row));
+                push_text(&mut builder, *line, main_line, is_wrapped, rows, &self.cfg);
 
                 if main_line == *line {
                     text!(builder, { tag_from_align(self.cfg.align) });
@@ -80,11 +90,32 @@ impl<U: Ui> Widget<U> for LineNumbers<U> {
 
     fn update(&mut self, area: &U::Area) {
         let width = self.calculate_width();
-        area.constrain_hor(Constraint::Length(width + 1.0)).unwrap();
+        // Floating areas have no constraints, so this can fail.
+        let _ = area.constrain_hor(Constraint::Length(width + 1.0));
 
         self.update_text();
     }
 
+    fn cache_key(&self, area: &U::Area) -> Option<u64> {
+        Some(self.reader.inspect(|file, file_area, cursors| {
+            let main_line = cursors.get_main().map(|main| main.line());
+            // Moving within a wrapped line changes the main row.
+            let main_row = if self.cfg.count_wraps {
+                main_row::<U>(file, file_area, cursors)
+            } else {
+                None
+            };
+            let lines = file.text().len().line();
+            cache_key((
+                file.printed_lines(),
+                main_line,
+                main_row,
+                lines,
+                area.height(),
+            ))
+        }))
+    }
+
     fn text(&self) -> &Text {
         &self.text
     }
@@ -121,6 +152,8 @@ pub struct LineNumbersCfg<U> {
     align: Alignment,
     main_align: Alignment,
     show_wraps: bool,
+    count_wraps: bool,
+    wrap_marker: Option<char>,
     specs: PushSpecs,
     ghost: PhantomData<U>,
 }
@@ -138,6 +171,8 @@ impl<U> LineNumbersCfg<U> {
             align: Alignment::Left,
             main_align: Alignment::Right,
             show_wraps: false,
+            count_wraps: false,
+            wrap_marker: None,
             specs: PushSpecs::left(),
             ghost: PhantomData,
         }
@@ -199,6 +234,31 @@ impl<U> LineNumbersCfg<U> {
         Self { show_wraps: false, ..self }
     }
 
+    /// Makes relative numbers count wrapped rows, not just lines
+    ///
+    /// With this, the number on each row is how many rows away it is
+    /// from the main cursor, which is what vertical movement over
+    /// wrapped lines goes by. When the main cursor is off screen,
+    /// lines are counted instead.
+    ///
+    /// This only affects [`relative`] and [`rel_abs`] numbers.
+    ///
+    /// [`relative`]: Self::relative
+    /// [`rel_abs`]: Self::rel_abs
+    pub fn count_wraps(self) -> Self {
+        Self { count_wraps: true, ..self }
+    }
+
+    /// Shows a [`char`] on the wrapped rows of a line
+    ///
+    /// This is shown instead of an empty row, so it has no effect if
+    /// [`show_wraps`] is set.
+    ///
+    /// [`show_wraps`]: Self::show_wraps
+    pub fn with_wrap_marker(self, marker: char) -> Self {
+        Self { wrap_marker: Some(marker), ..self }
+    }
+
     pub fn on_the_right(self) -> Self {
         Self { specs: self.specs.to_right(), ..self }
     }
@@ -228,17 +288,26 @@ fn push_text<U>(
     line: u32,
     main: u32,
     is_wrapped: bool,
+    rows: Option<(u32, u32)>,
     cfg: &LineNumbersCfg<U>,
 ) {
     if is_wrapped && !cfg.show_wraps {
-        text!(*builder, "\n");
+        match cfg.wrap_marker {
+            Some(marker) => text!(*builder, marker "\n"),
+            None => text!(*builder, "\n"),
+        }
     } else if main != u32::MAX {
+        // With wraps counted, the distance is in rows, not in lines.
+        let (is_main, dist) = match rows {
+            Some((row, main_row)) => (row == main_row, row.abs_diff(main_row)),
+            None => (line == main, line.abs_diff(main)),
+        };
         let num = match cfg.numbers {
             Numbers::Absolute => line + 1,
-            Numbers::Relative => line.abs_diff(main),
+            Numbers::Relative => dist,
             Numbers::RelAbs => {
-                if line != main {
-                    line.abs_diff(main)
+                if !is_main {
+                    dist
                 } else {
                     line + 1
                 }
@@ -251,6 +320,17 @@ fn push_text<U>(
     }
 }
 
+/// The row of the main cursor, counting from the top of the [`File`]
+///
+/// Returns [`None`] if the main cursor is not on screen.
+fn main_row<U: Ui>(file: &File, area: &U::Area, cursors: &Cursors) -> Option<u32> {
+    let main = cursors.get_main()?;
+    let cfg = file.print_cfg();
+    let (_, y) = area.coord_of_point(file.text(), main.caret(), cfg)?;
+    let ((_, top), _) = area.bounds();
+    Some(y - top - cfg.padding.top())
+}
+
 fn tag_from_align(alignment: Alignment) -> Tag {
     match alignment {
         Alignment::Left => Tag::StartAlignLeft,
@@ -44,14 +44,24 @@
 //! [`OnFileOpen`]: crate::hooks::OnFileOpen
 //! [`OnWindowOpen`]: crate::hooks::OnWindowOpen
 //! [`Constraint`]: crate::ui::Constraint
-use std::sync::{
-    Arc,
-    atomic::{AtomicBool, Ordering},
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
+use parking_lot::Mutex;
+
 pub use self::{
-    command_line::{CmdLine, CmdLineCfg, CmdLineMode, IncSearch, RunCommands, ShowNotifications},
-    file::{File, FileCfg},
+    command_line::{
+        CmdLine, CmdLineCfg, CmdLineMode, IncSearch, OnUnfocus, RunCommands, ShowNotifications,
+    },
+    diff_gutter::{DiffGutter, DiffGutterCfg},
+    file::{File, FileCfg, FileInfo, Vars},
+    filter_list::FilterList,
+    key_hints::{KeyHints, KeyHintsCfg},
     line_numbers::{LineNumbers, LineNumbersCfg},
     status_line::{State, StatusLine, StatusLineCfg, common, status},
 };
@@ -60,14 +70,17 @@ use crate::{
     context::FileParts,
     data::{Data, RwData},
     form,
-    hooks::{self, FocusedOn, UnfocusedFrom},
-    mode::Cursors,
+    hooks::{self, FocusedOn, OnFirstPrint, UnfocusedFrom},
+    mode::{Cursors, MouseEvent},
     text::Text,
     ui::{Area, PushSpecs, Ui},
 };
 
 mod command_line;
+mod diff_gutter;
 mod file;
+mod filter_list;
+mod key_hints;
 mod line_numbers;
 mod status_line;
 
@@ -356,6 +369,23 @@ where
     /// [more or less width]: Area::constrain_hor
     fn update(&mut self, _area: &U::Area) {}
 
+    /// A key for everything that [`update`] depends on
+    ///
+    /// If this returns [`Some`], and the key is the same as the one
+    /// from the last update, [`update`] is skipped entirely, and the
+    /// [`Text`] is only reprinted if the [`Area`] has changed. This
+    /// is meant for passive widgets, like [`LineNumbers`], whose
+    /// [`Text`] would be rebuilt identically most of the time.
+    ///
+    /// You can build the key from any [hashable] value with
+    /// [`cache_key`].
+    ///
+    /// [`update`]: Widget::update
+    /// [hashable]: Hash
+    fn cache_key(&self, _area: &U::Area) -> Option<u64> {
+        None
+    }
+
     /// The text that this widget prints out
     fn text(&self) -> &Text;
 
@@ -372,6 +402,17 @@ where
     #[allow(unused)]
     fn on_unfocus(&mut self, area: &U::Area) {}
 
+    /// Actions to do when a [`MouseEvent`] happens on this
+    /// [`Widget`], while it is not the active one
+    ///
+    /// [`MouseEvent`]s on the active widget are sent to the active
+    /// [`Mode`] instead, through [`Mode::send_mouse`].
+    ///
+    /// [`Mode`]: crate::mode::Mode
+    /// [`Mode::send_mouse`]: crate::mode::Mode::send_mouse
+    #[allow(unused)]
+    fn on_mouse(&mut self, event: MouseEvent, area: &U::Area) {}
+
     /// The [configuration] for how to print [`Text`]
     ///
     /// The default configuration, used when `print_cfg` is not
@@ -404,6 +445,16 @@ where
         Self: Sized;
 }
 
+/// Hashes a value into a key for [`Widget::cache_key`]
+///
+/// Tuples of the relevant inputs are the usual thing to pass here,
+/// like `(text.revision(), area.height())`.
+pub fn cache_key(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
 /// A configuration struct for a [`Widget`]
 ///
 /// This configuration is used to make adjustments on how a widget
@@ -440,15 +491,19 @@ where
 // Elements related to the [`Widget`]s
 pub struct Node<U: Ui> {
     widget: RwData<dyn Widget<U>>,
-    area: U::Area,
-    cursors: RwData<Cursors>,
+    view: View<U>,
 
     checker: Arc<dyn Fn() -> bool>,
     busy_updating: Arc<AtomicBool>,
+    has_printed: Arc<AtomicBool>,
+    last_print: Arc<Mutex<Option<(u64, <U::Area as Area>::PrintInfo)>>>,
+    last_key: Arc<Mutex<Option<u64>>>,
 
-    related_widgets: Option<RwData<Vec<Node<U>>>>,
+    related_widgets: Option<RelatedWidgets<U>>,
+    widget_name: &'static str,
     on_focus: fn(&Node<U>),
     on_unfocus: fn(&Node<U>),
+    on_first_print: fn(&Node<U>),
 }
 
 impl<U: Ui> Node<U> {
@@ -460,22 +515,31 @@ impl<U: Ui> Node<U> {
         let (cursors, related_widgets) = widget
             .inspect_as(|file: &File| {
                 let cursors = crate::cache::load_cache::<Cursors>(file.path());
-                let related = RwData::default();
+                let related = RelatedWidgets::default();
                 (cursors.unwrap_or(Cursors::new_excl()), related)
             })
             .unzip();
 
+        let widget_name = crate::duat_name::<W>();
+        if crate::toggle::starts_hidden(widget_name) {
+            let _ = area.hide();
+        }
+
         Self {
             widget,
-            area,
-            cursors: RwData::new(cursors.unwrap_or_default()),
+            view: View::new(area, RwData::new(cursors.unwrap_or_default())),
 
             checker: Arc::new(checker),
             busy_updating: Arc::new(AtomicBool::new(false)),
+            has_printed: Arc::new(AtomicBool::new(false)),
+            last_print: Arc::new(Mutex::new(None)),
+            last_key: Arc::new(Mutex::new(None)),
 
             related_widgets,
+            widget_name,
             on_focus: Self::on_focus_fn::<W>,
             on_unfocus: Self::on_unfocus_fn::<W>,
+            on_first_print: Self::on_first_print_fn::<W>,
         }
     }
 
@@ -492,14 +556,76 @@ impl<U: Ui> Node<U> {
         self.widget.data_is::<W>()
     }
 
+    /// The name of the type of this [`Node`]'s [`Widget`]
+    pub fn widget_name(&self) -> &'static str {
+        self.widget_name
+    }
+
     pub fn update_and_print(&self) {
+        self.update_and_print_inner(true)
+    }
+
+    /// Updates the [`Widget`], only printing it if it has changed
+    ///
+    /// The [`Widget`] is considered changed if the [revision] of its
+    /// [`Text`] or its [`PrintInfo`] are different from the ones of
+    /// the last print, or if its [`Area`] has changed.
+    ///
+    /// [revision]: Text::revision
+    /// [`PrintInfo`]: Area::PrintInfo
+    pub fn update_and_print_if_changed(&self) {
+        self.update_and_print_inner(false)
+    }
+
+    fn update_and_print_inner(&self, force: bool) {
+        // Hidden Widgets keep their state until they are revealed.
+        if self.view.area.is_hidden() {
+            return;
+        }
         self.busy_updating.store(true, Ordering::Release);
 
+        // Read before the Widget is locked, like when sending keys.
+        let main = self
+            .view
+            .cursors
+            .raw_read()
+            .get_main()
+            .map(|main| main.caret());
+
         let mut widget = self.widget.raw_write();
-        widget.update(&self.area);
-        widget.print(&self.area);
+        self.update_unless_cached(&mut *widget);
+
+        let mut last_print = self.last_print.lock();
+        let text_changed = last_print
+            .as_ref()
+            .is_none_or(|(revision, _)| *revision != widget.text().revision());
+        let area_changed = self.view.area.has_changed();
+
+        // Files are scrolled as they are edited, and can be scrolled away
+        // from their Cursors, but other widgets should always follow them.
+        if let Some(main) = main
+            && (text_changed || area_changed)
+            && !self.widget.data_is::<File>()
+        {
+            let cfg = widget.print_cfg();
+            self.view.area.scroll_around_point(widget.text(), main, cfg);
+        }
+
+        let is_unchanged = last_print.as_ref().is_some_and(|(revision, info)| {
+            *revision == widget.text().revision() && *info == self.view.area.print_info()
+        });
+
+        if force || !is_unchanged || area_changed {
+            widget.print(&self.view.area);
+            *last_print = Some((widget.text().revision(), self.view.area.print_info()));
+        }
+        drop((widget, last_print));
 
         self.busy_updating.store(false, Ordering::Release);
+
+        if !self.has_printed.swap(true, Ordering::AcqRel) {
+            (self.on_first_print)(self)
+        }
     }
 
     pub fn inspect_as<W: 'static, B>(&self, f: impl FnOnce(&W) -> B) -> Option<B> {
@@ -515,82 +641,120 @@ impl<U: Ui> Node<U> {
     }
 
     pub fn needs_update(&self) -> bool {
-        if !self.busy_updating.load(Ordering::Acquire) {
-            (self.checker)() || self.area.has_changed()
+        if !self.busy_updating.load(Ordering::Acquire) && !self.view.area.is_hidden() {
+            (self.checker)() || self.view.area.has_changed()
         } else {
             false
         }
     }
 
     pub(crate) fn update(&self) {
-        self.widget.raw_write().update(&self.area)
+        self.update_unless_cached(&mut *self.widget.raw_write())
+    }
+
+    /// Updates the [`Widget`], unless its [`cache_key`] hasn't
+    /// changed
+    ///
+    /// [`cache_key`]: Widget::cache_key
+    fn update_unless_cached(&self, widget: &mut dyn Widget<U>) {
+        let key = widget.cache_key(&self.view.area);
+        let mut last_key = self.last_key.lock();
+
+        if key.is_none() || *last_key != key {
+            widget.update(&self.view.area);
+            *last_key = key;
+        }
     }
 
     pub(crate) fn as_active(&self) -> (&RwData<dyn Widget<U>>, &U::Area, &RwData<Cursors>) {
         // Since this function is only ever used on widgets that became active
         // via `command::set_mode`, technically speaking, every widget is
         // active, so no need to return an `Option`.
-        (&self.widget, &self.area, &self.cursors)
+        (&self.widget, &self.view.area, &self.view.cursors)
     }
 
     pub(crate) fn as_file(&self) -> Option<FileParts<U>> {
         self.widget.try_downcast().map(|file| {
             (
                 file,
-                self.area.clone(),
-                self.cursors.clone(),
+                self.view.clone(),
                 self.related_widgets.clone().unwrap(),
             )
         })
     }
 
     pub(crate) fn on_focus(&self) {
-        self.area.set_as_active();
+        self.view.restore_print_info();
+        self.view.area.set_as_active();
         (self.on_focus)(self)
     }
 
     pub(crate) fn on_unfocus(&self) {
+        self.view.store_print_info();
         (self.on_unfocus)(self)
     }
 
+    pub(crate) fn on_mouse(&self, event: MouseEvent) {
+        self.widget.write().on_mouse(event, &self.view.area)
+    }
+
     pub(crate) fn raw_inspect<B>(&self, f: impl FnOnce(&dyn Widget<U>) -> B) -> B {
         let widget = self.widget.raw_read();
         f(&*widget)
     }
 
     pub(crate) fn area(&self) -> &U::Area {
-        &self.area
+        &self.view.area
+    }
+
+    /// The [`View`] of this [`Node`]
+    pub fn view(&self) -> &View<U> {
+        &self.view
     }
 
-    pub(crate) fn related_widgets(&self) -> Option<&RwData<Vec<Node<U>>>> {
+    /// The [`RelatedWidgets`] of this [`Node`], if it is a [`File`]
+    pub fn related_widgets(&self) -> Option<&RelatedWidgets<U>> {
         self.related_widgets.as_ref()
     }
 
     fn on_focus_fn<W: Widget<U>>(&self) {
-        self.cursors.inspect(|c| {
+        self.view.cursors.inspect(|c| {
             let mut widget = self.widget.write();
             let cfg = widget.print_cfg();
-            widget.text_mut().remove_cursors(c, &self.area, cfg);
-            widget.on_focus(&self.area);
+            widget.text_mut().remove_cursors(c, &self.view.area, cfg);
+            widget.on_focus(&self.view.area);
         });
 
-        self.area.set_as_active();
+        self.view.area.set_as_active();
         let widget = self.widget.try_downcast().unwrap();
 
-        hooks::trigger::<FocusedOn<W, U>>((widget, self.area.clone(), self.cursors.clone()));
+        hooks::trigger::<FocusedOn<W, U>>((
+            widget,
+            self.view.area.clone(),
+            self.view.cursors.clone(),
+        ));
     }
 
     fn on_unfocus_fn<W: Widget<U>>(&self) {
-        self.cursors.inspect(|c| {
+        self.view.cursors.inspect(|c| {
             let mut widget = self.widget.write();
             let cfg = widget.print_cfg();
-            widget.text_mut().remove_cursors(c, &self.area, cfg);
-            widget.on_unfocus(&self.area);
+            widget.text_mut().remove_cursors(c, &self.view.area, cfg);
+            widget.on_unfocus(&self.view.area);
         });
 
         let widget = self.widget.try_downcast().unwrap();
 
-        hooks::trigger::<UnfocusedFrom<W, U>>((widget, self.area.clone(), self.cursors.clone()));
+        hooks::trigger::<UnfocusedFrom<W, U>>((
+            widget,
+            self.view.area.clone(),
+            self.view.cursors.clone(),
+        ));
+    }
+
+    fn on_first_print_fn<W: Widget<U>>(&self) {
+        let widget = self.widget.try_downcast().unwrap();
+        hooks::trigger::<OnFirstPrint<W, U>>((widget, self.view.area.clone()));
     }
 }
 
@@ -598,13 +762,177 @@ impl<U: Ui> Clone for Node<U> {
     fn clone(&self) -> Self {
         Self {
             widget: self.widget.clone(),
-            area: self.area.clone(),
-            cursors: self.cursors.clone(),
+            view: self.view.clone(),
             checker: self.checker.clone(),
             busy_updating: self.busy_updating.clone(),
+            has_printed: self.has_printed.clone(),
+            last_print: self.last_print.clone(),
+            last_key: self.last_key.clone(),
             related_widgets: self.related_widgets.clone(),
+            widget_name: self.widget_name,
             on_focus: self.on_focus,
             on_unfocus: self.on_unfocus,
+            on_first_print: self.on_first_print,
+        }
+    }
+}
+
+/// The [`Widget`]s that were pushed around a [`File`]
+///
+/// Whenever a [`Widget`] is pushed to a [`File`], like through the
+/// [`OnFileOpen`] hook, it is registered here, in the order in which
+/// it was pushed. It is unregistered if its [`Area`] is removed.
+///
+/// This is what determines which [`Widget`] is affected by commands
+/// added with [`cmd::add_for`], which will always pick the first one
+/// of the given type that was pushed to the active [`File`].
+///
+/// [`OnFileOpen`]: crate::hooks::OnFileOpen
+/// [`cmd::add_for`]: crate::cmd::add_for
+pub struct RelatedWidgets<U: Ui>(RwData<Vec<Node<U>>>);
+
+impl<U: Ui> RelatedWidgets<U> {
+    /// The first related [`Widget`] of type `W`
+    pub fn get<W: Widget<U>>(&self) -> Option<RwData<W>> {
+        self.0.read().iter().find_map(Node::try_downcast)
+    }
+
+    /// Every related [`Widget`] of type `W`, in the order they were
+    /// pushed
+    pub fn get_all<W: Widget<U>>(&self) -> Vec<RwData<W>> {
+        self.0
+            .read()
+            .iter()
+            .filter_map(Node::try_downcast)
+            .collect()
+    }
+
+    /// Every related [`Node`], in the order they were pushed
+    pub fn nodes(&self) -> Vec<Node<U>> {
+        self.0.read().clone()
+    }
+
+    /// The names of the related [`Widget`]s' types
+    pub fn names(&self) -> Vec<&'static str> {
+        self.0.read().iter().map(Node::widget_name).collect()
+    }
+
+    /// How many [`Widget`]s are related
+    pub fn len(&self) -> usize {
+        self.0.read().len()
+    }
+
+    /// Whether there are no related [`Widget`]s
+    pub fn is_empty(&self) -> bool {
+        self.0.read().is_empty()
+    }
+
+    /// The first related [`Node`] with a [`Widget`] of type `W`
+    pub(crate) fn node_of<W: 'static>(&self) -> Option<Node<U>> {
+        self.0
+            .read()
+            .iter()
+            .find(|node| node.data_is::<W>())
+            .cloned()
+    }
+
+    /// Registers a newly pushed [`Node`]
+    pub(crate) fn register(&self, node: Node<U>) {
+        self.0.write().push(node)
+    }
+
+    /// Unregisters the [`Node`] in an [`Area`], returning whether
+    /// there was one
+    pub(crate) fn unregister(&self, area: &U::Area) -> bool {
+        let mut nodes = self.0.write();
+        let len = nodes.len();
+        nodes.retain(|node| node.area() != area);
+        nodes.len() < len
+    }
+}
+
+impl<U: Ui> Clone for RelatedWidgets<U> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<U: Ui> Default for RelatedWidgets<U> {
+    fn default() -> Self {
+        Self(RwData::default())
+    }
+}
+
+/// The way that a [`Widget`] is being viewed in an [`Area`]
+///
+/// A [`View`] owns the [`Cursors`] and [`PrintInfo`] of a binding
+/// between a [`Widget`] and an [`Area`]. This means that, if the same
+/// [`Widget`] were to be shown in more than one [`Area`], each one of
+/// them would have its own [`Cursors`] and its own scrolling state,
+/// while still sharing the same [`Text`].
+///
+/// [`Mode`]s can access the [`View`] of the focused [`Area`] through
+/// [`context::cur_view`].
+///
+/// [`PrintInfo`]: Area::PrintInfo
+/// [`Mode`]: crate::mode::Mode
+/// [`context::cur_view`]: crate::context::cur_view
+pub struct View<U: Ui> {
+    pub(crate) area: U::Area,
+    pub(crate) cursors: RwData<Cursors>,
+    info: RwData<Option<<U::Area as Area>::PrintInfo>>,
+}
+
+impl<U: Ui> View<U> {
+    /// Returns a new [`View`] with the given [`Area`] and [`Cursors`]
+    pub(crate) fn new(area: U::Area, cursors: RwData<Cursors>) -> Self {
+        Self { area, cursors, info: RwData::new(None) }
+    }
+
+    /// The [`Area`] that this [`View`] is bound to
+    pub fn area(&self) -> &U::Area {
+        &self.area
+    }
+
+    /// The [`Cursors`] of this [`View`]
+    pub fn cursors(&self) -> &RwData<Cursors> {
+        &self.cursors
+    }
+
+    /// The current [`PrintInfo`] of this [`View`]
+    ///
+    /// [`PrintInfo`]: Area::PrintInfo
+    pub fn print_info(&self) -> <U::Area as Area>::PrintInfo {
+        self.area.print_info()
+    }
+
+    /// Stores the [`PrintInfo`] of the [`Area`] in this [`View`]
+    ///
+    /// This is done when the [`View`] is unfocused, so it can later
+    /// be restored, even if the [`Area`] was used by another [`View`]
+    /// in the meantime.
+    ///
+    /// [`PrintInfo`]: Area::PrintInfo
+    pub(crate) fn store_print_info(&self) {
+        *self.info.write() = Some(self.area.print_info());
+    }
+
+    /// Restores the previously stored [`PrintInfo`], if there is one
+    ///
+    /// [`PrintInfo`]: Area::PrintInfo
+    pub(crate) fn restore_print_info(&self) {
+        if let Some(info) = self.info.read().clone() {
+            self.area.set_print_info(info);
+        }
+    }
+}
+
+impl<U: Ui> Clone for View<U> {
+    fn clone(&self) -> Self {
+        Self {
+            area: self.area.clone(),
+            cursors: self.cursors.clone(),
+            info: self.info.clone(),
         }
     }
 }
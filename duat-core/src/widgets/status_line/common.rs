@@ -8,17 +8,23 @@
 //! formatted version of the main [`Cursor`], and [`mode_fmt`] which
 //! will show a formatted version of the current [`Mode`] of Duat.
 //!
+//! Other functions, like [`window_fmt`] and [`area_size`], show
+//! information about the layout, without needing to poke at the
+//! [`Ui`].
+//!
 //! [`StatusLine`]: super::StatusLine
 //! [`status!`]: super::status
 //! [`Cursor`]: crate::mode::Cursor
 //! [`Mode`]: crate::mode::Mode
 use crossterm::event::KeyEvent;
+use parking_lot::Mutex;
 
 use crate::{
     context,
     data::DataMap,
     mode::{self, Cursors},
     text::{Text, text},
+    ui::{Area, Axis, Ui},
     widgets::File,
 };
 
@@ -38,6 +44,47 @@ pub fn mode_fmt() -> DataMap<&'static str, Text> {
     context::mode_name().map(|mode| text!([Mode] { mode.to_lowercase() }))
 }
 
+/// A variable local to the [`File`], shown as a [`String`]
+///
+/// If the variable doesn't exist, or [can't be shown], nothing is
+/// shown.
+///
+/// [can't be shown]: crate::widgets::Vars::display
+pub fn var(key: &'static str) -> impl Fn(&File) -> String + Send + Sync + 'static {
+    move |file| file.vars().display(key).unwrap_or_default()
+}
+
+/// The size of the [`File`] on disk, like `"3.4 KiB"`
+///
+/// If the [`File`] isn't on disk, nothing is shown.
+pub fn file_size(file: &File) -> String {
+    file.info().size_fmt().unwrap_or_default()
+}
+
+/// The permissions of the [`File`] on disk, like `"rw-r--r--"`
+///
+/// If the [`File`] isn't on disk, nothing is shown.
+pub fn file_perms(file: &File) -> String {
+    file.info().permissions().unwrap_or_default().to_string()
+}
+
+/// When the [`File`] was last modified, in UTC
+///
+/// If the [`File`] isn't on disk, nothing is shown.
+pub fn file_mtime(file: &File) -> String {
+    file.info().modified_fmt().unwrap_or_default()
+}
+
+/// The encoding of the [`File`]'s contents, like `"utf-8"`
+pub fn file_encoding(file: &File) -> &'static str {
+    file.info().encoding()
+}
+
+/// The line ending of the [`File`]'s contents, `"lf"` or `"crlf"`
+pub fn line_ending(file: &File) -> &'static str {
+    file.info().line_ending()
+}
+
 /// The byte of the main cursor in the file. Indexed at 1
 pub fn main_byte(cursors: &Cursors) -> u32 {
     cursors.get_main().unwrap_or_default().byte()
@@ -116,3 +163,145 @@ pub fn cur_map_fmt() -> DataMap<(Vec<KeyEvent>, bool), Text> {
         }
     })
 }
+
+/// The index of the current window. Indexed at 1
+///
+/// Unlike most other parts, this is a reader and checker pair, since
+/// it doesn't depend on the [`File`].
+pub fn window_index() -> (
+    impl Fn() -> usize + Send + Sync + 'static,
+    impl Fn() -> bool + Send + Sync + 'static,
+) {
+    let reader = || context::cur_window() + 1;
+    (reader, changed_by(reader))
+}
+
+/// The number of open windows
+pub fn window_count<U: Ui>() -> (
+    impl Fn() -> usize + Send + Sync + 'static,
+    impl Fn() -> bool + Send + Sync + 'static,
+) {
+    let reader = || {
+        context::windows::<U>()
+            .try_inspect(|windows| windows.len())
+            .unwrap_or(1)
+    };
+    (reader, changed_by(reader))
+}
+
+/// The current window, and the number of windows, formatted
+///
+/// # Formatting
+///
+/// ```text
+/// [Window] index [Separator] "/" [Window] count
+/// ```
+pub fn window_fmt<U: Ui>() -> (
+    impl Fn() -> Text + Send + Sync + 'static,
+    impl Fn() -> bool + Send + Sync + 'static,
+) {
+    let (index, index_changed) = window_index();
+    let (count, count_changed) = window_count::<U>();
+    let reader = move || text!([Window] { index() } [Separator] "/" [Window] { count() });
+    // Not short circuiting, so both checkers stay up to date.
+    (reader, move || index_changed() | count_changed())
+}
+
+/// The width and height of the current [`File`]'s [`Area`]
+///
+/// # Formatting
+///
+/// ```text
+/// width "x" height
+/// ```
+pub fn area_size<U: Ui>() -> (
+    impl Fn() -> String + Send + Sync + 'static,
+    impl Fn() -> bool + Send + Sync + 'static,
+) {
+    let size = || {
+        context::cur_file::<U>()
+            .map(|cur_file| cur_file.inspect(|_, area, _| (area.width(), area.height())))
+            .unwrap_or_default()
+    };
+    let reader = move || {
+        let (width, height) = size();
+        format!("{width}x{height}")
+    };
+    (reader, changed_by(size))
+}
+
+/// How far the [`File`] is scrolled
+///
+/// This is `"All"` if the whole [`File`] is shown, `"Top"` or
+/// `"Bot"` if its start or end are shown, and a percentage of the
+/// lines above the screen otherwise.
+pub fn scroll_pct(file: &File) -> String {
+    let lines = file.printed_lines();
+    let (Some((first, _)), Some((last, _))) = (lines.first(), lines.last()) else {
+        return "All".to_string();
+    };
+    let last_line = file.len_lines().saturating_sub(1);
+
+    match (*first == 0, *last >= last_line) {
+        (true, true) => "All".to_string(),
+        (true, false) => "Top".to_string(),
+        (false, true) => "Bot".to_string(),
+        (false, false) => format!("{}%", *first as usize * 100 / file.len_lines() as usize),
+    }
+}
+
+/// The [`Axis`] along which the current [`File`] was split from its
+/// neighbours, in lowercase
+///
+/// If the [`File`] is alone in its window, nothing is shown.
+pub fn split_axis<U: Ui>() -> (
+    impl Fn() -> &'static str + Send + Sync + 'static,
+    impl Fn() -> bool + Send + Sync + 'static,
+) {
+    let reader = || {
+        let Ok(cur_file) = context::cur_file::<U>() else {
+            return "";
+        };
+        cur_file.inspect(|_, area, _| match parent_axis(area) {
+            Some(Axis::Horizontal) => "horizontal",
+            Some(Axis::Vertical) => "vertical",
+            None => "",
+        })
+    };
+    (reader, changed_by(reader))
+}
+
+/// The [`Axis`] of the parent of an [`Area`]'s cluster, if it was
+/// split with another [`File`]
+///
+/// The cluster of a [`File`] holds the [`Widget`]s pushed to it, so
+/// it's the cluster that was split from other [`File`]s.
+///
+/// [`Widget`]: crate::widgets::Widget
+fn parent_axis<A: Area + Clone + PartialEq>(area: &A) -> Option<Axis> {
+    let master = area.get_cluster_master().unwrap_or_else(|| area.clone());
+    let tree = master.layout_tree();
+    let i = tree.iter().position(|node| node.area == master)?;
+    let depth = tree[i].depth.checked_sub(1)?;
+
+    tree[..i]
+        .iter()
+        .rev()
+        .find(|node| node.depth == depth)
+        .and_then(|parent| parent.axis)
+}
+
+/// A checker that returns `true` whenever the value returned by `f`
+/// changes
+fn changed_by<T: PartialEq + Send + 'static>(
+    f: impl Fn() -> T + Send + Sync + 'static,
+) -> impl Fn() -> bool + Send + Sync + 'static {
+    let last = Mutex::new(None);
+    move || {
+        let value = Some(f());
+        let mut last = last.lock();
+        let has_changed = *last != value;
+        *last = value;
+        has_changed
+    }
+}
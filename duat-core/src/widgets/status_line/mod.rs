@@ -216,6 +216,7 @@ impl<U: Ui> Widget<U> for StatusLine<U> {
         form::set_weak("Coord", Form::dark_yellow());
         form::set_weak("Separator", Form::cyan());
         form::set_weak("Mode", Form::green());
+        form::set_weak("Window", Form::dark_yellow());
     }
 
     fn print_cfg(&self) -> PrintCfg {
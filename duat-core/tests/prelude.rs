@@ -0,0 +1,121 @@
+//! Pins the API exported by [`duat_core::prelude`]
+//!
+//! Everything in here only has to compile. If a signature of an
+//! item in the prelude changes, this file stops compiling, which
+//! means that the change is a breaking one, and should be kept for
+//! a breaking release.
+use std::time::Duration;
+
+use duat_core::prelude::*;
+
+#[allow(dead_code)]
+struct Pinned;
+
+impl<U: Ui> Plugin<U> for Pinned {
+    type Cache = ();
+
+    fn new(_cache: Self::Cache) -> Self {
+        Pinned
+    }
+}
+
+#[derive(Clone)]
+struct PinnedMode;
+
+impl<U: Ui> Mode<U> for PinnedMode {
+    type Widget = File;
+
+    fn send_key(
+        &mut self,
+        _key: KeyEvent,
+        _widget: &RwData<Self::Widget>,
+        _area: &U::Area,
+        _cursors: &mut Cursors,
+    ) {
+    }
+
+    fn on_switch(
+        &mut self,
+        _widget: &RwData<Self::Widget>,
+        _area: &U::Area,
+        _cursors: &mut Cursors,
+    ) {
+    }
+}
+
+struct PinnedHook;
+
+impl Hookable for PinnedHook {
+    type Args = ();
+}
+
+#[allow(dead_code)]
+fn pinned_traits<U: Ui>() {
+    fn is_mode<U: Ui, M: Mode<U>>() {}
+    fn is_widget<U: Ui, W: Widget<U>>() {}
+    fn is_cfg<U: Ui, C: WidgetCfg<U>>() {}
+    fn is_keys<K: Keys>() {}
+    fn is_two_points<T: TwoPoints>() {}
+
+    is_mode::<U, PinnedMode>();
+    is_widget::<U, File>();
+    is_cfg::<U, <File as Widget<U>>::Cfg>();
+    is_keys::<Key>();
+    is_keys::<std::ops::Range<Key>>();
+    is_two_points::<Point>();
+    is_two_points::<(Point, Option<Point>)>();
+
+    let _: fn(&File) -> &Text = <File as Widget<U>>::text;
+    let _: fn(&mut File) -> &mut Text = <File as Widget<U>>::text_mut;
+    let _: fn(&mut File, &U::Area) = <File as Widget<U>>::update;
+}
+
+#[allow(dead_code)]
+fn pinned_data() {
+    let _: fn(usize) -> RwData<usize> = RwData::new;
+    let _: fn(&RwData<usize>) -> bool = RwData::has_changed;
+    let _: fn(&RoData<usize>) -> bool = RoData::has_changed;
+}
+
+#[allow(dead_code)]
+fn pinned_text() {
+    let _: fn() -> Text = Text::new;
+    let _: fn(&Text) -> Point = Text::len;
+    let _: fn(&Text) -> bool = Text::is_empty;
+    let _: fn(&Text) -> [&str; 2] = Text::strs;
+    let _: fn(&Text, u32) -> Point = Text::point_at;
+    let _: fn(&mut Text, u32, Tag, Key) = Text::insert_tag;
+    let _: fn(&mut Text, u32, Key) = Text::remove_tags_on;
+    let _: fn(&mut Text, (Point, Point), String) = Text::replace_range;
+    let _: fn() -> Key = Key::new;
+    let _: fn() -> Builder = Builder::new;
+
+    let _: Text = text!("pinned" [Default] 1);
+    let _: CmdResult = ok!("pinned");
+    let _: Text = err!("pinned");
+    let _: Text = hint!("pinned");
+}
+
+#[allow(dead_code, clippy::result_large_err)]
+fn pinned_globals() {
+    let _: fn(String) -> duat_core::Result<Option<Text>, ()> = cmd::run;
+    let _: fn(i32) = cmd::cquit;
+    let _: fn(&'static str) = hooks::remove;
+    let _: fn(()) = hooks::trigger::<PinnedHook>;
+    let _: fn(&'static str) -> bool = hooks::group_exists;
+    let _: fn(String, Form) -> FormId = form::set;
+    let _: fn(FormId) -> Form = form::from_id;
+    let _: fn(CursorShape) = form::set_main_cursor;
+    let _: fn(Text) = context::notify;
+    let _: fn(Text, Duration) = context::echo_for;
+
+    hooks::add::<PinnedHook>(|_: &()| {});
+    cmd::add(["pinned"], |_: Flags, _: Args| -> CmdResult { Ok(None) }).unwrap();
+    let _: fn() -> When = When::anywhere;
+    let _: fn() -> PushSpecs = PushSpecs::left;
+}
+
+#[allow(dead_code)]
+fn pinned_keys(key: KeyEvent) -> bool {
+    matches!(key, key!(KeyCode::Char('p'), KeyMod::CONTROL))
+}
@@ -4,25 +4,27 @@ use std::{ops::RangeInclusive, sync::LazyLock};
 
 use duat_core::{
     cfg::WordChars,
-    cmd, context,
+    clip_history, cmd, context,
     data::{RwData, RwLock},
     mode::{
-        self, Cursors, EditHelper, ExtendFwd, ExtendRev, Fwd, IncSearcher, KeyCode::*,
-        KeyEvent as Event, KeyMod as Mod, Mode, Rev, key,
+        self, CharCategory, Cursors, EditHelper, ExtendFwd, ExtendRev, Fwd, IncSearcher,
+        KeyCode::*, KeyEvent as Event, KeyMod as Mod, Mode, Rev, key,
     },
+    registers,
     text::{Point, err},
     ui::{Area, Ui},
     widgets::{File, IncSearch, RunCommands},
 };
 
 const ALTSHIFT: Mod = Mod::ALT.union(Mod::SHIFT);
+const BRACKETS: &str = "[(){}\\[\\]]";
 
 #[derive(Clone)]
-pub struct Normal(SelType);
+pub struct Normal(SelType, char);
 
 impl Normal {
     pub fn new() -> Self {
-        Normal(SelType::Normal)
+        Normal(SelType::Normal, registers::DEFAULT)
     }
 }
 
@@ -39,6 +41,7 @@ impl<U: Ui> Mode<U> for Normal {
         cursors.make_incl();
         let mut helper = EditHelper::new(widget, area, cursors);
         let w_chars = helper.cfg().word_chars;
+        let reg = std::mem::replace(&mut self.1, registers::DEFAULT);
 
         if let key!(Char('h' | 'j' | 'k' | 'l' | 'w' | 'b' | 'e') | Down | Up) = key {
             helper.move_each(|mut m| m.unset_anchor())
@@ -150,7 +153,7 @@ impl<U: Ui> Mode<U> for Normal {
                 let init = no_nl_windows(m.iter()).next();
 
                 if let Some(((p0, c0), (p1, c1))) = init {
-                    if Category::of(c0, w_chars) == Category::of(c1, w_chars) {
+                    if CharCategory::of(c0, w_chars) == CharCategory::of(c1, w_chars) {
                         m.move_to(p0);
                     } else {
                         m.move_to(p1);
@@ -171,7 +174,7 @@ impl<U: Ui> Mode<U> for Normal {
                 let init = no_nl_windows(m.iter()).next();
 
                 if let Some(((p0, c0), (p1, c1))) = init {
-                    if Category::of(c0, w_chars) == Category::of(c1, w_chars) {
+                    if CharCategory::of(c0, w_chars) == CharCategory::of(c1, w_chars) {
                         m.move_to(p0);
                     } else {
                         m.move_to(p1);
@@ -195,7 +198,7 @@ impl<U: Ui> Mode<U> for Normal {
                 };
 
                 if let Some(((_, c1), (_, c0))) = init {
-                    if Category::of(c0, w_chars) != Category::of(c1, w_chars) {
+                    if CharCategory::of(c0, w_chars) != CharCategory::of(c1, w_chars) {
                         m.move_hor(-1);
                     }
                     let points = m.search_rev(word_and_space(mf, w_chars), None).next();
@@ -280,10 +283,36 @@ impl<U: Ui> Mode<U> for Normal {
                 m.set_anchor();
                 m.move_to(m.last_point().unwrap())
             }),
+            key!(Char('m')) => helper.move_each(|mut m| {
+                let (p0, _) = m.search_fwd(BRACKETS, None).next().unzip();
+                if let Some(p0) = p0 {
+                    m.move_to(p0);
+                    if let Some(p1) = m.matching_bracket() {
+                        m.set_anchor();
+                        m.move_to(p1);
+                    }
+                }
+            }),
+            key!(Char('M'), Mod::SHIFT) => helper.move_each(|mut m| {
+                let (p0, _) = m.search_fwd(BRACKETS, None).next().unzip();
+                if m.anchor().is_none() {
+                    m.set_anchor();
+                }
+                if let Some(p0) = p0 {
+                    m.move_to(p0);
+                    if let Some(p1) = m.matching_bracket() {
+                        m.move_to(p1);
+                    }
+                }
+            }),
+            key!(Char('i'), Mod::ALT) => mode::set::<U>(OneKey::Object(Object::Inner)),
+            key!(Char('a'), Mod::ALT) => mode::set::<U>(OneKey::Object(Object::Around)),
             key!(Char(';'), Mod::ALT) => helper.move_each(|mut m| m.swap_ends()),
             key!(Char(';')) => helper.move_each(|mut m| m.unset_anchor()),
             key!(Char(')')) => helper.rotate_main(1),
             key!(Char('(')) => helper.rotate_main(-1),
+            key!(Char(')'), Mod::ALT) => helper.rotate_contents(1),
+            key!(Char('('), Mod::ALT) => helper.rotate_contents(-1),
 
             ////////// Text modifying keys.
             key!(Char('i')) => {
@@ -303,31 +332,49 @@ impl<U: Ui> Mode<U> for Normal {
                 });
                 mode::set::<U>(Insert);
             }
-            key!(Char('c')) => {
+            key!(Char('c'), mf) if let Mod::ALT | Mod::NONE = mf => {
+                if mf == Mod::NONE {
+                    helper.copy_selection_to(reg);
+                }
                 helper.edit_each(|e| e.replace(""));
                 helper.move_each(|mut m| m.unset_anchor());
                 mode::set::<U>(Insert);
             }
-            key!(Char('d')) => {
+            key!(Char('d'), mf) if let Mod::ALT | Mod::NONE = mf => {
+                if mf == Mod::NONE {
+                    helper.copy_selection_to(reg);
+                }
                 helper.edit_each(|e| e.replace(""));
                 helper.move_each(|mut m| m.unset_anchor());
             }
 
+            ////////// Register keys.
+            key!(Char('"')) => mode::set::<U>(OneKey::Register),
+            key!(Char('y')) => helper.copy_selection_to(reg),
+            key!(Char('p')) => paste(&mut helper, reg, false),
+            key!(Char('P'), Mod::SHIFT) => paste(&mut helper, reg, true),
+            key!(Char('y'), Mod::CONTROL) => mode::set::<U>(clip_history::Picker::new()),
+            key!(Char('R'), Mod::SHIFT) => {
+                if let Some(strs) = registers::get(reg) {
+                    let last = strs.last().unwrap();
+                    let mut strs = strs.iter().chain(std::iter::repeat(last));
+                    helper.edit_each(|e| e.replace(strs.next().unwrap()));
+                } else {
+                    context::notify(err!("Register " [*a] {reg} [] " is empty."))
+                }
+            }
+
             ////////// Cursor creation and destruction.
             key!(Char(',')) => helper.remove_extra_cursors(),
-            key!(Char('C'), Mod::SHIFT) => helper.move_nth(helper.cursors().len() - 1, |mut m| {
-                m.copy();
-                m.move_ver(1);
-            }),
-            key!(Char('C'), ALTSHIFT) => helper.move_nth(0, |mut m| {
-                m.copy();
-                m.move_ver(-1);
-            }),
+            key!(Char('C'), Mod::SHIFT) => copy_each_to_line(&mut helper, 1),
+            key!(Char('C'), ALTSHIFT) => copy_each_to_line(&mut helper, -1),
 
             ////////// Other mode changing keys.
             key!(Char(':')) => mode::set_cmd::<U>(RunCommands::new()),
             key!(Char('G'), Mod::SHIFT) => mode::set::<U>(OneKey::GoTo(SelType::Extend)),
             key!(Char('g')) => mode::set::<U>(OneKey::GoTo(SelType::Normal)),
+            key!(Char('v')) => mode::set::<U>(OneKey::View(false)),
+            key!(Char('V'), Mod::SHIFT) => mode::set::<U>(OneKey::View(true)),
 
             ////////// Incremental search methods.
             key!(Char('/')) => mode::set_cmd::<U>(IncSearch::new(Fwd::new)),
@@ -335,13 +382,29 @@ impl<U: Ui> Mode<U> for Normal {
             key!(Char('?')) => mode::set_cmd::<U>(IncSearch::new(ExtendFwd::new)),
             key!(Char('?'), Mod::ALT) => mode::set_cmd::<U>(IncSearch::new(ExtendRev::new)),
             key!(Char('s')) => mode::set_cmd::<U>(IncSearch::new(Select::new)),
-
-            ////////// Temporary.
-            key!(Char('q')) => panic!("Panicked on purpose"),
+            key!(Char('S'), Mod::SHIFT) => mode::set_cmd::<U>(IncSearch::new(Split::new)),
+            key!(Char('k'), Mod::ALT) => mode::set_cmd::<U>(IncSearch::new(Keep::new)),
+            key!(Char('K'), ALTSHIFT) => mode::set_cmd::<U>(IncSearch::new(Keep::new_drop)),
+
+            ////////// Macro keys.
+            key!(Char('Q'), Mod::SHIFT) => {
+                let reg = macro_reg(reg);
+                if mode::stop_recording().is_none() {
+                    mode::record_macro(reg);
+                }
+            }
+            key!(Char('q')) => {
+                let reg = macro_reg(reg);
+                if !mode::replay_macro::<U>(reg, 1) {
+                    context::notify(err!("Register " [*a] {reg} [] " has no macro."))
+                }
+            }
 
             ////////// History manipulation.
             key!(Char('u')) => helper.undo(),
             key!(Char('U'), Mod::SHIFT) => helper.redo(),
+            key!(Char('u'), Mod::ALT) => helper.selection_undo(),
+            key!(Char('U'), ALTSHIFT) => helper.selection_redo(),
             _ => {}
         }
     }
@@ -369,6 +432,13 @@ impl<U: Ui> Mode<U> for Insert {
         cursors.make_incl();
         let mut helper = EditHelper::new(widget, area, cursors);
 
+        if let key!(Tab) = key
+            && helper.accept_suggestion()
+        {
+            return;
+        }
+        helper.dismiss_suggestion();
+
         if let key!(Left | Down | Up | Right) = key {
             helper.move_each(|mut m| m.unset_anchor())
         }
@@ -377,14 +447,18 @@ impl<U: Ui> Mode<U> for Insert {
             key!(Char(char)) => {
                 helper.edit_each(|e| e.insert(char));
                 helper.move_each(|mut m| m.move_hor(1));
+                helper.suggest();
             }
             key!(Char(char), Mod::SHIFT) => {
                 helper.edit_each(|e| e.insert(char));
                 helper.move_each(|mut m| m.move_hor(1));
+                helper.suggest();
             }
             key!(Enter) => {
-                helper.edit_each(|e| e.insert('\n'));
-                helper.move_each(|mut m| m.move_hor(1));
+                let mut lens = Vec::with_capacity(helper.cursors().len());
+                helper.edit_each(|e| lens.push(e.insert_newline_indented()));
+                let mut lens = lens.into_iter();
+                helper.move_each(|mut m| m.move_hor(lens.next().unwrap_or(1) as i32));
             }
             key!(Backspace) => {
                 let mut anchors = Vec::with_capacity(helper.cursors().len());
@@ -454,6 +528,9 @@ enum OneKey {
     GoTo(SelType),
     Find(SelType),
     Until(SelType),
+    Object(Object),
+    Register,
+    View(bool),
 }
 
 impl OneKey {
@@ -540,11 +617,57 @@ impl OneKey {
         self.sel_type()
     }
 
+    fn match_object<S>(helper: &mut EditHelper<File, impl Area, S>, object: Object, key: Event) {
+        let pair = match key {
+            key!(Char('b' | '(' | ')')) => Some(('(', ')')),
+            key!(Char('B' | '{' | '}'), Mod::SHIFT | Mod::NONE) => Some(('{', '}')),
+            key!(Char('r' | '[' | ']')) => Some(('[', ']')),
+            key!(Char('a' | '<' | '>'), Mod::SHIFT | Mod::NONE) => Some(('<', '>')),
+            key!(Char('Q' | '"'), Mod::SHIFT | Mod::NONE) => Some(('"', '"')),
+            key!(Char('q' | '\'')) => Some(('\'', '\'')),
+            key!(Char('g' | '`')) => Some(('`', '`')),
+            key!(Char('w' | 'W' | ' ' | 'p' | 'i'), Mod::SHIFT | Mod::NONE) => None,
+            key!(Char(char), Mod::SHIFT | Mod::NONE) if char.is_ascii_punctuation() => {
+                Some((char, char))
+            }
+            Event { code, .. } => {
+                let code = format!("{code:?}");
+                context::notify(err!("Key " [*a] code [] " not mapped on " [*a] "object" [] "."));
+                return;
+            }
+        };
+
+        let around = matches!(object, Object::Around);
+        helper.move_each(|mut m| match (pair, key) {
+            (Some((open, close)), _) => {
+                m.select_surrounding_pair(open, close, around);
+            }
+            (None, key!(Char(' '))) => {
+                if let Some((p0, p1)) = m.enclosing(|c| [' ', '\t'].contains(&c)) {
+                    m.move_to(p0);
+                    m.set_anchor();
+                    m.move_to(p1);
+                }
+            }
+            (None, key!(Char('p'))) => {
+                m.select_paragraph(around);
+            }
+            (None, key!(Char('i'))) => {
+                m.select_indent_block(around);
+            }
+            (None, key!(Char(char), _)) => {
+                m.select_word(char == 'W', around);
+            }
+            _ => unreachable!(),
+        });
+    }
+
     fn sel_type(&self) -> SelType {
         match self {
             OneKey::GoTo(sel_type) => *sel_type,
             OneKey::Find(sel_type) => *sel_type,
             OneKey::Until(sel_type) => *sel_type,
+            OneKey::Object(_) | OneKey::Register | OneKey::View(_) => SelType::Normal,
         }
     }
 }
@@ -559,12 +682,31 @@ impl<U: Ui> Mode<U> for OneKey {
         area: &<U as Ui>::Area,
         cursors: &mut Cursors,
     ) {
+        if let OneKey::View(is_locked) = *self {
+            view::<U>(key, widget, area, cursors);
+            if !is_locked || matches!(key, key!(Esc)) {
+                mode::set::<U>(Normal::new());
+            }
+            return;
+        }
+
         cursors.make_incl();
         let mut helper = EditHelper::new(widget, area, cursors);
         let mut sel_type = self.sel_type();
+        let mut reg = registers::DEFAULT;
 
         sel_type = match self {
             OneKey::GoTo(_) => self.match_goto::<(), U>(&mut helper, key),
+            OneKey::Object(object) => {
+                Self::match_object(&mut helper, *object, key);
+                SelType::Normal
+            }
+            OneKey::Register => {
+                if let key!(Char(char), Mod::SHIFT | Mod::NONE) = key {
+                    reg = char;
+                }
+                SelType::Normal
+            }
             OneKey::Find(_) | OneKey::Until(_)
                 if let key!(Char(char), Mod::SHIFT | Mod::NONE) = key =>
             {
@@ -600,8 +742,95 @@ impl<U: Ui> Mode<U> for OneKey {
             _ => SelType::Normal,
         };
 
-        mode::set::<U>(Normal(sel_type));
+        mode::set::<U>(Normal(sel_type, reg));
     }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self {
+            OneKey::GoTo(_) => vec![
+                ("h", "line start"),
+                ("l", "line end"),
+                ("i", "line non blank start"),
+                ("j", "buffer bottom"),
+                ("k", "buffer top"),
+                ("a", "last buffer"),
+                ("n", "next buffer"),
+                ("N", "previous buffer"),
+            ],
+            OneKey::View(_) => vec![
+                ("v c", "center cursor"),
+                ("t", "cursor on top"),
+                ("b", "cursor on bottom"),
+                ("j", "scroll down"),
+                ("k", "scroll up"),
+            ],
+            OneKey::Register => vec![
+                ("\"", "default register"),
+                ("_", "null register"),
+                ("+", "clipboard register"),
+            ],
+            OneKey::Find(_) | OneKey::Until(_) | OneKey::Object(_) => Vec::new(),
+        }
+    }
+}
+
+/// Scrolls the [`File`] without moving the [`Cursors`]
+fn view<U: Ui>(key: Event, widget: &RwData<File>, area: &U::Area, cursors: &Cursors) {
+    let file = widget.read();
+    let (text, cfg) = (file.text(), file.print_cfg());
+    let Some(main) = cursors.get_main().map(|main| main.caret()) else {
+        return;
+    };
+
+    match key {
+        key!(Char('v' | 'c')) => area.scroll_to_row(text, main, area.height() / 2, cfg),
+        key!(Char('t')) => area.scroll_to_row(text, main, 0, cfg),
+        key!(Char('b')) => area.scroll_to_row(text, main, area.height().saturating_sub(1), cfg),
+        key!(Char('j') | Down) => area.scroll_ver(text, 1, cfg),
+        key!(Char('k') | Up) => area.scroll_ver(text, -1, cfg),
+        key!(Esc) => {}
+        Event { code, .. } => {
+            let code = format!("{code:?}");
+            context::notify(err!("Key " [*a] code [] " not mapped on " [*a] "view" [] "."))
+        }
+    }
+}
+
+/// The register used by macros, which is `@` by default
+fn macro_reg(reg: char) -> char {
+    if reg == registers::DEFAULT { '@' } else { reg }
+}
+
+/// Pastes the contents of a register after or before each selection
+///
+/// If there are less strings in the register than there are
+/// selections, the last string will be repeated.
+fn paste<S>(helper: &mut EditHelper<File, impl Area, S>, reg: char, before: bool) {
+    let Some(strs) = registers::get(reg) else {
+        context::notify(err!("Register " [*a] {reg} [] " is empty."));
+        return;
+    };
+
+    helper.move_each(|mut m| {
+        if m.anchor_is_start() == before {
+            m.swap_ends();
+        }
+        m.unset_anchor();
+        if !before {
+            m.move_hor(1);
+        }
+    });
+
+    helper.paste_from(reg);
+
+    let mut iter = strs.iter().chain(std::iter::repeat(strs.last().unwrap()));
+    helper.move_each(|mut m| {
+        let len = iter.next().unwrap().chars().count() as i32;
+        if len > 0 {
+            m.set_anchor();
+            m.move_hor(len - 1);
+        }
+    });
 }
 
 fn select_and_move_each<S>(
@@ -639,6 +868,29 @@ fn select_and_move_each_wrapped<S>(
     });
 }
 
+/// Copies each selection to the `by`th next (or previous) line
+///
+/// The copies keep the columns of the original selection, and
+/// selections whose copies would overlap are merged.
+fn copy_each_to_line<S>(helper: &mut EditHelper<File, impl Area, S>, by: i32) {
+    helper.move_each(|mut m| {
+        let line = m.caret().line();
+        m.copy();
+
+        m.move_ver(by);
+        if m.anchor().is_some() {
+            m.swap_ends();
+            m.move_ver(by);
+            m.swap_ends();
+        }
+
+        // There was no line to copy to.
+        if m.caret().line() == line {
+            m.destroy();
+        }
+    });
+}
+
 fn no_nl_windows<'a>(
     iter: impl Iterator<Item = (Point, char)> + 'a,
 ) -> impl Iterator<Item = ((Point, char), (Point, char))> + 'a {
@@ -653,6 +905,12 @@ enum Side {
     Bottom,
 }
 
+#[derive(Clone, Copy)]
+enum Object {
+    Inner,
+    Around,
+}
+
 #[derive(Clone, Copy)]
 enum SelType {
     UntilNL,
@@ -710,25 +968,6 @@ fn w_char_cat(ranges: &'static [RangeInclusive<char>]) -> String {
 
 type RegexStrs = LazyLock<RwLock<Vec<(&'static [RangeInclusive<char>], &'static str)>>>;
 
-#[derive(PartialEq, Eq)]
-enum Category {
-    Word,
-    Special,
-    Space,
-}
-
-impl Category {
-    fn of(char: char, w_chars: WordChars) -> Self {
-        if w_chars.contains(char) {
-            Category::Word
-        } else if [' ', '\t', '\n'].contains(&char) {
-            Category::Space
-        } else {
-            Category::Special
-        }
-    }
-}
-
 struct Select<U: Ui> {
     cursors: Cursors,
     info: <U::Area as Area>::PrintInfo,
@@ -756,29 +995,7 @@ impl<U: Ui> IncSearcher<U> for Select<U> {
         }
 
         let mut helper = EditHelper::new_inc(file, area, cursors, searcher);
-
-        helper.move_each(|mut m| {
-            if m.anchor_is_start() {
-                m.swap_ends();
-            }
-            if let Some(anchor) = m.anchor() {
-                let ranges: Vec<(Point, Point)> = m.search_inc_fwd(Some(anchor)).collect();
-
-                for (i, &(p0, p1)) in ranges.iter().enumerate() {
-                    m.move_to(p0);
-                    if p1.char() > p0.char() + 1 {
-                        m.set_anchor();
-                        m.move_to(p1);
-                        m.move_hor(-1);
-                    } else {
-                        m.unset_anchor();
-                    }
-                    if i < ranges.len() - 1 {
-                        m.copy();
-                    }
-                }
-            }
-        });
+        helper.select_matches_inc();
     }
 }
 
@@ -809,28 +1026,48 @@ impl<U: Ui> IncSearcher<U> for Split<U> {
         }
 
         let mut helper = EditHelper::new_inc(file, area, cursors, searcher);
+        helper.split_by_inc();
+    }
+}
 
-        helper.move_each(|mut m| {
-            if m.anchor_is_start() {
-                m.swap_ends();
-            }
-            if let Some(anchor) = m.anchor() {
-                let ranges: Vec<(Point, Point)> = m.search_inc_fwd(Some(anchor)).collect();
+struct Keep<U: Ui> {
+    cursors: Cursors,
+    info: <U::Area as Area>::PrintInfo,
+    keep: bool,
+}
 
-                for (i, &(p0, p1)) in ranges.iter().enumerate() {
-                    m.move_to(p0);
-                    if p1 > p0 {
-                        m.set_anchor();
-                        m.move_to(p1);
-                        m.move_hor(-1);
-                    } else {
-                        m.unset_anchor();
-                    }
-                    if i < ranges.len() - 1 {
-                        m.copy();
-                    }
-                }
-            }
-        })
+impl<U: Ui> Keep<U> {
+    fn new_drop(file: &RwData<File>, area: &<U as Ui>::Area, cursors: &mut Cursors) -> Self {
+        Self {
+            keep: false,
+            ..<Self as IncSearcher<U>>::new(file, area, cursors)
+        }
+    }
+}
+
+impl<U: Ui> IncSearcher<U> for Keep<U> {
+    fn new(_: &RwData<File>, area: &<U as Ui>::Area, cursors: &mut Cursors) -> Self {
+        Self {
+            cursors: cursors.clone(),
+            info: area.print_info(),
+            keep: true,
+        }
+    }
+
+    fn search(
+        &mut self,
+        file: &RwData<File>,
+        area: &<U as Ui>::Area,
+        cursors: &mut Cursors,
+        searcher: duat_core::text::Searcher,
+    ) {
+        *cursors = self.cursors.clone();
+        if searcher.is_empty() {
+            area.set_print_info(self.info.clone());
+            return;
+        }
+
+        let mut helper = EditHelper::new_inc(file, area, cursors, searcher);
+        helper.keep_matching_inc(self.keep);
     }
 }
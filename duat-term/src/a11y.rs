@@ -0,0 +1,173 @@
+//! A screen reader friendly mode
+//!
+//! When enabled with [`set_screen_reader`], decorative output, like
+//! frames and [`VertRule`]s, is not printed, and mode changes and
+//! notifications are announced as plain lines at the bottom of the
+//! screen, which terminal screen readers can read out.
+//!
+//! Additionally, a socket is opened in the temporary directory, named
+//! `duat-{pid}.sock`. Whenever something connects to it, a linear
+//! description of the current [`File`] and its main cursor is
+//! written to it, so external tools can query the state of Duat
+//! without having to parse the screen.
+//!
+//! [`VertRule`]: crate::VertRule
+//! [`File`]: duat_core::widgets::File
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use crossterm::{
+    cursor,
+    style::{Print, ResetColor},
+    terminal::{self, ClearType},
+};
+use duat_core::{context, data::RoData, text::Text};
+
+use crate::Ui;
+
+static SCREEN_READER: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables the screen reader friendly mode
+///
+/// This should be called in the config, since the socket is only
+/// opened when Duat starts.
+pub fn set_screen_reader(is_on: bool) {
+    SCREEN_READER.store(is_on, Ordering::Relaxed);
+}
+
+/// Whether the screen reader friendly mode is enabled
+pub fn screen_reader_is_on() -> bool {
+    SCREEN_READER.load(Ordering::Relaxed)
+}
+
+/// Watches for mode changes and notifications, in order to
+/// announce them
+pub(crate) struct Announcer {
+    mode: RoData<&'static str>,
+    notifications: RoData<Text>,
+}
+
+impl Announcer {
+    pub(crate) fn new() -> Self {
+        Self {
+            mode: RoData::from(context::mode_name()),
+            notifications: RoData::from(context::notifications()),
+        }
+    }
+
+    /// Announces anything that changed since the last call
+    pub(crate) fn announce_changes(&self) {
+        if !screen_reader_is_on() {
+            return;
+        }
+
+        if self.mode.has_changed() {
+            announce(&format!("{} mode", self.mode.read().to_lowercase()));
+        }
+        if self.notifications.has_changed() {
+            let notification = self.notifications.read().to_string();
+            if !notification.is_empty() {
+                announce(&notification);
+            }
+        }
+    }
+}
+
+/// Prints a plain line at the bottom of the screen
+fn announce(msg: &str) {
+    let (_, height) = terminal::size().unwrap_or((0, 1));
+    let line = msg.lines().collect::<Vec<_>>().join(" ");
+
+    let mut output = crate::output();
+    let _ = crossterm::queue!(
+        output,
+        cursor::SavePosition,
+        cursor::MoveTo(0, height.saturating_sub(1)),
+        ResetColor,
+        terminal::Clear(ClearType::CurrentLine),
+        Print(line),
+        cursor::RestorePosition,
+    );
+    let _ = output.flush();
+}
+
+/// Opens the socket, serving the linear view until Duat ends
+#[cfg(unix)]
+pub(crate) fn serve() {
+    use std::os::unix::net::UnixListener;
+
+    let path = socket_path();
+    let _ = std::fs::remove_file(&path);
+    let Ok(listener) = UnixListener::bind(&path) else {
+        return;
+    };
+    if listener.set_nonblocking(true).is_err() {
+        return;
+    }
+
+    duat_core::thread::spawn(move || {
+        while !duat_core::has_ended() {
+            match listener.accept() {
+                Ok((mut stream, _)) => {
+                    let _ = stream.write_all(linear_view().as_bytes());
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                Err(_) => break,
+            }
+        }
+
+        let _ = std::fs::remove_file(path);
+    });
+}
+
+#[cfg(not(unix))]
+pub(crate) fn serve() {}
+
+/// The path of the socket for this instance of Duat
+pub fn socket_path() -> PathBuf {
+    std::env::temp_dir().join(format!("duat-{}.sock", std::process::id()))
+}
+
+/// A linear description of the current [`File`]
+///
+/// This includes the name of the [`File`], the current mode, the
+/// position of the main cursor, the number of selections and the
+/// line that the main cursor is on.
+///
+/// [`File`]: duat_core::widgets::File
+pub fn linear_view() -> String {
+    let mode = context::mode_name().read().to_lowercase();
+    let Ok(cur_file) = context::cur_file::<Ui>() else {
+        return format!("mode: {mode}\n");
+    };
+
+    cur_file.inspect(|file, _, cursors| {
+        let mut view = format!("file: {}\nmode: {mode}\n", file.name());
+
+        if let Some(main) = cursors.get_main() {
+            let caret = main.caret();
+            let start = file.text().point_at_line(caret.line());
+            let line: String = file
+                .text()
+                .chars_fwd(start)
+                .map(|(_, char)| char)
+                .take_while(|char| *char != '\n')
+                .collect();
+
+            view.push_str(&format!(
+                "line: {}, column: {}, selections: {}\n{line}\n",
+                caret.line() + 1,
+                caret.char() - start.char() + 1,
+                cursors.len()
+            ));
+        }
+
+        view
+    })
+}
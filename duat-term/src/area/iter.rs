@@ -5,7 +5,8 @@ use duat_core::{
     text::{Item, Iter as TextIter, Part, Point, RevIter as RevTextIter},
     ui::Caret,
 };
-use unicode_width::UnicodeWidthChar;
+
+use crate::{glyph_width, glyphs::glyph_width_after};
 
 /// Returns an [`Iterator`] that also shows the current level of
 /// indentation.
@@ -38,12 +39,13 @@ fn indents<'a>(
 fn parts<'a>(
     iter: impl Iterator<Item = (u32, Item)> + Clone + 'a,
     cap: u32,
+    col: u32,
     cfg: IterCfg,
 ) -> impl Iterator<Item = (Caret, Item)> + Clone + 'a {
     iter.scan(
-        (0, true, None),
-        move |(x, needs_to_wrap, prev_char), (indent, unit)| {
-            attach_caret((x, needs_to_wrap, prev_char), indent, unit, cap, &cfg)
+        (0, col, true, None),
+        move |(x, col, needs_to_wrap, prev_char), (indent, unit)| {
+            attach_caret((x, col, needs_to_wrap, prev_char), indent, unit, cap, &cfg)
         },
     )
 }
@@ -53,6 +55,7 @@ fn parts<'a>(
 fn words<'a>(
     iter: impl Iterator<Item = (u32, Item)> + Clone + 'a,
     width: u32,
+    mut col: u32,
     cfg: IterCfg,
 ) -> impl Iterator<Item = (Caret, Item)> + Clone + 'a {
     let mut iter = iter.peekable();
@@ -65,7 +68,7 @@ fn words<'a>(
     let mut needs_wrap = true;
     std::iter::from_fn(move || {
         if let Some(unit) = finished_word.pop() {
-            let vars = (&mut x, &mut needs_wrap, &mut prev_char);
+            let vars = (&mut x, &mut col, &mut needs_wrap, &mut prev_char);
             return attach_caret(vars, indent, unit, width, &cfg);
         }
 
@@ -75,7 +78,7 @@ fn words<'a>(
                 indent = *new_indent;
 
                 if cfg.word_chars().contains(c) {
-                    word_len += len_from(c, x + word_len, width, &cfg, prev_char)
+                    word_len += len_from(c, col + word_len, x + word_len, width, &cfg, prev_char)
                 } else {
                     word.push(iter.next().map(|(_, unit)| unit).unwrap());
                     break;
@@ -89,7 +92,7 @@ fn words<'a>(
         std::mem::swap(&mut word, &mut finished_word);
         finished_word.reverse();
         finished_word.pop().and_then(|unit| {
-            let vars = (&mut x, &mut needs_wrap, &mut prev_char);
+            let vars = (&mut x, &mut col, &mut needs_wrap, &mut prev_char);
             attach_caret(vars, indent, unit, width, &cfg)
         })
     })
@@ -97,13 +100,13 @@ fn words<'a>(
 
 #[inline(always)]
 fn attach_caret(
-    (x, needs_to_wrap, prev_char): (&mut u32, &mut bool, &mut Option<char>),
+    (x, col, needs_to_wrap, prev_char): (&mut u32, &mut u32, &mut bool, &mut Option<char>),
     indent: u32,
     mut item: Item,
     cap: u32,
     cfg: &IterCfg,
 ) -> Option<(Caret, Item)> {
-    let (len, processed_part) = process_part(item.part, cfg, prev_char, *x, cap);
+    let (mut len, processed_part) = process_part(item.part, cfg, prev_char, *col, *x, cap);
 
     let mut old_x = *x;
     *x += len;
@@ -111,15 +114,27 @@ fn attach_caret(
     let width_wrap = *x > cap || (*x == cap && len == 0);
     let nl_wrap = *needs_to_wrap && prev_char.is_some();
     if nl_wrap || width_wrap {
+        // Lines that don't start at the beginning are continuations.
+        let indent = if *col > 0 {
+            indent + wrap_prefix_len(cfg, indent, cap)
+        } else {
+            indent
+        };
+        // Tabs need to be clamped again, since the space left changed.
+        if item.part == Part::Char('\t') {
+            len = len_from('\t', *col, indent, cap, cfg, None);
+        }
         old_x = indent;
         *x = indent + len;
         *needs_to_wrap = false;
     };
 
+    *col += len;
     if let Some(b) = item.part.as_char() {
         if b == '\n' {
             *needs_to_wrap = true;
             *x = 0;
+            *col = 0;
         }
     }
 
@@ -132,6 +147,7 @@ fn process_part(
     part: Part,
     cfg: &IterCfg,
     prev_char: &mut Option<char>,
+    col: u32,
     x: u32,
     cap: u32,
 ) -> (u32, Part) {
@@ -140,11 +156,14 @@ fn process_part(
             let ret = if b == '\n' {
                 let char = cfg.new_line().char(*prev_char);
                 match char {
-                    Some(char) => (len_from(char, x, cap, cfg, *prev_char), Part::Char(char)),
+                    Some(char) => (
+                        len_from(char, col, x, cap, cfg, *prev_char),
+                        Part::Char(char),
+                    ),
                     None => (0, Part::Char('\n')),
                 }
             } else {
-                (len_from(b, x, cap, cfg, *prev_char), Part::Char(b))
+                (len_from(b, col, x, cap, cfg, *prev_char), Part::Char(b))
             };
 
             *prev_char = Some(b);
@@ -191,6 +210,8 @@ pub fn print_iter(
     cfg: IterCfg,
     points: (Point, Option<Point>),
 ) -> impl Iterator<Item = (Caret, Item)> + Clone + '_ {
+    let col = col_at(iter.clone(), cfg, points);
+
     let (Continue(indent) | Break(indent)) = iter
         .clone()
         .take_while(|&Item { real, ghost, .. }| (real, ghost) < points)
@@ -206,7 +227,29 @@ pub fn print_iter(
     if !iter_at_line_start {
         iter.skip_to(points);
     }
-    inner_iter(iter, cap, (indent, iter_at_line_start), cfg)
+    inner_iter(iter, cap, (indent, iter_at_line_start), col, cfg)
+}
+
+/// The absolute column of `points`, given an iterator starting on
+/// its line
+///
+/// Tabs are expanded based on this column, so they look the same
+/// regardless of where the iteration starts or how far the line was
+/// scrolled horizontally.
+pub(super) fn col_at(
+    iter: impl Iterator<Item = Item>,
+    cfg: IterCfg,
+    points: (Point, Option<Point>),
+) -> u32 {
+    let (col, _) = iter
+        .take_while(|&Item { real, ghost, .. }| (real, ghost) < points)
+        .fold((0, None), |(col, prev), item| match item.part {
+            Part::Char('\n') => (0, Some('\n')),
+            Part::Char('\t') => (col + cfg.tab_stops().spaces_at(col), Some('\t')),
+            Part::Char(char) => (col + glyph_width_after(char, prev), Some(char)),
+            _ => (col, prev),
+        });
+    col
 }
 
 /// An [`Iterator`] that returns both an [`Item`] and a [`Caret`].
@@ -214,13 +257,17 @@ pub fn print_iter(
 /// This function will function properly given that, elsewhere in
 /// the code, the passed [`PrintInfo`] and `width` have beend
 /// validated.
+///
+/// The `col` is the absolute column of the first [`Item`], which is
+/// used in order to properly expand tabs.
 pub(super) fn print_iter_indented(
     iter: TextIter<'_>,
     cap: u32,
     cfg: IterCfg,
     indent: u32,
+    col: u32,
 ) -> impl Iterator<Item = (Caret, Item)> + Clone + '_ {
-    inner_iter(iter, cap, (indent, false), cfg)
+    inner_iter(iter, cap, (indent, false), col, cfg)
 }
 
 pub fn rev_print_iter(
@@ -250,7 +297,13 @@ pub fn rev_print_iter(
                 }
             }
 
-            returns.extend(inner_iter(items.into_iter().rev(), width, (0, true), cfg));
+            returns.extend(inner_iter(
+                items.into_iter().rev(),
+                width,
+                (0, true),
+                0,
+                cfg,
+            ));
 
             returns.pop()
         }
@@ -261,30 +314,52 @@ fn inner_iter<'a>(
     iter: impl Iterator<Item = Item> + Clone + 'a,
     cap: u32,
     initial: (u32, bool),
+    col: u32,
     cfg: IterCfg,
 ) -> impl Iterator<Item = (Caret, Item)> + Clone + 'a {
     let indents = indents(iter, cap, initial, cfg);
 
     match cfg.wrap_method() {
         WrapMethod::Width | WrapMethod::NoWrap | WrapMethod::Capped(_) => {
-            Iter::Parts(parts(indents, cap, cfg), PhantomData)
+            Iter::Parts(parts(indents, cap, col, cfg), PhantomData)
         }
-        WrapMethod::Word => Iter::Words(words(indents, cap, cfg)),
+        WrapMethod::Word => Iter::Words(words(indents, cap, col, cfg)),
     }
 }
 
+/// The width of the [wrap prefix], if it fits after the `indent`
+///
+/// [wrap prefix]: duat_core::cfg::PrintCfg::wrap_prefix
+pub(super) fn wrap_prefix_len(cfg: &IterCfg, indent: u32, cap: u32) -> u32 {
+    let len: u32 = cfg.wrap_prefix().chars().map(glyph_width).sum();
+
+    if indent + len < cap { len } else { 0 }
+}
+
+/// The length of a [`char`], given its absolute column and the
+/// position on screen
+///
+/// Tabs are expanded based on the absolute column, but clamped
+/// based on the space left on screen.
 #[inline(always)]
-fn len_from(char: char, start: u32, max_width: u32, cfg: &IterCfg, prev_char: Option<char>) -> u32 {
+fn len_from(
+    char: char,
+    col: u32,
+    x: u32,
+    max_width: u32,
+    cfg: &IterCfg,
+    prev_char: Option<char>,
+) -> u32 {
     let char = if char == '\n' {
         cfg.new_line().char(prev_char).unwrap_or('\n')
     } else {
         char
     };
     match char {
-        '\t' => (cfg.tab_stops().spaces_at(start))
-            .min(max_width.saturating_sub(start))
+        '\t' => (cfg.tab_stops().spaces_at(col))
+            .min(max_width.saturating_sub(x))
             .max(1),
         '\n' => 0,
-        _ => UnicodeWidthChar::width(char).unwrap_or(0) as u32,
+        _ => glyph_width_after(char, prev_char),
     }
 }
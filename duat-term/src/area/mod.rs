@@ -1,22 +1,23 @@
 mod iter;
 
-use std::{fmt::Alignment, io::Write};
+use std::{fmt::Alignment, io::Write, sync::LazyLock};
 
-use crossterm::{
-    cursor,
-    style::{ResetColor, SetStyle},
-};
+use crossterm::{cursor, style::ResetColor};
 use duat_core::{
     cache::{Deserialize, Serialize},
-    cfg::{IterCfg, PrintCfg},
+    cfg::{IterCfg, PrintCfg, SelectedNewLine},
     data::RwData,
-    form::Painter,
-    text::{Item, Iter, Part, Point, RevIter, Text},
-    ui::{self, Axis, Caret, Constraint, PushSpecs},
+    form::{self, E_CUR_EOL_ID, E_SEL_ID, FormId, M_CUR_EOL_ID, M_SEL_ID, Painter},
+    text::{Item, Iter, Part, Point, RevIter, Text, TextId},
+    ui::{self, Axis, Caret, Constraint, FloatSpecs, PushSpecs, Side},
 };
-use iter::{print_iter, print_iter_indented, rev_print_iter};
+use iter::{col_at, print_iter, print_iter_indented, rev_print_iter, wrap_prefix_len};
 
-use crate::{AreaId, ConstraintErr, layout::Layout};
+use crate::{
+    AreaId, Brush, ConstraintErr, Frame, ResizeStrategy, glyph_width,
+    layout::Layout,
+    print::{Lines, SetStyle, Title},
+};
 
 macro_rules! queue {
     ($writer:expr $(, $command:expr)* $(,)?) => {
@@ -53,11 +54,11 @@ impl Coords {
     }
 
     pub fn width(&self) -> u32 {
-        self.br.x - self.tl.x
+        self.br.x.saturating_sub(self.tl.x)
     }
 
     pub fn height(&self) -> u32 {
-        self.br.y - self.tl.y
+        self.br.y.saturating_sub(self.tl.y)
     }
 
     pub fn tl(&self) -> Coord {
@@ -86,6 +87,120 @@ impl Area {
         Self { layout, id: index }
     }
 
+    /// Sets a [`Title`] on the top edge of this [`Area`]
+    ///
+    /// This can also be used on a cluster master, in order to title
+    /// a whole cluster of [`Area`]s. If there is no edge above the
+    /// [`Area`], the [`Title`] is not printed.
+    pub fn set_title(&self, title: Title) {
+        let layout = self.layout.read();
+        let rect = layout.get(self.id).unwrap();
+        layout
+            .printer
+            .write()
+            .set_title(self.id, rect.tl_var(), title);
+    }
+
+    /// Sets the [`Brush`] of the edges around this [`Area`]
+    ///
+    /// If this [`Area`] is a cluster master, this will change the
+    /// edges around the whole cluster. The change persists when new
+    /// [`Area`]s are pushed around it.
+    ///
+    /// In order to configure the cluster of every [`File`], you can
+    /// call this on [`FileBuilder::area`], in an [`OnFileOpen`] hook.
+    ///
+    /// [`File`]: duat_core::widgets::File
+    /// [`FileBuilder::area`]: duat_core::ui::FileBuilder::area
+    /// [`OnFileOpen`]: duat_core::hooks::OnFileOpen
+    pub fn set_brush(&self, brush: Brush) {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+        layout
+            .rects
+            .get_mut(self.id)
+            .unwrap()
+            .set_brush(brush, &mut p);
+    }
+
+    /// The [`Frame`] of the whole window of this [`Area`]
+    pub fn frame(&self) -> Frame {
+        self.layout.read().rects.frame()
+    }
+
+    /// Sets the [`Frame`] of the whole window of this [`Area`]
+    ///
+    /// Unlike [`Area::set_brush`], this can add or remove edges, so
+    /// the layout of the window is solved again.
+    pub fn set_frame(&self, fr: Frame) {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+        layout.rects.set_frame(fr, &mut p);
+        p.flush_equalities().unwrap();
+    }
+
+    /// Sets whether the edges around this [`Area`] are framed
+    ///
+    /// If this [`Area`] is a cluster master, this affects the edges
+    /// around the whole cluster.
+    pub fn set_framed(&self, is_framed: bool) {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+        layout.rects.set_framed(self.id, is_framed, &mut p);
+        p.flush_equalities().unwrap();
+    }
+
+    /// Sets how the [`Area`]s inside of this one are resized
+    ///
+    /// This is meant to be called on cluster masters, in order to,
+    /// for example, keep the proportions between [`File`]s when the
+    /// terminal is resized. If this [`Area`] has no children, the
+    /// [`ResizeStrategy`] applies to it and its siblings instead.
+    ///
+    /// [`File`]: duat_core::widgets::File
+    pub fn set_resize_strategy(&self, strategy: ResizeStrategy) {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+        layout.rects.set_resize_strategy(self.id, strategy, &mut p);
+        p.flush_equalities().unwrap();
+    }
+
+    /// Updates the layout of this [`Area`]'s window right before
+    /// the terminal is resized
+    ///
+    /// This lets [`ResizeStrategy`]s act on the lengths from before
+    /// the resize.
+    pub(crate) fn prepare_for_resize(&self) {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+        layout.rects.prepare_for_resize(&mut p);
+        p.flush_equalities().unwrap();
+    }
+
+    /// Removes the [`Title`] of this [`Area`], if there was one
+    pub fn remove_title(&self) {
+        let layout = self.layout.read();
+        layout.printer.write().remove_title(self.id);
+    }
+
+    /// Moves this floating [`Area`] to the top or bottom of the
+    /// z-order
+    fn move_floating(&self, to_top: bool) -> Result<(), ConstraintErr> {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+
+        if !layout.rects.move_floating(self.id, to_top, &mut p) {
+            return Err(ConstraintErr::NoParent);
+        }
+        Ok(())
+    }
+
     fn print<'a>(
         &self,
         text: &Text,
@@ -94,6 +209,10 @@ impl Area {
         f: impl FnMut(&Caret, &Item) + 'a,
     ) {
         let layout = self.layout.read();
+        let painter = match layout.rects.is_shaded(self.id) {
+            true => painter.shaded(),
+            false => painter,
+        };
         let Some((sender, info)) = layout.rects.get(self.id).and_then(|rect| {
             let sender = rect.sender();
             let info = rect.print_info();
@@ -111,11 +230,17 @@ impl Area {
             (text.iter_fwd(line_start), IterCfg::new(cfg).outsource_lfs())
         };
 
-        let cap = cfg.wrap_width(sender.coords().width());
+        let pad = cfg.padding();
+        let cap = cfg.wrap_width(pad.inner_width(sender.coords().width()));
         let active = layout.active_id == self.id;
         let iter = print_iter(iter, cap, cfg, info.points);
 
-        let mut lines = sender.lines(info.x_shift, cap);
+        let mut lines = sender.lines(info.x_shift, cap, pad);
+        let top = sender.coords().tl.y + pad.top().min(sender.coords().height());
+        let bottom = sender.coords().br.y.saturating_sub(pad.bottom()).max(top);
+        for _ in sender.coords().tl.y..top {
+            lines.flush().unwrap();
+        }
 
         enum Cursor {
             Main,
@@ -125,42 +250,102 @@ impl Area {
         let lines_left = {
             let (mut painter, mut f) = (painter, f);
             // The y here represents the bottom of the current row of cells.
-            let mut y = sender.coords().tl.y;
+            let mut y = top;
             let mut cursor = None;
+            let mut eol_ghosts = Vec::new();
+            let mut prev_was_lf = false;
 
             for (caret, item) in iter {
                 f(&caret, &item);
 
                 let Caret { x, len, wrap } = caret;
-                let Item { part, .. } = item;
+                let Item { part, real, ghost } = item;
+                // The new line may have been replaced by another char.
+                let is_lf = part == Part::Char('\n')
+                    || (part.is_char() && ghost.is_none() && text.char_at(real) == Some('\n'));
 
                 if wrap {
-                    if y > sender.coords().tl.y {
+                    if y > top {
                         lines.flush().unwrap();
                     }
-                    if y == sender.coords().br.y {
+                    if prev_was_lf {
+                        lines.set_line_bg(None);
+                    }
+                    if y == bottom {
                         break;
                     }
-                    (0..x).for_each(|_| lines.push_char(' ', 1));
+
+                    // Rows that don't start a line are continuations of it.
+                    let prefix_len = if real != text.point_at_line(real.line()) {
+                        Some(wrap_prefix_len(&cfg, 0, cap))
+                            .filter(|len| *len <= x)
+                            .unwrap_or(0)
+                    } else {
+                        0
+                    };
+                    if prefix_len > 0 {
+                        queue!(lines, ResetColor, SetStyle(painter.apply(*WRAP_PREFIX)));
+                        for char in cfg.wrap_prefix().chars() {
+                            lines.push_char(char, glyph_width(char));
+                        }
+                        queue!(lines, ResetColor, SetStyle(painter.remove(*WRAP_PREFIX)));
+                    }
+                    (prefix_len..x).for_each(|_| lines.push_char(' ', 1));
                     queue!(lines, SetStyle(painter.make_style()));
                     if part.is_char() {
                         y += 1
                     }
                 }
 
+                if part.is_char() {
+                    prev_was_lf = is_lf;
+                }
+
                 match part {
                     Part::Char(char) => {
-                        match char {
-                            '\t' => (0..len).for_each(|_| lines.push_char(' ', 1)),
-                            '\n' => {}
-                            char => lines.push_char(char, len),
-                        }
-                        if let Some(cursor) = cursor.take() {
-                            let style = match cursor {
-                                Cursor::Main => painter.remove_main_cursor(),
-                                Cursor::Extra => painter.remove_extra_cursor(),
+                        let is_selected = is_lf
+                            && cfg.selected_new_line() != SelectedNewLine::Hidden
+                            && (painter.is_applied(M_SEL_ID) || painter.is_applied(E_SEL_ID));
+
+                        if is_lf && let Some(cursor) = cursor.take() {
+                            // Cursors on new lines get their own form.
+                            let id = match cursor {
+                                Cursor::Main => {
+                                    painter.remove_main_cursor();
+                                    M_CUR_EOL_ID
+                                }
+                                Cursor::Extra => {
+                                    painter.remove_extra_cursor();
+                                    E_CUR_EOL_ID
+                                }
                             };
-                            queue!(lines, ResetColor, SetStyle(style));
+                            queue!(lines, ResetColor, SetStyle(painter.apply(id)));
+                            match char {
+                                '\n' => lines.push_char(' ', 1),
+                                char => lines.push_char(char, len.max(1)),
+                            }
+                            queue!(lines, ResetColor, SetStyle(painter.remove(id)));
+                        } else {
+                            match char {
+                                '\t' => (0..len).for_each(|_| lines.push_char(' ', 1)),
+                                '\n' if is_selected => lines.push_char(' ', 1),
+                                '\n' => {}
+                                char => lines.push_char(char, len),
+                            }
+                            if let Some(cursor) = cursor.take() {
+                                let style = match cursor {
+                                    Cursor::Main => painter.remove_main_cursor(),
+                                    Cursor::Extra => painter.remove_extra_cursor(),
+                                };
+                                queue!(lines, ResetColor, SetStyle(style));
+                            }
+                        }
+
+                        if is_selected && cfg.selected_new_line() == SelectedNewLine::ToEdge {
+                            lines.set_line_bg(painter.make_style().background_color);
+                        }
+                        if is_lf {
+                            print_eol_ghosts(&mut lines, text, &mut painter, &mut eol_ghosts);
                         }
                     }
                     Part::PushForm(id) => {
@@ -197,12 +382,19 @@ impl Area {
                     Part::ResetState => {
                         queue!(lines, SetStyle(painter.reset()))
                     }
+                    Part::EolGhost(id) => eol_ghosts.push((id, false)),
+                    Part::RightAligned(id) => eol_ghosts.push((id, true)),
+                    Part::LineBg(id) => {
+                        lines.set_line_bg(duat_core::form::from_id(id).style.background_color)
+                    }
                     Part::ToggleStart(_) => todo!(),
                     Part::ToggleEnd(_) => todo!(),
                     _ => {}
                 }
             }
 
+            print_eol_ghosts(&mut lines, text, &mut painter, &mut eol_ghosts);
+
             if !lines.is_empty() {
                 if cfg.ending_space() {
                     lines.push_char(' ', 1)
@@ -210,7 +402,7 @@ impl Area {
                 lines.flush().unwrap();
             }
 
-            sender.coords().br.y - y
+            sender.coords().br.y.saturating_sub(y)
         };
 
         for _ in 0..lines_left {
@@ -221,6 +413,69 @@ impl Area {
     }
 }
 
+/// Prints the [`Tag::EolGhost`]s and [`Tag::RightAligned`]s of a line
+///
+/// These are printed after everything else in the line, and are
+/// clipped if there isn't enough space left.
+///
+/// [`Tag::EolGhost`]: duat_core::text::Tag::EolGhost
+/// [`Tag::RightAligned`]: duat_core::text::Tag::RightAligned
+fn print_eol_ghosts(
+    lines: &mut Lines,
+    text: &Text,
+    painter: &mut Painter,
+    ghosts: &mut Vec<(TextId, bool)>,
+) {
+    for (id, right_aligned) in ghosts.drain(..) {
+        let Some(ghost) = text.ghost_text(id) else {
+            continue;
+        };
+
+        let width: u32 = ghost
+            .iter_fwd(Point::default())
+            .filter_map(|item| item.part.as_char())
+            .map(glyph_width)
+            .sum();
+
+        let padding = if right_aligned {
+            lines.remaining().saturating_sub(width)
+        } else {
+            lines.remaining().min(1)
+        };
+        (0..padding).for_each(|_| lines.push_char(' ', 1));
+
+        let mut pushed = Vec::new();
+        for item in ghost.iter_fwd(Point::default()) {
+            match item.part {
+                Part::Char('\n') => {}
+                Part::Char(char) => {
+                    let len = glyph_width(char);
+                    if len > lines.remaining() {
+                        break;
+                    }
+                    lines.push_char(char, len);
+                }
+                Part::PushForm(id) => {
+                    pushed.push(id);
+                    queue!(lines, ResetColor, SetStyle(painter.apply(id)));
+                }
+                Part::PopForm(id) => {
+                    if let Some(i) = pushed.iter().position(|p| *p == id) {
+                        pushed.remove(i);
+                    }
+                    queue!(lines, ResetColor, SetStyle(painter.remove(id)))
+                }
+                _ => {}
+            }
+        }
+
+        for id in pushed {
+            painter.remove(id);
+        }
+        queue!(lines, ResetColor, SetStyle(painter.make_style()));
+    }
+}
+
 impl ui::Area for Area {
     type Cache = PrintInfo;
     type ConstraintChangeErr = ConstraintErr;
@@ -239,14 +494,22 @@ impl ui::Area for Area {
     fn width(&self) -> u32 {
         self.layout.inspect(|layout| {
             let rect = layout.get(self.id).unwrap();
-            rect.br().x - rect.tl().x
+            rect.br().x.saturating_sub(rect.tl().x)
         })
     }
 
     fn height(&self) -> u32 {
         self.layout.inspect(|window| {
             let rect = window.get(self.id).unwrap();
-            rect.br().y - rect.tl().y
+            rect.br().y.saturating_sub(rect.tl().y)
+        })
+    }
+
+    fn bounds(&self) -> ((u32, u32), (u32, u32)) {
+        self.layout.inspect(|layout| {
+            let rect = layout.get(self.id).unwrap();
+            let (tl, br) = (rect.tl(), rect.br());
+            ((tl.x, tl.y), (br.x, br.y))
         })
     }
 
@@ -256,10 +519,11 @@ impl ui::Area for Area {
             let rect = layout.get(self.id).unwrap();
             let info = rect.print_info().unwrap();
             let info = info.read();
-            let width = rect.br().x - rect.tl().x;
-            let height = rect.br().y - rect.tl().y;
+            let width = rect.br().x.saturating_sub(rect.tl().x);
+            let height = rect.br().y.saturating_sub(rect.tl().y);
             (*info, width, height)
         };
+        let (w, h) = (cfg.padding.inner_width(w), cfg.padding.inner_height(h));
 
         let info = scroll_ver_around(info, w, h, point, text, IterCfg::new(cfg).outsource_lfs());
         let info = scroll_hor_around(info, w, point, text, IterCfg::new(cfg).outsource_lfs());
@@ -271,6 +535,46 @@ impl ui::Area for Area {
         old_info.last_main = point;
     }
 
+    fn scroll_ver(&self, text: &Text, by: i32, cfg: PrintCfg) {
+        let mut info = self.print_info();
+        let cfg = IterCfg::new(cfg).outsource_lfs();
+        let cap = cfg.wrap_width(cfg.padding().inner_width(self.width()));
+
+        info.points = if by >= 0 {
+            let line_start = text.visual_line_start(info.points);
+            print_iter(text.iter_fwd(line_start), cap, cfg, info.points)
+                .filter_map(|(caret, item)| caret.wrap.then_some(item.points()))
+                .take(by as usize + 1)
+                .last()
+                .unwrap_or(info.points)
+        } else {
+            rev_print_iter(text.iter_rev(info.points), cap, cfg)
+                .filter_map(|(caret, item)| caret.wrap.then_some(item.points()))
+                .take(by.unsigned_abs() as usize)
+                .last()
+                .unwrap_or(info.points)
+        };
+
+        self.set_print_info(info);
+    }
+
+    fn scroll_to_row(&self, text: &Text, point: Point, row: u32, cfg: PrintCfg) {
+        let mut info = self.print_info();
+        let cfg = IterCfg::new(cfg).outsource_lfs();
+        let cap = cfg.wrap_width(cfg.padding().inner_width(self.width()));
+
+        let points = text.ghost_max_points_at(point.byte());
+        let after = text.points_after(points).unwrap_or(text.len_points());
+
+        info.points = rev_print_iter(text.iter_rev(after), cap, cfg)
+            .filter_map(|(caret, item)| caret.wrap.then_some(item.points()))
+            .take(row as usize + 1)
+            .last()
+            .unwrap_or_default();
+
+        self.set_print_info(info);
+    }
+
     fn top_left(&self) -> (Point, Option<Point>) {
         let layout = self.layout.read();
         let rect = layout.get(self.id).unwrap();
@@ -367,6 +671,70 @@ impl ui::Area for Area {
         todo!();
     }
 
+    fn hide(&self) -> Result<(), ConstraintErr> {
+        let mut layout = self.layout.write();
+        let cons = layout
+            .rects
+            .get_constraints_mut(self.id)
+            .ok_or(ConstraintErr::NoParent)?
+            .clone();
+
+        if cons.is_hidden() {
+            return Ok(());
+        }
+
+        let cons = {
+            let mut p = layout.printer.write();
+            let (_, parent) = layout.get_parent(self.id).unwrap();
+            let rect = layout.get(self.id).unwrap();
+
+            let cons = cons.hide(parent.axis().unwrap(), &mut p);
+            let cons = cons.apply(rect, parent.id(), &layout.rects, &mut p);
+            p.flush_equalities().unwrap();
+            cons
+        };
+
+        *layout.rects.get_constraints_mut(self.id).unwrap() = cons;
+
+        Ok(())
+    }
+
+    fn reveal(&self) -> Result<(), ConstraintErr> {
+        let mut layout = self.layout.write();
+        let cons = layout
+            .rects
+            .get_constraints_mut(self.id)
+            .ok_or(ConstraintErr::NoParent)?
+            .clone();
+
+        if !cons.is_hidden() {
+            return Ok(());
+        }
+
+        let cons = {
+            let mut p = layout.printer.write();
+            let (_, parent) = layout.get_parent(self.id).unwrap();
+            let rect = layout.get(self.id).unwrap();
+
+            let cons = cons.reveal(&mut p);
+            let cons = cons.apply(rect, parent.id(), &layout.rects, &mut p);
+            p.flush_equalities().unwrap();
+            cons
+        };
+
+        *layout.rects.get_constraints_mut(self.id).unwrap() = cons;
+
+        Ok(())
+    }
+
+    fn is_hidden(&self) -> bool {
+        let layout = self.layout.read();
+        layout.get_parent(self.id).is_some_and(|(pos, parent)| {
+            let children = parent.children().unwrap();
+            children[pos].1.is_hidden()
+        })
+    }
+
     fn request_width_to_fit(&self, _text: &str) -> Result<(), Self::ConstraintChangeErr> {
         todo!();
     }
@@ -375,6 +743,10 @@ impl ui::Area for Area {
         self.layout.read().get(self.id).unwrap().has_changed()
     }
 
+    fn is_floating(&self) -> bool {
+        self.layout.read().rects.is_floating(self.id)
+    }
+
     fn is_master_of(&self, other: &Self) -> bool {
         self.layout.inspect(|layout| {
             let mut parent_id = other.id;
@@ -389,6 +761,38 @@ impl ui::Area for Area {
         })
     }
 
+    fn layout_tree(&self) -> Vec<ui::LayoutNode<Self>> {
+        let tree = self.layout.read().tree();
+        tree.into_iter()
+            .map(|node| ui::LayoutNode {
+                area: Area::new(node.area, self.layout.clone()),
+                depth: node.depth,
+                tl: node.tl,
+                br: node.br,
+                axis: node.axis,
+                hor_con: node.hor_con,
+                ver_con: node.ver_con,
+                is_clustered: node.is_clustered,
+            })
+            .collect()
+    }
+
+    fn cluster_with(&self, other: &Self) -> Result<(), ConstraintErr> {
+        self.layout.write().cluster(self.id, other.id)
+    }
+
+    fn uncluster(&self) -> Result<(), ConstraintErr> {
+        self.layout.write().uncluster(self.id)
+    }
+
+    fn move_next_to(&self, target: &Self, side: Side) -> Result<(), ConstraintErr> {
+        self.layout.write().move_next_to(self.id, target.id, side)
+    }
+
+    fn swap(&self, other: &Self) -> Result<(), ConstraintErr> {
+        self.layout.write().swap(self.id, other.id)
+    }
+
     fn get_cluster_master(&self) -> Option<Self> {
         let clone = self.layout.clone();
         self.layout.inspect(|layout| {
@@ -426,13 +830,65 @@ impl ui::Area for Area {
         )
     }
 
+    fn spawn_floating(&self, specs: FloatSpecs, cache: PrintInfo) -> Area {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+
+        let id = layout.rects.new_floating(self.id, specs, &mut p, cache);
+        p.flush_equalities().unwrap();
+
+        Area::new(id, self.layout.clone())
+    }
+
+    fn set_float_specs(&self, specs: FloatSpecs) -> Result<(), ConstraintErr> {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+
+        if !layout.rects.set_float_specs(self.id, specs, &mut p) {
+            return Err(ConstraintErr::NoParent);
+        }
+        p.flush_equalities().unwrap();
+        Ok(())
+    }
+
+    fn float_specs(&self) -> Option<FloatSpecs> {
+        self.layout.read().rects.float_specs(self.id)
+    }
+
+    fn raise_floating(&self) -> Result<(), ConstraintErr> {
+        self.move_floating(true)
+    }
+
+    fn lower_floating(&self) -> Result<(), ConstraintErr> {
+        self.move_floating(false)
+    }
+
+    fn float_level(&self) -> Option<usize> {
+        self.layout.read().rects.float_level(self.id)
+    }
+
+    fn remove_floating(&self) -> Result<(), ConstraintErr> {
+        let mut layout = self.layout.write();
+        let layout = &mut *layout;
+        let mut p = layout.printer.write();
+
+        if !layout.rects.remove_floating(self.id, &mut p) {
+            return Err(ConstraintErr::NoParent);
+        }
+        p.flush_equalities().unwrap();
+        Ok(())
+    }
+
     fn print_iter<'a>(
         &self,
         iter: Iter<'a>,
         cfg: IterCfg,
     ) -> impl Iterator<Item = (Caret, Item)> + Clone + 'a {
         let points = iter.points();
-        print_iter(iter, cfg.wrap_width(self.width()), cfg, points)
+        let cap = cfg.wrap_width(cfg.padding().inner_width(self.width()));
+        print_iter(iter, cap, cfg, points)
     }
 
     fn print_iter_from_top<'a>(
@@ -449,8 +905,9 @@ impl ui::Area for Area {
         };
         let line_start = text.visual_line_start(info.points);
         let iter = text.iter_fwd(line_start);
+        let cap = cfg.wrap_width(cfg.padding().inner_width(self.width()));
 
-        print_iter(iter, cfg.wrap_width(self.width()), cfg, info.points)
+        print_iter(iter, cap, cfg, info.points)
     }
 
     fn rev_print_iter<'a>(
@@ -458,7 +915,8 @@ impl ui::Area for Area {
         iter: RevIter<'a>,
         cfg: IterCfg,
     ) -> impl Iterator<Item = (Caret, Item)> + Clone + 'a {
-        rev_print_iter(iter, cfg.wrap_width(self.width()), cfg)
+        let cap = cfg.wrap_width(cfg.padding().inner_width(self.width()));
+        rev_print_iter(iter, cap, cfg)
     }
 
     fn print_info(&self) -> Self::PrintInfo {
@@ -472,6 +930,153 @@ impl ui::Area for Area {
         *layout.get(self.id).unwrap().print_info().unwrap().write() = info;
     }
 
+    fn point_at_coord(&self, text: &Text, (x, y): (u32, u32), cfg: PrintCfg) -> Option<Point> {
+        let (coords, info) = {
+            let layout = self.layout.read();
+            let rect = layout.get(self.id)?;
+            let info = *rect.print_info()?.read();
+            (Coords::new(rect.tl(), rect.br()), info)
+        };
+
+        let cfg = IterCfg::new(cfg).outsource_lfs();
+        let pad = cfg.padding();
+        let (left, top) = (coords.tl.x + pad.left(), coords.tl.y + pad.top());
+        let width = pad.inner_width(coords.width());
+        let height = pad.inner_height(coords.height());
+        if x < left || y < top || x >= left + width || y >= top + height {
+            return None;
+        }
+        let (col, row) = (x - left + info.x_shift, y - top);
+
+        let cap = cfg.wrap_width(width);
+        let line_start = text.visual_line_start(info.points);
+        let iter = print_iter(text.iter_fwd(line_start), cap, cfg, info.points);
+
+        let mut cur_row: Option<u32> = None;
+        let mut align = Alignment::Left;
+        let mut on_row = Vec::new();
+        let mut last = None;
+
+        for (caret, Item { part, real, .. }) in iter {
+            if caret.wrap && part.is_char() {
+                let next = cur_row.map_or(0, |row| row + 1);
+                if next > row {
+                    break;
+                }
+                cur_row = Some(next);
+            }
+
+            match part {
+                Part::Char(_) => {
+                    last = Some(real);
+                    if cur_row == Some(row) {
+                        on_row.push((caret, real));
+                    }
+                }
+                Part::AlignLeft if !cfg.wrap_method().is_no_wrap() => align = Alignment::Left,
+                Part::AlignCenter if !cfg.wrap_method().is_no_wrap() => align = Alignment::Center,
+                Part::AlignRight if !cfg.wrap_method().is_no_wrap() => align = Alignment::Right,
+                _ => {}
+            }
+        }
+
+        let Some(&(_, first)) = on_row.first() else {
+            return last;
+        };
+
+        // Mirrors the realignment done when flushing the Lines.
+        let len = on_row
+            .iter()
+            .map(|(caret, _)| caret.x + caret.len)
+            .max()
+            .unwrap();
+        let align_start = match align {
+            Alignment::Left => 0,
+            Alignment::Right => cap.saturating_sub(len),
+            Alignment::Center => cap.saturating_sub(len) / 2,
+        };
+        let Some(col) = col.checked_sub(align_start) else {
+            return Some(first);
+        };
+
+        on_row
+            .iter()
+            .find(|(caret, _)| col < caret.x + caret.len)
+            .or(on_row.last())
+            .map(|(_, point)| *point)
+    }
+
+    fn coord_of_point(&self, text: &Text, point: Point, cfg: PrintCfg) -> Option<(u32, u32)> {
+        let (coords, info) = {
+            let layout = self.layout.read();
+            let rect = layout.get(self.id)?;
+            let info = *rect.print_info()?.read();
+            (Coords::new(rect.tl(), rect.br()), info)
+        };
+        if point < info.points.0 {
+            return None;
+        }
+
+        let cfg = IterCfg::new(cfg).outsource_lfs();
+        let pad = cfg.padding();
+        let (left, top) = (coords.tl.x + pad.left(), coords.tl.y + pad.top());
+        let width = pad.inner_width(coords.width());
+        let height = pad.inner_height(coords.height());
+
+        let cap = cfg.wrap_width(width);
+        let line_start = text.visual_line_start(info.points);
+        let iter = print_iter(text.iter_fwd(line_start), cap, cfg, info.points);
+
+        let mut cur_row: Option<u32> = None;
+        let mut align = Alignment::Left;
+        let mut on_row = Vec::new();
+        let mut found = None;
+
+        for (caret, Item { part, real, .. }) in iter {
+            if caret.wrap && part.is_char() {
+                if found.is_some() {
+                    break;
+                }
+                let next = cur_row.map_or(0, |row| row + 1);
+                if next >= height {
+                    return None;
+                }
+                cur_row = Some(next);
+                on_row.clear();
+            }
+
+            match part {
+                Part::Char(_) => {
+                    on_row.push(caret);
+                    if found.is_none() && real >= point {
+                        found = Some(caret);
+                    }
+                }
+                Part::AlignLeft if !cfg.wrap_method().is_no_wrap() => align = Alignment::Left,
+                Part::AlignCenter if !cfg.wrap_method().is_no_wrap() => align = Alignment::Center,
+                Part::AlignRight if !cfg.wrap_method().is_no_wrap() => align = Alignment::Right,
+                _ => {}
+            }
+        }
+
+        let (found, row) = (found?, cur_row?);
+
+        // Mirrors the realignment done when flushing the Lines.
+        let len = on_row
+            .iter()
+            .map(|caret| caret.x + caret.len)
+            .max()
+            .unwrap();
+        let align_start = match align {
+            Alignment::Left => 0,
+            Alignment::Right => cap.saturating_sub(len),
+            Alignment::Center => cap.saturating_sub(len) / 2,
+        };
+        let col = (align_start + found.x).checked_sub(info.x_shift)?;
+
+        (col < width).then_some((left + col, top + row))
+    }
+
     fn first_point(&self, _text: &Text, _cfg: PrintCfg) -> Point {
         let layout = self.layout.read();
         let rect = layout.get(self.id).unwrap();
@@ -492,10 +1097,11 @@ impl ui::Area for Area {
         let iter = text.iter_fwd(line_start);
         let cfg = IterCfg::new(cfg);
 
-        let iter = print_iter(iter, cfg.wrap_width(self.width()), cfg, info.points);
+        let cap = cfg.wrap_width(cfg.padding().inner_width(self.width()));
+        let iter = print_iter(iter, cap, cfg, info.points);
         let mut point = info.points.0;
         let mut y = 0;
-        let height = self.height();
+        let height = cfg.padding().inner_height(self.height());
 
         for (Caret { wrap, .. }, Item { part, real, .. }) in iter {
             if wrap {
@@ -517,10 +1123,13 @@ impl ui::Area for Area {
 unsafe impl Send for Area {}
 unsafe impl Sync for Area {}
 
+static WRAP_PREFIX: LazyLock<FormId> =
+    LazyLock::new(|| form::set_weak("WrapPrefix", "DefaultHint"));
+
 // NOTE: The defaultness in here, when it comes to `last_main`, may
 // cause issues in the future.
 /// Information about how to print the file on the `Label`.
-#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(crate = "duat_core::cache::serde")]
 pub struct PrintInfo {
     /// The index of the first [`char`] that should be printed on
@@ -533,6 +1142,11 @@ pub struct PrintInfo {
 }
 
 impl PrintInfo {
+    /// The first [`Point`] that is printed on the screen
+    pub fn first_char(&self) -> Point {
+        self.points.0
+    }
+
     fn fix(&mut self, text: &Text) {
         let max = text.len().min(self.points.0);
         let (_, max_ghost) = text.ghost_max_points_at(max.byte());
@@ -604,11 +1218,15 @@ fn scroll_hor_around(
                     .unwrap()
             };
 
-            let len = print_iter_indented(text.iter_fwd(points), cap, cfg, indent)
+            let line_start = text.visual_line_start(points);
+            let col = col_at(text.iter_fwd(line_start), cfg, points);
+            let len = print_iter_indented(text.iter_fwd(points), cap, cfg, indent, col)
                 .inspect(|(_, Item { part, .. })| match part {
-                    Part::AlignLeft => align = Alignment::Left,
-                    Part::AlignCenter => align = Alignment::Center,
-                    Part::AlignRight => align = Alignment::Right,
+                    Part::AlignLeft if !cfg.wrap_method().is_no_wrap() => align = Alignment::Left,
+                    Part::AlignCenter if !cfg.wrap_method().is_no_wrap() => {
+                        align = Alignment::Center
+                    }
+                    Part::AlignRight if !cfg.wrap_method().is_no_wrap() => align = Alignment::Right,
                     _ => {}
                 })
                 .take_while(|(caret, item)| !caret.wrap || item.points() == points)
@@ -621,8 +1239,8 @@ fn scroll_hor_around(
 
         let diff = match align {
             Alignment::Left => 0,
-            Alignment::Right => cap - line_len,
-            Alignment::Center => (cap - line_len) / 2,
+            Alignment::Right => cap.saturating_sub(line_len),
+            Alignment::Center => cap.saturating_sub(line_len) / 2,
         };
 
         (line_len + diff, start + diff, end + diff)
@@ -640,3 +1258,217 @@ fn scroll_hor_around(
         });
     info
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use duat_core::{
+        cfg::PrintCfg,
+        context::{self, CurFile, CurWidget},
+        data::RwData,
+        form,
+        mode::{self, Cursors},
+        text::{Point, Text},
+        ui::{Anchor, Area as UiArea, FloatSpecs, MasterOnLeft, Window},
+        widgets::{
+            CmdLine, DiffGutter, File, KeyHints, LineNumbers, StatusLine, Widget, WidgetCfg,
+        },
+    };
+
+    use super::{Area, PrintInfo};
+    use crate::{Frame, Ui, layout::Layout, print::Printer};
+
+    /// A root [`Area`], without going through the [`Ui`]
+    ///
+    /// [`Ui`]: crate::Ui
+    fn root_area() -> Area {
+        let printer = RwData::new(Printer::new());
+        let layout = Layout::new(Frame::Empty, printer.clone(), PrintInfo::default());
+        printer.write().flush_equalities().unwrap();
+        Area::new(layout.main_index(), RwData::new(layout))
+    }
+
+    /// Prints the [`Text`], then moves a cursor around in every
+    /// direction, from its start and its end
+    fn print_and_move(text: &Text, area: &Area) {
+        let cfg = PrintCfg::new();
+        UiArea::print(area, text, cfg, form::painter());
+
+        for point in [Point::default(), text.len()] {
+            area.scroll_around_point(text, point, cfg);
+            UiArea::print(area, text, cfg, form::painter());
+
+            let mut cursors = Cursors::new_excl();
+            cursors.insert_from_parts(0, point, 0, text, area, cfg);
+            let mut cursor = cursors.get_main().unwrap();
+            for by in [1, -1, 3, -3] {
+                cursor.move_hor(by, text, area, &cfg);
+                cursor.move_ver(by, text, area, &cfg);
+                cursor.move_ver_wrapped(by, text, area, &cfg);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_text() {
+        let area = root_area();
+        let text = Text::new();
+
+        assert!(area.width() > 0 && area.height() > 0);
+        print_and_move(&text, &area);
+        assert_eq!(area.first_point(&text, PrintCfg::new()), Point::default());
+    }
+
+    #[test]
+    fn zero_sized_area() {
+        let root = root_area();
+        for (width, height) in [(0, 0), (0, 3), (3, 0)] {
+            let specs = FloatSpecs::new(Anchor::TopLeft, width, height);
+            let area = root.spawn_floating(specs, PrintInfo::default());
+            assert_eq!((area.width(), area.height()), (width, height));
+
+            print_and_move(&Text::from("hello\nworld\n"), &area);
+            print_and_move(&Text::new(), &area);
+        }
+    }
+
+    #[test]
+    fn one_cell_area() {
+        let root = root_area();
+        let area =
+            root.spawn_floating(FloatSpecs::new(Anchor::TopLeft, 1, 1), PrintInfo::default());
+        assert_eq!((area.width(), area.height()), (1, 1));
+
+        print_and_move(&Text::from("a wide char: 漢字\n\tand a tab\n"), &area);
+        print_and_move(&Text::new(), &area);
+    }
+
+    #[test]
+    fn float_z_order_and_shading() {
+        let root = root_area();
+        let specs = FloatSpecs::new(Anchor::TopLeft, 10, 5);
+        let bottom = root.spawn_floating(specs, PrintInfo::default());
+        let top = root.spawn_floating(specs.with_shade(), PrintInfo::default());
+        assert_eq!(
+            (bottom.float_level(), top.float_level()),
+            (Some(0), Some(1))
+        );
+        assert_eq!(root.float_level(), None);
+
+        let is_shaded = |area: &Area| area.layout.read().rects.is_shaded(area.id);
+        assert!(is_shaded(&root) && is_shaded(&bottom) && !is_shaded(&top));
+
+        bottom.raise_floating().unwrap();
+        assert_eq!(
+            (bottom.float_level(), top.float_level()),
+            (Some(1), Some(0))
+        );
+        assert!(is_shaded(&root) && !is_shaded(&bottom) && !is_shaded(&top));
+
+        bottom.lower_floating().unwrap();
+        top.remove_floating().unwrap();
+        assert!(!is_shaded(&root) && !is_shaded(&bottom));
+        assert!(root.raise_floating().is_err());
+    }
+
+    #[test]
+    fn desired_col_over_tabs_and_wide_chars() {
+        let area = root_area();
+        let cfg = PrintCfg::new();
+        let text = Text::from("a\tbc\nx\n漢字cd\n");
+        UiArea::print(&area, &text, cfg, form::painter());
+
+        // The tab goes up to the fourth column, so "c" is on the sixth.
+        let mut cursors = Cursors::new_excl();
+        let i = cursors.insert_from_parts(0, text.point_at(3), 0, &text, &area, cfg);
+        let mut cursor = cursors.get(i).unwrap();
+        assert_eq!((cursor.col(), cursor.desired_col()), (5, 5));
+
+        cursor.move_ver(1, &text, &area, &cfg);
+        assert_eq!(
+            (cursor.line(), cursor.col(), cursor.desired_col()),
+            (1, 1, 5)
+        );
+
+        // Both wide chars take two columns, so "d" is on the sixth.
+        cursor.move_ver(1, &text, &area, &cfg);
+        assert_eq!(text.char_at(cursor.caret()), Some('d'));
+        assert_eq!((cursor.col(), cursor.desired_col()), (5, 5));
+
+        cursor.move_ver(-2, &text, &area, &cfg);
+        assert_eq!(text.char_at(cursor.caret()), Some('c'));
+
+        cursor.move_hor(-1, &text, &area, &cfg);
+        assert_eq!((cursor.col(), cursor.desired_col()), (4, 4));
+    }
+
+    #[test]
+    fn desired_col_survives_edits_on_other_lines() {
+        let area = root_area();
+        let cfg = PrintCfg::new();
+        let text = Text::from("a\tbc\nx\n漢字cd\n");
+        UiArea::print(&area, &text, cfg, form::painter());
+
+        let mut cursors = Cursors::new_excl();
+        let i = cursors.insert_from_parts(0, text.point_at(3), 0, &text, &area, cfg);
+        let mut cursor = cursors.get(i).unwrap();
+        cursor.move_ver(1, &text, &area, &cfg);
+
+        // As if a line had been added at the top.
+        let text = Text::from("new\na\tbc\nx\n漢字cd\n");
+        UiArea::print(&area, &text, cfg, form::painter());
+        cursor.shift_by((4, 4, 1), &text, &area, &cfg);
+        assert_eq!(
+            (cursor.line(), cursor.col(), cursor.desired_col()),
+            (2, 1, 5)
+        );
+
+        cursor.move_ver(1, &text, &area, &cfg);
+        assert_eq!(text.char_at(cursor.caret()), Some('d'));
+    }
+
+    #[test]
+    fn every_widget_in_zero_and_one_cell_areas() {
+        static CUR_FILE: CurFile<Ui> = CurFile::new();
+        static CUR_WIDGET: CurWidget<Ui> = CurWidget::new();
+        static WINDOWS: LazyLock<RwData<Vec<Window<Ui>>>> = LazyLock::new(RwData::default);
+        context::setup(&CUR_FILE, &CUR_WIDGET, 0, &WINDOWS);
+
+        let mut ui = Ui {
+            windows: Vec::new(),
+            printer: RwData::new(Printer::new()),
+            fr: Frame::Empty,
+        };
+        let (file, checker, _) = WidgetCfg::<Ui>::build(<File as Widget<Ui>>::cfg(), false);
+        let (window, node) = Window::new(&mut ui, file, checker, Box::new(MasterOnLeft));
+        let name = node.inspect_as(File::name).unwrap();
+        WINDOWS.write().push(window);
+
+        // Widgets that follow a File are built from the current one.
+        mode::reset_switch_to::<Ui>(name);
+        mode::was_set().unwrap()();
+
+        fn push<W: Widget<Ui>>(cfg: impl WidgetCfg<Ui, Widget = W>, on: &Area, (w, h): (u32, u32)) {
+            let (widget, checker, _) = cfg.build(true);
+            let specs = FloatSpecs::new(Anchor::TopLeft, w, h);
+            let node = WINDOWS.write()[0].push_floating(widget, on, checker, specs);
+            assert_eq!(
+                (node.view().area().width(), node.view().area().height()),
+                (w, h)
+            );
+
+            node.update_and_print();
+            node.update_and_print();
+        }
+
+        for size in [(0, 0), (1, 1)] {
+            push(<File as Widget<Ui>>::cfg(), node.view().area(), size);
+            push(LineNumbers::<Ui>::cfg(), node.view().area(), size);
+            push(DiffGutter::<Ui>::cfg(), node.view().area(), size);
+            push(StatusLine::<Ui>::cfg(), node.view().area(), size);
+            push(CmdLine::<Ui>::cfg(), node.view().area(), size);
+            push(KeyHints::<Ui>::cfg(), node.view().area(), size);
+        }
+    }
+}
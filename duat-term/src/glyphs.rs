@@ -0,0 +1,182 @@
+//! The widths of glyphs, as assumed by the [`Ui`]
+//!
+//! These usually come from the [`unicode-width`] crate but, depending
+//! on the terminal and font, some glyphs, like emoji variants, are
+//! printed wider than that, which corrupts the grid. The functions
+//! in here let you override those widths, as well as replace glyphs
+//! that can't be printed at all.
+//!
+//! [`Ui`]: crate::Ui
+//! [`unicode-width`]: unicode_width
+use std::{
+    ops::RangeInclusive,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
+
+use duat_core::{
+    cmd,
+    text::{err, ok},
+};
+use parking_lot::RwLock;
+use unicode_width::UnicodeWidthChar;
+
+static OVERRIDES: RwLock<Vec<(RangeInclusive<char>, u32)>> = RwLock::new(Vec::new());
+static HAS_OVERRIDES: AtomicBool = AtomicBool::new(false);
+static REPLACEMENT: AtomicU32 = AtomicU32::new(NO_REPLACEMENT);
+const NO_REPLACEMENT: u32 = u32::MAX;
+/// The zero width joiner, which joins glyphs into one
+const ZWJ: char = '\u{200d}';
+
+/// Sets the width of a [`char`] on screen
+///
+/// Use this if your terminal prints a glyph wider or narrower than
+/// what Duat assumes, which you can check with the `check-glyph`
+/// command.
+pub fn set_glyph_width(char: char, width: u32) {
+    set_range_width(char..=char, width)
+}
+
+/// Sets the width of a range of [`char`]s on screen
+///
+/// Later calls take precedence over earlier ones, so you can, for
+/// example, set the width of a whole block and then of a single
+/// [`char`] inside it.
+pub fn set_range_width(chars: RangeInclusive<char>, width: u32) {
+    OVERRIDES.write().push((chars, width));
+    HAS_OVERRIDES.store(true, Ordering::Relaxed);
+}
+
+/// Replaces unsupported [`char`]s with another when printing
+///
+/// Unsupported [`char`]s are those that Unicode assigns no width to,
+/// like control characters, and noncharacters like `U+FFFF`. These
+/// are printed as is by default, which may move the terminal's
+/// cursor unexpectedly. Passing [`None`] stops replacing them.
+pub fn replace_unsupported_with(replacement: Option<char>) {
+    let replacement = replacement.map_or(NO_REPLACEMENT, u32::from);
+    REPLACEMENT.store(replacement, Ordering::Relaxed);
+}
+
+/// The width that Duat assumes a [`char`] takes on screen
+///
+/// This takes into account the widths set with [`set_glyph_width`]
+/// and [`set_range_width`], as well as the replacement set with
+/// [`replace_unsupported_with`].
+pub fn glyph_width(char: char) -> u32 {
+    if HAS_OVERRIDES.load(Ordering::Relaxed)
+        && let Some(width) = overridden_width(char)
+    {
+        return width;
+    }
+
+    match replacement_of(char) {
+        Some(replacement) => overridden_width(replacement).unwrap_or(base_width(replacement)),
+        None => base_width(char),
+    }
+}
+
+/// The width of a [`char`], given the one that comes before it
+///
+/// A [`char`] that comes after a zero width joiner is drawn as part
+/// of the same glyph as the ones before it, like in "👩‍🔬", so it takes
+/// no extra space.
+pub(crate) fn glyph_width_after(char: char, prev: Option<char>) -> u32 {
+    if prev == Some(ZWJ) {
+        0
+    } else {
+        glyph_width(char)
+    }
+}
+
+/// The [`char`] that is actually printed in place of another
+pub(crate) fn displayed(char: char) -> char {
+    replacement_of(char).unwrap_or(char)
+}
+
+/// Adds the `check-glyph` command
+///
+/// `check-glyph <char>` tells what width Duat assumes a [`char`] has,
+/// and whether it is replaced when printing. The [`char`] can be
+/// given as is, or as a codepoint, like `U+2764`.
+pub(crate) fn add_check_glyph_command() {
+    // On reloads, the command may already be there.
+    let _ = cmd::add(["check-glyph"], |_flags, mut args| {
+        let arg = args.next()?;
+        args.ended()?;
+
+        let char = parse_char(arg).ok_or_else(|| err!([*a] arg [] " is not a character."))?;
+        let code = format!("U+{:04X}", char as u32);
+        let width = glyph_width(char);
+
+        if let Some(replacement) = replacement_of(char) {
+            ok!(
+                [*a] code [] " is unsupported, it is printed as " [*a] replacement []
+                ", which is " [*a] width [] " wide."
+            )
+        } else if HAS_OVERRIDES.load(Ordering::Relaxed) && overridden_width(char).is_some() {
+            ok!(
+                [*a] char [] " (" [*a] code [] ") is set to be " [*a] width []
+                " wide, instead of " [*a] { base_width(char) } [] "."
+            )
+        } else {
+            ok!([*a] char [] " (" [*a] code [] ") is " [*a] width [] " wide.")
+        }
+    });
+}
+
+/// The last width set for a [`char`], if there is one
+fn overridden_width(char: char) -> Option<u32> {
+    OVERRIDES
+        .read()
+        .iter()
+        .rev()
+        .find_map(|(chars, width)| chars.contains(&char).then_some(*width))
+}
+
+/// The [`char`] to print instead of an unsupported one
+fn replacement_of(char: char) -> Option<char> {
+    let replacement = REPLACEMENT.load(Ordering::Relaxed);
+    if replacement == NO_REPLACEMENT || !is_unsupported(char) {
+        None
+    } else {
+        char::from_u32(replacement)
+    }
+}
+
+fn is_unsupported(char: char) -> bool {
+    let code = char as u32;
+    // Tabs and new lines are handled separately.
+    let is_control = char.width().is_none() && !matches!(char, '\t' | '\n');
+    let is_noncharacter = (0xfdd0..=0xfdef).contains(&code) || code & 0xfffe == 0xfffe;
+    is_control || is_noncharacter
+}
+
+fn base_width(char: char) -> u32 {
+    UnicodeWidthChar::width(char).unwrap_or(0) as u32
+}
+
+/// Parses either a single [`char`] or a codepoint like `U+2764`
+fn parse_char(arg: &str) -> Option<char> {
+    let mut chars = arg.chars();
+    match (chars.next(), chars.next()) {
+        (Some(char), None) => Some(char),
+        _ => {
+            let hex = arg.strip_prefix("U+").or(arg.strip_prefix("u+"))?;
+            char::from_u32(u32::from_str_radix(hex, 16).ok()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_char;
+
+    #[test]
+    fn parses_chars_and_codepoints() {
+        assert_eq!(parse_char("a"), Some('a'));
+        assert_eq!(parse_char("U+2764"), Some('❤'));
+        assert_eq!(parse_char("u+1F600"), Some('😀'));
+        assert_eq!(parse_char("ab"), None);
+        assert_eq!(parse_char("U+D800"), None);
+    }
+}
@@ -1,11 +1,11 @@
 use cassowary::{WeightedRelation::*, strength::STRONG};
 use duat_core::{
     data::RwData,
-    ui::{Axis, Constraint, PushSpecs},
+    ui::{Axis, Constraint, LayoutNode, PushSpecs, Side},
 };
 
 use self::rect::{Rect, Rects};
-use crate::{AreaId, Equality, Frame, area::PrintInfo, print::Printer};
+use crate::{AreaId, ConstraintErr, Equality, Frame, area::PrintInfo, print::Printer};
 
 mod rect;
 
@@ -31,6 +31,7 @@ pub struct Constraints {
     hor_eq: Option<Equality>,
     ver_con: Option<Constraint>,
     hor_con: Option<Constraint>,
+    hidden: Option<(Axis, Option<Constraint>)>,
 }
 
 impl Constraints {
@@ -48,20 +49,70 @@ impl Constraints {
             hor_eq,
             ver_con: ps.ver_constraint(),
             hor_con: ps.hor_constraint(),
+            hidden: None,
         }
     }
 
-    pub fn replace(mut self, con: Constraint, axis: Axis, p: &mut Printer) -> Self {
+    /// Replaces the [`Constraint`] on an [`Axis`]
+    ///
+    /// If [`self`] is hidden on that [`Axis`], the [`Constraint`] is
+    /// only applied once it is [revealed].
+    ///
+    /// [revealed]: Constraints::reveal
+    pub fn replace(self, con: Constraint, axis: Axis, p: &mut Printer) -> Self {
+        let mut cons = self.clear(p);
+        if let Some((hidden_axis, prev)) = &mut cons.hidden
+            && *hidden_axis == axis
+        {
+            *prev = Some(con);
+            return cons;
+        }
+
+        match axis {
+            Axis::Vertical => cons.ver_con.replace(con),
+            Axis::Horizontal => cons.hor_con.replace(con),
+        };
+        cons
+    }
+
+    /// Shrinks the length on an [`Axis`] down to nothing
+    ///
+    /// The previous [`Constraint`] is kept, in order to be restored
+    /// by [`Constraints::reveal`].
+    pub fn hide(self, axis: Axis, p: &mut Printer) -> Self {
+        let prev = self.on(axis);
+        let mut cons = self.replace(Constraint::Length(0.0), axis, p);
+        cons.hidden = Some((axis, prev));
+        cons
+    }
+
+    /// Restores the [`Constraint`] from before [`Constraints::hide`]
+    pub fn reveal(self, p: &mut Printer) -> Self {
+        let mut cons = self.clear(p);
+        match cons.hidden.take() {
+            Some((Axis::Vertical, prev)) => cons.ver_con = prev,
+            Some((Axis::Horizontal, prev)) => cons.hor_con = prev,
+            None => {}
+        }
+        cons
+    }
+
+    /// Whether [`Constraints::hide`] was called
+    pub fn is_hidden(&self) -> bool {
+        self.hidden.is_some()
+    }
+
+    /// Removes the [`Equality`]s of [`self`], keeping its
+    /// [`Constraint`]s
+    ///
+    /// They can be added back with [`Constraints::apply`].
+    pub fn clear(mut self, p: &mut Printer) -> Self {
         for eq in [self.ver_eq.take(), self.hor_eq.take()]
             .into_iter()
             .flatten()
         {
             p.remove_equality(eq);
         }
-        match axis {
-            Axis::Vertical => self.ver_con.replace(con),
-            Axis::Horizontal => self.hor_con.replace(con),
-        };
         self
     }
 
@@ -81,13 +132,49 @@ impl Constraints {
         }
     }
 
-    /// Whether or not [`self`] has flexibility in terms of its length.
+    /// Whether or not [`self`] has flexibility in terms of its
+    /// length.
     fn is_resizable_on(&self, axis: Axis) -> bool {
         let con = self.on(axis);
         matches!(con, Some(Constraint::Min(_) | Constraint::Max(_)) | None)
     }
 }
 
+/// How the [`Area`]s inside of another are resized
+///
+/// This is consulted whenever the terminal is resized, and only
+/// affects [`Area`]s whose length is not fixed by a [`Constraint`].
+/// It is set with [`Area::set_resize_strategy`].
+///
+/// [`Area`]: crate::Area
+/// [`Area::set_resize_strategy`]: crate::Area::set_resize_strategy
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeStrategy {
+    /// The [`Area`]s try to have the same length
+    ///
+    /// This is the default.
+    ///
+    /// [`Area`]: crate::Area
+    #[default]
+    Equalize,
+    /// The [`Area`]s keep the ratios between their lengths
+    ///
+    /// If one [`Area`] is twice as long as the next one, it will
+    /// still be after the resize.
+    ///
+    /// [`Area`]: crate::Area
+    KeepRatios,
+    /// The [`Area`]s try to keep their lengths, with the last ones
+    /// giving way first
+    ///
+    /// When shrinking or growing, the last [`Area`] takes in all of
+    /// the difference, until it can't anymore, then the one before
+    /// it, and so on.
+    ///
+    /// [`Area`]: crate::Area
+    FixedPriority,
+}
+
 /// The overrall structure of a window on `duat_term`.
 ///
 /// The [`Layout`] handles all of the [`Rect`]s inside of it,
@@ -99,8 +186,9 @@ impl Constraints {
 /// The [`Layout`] also handles the [`Edge`]s that are supposed to be
 /// printed to the screen.
 ///
-/// The [`Layout`] will also hold floating [`Rect`]s, once those
-/// become a thing.
+/// The [`Layout`] also holds floating [`Rect`]s, which are anchored
+/// to other [`Rect`]s. These are kept in z-order, with the
+/// [`Printer`] printing them above the tiled ones.
 pub struct Layout {
     pub rects: Rects,
     pub active_id: AreaId,
@@ -195,6 +283,148 @@ impl Layout {
         (new_id, new_parent_id)
     }
 
+    /// Clusters two [`Rect`]s, so they move together
+    ///
+    /// Every [`Rect`] between the two and their closest common
+    /// ancestor is clustered as well, including said ancestor, which
+    /// becomes the cluster master of both.
+    pub fn cluster(&mut self, a: AreaId, b: AreaId) -> Result<(), ConstraintErr> {
+        if a == b {
+            return Err(ConstraintErr::Impossible);
+        }
+
+        let (a_lineage, b_lineage) = (self.rects.lineage(a), self.rects.lineage(b));
+        let Some((a_i, b_i)) = a_lineage
+            .iter()
+            .enumerate()
+            .find_map(|(a_i, id)| Some((a_i, b_lineage.iter().position(|b_id| b_id == id)?)))
+        else {
+            return Err(ConstraintErr::NoParent);
+        };
+
+        let master = a_lineage[a_i];
+        for id in a_lineage[1..=a_i].iter().chain(&b_lineage[1..=b_i]) {
+            self.rects.set_clustered(*id, true);
+        }
+
+        let mut p = self.printer.write();
+        self.rects.reset_eqs_of_children(master, &mut p);
+        p.flush_equalities().unwrap();
+
+        Ok(())
+    }
+
+    /// Dissolves the cluster that a [`Rect`] belongs to
+    ///
+    /// The cluster master and all of its clustered descendants stop
+    /// being clustered, so each [`Rect`] in it can be moved on its
+    /// own.
+    pub fn uncluster(&mut self, id: AreaId) -> Result<(), ConstraintErr> {
+        let master = self
+            .rects
+            .lineage(id)
+            .into_iter()
+            .skip(1)
+            .take_while(|id| self.rects.get(*id).is_some_and(Rect::is_clustered))
+            .last()
+            .ok_or(ConstraintErr::NoParent)?;
+
+        let mut clustered = vec![master];
+        let mut i = 0;
+        while let Some(id) = clustered.get(i).copied() {
+            let children = self
+                .rects
+                .get(id)
+                .and_then(Rect::children)
+                .unwrap_or_default();
+            clustered.extend(
+                children
+                    .iter()
+                    .filter(|(child, _)| child.is_clustered())
+                    .map(|(child, _)| child.id()),
+            );
+            i += 1;
+        }
+
+        for id in clustered {
+            self.rects.set_clustered(id, false);
+        }
+
+        let mut p = self.printer.write();
+        self.rects.reset_eqs_of_children(master, &mut p);
+        p.flush_equalities().unwrap();
+
+        Ok(())
+    }
+
+    /// Moves a [`Rect`] to a [`Side`] of another
+    ///
+    /// This is done at the cluster level: if either [`Rect`] is part
+    /// of a cluster, its whole cluster is moved (or moved next to)
+    /// instead, keeping the [`Constraints`] of every member. This
+    /// way, a file's satellites, like its line numbers, follow it
+    /// around.
+    pub fn move_next_to(
+        &mut self,
+        id: AreaId,
+        target: AreaId,
+        side: Side,
+    ) -> Result<(), ConstraintErr> {
+        let (id, target) = (self.movable(id), self.movable(target));
+        if self.is_related(id, target) {
+            return Err(ConstraintErr::Impossible);
+        }
+
+        let mut p = self.printer.write();
+        let rect = self
+            .rects
+            .detach(id, &mut p)
+            .ok_or(ConstraintErr::NoParent)?;
+        self.rects.insert_next_to(target, rect, side, &mut p);
+        p.flush_equalities().unwrap();
+
+        Ok(())
+    }
+
+    /// Swaps the positions of two [`Rect`]s
+    ///
+    /// Like with [`Layout::move_next_to`], this is done at the
+    /// cluster level.
+    pub fn swap(&mut self, a: AreaId, b: AreaId) -> Result<(), ConstraintErr> {
+        let (a, b) = (self.movable(a), self.movable(b));
+        if self.is_related(a, b) {
+            return Err(ConstraintErr::Impossible);
+        }
+
+        let mut p = self.printer.write();
+        if !self.rects.swap(a, b, &mut p) {
+            return Err(ConstraintErr::NoParent);
+        }
+        p.flush_equalities().unwrap();
+
+        Ok(())
+    }
+
+    /// The [`Rect`] that has to move in order to move another
+    ///
+    /// This is the cluster master, if the [`Rect`] is part of a
+    /// cluster, or the [`Rect`] itself otherwise.
+    fn movable(&self, id: AreaId) -> AreaId {
+        let lineage = self.rects.lineage(id);
+        lineage
+            .iter()
+            .skip(1)
+            .take_while(|id| self.rects.get(**id).is_some_and(Rect::is_clustered))
+            .last()
+            .copied()
+            .unwrap_or(id)
+    }
+
+    /// Whether one [`Rect`] is the ancestor of the other, or the same
+    fn is_related(&self, a: AreaId, b: AreaId) -> bool {
+        self.rects.lineage(a).contains(&b) || self.rects.lineage(b).contains(&a)
+    }
+
     /// The current value for the width of [`self`].
     pub fn width(&self) -> u32 {
         self.rects.main.len_value(Axis::Horizontal)
@@ -212,6 +442,36 @@ impl Layout {
     pub fn get_parent(&self, id: AreaId) -> Option<(usize, &Rect)> {
         self.rects.get_parent(id)
     }
+
+    /// The [`Rect`] tree, in pre-order, for introspection
+    pub fn tree(&self) -> Vec<LayoutNode<AreaId>> {
+        fn push(
+            rect: &Rect,
+            cons: Option<&Constraints>,
+            depth: usize,
+            nodes: &mut Vec<LayoutNode<AreaId>>,
+        ) {
+            let (tl, br) = (rect.tl(), rect.br());
+            nodes.push(LayoutNode {
+                area: rect.id(),
+                depth,
+                tl: (tl.x, tl.y),
+                br: (br.x, br.y),
+                axis: rect.axis(),
+                hor_con: cons.and_then(|cons| cons.on(Axis::Horizontal)),
+                ver_con: cons.and_then(|cons| cons.on(Axis::Vertical)),
+                is_clustered: rect.is_clustered(),
+            });
+
+            for (child, cons) in rect.children().into_iter().flatten() {
+                push(child, Some(cons), depth + 1, nodes);
+            }
+        }
+
+        let mut nodes = Vec::new();
+        push(&self.rects.main, None, 0, &mut nodes);
+        nodes
+    }
 }
 
 fn get_eqs(
@@ -1,19 +1,19 @@
 use cassowary::{
     Expression,
     WeightedRelation::{EQ, GE, LE},
-    strength::{REQUIRED, STRONG, WEAK},
+    strength::{MEDIUM, REQUIRED, STRONG, WEAK},
 };
 use duat_core::{
     data::RwData,
     ui::{
         Axis::{self, *},
-        Constraint, PushSpecs,
+        Constraint, FloatSpecs, PushSpecs, Side,
     },
 };
 
-use super::Constraints;
+use super::{Constraints, ResizeStrategy};
 use crate::{
-    Area, AreaId, Equality, Frame,
+    Area, AreaId, Brush, Equality, Frame,
     area::{Coord, PrintInfo},
     print::{Printer, Sender, VarPoint, VarValue},
 };
@@ -81,6 +81,11 @@ pub struct Rect {
     eqs: Vec<Equality>,
     kind: Kind,
     on_files: bool,
+    brush: Option<Brush>,
+    is_framed: bool,
+    float_parent: Option<AreaId>,
+    float_specs: Option<FloatSpecs>,
+    strategy: ResizeStrategy,
 }
 
 impl Rect {
@@ -94,9 +99,53 @@ impl Rect {
             eqs: Vec::new(),
             kind,
             on_files,
+            brush: None,
+            is_framed: true,
+            float_parent: None,
+            float_specs: None,
+            strategy: ResizeStrategy::default(),
         }
     }
 
+    /// Sets the [`Equality`]s of a floating [`Rect`]
+    ///
+    /// The size and position are only strong suggestions, so the
+    /// [`Rect`] is moved, and then shrunk, in order to fit on the
+    /// screen.
+    fn set_float_eqs(&mut self, parent: &Rect, specs: FloatSpecs, p: &mut Printer) {
+        self.clear_eqs(p);
+        self.float_parent = Some(parent.id());
+        self.float_specs = Some(specs);
+
+        let anchor = specs.anchor();
+        let (x, y) = (specs.offset().0 as f64, specs.offset().1 as f64);
+        let (width, height) = (specs.size().0 as f64, specs.size().1 as f64);
+
+        let hor_anchor = match anchor.is_left() {
+            true => self.tl.x() | EQ(MEDIUM) | (parent.tl.x() + x),
+            false => self.br.x() | EQ(MEDIUM) | (parent.br.x() + x),
+        };
+        let ver_anchor = match anchor.is_top() {
+            true => self.tl.y() | EQ(MEDIUM) | (parent.tl.y() + y),
+            false => self.br.y() | EQ(MEDIUM) | (parent.br.y() + y),
+        };
+
+        self.eqs.extend([
+            self.tl.x() | GE(REQUIRED) | 0.0,
+            self.tl.y() | GE(REQUIRED) | 0.0,
+            self.br.x() | LE(REQUIRED) | p.max().x(),
+            self.br.y() | LE(REQUIRED) | p.max().y(),
+            self.br.x() | GE(REQUIRED) | self.tl.x(),
+            self.br.y() | GE(REQUIRED) | self.tl.y(),
+            self.len(Horizontal) | EQ(STRONG) | width,
+            self.len(Vertical) | EQ(STRONG) | height,
+            hor_anchor,
+            ver_anchor,
+        ]);
+
+        p.add_equalities(&self.eqs);
+    }
+
     /// Sets the bare minimum equalities for a [`Rect`]
     ///
     /// This includes equalities to ensure no overlap, and a ratio
@@ -137,15 +186,31 @@ impl Rect {
             unreachable!();
         };
 
-        // If possible, try to make both Rects have the same length.
-        // This may not necessarily be the next child.
-        if is_resizable
-            && !clustered
-            && let Some((res, _)) = children[i..]
-                .iter()
-                .find(|(child, cons)| child.is_resizable_on(axis, cons))
-        {
-            self.eqs.push(self.len(axis) | EQ(WEAK) | res.len(axis));
+        // The next resizable Rect, which may not be the next child.
+        let next_res = children[i..]
+            .iter()
+            .find(|(child, cons)| child.is_resizable_on(axis, cons))
+            .map(|(res, _)| res);
+
+        if is_resizable {
+            let len = self.len_value(axis) as f64;
+            match (parent.strategy, next_res) {
+                (ResizeStrategy::KeepRatios, Some(res)) if len > 0.0 && res.len_value(axis) > 0 => {
+                    let res_len = res.len_value(axis) as f64;
+                    self.eqs
+                        .push((self.len(axis) * res_len) | EQ(WEAK) | (res.len(axis) * len));
+                }
+                // Earlier Rects hold on to their lengths more strongly.
+                (ResizeStrategy::FixedPriority, _) if len > 0.0 => {
+                    let weight = (children.len() + 1 - i) as f64;
+                    self.eqs.push(self.len(axis) | EQ(WEAK * weight) | len);
+                }
+                // If possible, try to make both Rects have the same length.
+                (_, Some(res)) if !clustered => {
+                    self.eqs.push(self.len(axis) | EQ(WEAK) | res.len(axis));
+                }
+                _ => {}
+            }
         }
 
         if let Some((next, _)) = children.get(i) {
@@ -154,7 +219,11 @@ impl Rect {
                 false => fr.files_edge_on(axis),
             };
 
-            if edge == 1.0 && !*clustered {
+            if edge == 1.0 && !*clustered && self.is_framed && next.is_framed {
+                let fr = match self.brush.or(next.brush) {
+                    Some(brush) => fr.with_brush(brush),
+                    None => fr,
+                };
                 let frame = p.edge(&self.br, &next.tl, axis, fr);
                 self.eqs.extend([
                     &frame | EQ(STRONG) | 1.0,
@@ -254,6 +323,17 @@ impl Rect {
         }
     }
 
+    /// The [`VarPoint`] of the top left corner of [`self`].
+    pub fn tl_var(&self) -> &VarPoint {
+        &self.tl
+    }
+
+    /// Sets the [`Brush`] of the edges around [`self`]
+    pub fn set_brush(&mut self, brush: Brush, p: &mut Printer) {
+        self.brush = Some(brush);
+        p.set_brush_around(&self.tl, &self.br, brush);
+    }
+
     pub fn has_changed(&self) -> bool {
         let br_x_changed = self.br.x().has_changed();
         let br_y_changed = self.br.y().has_changed();
@@ -274,6 +354,10 @@ impl Rect {
         }
     }
 
+    pub fn axis(&self) -> Option<Axis> {
+        self.kind.axis()
+    }
+
     pub fn aligns_with(&self, other: Axis) -> bool {
         match &self.kind {
             Kind::Middle { axis, .. } => *axis == other,
@@ -368,6 +452,7 @@ impl Rects {
             let kind = Kind::end(p.sender(&tl, &br), info);
             Rect::new(tl, br, on_files, kind)
         };
+        rect.is_framed = ps.is_framed();
         let new_id = rect.id();
 
         let (i, parent, cons, axis) = {
@@ -459,6 +544,337 @@ impl Rects {
         parent.kind.children_mut().unwrap().push((child, cons));
     }
 
+    /// The [`Frame`] of every [`Rect`]
+    pub fn frame(&self) -> Frame {
+        self.fr
+    }
+
+    /// Changes the [`Frame`] of every [`Rect`], solving their
+    /// [`Equality`]s again
+    pub fn set_frame(&mut self, fr: Frame, p: &mut Printer) {
+        self.fr = fr;
+        let ids: Vec<AreaId> = std::iter::once(&self.main)
+            .chain(&self.floating)
+            .map(Rect::id)
+            .collect();
+
+        for id in ids {
+            self.reset_eqs_of_children(id, p);
+        }
+    }
+
+    /// Sets whether the edges around a [`Rect`] should be framed
+    pub fn set_framed(&mut self, id: AreaId, is_framed: bool, p: &mut Printer) {
+        let Some(rect) = self.get_mut(id) else {
+            return;
+        };
+        rect.is_framed = is_framed;
+
+        if let Some((_, parent)) = self.get_parent(id) {
+            let parent_id = parent.id();
+            self.reset_eqs_of_children(parent_id, p);
+        }
+    }
+
+    /// Adds a floating [`Rect`], anchored to the one with the given
+    /// [`AreaId`]
+    pub fn new_floating(
+        &mut self,
+        parent: AreaId,
+        specs: FloatSpecs,
+        p: &mut Printer,
+        info: PrintInfo,
+    ) -> AreaId {
+        let mut rect = {
+            let (tl, br) = (p.var_point(), p.var_point());
+            let kind = Kind::end(p.floating_sender(&tl, &br), info);
+            Rect::new(tl, br, false, kind)
+        };
+        rect.is_framed = false;
+
+        rect.set_float_eqs(self.get(parent).unwrap(), specs, p);
+        let id = rect.id();
+        self.floating.push(rect);
+
+        id
+    }
+
+    /// Changes the [`FloatSpecs`] of a floating [`Rect`]
+    ///
+    /// Returns `false` if the [`Rect`] is not floating.
+    pub fn set_float_specs(&mut self, id: AreaId, specs: FloatSpecs, p: &mut Printer) -> bool {
+        let Some(i) = self.floating.iter().position(|rect| rect.id == id) else {
+            return false;
+        };
+        let mut rect = self.floating.remove(i);
+        // The parent may have been removed in the meantime.
+        let parent = self.get(rect.float_parent.unwrap()).unwrap_or(&self.main);
+
+        rect.set_float_eqs(parent, specs, p);
+        self.floating.insert(i, rect);
+
+        true
+    }
+
+    /// Removes a floating [`Rect`]
+    ///
+    /// Returns `false` if the [`Rect`] is not floating.
+    pub fn remove_floating(&mut self, id: AreaId, p: &mut Printer) -> bool {
+        let Some(i) = self.floating.iter().position(|rect| rect.id == id) else {
+            return false;
+        };
+        let mut rect = self.floating.remove(i);
+
+        rect.clear_eqs(p);
+        if let Some(sender) = rect.sender() {
+            p.remove_sender(sender);
+        }
+
+        true
+    }
+
+    /// Removes a [`Rect`] from its parent, along with its
+    /// [`Constraints`]
+    ///
+    /// The [`Equality`]s of the [`Rect`] are cleared, and the ones of
+    /// its former siblings are set again, so they fill the space that
+    /// it left behind. Returns [`None`] if the [`Rect`] has no
+    /// parent.
+    pub fn detach(&mut self, id: AreaId, p: &mut Printer) -> Option<(Rect, Constraints)> {
+        let (i, parent) = self.get_parent_mut(id)?;
+        let parent_id = parent.id();
+
+        let (mut rect, cons) = parent.kind.children_mut().unwrap().remove(i);
+        rect.clear_eqs(p);
+        let cons = cons.clear(p);
+
+        self.reset_eqs_of_children(parent_id, p);
+        Some((rect, cons))
+    }
+
+    /// Inserts a detached [`Rect`] on a [`Side`] of another
+    ///
+    /// If the parent of the target isn't aligned with the [`Side`], a
+    /// new parent is created to hold both of them, unless the target
+    /// is its only child, in which case the [`Axis`] of the parent is
+    /// just changed.
+    pub fn insert_next_to(
+        &mut self,
+        target: AreaId,
+        (rect, cons): (Rect, Constraints),
+        side: Side,
+        p: &mut Printer,
+    ) {
+        let (axis, comes_earlier) = match side {
+            Side::Above => (Vertical, true),
+            Side::Below => (Vertical, false),
+            Side::Left => (Horizontal, true),
+            Side::Right => (Horizontal, false),
+        };
+
+        if let Some((_, parent)) = self.get_parent_mut(target)
+            && let Kind::Middle { children, axis: p_axis, clustered: false } = &mut parent.kind
+            && children.len() == 1
+        {
+            *p_axis = axis;
+        }
+
+        if !self
+            .get_parent(target)
+            .is_some_and(|(_, parent)| parent.aligns_with(axis))
+        {
+            let on_files = self.get(target).unwrap().on_files;
+            self.new_parent_of(target, axis, p, false, on_files);
+        }
+
+        let (i, parent) = self.get_parent(target).unwrap();
+        let (i, parent_id) = (i + !comes_earlier as usize, parent.id());
+        let cons = cons.apply(&rect, parent_id, self, p);
+
+        self.insert_entry(parent_id, i, (rect, cons));
+        self.reset_eqs_of_children(parent_id, p);
+    }
+
+    /// Swaps the positions of two [`Rect`]s
+    ///
+    /// Neither of them can be an ancestor of the other. Returns
+    /// `false` if either of them has no parent.
+    pub fn swap(&mut self, a: AreaId, b: AreaId, p: &mut Printer) -> bool {
+        let (Some((a_i, a_parent)), Some((b_i, b_parent))) = (
+            self.get_parent(a).map(|(i, parent)| (i, parent.id())),
+            self.get_parent(b).map(|(i, parent)| (i, parent.id())),
+        ) else {
+            return false;
+        };
+
+        if a_parent == b_parent {
+            let parent = self.get_mut(a_parent).unwrap();
+            parent.kind.children_mut().unwrap().swap(a_i, b_i);
+        } else {
+            let (a_rect, a_cons) = self.remove_child(a_parent, a_i);
+            let (b_rect, b_cons) = self.remove_child(b_parent, b_i);
+
+            let a_cons = a_cons.clear(p).apply(&a_rect, b_parent, self, p);
+            let b_cons = b_cons.clear(p).apply(&b_rect, a_parent, self, p);
+
+            self.insert_entry(a_parent, a_i, (b_rect, b_cons));
+            self.insert_entry(b_parent, b_i, (a_rect, a_cons));
+
+            self.reset_eqs_of_children(b_parent, p);
+        }
+
+        self.reset_eqs_of_children(a_parent, p);
+        true
+    }
+
+    fn remove_child(&mut self, parent: AreaId, i: usize) -> (Rect, Constraints) {
+        let parent = self.get_mut(parent).unwrap();
+        parent.kind.children_mut().unwrap().remove(i)
+    }
+
+    fn insert_entry(&mut self, parent: AreaId, i: usize, entry: (Rect, Constraints)) {
+        let parent = self.get_mut(parent).unwrap();
+        parent.kind.children_mut().unwrap().insert(i, entry)
+    }
+
+    /// Whether the [`Rect`] is floating
+    pub fn is_floating(&self, id: AreaId) -> bool {
+        self.floating.iter().any(|rect| rect.id == id)
+    }
+
+    /// The [`FloatSpecs`] of a floating [`Rect`]
+    pub fn float_specs(&self, id: AreaId) -> Option<FloatSpecs> {
+        self.floating
+            .iter()
+            .find(|rect| rect.id == id)
+            .and_then(|rect| rect.float_specs)
+    }
+
+    /// The position of a floating [`Rect`] in the z-order
+    ///
+    /// Floating [`Rect`]s are kept in z-order, from the bottom to
+    /// the top.
+    pub fn float_level(&self, id: AreaId) -> Option<usize> {
+        self.floating.iter().position(|rect| rect.id == id)
+    }
+
+    /// Moves a floating [`Rect`] to the top or bottom of the z-order
+    ///
+    /// Returns `false` if the [`Rect`] is not floating.
+    pub fn move_floating(&mut self, id: AreaId, to_top: bool, p: &mut Printer) -> bool {
+        let Some(i) = self.float_level(id) else {
+            return false;
+        };
+        let rect = self.floating.remove(i);
+        if let Some(sender) = rect.sender() {
+            p.move_float(sender, to_top);
+        }
+
+        match to_top {
+            true => self.floating.push(rect),
+            false => self.floating.insert(0, rect),
+        }
+        true
+    }
+
+    /// Whether a [`Rect`] is beneath a floating one that shades
+    ///
+    /// Tiled [`Rect`]s are beneath every floating one, while
+    /// floating ones are only beneath those above them in the
+    /// z-order.
+    pub fn is_shaded(&self, id: AreaId) -> bool {
+        let above = match self.float_level(id) {
+            Some(level) => &self.floating[level + 1..],
+            None => &self.floating[..],
+        };
+        above
+            .iter()
+            .any(|rect| rect.float_specs.is_some_and(|specs| specs.shades()))
+    }
+
+    /// Sets whether the children of a [`Rect`] are clustered
+    ///
+    /// This doesn't set the [`Equality`]s again, so
+    /// [`Rects::reset_eqs_of_children`] should be called afterwards.
+    /// Returns `false` if the [`Rect`] has no children.
+    pub fn set_clustered(&mut self, id: AreaId, is_clustered: bool) -> bool {
+        match self.get_mut(id).map(|rect| &mut rect.kind) {
+            Some(Kind::Middle { clustered, .. }) => {
+                *clustered = is_clustered;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The `id` followed by the ids of its ancestors, up to the root
+    pub fn lineage(&self, id: AreaId) -> Vec<AreaId> {
+        let mut lineage = vec![id];
+        while let Some((_, parent)) = self.get_parent(*lineage.last().unwrap()) {
+            lineage.push(parent.id());
+        }
+        lineage
+    }
+
+    /// Sets the base [`Equality`]s of the children of a [`Rect`]
+    /// again
+    ///
+    /// Since [`Rect::set_base_eqs`] is recursive, this affects every
+    /// descendant of said [`Rect`].
+    pub fn reset_eqs_of_children(&mut self, id: AreaId, p: &mut Printer) {
+        let fr = self.fr;
+        let parent = self.get_mut(id).unwrap();
+        let Some(axis) = parent.kind.axis() else {
+            return;
+        };
+
+        for i in 0..parent.kind.children().unwrap().len() {
+            let (mut child, cons) = parent.kind.children_mut().unwrap().remove(i);
+
+            let is_resizable = child.is_resizable_on(axis, &cons);
+            child.set_base_eqs(i, parent, p, fr, is_resizable);
+
+            parent.kind.children_mut().unwrap().insert(i, (child, cons));
+        }
+    }
+
+    /// Sets the [`ResizeStrategy`] for the children of a [`Rect`]
+    ///
+    /// If the [`Rect`] has no children, it is set for its siblings
+    /// instead.
+    pub fn set_resize_strategy(&mut self, id: AreaId, strategy: ResizeStrategy, p: &mut Printer) {
+        let id = match self.get(id) {
+            Some(rect) if rect.kind.children().is_some() => id,
+            Some(_) if let Some((_, parent)) = self.get_parent(id) => parent.id(),
+            _ => return,
+        };
+
+        self.get_mut(id).unwrap().strategy = strategy;
+        self.reset_eqs_of_children(id, p);
+    }
+
+    /// Updates the [`Equality`]s that depend on the current lengths
+    /// of [`Rect`]s, before they are resized
+    ///
+    /// These are the ones set by [`ResizeStrategy`]s other than
+    /// [`ResizeStrategy::Equalize`].
+    pub fn prepare_for_resize(&mut self, p: &mut Printer) {
+        fn strategic(rect: &Rect, ids: &mut Vec<AreaId>) {
+            if rect.strategy != ResizeStrategy::Equalize {
+                ids.push(rect.id);
+            }
+            for (child, _) in rect.kind.children().into_iter().flatten() {
+                strategic(child, ids);
+            }
+        }
+
+        let mut ids = Vec::new();
+        strategic(&self.main, &mut ids);
+        for id in ids {
+            self.reset_eqs_of_children(id, p);
+        }
+    }
+
     /// Gets a mut reference to the parent of the `id`'s [`Rect`]
     pub fn get_mut(&mut self, id: AreaId) -> Option<&mut Rect> {
         std::iter::once(&mut self.main)
@@ -9,30 +9,182 @@
 
 use std::{
     fmt::Debug,
-    io,
-    sync::{OnceLock, atomic::Ordering},
+    fs,
+    io::{self, IsTerminal, Write},
+    sync::{
+        LazyLock, Mutex, MutexGuard, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
     time::Duration,
 };
 
 pub use area::{Area, Coords};
+use base64::Engine;
 use crossterm::{
     cursor, event, execute,
     terminal::{self, ClearType},
 };
-use duat_core::{DuatError, data::RwData, text::err, ui};
+use duat_core::{
+    DuatError, cmd, context,
+    data::RwData,
+    mode::MouseEvent,
+    text::{err, ok},
+    ui::{self, Area as UiArea},
+};
 
-use self::{layout::Layout, print::Printer};
 pub use self::{
-    print::{Brush, Frame},
+    a11y::{linear_view, screen_reader_is_on, set_screen_reader, socket_path},
+    glyphs::{glyph_width, replace_unsupported_with, set_glyph_width, set_range_width},
+    layout::ResizeStrategy,
+    print::{
+        Brush, BrushSet, Frame, Title, set_styled_underlines, styled_underlines_are_supported,
+    },
     rules::{VertRule, VertRuleCfg},
+    scroll_bar::{ScrollBar, ScrollBarCfg},
 };
+use self::{layout::Layout, print::Printer};
 
+mod a11y;
 mod area;
+mod glyphs;
 mod layout;
 mod print;
 mod rules;
+mod scroll_bar;
+#[cfg(test)]
+mod virt;
 
 static FUNCTIONS: OnceLock<StaticFns> = OnceLock::new();
+static FRAME: parking_lot::Mutex<Frame> = parking_lot::Mutex::new(Frame::Border(Brush::Regular));
+static UI_WAS_CREATED: AtomicBool = AtomicBool::new(false);
+static TTY: LazyLock<Option<Mutex<fs::File>>> = LazyLock::new(|| {
+    if io::stdout().is_terminal() {
+        None
+    } else {
+        fs::OpenOptions::new()
+            .write(true)
+            .open("/dev/tty")
+            .ok()
+            .map(Mutex::new)
+    }
+});
+
+/// Sets the [`Frame`] used by all windows
+///
+/// This must be called in the `setup` function of the config, since
+/// the [`Frame`] is read when the [`Ui`] is created. Calling it
+/// afterwards does nothing besides notifying an error. To change the
+/// [`Frame`] at runtime, use the `frame set` command, and to change
+/// the [`Brush`] of a single cluster, use [`Area::set_brush`].
+pub fn set_frame(fr: Frame) {
+    if UI_WAS_CREATED.load(Ordering::Relaxed) {
+        context::notify(err!(
+            [*a] "set_frame" [] " was called after the " [*a] "Ui" [] " was created, use "
+            [*a] "frame set" [] " instead."
+        ));
+    } else {
+        *FRAME.lock() = fr;
+    }
+}
+
+/// Adds the `frame` command
+///
+/// `frame set <brush> [target]` changes the [`Brush`] of the edges
+/// in the current window, or those around a cluster. The target can
+/// be `window` (the default), `cluster`, for the cluster of the
+/// current widget, or `widget`, for the current widget alone. The
+/// `none` brush removes the edges entirely, solving the layout
+/// again.
+fn add_frame_command() {
+    // On reloads, the command may already be there.
+    let _ = cmd::add(["frame"], |_flags, mut args| {
+        let subcommand = args.next()?;
+        if subcommand != "set" {
+            return Err(err!("Unknown subcommand " [*a] subcommand [] "."));
+        }
+
+        let name = args.next()?.to_string();
+        let brush = match name.as_str() {
+            "none" => None,
+            name => {
+                Some(Brush::from_name(name).ok_or_else(|| err!([*a] name [] " is not a brush."))?)
+            }
+        };
+        let target = args.next().unwrap_or("window").to_string();
+        args.ended()?;
+
+        let view = context::cur_view::<Ui>()?;
+        let area = view.area();
+
+        match target.as_str() {
+            "window" => area.set_frame(match (area.frame(), brush) {
+                (_, None) => Frame::Empty,
+                (Frame::Empty, Some(brush)) => Frame::Border(brush),
+                (fr, Some(brush)) => fr.with_brush(brush),
+            }),
+            "cluster" | "widget" => {
+                let area = match target.as_str() {
+                    "cluster" => area.get_cluster_master().unwrap_or_else(|| area.clone()),
+                    _ => area.clone(),
+                };
+                area.set_framed(brush.is_some());
+                if let Some(brush) = brush {
+                    area.set_brush(brush);
+                }
+            }
+            target => return Err(err!("Unknown target " [*a] target [] ".")),
+        }
+
+        ok!("Set the frame of the " [*a] target [] " to " [*a] name [] ".")
+    });
+}
+
+/// Where the terminal is printed to
+///
+/// This is usually stdout but, if Duat is part of a pipeline (e.g.
+/// when used as a filter), stdout won't be a terminal, so `/dev/tty`
+/// is printed to instead.
+fn output() -> Output {
+    #[cfg(test)]
+    if virt::is_active() {
+        return Output::Virtual;
+    }
+
+    match TTY.as_ref() {
+        Some(tty) => Output::Tty(tty.lock().unwrap()),
+        None => Output::Stdout(io::stdout().lock()),
+    }
+}
+
+enum Output {
+    Stdout(io::StdoutLock<'static>),
+    Tty(MutexGuard<'static, fs::File>),
+    #[cfg(test)]
+    Virtual,
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(stdout) => stdout.write(buf),
+            Output::Tty(tty) => tty.write(buf),
+            #[cfg(test)]
+            Output::Virtual => {
+                virt::write(buf);
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::Tty(tty) => tty.flush(),
+            #[cfg(test)]
+            Output::Virtual => Ok(()),
+        }
+    }
+}
 
 pub struct Ui {
     windows: Vec<Area>,
@@ -50,10 +202,12 @@ impl ui::Ui for Ui {
         std::panic::set_hook(Box::new(|info| {
             let trace = std::backtrace::Backtrace::capture();
             execute!(
-                io::stdout(),
+                output(),
                 terminal::Clear(ClearType::All),
                 terminal::LeaveAlternateScreen,
                 terminal::EnableLineWrap,
+                event::DisableMouseCapture,
+                event::DisableBracketedPaste,
                 cursor::Show
             )
             .unwrap();
@@ -64,29 +218,44 @@ impl ui::Ui for Ui {
             println!("{info}")
         }));
 
+        add_frame_command();
+        glyphs::add_check_glyph_command();
+        UI_WAS_CREATED.store(true, Ordering::Relaxed);
+
         Ui {
             windows: Vec::new(),
             printer: RwData::new(Printer::new()),
-            fr: Frame::default(),
+            fr: *FRAME.lock(),
         }
     }
 
     fn start(&mut self, sender: ui::Sender) {
         let functions = FUNCTIONS.get().unwrap();
         let printer = self.printer.clone();
+        let windows = self.windows.clone();
+        if a11y::screen_reader_is_on() {
+            a11y::serve();
+        }
+
         duat_core::thread::spawn(move || {
+            let announcer = a11y::Announcer::new();
             loop {
                 if let Ok(true) = (functions.poll)() {
                     let res = match (functions.read)().unwrap() {
                         event::Event::Key(key) => sender.send_key(key),
+                        event::Event::Mouse(mouse) => {
+                            let coord = (mouse.column as u32, mouse.row as u32);
+                            sender.send_mouse(MouseEvent::new(mouse.kind, coord, mouse.modifiers))
+                        }
                         event::Event::Resize(..) => {
+                            for window in &windows {
+                                window.prepare_for_resize();
+                            }
                             printer.write().update(true);
                             sender.send_resize()
                         }
-                        event::Event::FocusGained
-                        | event::Event::FocusLost
-                        | event::Event::Mouse(_)
-                        | event::Event::Paste(_) => Ok(()),
+                        event::Event::Paste(text) => sender.send_paste(text),
+                        event::Event::FocusGained | event::Event::FocusLost => Ok(()),
                     };
 
                     if res.is_err() {
@@ -95,6 +264,7 @@ impl ui::Ui for Ui {
                 }
 
                 printer.read().print();
+                announcer.announce_changes();
 
                 if duat_core::has_ended() {
                     break;
@@ -117,9 +287,11 @@ impl ui::Ui for Ui {
 
     fn open(&mut self) {
         execute!(
-            io::stdout(),
+            output(),
             terminal::EnterAlternateScreen,
-            terminal::DisableLineWrap
+            terminal::DisableLineWrap,
+            event::EnableMouseCapture,
+            event::EnableBracketedPaste
         )
         .unwrap();
         terminal::enable_raw_mode().unwrap();
@@ -129,10 +301,12 @@ impl ui::Ui for Ui {
 
     fn close(&mut self) {
         execute!(
-            io::stdout(),
+            output(),
             terminal::Clear(ClearType::All),
             terminal::LeaveAlternateScreen,
             terminal::EnableLineWrap,
+            event::DisableMouseCapture,
+            event::DisableBracketedPaste,
             cursor::Show
         )
         .unwrap();
@@ -150,6 +324,14 @@ impl ui::Ui for Ui {
     fn flush_layout(&mut self) {
         self.printer.write().flush_equalities().unwrap();
     }
+
+    fn set_clipboard(str: &str) {
+        // OSC 52, which most terminals support, even through ssh.
+        let str = base64::prelude::BASE64_STANDARD.encode(str);
+        let mut output = output();
+        let _ = write!(output, "\x1b]52;c;{str}\x07");
+        let _ = output.flush();
+    }
 }
 
 #[derive(Clone, Copy)]
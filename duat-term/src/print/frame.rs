@@ -1,14 +1,21 @@
-use std::sync::{
-    Arc,
-    atomic::{AtomicU32, Ordering},
+use std::{
+    fmt::Alignment,
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicU32, Ordering},
+    },
 };
 
-use duat_core::ui::Axis;
+use duat_core::{
+    data::RoData,
+    form::{self, FormId},
+    ui::Axis,
+};
 
 use crate::{area::Coord, print::VarPoint};
 
 /// What type of line should separate widgets
-#[derive(Default, Clone, Copy, Debug)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Brush {
     /// Uses `─`, `│`, `┐`
     #[default]
@@ -27,6 +34,97 @@ pub enum Brush {
     Ascii,
     /// Uses `char` for all positions
     Custom(char),
+    /// Uses a custom [`BrushSet`]
+    Set(BrushSet),
+}
+
+impl Brush {
+    /// The [`Brush`] with a given name
+    ///
+    /// The names are the variants in kebab case, like
+    /// `"thick-dashed"`. A single character is turned into a
+    /// [`Brush::Custom`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "regular" => Brush::Regular,
+            "thick" => Brush::Thick,
+            "dashed" => Brush::Dashed,
+            "thick-dashed" => Brush::ThickDashed,
+            "double" => Brush::Double,
+            "rounded" => Brush::Rounded,
+            "ascii" => Brush::Ascii,
+            name => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(char), None) => Brush::Custom(char),
+                    _ => return None,
+                }
+            }
+        })
+    }
+}
+
+/// A set of characters for every position of a [`Brush`]
+///
+/// When edges of a [`Brush::Set`] meet edges with other [`Brush`]es,
+/// the characters of the [`BrushSet`] take precedence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BrushSet {
+    /// Like `─`
+    pub horizontal: char,
+    /// Like `│`
+    pub vertical: char,
+    /// Like `┌`
+    pub right_down: char,
+    /// Like `┐`
+    pub left_down: char,
+    /// Like `└`
+    pub right_up: char,
+    /// Like `┘`
+    pub left_up: char,
+    /// Like `├`
+    pub right_vertical: char,
+    /// Like `┤`
+    pub left_vertical: char,
+    /// Like `┬`
+    pub down_horizontal: char,
+    /// Like `┴`
+    pub up_horizontal: char,
+    /// Like `┼`
+    pub cross: char,
+}
+
+impl BrushSet {
+    /// Returns a new [`BrushSet`], in the order `─│┌┐└┘├┤┬┴┼`
+    pub const fn new(chars: [char; 11]) -> Self {
+        let [
+            horizontal,
+            vertical,
+            right_down,
+            left_down,
+            right_up,
+            left_up,
+            right_vertical,
+            left_vertical,
+            down_horizontal,
+            up_horizontal,
+            cross,
+        ] = chars;
+
+        Self {
+            horizontal,
+            vertical,
+            right_down,
+            left_down,
+            right_up,
+            left_up,
+            right_vertical,
+            left_vertical,
+            down_horizontal,
+            up_horizontal,
+            cross,
+        }
+    }
 }
 
 /// Details of the right/bottom edge of a widget
@@ -40,6 +138,17 @@ pub struct Edge {
 }
 
 impl Edge {
+    /// Whether this [`Edge`] is right after `br` or right before
+    /// `tl`
+    pub fn borders(&self, tl: &VarPoint, br: &VarPoint) -> bool {
+        self.lhs == *br || self.rhs == *tl
+    }
+
+    /// Changes the [`Brush`] used by this [`Edge`]
+    pub fn set_brush(&mut self, brush: Brush) {
+        self.fr = self.fr.with_brush(brush);
+    }
+
     /// Returns a new instance of [`Edge`].
     pub fn new(
         width: &Arc<AtomicU32>,
@@ -88,58 +197,6 @@ impl EdgeCoords {
     fn new(tl: Coord, br: Coord, axis: Axis, line: Option<Brush>) -> Self {
         Self { tl, br, axis, line }
     }
-
-    #[allow(clippy::type_complexity)]
-    pub fn crossing(&self, other: EdgeCoords) -> Option<(Coord, [Option<Brush>; 4])> {
-        if let Axis::Vertical = self.axis {
-            if let Axis::Vertical = other.axis
-                && self.br.x == other.tl.x
-                && self.br.y + 2 == other.tl.y
-            {
-                let coord = Coord::new(self.br.x, self.br.y + 1);
-                return Some((coord, [None, self.line, None, other.line]));
-            } else {
-                return other.crossing(*self);
-            }
-        }
-
-        if let Axis::Horizontal = other.axis {
-            if self.br.y == other.br.y && self.br.x + 2 == other.tl.x {
-                let coord = Coord::new(self.br.x + 1, self.br.y);
-                Some((coord, [other.line, None, self.line, None]))
-            } else {
-                None
-            }
-        } else if self.tl.x <= other.tl.x + 1 && other.tl.x <= self.br.x + 1 {
-            let right_height = (other.tl.y..=other.br.y + 2).contains(&(self.br.y + 1));
-            let right = match right_height && other.br.x <= self.br.x {
-                true => self.line,
-                false => None,
-            };
-            let up = match other.tl.y <= self.tl.y && self.br.y <= other.br.y + 1 {
-                true => other.line,
-                false => None,
-            };
-            let left = match right_height && self.tl.x <= other.tl.x {
-                true => self.line,
-                false => None,
-            };
-            let down = match self.br.y <= other.br.y && other.tl.y <= self.tl.y + 1 {
-                true => other.line,
-                false => None,
-            };
-
-            if up.is_some() || down.is_some() {
-                let coord = Coord { x: other.tl.x, y: self.tl.y };
-
-                Some((coord, [right, up, left, down]))
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }
 }
 
 /// Where to apply a [`Brush`] around widgets
@@ -209,6 +266,19 @@ impl Frame {
         }
     }
 
+    /// The same [`Frame`], but with a different [`Brush`]
+    pub fn with_brush(self, brush: Brush) -> Self {
+        match self {
+            Self::Empty => Self::Empty,
+            Self::Surround(_) => Self::Surround(brush),
+            Self::Border(_) => Self::Border(brush),
+            Self::Vertical(_) => Self::Vertical(brush),
+            Self::VerBorder(_) => Self::VerBorder(brush),
+            Self::Horizontal(_) => Self::Horizontal(brush),
+            Self::HorBorder(_) => Self::HorBorder(brush),
+        }
+    }
+
     /// The [`Brush`] in use, [`None`] for [`Frame::Empty`]
     fn brush(&self) -> Option<Brush> {
         match self {
@@ -246,3 +316,77 @@ impl Frame {
         }
     }
 }
+
+/// A title, to be printed on the top edge of an [`Area`]
+///
+/// The title is printed on top of the [`Frame`] above the [`Area`],
+/// so it will only show up if said [`Area`] has an edge above it.
+/// Since its text is an [`RoData`], it will be reprinted whenever it
+/// changes.
+///
+/// ```rust
+/// # use duat_core::data::{RoData, RwData};
+/// # use duat_term::Title;
+/// let name = RwData::new(String::from("main.rs"));
+/// let title = Title::new(RoData::from(&name))
+///     .left()
+///     .with_form("FileTitle");
+/// ```
+///
+/// [`Area`]: crate::Area
+#[derive(Clone)]
+pub struct Title {
+    pub(super) text: RoData<String>,
+    pub(super) align: Alignment,
+    pub(super) form: FormId,
+}
+
+impl Title {
+    /// Returns a new centered [`Title`]
+    ///
+    /// By default, it is printed with the `"FrameTitle"` form, which
+    /// references `"Frame"`.
+    pub fn new(text: RoData<String>) -> Self {
+        Self {
+            text,
+            align: Alignment::Center,
+            form: *TITLE_FORM,
+        }
+    }
+
+    /// Aligns the [`Title`] to the left
+    pub fn left(self) -> Self {
+        Self { align: Alignment::Left, ..self }
+    }
+
+    /// Aligns the [`Title`] to the center
+    pub fn center(self) -> Self {
+        Self { align: Alignment::Center, ..self }
+    }
+
+    /// Aligns the [`Title`] to the right
+    pub fn right(self) -> Self {
+        Self { align: Alignment::Right, ..self }
+    }
+
+    /// Prints the [`Title`] with the given form
+    ///
+    /// If the form doesn't exist, it will reference `"FrameTitle"`.
+    pub fn with_form(self, name: impl ToString) -> Self {
+        Self {
+            form: form::set_weak(name, "FrameTitle"),
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Debug for Title {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Title")
+            .field("text", &*self.text.read())
+            .field("align", &self.align)
+            .finish()
+    }
+}
+
+static TITLE_FORM: LazyLock<FormId> = LazyLock::new(|| form::set_weak("FrameTitle", "Frame"));
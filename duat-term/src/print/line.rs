@@ -39,6 +39,8 @@ pub fn horizontal(right: Brush, left: Brush) -> char {
 
         (_, Custom(char)) | (Custom(char), _) => char,
 
+        (_, Set(set)) | (Set(set), _) => set.horizontal,
+
         (Regular, Regular | Dashed | Double | Rounded)
         | (Dashed, Regular | Double)
         | (Double, Regular | Dashed)
@@ -64,6 +66,8 @@ pub fn vertical(up: Brush, down: Brush) -> char {
 
         (Custom(char), _) | (_, Custom(char)) => char,
 
+        (Set(set), _) | (_, Set(set)) => set.vertical,
+
         (Regular, Regular | Dashed | Double | Rounded)
         | (Dashed, Regular | Double)
         | (Double, Regular | Dashed)
@@ -89,6 +93,7 @@ fn right_end(line: Brush) -> char {
         Thick | ThickDashed | Double => '╺',
         Ascii => '-',
         Custom(char) => char,
+        Set(set) => set.horizontal,
     }
 }
 
@@ -98,6 +103,7 @@ fn up_end(line: Brush) -> char {
         Thick | ThickDashed | Double => '╹',
         Ascii => '|',
         Custom(char) => char,
+        Set(set) => set.vertical,
     }
 }
 
@@ -107,6 +113,7 @@ fn left_end(line: Brush) -> char {
         Thick | ThickDashed | Double => '╸',
         Ascii => '-',
         Custom(char) => char,
+        Set(set) => set.horizontal,
     }
 }
 
@@ -116,6 +123,7 @@ fn down_end(line: Brush) -> char {
         Thick | ThickDashed | Double => '╻',
         Ascii => '|',
         Custom(char) => char,
+        Set(set) => set.vertical,
     }
 }
 
@@ -125,6 +133,8 @@ fn left_down(left: Brush, down: Brush) -> char {
 
         (Custom(char), _) | (_, Custom(char)) => char,
 
+        (Set(set), _) | (_, Set(set)) => set.left_down,
+
         (Rounded, _) | (_, Rounded) => '╮',
 
         (Regular | Dashed, Dashed | Regular) => '┐',
@@ -149,6 +159,8 @@ fn right_down(right: Brush, down: Brush) -> char {
 
         (Custom(char), _) | (_, Custom(char)) => char,
 
+        (Set(set), _) | (_, Set(set)) => set.right_down,
+
         (Rounded, _) | (_, Rounded) => '╭',
 
         (Regular | Dashed, Dashed | Regular) => '┌',
@@ -173,6 +185,8 @@ fn right_up(right: Brush, up: Brush) -> char {
 
         (Custom(char), _) | (_, Custom(char)) => char,
 
+        (Set(set), _) | (_, Set(set)) => set.right_up,
+
         (Rounded, _) | (_, Rounded) => '╰',
 
         (Regular | Dashed, Dashed | Regular) => '└',
@@ -197,6 +211,8 @@ fn left_up(left: Brush, up: Brush) -> char {
 
         (Custom(char), _) | (_, Custom(char)) => char,
 
+        (Set(set), _) | (_, Set(set)) => set.left_up,
+
         (Rounded, _) | (_, Rounded) => '╯',
 
         (Regular | Dashed, Dashed | Regular) => '┘',
@@ -233,6 +249,8 @@ fn right_vertical(right: Brush, up: Brush, down: Brush) -> char {
 
         (Custom(char), ..) | (_, Custom(char), _) | (_, _, Custom(char)) => char,
 
+        (Set(set), ..) | (_, Set(set), _) | (_, _, Set(set)) => set.right_vertical,
+
         (regular!(), regular!(), regular!()) => '├',
 
         (thick!(), regular!() | Double, regular!() | Double) => '┝',
@@ -263,6 +281,8 @@ fn up_horizontal(up: Brush, left: Brush, right: Brush) -> char {
 
         (Custom(char), ..) | (_, Custom(char), _) | (_, _, Custom(char)) => char,
 
+        (Set(set), ..) | (_, Set(set), _) | (_, _, Set(set)) => set.up_horizontal,
+
         (regular!(), regular!(), regular!()) => '┴',
 
         (thick!(), regular!() | Double, regular!() | Double) => '┸',
@@ -293,6 +313,8 @@ fn left_vertical(left: Brush, up: Brush, down: Brush) -> char {
 
         (Custom(char), ..) | (_, Custom(char), _) | (_, _, Custom(char)) => char,
 
+        (Set(set), ..) | (_, Set(set), _) | (_, _, Set(set)) => set.left_vertical,
+
         (regular!(), regular!(), regular!()) => '┤',
 
         (thick!(), regular!() | Double, regular!() | Double) => '┥',
@@ -305,7 +327,7 @@ fn left_vertical(left: Brush, up: Brush, down: Brush) -> char {
 
         (thick!(), regular!() | Double, thick!()) => '┪',
 
-        (thick!(), thick!(), regular!() | Double) => '┪',
+        (thick!(), thick!(), regular!() | Double) => '┩',
 
         (thick!(), thick!(), thick!()) => '┫',
 
@@ -323,6 +345,8 @@ fn down_horizontal(down: Brush, left: Brush, right: Brush) -> char {
 
         (Custom(char), ..) | (_, Custom(char), _) | (_, _, Custom(char)) => char,
 
+        (Set(set), ..) | (_, Set(set), _) | (_, _, Set(set)) => set.down_horizontal,
+
         (regular!(), regular!(), regular!()) => '┬',
 
         (thick!(), regular!() | Double, regular!() | Double) => '┰',
@@ -355,6 +379,8 @@ fn cross(right: Brush, up: Brush, left: Brush, down: Brush) -> char {
             char
         }
 
+        (Set(set), ..) | (_, Set(set), ..) | (.., Set(set), _) | (.., Set(set)) => set.cross,
+
         (regular!(), regular!(), regular!(), regular!()) => '┼',
 
         (thick!(), regular!() | Double, regular!() | Double, regular!() | Double) => '┾',
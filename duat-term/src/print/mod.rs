@@ -1,6 +1,7 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fmt::Alignment,
-    io::{Write, stdout},
+    io::Write,
     sync::{
         Arc, LazyLock, Mutex,
         atomic::{AtomicBool, AtomicU32, Ordering},
@@ -11,23 +12,26 @@ use cassowary::{AddConstraintError, Solver, Variable, strength::STRONG};
 use crossterm::{
     cursor::{self, MoveTo, MoveToColumn, MoveToNextLine},
     execute,
-    style::{Print, ResetColor, SetStyle},
+    style::{Color, ContentStyle, Print, ResetColor},
     terminal,
 };
 use duat_core::{
+    cfg::Padding,
     form::{self, DEFAULT_ID, FormId},
     ui::Axis,
 };
 
-use self::frame::Edge;
-use crate::{Coords, Equality, area::Coord};
+use self::frame::{Edge, EdgeCoords};
+use crate::{AreaId, Coords, Equality, area::Coord, glyph_width, glyphs};
 
 mod frame;
 mod line;
+mod style;
 mod var;
 
 pub use self::{
-    frame::{Brush, Frame},
+    frame::{Brush, BrushSet, Frame, Title},
+    style::{SetStyle, set_styled_underlines, styled_underlines_are_supported},
     var::{VarPoint, VarValue},
 };
 
@@ -37,8 +41,11 @@ pub struct Printer {
     solver: Solver,
     vars: Vec<(Variable, SavedVar)>,
     edges: Vec<Edge>,
+    titles: Vec<(AreaId, VarPoint, Title)>,
 
     recvs: Vec<Receiver>,
+    floats: Vec<Receiver>,
+    reprint_all: AtomicBool,
     is_offline: bool,
     is_disabled: bool,
     max: VarPoint,
@@ -47,8 +54,7 @@ pub struct Printer {
 impl Printer {
     pub fn new() -> Self {
         let (vars, solver, max) = {
-            let (width, height) = crossterm::terminal::size().unwrap();
-            let (width, height) = (width as f64, height as f64);
+            let (width, height) = term_size();
 
             let mut vars = Vec::new();
             let mut solver = Solver::new();
@@ -69,8 +75,11 @@ impl Printer {
             solver,
             vars,
             edges: Vec::new(),
+            titles: Vec::new(),
 
             recvs: Vec::new(),
+            floats: Vec::new(),
+            reprint_all: AtomicBool::new(false),
             is_offline: false,
             is_disabled: false,
             max,
@@ -116,8 +125,7 @@ impl Printer {
     /// returning true if any of them have.
     pub fn update(&mut self, change_max: bool) {
         if change_max {
-            let (width, height) = crossterm::terminal::size().unwrap();
-            let (width, height) = (width as f64, height as f64);
+            let (width, height) = term_size();
 
             let max = &self.max;
             self.solver.suggest_value(max.x_var(), width).unwrap();
@@ -169,12 +177,44 @@ impl Printer {
             has_changed.store(old != new, Ordering::Release);
         }
 
-        print_edges(&self.edges);
+        print_edges(&self.edges, &self.titles);
+        // The edges may have been printed over floating Areas, and
+        // these may have moved.
+        self.reprint_all.store(true, Ordering::Relaxed);
+    }
+
+    /// Changes the [`Brush`] of all [`Edge`]s around an [`Area`]
+    ///
+    /// [`Area`]: crate::Area
+    pub fn set_brush_around(&mut self, tl: &VarPoint, br: &VarPoint, brush: Brush) {
+        for edge in self.edges.iter_mut().filter(|edge| edge.borders(tl, br)) {
+            edge.set_brush(brush);
+        }
+        print_edges(&self.edges, &self.titles);
+    }
+
+    /// Sets the [`Title`] of an [`Area`], printed on the edge above
+    /// it
+    ///
+    /// [`Area`]: crate::Area
+    pub fn set_title(&mut self, id: AreaId, tl: &VarPoint, title: Title) {
+        self.titles.retain(|(other, ..)| *other != id);
+        self.titles.push((id, tl.clone(), title));
+        print_edges(&self.edges, &self.titles);
+    }
+
+    /// Removes the [`Title`] of an [`Area`]
+    ///
+    /// [`Area`]: crate::Area
+    pub fn remove_title(&mut self, id: AreaId) {
+        self.titles.retain(|(other, ..)| *other != id);
+        print_edges(&self.edges, &self.titles);
     }
 
     pub fn sender(&mut self, tl: &VarPoint, br: &VarPoint) -> Sender {
         let recv = Receiver {
             lines: Arc::new(Mutex::new(None)),
+            last: Mutex::new(None),
             tl: tl.clone(),
             br: br.clone(),
         };
@@ -193,9 +233,61 @@ impl Printer {
         sender
     }
 
-    pub fn remove_sender(&mut self, sender: Sender) {
+    /// A [`Sender`] for a floating [`Area`]
+    ///
+    /// These are printed above all others, in the order that they
+    /// were created, unless moved with [`Printer::move_float`].
+    ///
+    /// [`Area`]: crate::Area
+    pub fn floating_sender(&mut self, tl: &VarPoint, br: &VarPoint) -> Sender {
+        let recv = Receiver {
+            lines: Arc::new(Mutex::new(None)),
+            last: Mutex::new(None),
+            tl: tl.clone(),
+            br: br.clone(),
+        };
+
+        let sender = Sender {
+            lines: recv.lines.clone(),
+            tl: tl.clone(),
+            br: br.clone(),
+        };
+
+        self.floats.push(recv);
+
+        sender
+    }
+
+    /// Moves the [`Receiver`] of a floating [`Sender`] to the top or
+    /// bottom of the printing order
+    pub fn move_float(&mut self, sender: &Sender, to_top: bool) {
+        let Some(i) = self
+            .floats
+            .iter()
+            .position(|recv| Arc::ptr_eq(&recv.lines, &sender.lines))
+        else {
+            return;
+        };
+        let recv = self.floats.remove(i);
+        match to_top {
+            true => self.floats.push(recv),
+            false => self.floats.insert(0, recv),
+        }
+        // What was above may now be below, and vice versa.
+        self.reprint_all.store(true, Ordering::Relaxed);
+    }
+
+    pub fn remove_sender(&mut self, sender: &Sender) {
         self.recvs
             .retain(|recv| !Arc::ptr_eq(&recv.lines, &sender.lines));
+
+        let len = self.floats.len();
+        self.floats
+            .retain(|recv| !Arc::ptr_eq(&recv.lines, &sender.lines));
+        // What was below the floating Area has to be shown again.
+        if self.floats.len() < len {
+            self.reprint_all.store(true, Ordering::Relaxed);
+        }
     }
 
     pub fn shutdown(&mut self) {
@@ -208,20 +300,45 @@ impl Printer {
 
     pub fn print(&self) {
         static CURSOR_IS_REAL: AtomicBool = AtomicBool::new(false);
-        let list: Vec<_> = self.recvs.iter().flat_map(Receiver::take).collect();
+        let reprint_all = self.reprint_all.swap(false, Ordering::Relaxed);
+        let list: Vec<_> = self
+            .recvs
+            .iter()
+            .flat_map(|recv| recv.take(reprint_all))
+            .collect();
+        let titles_changed = self
+            .titles
+            .iter()
+            .filter(|(.., title)| title.text.has_changed())
+            .count()
+            > 0;
+
+        // Floating Areas are printed over everything else, so they have
+        // to be printed again whenever anything below them is.
+        let floats_changed = self.floats.iter().any(Receiver::has_new_lines);
+        let reprint_floats = reprint_all || floats_changed || titles_changed || !list.is_empty();
+        let floats: Vec<_> = self
+            .floats
+            .iter()
+            .flat_map(|recv| recv.take(reprint_floats))
+            .collect();
 
-        if list.is_empty() {
+        if list.is_empty() && floats.is_empty() && !titles_changed {
             return;
         }
 
-        let mut stdout = stdout().lock();
+        if titles_changed {
+            print_edges(&self.edges, &self.titles);
+        }
+
+        let mut stdout = crate::output();
         execute!(stdout, terminal::BeginSynchronizedUpdate).unwrap();
         queue!(stdout, cursor::Hide, MoveTo(0, 0));
 
         for y in 0..self.max.coord().y {
             let mut x = 0;
 
-            let iter = list.iter().flat_map(|lines| lines.on(y));
+            let iter = list.iter().chain(&floats).flat_map(|lines| lines.on(y));
 
             for (bytes, start, end) in iter {
                 if x != start {
@@ -238,6 +355,7 @@ impl Printer {
 
         let cursor_was_real = if let Some(was_real) = list
             .iter()
+            .chain(&floats)
             .filter_map(|lines| lines.real_cursor)
             .reduce(|prev, was_real| prev || was_real)
         {
@@ -322,13 +440,31 @@ unsafe impl Sync for Printer {}
 #[derive(Debug)]
 struct Receiver {
     lines: Arc<Mutex<Option<Lines>>>,
+    last: Mutex<Option<Arc<Lines>>>,
     tl: VarPoint,
     br: VarPoint,
 }
 
 impl Receiver {
-    fn take(&self) -> Option<Lines> {
-        self.lines.lock().unwrap().take()
+    /// Takes the new [`Lines`], if there are any
+    ///
+    /// If there aren't and `or_last`, returns the last [`Lines`]
+    /// that were taken instead.
+    fn take(&self, or_last: bool) -> Option<Arc<Lines>> {
+        let mut last = self.last.lock().unwrap();
+        match self.lines.lock().unwrap().take() {
+            Some(lines) => {
+                let lines = Arc::new(lines);
+                *last = Some(lines.clone());
+                Some(lines)
+            }
+            None if or_last => last.clone(),
+            None => None,
+        }
+    }
+
+    fn has_new_lines(&self) -> bool {
+        self.lines.lock().unwrap().is_some()
     }
 
     fn coords(&self) -> Coords {
@@ -344,7 +480,7 @@ pub struct Sender {
 }
 
 impl Sender {
-    pub fn lines(&self, shift: u32, cap: u32) -> Lines {
+    pub fn lines(&self, shift: u32, cap: u32, pad: Padding) -> Lines {
         let area = (self.coords().width() * self.coords().height()) as usize;
         let mut cutoffs = Vec::with_capacity(self.coords().height() as usize);
         cutoffs.push(0);
@@ -359,8 +495,10 @@ impl Sender {
             len: 0,
             positions: Vec::new(),
             align: Alignment::Left,
+            line_bg: None,
             shift,
             cap,
+            pad,
         }
     }
 
@@ -384,12 +522,15 @@ pub struct Lines {
     len: u32,
     shift: u32,
     cap: u32,
+    pad: Padding,
     positions: Vec<(usize, u32)>,
     align: Alignment,
+    line_bg: Option<Color>,
 }
 
 impl Lines {
     pub fn push_char(&mut self, char: char, len: u32) {
+        let char = glyphs::displayed(char);
         self.len += len;
         let mut bytes = [0; 4];
         char.encode_utf8(&mut bytes);
@@ -401,6 +542,11 @@ impl Lines {
         self.align = alignment;
     }
 
+    /// Sets the background of the empty space at the end of lines
+    pub fn set_line_bg(&mut self, bg: Option<Color>) {
+        self.line_bg = bg;
+    }
+
     pub fn show_real_cursor(&mut self) {
         self.real_cursor = Some(true);
     }
@@ -431,6 +577,24 @@ impl Lines {
     pub(crate) fn is_empty(&self) -> bool {
         self.line.is_empty()
     }
+
+    /// How many cells are left before the right edge of the line
+    pub(crate) fn remaining(&self) -> u32 {
+        self.cap
+            .min(self.shift + self.width())
+            .saturating_sub(self.len)
+    }
+
+    /// The width available for printing, without the padding
+    fn width(&self) -> u32 {
+        self.pad.inner_width(self.coords.width())
+    }
+
+    /// Pushes the padding on the right of the line
+    fn push_right_pad(&mut self, style: ContentStyle) {
+        queue!(self.bytes, ResetColor, SetStyle(style));
+        push_blank(&mut self.bytes, self.pad.right());
+    }
 }
 
 impl Write for Lines {
@@ -441,24 +605,46 @@ impl Write for Lines {
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        const BLANK: [u8; 1000] = [b' '; 1000];
         let default_form = duat_core::form::from_id(DEFAULT_ID);
+        let end_style = {
+            let mut style = default_form.style;
+            if let Some(bg) = self.line_bg {
+                style.background_color = Some(bg);
+            }
+            style
+        };
 
         let align_start = match self.align {
             Alignment::Left => 0,
-            Alignment::Right => self.cap - self.len,
-            Alignment::Center => (self.cap - self.len) / 2,
+            Alignment::Right => self.cap.saturating_sub(self.len),
+            Alignment::Center => self.cap.saturating_sub(self.len) / 2,
         };
 
+        // Nothing can be shown on an area without width, but the line
+        // still needs to be accounted for.
+        if self.width() == 0 {
+            queue!(self.bytes, ResetColor, SetStyle(default_form.style));
+            push_blank(&mut self.bytes, self.coords.width());
+            self.cutoffs.push(self.bytes.len());
+            self.line.clear();
+            self.positions.clear();
+            self.len = 0;
+            return Ok(());
+        }
+
+        queue!(self.bytes, ResetColor, SetStyle(default_form.style));
+        push_blank(&mut self.bytes, self.pad.left());
+
         let (start_i, start_d) = {
             let mut dist = align_start;
             let Some(&(start, len)) = self.positions.iter().find(|(_, len)| {
                 dist += len;
                 dist > self.shift
             }) else {
-                queue!(self.bytes, ResetColor, SetStyle(default_form.style));
-                self.bytes
-                    .extend_from_slice(&BLANK[..self.coords.width() as usize]);
+                queue!(self.bytes, ResetColor, SetStyle(end_style));
+                let width = self.width();
+                push_blank(&mut self.bytes, width);
+                self.push_right_pad(default_form.style);
                 self.cutoffs.push(self.bytes.len());
 
                 self.line.clear();
@@ -482,11 +668,12 @@ impl Write for Lines {
 
             let Some(&(end, len)) = self.positions.iter().rev().find(|(_, len)| {
                 dist -= len;
-                dist < self.shift + self.coords.width()
+                dist < self.shift + self.width()
             }) else {
-                queue!(self.bytes, ResetColor, SetStyle(default_form.style));
-                self.bytes
-                    .extend_from_slice(&BLANK[..self.coords.width() as usize]);
+                queue!(self.bytes, ResetColor, SetStyle(end_style));
+                let width = self.width();
+                push_blank(&mut self.bytes, width);
+                self.push_right_pad(default_form.style);
                 self.cutoffs.push(self.bytes.len());
 
                 self.line.clear();
@@ -496,8 +683,8 @@ impl Write for Lines {
             };
 
             // If the character is cut by the end, don't print it.
-            if dist + len > self.shift + self.coords.width() {
-                (end, dist - self.shift)
+            if dist + len > self.shift + self.width() {
+                (end, dist.saturating_sub(self.shift))
             } else {
                 let str = unsafe { std::str::from_utf8_unchecked(&self.line[end..]) };
                 let char = str.chars().next().unwrap();
@@ -506,7 +693,8 @@ impl Write for Lines {
         };
 
         queue!(self.bytes, ResetColor, SetStyle(default_form.style));
-        self.bytes.extend_from_slice(&BLANK[..start_d as usize]);
+        let start_d = start_d.min(self.width());
+        push_blank(&mut self.bytes, start_d);
 
         let mut adding_ansi = false;
         for &b in &self.line[..start_i] {
@@ -521,10 +709,14 @@ impl Write for Lines {
             }
         }
 
-        self.bytes.extend_from_slice(&self.line[start_i..end_i]);
-        queue!(self.bytes, ResetColor, SetStyle(default_form.style));
+        // If a wide character is cut on both ends, nothing is printed.
+        let end_d = end_d.max(start_d);
         self.bytes
-            .extend_from_slice(&BLANK[..(self.coords.width() - end_d) as usize]);
+            .extend_from_slice(&self.line[start_i..end_i.max(start_i)]);
+        queue!(self.bytes, ResetColor, SetStyle(end_style));
+        let width = self.width().saturating_sub(end_d);
+        push_blank(&mut self.bytes, width);
+        self.push_right_pad(default_form.style);
         self.cutoffs.push(self.bytes.len());
 
         self.line.clear();
@@ -534,6 +726,17 @@ impl Write for Lines {
     }
 }
 
+/// Pushes `len` spaces to the bytes of a [`Lines`]
+fn push_blank(bytes: &mut Vec<u8>, len: u32) {
+    const BLANK: [u8; 1000] = [b' '; 1000];
+    let mut len = len as usize;
+    while len > 0 {
+        let chunk = len.min(BLANK.len());
+        bytes.extend_from_slice(&BLANK[..chunk]);
+        len -= chunk;
+    }
+}
+
 pub enum SavedVar {
     Val {
         value: Arc<AtomicU32>,
@@ -578,17 +781,36 @@ impl SavedVar {
     }
 }
 
-fn print_edges(edges: &[Edge]) {
+/// The size of the terminal, as `(width, height)`
+///
+/// If there is no terminal attached, like when benchmarking in CI,
+/// a size of 80 by 24 cells is assumed.
+fn term_size() -> (f64, f64) {
+    #[cfg(test)]
+    if let Some((width, height)) = crate::virt::size() {
+        return (width as f64, height as f64);
+    }
+
+    let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+    (width as f64, height as f64)
+}
+
+fn print_edges(edges: &[Edge], titles: &[(AreaId, VarPoint, Title)]) {
+    // Frames are purely decorative, and only get in the way of screen
+    // readers.
+    if crate::screen_reader_is_on() {
+        return;
+    }
+
     static FRAME_FORM: LazyLock<FormId> = LazyLock::new(|| form::set_weak("Frame", "Default"));
     let frame_form = form::from_id(*FRAME_FORM);
 
-    let mut stdout = std::io::stdout().lock();
+    let mut stdout = crate::output();
 
     let edges: Vec<_> = edges.iter().filter_map(|edge| edge.edge_coords()).collect();
+    let junctions = junctions(&edges);
 
-    let mut crossings = Vec::<(Coord, [Option<Brush>; 4])>::new();
-
-    for (i, &coords) in edges.iter().enumerate() {
+    for coords in edges.iter() {
         if let Axis::Horizontal = coords.axis {
             let char = match coords.line {
                 Some(line) => line::horizontal(line, line),
@@ -620,23 +842,9 @@ fn print_edges(edges: &[Edge]) {
                 )
             }
         }
-
-        for &other_coords in edges[(i + 1)..].iter() {
-            if let Some((coord, sides)) = coords.crossing(other_coords) {
-                let prev_crossing = crossings.iter_mut().find(|(c, ..)| *c == coord);
-                if let Some((_, [right, up, left, down])) = prev_crossing {
-                    *right = right.or(sides[0]);
-                    *up = up.or(sides[1]);
-                    *left = left.or(sides[2]);
-                    *down = down.or(sides[3]);
-                } else {
-                    crossings.push((coord, sides));
-                }
-            }
-        }
     }
 
-    for (coord, [right, up, left, down]) in crossings {
+    for (coord, [right, up, left, down]) in junctions {
         queue!(
             stdout,
             cursor::MoveTo(coord.x as u16, coord.y as u16),
@@ -644,6 +852,142 @@ fn print_edges(edges: &[Edge]) {
             Print(line::crossing(right, up, left, down, true))
         )
     }
+
+    for (_, tl, title) in titles {
+        let tl = tl.coord();
+        let Some(y) = tl.y.checked_sub(1) else {
+            continue;
+        };
+        let Some(edge) = edges.iter().find(|coords| {
+            matches!(coords.axis, Axis::Horizontal)
+                && coords.tl.y == y
+                && coords.tl.x <= tl.x
+                && tl.x <= coords.br.x
+        }) else {
+            continue;
+        };
+
+        // Leaves one cell of the edge on each side of the title.
+        let (start, end) = (tl.x.max(edge.tl.x) + 1, edge.br.x);
+        let Some(space) = end.checked_sub(start).filter(|space| *space >= 3) else {
+            continue;
+        };
+
+        // The title is surrounded by spaces, which take 2 cells.
+        let mut width = 2;
+        let text: String = title
+            .text
+            .read()
+            .chars()
+            .filter(|char| !char.is_control())
+            .take_while(|char| {
+                let len = glyph_width(*char);
+                width += len;
+                if width > space {
+                    width -= len;
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect();
+
+        let x = match title.align {
+            Alignment::Left => start,
+            Alignment::Center => start + (space - width) / 2,
+            Alignment::Right => end - width,
+        };
+
+        queue!(
+            stdout,
+            cursor::MoveTo(x as u16, y as u16),
+            ResetColor,
+            SetStyle(form::from_id(title.form).style),
+            Print(format!(" {text} "))
+        )
+    }
+}
+
+/// Resolves the junctions between [`EdgeCoords`]
+///
+/// Edges stop short of the cells where they meet other edges, so
+/// every cell on or right past the end of an edge is checked for
+/// edges on each of its four sides. Those with more than one arm,
+/// other than the straight line of the edge going through them,
+/// become junctions, whose characters depend on all of their
+/// neighbours, instead of just pairs of edges.
+///
+/// This is done on every call to [`print_edges`], so it is always
+/// up to date with the layout.
+fn junctions(edges: &[EdgeCoords]) -> Vec<(Coord, [Option<Brush>; 4])> {
+    // For each cell, the Brushes of the horizontal and vertical edges
+    // going through it.
+    let mut cells = BTreeMap::<Coord, [Option<Brush>; 2]>::new();
+    let mut candidates = BTreeSet::new();
+
+    for coords in edges {
+        let (i, cells_iter): (usize, Box<dyn Iterator<Item = Coord>>) = match coords.axis {
+            Axis::Horizontal => (
+                0,
+                Box::new((coords.tl.x..=coords.br.x).map(|x| Coord::new(x, coords.tl.y))),
+            ),
+            Axis::Vertical => (
+                1,
+                Box::new((coords.tl.y..=coords.br.y).map(|y| Coord::new(coords.tl.x, y))),
+            ),
+        };
+
+        for coord in cells_iter {
+            let brushes = cells.entry(coord).or_default();
+            brushes[i] = brushes[i].or(coords.line);
+            candidates.insert(coord);
+        }
+
+        let (before, after) = match coords.axis {
+            Axis::Horizontal => (
+                coords
+                    .tl
+                    .x
+                    .checked_sub(1)
+                    .map(|x| Coord::new(x, coords.tl.y)),
+                Coord::new(coords.br.x + 1, coords.tl.y),
+            ),
+            Axis::Vertical => (
+                coords
+                    .tl
+                    .y
+                    .checked_sub(1)
+                    .map(|y| Coord::new(coords.tl.x, y)),
+                Coord::new(coords.tl.x, coords.br.y + 1),
+            ),
+        };
+        candidates.extend(before);
+        candidates.insert(after);
+    }
+
+    let brush_at = |x: Option<u32>, y: Option<u32>, i: usize| {
+        let coord = Coord::new(x?, y?);
+        cells.get(&coord).and_then(|brushes| brushes[i])
+    };
+
+    candidates
+        .into_iter()
+        .filter_map(|coord| {
+            let Coord { x, y } = coord;
+            let [hor, ver] = cells.get(&coord).copied().unwrap_or_default();
+
+            let right = brush_at(Some(x + 1), Some(y), 0);
+            let up = brush_at(Some(x), y.checked_sub(1), 1);
+            let left = brush_at(x.checked_sub(1), Some(y), 0);
+            let down = brush_at(Some(x), Some(y + 1), 1);
+
+            let arms = [right, up, left, down].iter().flatten().count();
+            let is_straight = (hor.is_some() && up.is_none() && down.is_none())
+                || (ver.is_some() && right.is_none() && left.is_none());
+
+            (arms >= 2 && !is_straight).then_some((coord, [right, up, left, down]))
+        })
+        .collect()
 }
 
 macro queue($writer:expr $(, $command:expr)* $(,)?) {
@@ -653,3 +997,95 @@ macro queue($writer:expr $(, $command:expr)* $(,)?) {
 fn key(entry: &(Variable, SavedVar)) -> Variable {
     entry.0
 }
+
+#[cfg(test)]
+mod tests {
+    use duat_core::ui::Axis;
+
+    use super::{Brush, EdgeCoords, junctions};
+    use crate::area::Coord;
+
+    const R: Option<Brush> = Some(Brush::Regular);
+    const T: Option<Brush> = Some(Brush::Thick);
+
+    fn edge(axis: Axis, (x0, y0): (u32, u32), (x1, y1): (u32, u32), line: Brush) -> EdgeCoords {
+        EdgeCoords {
+            tl: Coord::new(x0, y0),
+            br: Coord::new(x1, y1),
+            axis,
+            line: Some(line),
+        }
+    }
+
+    fn hor(y: u32, x0: u32, x1: u32) -> EdgeCoords {
+        edge(Axis::Horizontal, (x0, y), (x1, y), Brush::Regular)
+    }
+
+    fn ver(x: u32, y0: u32, y1: u32) -> EdgeCoords {
+        edge(Axis::Vertical, (x, y0), (x, y1), Brush::Regular)
+    }
+
+    #[test]
+    fn t_junctions() {
+        let edges = [hor(5, 0, 9), ver(4, 6, 9)];
+        assert_eq!(junctions(&edges), [(Coord::new(4, 5), [R, None, R, R])]);
+
+        let edges = [hor(5, 0, 9), ver(4, 0, 4)];
+        assert_eq!(junctions(&edges), [(Coord::new(4, 5), [R, R, R, None])]);
+    }
+
+    #[test]
+    fn l_junctions() {
+        let edges = [hor(0, 1, 9), ver(0, 1, 9)];
+        assert_eq!(junctions(&edges), [(Coord::new(0, 0), [R, None, None, R])]);
+
+        let edges = [hor(9, 0, 8), ver(9, 0, 8)];
+        assert_eq!(junctions(&edges), [(Coord::new(9, 9), [None, R, R, None])]);
+    }
+
+    #[test]
+    fn cross_junctions() {
+        let edges = [hor(5, 0, 9), ver(4, 0, 4), ver(4, 6, 9)];
+        assert_eq!(junctions(&edges), [(Coord::new(4, 5), [R, R, R, R])]);
+    }
+
+    #[test]
+    fn three_frames_meeting() {
+        // One area on the left, and two on the right, one above the other.
+        let edges = [ver(4, 0, 9), hor(5, 5, 9)];
+        assert_eq!(junctions(&edges), [(Coord::new(4, 5), [R, R, None, R])]);
+
+        // Two areas, side by side, surrounded by a frame.
+        let edges = [
+            hor(0, 1, 9),
+            hor(10, 1, 9),
+            ver(0, 1, 9),
+            ver(10, 1, 9),
+            ver(5, 1, 9),
+        ];
+        assert_eq!(junctions(&edges), [
+            (Coord::new(0, 0), [R, None, None, R]),
+            (Coord::new(5, 0), [R, None, R, R]),
+            (Coord::new(10, 0), [None, None, R, R]),
+            (Coord::new(0, 10), [R, R, None, None]),
+            (Coord::new(5, 10), [R, R, R, None]),
+            (Coord::new(10, 10), [None, R, R, None]),
+        ]);
+    }
+
+    #[test]
+    fn mixed_brushes() {
+        let edges = [
+            edge(Axis::Horizontal, (0, 5), (9, 5), Brush::Thick),
+            ver(4, 6, 9),
+        ];
+        assert_eq!(junctions(&edges), [(Coord::new(4, 5), [T, None, T, R])]);
+
+        let edges = [
+            hor(5, 0, 9),
+            edge(Axis::Vertical, (4, 0), (4, 4), Brush::Thick),
+            ver(4, 6, 9),
+        ];
+        assert_eq!(junctions(&edges), [(Coord::new(4, 5), [R, T, R, R])]);
+    }
+}
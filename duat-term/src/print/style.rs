@@ -0,0 +1,142 @@
+//! Printing of [`ContentStyle`]s, adapted to the terminal
+//!
+//! Not every terminal supports the underline styles (double, curly,
+//! dotted and dashed), nor the underline color, and the ones that
+//! don't may print garbage or ignore the whole sequence. So, when
+//! they aren't supported, these styles fall back to a plain
+//! underline in the color of the text.
+use std::{
+    fmt,
+    sync::{
+        LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crossterm::{
+    Command,
+    style::{Attribute, ContentStyle},
+};
+
+static STYLED_UNDERLINES: LazyLock<AtomicBool> =
+    LazyLock::new(|| AtomicBool::new(terminal_supports_styled_underlines()));
+
+/// Sets whether the terminal supports underline styles and colors
+///
+/// By default, this is detected from the environment, which may not
+/// always be accurate, especially through multiplexers like `tmux`.
+/// When `false`, double, curly, dotted and dashed underlines are
+/// printed as regular underlines, and the underline color is the
+/// color of the text.
+///
+/// This should be called in the config, since [`Form`]s that were
+/// already printed won't be printed again.
+///
+/// [`Form`]: duat_core::form::Form
+pub fn set_styled_underlines(is_supported: bool) {
+    STYLED_UNDERLINES.store(is_supported, Ordering::Relaxed);
+}
+
+/// Whether underline styles and colors are printed as they are
+pub fn styled_underlines_are_supported() -> bool {
+    STYLED_UNDERLINES.load(Ordering::Relaxed)
+}
+
+/// A replacement for crossterm's [`SetStyle`], which adapts the
+/// [`ContentStyle`] to the capabilities of the terminal
+///
+/// [`SetStyle`]: crossterm::style::SetStyle
+#[derive(Clone, Copy, Debug)]
+pub struct SetStyle(pub ContentStyle);
+
+impl Command for SetStyle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        crossterm::style::SetStyle(adapt(self.0)).write_ansi(f)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        crossterm::style::SetStyle(adapt(self.0)).execute_winapi()
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        crossterm::style::SetStyle(self.0).is_ansi_code_supported()
+    }
+}
+
+/// Falls back to a plain underline if styled ones aren't supported
+fn adapt(mut style: ContentStyle) -> ContentStyle {
+    const STYLED: [Attribute; 4] = [
+        Attribute::DoubleUnderlined,
+        Attribute::Undercurled,
+        Attribute::Underdotted,
+        Attribute::Underdashed,
+    ];
+
+    if styled_underlines_are_supported() {
+        return style;
+    }
+
+    let attrs = &mut style.attributes;
+    if STYLED.iter().any(|attr| attrs.has(*attr)) {
+        for attr in STYLED {
+            attrs.unset(attr);
+        }
+        attrs.set(Attribute::Underlined);
+    }
+    style.underline_color = None;
+
+    style
+}
+
+/// Guesses if the terminal supports underline styles and colors
+///
+/// There is no reliable way to query this, so the environment
+/// variables set by terminals known to support them are checked.
+fn terminal_supports_styled_underlines() -> bool {
+    const TERMS: [&str; 6] = [
+        "kitty",
+        "wezterm",
+        "foot",
+        "alacritty",
+        "ghostty",
+        "contour",
+    ];
+    const PROGRAMS: [&str; 4] = ["WezTerm", "iTerm.app", "ghostty", "vscode"];
+
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+
+    let term = var("TERM");
+    TERMS.iter().any(|name| term.contains(name))
+        || PROGRAMS.contains(&var("TERM_PROGRAM").as_str())
+        // VTE terminals, like gnome-terminal, support them since 0.51.2.
+        || var("VTE_VERSION").parse().is_ok_and(|version: u32| version >= 5102)
+        || !var("KITTY_WINDOW_ID").is_empty()
+        || !var("WEZTERM_EXECUTABLE").is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::{Attribute, Color, ContentStyle};
+
+    use super::{adapt, set_styled_underlines};
+
+    #[test]
+    fn falls_back_to_plain_underline() {
+        let mut style = ContentStyle::new();
+        style.attributes.set(Attribute::Undercurled);
+        style.attributes.set(Attribute::Bold);
+        style.underline_color = Some(Color::Red);
+
+        set_styled_underlines(true);
+        assert_eq!(adapt(style), style);
+
+        set_styled_underlines(false);
+        let adapted = adapt(style);
+        assert!(adapted.attributes.has(Attribute::Underlined));
+        assert!(adapted.attributes.has(Attribute::Bold));
+        assert!(!adapted.attributes.has(Attribute::Undercurled));
+        assert_eq!(adapted.underline_color, None);
+    }
+}
@@ -3,7 +3,7 @@ use duat_core::{
     form::{self, Form},
     text::{Text, text},
     ui::{Area as UiArea, PushSpecs},
-    widgets::{Widget, WidgetCfg},
+    widgets::{Widget, WidgetCfg, cache_key},
 };
 
 use crate::{Area, Ui};
@@ -27,7 +27,9 @@ impl Widget<Ui> for VertRule {
     }
 
     fn update(&mut self, area: &Area) {
-        self.text = if let Some(reader) = self.reader.as_ref()
+        self.text = if crate::screen_reader_is_on() {
+            Text::new()
+        } else if let Some(reader) = self.reader.as_ref()
             && let SepChar::ThreeWay(..) | SepChar::TwoWay(..) = self.sep_char
         {
             reader.inspect(|file, _, cursors| {
@@ -58,6 +60,20 @@ impl Widget<Ui> for VertRule {
         }
     }
 
+    fn cache_key(&self, area: &Area) -> Option<u64> {
+        if crate::screen_reader_is_on() {
+            return Some(cache_key(()));
+        }
+
+        Some(match self.reader.as_ref() {
+            Some(reader) => reader.inspect(|file, _, cursors| {
+                let main_line = cursors.get_main().map(|main| main.line());
+                cache_key((file.printed_lines(), main_line, area.height()))
+            }),
+            None => cache_key(area.height()),
+        })
+    }
+
     fn text(&self) -> &Text {
         &self.text
     }
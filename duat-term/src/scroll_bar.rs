@@ -0,0 +1,159 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use duat_core::{
+    context::{self, FileReader},
+    form::{self, Form},
+    text::{Text, text},
+    ui::{Area as UiArea, PushSpecs},
+    widgets::{Widget, WidgetCfg, cache_key},
+};
+
+use crate::{Area, Ui};
+
+/// A vertical bar showing which part of a [`File`] is on screen
+///
+/// The thumb of the bar takes up a proportion of its height equal
+/// to the proportion of the lines of the [`File`] that are visible,
+/// and is moved down as the [`File`] is scrolled.
+///
+/// [`File`]: duat_core::widgets::File
+pub struct ScrollBar {
+    reader: Option<FileReader<Ui>>,
+    text: Text,
+    chars: (char, char),
+}
+
+impl Widget<Ui> for ScrollBar {
+    type Cfg = ScrollBarCfg;
+
+    fn cfg() -> Self::Cfg {
+        ScrollBarCfg::new()
+    }
+
+    fn update(&mut self, area: &Area) {
+        let height = area.height() as usize;
+        let (track, thumb) = self.chars;
+
+        let Some((start, len)) = self
+            .reader
+            .as_ref()
+            .and_then(|reader| reader.inspect(|file, area, _| thumb_of(file.text(), area, height)))
+        else {
+            self.text = text!([ScrollBar] { form_string(track, height) });
+            return;
+        };
+
+        self.text = text!(
+            [ScrollBar] { form_string(track, start) }
+            [ScrollBarThumb] { form_string(thumb, len) }
+            [ScrollBar] { form_string(track, height - start - len) }
+        );
+    }
+
+    fn cache_key(&self, area: &Area) -> Option<u64> {
+        Some(match self.reader.as_ref() {
+            Some(reader) => reader.inspect(|file, file_area, _| {
+                let first = file_area.print_info().first_char().line();
+                cache_key((first, file.len_lines(), file_area.height(), area.height()))
+            }),
+            None => cache_key(area.height()),
+        })
+    }
+
+    fn text(&self) -> &Text {
+        &self.text
+    }
+
+    fn text_mut(&mut self) -> &mut Text {
+        &mut self.text
+    }
+
+    fn once() {
+        form::set_weak("ScrollBar", Form::dark_grey());
+        form::set_weak("ScrollBarThumb", Form::grey());
+    }
+}
+
+/// The configurations for the [`ScrollBar`] widget.
+#[derive(Clone)]
+pub struct ScrollBarCfg {
+    chars: (char, char),
+    specs: PushSpecs,
+}
+
+impl ScrollBarCfg {
+    /// Returns a new instance of [`ScrollBarCfg`].
+    pub fn new() -> Self {
+        Self {
+            chars: ('│', '┃'),
+            specs: PushSpecs::right().with_hor_len(1.0),
+        }
+    }
+
+    pub fn on_the_left(self) -> Self {
+        Self {
+            specs: PushSpecs::left().with_hor_len(1.0),
+            ..self
+        }
+    }
+
+    /// The [`char`]s for the track and the thumb, respectively
+    pub fn with_chars(self, track: char, thumb: char) -> Self {
+        Self { chars: (track, thumb), ..self }
+    }
+}
+
+impl Default for ScrollBarCfg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WidgetCfg<Ui> for ScrollBarCfg {
+    type Widget = ScrollBar;
+
+    fn build(self, on_file: bool) -> (Self::Widget, impl Fn() -> bool + 'static, PushSpecs) {
+        let reader = on_file.then_some(context::fixed_reader().unwrap());
+
+        let widget = ScrollBar {
+            reader: reader.clone(),
+            text: Text::default(),
+            chars: self.chars,
+        };
+
+        // Scrolling doesn't necessarily change the File or its Cursors.
+        let checker = if let Some(reader) = reader {
+            let last_first = AtomicU32::new(u32::MAX);
+            Box::new(move || {
+                let has_changed = reader.has_changed();
+                let first = reader.inspect(|_, area, _| area.print_info().first_char().line());
+                has_changed || last_first.swap(first, Ordering::Relaxed) != first
+            }) as Box<dyn Fn() -> bool>
+        } else {
+            Box::new(move || false)
+        };
+
+        (widget, checker, self.specs)
+    }
+}
+
+/// The starting row and length of the thumb, if there is one
+///
+/// There is no thumb if the whole [`Text`] fits on the screen.
+fn thumb_of(text: &Text, area: &Area, height: usize) -> Option<(usize, usize)> {
+    let lines = text.len().line() as usize + 1;
+    let visible = area.height() as usize;
+    if lines <= visible || height == 0 {
+        return None;
+    }
+
+    let first = area.print_info().first_char().line() as usize;
+    let len = (height * visible).div_ceil(lines).clamp(1, height);
+    let start = (height * first / lines).min(height - len);
+
+    Some((start, len))
+}
+
+fn form_string(char: char, count: usize) -> String {
+    [char, '\n'].repeat(count).iter().collect()
+}
@@ -0,0 +1,304 @@
+//! A virtual terminal, for testing what gets printed
+//!
+//! While a virtual terminal is active on a thread, everything that
+//! would be printed to the terminal from that thread is parsed into
+//! a grid of cells instead, and the size of the terminal is the one
+//! given to [`start`]. This lets the output of the [`Printer`] be
+//! compared against snapshots stored in `duat-term/snapshots`.
+//!
+//! Only the characters in each cell are kept, since styles depend on
+//! the colorscheme, and would make the snapshots unreadable.
+//!
+//! [`Printer`]: crate::print::Printer
+use std::cell::RefCell;
+
+use unicode_width::UnicodeWidthChar;
+
+thread_local! {
+    static VIRTUAL: RefCell<Option<Screen>> = const { RefCell::new(None) };
+}
+
+/// Starts a virtual terminal on this thread, with the given size
+pub fn start(width: u16, height: u16) {
+    VIRTUAL.set(Some(Screen::new(width, height)));
+}
+
+/// Resizes the virtual terminal, clearing it, like most terminals do
+pub fn resize(width: u16, height: u16) {
+    VIRTUAL.with_borrow_mut(|screen| {
+        let screen = screen.as_mut().unwrap();
+        *screen = Screen {
+            pending: std::mem::take(&mut screen.pending),
+            ..Screen::new(width, height)
+        };
+    })
+}
+
+/// Whether a virtual terminal is active on this thread
+pub fn is_active() -> bool {
+    VIRTUAL.with_borrow(Option::is_some)
+}
+
+/// The size of the virtual terminal, if one is active
+pub fn size() -> Option<(u16, u16)> {
+    VIRTUAL.with_borrow(|screen| screen.as_ref().map(|s| (s.width, s.height)))
+}
+
+/// Feeds bytes to the virtual terminal
+pub fn write(bytes: &[u8]) {
+    VIRTUAL.with_borrow_mut(|screen| {
+        if let Some(screen) = screen.as_mut() {
+            screen.pending.extend_from_slice(bytes);
+            screen.parse();
+        }
+    })
+}
+
+/// The cells of the virtual terminal, one row per line
+///
+/// Trailing spaces are removed from every row.
+pub fn contents() -> String {
+    VIRTUAL.with_borrow(|screen| {
+        let screen = screen.as_ref().unwrap();
+        let mut contents = String::new();
+        for row in &screen.cells {
+            let row: String = row.iter().flatten().collect();
+            contents.push_str(row.trim_end());
+            contents.push('\n');
+        }
+        contents
+    })
+}
+
+/// The cells and cursor of a virtual terminal
+struct Screen {
+    width: u16,
+    height: u16,
+    /// A [`None`] cell is covered by the wide character before it.
+    cells: Vec<Vec<Option<char>>>,
+    cursor: (u16, u16),
+    saved: (u16, u16),
+    pending: Vec<u8>,
+}
+
+impl Screen {
+    fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![vec![Some(' '); width as usize]; height as usize],
+            cursor: (0, 0),
+            saved: (0, 0),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Parses as many of the pending bytes as possible
+    ///
+    /// Incomplete escape sequences and characters are left pending,
+    /// since they may be split between writes.
+    fn parse(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        let mut i = 0;
+
+        while i < pending.len() {
+            let rest = &pending[i..];
+            let parsed = if rest[0] == b'\x1b' {
+                self.escape(rest)
+            } else {
+                self.char(rest)
+            };
+
+            match parsed {
+                Some(len) => i += len,
+                None => break,
+            }
+        }
+
+        self.pending = pending[i..].to_vec();
+    }
+
+    /// Applies an escape sequence, returning its length
+    fn escape(&mut self, bytes: &[u8]) -> Option<usize> {
+        match bytes.get(1)? {
+            b'7' => {
+                self.saved = self.cursor;
+                return Some(2);
+            }
+            b'8' => {
+                self.cursor = self.saved;
+                return Some(2);
+            }
+            b'[' => {}
+            _ => return Some(2),
+        }
+
+        let end = bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b))? + 2;
+        let params: Vec<u16> = std::str::from_utf8(&bytes[2..end])
+            .unwrap()
+            .trim_start_matches('?')
+            .split(';')
+            .map(|param| param.parse().unwrap_or(0))
+            .collect();
+        let param = |i: usize| params.get(i).copied().filter(|p| *p > 0).unwrap_or(1);
+
+        match bytes[end] {
+            b'H' => self.cursor = (param(1) - 1, param(0) - 1),
+            b'G' => self.cursor.0 = param(0) - 1,
+            b'E' => self.cursor = (0, self.cursor.1 + param(0)),
+            b'J' if params[0] == 2 => *self = Screen::new(self.width, self.height),
+            _ => {}
+        }
+
+        Some(end + 1)
+    }
+
+    /// Prints a character, returning its length in bytes
+    fn char(&mut self, bytes: &[u8]) -> Option<usize> {
+        let len = bytes[0].leading_ones().max(1) as usize;
+        let char = std::str::from_utf8(bytes.get(..len)?)
+            .ok()?
+            .chars()
+            .next()?;
+
+        let (x, y) = (self.cursor.0 as usize, self.cursor.1 as usize);
+        let width = char.width().unwrap_or(0);
+        if let Some(row) = self.cells.get_mut(y)
+            && x + width <= row.len()
+        {
+            row[x] = Some(char);
+            for cell in &mut row[x + 1..x + width] {
+                *cell = None;
+            }
+        }
+        self.cursor.0 += width as u16;
+
+        Some(len)
+    }
+}
+
+mod tests {
+    use duat_core::{
+        cfg::PrintCfg,
+        data::RwData,
+        form,
+        text::Text,
+        ui::{Area as UiArea, PushSpecs},
+    };
+
+    use crate::{Area, Brush, Frame, area::PrintInfo, layout::Layout, print::Printer};
+
+    /// Compares the virtual terminal with a stored snapshot
+    ///
+    /// Run the tests with `DUAT_BLESS=1` in order to store the
+    /// current contents as the snapshot, after checking that they
+    /// are correct.
+    fn assert_snapshot(name: &str) {
+        let contents = super::contents();
+        let path = format!("{}/snapshots/{name}.txt", env!("CARGO_MANIFEST_DIR"));
+
+        if std::env::var_os("DUAT_BLESS").is_some() {
+            std::fs::write(&path, &contents).unwrap();
+        } else {
+            let snapshot = std::fs::read_to_string(&path)
+                .unwrap_or_else(|_| panic!("No snapshot at {path}, run with DUAT_BLESS=1"));
+            assert!(
+                contents == snapshot,
+                "Snapshot {name} differs\n--- expected\n{snapshot}--- found\n{contents}"
+            );
+        }
+    }
+
+    /// A root [`Area`] on a virtual terminal of the given size
+    fn root_area(width: u16, height: u16, fr: Frame) -> (RwData<Printer>, Area) {
+        super::start(width, height);
+        let printer = RwData::new(Printer::new());
+        let layout = Layout::new(fr, printer.clone(), PrintInfo::default());
+        let area = Area::new(layout.main_index(), RwData::new(layout));
+        printer.write().flush_equalities().unwrap();
+        (printer, area)
+    }
+
+    /// Prints every [`Area`], then flushes the [`Printer`]
+    fn print(printer: &RwData<Printer>, areas: &[(&Area, &Text)]) {
+        for (area, text) in areas {
+            UiArea::print(*area, text, PrintCfg::new(), form::painter());
+        }
+        printer.read().print();
+    }
+
+    /// Line numbers, to be shown next to a file
+    fn numbers_text() -> Text {
+        Text::from("1\n2\n3\n4\n5\n")
+    }
+
+    /// Numbered lines, to make scrolling visible
+    fn lines(amount: usize) -> Text {
+        let lines: String = (1..=amount).map(|i| format!("line {i}\n")).collect();
+        Text::from(lines)
+    }
+
+    #[test]
+    fn open_file() {
+        let (printer, area) = root_area(24, 4, Frame::Empty);
+        let text = Text::from("fn main() {\n    println!(\"漢字\");\n}\n");
+
+        print(&printer, &[(&area, &text)]);
+        assert_snapshot("open_file");
+    }
+
+    /// Two files side by side, with line numbers on the left one
+    ///
+    /// Like in the [`Window`], everything pushed to a file is also on
+    /// the files region.
+    ///
+    /// [`Window`]: duat_core::ui::Window
+    fn split_files(width: u16, height: u16) -> (RwData<Printer>, [Area; 3]) {
+        let (printer, left) = root_area(width, height, Frame::Border(Brush::Regular));
+        let specs = PushSpecs::right();
+        let (right, _) = left.bisect(specs, false, true, PrintInfo::default());
+        let specs = PushSpecs::left().with_hor_len(2.0);
+        let (numbers, _) = left.bisect(specs, true, true, PrintInfo::default());
+        printer.write().flush_equalities().unwrap();
+
+        (printer, [left, right, numbers])
+    }
+
+    #[test]
+    fn split() {
+        let (printer, [left, right, numbers]) = split_files(24, 5);
+
+        let (file, other, nums) = (lines(10), Text::from("other\nfile\n"), numbers_text());
+        print(&printer, &[
+            (&left, &file),
+            (&right, &other),
+            (&numbers, &nums),
+        ]);
+        assert_snapshot("split");
+    }
+
+    #[test]
+    fn resize() {
+        let (printer, [left, right, numbers]) = split_files(24, 5);
+
+        let (file, other, nums) = (lines(10), Text::from("other\nfile\n"), numbers_text());
+        let areas = [(&left, &file), (&right, &other), (&numbers, &nums)];
+        print(&printer, &areas);
+
+        super::resize(16, 3);
+        printer.write().update(true);
+        print(&printer, &areas);
+        assert_snapshot("resize");
+    }
+
+    #[test]
+    fn scroll() {
+        let (printer, area) = root_area(12, 4, Frame::Empty);
+        let text = lines(20);
+        print(&printer, &[(&area, &text)]);
+
+        area.scroll_ver(&text, 5, PrintCfg::new());
+        print(&printer, &[(&area, &text)]);
+        assert_snapshot("scroll");
+    }
+}
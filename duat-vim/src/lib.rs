@@ -0,0 +1,1011 @@
+#![feature(let_chains, if_let_guard)]
+
+use std::sync::{LazyLock, Once};
+
+use duat_core::{
+    cfg::WordChars,
+    context,
+    data::RwData,
+    hooks::{self, ChangeRecorded},
+    mode::{
+        self, Cursors, EditHelper, Fwd, IncSearcher, KeyCode::*, KeyEvent as Event, KeyMod as Mod,
+        Mode, Mover, RepeatableChange, Rev, key,
+    },
+    text::{Point, err},
+    ui::{Area, Ui},
+    widgets::{File, IncSearch, RunCommands},
+};
+
+static REGISTER: LazyLock<RwData<Register>> = LazyLock::new(RwData::default);
+static CHANGE_COUNT: LazyLock<RwData<Option<usize>>> = LazyLock::new(RwData::default);
+static LAST_COUNT: LazyLock<RwData<Option<usize>>> = LazyLock::new(RwData::default);
+
+#[derive(Clone)]
+pub struct Normal(Parser);
+
+impl Normal {
+    pub fn new() -> Self {
+        static RECORD_CHANGES: Once = Once::new();
+        RECORD_CHANGES.call_once(|| {
+            hooks::add::<ChangeRecorded>(|_| *LAST_COUNT.write() = *CHANGE_COUNT.read())
+        });
+
+        Normal(Parser::default())
+    }
+}
+
+impl<U: Ui> Mode<U> for Normal {
+    type Widget = File;
+
+    fn send_key(
+        &mut self,
+        key: Event,
+        widget: &RwData<Self::Widget>,
+        area: &U::Area,
+        cursors: &mut Cursors,
+    ) {
+        cursors.make_incl();
+        let mut helper = EditHelper::new(widget, area, cursors);
+        let w_chars = helper.cfg().word_chars;
+
+        let count = match self.0.parse(key, false) {
+            Parsed::Count | Parsed::Pending | Parsed::Cancelled => return,
+            Parsed::Motion(motion, count) => {
+                helper.move_each(|mut m| {
+                    m.unset_anchor();
+                    move_by(&mut m, motion, count, w_chars);
+                });
+                return;
+            }
+            Parsed::Object(..) => unreachable!(),
+            Parsed::Replace(char, count) => {
+                start_change(count);
+                let n = count.unwrap_or(1);
+
+                let mut fits = Vec::with_capacity(helper.cursors().len());
+                helper.move_each(|mut m| {
+                    m.unset_anchor();
+                    let len = m.iter().take_while(|(_, c)| *c != '\n').count();
+                    fits.push(len >= n);
+                    if len >= n {
+                        m.set_anchor();
+                        m.move_hor(n as i32 - 1);
+                    }
+                });
+
+                let mut fits = fits.into_iter();
+                let mut fits_clone = fits.clone();
+                helper.edit_each(|e| {
+                    if fits.next().unwrap() {
+                        e.replace(char.to_string().repeat(n))
+                    }
+                });
+                helper.move_each(|mut m| {
+                    if fits_clone.next().unwrap() {
+                        m.move_hor(-1);
+                    }
+                });
+
+                helper.new_moment();
+                mode::finish_change();
+                return;
+            }
+            Parsed::Other(count) => count,
+        };
+
+        match key {
+            ////////// Insert mode entering keys.
+            key!(Char('i')) => {
+                start_change(count);
+                mode::set::<U>(Insert);
+            }
+            key!(Char('a')) => {
+                start_change(count);
+                helper.move_each(|mut m| {
+                    if m.char() != '\n' {
+                        m.move_hor(1)
+                    }
+                });
+                mode::set::<U>(Insert);
+            }
+            key!(Char('I'), Mod::SHIFT) => {
+                start_change(count);
+                helper.move_each(|mut m| move_by(&mut m, Motion::FirstNonBlank, None, w_chars));
+                mode::set::<U>(Insert);
+            }
+            key!(Char('A'), Mod::SHIFT) => {
+                start_change(count);
+                helper.move_each(|mut m| {
+                    let (p0, _) = m.search_fwd('\n', None).next().unzip();
+                    if let Some(p0) = p0.or(m.last_point()) {
+                        m.move_to(p0);
+                    }
+                });
+                mode::set::<U>(Insert);
+            }
+            key!(Char('o')) => {
+                start_change(count);
+                helper.move_each(|mut m| {
+                    let (p0, _) = m.search_fwd('\n', None).next().unzip();
+                    if let Some(p0) = p0.or(m.last_point()) {
+                        m.move_to(p0);
+                    }
+                });
+                helper.edit_each(|e| e.insert('\n'));
+                helper.move_each(|mut m| m.move_hor(1));
+                mode::set::<U>(Insert);
+            }
+            key!(Char('O'), Mod::SHIFT) => {
+                start_change(count);
+                helper.move_each(|mut m| move_by(&mut m, Motion::LineStart, None, w_chars));
+                helper.edit_each(|e| e.insert('\n'));
+                mode::set::<U>(Insert);
+            }
+
+            ////////// Operators.
+            key!(Char('d')) => {
+                start_change(count);
+                mode::set::<U>(OpPending::new(Operator::Delete, count));
+            }
+            key!(Char('c')) => {
+                start_change(count);
+                mode::set::<U>(OpPending::new(Operator::Change, count));
+            }
+            key!(Char('y')) => mode::set::<U>(OpPending::new(Operator::Yank, count)),
+
+            ////////// Operator shorthands.
+            key!(Char('x')) => {
+                start_change(count);
+                operate::<U>(&mut helper, Operator::Delete, Target::Chars, count);
+            }
+            key!(Char('X'), Mod::SHIFT) => {
+                start_change(count);
+                operate::<U>(
+                    &mut helper,
+                    Operator::Delete,
+                    Target::Motion(Motion::Left),
+                    count,
+                );
+            }
+            key!(Char('D'), Mod::SHIFT) => {
+                start_change(count);
+                operate::<U>(
+                    &mut helper,
+                    Operator::Delete,
+                    Target::Motion(Motion::LineEnd),
+                    count,
+                );
+            }
+            key!(Char('C'), Mod::SHIFT) => {
+                start_change(count);
+                operate::<U>(
+                    &mut helper,
+                    Operator::Change,
+                    Target::Motion(Motion::LineEnd),
+                    count,
+                );
+            }
+            key!(Char('s')) => {
+                start_change(count);
+                operate::<U>(&mut helper, Operator::Change, Target::Chars, count);
+            }
+            key!(Char('S'), Mod::SHIFT) => {
+                start_change(count);
+                operate::<U>(&mut helper, Operator::Change, Target::Lines, count);
+            }
+            key!(Char('Y'), Mod::SHIFT) => {
+                operate::<U>(&mut helper, Operator::Yank, Target::Lines, count);
+            }
+
+            ////////// Register keys.
+            key!(Char('p')) => {
+                start_change(count);
+                paste(&mut helper, count, false);
+                mode::finish_change();
+            }
+            key!(Char('P'), Mod::SHIFT) => {
+                start_change(count);
+                paste(&mut helper, count, true);
+                mode::finish_change();
+            }
+
+            ////////// Other mode changing keys.
+            key!(Char('v')) => {
+                helper.move_each(|mut m| m.set_anchor());
+                mode::set::<U>(Visual::new(false));
+            }
+            key!(Char('V'), Mod::SHIFT) => {
+                helper.move_each(|mut m| m.set_anchor());
+                mode::set::<U>(Visual::new(true));
+            }
+            key!(Char(':')) => mode::set_cmd::<U>(RunCommands::new()),
+            key!(Char('/')) => mode::set_cmd::<U>(IncSearch::new(Fwd::new)),
+            key!(Char('?'), Mod::SHIFT | Mod::NONE) => mode::set_cmd::<U>(IncSearch::new(Rev::new)),
+
+            ////////// History manipulation.
+            key!(Char('u')) => helper.undo(),
+            key!(Char('r'), Mod::CONTROL) => helper.redo(),
+            key!(Char('.')) => {
+                if let Some(RepeatableChange::Keys(_)) = mode::last_change() {
+                    // The count is not part of the recorded keys, so it is sent
+                    // separately.
+                    let count = count.or(*LAST_COUNT.read());
+                    let digits = count.map(|c| c.to_string()).unwrap_or_default();
+                    mode::send_keys(digits.chars().map(|d| Event::new(Char(d), Mod::NONE)));
+                }
+
+                if !mode::repeat_last_change() {
+                    context::notify(err!("There is no change to repeat."))
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Default for Normal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone)]
+pub struct Insert;
+
+impl<U: Ui> Mode<U> for Insert {
+    type Widget = File;
+
+    fn send_key(
+        &mut self,
+        key: Event,
+        widget: &RwData<Self::Widget>,
+        area: &<U as Ui>::Area,
+        cursors: &mut Cursors,
+    ) {
+        cursors.make_incl();
+        let mut helper = EditHelper::new(widget, area, cursors);
+
+        if let key!(Tab) = key
+            && helper.accept_suggestion()
+        {
+            return;
+        }
+        helper.dismiss_suggestion();
+
+        match key {
+            key!(Char(char), Mod::SHIFT | Mod::NONE) => {
+                helper.edit_each(|e| e.insert(char));
+                helper.move_each(|mut m| m.move_hor(1));
+                helper.suggest();
+            }
+            key!(Enter) => {
+                helper.edit_each(|e| e.insert('\n'));
+                helper.move_each(|mut m| m.move_hor(1));
+            }
+            key!(Tab) => {
+                helper.edit_each(|e| e.insert('\t'));
+                helper.move_each(|mut m| m.move_hor(1));
+            }
+            key!(Backspace) => {
+                let mut is_start = Vec::with_capacity(helper.cursors().len());
+                helper.move_each(|mut m| {
+                    is_start.push(m.caret() == Point::default());
+                    m.move_hor(-1);
+                });
+                let mut is_start = is_start.into_iter();
+                helper.edit_each(|e| {
+                    if !is_start.next().unwrap() {
+                        e.replace("")
+                    }
+                });
+            }
+            key!(Delete) => helper.edit_each(|e| e.replace("")),
+
+            key!(Left) => helper.move_each(|mut m| m.move_hor(-1)),
+            key!(Down) => helper.move_each(|mut m| m.move_ver_wrapped(1)),
+            key!(Up) => helper.move_each(|mut m| m.move_ver_wrapped(-1)),
+            key!(Right) => helper.move_each(|mut m| m.move_hor(1)),
+
+            key!(Esc) => {
+                helper.move_each(|mut m| {
+                    if m.iter_rev().next().is_some_and(|(_, c)| c != '\n') {
+                        m.move_hor(-1);
+                    }
+                });
+                helper.new_moment();
+                mode::finish_change();
+                mode::set::<U>(Normal::new());
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Visual {
+    parser: Parser,
+    is_line: bool,
+}
+
+impl Visual {
+    pub fn new(is_line: bool) -> Self {
+        Self { parser: Parser::default(), is_line }
+    }
+}
+
+impl<U: Ui> Mode<U> for Visual {
+    type Widget = File;
+
+    fn send_key(
+        &mut self,
+        key: Event,
+        widget: &RwData<Self::Widget>,
+        area: &U::Area,
+        cursors: &mut Cursors,
+    ) {
+        cursors.make_incl();
+        let mut helper = EditHelper::new(widget, area, cursors);
+        let w_chars = helper.cfg().word_chars;
+
+        match self.parser.parse(key, true) {
+            Parsed::Count | Parsed::Pending | Parsed::Cancelled => return,
+            Parsed::Motion(motion, count) => {
+                helper.move_each(|mut m| move_by(&mut m, motion, count, w_chars));
+                return;
+            }
+            Parsed::Object(char, is_inner, _) => {
+                helper.move_each(|mut m| select_object(&mut m, char, is_inner, w_chars));
+                return;
+            }
+            Parsed::Replace(..) | Parsed::Other(_) => {}
+        }
+
+        let op = match key {
+            key!(Char('d' | 'x')) => Operator::Delete,
+            key!(Char('c' | 's')) => Operator::Change,
+            key!(Char('y')) => Operator::Yank,
+            key!(Char('o')) => {
+                helper.move_each(|mut m| m.swap_ends());
+                return;
+            }
+            key!(Char('v')) if self.is_line => {
+                self.is_line = false;
+                return;
+            }
+            key!(Char('V'), Mod::SHIFT) if !self.is_line => {
+                self.is_line = true;
+                return;
+            }
+            key!(Char('v') | Esc) | key!(Char('V'), Mod::SHIFT) => {
+                helper.move_each(|mut m| m.unset_anchor());
+                mode::set::<U>(Normal::new());
+                return;
+            }
+            _ => return,
+        };
+
+        if self.is_line {
+            helper.move_each(|mut m| expand_to_lines(&mut m));
+        }
+        finish_op::<U>(&mut helper, op, self.is_line, Vec::new());
+    }
+}
+
+#[derive(Clone)]
+struct OpPending {
+    op: Operator,
+    count: Option<usize>,
+    parser: Parser,
+}
+
+impl OpPending {
+    fn new(op: Operator, count: Option<usize>) -> Self {
+        Self { op, count, parser: Parser::default() }
+    }
+}
+
+impl<U: Ui> Mode<U> for OpPending {
+    type Widget = File;
+
+    fn send_key(
+        &mut self,
+        key: Event,
+        widget: &RwData<Self::Widget>,
+        area: &U::Area,
+        cursors: &mut Cursors,
+    ) {
+        cursors.make_incl();
+        let mut helper = EditHelper::new(widget, area, cursors);
+
+        // Counts before and after the operator are multiplied, like in Vim.
+        let count = |count: Option<usize>| match (self.count, count) {
+            (Some(c0), Some(c1)) => Some(c0 * c1),
+            (c0, c1) => c0.or(c1),
+        };
+
+        let target = match self.parser.parse(key, true) {
+            Parsed::Count | Parsed::Pending => return,
+            Parsed::Motion(motion, c) => (Target::Motion(motion), count(c)),
+            Parsed::Object(char, is_inner, c) => (Target::Object(char, is_inner), count(c)),
+            Parsed::Other(c) => match (self.op, key) {
+                (Operator::Delete, key!(Char('d')))
+                | (Operator::Change, key!(Char('c')))
+                | (Operator::Yank, key!(Char('y'))) => (Target::Lines, count(c)),
+                _ => {
+                    mode::cancel_change();
+                    mode::set::<U>(Normal::new());
+                    return;
+                }
+            },
+            Parsed::Replace(..) | Parsed::Cancelled => {
+                mode::cancel_change();
+                mode::set::<U>(Normal::new());
+                return;
+            }
+        };
+
+        operate::<U>(&mut helper, self.op, target.0, target.1);
+    }
+}
+
+/// Applies an [`Operator`] on the selections made by a [`Target`]
+fn operate<U: Ui>(
+    helper: &mut EditHelper<File, U::Area, ()>,
+    op: Operator,
+    target: Target,
+    count: Option<usize>,
+) {
+    let w_chars = helper.cfg().word_chars;
+    let mut is_line = false;
+    let mut are_empty = Vec::with_capacity(helper.cursors().len());
+
+    helper.move_each(|mut m| {
+        m.unset_anchor();
+        m.set_anchor();
+
+        let is_empty = match target {
+            Target::Motion(motion) => {
+                move_by(&mut m, motion, count, w_chars);
+                match motion.kind() {
+                    Kind::Exclusive => {
+                        let is_empty = m.anchor() == Some(m.caret());
+                        if !is_empty {
+                            if !m.anchor_is_start() {
+                                m.swap_ends();
+                            }
+                            m.move_hor(-1);
+                        }
+                        is_empty
+                    }
+                    Kind::Inclusive => false,
+                    Kind::Linewise => {
+                        is_line = true;
+                        expand_to_lines(&mut m);
+                        false
+                    }
+                }
+            }
+            Target::Object(char, is_inner) => {
+                m.unset_anchor();
+                !select_object(&mut m, char, is_inner, w_chars)
+            }
+            Target::Chars => {
+                let rem = m.iter().take_while(|(_, c)| *c != '\n').count();
+                m.move_hor(count.unwrap_or(1).min(rem) as i32 - 1);
+                rem == 0
+            }
+            Target::Lines => {
+                is_line = true;
+                m.move_ver(count.unwrap_or(1) as i32 - 1);
+                expand_to_lines(&mut m);
+                false
+            }
+        };
+
+        are_empty.push(is_empty);
+    });
+
+    finish_op::<U>(helper, op, is_line, are_empty);
+}
+
+/// Yanks, deletes or changes the selections
+///
+/// If an entry of `are_empty` is `true`, the respective selection is
+/// not edited.
+fn finish_op<U: Ui>(
+    helper: &mut EditHelper<File, U::Area, ()>,
+    op: Operator,
+    is_line: bool,
+    are_empty: Vec<bool>,
+) {
+    // When changing lines, the last newline is kept.
+    if let (Operator::Change, true) = (op, is_line) {
+        helper.move_each(|mut m| {
+            if m.char() == '\n' && m.anchor().is_some_and(|a| a < m.caret()) {
+                m.move_hor(-1);
+            } else if m.char() == '\n' {
+                m.unset_anchor();
+            }
+        });
+    }
+
+    let mut strs = Vec::with_capacity(helper.cursors().len());
+    let mut are_empty = are_empty.into_iter().chain(std::iter::repeat(false));
+    helper.move_each(|m| {
+        let is_empty = are_empty.next().unwrap() || (is_line && m.anchor().is_none());
+        strs.push((!is_empty).then(|| m.selection().concat()));
+    });
+
+    *REGISTER.write() = Register {
+        strs: strs
+            .iter()
+            .map(|str| str.clone().unwrap_or_default())
+            .collect(),
+        is_line,
+    };
+
+    match op {
+        Operator::Yank => helper.move_each(|mut m| {
+            if m.anchor_is_start() {
+                m.swap_ends();
+            }
+            m.unset_anchor();
+        }),
+        Operator::Delete | Operator::Change => {
+            let mut strs = strs.iter();
+            helper.edit_each(|e| {
+                if strs.next().unwrap().is_some() {
+                    e.replace("")
+                }
+            });
+            helper.move_each(|mut m| m.unset_anchor());
+        }
+    }
+
+    match op {
+        Operator::Yank => mode::set::<U>(Normal::new()),
+        Operator::Delete => {
+            helper.new_moment();
+            mode::finish_change();
+            mode::set::<U>(Normal::new());
+        }
+        Operator::Change => mode::set::<U>(Insert),
+    }
+}
+
+/// Pastes the contents of the [`Register`] after or before the
+/// cursors
+fn paste<S>(helper: &mut EditHelper<File, impl Area, S>, count: Option<usize>, before: bool) {
+    let Register { strs, is_line } = REGISTER.read().clone();
+    let Some(last) = strs.last().cloned() else {
+        context::notify(err!("There is nothing to paste."));
+        return;
+    };
+
+    let w_chars = helper.cfg().word_chars;
+    let count = count.unwrap_or(1);
+    let strs: Vec<String> = strs.into_iter().map(|str| str.repeat(count)).collect();
+
+    helper.move_each(|mut m| {
+        m.unset_anchor();
+        match (is_line, before) {
+            (true, true) => move_by(&mut m, Motion::LineStart, None, w_chars),
+            (true, false) => {
+                let (_, p1) = m.search_fwd('\n', None).next().unzip();
+                if let Some(p1) = p1 {
+                    m.move_to(p1);
+                }
+            }
+            (false, false) if m.char() != '\n' => m.move_hor(1),
+            (false, _) => {}
+        }
+    });
+
+    let last = last.repeat(count);
+    let mut iter = strs.iter().chain(std::iter::repeat(&last));
+    helper.edit_each(|e| e.insert(iter.next().unwrap()));
+
+    if !is_line {
+        let mut iter = strs.iter().chain(std::iter::repeat(&last));
+        helper.move_each(|mut m| {
+            let len = iter.next().unwrap().chars().count() as i32;
+            m.move_hor(len - 1);
+        });
+    }
+    helper.new_moment();
+}
+
+/// Starts recording a change, alongside the count that preceded it
+fn start_change(count: Option<usize>) {
+    *CHANGE_COUNT.write() = count;
+    mode::start_change();
+}
+
+/// Moves the caret according to a [`Motion`]
+fn move_by<S>(
+    m: &mut Mover<impl Area, S>,
+    motion: Motion,
+    count: Option<usize>,
+    w_chars: WordChars,
+) {
+    let n = count.unwrap_or(1);
+    let is_space = |c: char| [' ', '\t', '\n'].contains(&c);
+
+    match motion {
+        Motion::Left => {
+            let col = m.iter_rev().take_while(|(_, c)| *c != '\n').count();
+            m.move_hor(-(n.min(col) as i32));
+        }
+        Motion::Right => {
+            let rem = m.iter().take_while(|(_, c)| *c != '\n').count();
+            m.move_hor(n.min(rem.saturating_sub(1)) as i32);
+        }
+        Motion::Down => m.move_ver(n as i32),
+        Motion::Up => m.move_ver(-(n as i32)),
+        Motion::WordFwd(is_big) => {
+            for _ in 0..n {
+                let cat = Category::of(m.char(), w_chars, is_big);
+                let next = m
+                    .iter()
+                    .skip_while(|(_, c)| Category::of(*c, w_chars, is_big) == cat)
+                    .find(|(_, c)| !is_space(*c));
+                match next {
+                    Some((p, _)) => m.move_to(p),
+                    None => break,
+                }
+            }
+        }
+        Motion::WordEnd(is_big) => {
+            for _ in 0..n {
+                let mut iter = m
+                    .iter()
+                    .skip(1)
+                    .skip_while(|(_, c)| is_space(*c))
+                    .peekable();
+                let Some(&(_, first)) = iter.peek() else {
+                    break;
+                };
+                let cat = Category::of(first, w_chars, is_big);
+                let last = iter
+                    .take_while(|(_, c)| Category::of(*c, w_chars, is_big) == cat)
+                    .last();
+                if let Some((p, _)) = last {
+                    m.move_to(p);
+                }
+            }
+        }
+        Motion::WordBack(is_big) => {
+            for _ in 0..n {
+                let mut iter = m.iter_rev().skip_while(|(_, c)| is_space(*c)).peekable();
+                let Some(&(_, first)) = iter.peek() else {
+                    break;
+                };
+                let cat = Category::of(first, w_chars, is_big);
+                let last = iter
+                    .take_while(|(_, c)| Category::of(*c, w_chars, is_big) == cat)
+                    .last();
+                if let Some((p, _)) = last {
+                    m.move_to(p);
+                }
+            }
+        }
+        Motion::LineStart => {
+            let start = m.iter_rev().take_while(|(_, c)| *c != '\n').last();
+            if let Some((p, _)) = start {
+                m.move_to(p);
+            }
+        }
+        Motion::FirstNonBlank => {
+            move_by(m, Motion::LineStart, None, w_chars);
+            let first = m.iter().find(|(_, c)| !matches!(c, ' ' | '\t'));
+            if let Some((p, c)) = first
+                && c != '\n'
+            {
+                m.move_to(p);
+            }
+        }
+        Motion::LineEnd => {
+            m.move_ver(n as i32 - 1);
+            let end = m.iter().take_while(|(_, c)| *c != '\n').last();
+            if let Some((p, _)) = end {
+                m.move_to(p);
+            }
+        }
+        Motion::FileStart => {
+            m.move_to_coords(count.map_or(0, |n| n as u32 - 1), 0);
+            move_by(m, Motion::FirstNonBlank, None, w_chars);
+        }
+        Motion::FileEnd => {
+            match count {
+                Some(n) => m.move_to_coords(n as u32 - 1, 0),
+                None => m.move_ver(i32::MAX),
+            }
+            move_by(m, Motion::FirstNonBlank, None, w_chars);
+        }
+        Motion::Find(find, char) => {
+            let target = match find {
+                Find::To | Find::Till => m
+                    .iter()
+                    .skip(1)
+                    .take_while(|(_, c)| *c != '\n')
+                    .filter(|(_, c)| *c == char)
+                    .nth(n - 1),
+                Find::ToRev | Find::TillRev => m
+                    .iter_rev()
+                    .take_while(|(_, c)| *c != '\n')
+                    .filter(|(_, c)| *c == char)
+                    .nth(n - 1),
+            };
+
+            if let Some((p, _)) = target {
+                m.move_to(p);
+                match find {
+                    Find::Till => m.move_hor(-1),
+                    Find::TillRev => m.move_hor(1),
+                    Find::To | Find::ToRev => {}
+                }
+            }
+        }
+        Motion::MatchingBracket => {
+            if let Some(p) = m.matching_bracket() {
+                m.move_to(p);
+            }
+        }
+    }
+}
+
+/// Selects a text object around the caret
+///
+/// Returns `false` if there was no such object.
+fn select_object<S>(
+    m: &mut Mover<impl Area, S>,
+    char: char,
+    is_inner: bool,
+    w_chars: WordChars,
+) -> bool {
+    let pair = match char {
+        'b' | '(' | ')' => ('(', ')'),
+        'B' | '{' | '}' => ('{', '}'),
+        '[' | ']' => ('[', ']'),
+        '<' | '>' => ('<', '>'),
+        '"' | '\'' | '`' => (char, char),
+        'w' | 'W' => {
+            let is_big = char == 'W';
+            let cat = Category::of(m.char(), w_chars, is_big);
+            let Some((p0, p1)) = m.enclosing(|c| Category::of(c, w_chars, is_big) == cat) else {
+                return false;
+            };
+
+            m.move_to(p0);
+            m.set_anchor();
+            m.move_to(p1);
+            if !is_inner {
+                m.move_hor(1);
+                match m.enclosing(|c| [' ', '\t'].contains(&c)) {
+                    Some((_, p1)) => m.move_to(p1),
+                    None => m.move_hor(-1),
+                }
+            }
+            return true;
+        }
+        _ => return false,
+    };
+
+    let Some((p0, p1)) = m.enclosing_pair(pair.0, pair.1) else {
+        return false;
+    };
+
+    if is_inner {
+        if p1.char() == p0.char() + 1 {
+            return false;
+        }
+        m.move_to(p0);
+        m.move_hor(1);
+        m.set_anchor();
+        m.move_to(p1);
+        m.move_hor(-1);
+    } else {
+        m.move_to(p0);
+        m.set_anchor();
+        m.move_to(p1);
+    }
+
+    true
+}
+
+/// Expands the selection to cover whole lines, newlines included
+fn expand_to_lines<S>(m: &mut Mover<impl Area, S>) {
+    if !m.anchor_is_start() {
+        m.swap_ends();
+    }
+
+    let (p0, _) = m.search_fwd('\n', None).next().unzip();
+    if let Some(p0) = p0.or(m.last_point()) {
+        m.move_to(p0);
+    }
+
+    m.swap_ends();
+    let (_, p1) = m.search_rev('\n', None).next().unzip();
+    m.move_to(p1.unwrap_or_default());
+    m.swap_ends();
+}
+
+/// A parser for counts and motions, shared by every [`Mode`]
+#[derive(Clone, Copy, Default)]
+struct Parser {
+    count: Option<usize>,
+    pending: Option<Pending>,
+}
+
+impl Parser {
+    fn parse(&mut self, key: Event, with_objects: bool) -> Parsed {
+        if let Some(pending) = self.pending.take() {
+            let count = self.count.take();
+            return match (pending, key) {
+                (Pending::Find(find), key!(Char(char), Mod::SHIFT | Mod::NONE)) => {
+                    Parsed::Motion(Motion::Find(find, char), count)
+                }
+                (Pending::G, key!(Char('g'))) => Parsed::Motion(Motion::FileStart, count),
+                (Pending::Object(is_inner), key!(Char(char), Mod::SHIFT | Mod::NONE)) => {
+                    Parsed::Object(char, is_inner, count)
+                }
+                (Pending::Replace, key!(Char(char), Mod::SHIFT | Mod::NONE)) => {
+                    Parsed::Replace(char, count)
+                }
+                _ => Parsed::Cancelled,
+            };
+        }
+
+        let pending = match key {
+            key!(Char(char))
+                if let Some(digit) = char.to_digit(10)
+                    && (digit > 0 || self.count.is_some()) =>
+            {
+                self.count = Some(self.count.unwrap_or(0) * 10 + digit as usize);
+                return Parsed::Count;
+            }
+            key!(Char('f')) => Pending::Find(Find::To),
+            key!(Char('F'), Mod::SHIFT) => Pending::Find(Find::ToRev),
+            key!(Char('t')) => Pending::Find(Find::Till),
+            key!(Char('T'), Mod::SHIFT) => Pending::Find(Find::TillRev),
+            key!(Char('g')) => Pending::G,
+            key!(Char('r')) if !with_objects => Pending::Replace,
+            key!(Char('i')) if with_objects => Pending::Object(true),
+            key!(Char('a')) if with_objects => Pending::Object(false),
+            key => {
+                let count = self.count.take();
+                return match Motion::from_key(key) {
+                    Some(motion) => Parsed::Motion(motion, count),
+                    None => Parsed::Other(count),
+                };
+            }
+        };
+
+        self.pending = Some(pending);
+        Parsed::Pending
+    }
+}
+
+/// The result of parsing a key
+enum Parsed {
+    Count,
+    Pending,
+    Motion(Motion, Option<usize>),
+    Object(char, bool, Option<usize>),
+    Replace(char, Option<usize>),
+    Other(Option<usize>),
+    Cancelled,
+}
+
+#[derive(Clone, Copy)]
+enum Pending {
+    Find(Find),
+    Object(bool),
+    Replace,
+    G,
+}
+
+#[derive(Clone, Copy)]
+enum Motion {
+    Left,
+    Down,
+    Up,
+    Right,
+    WordFwd(bool),
+    WordEnd(bool),
+    WordBack(bool),
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    FileStart,
+    FileEnd,
+    Find(Find, char),
+    MatchingBracket,
+}
+
+impl Motion {
+    fn from_key(key: Event) -> Option<Self> {
+        Some(match key {
+            key!(Char('h') | Left | Backspace) => Motion::Left,
+            key!(Char('j') | Down) => Motion::Down,
+            key!(Char('k') | Up) => Motion::Up,
+            key!(Char('l') | Right | Char(' ')) => Motion::Right,
+            key!(Char('w')) => Motion::WordFwd(false),
+            key!(Char('W'), Mod::SHIFT) => Motion::WordFwd(true),
+            key!(Char('e')) => Motion::WordEnd(false),
+            key!(Char('E'), Mod::SHIFT) => Motion::WordEnd(true),
+            key!(Char('b')) => Motion::WordBack(false),
+            key!(Char('B'), Mod::SHIFT) => Motion::WordBack(true),
+            key!(Char('0') | Home) => Motion::LineStart,
+            key!(Char('^'), Mod::SHIFT | Mod::NONE) => Motion::FirstNonBlank,
+            key!(Char('$'), Mod::SHIFT | Mod::NONE) | key!(End) => Motion::LineEnd,
+            key!(Char('G'), Mod::SHIFT) => Motion::FileEnd,
+            key!(Char('%'), Mod::SHIFT | Mod::NONE) => Motion::MatchingBracket,
+            _ => return None,
+        })
+    }
+
+    fn kind(&self) -> Kind {
+        match self {
+            Motion::Down | Motion::Up | Motion::FileStart | Motion::FileEnd => Kind::Linewise,
+            Motion::WordEnd(_) | Motion::LineEnd | Motion::MatchingBracket => Kind::Inclusive,
+            Motion::Find(Find::To | Find::Till, _) => Kind::Inclusive,
+            _ => Kind::Exclusive,
+        }
+    }
+}
+
+/// How a [`Motion`] selects text when used by an [`Operator`]
+enum Kind {
+    Exclusive,
+    Inclusive,
+    Linewise,
+}
+
+#[derive(Clone, Copy)]
+enum Find {
+    To,
+    ToRev,
+    Till,
+    TillRev,
+}
+
+#[derive(Clone, Copy)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Clone, Copy)]
+enum Target {
+    Motion(Motion),
+    Object(char, bool),
+    Chars,
+    Lines,
+}
+
+#[derive(Default, Clone)]
+struct Register {
+    strs: Vec<String>,
+    is_line: bool,
+}
+
+#[derive(PartialEq, Eq)]
+enum Category {
+    Word,
+    Special,
+    Space,
+}
+
+impl Category {
+    fn of(char: char, w_chars: WordChars, is_big: bool) -> Self {
+        if [' ', '\t', '\n'].contains(&char) {
+            Category::Space
+        } else if is_big || w_chars.contains(char) {
+            Category::Word
+        } else {
+            Category::Special
+        }
+    }
+}
@@ -132,7 +132,7 @@
 //!
 //! [`Form`]: crate::form::Form
 
-pub use duat_core::cmd::{Args, Flags};
+pub use duat_core::cmd::{Args, Flags, When, run_with_payload, set_payload};
 use duat_core::{
     cmd::{self, CmdResult},
     mode::Cursors,
@@ -222,6 +222,27 @@ pub fn add(
     cmd::add(callers, f)
 }
 
+/// Adds a command that can only be ran in certain conditions
+///
+/// See [`When`] for the available conditions. If the condition is
+/// not met, running the command will return an error explaining why.
+///
+/// ```rust
+/// # use duat::prelude::{cmd::{self, When}, Ui};
+/// cmd::add_when(["clear-file"], When::file::<Ui>(), |_flags, _args| {
+///     // Logic to clear the current file.
+///     Ok(None)
+/// });
+/// ```
+#[inline(never)]
+pub fn add_when(
+    callers: impl IntoIterator<Item = impl ToString>,
+    when: When,
+    f: impl FnMut(Flags, Args) -> CmdResult + 'static,
+) -> Result<()> {
+    cmd::add_when(callers, when, f)
+}
+
 /// Adds a command that can mutate a widget of the given type,
 /// along with its associated [`dyn Area`].
 ///
@@ -0,0 +1,219 @@
+//! The `checkhealth` command
+//!
+//! This command checks the environment that Duat is running in, and
+//! shows a report of what may be wrong with it, alongside hints on
+//! how to fix it:
+//!
+//! ```text
+//! :checkhealth
+//! ```
+//!
+//! It checks if the config crate is built and up to date, if the
+//! terminal supports true colors and the kitty keyboard protocol, if
+//! the clipboard can be reached, if enough files can be watched, and
+//! if optional tools like `rg` and `git` are installed.
+use std::{path::Path, time::SystemTime};
+
+use duat_core::text::{Builder, Text, err, hint, ok};
+
+/// The profile that the config crate is built with
+const PROFILE: &str = if cfg!(debug_assertions) {
+    "debug"
+} else {
+    "release"
+};
+/// Below this, watching files in big projects may fail
+const MIN_INOTIFY_WATCHES: u64 = 8192;
+
+/// Adds the `checkhealth` command
+pub(crate) fn setup() {
+    let _ = crate::cmd::add(["checkhealth"], |_, _| {
+        let checks = [
+            check_config_crate(),
+            check_true_color(),
+            check_kitty_keyboard(),
+            check_clipboard(),
+            check_watch_limit(),
+            check_tool("rg", "Searching across files will be slower."),
+            check_tool("git", "Project information won't be available."),
+        ];
+
+        let mut builder = Text::builder();
+        ok!(builder, "Health of Duat:");
+        for check in checks.into_iter().flatten() {
+            check.push_to(&mut builder);
+        }
+
+        Ok(Some(builder.finish()))
+    });
+}
+
+/// The result of checking one part of the environment
+struct Check {
+    name: &'static str,
+    result: Result<String, (String, String)>,
+}
+
+impl Check {
+    /// A [`Check`] that passed, with some details
+    fn passed(name: &'static str, details: impl ToString) -> Self {
+        Self { name, result: Ok(details.to_string()) }
+    }
+
+    /// A [`Check`] that failed, with a hint on how to fix it
+    fn failed(name: &'static str, problem: impl ToString, hint: impl ToString) -> Self {
+        Self {
+            name,
+            result: Err((problem.to_string(), hint.to_string())),
+        }
+    }
+
+    /// Adds this [`Check`] as a line of the report
+    fn push_to(self, builder: &mut Builder) {
+        match self.result {
+            Ok(details) => ok!(*builder, "\n  " [*a] "ok " [] { self.name } ": " details),
+            Err((problem, fix)) => {
+                err!(*builder, "\n  " [*a] "err " [] { self.name } ": " problem);
+                hint!(*builder, "\n      " fix);
+            }
+        }
+    }
+}
+
+/// Checks if the config crate exists and is built
+///
+/// Since the config crate is built when Duat starts, a library older
+/// than the sources means that the last build failed.
+fn check_config_crate() -> Option<Check> {
+    let name = "config crate";
+    let Some(crate_dir) = dirs_next::config_dir().map(|dir| dir.join("duat")) else {
+        return Some(Check::failed(
+            name,
+            "no config directory",
+            "Set $XDG_CONFIG_HOME or $HOME.",
+        ));
+    };
+    let build = format!(
+        "Run `cargo build` in {} to see the errors.",
+        crate_dir.display()
+    );
+
+    if !crate_dir.join("Cargo.toml").exists() {
+        return Some(Check::failed(
+            name,
+            format!("not found in {}", crate_dir.display()),
+            "Create it, or run Duat once to have it created.",
+        ));
+    }
+
+    let lib = crate_dir.join(format!("target/{PROFILE}/libconfig.so"));
+    let Some(built) = newest_mtime(&lib) else {
+        return Some(Check::failed(name, "was never built", build));
+    };
+    let sources = [crate_dir.join("src"), crate_dir.join("Cargo.toml")];
+    let changed = sources.iter().filter_map(|path| newest_mtime(path)).max();
+
+    Some(match changed.is_some_and(|changed| changed > built) {
+        true => Check::failed(name, "the last build failed", build),
+        false => Check::passed(name, format!("built in {}", crate_dir.display())),
+    })
+}
+
+/// Checks if the terminal says it supports 24 bit colors
+fn check_true_color() -> Option<Check> {
+    let name = "true color";
+    Some(match std::env::var("COLORTERM").as_deref() {
+        Ok("truecolor" | "24bit") => Check::passed(name, "supported"),
+        _ => Check::failed(
+            name,
+            "$COLORTERM isn't truecolor or 24bit",
+            "Colors may be off. If your terminal supports them, set COLORTERM=truecolor.",
+        ),
+    })
+}
+
+/// Checks if the terminal is known to support the kitty keyboard
+/// protocol
+///
+/// Asking the terminal directly would race with the reading of
+/// input, so this is only a guess based on the environment.
+fn check_kitty_keyboard() -> Option<Check> {
+    let name = "kitty keyboard protocol";
+    let term = std::env::var("TERM").unwrap_or_default();
+    let program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+
+    let is_known = term == "xterm-kitty"
+        || term == "xterm-ghostty"
+        || term == "foot"
+        || ["WezTerm", "ghostty", "iTerm.app"].contains(&program.as_str())
+        || std::env::var_os("KITTY_WINDOW_ID").is_some();
+
+    Some(match is_known {
+        true => Check::passed(name, "probably supported"),
+        false => Check::failed(
+            name,
+            format!("not known to be supported by {term}"),
+            "Some keys, like ctrl-i and tab, may be indistinguishable.",
+        ),
+    })
+}
+
+/// Checks if the clipboard can be reached through OSC 52
+fn check_clipboard() -> Option<Check> {
+    let name = "clipboard";
+    Some(match std::env::var_os("TMUX").is_some() {
+        true => Check::failed(
+            name,
+            "running inside tmux",
+            "Make sure that `set -g set-clipboard on` is in your tmux config.",
+        ),
+        false => Check::passed(name, "through OSC 52"),
+    })
+}
+
+/// Checks how many files can be watched, on Linux
+fn check_watch_limit() -> Option<Check> {
+    let name = "file watching";
+    let limit = std::fs::read_to_string("/proc/sys/fs/inotify/max_user_watches").ok()?;
+    let limit: u64 = limit.trim().parse().ok()?;
+
+    Some(match limit < MIN_INOTIFY_WATCHES {
+        true => Check::failed(
+            name,
+            format!("only {limit} watches are allowed"),
+            "Raise fs.inotify.max_user_watches with sysctl.",
+        ),
+        false => Check::passed(name, format!("up to {limit} watches")),
+    })
+}
+
+/// Checks if an optional tool is in the `$PATH`
+fn check_tool(tool: &'static str, without_it: &str) -> Option<Check> {
+    let path = std::env::var_os("PATH")?;
+    let found = std::env::split_paths(&path)
+        .map(|dir| dir.join(tool))
+        .find(|path| path.is_file());
+
+    Some(match found {
+        Some(path) => Check::passed(tool, format!("found at {}", path.display())),
+        None => Check::failed(
+            tool,
+            "not found",
+            format!("{without_it} Install it, or add it to $PATH."),
+        ),
+    })
+}
+
+/// The latest modification time of a file, or of any file in a
+/// directory
+fn newest_mtime(path: &Path) -> Option<SystemTime> {
+    let metadata = path.metadata().ok()?;
+    if metadata.is_dir() {
+        std::fs::read_dir(path)
+            .ok()?
+            .filter_map(|entry| newest_mtime(&entry.ok()?.path()))
+            .max()
+    } else {
+        metadata.modified().ok()
+    }
+}
@@ -203,11 +203,11 @@
 //! - [x] Add the ability to create hooks;
 //! - [x] Create a more generalized plugin system;
 //! - [x] Implement incremental Regex searching;
-//! - [ ] Add floating widgets, not tied to the session layout;
+//! - [x] Add floating widgets, not tied to the session layout;
 //! - [ ] Implement autocompletion lists;
-//! - [ ] Implement tree-sitter;
+//! - [x] Implement tree-sitter;
 //! - [ ] Create an LSP plugin;
-//! - [ ] Create a vim mode;
+//! - [x] Create a vim mode;
 //!
 //! ︙
 //!
@@ -267,11 +267,15 @@
 use std::sync::RwLock;
 
 use duat_core::session::SessionCfg;
-pub use duat_core::thread;
+pub use duat_core::{
+    clip_history, format, hover, indent, opts, registers, suggestion, thread, watcher,
+};
 pub use setup::{pre_setup, run_duat};
 
 pub mod cmd;
+mod health;
 pub mod print;
+mod project;
 mod setup;
 
 pub mod mode {
@@ -310,6 +314,13 @@ pub mod mode {
         mode::set_cmd(mode);
     }
 
+    /// Switches back to the previously focused widget
+    ///
+    /// Returns `false` if there is no widget to switch back to.
+    pub fn switch_back() -> bool {
+        mode::switch_back::<Ui>()
+    }
+
     /// Maps a sequence of keys to another
     ///
     /// The keys follow the same rules as Vim, so regular, standalone
@@ -376,6 +387,64 @@ pub mod mode {
     }
 }
 
+pub mod filetype {
+    //! Configuration of [`File`]s by their filetype
+    //!
+    //! [`File`]: duat_core::widgets::File
+    pub use duat_core::filetype::{add_extension, detect, set_form, set_print_cfg};
+    use duat_core::{filetype, mode::AsGives};
+
+    use crate::{Ui, mode::Mode};
+
+    /// Maps a sequence of keys to another, on [`File`]s of a filetype
+    ///
+    /// This works just like [`mode::map`], but the remap only
+    /// happens while the active [`File`] is of the given filetype.
+    ///
+    /// [`File`]: duat_core::widgets::File
+    /// [`mode::map`]: crate::mode::map
+    pub fn map<M: Mode<Ui>>(filetype: impl ToString, take: &str, give: impl AsGives<Ui>) {
+        filetype::map::<M, Ui>(filetype, take, give);
+    }
+
+    /// Aliases a sequence of keys to another, on [`File`]s of a
+    /// filetype
+    ///
+    /// This works just like [`mode::alias`], but the alias only
+    /// happens while the active [`File`] is of the given filetype.
+    ///
+    /// [`File`]: duat_core::widgets::File
+    /// [`mode::alias`]: crate::mode::alias
+    pub fn alias<M: Mode<Ui>>(filetype: impl ToString, take: &str, give: impl AsGives<Ui>) {
+        filetype::alias::<M, Ui>(filetype, take, give);
+    }
+}
+
+pub mod toggle {
+    //! [`Widget`]s that can be shown and hidden with the `toggle`
+    //! command
+    //!
+    //! By default, the [`LineNumbers`], [`VertRule`] and
+    //! [`StatusLine`] widgets can be toggled, as `line-numbers`,
+    //! `vert-rule` and `status-line` respectively.
+    //!
+    //! [`LineNumbers`]: crate::prelude::LineNumbers
+    //! [`VertRule`]: crate::prelude::VertRule
+    //! [`StatusLine`]: crate::prelude::StatusLine
+    pub use duat_core::toggle::is_shown;
+    use duat_core::{toggle, widgets::Widget};
+
+    use crate::Ui;
+
+    /// Lets [`Widget`]s of type `W` be toggled by a name
+    ///
+    /// If the name was already registered, it will now toggle `W`
+    /// instead.
+    pub fn register<W: Widget<Ui>>(name: impl ToString) {
+        toggle::register::<W, Ui>(name);
+    }
+}
+
 pub mod cursor {
     //! Functions to alter the [`Cursors`] of Duat
     //!
@@ -389,12 +458,17 @@ pub mod cursor {
 
 pub mod form {
     //! Functions to alter the [`Form`]s of Duat
-    pub use duat_core::form::{CursorShape, Form, from_id, id_of, set};
+    pub use duat_core::form::{
+        CursorShape, DEFAULT_PRIORITY, Form, from_id, id_of, set, set_dim_inactive, set_priority,
+    };
 }
 
 pub mod hooks {
     //! Hook utilities
-    pub use duat_core::hooks::{ModeSwitched, add, add_grouped, group_exists, remove};
+    pub use duat_core::hooks::{
+        ModeSwitched, OnFileChangedExternally, OnFileTypeSet, OnHover, OnOptionChanged,
+        TimerHandle, add, add_grouped, every, group_exists, remove,
+    };
 
     use crate::Ui;
     /// Triggers whenever a [`File`] is created
@@ -435,31 +509,64 @@ pub mod hooks {
     ///
     /// [`widget`]: duat_core::widgets::Widget
     pub type UnfocusedFrom<W> = duat_core::hooks::UnfocusedFrom<W, Ui>;
+
+    /// Triggers whenever the focus changes from one widget to another
+    ///
+    /// # Arguments
+    ///
+    /// - The previously focused widget.
+    /// - The newly focused widget.
+    pub type OnFocusChange = duat_core::hooks::OnFocusChange<Ui>;
 }
 
 pub mod plugin {
     //! Functions to load [`Plugin`]s
-    pub use duat_core::Plugin;
+    use duat_core::duat_name;
+    pub use duat_core::{Plugin, session::FileOpener};
 
-    use crate::{Ui, setup::PLUGIN_FN};
+    use crate::{Ui, project::plugin_is_disabled, setup::PLUGIN_FN};
 
     /// Loads the [`Plugin`]
+    ///
+    /// The [`Plugin`] won't be loaded if it was disabled by a trusted
+    /// project configuration.
     pub fn load<P: Plugin<Ui>>() {
         let mut old = PLUGIN_FN.write().unwrap();
         let old_f = std::mem::replace(&mut *old, Box::new(|_| {}));
         *old = Box::new(|cfg| {
             old_f(cfg);
-            cfg.load_plugin::<P>();
+            if !plugin_is_disabled(duat_name::<P>()) {
+                cfg.load_plugin::<P>();
+            }
         });
     }
 
     /// Loads the [`Plugin`], then mutates it
+    ///
+    /// The [`Plugin`] won't be loaded if it was disabled by a trusted
+    /// project configuration.
     pub fn load_and<P: Plugin<Ui>>(f: impl FnOnce(&mut P) + Send + Sync + 'static) {
         let mut old = PLUGIN_FN.write().unwrap();
         let old_f = std::mem::replace(&mut *old, Box::new(|_| {}));
         *old = Box::new(|cfg| {
             old_f(cfg);
-            cfg.load_plugin_and(f);
+            if !plugin_is_disabled(duat_name::<P>()) {
+                cfg.load_plugin_and(f);
+            }
+        });
+    }
+
+    /// Adds a [`FileOpener`], which can intercept the opening of
+    /// files
+    ///
+    /// Openers added later take precedence over ones added earlier,
+    /// including Duat's default ones.
+    pub fn add_opener(opener: impl FileOpener) {
+        let mut old = PLUGIN_FN.write().unwrap();
+        let old_f = std::mem::replace(&mut *old, Box::new(|_| {}));
+        *old = Box::new(|cfg| {
+            old_f(cfg);
+            cfg.add_opener(opener);
         });
     }
 }
@@ -468,23 +575,68 @@ pub mod widgets {
     //! Duat's builtin widgets
     pub use duat_core::{
         ui::Constraint,
-        widgets::{File, status},
+        widgets::{File, Vars, cache_key, status},
     };
 
     use crate::Ui;
 
     pub type CmdLine = duat_core::widgets::CmdLine<Ui>;
     pub type CmdLineCfg = duat_core::widgets::CmdLineCfg<Ui>;
+    pub type DiffGutter = duat_core::widgets::DiffGutter<Ui>;
+    pub type DiffGutterCfg = duat_core::widgets::DiffGutterCfg<Ui>;
     pub type StatusLine = duat_core::widgets::StatusLine<Ui>;
     pub type StatusLineCfg = duat_core::widgets::StatusLineCfg<Ui>;
+    pub type KeyHints = duat_core::widgets::KeyHints<Ui>;
+    pub type KeyHintsCfg = duat_core::widgets::KeyHintsCfg<Ui>;
     pub type LineNumbers = duat_core::widgets::LineNumbers<Ui>;
+    pub type View = duat_core::widgets::View<Ui>;
 }
 
 pub mod state {
     //! Common [`StatusLine`] fields
     //!
     //! [`StatusLine`]: crate::widgets::StatusLine
+    use duat_core::text::Text;
     pub use duat_core::widgets::common::*;
+
+    use crate::Ui;
+
+    /// The number of open windows
+    pub fn window_count() -> (
+        impl Fn() -> usize + Send + Sync + 'static,
+        impl Fn() -> bool + Send + Sync + 'static,
+    ) {
+        duat_core::widgets::common::window_count::<Ui>()
+    }
+
+    /// The current window, and the number of windows, formatted
+    pub fn window_fmt() -> (
+        impl Fn() -> Text + Send + Sync + 'static,
+        impl Fn() -> bool + Send + Sync + 'static,
+    ) {
+        duat_core::widgets::common::window_fmt::<Ui>()
+    }
+
+    /// The width and height of the current [`File`]'s area
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn area_size() -> (
+        impl Fn() -> String + Send + Sync + 'static,
+        impl Fn() -> bool + Send + Sync + 'static,
+    ) {
+        duat_core::widgets::common::area_size::<Ui>()
+    }
+
+    /// The axis along which the current [`File`] was split, in
+    /// lowercase
+    ///
+    /// [`File`]: crate::widgets::File
+    pub fn split_axis() -> (
+        impl Fn() -> &'static str + Send + Sync + 'static,
+        impl Fn() -> bool + Send + Sync + 'static,
+    ) {
+        duat_core::widgets::common::split_axis::<Ui>()
+    }
 }
 
 pub mod control {
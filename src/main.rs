@@ -7,6 +7,7 @@ use std::{
         atomic::{AtomicBool, AtomicU32, Ordering},
         mpsc::{self, Receiver, Sender},
     },
+    time::SystemTime,
 };
 
 use duat::{pre_setup, prelude::*, run_duat};
@@ -26,6 +27,11 @@ static PROFILE: &str = if cfg!(debug_assertions) {
 fn main() {
     let statics = Statics::default();
 
+    if std::env::args().any(|arg| arg == "--quick") {
+        run_quick(statics);
+        return;
+    }
+
     // Assert that the configuration crate actually exists.
     // The watcher is returned as to not be dropped.
     if let Some((_watcher, toml_path, so_path)) = dirs_next::config_dir().and_then(|config_dir| {
@@ -109,6 +115,59 @@ fn main() {
     }
 }
 
+/// Runs Duat without compiling or watching the config crate
+///
+/// The prebuilt config is only loaded if it is newer than its
+/// sources, otherwise, the default config is used. This is meant for
+/// quick edits, like when Duat is the `$EDITOR` of `git commit`.
+fn run_quick(statics: Statics) {
+    let lib = dirs_next::config_dir().and_then(|config_dir| {
+        let crate_dir = config_dir.join("duat");
+
+        let so = crate_dir.join(format!("target/{PROFILE}/libconfig.so"));
+        let so_time = newest_mtime(&so)?;
+        let src_time =
+            newest_mtime(&crate_dir.join("src"))?.max(newest_mtime(&crate_dir.join("Cargo.toml"))?);
+
+        if so_time >= src_time {
+            unsafe { Library::new(&so).ok() }
+        } else {
+            None
+        }
+    });
+    let run = lib.as_ref().and_then(find_run_fn);
+
+    let mut prev_files = Vec::new();
+    loop {
+        let (tx, rx) = mpsc::channel();
+
+        prev_files = if let Some(run) = &run {
+            run(prev_files, tx, rx, statics)
+        } else {
+            pre_setup();
+            run_duat(prev_files, tx, rx, statics)
+        };
+
+        if prev_files.is_empty() {
+            break;
+        }
+    }
+}
+
+/// The latest modification time of a file, or of any file in a
+/// directory
+fn newest_mtime(path: &Path) -> Option<SystemTime> {
+    let metadata = path.metadata().ok()?;
+    if metadata.is_dir() {
+        std::fs::read_dir(path)
+            .ok()?
+            .filter_map(|entry| newest_mtime(&entry.ok()?.path()))
+            .max()
+    } else {
+        metadata.modified().ok()
+    }
+}
+
 fn run_cargo(toml_path: &Path) -> Result<std::process::Output, std::io::Error> {
     let mut cargo = Command::new("cargo");
     cargo.args([
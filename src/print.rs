@@ -105,6 +105,23 @@ pub fn scrolloff(x: u8, y: u8) {
     })
 }
 
+/// Leaves empty cells around the [`File`]s
+///
+/// The width of the padding is taken out of the area of the
+/// [`File`], so wrapping will happen earlier.
+///
+/// [`File`]: crate::widgets::File
+#[inline(never)]
+pub fn padding(left: u8, right: u8, top: u8, bottom: u8) {
+    let mut print_cfg = PRINT_CFG.write().unwrap();
+    let prev = print_cfg.take();
+
+    *print_cfg = Some(match prev {
+        Some(prev) => prev.with_padding(left, right, top, bottom),
+        None => PrintCfg::default_for_input().with_padding(left, right, top, bottom),
+    })
+}
+
 pub macro word_chars($($w_chars:tt)+) {
     word_chars(w_chars!($($w_chars:tt)+))
 }
@@ -0,0 +1,403 @@
+//! Project local configuration
+//!
+//! When Duat starts, it looks for a `.duat.toml` file in the current
+//! directory or any of its parents. The directory containing it is
+//! considered the root of the project, and the file can override
+//! some of the user's configuration for [`File`]s under that root:
+//!
+//! ```toml
+//! commands = ["alias wq write", "my-build-command"]
+//!
+//! [print]
+//! wrap = "words"
+//! tab_size = 2
+//! scrolloff = [3, 5]
+//!
+//! [forms]
+//! Comment = { fg = "#8a8a8a", italic = true }
+//! Mode = "Accent"
+//!
+//! [plugins]
+//! AutoSaver = false
+//! ```
+//!
+//! Since a project configuration can come from anywhere, only a
+//! whitelisted set of options, the `[print]` and `[forms]` tables,
+//! are applied by default. Anything else, like the `commands` that
+//! are ran on startup, which could execute arbitrary processes, or
+//! the toggling of `[plugins]`, is only applied after the project
+//! is trusted through the `trust-project` command.
+//!
+//! Trusted projects are remembered in
+//! `$config/duat/trusted_projects.toml`, alongside a copy of their
+//! privileged options. If those options change in any way, the
+//! project will need to be trusted again.
+//!
+//! [`File`]: duat_core::widgets::File
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use duat_core::{
+    cfg::PrintCfg,
+    context,
+    form::{self, BuiltForm, Color, Form},
+    hooks::{self, SessionStarted},
+    session::SessionCfg,
+    text::{err, hint, ok},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::Ui;
+
+/// The name of the project configuration file
+const FILE_NAME: &str = ".duat.toml";
+
+static PROJECT: RwLock<Option<Project>> = RwLock::new(None);
+
+/// Looks for a project configuration, applying its options
+///
+/// If the project has privileged options and is not trusted, the
+/// user is asked to trust it through the `trust-project` command.
+pub(crate) fn setup(cfg: &mut SessionCfg<Ui>) {
+    cfg.add_print_cfg_override(|path, print_cfg| match PROJECT.read().unwrap().as_ref() {
+        Some(project) if Path::new(path).starts_with(&project.root) => {
+            project.cfg.print.apply(print_cfg)
+        }
+        _ => print_cfg,
+    });
+
+    let _ = crate::cmd::add(["trust-project"], |_, _| {
+        let mut project = PROJECT.write().unwrap();
+        let project = project
+            .as_mut()
+            .ok_or_else(|| err!("There is no " [*a] FILE_NAME [] " to trust."))?;
+
+        if project.trusted {
+            return ok!("Project at " [*a] { project.root.display() } [] " was already trusted.");
+        }
+
+        trust(&project.root, project.cfg.privileged())
+            .map_err(|e| err!("Failed to trust project: " [*a] e))?;
+        project.trusted = true;
+        project.cfg.run_commands();
+
+        ok!("Trusted " [*a] { project.root.display() } [] ", plugins will change on reload.")
+    });
+
+    // Since setup happens on every reload, the commands of the
+    // previous project shouldn't be ran again.
+    hooks::remove("ProjectCommands");
+
+    let Some(root) = find_root() else {
+        return;
+    };
+
+    let contents = match std::fs::read_to_string(root.join(FILE_NAME)) {
+        Ok(contents) => contents,
+        Err(e) => {
+            context::notify(err!("Failed to read " [*a] FILE_NAME [] ": " e));
+            return;
+        }
+    };
+
+    let cfg: ProjectCfg = match toml::from_str(&contents) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            context::notify(err!("Failed to parse " [*a] FILE_NAME [] ": " { e.message() }));
+            return;
+        }
+    };
+
+    cfg.set_forms();
+
+    let trusted = !cfg.is_privileged() || is_trusted(&root, &cfg.privileged());
+    if trusted {
+        hooks::add_grouped::<SessionStarted<Ui>>("ProjectCommands", |_| {
+            if let Some(project) = PROJECT.read().unwrap().as_ref() {
+                project.cfg.run_commands();
+            }
+        });
+    } else {
+        let path = root.join(FILE_NAME);
+        context::notify(hint!(
+            "Found untrusted " [*a] { path.display() } []
+            ", use " [*a] "trust-project" [] " to run its commands and toggle its plugins."
+        ));
+    }
+
+    *PROJECT.write().unwrap() = Some(Project { root, cfg, trusted });
+}
+
+/// Whether the plugin was disabled by the project configuration
+pub(crate) fn plugin_is_disabled(name: &str) -> bool {
+    PROJECT
+        .read()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|project| project.trusted && project.cfg.plugins.get(name) == Some(&false))
+}
+
+/// A project found by Duat
+struct Project {
+    root: PathBuf,
+    cfg: ProjectCfg,
+    trusted: bool,
+}
+
+/// The contents of a `.duat.toml` file
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProjectCfg {
+    commands: Vec<String>,
+    print: PrintOverlay,
+    forms: HashMap<String, FormOverlay>,
+    plugins: BTreeMap<String, bool>,
+}
+
+impl ProjectCfg {
+    /// Whether there are options that require trusting the project
+    fn is_privileged(&self) -> bool {
+        !self.commands.is_empty() || !self.plugins.is_empty()
+    }
+
+    /// The options that require trust, as they should be stored
+    fn privileged(&self) -> Privileged {
+        Privileged {
+            commands: self.commands.clone(),
+            plugins: self.plugins.clone(),
+        }
+    }
+
+    /// Runs the commands of the project
+    fn run_commands(&self) {
+        for command in self.commands.iter() {
+            if let Err(err) = crate::cmd::run(command) {
+                context::notify(err.into());
+            }
+        }
+    }
+
+    /// Sets all the [`Form`]s of the project
+    fn set_forms(&self) {
+        for (name, form) in self.forms.iter() {
+            match form {
+                FormOverlay::Ref(refed) => {
+                    form::set(name, refed.as_str());
+                }
+                FormOverlay::Style(style) => match style.to_form() {
+                    Ok(form) => {
+                        form::set(name, form);
+                    }
+                    Err(color) => context::notify(
+                        err!("Invalid color " [*a] color [] " for form " [*a] name [] "."),
+                    ),
+                },
+            }
+        }
+    }
+}
+
+/// The options of a project that require trust
+#[derive(Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Privileged {
+    commands: Vec<String>,
+    plugins: BTreeMap<String, bool>,
+}
+
+/// A project in the list of trusted projects
+#[derive(Serialize, Deserialize)]
+struct TrustedProject {
+    root: PathBuf,
+    #[serde(flatten)]
+    privileged: Privileged,
+}
+
+/// The list of trusted projects
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct TrustedList {
+    projects: Vec<TrustedProject>,
+}
+
+/// Overrides over the [`PrintCfg`] of the user
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PrintOverlay {
+    wrap: Option<Wrap>,
+    wrap_cap: Option<u8>,
+    indent_wrap: Option<bool>,
+    tab_size: Option<u8>,
+    new_line: Option<char>,
+    scrolloff: Option<(u8, u8)>,
+}
+
+impl PrintOverlay {
+    /// Applies the overrides on top of a [`PrintCfg`]
+    fn apply(&self, mut cfg: PrintCfg) -> PrintCfg {
+        cfg = match self.wrap {
+            Some(Wrap::None) => cfg.with_no_wrapping(),
+            Some(Wrap::Width) => cfg.width_wrapped(),
+            Some(Wrap::Words) => cfg.word_wrapped(),
+            None => cfg,
+        };
+        if let Some(cap) = self.wrap_cap {
+            cfg = cfg.wrapped_on_cap(cap);
+        }
+        if let Some(indent_wrap) = self.indent_wrap {
+            cfg.indent_wrap = indent_wrap;
+        }
+        if let Some(tab_size) = self.tab_size {
+            cfg = cfg.with_tabs_size(tab_size);
+        }
+        if let Some(char) = self.new_line {
+            cfg = cfg.with_new_line_as(char);
+        }
+        if let Some((x, y)) = self.scrolloff {
+            cfg = cfg.with_scrolloff(x, y);
+        }
+        cfg
+    }
+}
+
+/// How to wrap [`File`]s in the project
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Wrap {
+    None,
+    Width,
+    Words,
+}
+
+/// A [`Form`] in the project, either referencing another or not
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FormOverlay {
+    Ref(String),
+    Style(Style),
+}
+
+/// The style of a [`Form`] in the project
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Style {
+    fg: Option<String>,
+    bg: Option<String>,
+    bold: bool,
+    italic: bool,
+    underlined: bool,
+    reverse: bool,
+    dim: bool,
+    crossed_out: bool,
+}
+
+impl Style {
+    /// Turns this [`Style`] into a [`Form`]
+    ///
+    /// Returns the offending color if one of them is not valid.
+    fn to_form(&self) -> Result<BuiltForm, String> {
+        let mut form = Form::new();
+        if let Some(fg) = &self.fg {
+            form = form.with(parse_color(fg).ok_or_else(|| fg.clone())?);
+        }
+        if let Some(bg) = &self.bg {
+            form = form.on(parse_color(bg).ok_or_else(|| bg.clone())?);
+        }
+
+        if self.bold {
+            form = form.bold();
+        }
+        if self.italic {
+            form = form.italic();
+        }
+        if self.underlined {
+            form = form.underlined();
+        }
+        if self.reverse {
+            form = form.reverse();
+        }
+        if self.dim {
+            form = form.dim();
+        }
+        if self.crossed_out {
+            form = form.crossed_out();
+        }
+
+        Ok(form)
+    }
+}
+
+/// Parses a color, either as `"#rrggbb"` or by its name
+fn parse_color(color: &str) -> Option<Color> {
+    if let Some(hex) = color.strip_prefix('#')
+        && hex.len() == 6
+    {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        Some(Color::Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    } else {
+        Color::try_from(color).ok()
+    }
+}
+
+/// Finds the closest directory with a `.duat.toml` file
+fn find_root() -> Option<PathBuf> {
+    let cur_dir = std::env::current_dir().ok()?;
+    cur_dir
+        .ancestors()
+        .find(|dir| dir.join(FILE_NAME).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// The file where trusted project roots are stored
+fn trusted_list() -> Option<PathBuf> {
+    dirs_next::config_dir().map(|dir| dir.join("duat/trusted_projects.toml"))
+}
+
+/// Reads the list of trusted projects
+///
+/// If the list doesn't exist or is malformed, no project is
+/// considered trusted.
+fn read_trusted_list() -> TrustedList {
+    trusted_list()
+        .and_then(|list| std::fs::read_to_string(list).ok())
+        .and_then(|list| toml::from_str(&list).ok())
+        .unwrap_or_default()
+}
+
+/// Whether the given project root was trusted before
+///
+/// The privileged options must also be exactly the same as when the
+/// project was trusted, so any change to them requires trusting it
+/// again.
+fn is_trusted(root: &Path, privileged: &Privileged) -> bool {
+    read_trusted_list()
+        .projects
+        .iter()
+        .any(|project| project.root == root && project.privileged == *privileged)
+}
+
+/// Adds the project root to the list of trusted projects
+///
+/// This replaces any previous entry for the same root.
+fn trust(root: &Path, privileged: Privileged) -> std::io::Result<()> {
+    let list = trusted_list().ok_or(std::io::ErrorKind::NotFound)?;
+    if let Some(parent) = list.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut trusted = read_trusted_list();
+    trusted.projects.retain(|project| project.root != root);
+    trusted
+        .projects
+        .push(TrustedProject { root: root.to_path_buf(), privileged });
+
+    let contents = toml::to_string(&trusted).map_err(std::io::Error::other)?;
+    std::fs::write(list, contents)
+}
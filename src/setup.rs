@@ -26,6 +26,7 @@ use crate::{
     hooks::{self, OnFileOpen, OnWindowOpen, UnfocusedFrom},
     mode,
     prelude::{CmdLine, LineNumbers, StatusLine},
+    toggle,
 };
 
 static CUR_FILE: CurFile<Ui> = CurFile::new();
@@ -63,6 +64,10 @@ pub fn pre_setup() {
     hooks::add_grouped::<UnfocusedFrom<CmdLine>>("CmdLineNotifications", |_cmd_line| {
         mode::set_cmd(ShowNotifications::new());
     });
+
+    toggle::register::<LineNumbers>("line-numbers");
+    toggle::register::<VertRule>("vert-rule");
+    toggle::register::<StatusLine>("status-line");
 }
 
 #[doc(hidden)]
@@ -80,6 +85,12 @@ pub fn run_duat(
         cfg_fn(&mut cfg)
     }
 
+    crate::project::setup(&mut cfg);
+    crate::health::setup();
+
+    let plugin_fn = std::mem::replace(&mut *PLUGIN_FN.write().unwrap(), Box::new(|_| {}));
+    plugin_fn(&mut cfg);
+
     let print_cfg = match PRINT_CFG.write().unwrap().take() {
         Some(cfg) => cfg,
         None => PrintCfg::default_for_input(),